@@ -0,0 +1,75 @@
+use crate::{ObsidianNote, Properties};
+
+impl ObsidianNote {
+    /// The other names the note goes by, from its `aliases` property
+    pub fn aliases(&self) -> Vec<String> {
+        self.properties
+            .as_ref()
+            .map(frontmatter_aliases)
+            .unwrap_or_default()
+    }
+}
+
+/// Names listed under the `aliases` (or legacy `alias`) frontmatter property,
+/// given either as a YAML list or a comma separated string
+pub fn frontmatter_aliases(properties: &Properties) -> Vec<String> {
+    let value = properties
+        .get("aliases")
+        .or_else(|| properties.get("alias"));
+
+    let raw: Vec<&str> = match value {
+        Some(serde_yaml::Value::Sequence(seq)) => {
+            seq.iter().filter_map(serde_yaml::Value::as_str).collect()
+        }
+        Some(serde_yaml::Value::String(aliases)) => aliases.split(',').collect(),
+        _ => Vec::new(),
+    };
+
+    let mut aliases: Vec<String> = Vec::new();
+    for alias in raw {
+        let alias = alias.trim().to_string();
+        if !alias.is_empty() && !aliases.contains(&alias) {
+            aliases.push(alias);
+        }
+    }
+
+    aliases
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use std::path::Path;
+
+    #[test]
+    fn reads_aliases_from_a_list() {
+        let note = ObsidianNote::parse(
+            Path::new("note.md"),
+            indoc! {"
+                ---
+                aliases:
+                  - First Name
+                  - Second
+                  - First Name
+                ---
+                Body
+            "}
+            .to_string(),
+        )
+        .unwrap();
+
+        assert_eq!(note.aliases(), ["First Name", "Second"]);
+    }
+
+    #[test]
+    fn reads_aliases_from_a_string() {
+        let note = ObsidianNote::parse(
+            Path::new("note.md"),
+            "---\nalias: One, Two\n---\n".to_string(),
+        )
+        .unwrap();
+
+        assert_eq!(note.aliases(), ["One", "Two"]);
+    }
+}
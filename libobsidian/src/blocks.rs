@@ -0,0 +1,182 @@
+use crate::{links::Link, ObsidianNote};
+
+/// A paragraph, list item or other chunk of a note marked with a `^id`, which
+/// `[[note#^id]]` links point into
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block {
+    /// Without the leading `^`
+    pub id: String,
+    /// 1-based line number of the `^id` within the parsed text
+    pub line: usize,
+    /// The block's lines, without the `^id`
+    pub text: String,
+}
+
+impl Link {
+    /// The id of the block the link points into, without the leading `^`
+    pub fn block_id(&self) -> Option<&str> {
+        self.subpath.as_deref()?.strip_prefix('^')
+    }
+}
+
+impl ObsidianNote {
+    /// Every block in the note body with a `^id`, in order
+    pub fn blocks(&self) -> Vec<Block> {
+        parse_blocks(&self.file_body)
+    }
+}
+
+/// The block `id` marks in `content`, ignoring case like Obsidian does. A
+/// leading `^` is optional.
+pub fn find_block(content: &str, id: &str) -> Option<Block> {
+    let id = id.strip_prefix('^').unwrap_or(id);
+    parse_blocks(content)
+        .into_iter()
+        .find(|block| block.id.eq_ignore_ascii_case(id))
+}
+
+/// Find the blocks marked with a `^id` in `content`. An id at the end of a
+/// line marks its list item, or else its paragraph. An id on a line of its
+/// own marks the block above it, which is how lists, quotes and tables are
+/// given one.
+pub fn parse_blocks(content: &str) -> Vec<Block> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut blocks = Vec::new();
+    let mut in_code_block = false;
+    // Where the paragraph and list item being read started
+    let mut block_start: Option<usize> = None;
+    let mut item_start: Option<usize> = None;
+    // The lines of the last block to end, for an id on its own line
+    let mut previous: Option<(usize, usize)> = None;
+
+    for (idx, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            in_code_block = !in_code_block;
+            block_start.get_or_insert(idx);
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+
+        if trimmed.is_empty() {
+            if let Some(start) = block_start.take() {
+                previous = Some((start, idx));
+            }
+            item_start = None;
+            continue;
+        }
+
+        // Headings are blocks of their own, linked to by their text instead
+        if is_heading(trimmed) {
+            if let Some(start) = block_start.take() {
+                previous = Some((start, idx));
+            }
+            item_start = None;
+            continue;
+        }
+
+        if block_start.is_none() {
+            if let Some(id) = trimmed.strip_prefix('^').filter(|id| is_id(id)) {
+                if let Some((start, end)) = previous {
+                    blocks.push(Block {
+                        id: id.to_string(),
+                        line: idx + 1,
+                        text: lines[start..end].join("\n"),
+                    });
+                }
+                continue;
+            }
+        }
+
+        block_start.get_or_insert(idx);
+        if is_list_item(trimmed) {
+            item_start = Some(idx);
+        }
+
+        let Some((text, id)) = line.trim_end().rsplit_once(" ^") else {
+            continue;
+        };
+        if !is_id(id) {
+            continue;
+        }
+        let start = item_start.or(block_start).unwrap_or(idx);
+        let mut text_lines = lines[start..idx].to_vec();
+        text_lines.push(text.trim_end());
+        blocks.push(Block {
+            id: id.to_string(),
+            line: idx + 1,
+            text: text_lines.join("\n"),
+        });
+    }
+
+    blocks
+}
+
+/// Block ids are letters, numbers and dashes
+fn is_id(text: &str) -> bool {
+    !text.is_empty() && text.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+fn is_heading(trimmed: &str) -> bool {
+    let level = trimmed.chars().take_while(|c| *c == '#').count();
+    (1..=6).contains(&level) && trimmed[level..].starts_with([' ', '\t'])
+}
+
+fn is_list_item(trimmed: &str) -> bool {
+    if trimmed.starts_with(['-', '*', '+']) {
+        return trimmed[1..].starts_with(' ');
+    }
+    let digits = trimmed.chars().take_while(char::is_ascii_digit).count();
+    digits > 0 && (trimmed[digits..].starts_with(". ") || trimmed[digits..].starts_with(") "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn parses_paragraphs_items_and_standalone_ids() {
+        let content = indoc! {"
+            # Heading
+            A paragraph
+            over two lines ^para
+
+            - first item
+            - second item ^item
+              continued
+
+            > A quote
+
+            ^quote
+            Not a ^block id because of the space
+        "};
+
+        let parsed: Vec<(String, usize, String)> = parse_blocks(content)
+            .into_iter()
+            .map(|block| (block.id, block.line, block.text))
+            .collect();
+
+        assert_eq!(
+            parsed,
+            vec![
+                ("para".into(), 3, "A paragraph\nover two lines".into()),
+                ("item".into(), 6, "- second item".into()),
+                ("quote".into(), 11, "> A quote".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_blocks_ignoring_case() {
+        let content = "Some text ^Abc-1";
+
+        assert_eq!(
+            find_block(content, "^abc-1").map(|block| block.text),
+            Some("Some text".to_string())
+        );
+        assert_eq!(find_block(content, "abc"), None);
+    }
+}
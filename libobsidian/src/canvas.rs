@@ -0,0 +1,239 @@
+use crate::links::{parse_links, Link, LinkKind};
+use serde::Deserialize;
+use std::path::Path;
+
+/// An Obsidian canvas: cards laid out on a board, joined by arrows, kept as
+/// JSON in a `.canvas` file
+#[derive(Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct Canvas {
+    #[serde(default)]
+    pub nodes: Vec<CanvasNode>,
+    #[serde(default)]
+    pub edges: Vec<CanvasEdge>,
+}
+
+/// A card on the canvas
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct CanvasNode {
+    pub id: String,
+    /// `text`, `file`, `link` or `group`
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// The markdown of a text card
+    pub text: Option<String>,
+    /// The vault-relative path of a file card
+    pub file: Option<String>,
+    /// The `#heading` or `#^block` of a note a file card shows
+    pub subpath: Option<String>,
+    /// The address of a link card
+    pub url: Option<String>,
+    /// The name of a group
+    pub label: Option<String>,
+}
+
+/// An arrow between two cards
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct CanvasEdge {
+    #[serde(rename = "fromNode")]
+    pub from_node: String,
+    #[serde(rename = "toNode")]
+    pub to_node: String,
+    pub label: Option<String>,
+}
+
+/// Whether a path is an Obsidian canvas
+pub fn is_canvas_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("canvas"))
+}
+
+impl Canvas {
+    pub fn parse(json: &str) -> anyhow::Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// The files the canvas refers to: each file card, as an embed of its
+    /// vault-relative path, and the links in text cards. Lines are the
+    /// card's line in `json`, which Obsidian writes a card per line.
+    pub fn links(&self, json: &str) -> Vec<(Link, String)> {
+        let mut links = Vec::new();
+        for node in &self.nodes {
+            let line = line_of(json, &node.id);
+            if let Some(file) = &node.file {
+                let link = Link {
+                    kind: LinkKind::Wikilink,
+                    embed: true,
+                    target: file.clone(),
+                    subpath: node
+                        .subpath
+                        .as_deref()
+                        .map(|subpath| subpath.trim_start_matches('#').to_string()),
+                    label: None,
+                    range: 0..0,
+                    line,
+                };
+                links.push((link, file.clone()));
+            }
+            if let Some(text) = &node.text {
+                for mut link in parse_links(text) {
+                    let written = text[link.range.clone()].to_string();
+                    link.line = line;
+                    links.push((link, written));
+                }
+            }
+        }
+        links
+    }
+
+    /// A plain-text outline of the cards and the arrows between them
+    pub fn summary(&self) -> String {
+        let noun = |count: usize, one: &str, many: &str| {
+            format!("{count} {}", if count == 1 { one } else { many })
+        };
+        let mut lines = vec![format!(
+            "Canvas: {}, {}",
+            noun(self.nodes.len(), "card", "cards"),
+            noun(self.edges.len(), "arrow", "arrows")
+        )];
+
+        if !self.nodes.is_empty() {
+            lines.push(String::new());
+            lines.push("Cards".to_string());
+            lines.extend(self.nodes.iter().map(|node| format!("- {}", node.title())));
+        }
+        if !self.edges.is_empty() {
+            lines.push(String::new());
+            lines.push("Arrows".to_string());
+            for edge in &self.edges {
+                let title = |id: &str| {
+                    self.nodes
+                        .iter()
+                        .find(|node| node.id == id)
+                        .map_or_else(|| id.to_string(), CanvasNode::name)
+                };
+                let label = edge
+                    .label
+                    .as_ref()
+                    .map(|label| format!(" ({label})"))
+                    .unwrap_or_default();
+                lines.push(format!(
+                    "- {} → {}{label}",
+                    title(&edge.from_node),
+                    title(&edge.to_node)
+                ));
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+impl CanvasNode {
+    /// The card's kind and what it shows, like `Note: Projects/Plan.md`
+    pub fn title(&self) -> String {
+        let kind = match self.kind.as_str() {
+            "text" => "Text",
+            "file"
+                if self
+                    .file
+                    .as_deref()
+                    .is_some_and(|file| file.ends_with(".md")) =>
+            {
+                "Note"
+            }
+            "file" => "File",
+            "link" => "Link",
+            "group" => "Group",
+            other => other,
+        };
+        format!("{kind}: {}", self.name())
+    }
+
+    /// What the card shows, in a line
+    pub fn name(&self) -> String {
+        let name = self
+            .file
+            .as_deref()
+            .or(self.url.as_deref())
+            .or(self.label.as_deref())
+            .or_else(|| {
+                self.text
+                    .as_deref()
+                    .and_then(|text| text.lines().find(|line| !line.trim().is_empty()))
+            })
+            .unwrap_or_default()
+            .trim();
+        let subpath = self
+            .subpath
+            .as_deref()
+            .map(|subpath| format!("#{}", subpath.trim_start_matches('#')))
+            .unwrap_or_default();
+        format!("{name}{subpath}")
+    }
+}
+
+/// The 1-based line `"id"` is first written on
+fn line_of(json: &str, id: &str) -> usize {
+    let quoted = format!("\"{id}\"");
+    json.find(&quoted)
+        .map_or(1, |offset| json[..offset].matches('\n').count() + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    const CANVAS: &str = indoc! {r##"
+        {
+        	"nodes":[
+        		{"id":"a","type":"text","text":"Ideas\nSee [[Plan]]","x":0,"y":0,"width":250,"height":60},
+        		{"id":"b","type":"file","file":"Projects/Plan.md","subpath":"#Goals","x":300,"y":0,"width":400,"height":400},
+        		{"id":"c","type":"link","url":"https://obsidian.md","x":0,"y":100,"width":250,"height":60}
+        	],
+        	"edges":[
+        		{"id":"e","fromNode":"a","fromSide":"right","toNode":"b","toSide":"left","label":"leads to"}
+        	]
+        }
+    "##};
+
+    #[test]
+    fn finds_file_cards_and_links_in_text() {
+        let canvas = Canvas::parse(CANVAS).unwrap();
+        let links: Vec<(String, Option<String>, usize, String)> = canvas
+            .links(CANVAS)
+            .into_iter()
+            .map(|(link, text)| (link.target, link.subpath, link.line, text))
+            .collect();
+
+        assert_eq!(
+            links,
+            vec![
+                ("Plan".into(), None, 3, "[[Plan]]".into()),
+                (
+                    "Projects/Plan.md".into(),
+                    Some("Goals".into()),
+                    4,
+                    "Projects/Plan.md".into()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn summarizes_cards_and_arrows() {
+        assert_eq!(
+            Canvas::parse(CANVAS).unwrap().summary(),
+            indoc! {"
+                Canvas: 3 cards, 1 arrow
+
+                Cards
+                - Text: Ideas
+                - Note: Projects/Plan.md#Goals
+                - Link: https://obsidian.md
+
+                Arrows
+                - Ideas → Projects/Plan.md#Goals (leads to)"}
+        );
+    }
+}
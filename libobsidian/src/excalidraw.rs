@@ -0,0 +1,207 @@
+use crate::ObsidianNote;
+use std::path::Path;
+
+/// The heading the Excalidraw plugin writes above the serialized drawing
+const DRAWING_HEADING: &str = "# Drawing";
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum DrawingPayload {
+    /// Plain JSON scene data, as written when compression is disabled
+    Json(String),
+    /// LZ-string compressed scene data, the plugin's default
+    Compressed,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Drawing {
+    pub payload: DrawingPayload,
+    /// Number of elements in the scene, when it could be parsed
+    pub element_count: Option<usize>,
+}
+
+impl Drawing {
+    /// A short stand-in for the drawing, used in place of the payload
+    pub fn placeholder(&self) -> String {
+        match self.element_count {
+            Some(1) => "[Excalidraw drawing: 1 element]".to_string(),
+            Some(count) => format!("[Excalidraw drawing: {count} elements]"),
+            None => "[Excalidraw drawing]".to_string(),
+        }
+    }
+}
+
+/// Whether a path looks like an Excalidraw drawing saved as markdown
+pub fn is_excalidraw_path(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .map(|name| name.to_lowercase().ends_with(".excalidraw.md"))
+        .unwrap_or(false)
+}
+
+/// The part of a file worth searching or previewing: for Excalidraw drawings
+/// the scene payload is dropped, everything else is returned unchanged
+pub fn readable_content<'a>(path: &Path, content: &'a str) -> &'a str {
+    if is_excalidraw_path(path) || has_excalidraw_frontmatter(content) {
+        split_drawing(content).0
+    } else {
+        content
+    }
+}
+
+fn has_excalidraw_frontmatter(content: &str) -> bool {
+    let mut lines = content.lines();
+    if lines.next().map(str::trim) != Some("---") {
+        return false;
+    }
+    lines
+        .take_while(|line| line.trim() != "---")
+        .any(|line| line.starts_with("excalidraw-plugin:"))
+}
+
+impl ObsidianNote {
+    /// Whether the note is an Excalidraw drawing, by file name or by the
+    /// `excalidraw-plugin` frontmatter key the plugin adds
+    pub fn is_excalidraw(&self) -> bool {
+        is_excalidraw_path(&self.file_path)
+            || self
+                .properties
+                .as_ref()
+                .map(|properties| properties.get("excalidraw-plugin").is_some())
+                .unwrap_or(false)
+    }
+}
+
+/// Split an Excalidraw note into the readable markdown before the drawing
+/// (text elements, embedded files) and the drawing itself. Everything from
+/// the `# Drawing` section onwards is dropped from the returned text, so
+/// line numbers in the readable part are unchanged.
+pub fn split_drawing(content: &str) -> (&str, Option<Drawing>) {
+    let mut offset = 0;
+    let mut drawing_start = None;
+
+    for line in content.split_inclusive('\n') {
+        if line.trim_end() == DRAWING_HEADING {
+            drawing_start = Some(offset);
+            break;
+        }
+        offset += line.len();
+    }
+
+    let Some(start) = drawing_start else {
+        return (content, None);
+    };
+
+    // The plugin wraps the drawing in a `%%` comment, keep that out too
+    let readable = content[..start]
+        .trim_end()
+        .strip_suffix("%%")
+        .unwrap_or(&content[..start]);
+
+    (readable, Some(parse_drawing(&content[start..])))
+}
+
+fn parse_drawing(section: &str) -> Drawing {
+    let mut lines = section.lines();
+    let fence = lines.find(|line| line.starts_with("```"));
+
+    let body: String = lines
+        .take_while(|line| !line.starts_with("```"))
+        .collect::<Vec<&str>>()
+        .join("\n");
+
+    match fence.map(|fence| fence.trim_start_matches('`').trim()) {
+        Some("json") => {
+            let element_count = serde_json::from_str::<serde_json::Value>(&body)
+                .ok()
+                .and_then(|scene| {
+                    scene
+                        .get("elements")
+                        .and_then(|e| e.as_array())
+                        .map(|elements| {
+                            elements
+                                .iter()
+                                .filter(|element| {
+                                    !element
+                                        .get("isDeleted")
+                                        .and_then(|deleted| deleted.as_bool())
+                                        .unwrap_or(false)
+                                })
+                                .count()
+                        })
+                });
+
+            Drawing {
+                payload: DrawingPayload::Json(body),
+                element_count,
+            }
+        }
+        _ => Drawing {
+            payload: DrawingPayload::Compressed,
+            element_count: None,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    const DRAWING: &str = indoc! {r#"
+        ---
+        excalidraw-plugin: parsed
+        ---
+        # Text Elements
+        A label ^abc123
+
+        %%
+        # Drawing
+        ```json
+        {"type":"excalidraw","elements":[{"id":"a"},{"id":"b"},{"id":"c","isDeleted":true}]}
+        ```
+        %%
+    "#};
+
+    #[test]
+    fn split_drawing_separates_payload() {
+        let (readable, drawing) = split_drawing(DRAWING);
+
+        assert!(readable.ends_with("A label ^abc123\n\n"));
+        assert!(!readable.contains("elements"));
+        assert_eq!(drawing.unwrap().element_count, Some(2));
+    }
+
+    #[test]
+    fn split_drawing_handles_compressed_payload() {
+        let content = "# Text Elements\n%%\n# Drawing\n```compressed-json\nN4Ig\n```\n%%\n";
+        let (readable, drawing) = split_drawing(content);
+
+        assert_eq!(readable, "# Text Elements\n");
+        assert_eq!(
+            drawing,
+            Some(Drawing {
+                payload: DrawingPayload::Compressed,
+                element_count: None
+            })
+        );
+    }
+
+    #[test]
+    fn split_drawing_leaves_regular_notes_alone() {
+        assert_eq!(split_drawing("# A note\nBody"), ("# A note\nBody", None));
+    }
+
+    #[test]
+    fn readable_content_only_strips_drawings() {
+        let note = "# Drawing\nA regular note with a heading";
+
+        assert_eq!(readable_content(Path::new("note.md"), note), note);
+        assert!(!readable_content(Path::new("note.md"), DRAWING).contains("elements"));
+    }
+
+    #[test]
+    fn is_excalidraw_path_checks_double_extension() {
+        assert!(is_excalidraw_path(Path::new("Drawing 2024.excalidraw.md")));
+        assert!(!is_excalidraw_path(Path::new("excalidraw.md")));
+    }
+}
@@ -0,0 +1,146 @@
+use crate::split_frontmatter;
+
+/// Set a top-level frontmatter key to `value`, given as YAML. An existing
+/// value is replaced in place, a new key is added at the end of the
+/// frontmatter, and a frontmatter block is created if the note has none.
+/// Edits are line-based, so comments and the formatting of every other
+/// property are left alone. A `value` starting with a line break, like a
+/// block list, goes on the lines below the key.
+pub fn set_property(content: &str, key: &str, value: &str) -> String {
+    let line = if value.starts_with('\n') {
+        format!("{key}:{value}\n")
+    } else {
+        format!("{key}: {value}\n")
+    };
+
+    let (Some(frontmatter), body) = split_frontmatter(content) else {
+        return format!("---\n{line}---\n{content}");
+    };
+
+    let lines: Vec<&str> = frontmatter.split_inclusive('\n').collect();
+    let updated = match key_span(&lines, key) {
+        Some((start, end)) => {
+            let mut updated: String = lines[..start].concat();
+            updated.push_str(&line);
+            updated.push_str(&lines[end..].concat());
+            updated
+        }
+        None => {
+            let mut updated = frontmatter.to_string();
+            if !updated.is_empty() && !updated.ends_with('\n') {
+                updated.push('\n');
+            }
+            updated.push_str(&line);
+            updated
+        }
+    };
+
+    format!("---\n{updated}---\n{body}")
+}
+
+/// Remove a top-level frontmatter key, returning `None` when the note
+/// doesn't have it
+pub fn remove_property(content: &str, key: &str) -> Option<String> {
+    let (Some(frontmatter), body) = split_frontmatter(content) else {
+        return None;
+    };
+
+    let lines: Vec<&str> = frontmatter.split_inclusive('\n').collect();
+    let (start, end) = key_span(&lines, key)?;
+    let updated = [&lines[..start], &lines[end..]].concat().concat();
+
+    Some(format!("---\n{updated}---\n{body}"))
+}
+
+/// The range of lines holding `key` and its value, including the indented or
+/// list lines of block values
+fn key_span(lines: &[&str], key: &str) -> Option<(usize, usize)> {
+    let prefix = format!("{key}:");
+    let start = lines.iter().position(|line| {
+        line.strip_prefix(&prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with([' ', '\t', '\r', '\n']))
+    })?;
+
+    let end = lines[start + 1..]
+        .iter()
+        .position(|line| {
+            !(line.starts_with([' ', '\t', '-']) || line.trim().is_empty())
+                || line.starts_with("---")
+        })
+        .map(|offset| start + 1 + offset)
+        .unwrap_or(lines.len());
+
+    Some((start, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    const NOTE: &str = indoc! {"
+        ---
+        # a comment
+        status: draft
+        tags:
+          - one
+          - two
+        due: 2024-01-01
+        ---
+        Body
+    "};
+
+    #[test]
+    fn set_property_replaces_value() {
+        assert_eq!(
+            set_property(NOTE, "tags", "[three]"),
+            indoc! {"
+                ---
+                # a comment
+                status: draft
+                tags: [three]
+                due: 2024-01-01
+                ---
+                Body
+            "}
+        );
+    }
+
+    #[test]
+    fn set_property_appends_new_key() {
+        assert!(set_property(NOTE, "owner", "me").contains("due: 2024-01-01\nowner: me\n---\n"));
+    }
+
+    #[test]
+    fn set_property_creates_frontmatter() {
+        assert_eq!(
+            set_property("Body\n", "status", "done"),
+            "---\nstatus: done\n---\nBody\n"
+        );
+    }
+
+    #[test]
+    fn set_property_matches_whole_keys() {
+        let content = "---\nstatus-old: a\n---\n";
+        assert_eq!(
+            set_property(content, "status", "b"),
+            "---\nstatus-old: a\nstatus: b\n---\n"
+        );
+    }
+
+    #[test]
+    fn remove_property_drops_block_values() {
+        assert_eq!(
+            remove_property(NOTE, "tags").unwrap(),
+            indoc! {"
+                ---
+                # a comment
+                status: draft
+                due: 2024-01-01
+                ---
+                Body
+            "}
+        );
+        assert_eq!(remove_property(NOTE, "missing"), None);
+    }
+}
@@ -0,0 +1,109 @@
+use crate::{links::Link, ObsidianNote};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heading {
+    /// 1 for `#`, up to 6 for `######`
+    pub level: usize,
+    pub text: String,
+    /// 1-based line number of the heading within the parsed text
+    pub line: usize,
+}
+
+impl Link {
+    /// The heading the link points into, the last one of a nested subpath
+    /// like `Heading#Subheading`. `None` for links to blocks or whole notes.
+    pub fn heading(&self) -> Option<&str> {
+        let subpath = self.subpath.as_deref()?;
+        if subpath.starts_with('^') {
+            return None;
+        }
+        let heading = subpath.rsplit('#').next()?.trim();
+        (!heading.is_empty()).then_some(heading)
+    }
+}
+
+impl ObsidianNote {
+    /// Every ATX (`#`-prefixed) heading in the note body, in order
+    pub fn headings(&self) -> Vec<Heading> {
+        parse_headings(&self.file_body)
+    }
+}
+
+/// Find the `#`-prefixed headings in `content`, skipping code blocks. Closing
+/// hashes (`## Title ##`) are dropped from the text.
+pub fn parse_headings(content: &str) -> Vec<Heading> {
+    let mut headings = Vec::new();
+    let mut in_code_block = false;
+
+    for (idx, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block || line.len() - trimmed.len() > 3 {
+            continue;
+        }
+
+        let level = trimmed.chars().take_while(|c| *c == '#').count();
+        let rest = &trimmed[level..];
+        if !(1..=6).contains(&level) || !(rest.is_empty() || rest.starts_with([' ', '\t'])) {
+            continue;
+        }
+
+        let text = rest.trim().trim_end_matches('#').trim_end();
+        headings.push(Heading {
+            level,
+            text: text.to_string(),
+            line: idx + 1,
+        });
+    }
+
+    headings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::links::parse_links;
+    use indoc::indoc;
+
+    #[test]
+    fn parses_atx_headings() {
+        let content = indoc! {"
+            # Title
+            Some text with a #tag
+            ## Section ##
+            ```
+            # not a heading
+            ```
+            ####### too deep
+            ### Last
+        "};
+
+        let parsed: Vec<(usize, String, usize)> = parse_headings(content)
+            .into_iter()
+            .map(|heading| (heading.level, heading.text, heading.line))
+            .collect();
+
+        assert_eq!(
+            parsed,
+            vec![
+                (1, "Title".into(), 1),
+                (2, "Section".into(), 3),
+                (3, "Last".into(), 8),
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_the_heading_a_link_points_into() {
+        let links = parse_links("[[a#Intro]] [[a#Intro#Details]] [[a#^block]] [[a]] [[#Top]]");
+        let headings: Vec<Option<&str>> = links.iter().map(Link::heading).collect();
+
+        assert_eq!(
+            headings,
+            [Some("Intro"), Some("Details"), None, None, Some("Top")]
+        );
+    }
+}
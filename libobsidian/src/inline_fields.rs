@@ -0,0 +1,238 @@
+use crate::{ObsidianNote, Properties};
+
+/// A Dataview inline field, `key:: value`, written on a line of its own or in
+/// brackets within a line like `[key:: value]` or `(key:: value)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct InlineField {
+    pub key: String,
+    /// The value as written, trimmed
+    pub value: String,
+    /// 1-based line number within the parsed text
+    pub line: usize,
+}
+
+impl InlineField {
+    /// The value parsed like YAML, so numbers, booleans and dates compare as
+    /// they would in frontmatter. Links and anything that isn't a plain value
+    /// stay text.
+    pub fn parsed_value(&self) -> Properties {
+        if self.value.is_empty() {
+            return Properties::Null;
+        }
+        match serde_yaml::from_str::<Properties>(&self.value) {
+            Ok(value @ (Properties::Bool(_) | Properties::Number(_))) => value,
+            _ => Properties::String(self.value.clone()),
+        }
+    }
+}
+
+impl ObsidianNote {
+    /// Every inline field in the note body, in order
+    pub fn inline_fields(&self) -> Vec<InlineField> {
+        parse_inline_fields(&self.file_body)
+    }
+
+    /// A frontmatter property, or else the inline fields with that key. Keys
+    /// of inline fields match ignoring case, and with spaces written as
+    /// dashes like Dataview allows, so `due-date` finds `Due Date::`. A key
+    /// given more than once has its values as a list.
+    pub fn field(&self, key: &str) -> Option<Properties> {
+        if let Some(value) = self
+            .properties
+            .as_ref()
+            .and_then(|properties| properties.get(key))
+        {
+            return Some(value.clone());
+        }
+
+        let wanted = sanitize(key);
+        let mut values: Vec<Properties> = self
+            .inline_fields()
+            .into_iter()
+            .filter(|field| sanitize(&field.key) == wanted)
+            .map(|field| field.parsed_value())
+            .collect();
+        match values.len() {
+            0 => None,
+            1 => values.pop(),
+            _ => Some(Properties::Sequence(values)),
+        }
+    }
+}
+
+/// Find the inline fields in a note body, skipping code blocks and inline
+/// code
+pub fn parse_inline_fields(body: &str) -> Vec<InlineField> {
+    let mut fields = Vec::new();
+    let mut in_code_block = false;
+
+    for (idx, line) in body.lines().enumerate() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+
+        let bracketed = bracketed_fields(line, idx + 1);
+        if !bracketed.is_empty() {
+            fields.extend(bracketed);
+            continue;
+        }
+        if let Some(field) = line_field(line, idx + 1) {
+            fields.push(field);
+        }
+    }
+
+    fields
+}
+
+/// A `key:: value` taking up the whole line, after any list marker or quote
+fn line_field(line: &str, line_number: usize) -> Option<InlineField> {
+    let content = line
+        .trim_start()
+        .trim_start_matches('>')
+        .trim_start()
+        .trim_start_matches(['-', '*', '+'])
+        .trim_start();
+    let (key, value) = content.split_once("::")?;
+    let key = key.trim().trim_matches('*').trim();
+    if !is_key(key) {
+        return None;
+    }
+
+    Some(InlineField {
+        key: key.to_string(),
+        value: value.trim().to_string(),
+        line: line_number,
+    })
+}
+
+/// The `[key:: value]` and `(key:: value)` fields within a line
+fn bracketed_fields(line: &str, line_number: usize) -> Vec<InlineField> {
+    let mut fields = Vec::new();
+    let mut in_inline_code = false;
+    let mut chars = line.char_indices();
+
+    while let Some((idx, c)) = chars.next() {
+        if c == '`' {
+            in_inline_code = !in_inline_code;
+        }
+        if in_inline_code || !matches!(c, '[' | '(') {
+            continue;
+        }
+        let close = if c == '[' { ']' } else { ')' };
+        let Some(len) = closing(&line[idx..], c, close) else {
+            continue;
+        };
+        let inner = &line[idx + 1..idx + len];
+        let Some((key, value)) = inner.split_once("::") else {
+            continue;
+        };
+        let key = key.trim();
+        if !is_key(key) {
+            continue;
+        }
+
+        fields.push(InlineField {
+            key: key.to_string(),
+            value: value.trim().to_string(),
+            line: line_number,
+        });
+        // Carry on after the field
+        for _ in line[idx + 1..idx + len + 1].chars() {
+            chars.next();
+        }
+    }
+
+    fields
+}
+
+/// The byte offset of the bracket closing the one `text` starts with
+fn closing(text: &str, open: char, close: char) -> Option<usize> {
+    let mut depth = 0;
+    for (idx, c) in text.char_indices() {
+        if c == open {
+            depth += 1;
+        } else if c == close {
+            depth -= 1;
+            if depth == 0 {
+                return Some(idx);
+            }
+        }
+    }
+    None
+}
+
+/// Keys are words, with spaces, dashes, underscores and slashes between them
+fn is_key(key: &str) -> bool {
+    key.starts_with(char::is_alphanumeric)
+        && key
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_' | '/'))
+}
+
+/// A key as Dataview lets queries write it: lowercase, with dashes for spaces
+fn sanitize(key: &str) -> String {
+    key.trim().to_lowercase().replace(' ', "-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use std::path::Path;
+
+    #[test]
+    fn parses_line_and_bracketed_fields() {
+        let body = indoc! {"
+            Status:: active
+            - **Due Date**:: 2024-05-01
+            I rated it [rating:: 4] and (mood:: good), [[not a field]]
+            A url like https://example.com is not one
+            `[code:: skipped]`
+            ```
+            inside:: a code block
+            ```
+        "};
+
+        let fields: Vec<(String, String, usize)> = parse_inline_fields(body)
+            .into_iter()
+            .map(|field| (field.key, field.value, field.line))
+            .collect();
+
+        assert_eq!(
+            fields,
+            vec![
+                ("Status".into(), "active".into(), 1),
+                ("Due Date".into(), "2024-05-01".into(), 2),
+                ("rating".into(), "4".into(), 3),
+                ("mood".into(), "good".into(), 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn looks_up_frontmatter_then_inline_fields() {
+        let content = indoc! {"
+            ---
+            status: draft
+            ---
+            status:: ignored
+            Due Date:: 2024-05-01
+            [rating:: 4] [rating:: 5]
+            link:: [[Other note]]
+        "};
+        let note = ObsidianNote::parse(Path::new("note.md"), content.to_string()).unwrap();
+
+        assert_eq!(note.field("status"), Some("draft".into()));
+        assert_eq!(note.field("due-date"), Some("2024-05-01".into()));
+        assert_eq!(
+            note.field("rating"),
+            Some(Properties::Sequence(vec![4.into(), 5.into()]))
+        );
+        assert_eq!(note.field("link"), Some("[[Other note]]".into()));
+        assert_eq!(note.field("missing"), None);
+    }
+}
@@ -1,3 +1,12 @@
+pub mod aliases;
+pub mod blocks;
+pub mod canvas;
+pub mod excalidraw;
+pub mod frontmatter;
+pub mod headings;
+pub mod inline_fields;
+pub mod links;
 pub mod obsidian_note;
+pub mod tags;
 
 pub use crate::obsidian_note::*;
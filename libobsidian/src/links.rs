@@ -0,0 +1,416 @@
+use crate::ObsidianNote;
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkKind {
+    /// `[[target#subpath|label]]`
+    Wikilink,
+    /// `[label](target#subpath)`
+    Markdown,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    pub kind: LinkKind,
+    /// Whether the link is an embed, written with a leading `!`
+    pub embed: bool,
+    /// The linked file as written, without any `#heading` or `#^block` subpath.
+    /// Empty for links to a heading within the same note. Markdown link
+    /// targets are percent-decoded.
+    pub target: String,
+    /// The heading or `^block` the link points into, without the leading `#`
+    pub subpath: Option<String>,
+    /// Display text, from the part after `|` or between the square brackets
+    pub label: Option<String>,
+    /// Byte range of the whole link (including the `!` of embeds) within the
+    /// parsed text
+    pub range: Range<usize>,
+    /// 1-based line number of the link within the parsed text
+    pub line: usize,
+}
+
+impl Link {
+    /// Whether the link points outside the vault, e.g. `https://` or `mailto:`
+    pub fn is_external(&self) -> bool {
+        self.kind == LinkKind::Markdown
+            && self
+                .target
+                .split_once(':')
+                .map(|(scheme, _)| {
+                    scheme.len() > 1 && scheme.chars().all(|c| c.is_ascii_alphabetic())
+                })
+                .unwrap_or(false)
+    }
+
+    /// The text Obsidian shows for the link: its label, or else the target
+    /// and subpath as `note > heading`
+    pub fn display_text(&self) -> String {
+        match (&self.label, &self.subpath) {
+            (Some(label), _) if !label.is_empty() => label.clone(),
+            (_, Some(subpath)) if self.target.is_empty() => subpath.clone(),
+            (_, Some(subpath)) => format!("{} > {subpath}", self.target),
+            (_, None) => self.target.clone(),
+        }
+    }
+
+    /// The link rewritten to point at `target`, keeping its subpath, label and
+    /// embed marker. Markdown targets are percent-encoded.
+    pub fn with_target(&self, target: &str) -> String {
+        let embed = if self.embed { "!" } else { "" };
+
+        match self.kind {
+            LinkKind::Wikilink => {
+                let subpath = self
+                    .subpath
+                    .as_ref()
+                    .map(|subpath| format!("#{subpath}"))
+                    .unwrap_or_default();
+                let label = self
+                    .label
+                    .as_ref()
+                    .map(|label| format!("|{label}"))
+                    .unwrap_or_default();
+
+                format!("{embed}[[{target}{subpath}{label}]]")
+            }
+            LinkKind::Markdown => {
+                let subpath = self
+                    .subpath
+                    .as_ref()
+                    .map(|subpath| format!("#{}", percent_encode(subpath)))
+                    .unwrap_or_default();
+                let label = self.label.as_deref().unwrap_or_default();
+
+                format!("{embed}[{label}]({}{subpath})", percent_encode(target))
+            }
+        }
+    }
+}
+
+impl ObsidianNote {
+    /// Every link and embed in the note body, in the order they appear
+    pub fn links(&self) -> Vec<Link> {
+        parse_links(&self.file_body)
+    }
+}
+
+/// Find every wikilink, markdown link and embed in `content`. Links inside
+/// code blocks and inline code are skipped.
+pub fn parse_links(content: &str) -> Vec<Link> {
+    let mut links = Vec::new();
+    let mut in_code_block = false;
+    let mut offset = 0;
+
+    for (idx, line) in content.split_inclusive('\n').enumerate() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+        } else if !in_code_block {
+            line_links(line, offset, idx + 1, &mut links);
+        }
+        offset += line.len();
+    }
+
+    links
+}
+
+/// A link parsed out of a single line, with its end position on that line
+struct ParsedLink {
+    end: usize,
+    kind: LinkKind,
+    target: String,
+    subpath: Option<String>,
+    label: Option<String>,
+}
+
+fn line_links(line: &str, offset: usize, line_number: usize, links: &mut Vec<Link>) {
+    let bytes = line.as_bytes();
+    let mut idx = 0;
+    let mut in_inline_code = false;
+
+    while idx < bytes.len() {
+        let byte = bytes[idx];
+
+        if byte == b'`' {
+            in_inline_code = !in_inline_code;
+            idx += 1;
+            continue;
+        }
+
+        if in_inline_code || (byte != b'[' && byte != b'!') {
+            idx += 1;
+            continue;
+        }
+
+        let embed = byte == b'!';
+        let open = if embed { idx + 1 } else { idx };
+        if bytes.get(open) != Some(&b'[') {
+            idx += 1;
+            continue;
+        }
+
+        let parsed = if line[open..].starts_with("[[") {
+            wikilink(line, open)
+        } else {
+            markdown_link(line, open)
+        };
+
+        match parsed {
+            Some(parsed) => {
+                links.push(Link {
+                    kind: parsed.kind,
+                    embed,
+                    target: parsed.target,
+                    subpath: parsed.subpath,
+                    label: parsed.label,
+                    range: offset + idx..offset + parsed.end,
+                    line: line_number,
+                });
+                idx = parsed.end;
+            }
+            None => idx += 1,
+        }
+    }
+}
+
+fn wikilink(line: &str, open: usize) -> Option<ParsedLink> {
+    let inner_start = open + 2;
+    let inner_len = line[inner_start..].find("]]")?;
+    let inner = &line[inner_start..inner_start + inner_len];
+    if inner.contains('[') {
+        return None;
+    }
+
+    let (destination, label) = match inner.split_once('|') {
+        Some((destination, label)) => (destination, Some(label.trim().to_string())),
+        None => (inner, None),
+    };
+    let (target, subpath) = split_subpath(destination.trim());
+
+    Some(ParsedLink {
+        end: inner_start + inner_len + 2,
+        kind: LinkKind::Wikilink,
+        target: target.to_string(),
+        subpath,
+        label,
+    })
+}
+
+fn markdown_link(line: &str, open: usize) -> Option<ParsedLink> {
+    let label_len = line[open + 1..].find(']')?;
+    let label = &line[open + 1..open + 1 + label_len];
+    let paren = open + 1 + label_len + 1;
+    if line.as_bytes().get(paren) != Some(&b'(') {
+        return None;
+    }
+
+    let rest = &line[paren + 1..];
+    let (destination, close) = if let Some(angled) = rest.strip_prefix('<') {
+        let end = angled.find('>')?;
+        let close = angled[end..].find(')')? + end + 1;
+        (&angled[..end], close)
+    } else {
+        let mut depth = 0;
+        let close = rest.char_indices().find_map(|(idx, c)| match c {
+            '(' => {
+                depth += 1;
+                None
+            }
+            ')' if depth == 0 => Some(idx),
+            ')' => {
+                depth -= 1;
+                None
+            }
+            _ => None,
+        })?;
+        // Anything after whitespace is an optional link title
+        let destination = rest[..close].split_whitespace().next().unwrap_or_default();
+        (destination, close)
+    };
+
+    if destination.is_empty() {
+        return None;
+    }
+
+    let (target, subpath) = split_subpath(destination);
+
+    Some(ParsedLink {
+        end: paren + 1 + close + 1,
+        kind: LinkKind::Markdown,
+        target: percent_decode(target),
+        subpath: subpath.map(|subpath| percent_decode(&subpath)),
+        label: Some(label.to_string()),
+    })
+}
+
+fn split_subpath(destination: &str) -> (&str, Option<String>) {
+    match destination.split_once('#') {
+        Some((target, subpath)) => (target.trim(), Some(subpath.trim().to_string())),
+        None => (destination, None),
+    }
+}
+
+/// Replace byte ranges of `content`, such as the ranges of parsed links, with
+/// new text. Ranges must not overlap.
+pub fn replace_ranges(content: &str, mut replacements: Vec<(Range<usize>, String)>) -> String {
+    replacements.sort_by_key(|(range, _)| range.start);
+
+    let mut replaced = String::with_capacity(content.len());
+    let mut position = 0;
+    for (range, replacement) in replacements {
+        replaced.push_str(&content[position..range.start]);
+        replaced.push_str(&replacement);
+        position = range.end;
+    }
+    replaced.push_str(&content[position..]);
+
+    replaced
+}
+
+/// Escape the characters that would otherwise end or split a markdown link
+/// target
+fn percent_encode(input: &str) -> String {
+    input
+        .replace('%', "%25")
+        .replace(' ', "%20")
+        .replace('(', "%28")
+        .replace(')', "%29")
+}
+
+/// Decode `%XX` escapes, as used for spaces in markdown link targets and
+/// `file://` URIs
+pub fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+
+    while idx < bytes.len() {
+        let escaped = (bytes[idx] == b'%')
+            .then(|| input.get(idx + 1..idx + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                idx += 3;
+            }
+            None => {
+                decoded.push(bytes[idx]);
+                idx += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    fn targets(content: &str) -> Vec<(LinkKind, bool, String, Option<String>)> {
+        parse_links(content)
+            .into_iter()
+            .map(|link| (link.kind, link.embed, link.target, link.subpath))
+            .collect()
+    }
+
+    #[test]
+    fn parses_wikilinks_and_embeds() {
+        assert_eq!(
+            targets("See [[note#Heading|label]] and ![[image.png]]"),
+            vec![
+                (
+                    LinkKind::Wikilink,
+                    false,
+                    "note".into(),
+                    Some("Heading".into())
+                ),
+                (LinkKind::Wikilink, true, "image.png".into(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_markdown_links() {
+        assert_eq!(
+            targets("A [link](folder/My%20Note.md#^block \"title\") and ![img](<a b.png>)"),
+            vec![
+                (
+                    LinkKind::Markdown,
+                    false,
+                    "folder/My Note.md".into(),
+                    Some("^block".into())
+                ),
+                (LinkKind::Markdown, true, "a b.png".into(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn records_ranges_and_lines() {
+        let content = "First line\nThen [[target|label]] here";
+        let link = &parse_links(content)[0];
+
+        assert_eq!(&content[link.range.clone()], "[[target|label]]");
+        assert_eq!(link.line, 2);
+        assert_eq!(link.label.as_deref(), Some("label"));
+    }
+
+    #[test]
+    fn skips_code() {
+        let content = indoc! {"
+            `[[inline]]`
+            ```
+            [[fenced]]
+            ```
+            [[real]]
+        "};
+        assert_eq!(
+            targets(content),
+            vec![(LinkKind::Wikilink, false, "real".into(), None)]
+        );
+    }
+
+    #[test]
+    fn with_target_keeps_subpath_and_label() {
+        let links = parse_links("![[old#Heading|label]] [text](old.md#Some%20Heading)");
+
+        assert_eq!(links[0].with_target("new"), "![[new#Heading|label]]");
+        assert_eq!(
+            links[1].with_target("folder/new note.md"),
+            "[text](folder/new%20note.md#Some%20Heading)"
+        );
+    }
+
+    #[test]
+    fn display_text_falls_back_to_target() {
+        let links = parse_links("[[note|label]] [[note#Heading]] [[#Local]] [[note]]");
+        let texts: Vec<String> = links.iter().map(Link::display_text).collect();
+
+        assert_eq!(texts, vec!["label", "note > Heading", "Local", "note"]);
+    }
+
+    #[test]
+    fn replace_ranges_rewrites_links() {
+        let content = "[[a]] and [[b]]";
+        let replacements = parse_links(content)
+            .into_iter()
+            .map(|link| (link.range.clone(), link.with_target("c")))
+            .collect();
+
+        assert_eq!(replace_ranges(content, replacements), "[[c]] and [[c]]");
+    }
+
+    #[test]
+    fn detects_external_links() {
+        let links = parse_links("[site](https://example.com) [mail](mailto:a@b.c) [[https]]");
+
+        assert!(links[0].is_external());
+        assert!(links[1].is_external());
+        assert!(!links[2].is_external());
+    }
+}
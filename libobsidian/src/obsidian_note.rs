@@ -1,4 +1,7 @@
-use std::{fs, path::PathBuf};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 
 pub type Properties = serde_yaml::Value;
 
@@ -15,12 +18,12 @@ pub struct ObsidianNote {
 }
 
 impl ObsidianNote {
-    pub fn read_from_path(file_path: &PathBuf) -> anyhow::Result<Self> {
+    pub fn read_from_path(file_path: &Path) -> anyhow::Result<Self> {
         let file_contents = fs::read_to_string(file_path)?;
         Self::parse(file_path, file_contents)
     }
 
-    pub fn parse(file_path: &PathBuf, file_contents: String) -> anyhow::Result<Self> {
+    pub fn parse(file_path: &Path, file_contents: String) -> anyhow::Result<Self> {
         let (frontmatter_str, file_body) = extract_frontmatter(&file_contents);
 
         let frontmatter = frontmatter_str
@@ -35,7 +38,7 @@ impl ObsidianNote {
             });
 
         let note = Self {
-            file_path: file_path.clone(),
+            file_path: file_path.to_path_buf(),
             file_body: file_body.unwrap_or_default(),
             file_raw_contents: Some(file_contents),
             properties: frontmatter,
@@ -45,6 +48,27 @@ impl ObsidianNote {
     }
 }
 
+/// Split raw note contents into the YAML between the `---` delimiters, if any,
+/// and everything after the closing delimiter, both untouched
+pub fn split_frontmatter(content: &str) -> (Option<&str>, &str) {
+    let Some(rest) = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))
+    else {
+        return (None, content);
+    };
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return (Some(&rest[..offset]), &rest[offset + line.len()..]);
+        }
+        offset += line.len();
+    }
+
+    (None, content)
+}
+
 fn extract_frontmatter(content: &str) -> (Option<String>, Option<String>) {
     let delimiter = "---";
     let mut parts = content.splitn(3, delimiter);
@@ -64,6 +88,14 @@ mod tests {
     use super::*;
     use indoc::indoc;
 
+    #[test]
+    fn split_frontmatter_keeps_raw_text() {
+        let content = "---\ntitle: A\n---\n\nBody\n";
+        assert_eq!(split_frontmatter(content), (Some("title: A\n"), "\nBody\n"));
+        assert_eq!(split_frontmatter("Body ---"), (None, "Body ---"));
+        assert_eq!(split_frontmatter("---\nunclosed"), (None, "---\nunclosed"));
+    }
+
     #[test]
     fn parse_returns_body() {
         let note_content = indoc! {r"
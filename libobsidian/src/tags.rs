@@ -0,0 +1,295 @@
+use crate::{
+    frontmatter::set_property, links::replace_ranges, split_frontmatter, ObsidianNote, Properties,
+};
+use std::ops::Range;
+
+impl ObsidianNote {
+    /// Every tag on the note, from both the frontmatter and inline `#tags` in
+    /// the body, without the leading `#`. Duplicates are removed, keeping the
+    /// order tags first appear in.
+    pub fn tags(&self) -> Vec<String> {
+        let mut tags = self
+            .properties
+            .as_ref()
+            .map(frontmatter_tags)
+            .unwrap_or_default();
+
+        for tag in inline_tags(&self.file_body) {
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+
+        tags
+    }
+}
+
+/// Tags listed under the `tags` (or legacy `tag`) frontmatter property, given
+/// either as a YAML list or a comma/space separated string
+pub fn frontmatter_tags(properties: &Properties) -> Vec<String> {
+    let value = properties.get("tags").or_else(|| properties.get("tag"));
+
+    let raw: Vec<String> = match value {
+        Some(serde_yaml::Value::Sequence(seq)) => seq
+            .iter()
+            .filter_map(|item| item.as_str().map(str::to_string))
+            .collect(),
+        Some(serde_yaml::Value::String(tags)) => tags
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let mut tags: Vec<String> = Vec::new();
+    for tag in raw {
+        let tag = tag.trim().trim_start_matches('#').to_string();
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+
+    tags
+}
+
+/// Inline `#tags` in a note body. Code blocks and inline code are skipped, as
+/// are headings and anything made up only of digits (e.g. `#123`).
+pub fn inline_tags(body: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for (_, tag) in inline_tag_ranges(body) {
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+
+    tags
+}
+
+/// Every inline `#tag` in a note body, as found by [`inline_tags`] but
+/// without removing duplicates, along with the byte range of the tag after
+/// its `#`
+pub fn inline_tag_ranges(body: &str) -> Vec<(Range<usize>, String)> {
+    let mut tags = Vec::new();
+    let mut in_code_block = false;
+    let mut offset = 0;
+
+    for line in body.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+
+        for (idx, tag) in line_tags(line) {
+            tags.push((start + idx..start + idx + tag.len(), tag));
+        }
+    }
+
+    tags
+}
+
+/// `content` with each tag `rename` gives a new name for renamed, both in the
+/// frontmatter `tags` property and inline, and how many tags were renamed.
+/// Frontmatter tags that end up the same are listed once, and the property
+/// keeps being written as a list, block list or string as it was.
+pub fn rename_tags(content: &str, rename: impl Fn(&str) -> Option<String>) -> (String, usize) {
+    let mut renamed = 0;
+    let mut content = content.to_string();
+
+    let frontmatter = split_frontmatter(&content).0;
+    let properties: Option<Properties> =
+        frontmatter.and_then(|text| serde_yaml::from_str(text).ok());
+    if let (Some(frontmatter), Some(properties)) = (frontmatter, properties) {
+        let mut tags: Vec<String> = Vec::new();
+        for tag in frontmatter_tags(&properties) {
+            let tag = match rename(&tag) {
+                Some(new) => {
+                    renamed += 1;
+                    new
+                }
+                None => tag,
+            };
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        if renamed > 0 {
+            let key = if properties.get("tags").is_some() {
+                "tags"
+            } else {
+                "tag"
+            };
+            let value = tags_value(frontmatter, key, &tags);
+            content = set_property(&content, key, &value);
+        }
+    }
+
+    let body = split_frontmatter(&content).1;
+    let body_start = content.len() - body.len();
+    let replacements: Vec<(Range<usize>, String)> = inline_tag_ranges(body)
+        .into_iter()
+        .filter_map(|(range, tag)| {
+            let new = rename(&tag)?;
+            Some((body_start + range.start..body_start + range.end, new))
+        })
+        .collect();
+    renamed += replacements.len();
+    if !replacements.is_empty() {
+        content = replace_ranges(&content, replacements);
+    }
+
+    (content, renamed)
+}
+
+/// `tags` as the YAML for the `key` property, in the style `frontmatter`
+/// already writes it
+fn tags_value(frontmatter: &str, key: &str, tags: &[String]) -> String {
+    let prefix = format!("{key}:");
+    let mut lines = frontmatter.lines();
+    let value = lines
+        .find_map(|line| line.strip_prefix(&prefix))
+        .unwrap_or_default()
+        .trim();
+
+    if value.starts_with('[') {
+        format!("[{}]", tags.join(", "))
+    } else if value.is_empty() {
+        let indent = lines
+            .next()
+            .and_then(|line| line.find('-').map(|idx| &line[..idx]))
+            .filter(|indent| indent.trim().is_empty())
+            .unwrap_or("  ");
+        tags.iter()
+            .map(|tag| format!("\n{indent}- {tag}"))
+            .collect()
+    } else if value.contains(',') {
+        tags.join(", ")
+    } else {
+        tags.join(" ")
+    }
+}
+
+pub fn is_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '/')
+}
+
+/// The tags in a line, with the byte offset each starts at after its `#`
+fn line_tags(line: &str) -> Vec<(usize, String)> {
+    let mut tags = Vec::new();
+    let mut in_inline_code = false;
+    let mut previous: Option<char> = None;
+    let mut chars = line.char_indices().peekable();
+
+    while let Some((idx, c)) = chars.next() {
+        if c == '`' {
+            in_inline_code = !in_inline_code;
+        } else if c == '#' && !in_inline_code && previous.map(char::is_whitespace).unwrap_or(true) {
+            let tag: String = line[idx + 1..]
+                .chars()
+                .take_while(|c| is_tag_char(*c))
+                .collect();
+            let tag = tag.trim_end_matches('/');
+
+            if !tag.is_empty() && !tag.chars().all(|c| c.is_ascii_digit() || c == '/') {
+                tags.push((idx + 1, tag.to_string()));
+            }
+
+            while chars.peek().is_some_and(|(_, c)| is_tag_char(*c)) {
+                chars.next();
+            }
+        }
+        previous = Some(c);
+    }
+
+    tags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use std::path::Path;
+
+    #[test]
+    fn inline_tags_finds_tags_in_body() {
+        let body = "Some #tag and #nested/tag, plus #another.";
+        assert_eq!(inline_tags(body), vec!["tag", "nested/tag", "another"]);
+    }
+
+    #[test]
+    fn inline_tags_ignores_headings_numbers_and_code() {
+        let body = indoc! {r"
+            # Heading
+            Issue #123 and a url.com/#fragment
+            `#not-a-tag` but #real
+            ```
+            #also-not
+            ```
+        "};
+        assert_eq!(inline_tags(body), vec!["real"]);
+    }
+
+    #[test]
+    fn renames_tags_in_frontmatter_and_body() {
+        let rename = |tag: &str| (tag == "old" || tag == "other").then(|| "new".to_string());
+        let content = indoc! {r"
+            ---
+            tags:
+              - old
+              - keep
+              - other
+            ---
+            Some #old, #older and `#old` #other
+        "};
+
+        assert_eq!(
+            rename_tags(content, rename),
+            (
+                indoc! {r"
+                    ---
+                    tags:
+                      - new
+                      - keep
+                    ---
+                    Some #new, #older and `#old` #new
+                "}
+                .to_string(),
+                4
+            )
+        );
+        assert_eq!(
+            rename_tags("---\ntags: [old, keep]\n---\n", rename).0,
+            "---\ntags: [new, keep]\n---\n"
+        );
+        assert_eq!(
+            rename_tags("---\ntags: keep old\n---\n", rename).0,
+            "---\ntags: keep new\n---\n"
+        );
+    }
+
+    #[test]
+    fn frontmatter_tags_accepts_lists_and_strings() {
+        let list: Properties = serde_yaml::from_str("tags: [a, '#b']").unwrap();
+        let string: Properties = serde_yaml::from_str("tags: a, b c").unwrap();
+
+        assert_eq!(frontmatter_tags(&list), vec!["a", "b"]);
+        assert_eq!(frontmatter_tags(&string), vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn note_tags_merges_frontmatter_and_inline() {
+        let note_content = indoc! {r"
+            ---
+            tags: [project, draft]
+            ---
+            Body with #draft and #idea
+        "};
+        let note = ObsidianNote::parse(Path::new("a-note.md"), note_content.to_string()).unwrap();
+
+        assert_eq!(note.tags(), vec!["project", "draft", "idea"]);
+    }
+}
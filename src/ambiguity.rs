@@ -0,0 +1,158 @@
+//! Which note a name means when several notes share it, like
+//! `Projects/Index.md` and `Archive/Index.md` for `[[Index]]`. Obsidian
+//! opens the one with the shortest path; a vault can instead prefer notes in
+//! particular folders, or have `obx open` and the like ask every time. Links,
+//! backlinks and the links rewritten by renames all follow the same choice.
+
+use crate::{cli_config, matching::MatchSettings};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum AmbiguityPolicy {
+    /// The shortest path, as Obsidian picks
+    Shortest,
+    /// The first of `folders` a note is in, then the shortest path
+    Folders,
+    /// Commands opening a note by name ask which one, links resolve to the
+    /// shortest path since there's no one to ask
+    #[default]
+    Ask,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct AmbiguitySettings {
+    pub policy: AmbiguityPolicy,
+    /// Vault-relative folders, most preferred first, for the `folders` policy
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub folders: Vec<String>,
+}
+
+impl AmbiguitySettings {
+    pub fn is_default(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// The config's settings, read afresh like [`MatchSettings::read`]
+    pub fn read() -> Self {
+        cli_config::read()
+            .map(|config| config.ambiguous_links)
+            .unwrap_or_default()
+    }
+
+    /// Whether commands opening a note by a name several notes have should
+    /// ask which one rather than pick
+    pub fn asks(&self) -> bool {
+        self.policy == AmbiguityPolicy::Ask
+    }
+
+    /// Sort vault-relative paths sharing a name so the one the name means
+    /// comes first, comparing folders as `matching` does
+    pub fn rank<S: AsRef<str>>(&self, paths: &mut [S], matching: &MatchSettings) {
+        paths.sort_by_cached_key(|path| {
+            let path = path.as_ref();
+            (
+                self.folder_rank(path, matching),
+                path.len(),
+                path.to_string(),
+            )
+        });
+    }
+
+    /// How far down `folders` the first folder holding `path` is, past the
+    /// end when none does or the policy doesn't use them
+    fn folder_rank(&self, path: &str, matching: &MatchSettings) -> usize {
+        if self.policy != AmbiguityPolicy::Folders {
+            return 0;
+        }
+        let path = matching.fold(path);
+        self.folders
+            .iter()
+            .position(|folder| {
+                let folder = matching.fold(folder.trim_matches('/'));
+                path.starts_with(&format!("{folder}/"))
+            })
+            .unwrap_or(self.folders.len())
+    }
+}
+
+/// `files` by their file name folded with `matching`
+pub fn files_by_name<'a>(
+    files: impl IntoIterator<Item = &'a str>,
+    matching: &MatchSettings,
+) -> BTreeMap<String, Vec<&'a str>> {
+    let mut names: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+    for file in files {
+        let name = file.rsplit('/').next().unwrap_or(file);
+        names.entry(matching.fold(name)).or_default().push(file);
+    }
+    names
+}
+
+/// The names shared by several of `files`, by file name folded with
+/// `matching`, each with its files ranked by `settings`
+pub fn shared_names<'a>(
+    files: impl IntoIterator<Item = &'a str>,
+    settings: &AmbiguitySettings,
+    matching: &MatchSettings,
+) -> BTreeMap<String, Vec<&'a str>> {
+    let mut names = files_by_name(files, matching);
+    names.retain(|_, files| files.len() > 1);
+    for files in names.values_mut() {
+        settings.rank(files, matching);
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranks_by_folder_then_length() {
+        let mut paths = vec!["Projects/Index.md", "Archive/2023/Index.md", "Index.md"];
+        let shortest = AmbiguitySettings::default();
+        shortest.rank(&mut paths, &MatchSettings::default());
+        assert_eq!(
+            paths,
+            ["Index.md", "Projects/Index.md", "Archive/2023/Index.md"]
+        );
+
+        let folders = AmbiguitySettings {
+            policy: AmbiguityPolicy::Folders,
+            folders: vec!["archive/".to_string(), "Projects".to_string()],
+        };
+        folders.rank(&mut paths, &MatchSettings::default());
+        assert_eq!(
+            paths,
+            ["Archive/2023/Index.md", "Projects/Index.md", "Index.md"]
+        );
+    }
+
+    #[test]
+    fn finds_shared_names() {
+        let files = [
+            "a/Note.md",
+            "b/note.md",
+            "Other.md",
+            "image.png",
+            "c/image.png",
+        ];
+
+        let shared = shared_names(
+            files,
+            &AmbiguitySettings::default(),
+            &MatchSettings::default(),
+        );
+
+        assert_eq!(
+            shared,
+            BTreeMap::from([
+                ("image.png".to_string(), vec!["image.png", "c/image.png"]),
+                ("note.md".to_string(), vec!["a/Note.md", "b/note.md"]),
+            ])
+        );
+    }
+}
@@ -0,0 +1,255 @@
+//! The JSON API `obx serve --api` answers with, kept apart from the HTTP
+//! server so each route is a plain function of the request:
+//!
+//! ```text
+//! GET  /notes                      every note's vault-relative path
+//! GET  /notes/<path>               a note's content and frontmatter
+//! PUT  /notes/<path>               replace or create a note with the body
+//! GET  /search?q=<query>&limit=<n> notes matching an `obx search` query
+//! GET  /query?q=<expr>&select=<fields>
+//!                                  notes whose frontmatter matches an `obx query` filter
+//! ```
+//!
+//! Note paths are always markdown, `.md` being added when left out, and
+//! never in hidden folders like `.obsidian`. Errors are `{"error": "..."}`
+//! with a 4xx or 5xx status.
+
+use crate::{
+    cli_config::Vault,
+    formats::yaml_to_json_value,
+    hooks::{self, Event},
+    note_text::NoteText,
+    query::{field_value, Expr},
+    scan,
+    search_query::SearchQuery,
+    util::vault_note_path,
+};
+use libobsidian::ObsidianNote;
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// What to send back for a request
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub body: Value,
+}
+
+impl Response {
+    fn ok(body: Value) -> Self {
+        Self { status: 200, body }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            body: json!({ "error": message.into() }),
+        }
+    }
+}
+
+/// Answer one request. `url` is the path and query as sent, still
+/// percent-encoded.
+pub fn handle(vault: &Vault, method: &str, url: &str, body: &str) -> Response {
+    let vault_path = vault.path.as_path();
+    let (path, query_string) = url.split_once('?').unwrap_or((url, ""));
+    let params: HashMap<String, String> = form_urlencoded::parse(query_string.as_bytes())
+        .into_owned()
+        .collect();
+    let path = percent_encoding::percent_decode_str(path).decode_utf8_lossy();
+
+    let result = match (method, path.trim_end_matches('/')) {
+        ("GET", "/notes") => list_notes(vault_path),
+        ("GET", "/search") => search(vault_path, &params),
+        ("GET", "/query") => query_notes(vault_path, &params),
+        ("GET", path) if path.starts_with("/notes/") => read_note(vault_path, &path[7..]),
+        ("PUT", path) if path.starts_with("/notes/") => write_note(vault, &path[7..], body),
+        (_, path)
+            if matches!(path, "/notes" | "/search" | "/query") || path.starts_with("/notes/") =>
+        {
+            return Response::error(405, format!("{method} isn't allowed here"))
+        }
+        (_, path) => return Response::error(404, format!("Nothing at {path}")),
+    };
+
+    result.unwrap_or_else(|err| Response::error(500, format!("{err:#}")))
+}
+
+fn list_notes(vault_path: &Path) -> anyhow::Result<Response> {
+    let paths: Vec<String> = scan::note_paths(vault_path)?
+        .iter()
+        .map(|path| scan::relative_note_path(path, vault_path))
+        .collect();
+    Ok(Response::ok(json!(paths)))
+}
+
+fn read_note(vault_path: &Path, note: &str) -> anyhow::Result<Response> {
+    let note_path = match note_path(vault_path, note) {
+        Ok(note_path) => note_path,
+        Err(response) => return Ok(response),
+    };
+    if !note_path.is_file() {
+        return Ok(Response::error(404, format!("No note {note}")));
+    }
+
+    let note = ObsidianNote::read_from_path(&note_path)?;
+    let content = match note.file_raw_contents {
+        Some(content) => content,
+        None => fs::read_to_string(&note_path)?,
+    };
+    Ok(Response::ok(json!({
+        "path": scan::relative_note_path(&note_path, vault_path),
+        "properties": note.properties.as_ref().map(yaml_to_json_value),
+        "content": content,
+    })))
+}
+
+/// Creating a note runs the `after_create` hook, like every other way obx
+/// creates them
+fn write_note(vault: &Vault, note: &str, content: &str) -> anyhow::Result<Response> {
+    let vault_path = vault.path.as_path();
+    let note_path = match note_path(vault_path, note) {
+        Ok(note_path) => note_path,
+        Err(response) => return Ok(response),
+    };
+    let created = !note_path.exists();
+    if let Some(parent) = note_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&note_path, content)?;
+    if created {
+        hooks::run(Event::AfterCreate, vault, &note_path, None)?;
+    }
+
+    Ok(Response {
+        status: if created { 201 } else { 200 },
+        body: json!({ "path": scan::relative_note_path(&note_path, vault_path) }),
+    })
+}
+
+fn search(vault_path: &Path, params: &HashMap<String, String>) -> anyhow::Result<Response> {
+    let Some(text) = params.get("q") else {
+        return Ok(Response::error(400, "Missing the `q` parameter"));
+    };
+    let limit = match limit(params) {
+        Ok(limit) => limit,
+        Err(response) => return Ok(response),
+    };
+    let query = match SearchQuery::parse(text) {
+        Ok(query) => query,
+        Err(err) => return Ok(Response::error(400, format!("{err:#}"))),
+    };
+
+    let results: Vec<Value> = query
+        .search(vault_path)?
+        .into_iter()
+        .take(limit)
+        .map(|result| {
+            let snippet = NoteText::read(&vault_path.join(&result.path))
+                .ok()
+                .and_then(|contents| query.snippet(&contents));
+            json!({
+                "path": result.path,
+                "score": result.score,
+                "modified": result.modified.map(|modified| modified.to_rfc3339()),
+                "line": snippet.as_ref().map(|snippet| snippet.line),
+                "snippet": snippet.map(|snippet| snippet.text),
+            })
+        })
+        .collect();
+    Ok(Response::ok(json!(results)))
+}
+
+fn query_notes(vault_path: &Path, params: &HashMap<String, String>) -> anyhow::Result<Response> {
+    let Some(text) = params.get("q") else {
+        return Ok(Response::error(400, "Missing the `q` parameter"));
+    };
+    let expr = match Expr::parse(text) {
+        Ok(expr) => expr,
+        Err(err) => return Ok(Response::error(400, format!("{err:#}"))),
+    };
+    let select: Vec<&str> = params
+        .get("select")
+        .map(|select| {
+            select
+                .split(',')
+                .filter(|field| !field.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut rows = Vec::new();
+    for note_path in scan::note_paths(vault_path)? {
+        let Ok(note) = ObsidianNote::read_from_path(&note_path) else {
+            continue;
+        };
+        if !expr.matches(&note) {
+            continue;
+        }
+
+        let mut row = serde_json::Map::new();
+        row.insert(
+            "path".to_string(),
+            scan::relative_note_path(&note_path, vault_path).into(),
+        );
+        for field in &select {
+            let value = field_value(&note, field)
+                .map(|value| yaml_to_json_value(&value))
+                .unwrap_or_default();
+            row.insert(field.to_string(), value);
+        }
+        rows.push(Value::Object(row));
+    }
+    Ok(Response::ok(Value::Array(rows)))
+}
+
+fn note_path(vault_path: &Path, note: &str) -> Result<PathBuf, Response> {
+    vault_note_path(note, vault_path).map_err(|err| Response::error(400, format!("{err:#}")))
+}
+
+fn limit(params: &HashMap<String, String>) -> Result<usize, Response> {
+    match params.get("limit") {
+        Some(limit) => limit
+            .parse()
+            .map_err(|_| Response::error(400, format!("Invalid limit `{limit}`"))),
+        None => Ok(usize::MAX),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vault() -> Vault {
+        Vault {
+            name: "vault".to_string(),
+            path: "/vault".into(),
+            ..Vault::default()
+        }
+    }
+
+    #[test]
+    fn refuses_paths_outside_the_vault() {
+        for note in [
+            "../secret",
+            "/etc/passwd",
+            "a/../../b",
+            ".obsidian/app.json",
+        ] {
+            assert_eq!(
+                handle(&vault(), "PUT", &format!("/notes/{note}"), "").status,
+                400
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_routes() {
+        assert_eq!(handle(&vault(), "GET", "/nothing", "").status, 404);
+        assert_eq!(handle(&vault(), "DELETE", "/notes", "").status, 405);
+    }
+}
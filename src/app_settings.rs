@@ -2,34 +2,66 @@ use anyhow::Context;
 #[cfg(target_os = "linux")]
 use etcetera::BaseStrategy;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, fs, path::PathBuf, sync::OnceLock};
+use std::{
+    collections::HashMap,
+    env::{self, VarError},
+    fs,
+    path::PathBuf,
+    sync::OnceLock,
+};
 
-fn obsidian_app_settings_path() -> &'static PathBuf {
+/// Where the Obsidian app keeps its list of vaults. `OBX_OBSIDIAN_SETTINGS`
+/// overrides the platform's location, otherwise the first candidate that
+/// exists wins (Linux has separate ones for Flatpak and Snap installs).
+pub fn obsidian_app_settings_path() -> &'static PathBuf {
     static SETTINGS_PATH: OnceLock<PathBuf> = OnceLock::new();
 
-    SETTINGS_PATH.get_or_init(|| {
-        #[cfg(target_os = "macos")]
-        let path = etcetera::home_dir()
-            .expect("should be able to find home dir")
-            .join("Library/Application Support/obsidian/obsidian.json");
+    SETTINGS_PATH.get_or_init(|| match env::var("OBX_OBSIDIAN_SETTINGS") {
+        Ok(path) => PathBuf::from(path),
+        Err(VarError::NotPresent) => {
+            let candidates = candidate_paths();
+            candidates
+                .iter()
+                .find(|path| path.is_file())
+                .unwrap_or(&candidates[0])
+                .clone()
+        }
+        _ => panic!("Malformed OBX_OBSIDIAN_SETTINGS"),
+    })
+}
 
-        #[cfg(target_os = "windows")]
-        let path = PathBuf::from("%APPDATA%\\Obsidian\\obsidian.json");
+fn candidate_paths() -> Vec<PathBuf> {
+    #[cfg(target_os = "macos")]
+    let paths = vec![etcetera::home_dir()
+        .expect("should be able to find home dir")
+        .join("Library/Application Support/obsidian/obsidian.json")];
 
-        #[cfg(target_os = "linux")]
-        let path = etcetera::choose_base_strategy()
-            .expect("etcetera base strategy should work")
-            .config_dir()
-            .join("Obsidian/obsidian.json");
+    #[cfg(target_os = "windows")]
+    let paths = vec![PathBuf::from(env::var_os("APPDATA").unwrap_or_default())
+        .join("obsidian")
+        .join("obsidian.json")];
 
-        path
-    })
+    #[cfg(target_os = "linux")]
+    let paths = {
+        let home = etcetera::home_dir().expect("should be able to find home dir");
+        vec![
+            etcetera::choose_base_strategy()
+                .expect("etcetera base strategy should work")
+                .config_dir()
+                .join("obsidian/obsidian.json"),
+            home.join(".var/app/md.obsidian.Obsidian/config/obsidian/obsidian.json"),
+            home.join("snap/obsidian/current/.config/obsidian/obsidian.json"),
+        ]
+    };
+
+    paths
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Vault {
     pub path: PathBuf,
-    pub ts: PathBuf,
+    /// When the vault was last opened, in milliseconds since the epoch
+    pub ts: Option<i64>,
     pub open: Option<bool>,
 }
 
@@ -38,6 +70,15 @@ pub struct Settings {
     pub vaults: HashMap<String, Vault>,
 }
 
+impl Settings {
+    /// The app's vaults, most recently opened first
+    pub fn vaults_by_recency(&self) -> Vec<&Vault> {
+        let mut vaults: Vec<&Vault> = self.vaults.values().collect();
+        vaults.sort_by(|a, b| b.ts.cmp(&a.ts).then_with(|| a.path.cmp(&b.path)));
+        vaults
+    }
+}
+
 pub fn read() -> anyhow::Result<Settings> {
     let settings_path = obsidian_app_settings_path();
     let settings_file = fs::read_to_string(settings_path).with_context(|| {
@@ -47,7 +88,12 @@ pub fn read() -> anyhow::Result<Settings> {
         )
     })?;
 
-    let settings = serde_yaml::from_str(&settings_file)?;
+    let settings = serde_yaml::from_str(&settings_file).with_context(|| {
+        format!(
+            "failed to parse obsidian app settings file at path `{}`",
+            settings_path.display()
+        )
+    })?;
 
     Ok(settings)
 }
@@ -0,0 +1,213 @@
+use crate::vault_settings;
+use anyhow::{bail, Context};
+use libobsidian::links::percent_decode;
+use sha2::{Digest, Sha256};
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+/// Folder (relative to the vault root) attachments are copied into when the
+/// config doesn't specify one
+pub const DEFAULT_ATTACHMENT_FOLDER: &str = "attachments";
+
+const EMBEDDABLE_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "gif", "bmp", "svg", "webp", "avif", "mp3", "wav", "m4a", "ogg", "flac",
+    "mp4", "webm", "ogv", "mov", "mkv", "pdf",
+];
+
+/// The vault-relative folder attachments for a note in `note_folder` go in:
+/// the config's `attachment_folder` if set, then the Obsidian app's own
+/// setting for the vault, then [`DEFAULT_ATTACHMENT_FOLDER`]
+pub fn attachment_folder(
+    configured: Option<&Path>,
+    vault_path: &Path,
+    note_folder: &Path,
+) -> PathBuf {
+    if let Some(folder) = configured {
+        return folder.to_path_buf();
+    }
+
+    match vault_settings::read(vault_path) {
+        Some(settings) => settings.attachment_folder(note_folder),
+        None => PathBuf::from(DEFAULT_ATTACHMENT_FOLDER),
+    }
+}
+
+/// Copy `source` into the vault's attachment folder and append a link to it
+/// at the end of the note. Files already present with identical contents are
+/// reused rather than copied again. Returns the inserted link.
+pub fn attach(
+    vault_path: &Path,
+    attachment_folder: &Path,
+    note_path: &Path,
+    source: &Path,
+) -> anyhow::Result<String> {
+    if !source.is_file() {
+        bail!("Could not attach `{}`, file not found", source.display());
+    }
+
+    let folder = vault_path.join(attachment_folder);
+    fs::create_dir_all(&folder)
+        .with_context(|| format!("Could not create directory {}", folder.display()))?;
+
+    let attachment_path = match find_duplicate(&folder, source)? {
+        Some(existing) => existing,
+        None => {
+            let destination = unique_destination(&folder, source)?;
+            fs::copy(source, &destination).with_context(|| {
+                format!(
+                    "Could not copy `{}` to {}",
+                    source.display(),
+                    destination.display()
+                )
+            })?;
+            destination
+        }
+    };
+
+    let link = attachment_link(&attachment_path);
+    append_to_note(note_path, &link)?;
+
+    Ok(link)
+}
+
+/// Turn a path as pasted or dropped into a terminal into a real path,
+/// handling surrounding quotes, backslash-escaped spaces and `file://` URIs
+pub fn normalize_dropped_path(input: &str) -> PathBuf {
+    let trimmed = input.trim();
+
+    let unquoted = ['\'', '"']
+        .iter()
+        .find_map(|quote| {
+            trimmed
+                .strip_prefix(*quote)
+                .and_then(|rest| rest.strip_suffix(*quote))
+        })
+        .unwrap_or(trimmed);
+
+    if let Some(uri_path) = unquoted.strip_prefix("file://") {
+        return PathBuf::from(percent_decode(uri_path));
+    }
+
+    PathBuf::from(unquoted.replace("\\ ", " "))
+}
+
+/// A wikilink to an attachment, as an embed when Obsidian can show it inline
+pub fn attachment_link(attachment_path: &Path) -> String {
+    let file_name = attachment_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let embeddable = attachment_path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| EMBEDDABLE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+        .unwrap_or(false);
+
+    if embeddable {
+        format!("![[{file_name}]]")
+    } else {
+        format!("[[{file_name}]]")
+    }
+}
+
+fn append_to_note(note_path: &Path, link: &str) -> anyhow::Result<()> {
+    let existing = fs::read_to_string(note_path).unwrap_or_default();
+
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(note_path)
+        .with_context(|| format!("Could not open note {}", note_path.display()))?;
+
+    let separator = if existing.is_empty() || existing.ends_with('\n') {
+        ""
+    } else {
+        "\n"
+    };
+
+    writeln!(file, "{separator}{link}")
+        .with_context(|| format!("Could not write to note {}", note_path.display()))
+}
+
+fn hash_file(path: &Path) -> anyhow::Result<Vec<u8>> {
+    let contents =
+        fs::read(path).with_context(|| format!("Could not read file {}", path.display()))?;
+    Ok(Sha256::digest(contents).to_vec())
+}
+
+/// Look for a file in the attachment folder with the same contents as `source`
+fn find_duplicate(folder: &Path, source: &Path) -> anyhow::Result<Option<PathBuf>> {
+    let source_len = fs::metadata(source)?.len();
+    let mut source_hash = None;
+
+    for entry in fs::read_dir(folder)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        if !metadata.is_file() || metadata.len() != source_len {
+            continue;
+        }
+
+        if source_hash.is_none() {
+            source_hash = Some(hash_file(source)?);
+        }
+
+        if source_hash.as_deref() == Some(hash_file(&entry.path())?.as_slice()) {
+            return Ok(Some(entry.path()));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Pick a destination in `folder` for `source` that doesn't clash with an
+/// existing file, following Obsidian's `name 1.ext`, `name 2.ext` convention
+pub fn unique_destination(folder: &Path, source: &Path) -> anyhow::Result<PathBuf> {
+    let file_name = source
+        .file_name()
+        .context("attachment should have a file name")?;
+    let candidate = folder.join(file_name);
+    if !candidate.exists() {
+        return Ok(candidate);
+    }
+
+    let stem = source
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let extension = source
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+
+    let destination = (1..)
+        .map(|n| folder.join(format!("{stem} {n}{extension}")))
+        .find(|path| !path.exists())
+        .expect("should find a free attachment name");
+
+    Ok(destination)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case("/tmp/image.png", "/tmp/image.png" ; "plain path")]
+    #[test_case("'/tmp/my image.png' ", "/tmp/my image.png" ; "single quoted")]
+    #[test_case("\"/tmp/my image.png\"", "/tmp/my image.png" ; "double quoted")]
+    #[test_case("/tmp/my\\ image.png", "/tmp/my image.png" ; "escaped spaces")]
+    #[test_case("file:///tmp/my%20image.png", "/tmp/my image.png" ; "file uri")]
+    fn normalize_dropped_path_cleans_input(input: &str, expected: &str) {
+        assert_eq!(normalize_dropped_path(input), PathBuf::from(expected));
+    }
+
+    #[test_case("photo.PNG", "![[photo.PNG]]" ; "image is embedded")]
+    #[test_case("archive.zip", "[[archive.zip]]" ; "other files are linked")]
+    fn attachment_link_embeds_media(file: &str, expected: &str) {
+        assert_eq!(attachment_link(Path::new(file)), expected);
+    }
+}
@@ -0,0 +1,80 @@
+//! State shared between `obx git autocommit` and the commands that steer it:
+//! a marker that pauses committing and a log of what it did, both kept in
+//! the vault's state directory so they work from any terminal.
+
+use crate::cli_config;
+use anyhow::Context;
+use chrono::Local;
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+const PAUSE_FILE: &str = "autocommit-paused";
+const LOG_FILE: &str = "autocommit.log";
+
+fn pause_path(vault_path: &Path) -> PathBuf {
+    cli_config::vault_state_dir(vault_path).join(PAUSE_FILE)
+}
+
+pub fn log_path(vault_path: &Path) -> PathBuf {
+    cli_config::vault_state_dir(vault_path).join(LOG_FILE)
+}
+
+pub fn is_paused(vault_path: &Path) -> bool {
+    pause_path(vault_path).exists()
+}
+
+/// Stop committing until `resume`. Returns whether it wasn't paused already.
+pub fn pause(vault_path: &Path) -> anyhow::Result<bool> {
+    let path = pause_path(vault_path);
+    if path.exists() {
+        return Ok(false);
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Could not create directory {}", parent.display()))?;
+    }
+    fs::write(&path, "").with_context(|| format!("Could not write {}", path.display()))?;
+    Ok(true)
+}
+
+/// Returns whether it was paused
+pub fn resume(vault_path: &Path) -> anyhow::Result<bool> {
+    let path = pause_path(vault_path);
+    if !path.exists() {
+        return Ok(false);
+    }
+    fs::remove_file(&path).with_context(|| format!("Could not remove {}", path.display()))?;
+    Ok(true)
+}
+
+/// Add a line to the activity log, stamped with the time
+pub fn record(vault_path: &Path, entry: &str) -> anyhow::Result<()> {
+    let path = log_path(vault_path);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Could not create directory {}", parent.display()))?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("Could not open {}", path.display()))?;
+    writeln!(file, "{} {entry}", Local::now().format("%Y-%m-%d %H:%M:%S"))
+        .with_context(|| format!("Could not write {}", path.display()))
+}
+
+/// The last `limit` lines of the activity log, oldest first
+pub fn activity(vault_path: &Path, limit: usize) -> anyhow::Result<Vec<String>> {
+    let path = log_path(vault_path);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let log =
+        fs::read_to_string(&path).with_context(|| format!("Could not read {}", path.display()))?;
+    let lines: Vec<String> = log.lines().map(str::to_string).collect();
+    let skip = lines.len().saturating_sub(limit);
+    Ok(lines.into_iter().skip(skip).collect())
+}
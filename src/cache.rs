@@ -0,0 +1,389 @@
+//! Note metadata cached in the vault's state directory, so listing and
+//! browsing a large vault only re-parses the notes that changed since last
+//! time
+
+use crate::{
+    matching::MatchSettings,
+    relations, scan,
+    state_file::{self, file_stamp},
+    util,
+};
+use chrono::{DateTime, Local, TimeZone};
+use libobsidian::{canvas, links::parse_links, ObsidianNote};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, UNIX_EPOCH},
+};
+
+/// Bumped whenever the format changes, older caches are rebuilt
+const CACHE_VERSION: u32 = 5;
+const CACHE_FILE: &str = "metadata.json";
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct MetadataCache {
+    version: u32,
+    /// Every folder in the vault, vault-relative, with `""` for the root
+    folders: Vec<String>,
+    /// Every note, by vault-relative path
+    notes: BTreeMap<String, NoteMetadata>,
+    /// When each canvas was modified, in milliseconds since the epoch, by
+    /// vault-relative path
+    canvases: BTreeMap<String, u128>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NoteMetadata {
+    /// Modification time in milliseconds since the epoch
+    modified: u128,
+    /// Creation time in milliseconds since the epoch, from the note's
+    /// `created` or `date` property when it has one
+    created: Option<i64>,
+    size: u64,
+    pub tags: Vec<String>,
+    pub word_count: usize,
+    /// The targets of the note's links to other notes and files, as written
+    pub links: Vec<String>,
+    /// Other names the note goes by, from its `aliases` property
+    pub aliases: Vec<String>,
+    /// The frontmatter properties holding wikilinks, by lowercase name, for
+    /// the [relations](crate::relations) they define
+    pub property_links: BTreeMap<String, Vec<String>>,
+}
+
+impl NoteMetadata {
+    fn read(note_path: &Path, modified: u128, size: u64) -> Self {
+        let contents = fs::read_to_string(note_path).unwrap_or_default();
+        let links = parse_links(&contents)
+            .into_iter()
+            .filter(|link| !link.is_external() && !link.target.is_empty())
+            .map(|link| link.target)
+            .collect();
+        let note = ObsidianNote::parse(note_path, contents).ok();
+
+        let created = util::note_created(
+            note.as_ref().and_then(|note| note.properties.as_ref()),
+            note_path,
+        );
+
+        Self {
+            modified,
+            created: created.map(|time| time.timestamp_millis()),
+            size,
+            tags: note.as_ref().map(ObsidianNote::tags).unwrap_or_default(),
+            word_count: note
+                .as_ref()
+                .map_or(0, |note| note.file_body.split_whitespace().count()),
+            links,
+            aliases: note.as_ref().map(ObsidianNote::aliases).unwrap_or_default(),
+            property_links: note
+                .as_ref()
+                .and_then(|note| note.properties.as_ref())
+                .map(relations::property_links)
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn modified(&self) -> Option<DateTime<Local>> {
+        local_time(self.modified)
+    }
+
+    pub fn created(&self) -> Option<DateTime<Local>> {
+        Local.timestamp_millis_opt(self.created?).single()
+    }
+}
+
+/// Where the cache for the vault at `vault_path` is kept
+pub fn cache_path(vault_path: &Path) -> PathBuf {
+    state_file::path(vault_path, CACHE_FILE)
+}
+
+impl MetadataCache {
+    /// The cache for the vault, with notes modified since it was saved parsed
+    /// again, saving it if anything changed. A cache that can't be read is
+    /// rebuilt.
+    pub fn current(vault_path: &Path) -> anyhow::Result<Self> {
+        let mut cache = Self::load(vault_path).unwrap_or_default();
+        if cache.refresh(vault_path)? {
+            cache.save(vault_path)?;
+        }
+
+        Ok(cache)
+    }
+
+    fn load(vault_path: &Path) -> Option<Self> {
+        let cache: Self = state_file::load(&cache_path(vault_path)).ok()??;
+
+        (cache.version == CACHE_VERSION).then_some(cache)
+    }
+
+    pub fn save(&self, vault_path: &Path) -> anyhow::Result<()> {
+        state_file::save(&cache_path(vault_path), self, "cache")
+    }
+
+    /// Walk the vault, parsing notes that are new or changed and dropping
+    /// ones that are gone, and return whether anything changed
+    pub fn refresh(&mut self, vault_path: &Path) -> anyhow::Result<bool> {
+        let (folders, files, note_paths) = scan::folders_files_and_notes(vault_path)?;
+        let folders: Vec<String> = folders
+            .iter()
+            .map(|folder| scan::relative_note_path(folder, vault_path))
+            .collect();
+
+        let mut changed = self.version != CACHE_VERSION || folders != self.folders;
+        self.version = CACHE_VERSION;
+        self.folders = folders;
+
+        let mut notes = BTreeMap::new();
+        for note_path in note_paths {
+            let relative = scan::relative_note_path(&note_path, vault_path);
+            let (modified, size) = file_stamp(&note_path).unwrap_or_default();
+
+            let cached = self
+                .notes
+                .remove(&relative)
+                .filter(|note| note.modified == modified && note.size == size);
+            let note = match cached {
+                Some(note) => note,
+                None => {
+                    changed = true;
+                    NoteMetadata::read(&note_path, modified, size)
+                }
+            };
+            notes.insert(relative, note);
+        }
+        changed |= !self.notes.is_empty();
+        self.notes = notes;
+
+        let canvases: BTreeMap<String, u128> = files
+            .iter()
+            .filter(|path| canvas::is_canvas_path(path))
+            .map(|path| {
+                let (modified, _) = file_stamp(path).unwrap_or_default();
+                (scan::relative_note_path(path, vault_path), modified)
+            })
+            .collect();
+        changed |= canvases != self.canvases;
+        self.canvases = canvases;
+
+        Ok(changed)
+    }
+
+    /// Parse one note again, such as after it was edited
+    pub fn update_note(&mut self, vault_path: &Path, note_path: &Path) {
+        let relative = scan::relative_note_path(note_path, vault_path);
+        match file_stamp(note_path) {
+            Some((modified, size)) => {
+                self.notes
+                    .insert(relative, NoteMetadata::read(note_path, modified, size));
+            }
+            None => {
+                self.notes.remove(&relative);
+            }
+        }
+    }
+
+    /// Vault-relative paths of every folder, starting with `""` for the root
+    pub fn folders(&self) -> &[String] {
+        &self.folders
+    }
+
+    /// Every note by vault-relative path, sorted like [`scan::note_paths`]
+    pub fn notes(&self) -> Vec<(&str, &NoteMetadata)> {
+        let mut notes: Vec<(&str, &NoteMetadata)> = self
+            .notes
+            .iter()
+            .map(|(relative, note)| (relative.as_str(), note))
+            .collect();
+        notes.sort_by(|a, b| Path::new(a.0).cmp(Path::new(b.0)));
+        notes
+    }
+
+    /// Every canvas by vault-relative path, with when it was modified
+    pub fn canvases(&self) -> Vec<(&str, Option<DateTime<Local>>)> {
+        self.canvases
+            .iter()
+            .map(|(relative, modified)| (relative.as_str(), local_time(*modified)))
+            .collect()
+    }
+
+    pub fn get(&self, relative: &str) -> Option<&NoteMetadata> {
+        self.notes.get(relative)
+    }
+
+    /// Every tag in the vault with how many notes carry it, most used first
+    /// with ties broken by name
+    pub fn tag_counts(&self) -> Vec<(String, usize)> {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for note in self.notes.values() {
+            for tag in &note.tags {
+                *counts.entry(tag).or_default() += 1;
+            }
+        }
+
+        let mut counts: Vec<(String, usize)> = counts
+            .into_iter()
+            .map(|(tag, count)| (tag.to_string(), count))
+            .collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        counts
+    }
+
+    /// Like [`Self::tag_counts`], but counting for each tag the notes carrying
+    /// it or a tag nested beneath it, with parents like `project` of
+    /// `project/alpha` included even when no note carries them alone
+    pub fn nested_tag_counts(&self) -> Vec<(String, usize)> {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for note in self.notes.values() {
+            let mut tags: HashSet<&str> = HashSet::new();
+            for tag in &note.tags {
+                tags.extend(tag.match_indices('/').map(|(idx, _)| &tag[..idx]));
+                tags.insert(tag);
+            }
+            for tag in tags.into_iter().filter(|tag| !tag.is_empty()) {
+                *counts.entry(tag).or_default() += 1;
+            }
+        }
+
+        let mut counts: Vec<(String, usize)> = counts
+            .into_iter()
+            .map(|(tag, count)| (tag.to_string(), count))
+            .collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        counts
+    }
+
+    /// Tags starting with what's been typed, ignoring case and any `#`, best
+    /// first: those where the whole tag matches, then those where a nested
+    /// part like `project` in `work/project` does, each most used first
+    pub fn complete_tag(&self, typed: &str, matching: &MatchSettings) -> Vec<String> {
+        let typed = matching.fold(typed.trim_start_matches('#'));
+
+        let mut matches: Vec<(bool, String)> = self
+            .tag_counts()
+            .into_iter()
+            .filter_map(|(tag, _)| {
+                let lower = matching.fold(&tag);
+                if lower.starts_with(&typed) {
+                    Some((false, tag))
+                } else if lower
+                    .split('/')
+                    .skip(1)
+                    .any(|part| part.starts_with(&typed))
+                {
+                    Some((true, tag))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        // Stable, so each group stays most used first
+        matches.sort_by_key(|(nested, _)| *nested);
+
+        matches.into_iter().map(|(_, tag)| tag).collect()
+    }
+}
+
+/// A time in milliseconds since the epoch
+fn local_time(millis: u128) -> Option<DateTime<Local>> {
+    let millis = u64::try_from(millis).ok()?;
+    Some(DateTime::<Local>::from(
+        UNIX_EPOCH + Duration::from_millis(millis),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::{prelude::*, TempDir};
+
+    #[test]
+    fn reads_note_metadata() {
+        let dir = TempDir::new().unwrap();
+        dir.child("folder/a.md")
+            .write_str("---\ntags: [one]\ncreated: 2024-03-01\n---\nSome #two words and [[b]]")
+            .unwrap();
+        dir.child("empty").create_dir_all().unwrap();
+
+        let mut cache = MetadataCache::default();
+        assert!(cache.refresh(dir.path()).unwrap());
+
+        assert_eq!(cache.folders(), ["", "empty", "folder"]);
+        let note = cache.get("folder/a.md").unwrap();
+        assert_eq!(note.tags, ["one", "two"]);
+        assert_eq!(note.word_count, 5);
+        assert_eq!(note.links, ["b"]);
+        assert!(note.modified().is_some());
+        assert_eq!(
+            note.created().unwrap().format("%Y-%m-%d").to_string(),
+            "2024-03-01"
+        );
+    }
+
+    #[test]
+    fn lists_canvases() {
+        let dir = TempDir::new().unwrap();
+        dir.child("folder/board.canvas").write_str("{}").unwrap();
+        dir.child("a.md").write_str("note").unwrap();
+
+        let mut cache = MetadataCache::default();
+        cache.refresh(dir.path()).unwrap();
+        assert!(!cache.refresh(dir.path()).unwrap());
+
+        let canvases = cache.canvases();
+        assert_eq!(canvases.len(), 1);
+        assert_eq!(canvases[0].0, "folder/board.canvas");
+        assert!(canvases[0].1.is_some());
+
+        fs::remove_file(dir.child("folder/board.canvas").path()).unwrap();
+        assert!(cache.refresh(dir.path()).unwrap());
+        assert!(cache.canvases().is_empty());
+    }
+
+    #[test]
+    fn only_reparses_changed_notes() {
+        let dir = TempDir::new().unwrap();
+        dir.child("a.md").write_str("#old").unwrap();
+        dir.child("b.md").write_str("gone").unwrap();
+
+        let mut cache = MetadataCache::default();
+        cache.refresh(dir.path()).unwrap();
+        assert!(!cache.refresh(dir.path()).unwrap());
+
+        dir.child("a.md").write_str("#newer").unwrap();
+        fs::remove_file(dir.child("b.md").path()).unwrap();
+
+        assert!(cache.refresh(dir.path()).unwrap());
+        assert_eq!(cache.get("a.md").unwrap().tags, ["newer"]);
+        assert!(cache.get("b.md").is_none());
+    }
+
+    #[test]
+    fn completes_tags_by_use() {
+        let dir = TempDir::new().unwrap();
+        dir.child("a.md")
+            .write_str("#project #work/programming")
+            .unwrap();
+        dir.child("b.md")
+            .write_str("#programming #Project")
+            .unwrap();
+        dir.child("c.md").write_str("#programming").unwrap();
+
+        let mut cache = MetadataCache::default();
+        cache.refresh(dir.path()).unwrap();
+
+        assert_eq!(
+            cache.complete_tag("#pro", &MatchSettings::default()),
+            ["programming", "Project", "project", "work/programming"]
+        );
+        assert_eq!(
+            cache.complete_tag("work", &MatchSettings::default()),
+            ["work/programming"]
+        );
+        assert!(cache
+            .complete_tag("zzz", &MatchSettings::default())
+            .is_empty());
+    }
+}
@@ -0,0 +1,290 @@
+//! Citations from a BibTeX bibliography, like the one Zotero's Better BibTeX
+//! keeps exported. Entries are found by citekey, cited in notes, and given
+//! literature notes named `@citekey`, which `[[@citekey]]` links resolve to
+//! like any other note.
+
+use crate::{cli_config, matching::MatchSettings};
+use anyhow::{anyhow, Context};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+pub const DEFAULT_LITERATURE_FOLDER: &str = "literature";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct CiteSettings {
+    /// The `.bib` file, absolute or relative to the vault
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bibliography: Option<PathBuf>,
+    /// Vault-relative folder literature notes are created in, `literature`
+    /// when unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub literature_folder: Option<PathBuf>,
+    /// The template literature notes are created from, by name within the
+    /// template folder
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub literature_template: Option<String>,
+}
+
+impl CiteSettings {
+    pub fn is_default(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// The entries in the configured bibliography
+    pub fn read_bibliography(&self, vault_path: &Path) -> anyhow::Result<Vec<Entry>> {
+        let path = self.bibliography.as_ref().ok_or_else(|| {
+            anyhow!("No bibliography configured, set `cite.bibliography` in your config")
+        })?;
+        let path = vault_path.join(path);
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("Could not read bibliography {}", path.display()))?;
+
+        Ok(parse_bibtex(&contents))
+    }
+
+    /// Where the literature note for `key` goes
+    pub fn literature_note_path(&self, vault_path: &Path, key: &str) -> PathBuf {
+        let folder = self
+            .literature_folder
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_LITERATURE_FOLDER));
+        vault_path.join(folder).join(format!("@{key}.md"))
+    }
+}
+
+/// The config's settings
+pub fn settings() -> anyhow::Result<CiteSettings> {
+    Ok(cli_config::read()?.cite)
+}
+
+/// A reference in the bibliography
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// Like `article` or `book`, in lowercase
+    pub kind: String,
+    pub key: String,
+    /// By lowercase field name, with braces removed and whitespace collapsed
+    pub fields: BTreeMap<String, String>,
+}
+
+impl Entry {
+    pub fn field(&self, name: &str) -> &str {
+        self.fields.get(name).map_or("", String::as_str)
+    }
+
+    /// The authors' surnames, or the editors' when there are no authors
+    pub fn surnames(&self) -> Vec<String> {
+        let names = match self.field("author") {
+            "" => self.field("editor"),
+            authors => authors,
+        };
+        names
+            .split(" and ")
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| match name.split_once(',') {
+                Some((surname, _)) => surname.trim().to_string(),
+                None => name.rsplit(' ').next().unwrap_or(name).to_string(),
+            })
+            .collect()
+    }
+
+    /// The authors as cited: `Kernighan`, `Kernighan & Ritchie` or
+    /// `Kernighan et al.`
+    pub fn cited_authors(&self) -> String {
+        match self.surnames().as_slice() {
+            [] => String::new(),
+            [one] => one.clone(),
+            [first, second] => format!("{first} & {second}"),
+            [first, ..] => format!("{first} et al."),
+        }
+    }
+
+    /// A citation for the end of a note, like
+    /// `Kernighan & Ritchie (1988). *The C Programming Language*. Prentice Hall.`
+    pub fn citation(&self) -> String {
+        let mut citation = match (self.cited_authors().as_str(), self.field("year")) {
+            ("", "") => String::new(),
+            (authors, "") => format!("{authors}. "),
+            ("", year) => format!("({year}). "),
+            (authors, year) => format!("{authors} ({year}). "),
+        };
+        if !self.field("title").is_empty() {
+            citation.push_str(&format!("*{}*. ", self.field("title")));
+        }
+        let source = ["journal", "booktitle", "publisher", "howpublished"]
+            .iter()
+            .map(|name| self.field(name))
+            .find(|value| !value.is_empty());
+        if let Some(source) = source {
+            citation.push_str(&format!("{source}. "));
+        }
+        citation.trim_end().to_string()
+    }
+
+    /// Whether every word of `query` is in the key, authors or title,
+    /// ignoring case
+    pub fn matches(&self, query: &str, matching: &MatchSettings) -> bool {
+        let haystack = matching.fold(&format!(
+            "{} {} {} {}",
+            self.key,
+            self.field("author"),
+            self.field("editor"),
+            self.field("title")
+        ));
+        query
+            .split_whitespace()
+            .all(|word| haystack.contains(&matching.fold(word)))
+    }
+}
+
+/// The entries in a BibTeX file. `@string`, `@preamble` and `@comment`
+/// blocks are skipped, as are entries that can't be parsed.
+pub fn parse_bibtex(contents: &str) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    let mut rest = contents;
+
+    while let Some(at) = rest.find('@') {
+        rest = &rest[at + 1..];
+        let Some(open) = rest.find(['{', '(']) else {
+            break;
+        };
+        let kind = rest[..open].trim().to_lowercase();
+        let Some(body_len) = matching_close(&rest[open..]) else {
+            break;
+        };
+        let body = &rest[open + 1..open + body_len];
+        rest = &rest[open + body_len..];
+
+        if matches!(kind.as_str(), "string" | "preamble" | "comment") || kind.is_empty() {
+            continue;
+        }
+        if let Some(entry) = parse_entry(kind, body) {
+            entries.push(entry);
+        }
+    }
+
+    entries
+}
+
+/// The length of a `{…}` or `(…)` group at the start of `text`, up to its
+/// closing bracket
+fn matching_close(text: &str) -> Option<usize> {
+    let (open, close) = if text.starts_with('(') {
+        ('(', ')')
+    } else {
+        ('{', '}')
+    };
+    let mut depth = 0;
+    for (idx, c) in text.char_indices() {
+        if c == open {
+            depth += 1;
+        } else if c == close {
+            depth -= 1;
+            if depth == 0 {
+                return Some(idx);
+            }
+        }
+    }
+    None
+}
+
+fn parse_entry(kind: String, body: &str) -> Option<Entry> {
+    let (key, mut rest) = body.split_once(',').unwrap_or((body, ""));
+    let key = key.trim().to_string();
+    if key.is_empty() {
+        return None;
+    }
+
+    let mut fields = BTreeMap::new();
+    while let Some((name, after)) = rest.split_once('=') {
+        let name = name.trim().trim_start_matches(',').trim().to_lowercase();
+        let after = after.trim_start();
+        let (value, remaining) = match after.chars().next() {
+            Some('{') => {
+                let len = matching_close(after)?;
+                (&after[1..len], &after[len + 1..])
+            }
+            Some('"') => {
+                let len = after[1..].find('"')? + 1;
+                (&after[1..len], &after[len + 1..])
+            }
+            _ => after.split_once(',').unwrap_or((after, "")),
+        };
+        let value = value
+            .replace(['{', '}'], "")
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        fields.insert(name, value);
+        rest = remaining;
+    }
+
+    Some(Entry { kind, key, fields })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    const BIBLIOGRAPHY: &str = indoc! {r#"
+        @comment{Exported by Zotero}
+        @string{ph = "Prentice Hall"}
+
+        @book{kernighan1988,
+          author    = {Kernighan, Brian W. and Ritchie, Dennis M.},
+          title     = {The {C} Programming
+                       Language},
+          publisher = "Prentice Hall",
+          year      = 1988,
+        }
+
+        @Article{lamport1978,
+          author = {Leslie Lamport},
+          title = {Time, Clocks, and the Ordering of Events in a Distributed System},
+          journal = {Communications of the ACM},
+          year = {1978}
+        }
+    "#};
+
+    #[test]
+    fn parses_entries() {
+        let entries = parse_bibtex(BIBLIOGRAPHY);
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].kind, "book");
+        assert_eq!(entries[0].key, "kernighan1988");
+        assert_eq!(entries[0].field("title"), "The C Programming Language");
+        assert_eq!(entries[0].field("year"), "1988");
+        assert_eq!(entries[1].kind, "article");
+        assert_eq!(entries[1].surnames(), ["Lamport"]);
+    }
+
+    #[test]
+    fn formats_citations() {
+        let entries = parse_bibtex(BIBLIOGRAPHY);
+
+        assert_eq!(
+            entries[0].citation(),
+            "Kernighan & Ritchie (1988). *The C Programming Language*. Prentice Hall."
+        );
+        assert_eq!(
+            entries[1].citation(),
+            "Lamport (1978). *Time, Clocks, and the Ordering of Events in a Distributed System*. Communications of the ACM."
+        );
+    }
+
+    #[test]
+    fn matches_every_word() {
+        let entries = parse_bibtex(BIBLIOGRAPHY);
+
+        assert!(entries[0].matches("kernighan programming", &MatchSettings::default()));
+        assert!(!entries[0].matches("kernighan lamport", &MatchSettings::default()));
+    }
+}
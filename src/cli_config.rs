@@ -1,28 +1,506 @@
-use crate::{app_settings, theme::ThemeName};
-use anyhow::{bail, Context};
+use crate::{
+    ambiguity::AmbiguitySettings,
+    app_settings,
+    cite::CiteSettings,
+    dates::DateFormats,
+    fuzzy::FuzzyScoring,
+    hooks::Hooks,
+    matching::MatchSettings,
+    periodic::PeriodicSettings,
+    relations::RelationSettings,
+    scan::ScanLimits,
+    secrets,
+    theme::{self, default_theme_name, CustomTheme, Theme},
+    zettel::ZettelSettings,
+};
+use anyhow::{anyhow, bail, Context};
 use etcetera::BaseStrategy;
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::BTreeMap,
     env::{self, VarError},
     fs,
     path::{Path, PathBuf},
     sync::OnceLock,
 };
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct Vault {
     pub name: String,
     pub path: PathBuf,
+    /// Used instead of the top-level `editor` while this is the current vault
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub editor: Option<String>,
+    /// Used instead of the top-level `theme` while this is the current vault
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub theme: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Config {
+    /// The schema version the file was written with, see [`MIGRATIONS`]
+    #[serde(default = "current_version")]
+    pub version: u32,
     pub current_vault: String,
+    /// Used when `current_vault` is empty
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_vault: Option<String>,
     pub vaults: Vec<Vault>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub editor: Option<String>,
-    #[serde(default)]
-    pub theme: ThemeName,
+    /// A built-in theme or one defined under `themes`
+    #[serde(default = "default_theme_name")]
+    pub theme: String,
+    /// User-defined themes, by name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub themes: BTreeMap<String, CustomTheme>,
+    /// Vault-relative folder that attachments are copied into
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attachment_folder: Option<PathBuf>,
+    /// Command used by `obx export excalidraw`, e.g.
+    /// `excalidraw_export --format {format} {input} -o {output}`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub excalidraw_converter: Option<String>,
+    /// Path to the pandoc executable used by `obx export pdf`, found on
+    /// `$PATH` when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pandoc: Option<PathBuf>,
+    /// Vault-relative folder holding note templates
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template_folder: Option<PathBuf>,
+    /// Paths left out of every vault, in gitignore syntax, on top of each
+    /// vault's own `.obxignore`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore: Vec<String>,
+    /// Size, binary and extension guards on which files are scanned
+    #[serde(default, skip_serializing_if = "ScanLimits::is_default")]
+    pub scan: ScanLimits,
+    /// Date and time formats, and the locale for month and day names
+    #[serde(default, skip_serializing_if = "DateFormats::is_default")]
+    pub dates: DateFormats,
+    /// How fuzzy note matches are ranked
+    #[serde(default, skip_serializing_if = "FuzzyScoring::is_default")]
+    pub fuzzy: FuzzyScoring,
+    /// How text typed into searches, note names and links is matched
+    #[serde(default, skip_serializing_if = "MatchSettings::is_default")]
+    pub matching: MatchSettings,
+    /// How Zettelkasten IDs are written at the start of note names
+    #[serde(default, skip_serializing_if = "ZettelSettings::is_default")]
+    pub zettel: ZettelSettings,
+    /// The bibliography `obx cite` reads and where literature notes go
+    #[serde(default, skip_serializing_if = "CiteSettings::is_default")]
+    pub cite: CiteSettings,
+    /// Where `obx daily` puts daily and weekly notes and how it starts them
+    #[serde(default, skip_serializing_if = "PeriodicSettings::is_default")]
+    pub periodic: PeriodicSettings,
+    /// Which note a name several notes share means
+    #[serde(default, skip_serializing_if = "AmbiguitySettings::is_default")]
+    pub ambiguous_links: AmbiguitySettings,
+    /// Which frontmatter properties link notes to their parents and to
+    /// related notes, for `obx tree`
+    #[serde(default, skip_serializing_if = "RelationSettings::is_default")]
+    pub relations: RelationSettings,
+    /// Tokens for integrations, by name. Each is a reference into the OS
+    /// keychain or an encrypted value, see [`secrets`]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub secrets: BTreeMap<String, String>,
+    /// Commands run around note operations, see [`Hooks`]
+    #[serde(default, skip_serializing_if = "Hooks::is_empty")]
+    pub hooks: Hooks,
+    /// Additional config files merged underneath this one, in order. Later
+    /// includes override earlier ones, and keys set in `config.yml` itself
+    /// override every include.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<PathBuf>,
+    /// Named sets of overrides, selected with `--profile` or `OBX_PROFILE`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Profile>,
+    /// The profile applied by [`read`], if any
+    #[serde(skip)]
+    pub active_profile: Option<String>,
+    /// Values [`read`] took from the environment or the current vault, which
+    /// [`write`] leaves out of the file
+    #[serde(skip)]
+    overrides: Vec<Override>,
+}
+
+/// The settings that can be overridden per vault or from the environment
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Setting {
+    CurrentVault,
+    Editor,
+    Theme,
+}
+
+#[derive(Debug, Clone)]
+struct Override {
+    setting: Setting,
+    applied: Option<String>,
+    previous: Option<String>,
+}
+
+/// Settings a profile can override. Unset fields fall back to the top-level
+/// config.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Profile {
+    /// Used instead of `current_vault`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vault: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub editor: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub theme: Option<String>,
+}
+
+/// Each profile field alongside the top-level key it overrides
+const PROFILE_KEYS: [(&str, &str); 3] = [
+    ("vault", "current_vault"),
+    ("editor", "editor"),
+    ("theme", "theme"),
+];
+
+impl Config {
+    fn get(&self, setting: Setting) -> Option<String> {
+        match setting {
+            Setting::CurrentVault => Some(self.current_vault.clone()),
+            Setting::Editor => self.editor.clone(),
+            Setting::Theme => Some(self.theme.clone()),
+        }
+    }
+
+    fn set(&mut self, setting: Setting, value: Option<String>) {
+        match setting {
+            Setting::CurrentVault => self.current_vault = value.unwrap_or_default(),
+            Setting::Editor => self.editor = value,
+            Setting::Theme => self.theme = value.unwrap_or_else(default_theme_name),
+        }
+    }
+
+    fn override_setting(&mut self, setting: Setting, value: String) {
+        let previous = self.get(setting);
+        self.set(setting, Some(value.clone()));
+        self.overrides.push(Override {
+            setting,
+            applied: Some(value),
+            previous,
+        });
+    }
+
+    /// Layer the vault picked from the environment, the current vault's own
+    /// settings, and then `OBX_EDITOR`/`OBX_THEME` over the config file.
+    /// `OBX_VAULT` (or `OBSIDIAN_VAULT`) picks the vault first, then the
+    /// registered vault the working directory is in, unless the active
+    /// profile names one, and `default_vault` fills in an empty
+    /// `current_vault`.
+    fn apply_overrides(&mut self) {
+        let profile_vault = self
+            .active_profile
+            .as_ref()
+            .and_then(|name| self.profiles.get(name))
+            .is_some_and(|profile| profile.vault.is_some());
+
+        let vault = env_setting("OBX_VAULT")
+            .or_else(|| env_setting("OBSIDIAN_VAULT"))
+            .or_else(|| match profile_vault {
+                true => None,
+                false => self.vault_containing_cwd(),
+            })
+            .or_else(|| match self.current_vault.is_empty() {
+                true => self.default_vault.clone(),
+                false => None,
+            });
+        if let Some(vault) = vault {
+            self.override_setting(Setting::CurrentVault, vault);
+        }
+
+        let current = self
+            .vaults
+            .iter()
+            .find(|v| v.name == self.current_vault)
+            .cloned()
+            .unwrap_or_default();
+
+        if let Some(editor) = env_setting("OBX_EDITOR").or(current.editor) {
+            self.override_setting(Setting::Editor, editor);
+        }
+        if let Some(theme) = env_setting("OBX_THEME").or(current.theme) {
+            self.override_setting(Setting::Theme, theme);
+        }
+    }
+
+    /// The registered vault the working directory is inside, the innermost
+    /// one if vaults are nested
+    fn vault_containing_cwd(&self) -> Option<String> {
+        let cwd = env::current_dir().ok()?.canonicalize().ok()?;
+
+        self.vaults
+            .iter()
+            .filter_map(|vault| Some((vault.path.canonicalize().ok()?, &vault.name)))
+            .filter(|(path, _)| cwd.starts_with(path))
+            .max_by_key(|(path, _)| path.components().count())
+            .map(|(_, name)| name.clone())
+    }
+
+    /// The config as it should be written: overridden settings that haven't
+    /// been changed since go back to their values from the file
+    fn without_overrides(&self) -> Config {
+        let mut config = self.clone();
+        for o in self.overrides.iter().rev() {
+            if config.get(o.setting) == o.applied {
+                config.set(o.setting, o.previous.clone());
+            }
+        }
+        config
+    }
+
+    /// The value at a dotted key like `vaults.work.path`, where list entries
+    /// are picked by their `name` or their index
+    pub fn get_key(&self, key: &str) -> anyhow::Result<serde_yaml::Value> {
+        let values = serde_yaml::to_value(self)?;
+        lookup(&values, key)
+            .cloned()
+            .ok_or_else(|| anyhow!("Config key `{key}` isn't set"))
+    }
+
+    /// A copy of the config with a dotted key set, or removed when `value` is
+    /// `None`. Values are read as YAML, falling back to a plain string when
+    /// that doesn't fit the key's type.
+    pub fn with_key(&self, key: &str, value: Option<&str>) -> anyhow::Result<Config> {
+        let Some(value) = value else {
+            return self.with_value(key, None);
+        };
+
+        let parsed = serde_yaml::from_str(value)
+            .unwrap_or_else(|_| serde_yaml::Value::String(value.to_string()));
+        match self.with_value(key, Some(parsed.clone())) {
+            Err(err) if !parsed.is_string() => self
+                .with_value(key, Some(serde_yaml::Value::String(value.to_string())))
+                .map_err(|_| err),
+            result => result,
+        }
+    }
+
+    fn with_value(&self, key: &str, value: Option<serde_yaml::Value>) -> anyhow::Result<Config> {
+        let (parent_key, last) = match key.rsplit_once('.') {
+            Some((parent_key, last)) => (Some(parent_key), last),
+            None => (None, key),
+        };
+
+        let mut values = serde_yaml::to_value(self)?;
+        let parent = match parent_key {
+            Some(parent_key) => lookup_mut(&mut values, parent_key, value.is_some())
+                .ok_or_else(|| anyhow!("Config key `{parent_key}` isn't set"))?,
+            None => &mut values,
+        };
+
+        let unsetting = value.is_none();
+        match (parent, value) {
+            (serde_yaml::Value::Mapping(map), Some(value)) => {
+                map.insert(last.into(), value);
+            }
+            (serde_yaml::Value::Mapping(map), None) => {
+                map.remove(last)
+                    .ok_or_else(|| anyhow!("Config key `{key}` isn't set"))?;
+            }
+            (serde_yaml::Value::Sequence(items), value) => {
+                let idx = entry_index(items, last)
+                    .ok_or_else(|| anyhow!("Config key `{key}` isn't set"))?;
+                match value {
+                    Some(value) => items[idx] = value,
+                    None => {
+                        items.remove(idx);
+                    }
+                }
+            }
+            _ => bail!("Config key `{key}` is inside a value that can't have keys"),
+        }
+
+        let mut config: Config = serde_yaml::from_value(values).map_err(|err| match unsetting {
+            true => anyhow!("Can't unset `{key}`: {err}"),
+            false => anyhow!("Invalid value for `{key}`: {err}"),
+        })?;
+        config.active_profile = self.active_profile.clone();
+        config.overrides = self.overrides.clone();
+
+        // Keys the config doesn't have are dropped when it's deserialized. The
+        // parent can go missing too when the key renames a list entry.
+        let written = serde_yaml::to_value(&config)?;
+        let parent_written = parent_key.map_or(Some(&written), |key| lookup(&written, key));
+        if !unsetting && parent_written.is_some_and(|parent| lookup(parent, last).is_none()) {
+            bail!("Unknown config key `{key}`");
+        }
+
+        Ok(config)
+    }
+
+    /// The colours of the configured theme
+    pub fn resolve_theme(&self) -> anyhow::Result<Theme> {
+        theme::resolve(&self.theme, &self.themes)
+    }
+
+    /// The value of the secret called `name`, fetched from wherever it's
+    /// stored
+    pub fn secret(&self, name: &str) -> anyhow::Result<String> {
+        let reference = self.secrets.get(name).with_context(|| {
+            format!("No secret named `{name}`, add it with `obx config secret set {name}`")
+        })?;
+
+        secrets::resolve(reference)
+    }
+
+    fn apply_profile(&mut self, name: &str) -> anyhow::Result<()> {
+        let Some(profile) = self.profiles.get(name).cloned() else {
+            bail!("Profile `{name}` not found in config");
+        };
+
+        if let Some(vault) = profile.vault {
+            self.current_vault = vault;
+        }
+        if let Some(editor) = profile.editor {
+            self.editor = Some(editor);
+        }
+        if let Some(theme) = profile.theme {
+            self.theme = theme;
+        }
+
+        self.active_profile = Some(name.to_string());
+        Ok(())
+    }
+}
+
+fn lookup<'a>(value: &'a serde_yaml::Value, key: &str) -> Option<&'a serde_yaml::Value> {
+    key.split('.').try_fold(value, |value, part| match value {
+        serde_yaml::Value::Mapping(map) => map.get(part),
+        serde_yaml::Value::Sequence(items) => entry_index(items, part).map(|idx| &items[idx]),
+        _ => None,
+    })
+}
+
+/// Like [`lookup`], creating missing mappings along the way when `create`
+/// is set
+fn lookup_mut<'a>(
+    value: &'a mut serde_yaml::Value,
+    key: &str,
+    create: bool,
+) -> Option<&'a mut serde_yaml::Value> {
+    let mut value = value;
+    for part in key.split('.') {
+        value = match value {
+            serde_yaml::Value::Mapping(map) => {
+                if create && !map.contains_key(part) {
+                    map.insert(part.into(), serde_yaml::Value::Mapping(Default::default()));
+                }
+                map.get_mut(part)?
+            }
+            serde_yaml::Value::Sequence(items) => {
+                let idx = entry_index(items, part)?;
+                &mut items[idx]
+            }
+            _ => return None,
+        };
+    }
+
+    Some(value)
+}
+
+/// The list entry named `part`, or at index `part`
+fn entry_index(items: &[serde_yaml::Value], part: &str) -> Option<usize> {
+    items
+        .iter()
+        .position(|item| item.get("name").and_then(serde_yaml::Value::as_str) == Some(part))
+        .or_else(|| part.parse().ok().filter(|idx| *idx < items.len()))
+}
+
+/// Upgrades the raw config from one version to the next, where the first
+/// entry upgrades version 1. Add one whenever a key is renamed or reshaped.
+type Migration = fn(&mut serde_yaml::Mapping);
+
+const MIGRATIONS: &[Migration] = &[];
+
+/// Files without a `version` predate versioning and are version 1
+const CONFIG_VERSION: u32 = MIGRATIONS.len() as u32 + 1;
+
+fn current_version() -> u32 {
+    CONFIG_VERSION
+}
+
+/// Apply the migrations `raw` hasn't had yet, returning the version it was
+/// upgraded from when any were needed
+fn migrate(raw: &mut serde_yaml::Value, migrations: &[Migration]) -> anyhow::Result<Option<u32>> {
+    let latest = migrations.len() as u32 + 1;
+    let Some(values) = raw.as_mapping_mut() else {
+        bail!("config should be a mapping");
+    };
+
+    let version = match values.get("version") {
+        None => 1,
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .filter(|version| *version >= 1)
+            .ok_or_else(|| anyhow!("Config `version` should be a positive number"))?,
+    };
+
+    if version > latest {
+        bail!(
+            "Config file {} is version {version}, but this obx only understands up to version {latest}, upgrade obx to use it",
+            get_config_path().display()
+        );
+    }
+    if version == latest {
+        return Ok(None);
+    }
+
+    for migration in &migrations[version as usize - 1..] {
+        migration(values);
+    }
+    values.insert("version".into(), latest.into());
+
+    Ok(Some(version))
+}
+
+/// Upgrade an older config file in place, keeping the original alongside it
+/// as e.g. `config.yml.v<version>.bak`. Included files are left as they are.
+fn migrate_config_file() -> anyhow::Result<()> {
+    let config_path = get_config_path();
+    if !config_path.is_file() {
+        return Ok(());
+    }
+
+    let mut raw = read_raw()?;
+    let Some(version) = migrate(&mut raw, MIGRATIONS)? else {
+        return Ok(());
+    };
+
+    let format = ConfigFormat::of(&config_path);
+    let extension = config_path
+        .extension()
+        .unwrap_or_default()
+        .to_string_lossy();
+    let backup_path = config_path.with_extension(format!("{extension}.v{version}.bak"));
+    fs::copy(&config_path, &backup_path)
+        .with_context(|| format!("failed to back up config file to {}", backup_path.display()))?;
+    fs::write(&config_path, format.serialize(&raw)?)
+        .with_context(|| format!("failed to write to config file {}", config_path.display()))
+}
+
+/// The profile chosen with `--profile` or `OBX_PROFILE`
+fn selected_profile() -> Option<String> {
+    env_setting("OBX_PROFILE")
+}
+
+/// An environment variable, ignoring it when it's empty
+fn env_setting(key: &str) -> Option<String> {
+    env::var(key).ok().filter(|value| !value.trim().is_empty())
+}
+
+/// The name a vault gets by default, its folder's name
+pub fn vault_name(vault_path: &Path) -> String {
+    vault_path
+        .components()
+        .next_back()
+        .map(|component| component.as_os_str().to_string_lossy().into_owned())
+        .unwrap_or_default()
 }
 
 fn get_config_dir() -> &'static PathBuf {
@@ -39,28 +517,373 @@ fn get_config_dir() -> &'static PathBuf {
     })
 }
 
+/// Where obx keeps data it can rebuild, like search indexes.
+/// `OBX_STATE_DIR` overrides the platform's location.
+pub fn get_state_dir() -> &'static PathBuf {
+    static STATE_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+    STATE_DIR.get_or_init(|| match env::var("OBX_STATE_DIR") {
+        Ok(dir) => PathBuf::from(dir),
+        Err(VarError::NotPresent) => {
+            let strategy =
+                etcetera::choose_base_strategy().expect("etcetera base strategy should work");
+            strategy
+                .state_dir()
+                .unwrap_or_else(|| strategy.data_dir())
+                .join("obx")
+        }
+        _ => panic!("Malformed OBX_STATE_DIR"),
+    })
+}
+
+/// The state directory for one vault, named after its folder plus a hash of
+/// its full path so vaults with the same folder name don't collide
+pub fn vault_state_dir(vault_path: &Path) -> PathBuf {
+    // FNV-1a, which unlike `DefaultHasher` is stable between Rust versions
+    let hash = vault_path
+        .to_string_lossy()
+        .trim_end_matches(['/', '\\'])
+        .bytes()
+        .fold(0xcbf29ce484222325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+        });
+
+    get_state_dir()
+        .join("vaults")
+        .join(format!("{}-{:08x}", vault_name(vault_path), hash as u32))
+}
+
+/// The formats the config can be written in, told apart by file extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Yaml,
+    Toml,
+}
+
+impl ConfigFormat {
+    pub fn of(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => ConfigFormat::Toml,
+            _ => ConfigFormat::Yaml,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ConfigFormat::Yaml => "YAML",
+            ConfigFormat::Toml => "TOML",
+        }
+    }
+
+    pub fn file_name(self) -> &'static str {
+        match self {
+            ConfigFormat::Yaml => "config.yml",
+            ConfigFormat::Toml => "config.toml",
+        }
+    }
+
+    fn deserialize<T: serde::de::DeserializeOwned>(self, contents: &str) -> Result<T, String> {
+        match self {
+            ConfigFormat::Yaml => serde_yaml::from_str(contents).map_err(|err| err.to_string()),
+            ConfigFormat::Toml => toml::from_str(contents).map_err(|err| err.to_string()),
+        }
+    }
+
+    fn parse(self, contents: &str) -> Result<serde_yaml::Value, String> {
+        Ok(match self.deserialize(contents)? {
+            serde_yaml::Value::Null => serde_yaml::Value::Mapping(Default::default()),
+            value => value,
+        })
+    }
+
+    pub fn serialize(self, values: &serde_yaml::Value) -> anyhow::Result<String> {
+        Ok(match self {
+            ConfigFormat::Yaml => serde_yaml::to_string(values)?,
+            ConfigFormat::Toml => toml::to_string(values)?,
+        })
+    }
+}
+
+/// `config.yml`, or `config.toml` when that's the only one there
 pub fn get_config_path() -> PathBuf {
     let config_dir = get_config_dir();
-    config_dir.join("config.yml")
+    let yaml_path = config_dir.join(ConfigFormat::Yaml.file_name());
+    let toml_path = config_dir.join(ConfigFormat::Toml.file_name());
+
+    if !yaml_path.exists() && toml_path.exists() {
+        toml_path
+    } else {
+        yaml_path
+    }
 }
 
 fn get_config() -> anyhow::Result<config::Config> {
     let config_path = get_config_path();
 
-    let settings = config::Config::builder()
+    let mut builder = config::Config::builder();
+    for include_path in include_paths(&read_raw()?) {
+        if !include_path.is_file() {
+            bail!(
+                "failed to read included config file {}",
+                include_path.display()
+            );
+        }
+        builder = builder.add_source(config::File::from(include_path));
+    }
+
+    let settings = builder
         .add_source(config::File::from(config_path))
         .build()?;
 
     Ok(settings)
 }
 
+/// Read the config file on its own, without merging any included files
+pub fn read_raw() -> anyhow::Result<serde_yaml::Value> {
+    let config_path = get_config_path();
+    let contents = fs::read_to_string(&config_path)
+        .with_context(|| format!("failed to read config file {}", config_path.display()))?;
+
+    ConfigFormat::of(&config_path)
+        .parse(&contents)
+        .map_err(|err| {
+            anyhow!(
+                "failed to parse config file {}: {err}",
+                config_path.display()
+            )
+        })
+}
+
+/// Resolve the `include:` entries of a raw config
+fn include_paths(raw: &serde_yaml::Value) -> Vec<PathBuf> {
+    let Some(serde_yaml::Value::Sequence(includes)) = raw.get("include") else {
+        return Vec::new();
+    };
+
+    includes
+        .iter()
+        .filter_map(|include| include.as_str())
+        .map(|include| config_relative_path(Path::new(include)))
+        .collect()
+}
+
+/// Resolve a path given in the config, relative to the config directory and
+/// with a leading `~` expanded to the home directory
+pub fn config_relative_path(path: &Path) -> PathBuf {
+    match path.strip_prefix("~") {
+        Ok(rest) => etcetera::home_dir()
+            .map(|home| home.join(rest))
+            .unwrap_or_else(|_| path.to_path_buf()),
+        Err(_) => get_config_dir().join(path),
+    }
+}
+
+/// Merge every included file into a single value, later includes winning
+fn read_includes(raw: &serde_yaml::Value) -> anyhow::Result<serde_yaml::Value> {
+    let mut merged = serde_yaml::Value::Mapping(Default::default());
+
+    for include_path in include_paths(raw) {
+        let contents = fs::read_to_string(&include_path).with_context(|| {
+            format!(
+                "failed to read included config file {}",
+                include_path.display()
+            )
+        })?;
+        let value = ConfigFormat::of(&include_path)
+            .parse(&contents)
+            .map_err(|err| {
+                anyhow!(
+                    "failed to parse included config file {}: {err}",
+                    include_path.display()
+                )
+            })?;
+        merge_yaml(&mut merged, value);
+    }
+
+    Ok(merged)
+}
+
+fn merge_yaml(base: &mut serde_yaml::Value, overlay: serde_yaml::Value) {
+    match (base, overlay) {
+        (serde_yaml::Value::Mapping(base_map), serde_yaml::Value::Mapping(overlay_map)) => {
+            for (key, value) in overlay_map {
+                match base_map.get_mut(&key) {
+                    Some(existing) => merge_yaml(existing, value),
+                    None => {
+                        base_map.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
 pub fn read() -> anyhow::Result<Config> {
-    let config = get_config()?
+    migrate_config_file()?;
+
+    let mut config = get_config()?
         .try_deserialize::<Config>()
         .context("failed to deserialize config")?;
+
+    if let Some(profile) = selected_profile() {
+        config.apply_profile(&profile)?;
+    }
+    config.apply_overrides();
+
     Ok(config)
 }
 
+/// Written by `obx config edit` when there's no config file yet
+const DEFAULT_CONFIG: &str = r#"# obx configuration. `obx config print --resolved` shows the effective values.
+
+# The format of this file, upgraded automatically by obx
+version: 1
+
+# The vault commands act on unless --vault is given, or you're inside another
+# registered vault. `default_vault` is used while this is empty
+current_vault: ""
+# default_vault: notes
+
+# Vaults obx knows about, e.g.
+#   - name: notes
+#     path: /home/me/notes
+# `obx vaults add` and `obx vaults discover` fill this in for you
+vaults: []
+
+# Command used to open notes, $EDITOR when unset. {file}, {line} and {vault}
+# are filled in, and the note is passed last without {file}
+# editor: nvim +{line} {file}
+
+# Colours for `obx browse`: obsidian-dark, obsidian-light, solarized-dark,
+# solarized-light, gruvbox-dark, gruvbox-light, terminal, or one of `themes`
+# theme: obsidian-dark
+
+# Vault-relative folders for attachments and note templates
+# attachment_folder: attachments
+# template_folder: templates
+
+# Paths to leave out of every vault, like a .obxignore file in each one
+# ignore:
+#   - Archive/
+#   - "*.excalidraw.md"
+
+# Files to leave out of listings, search and indexing by what they are
+# scan:
+#   max_file_size: 5MB      # notes larger than this aren't read
+#   skip_binary: true       # nor are notes that look binary
+#   exclude_extensions: [pdf, zip]
+
+# Date and time formats, using Moment.js tokens like Obsidian
+# dates:
+#   timestamp: YYYY-MM-DD HH:mm    # modified times in `obx list` and the TUI
+#   date: YYYY-MM-DD               # {{date}} in templates
+#   time: HH:mm                    # {{time}} in templates
+#   daily_note: YYYY-MM-DD         # daily note names
+#   weekly_note: YYYY-[W]ww        # weekly note names
+#   first_day_of_week: monday
+#   locale: fr_FR                  # month and day names
+
+# How fuzzy matches of note names rank, for `obx open`, `obx search --fuzzy`
+# and the TUI's quick switcher
+# fuzzy:
+#   title_boost: 1.0     # weight of a match in the note's name
+#   recency_boost: 0.25  # extra weight for a note modified today
+#   recency_days: 14     # how long the recency boost takes to fade
+
+# How searches, note names and links match text. Accents are always matched
+# however they're encoded, and with ignore_diacritics `cafe` finds `café`.
+# Searches ignore case unless what's typed has a capital letter (smart), or
+# case can be sensitive or insensitive throughout
+# matching:
+#   ignore_diacritics: true
+#   case: smart
+
+# Zettelkasten IDs added by `obx notes create --zettel`, in Moment.js tokens.
+# Notes can be opened and linked to by their ID alone
+# zettel:
+#   id_format: YYYYMMDDHHmm
+#   separator: " "
+
+# The BibTeX file `obx cite` reads, absolute or relative to the vault, and
+# where literature notes named @citekey are created, from which template
+# cite:
+#   bibliography: references.bib
+#   literature_folder: literature
+#   literature_template: literature
+
+# Where `obx daily` creates daily and weekly notes, from which templates, and
+# whether they start with links to the notes before and after them
+# periodic:
+#   folder: journal
+#   daily_template: daily
+#   weekly_template: weekly
+#   link_neighbors: true
+
+# Which note a name several notes share means, in links and in `obx open`:
+# `shortest` path like Obsidian, the first of `folders` holding one, or `ask`
+# when opening by name, links then resolving to the shortest path
+# ambiguous_links:
+#   policy: folders
+#   folders: [Projects, Areas]
+
+# The frontmatter properties linking notes to their parents, like
+# `up: "[[Projects]]"`, and to related notes, shown by `obx tree`
+# relations:
+#   parent_keys: [up, parent]
+#   related_keys: [related]
+
+# Tokens for integrations, set with `obx config secret set <name>` so they're
+# kept in the OS keychain or encrypted rather than written here
+# secrets:
+#   github-token: keyring://obx/github-token
+
+# Commands to run around note operations, from the vault's folder with the
+# note in $OBX_NOTE_PATH and the vault in $OBX_VAULT and $OBX_VAULT_PATH
+# hooks:
+#   after_create: git add .
+#   after_move: ./scripts/update-index.sh
+#   before_delete: ./scripts/check-unpublished.sh
+#   after_daily: ./scripts/add-agenda.sh
+
+# Other config files to merge in, relative to this one
+# include:
+#   - shared.yml
+"#;
+
+/// Create the config file with commented defaults, unless it exists already
+pub fn create_default() -> anyhow::Result<PathBuf> {
+    let config_path = get_config_path();
+    if config_path.exists() {
+        return Ok(config_path);
+    }
+
+    fs::create_dir_all(get_config_dir())?;
+    fs::write(&config_path, DEFAULT_CONFIG)
+        .with_context(|| format!("failed to create config file at {}", config_path.display()))?;
+
+    Ok(config_path)
+}
+
+/// Check that the config file parses and has the expected shape. Errors
+/// point at a line in the config file unless they come from an included
+/// file. The config is returned as written, without environment overrides.
+pub fn validate() -> anyhow::Result<Config> {
+    let config_path = get_config_path();
+    let format = ConfigFormat::of(&config_path);
+    let contents = fs::read_to_string(&config_path)
+        .with_context(|| format!("failed to read config file {}", config_path.display()))?;
+
+    let invalid = |err: String| anyhow!("Config file {} is invalid: {err}", config_path.display());
+
+    let raw = format.parse(&contents).map_err(invalid)?;
+    if raw.get("include").is_none() {
+        format.deserialize::<Config>(&contents).map_err(invalid)?;
+    }
+
+    Ok(read()?.without_overrides())
+}
+
 pub fn exists() -> bool {
     let config_path = get_config_path();
     Path::exists(&config_path)
@@ -68,12 +891,118 @@ pub fn exists() -> bool {
 
 pub fn write(new_config: &Config) -> anyhow::Result<()> {
     let config_path = get_config_path();
-    let serialized = serde_yaml::to_string(new_config)?;
 
-    fs::write(&config_path, serialized)
+    let mut values = serde_yaml::to_value(new_config.without_overrides())?;
+    if let Some(profile) = &new_config.active_profile {
+        values = into_profile(values, profile)?;
+    }
+    if !new_config.include.is_empty() {
+        values = without_included_values(values)?;
+    }
+
+    let contents = ConfigFormat::of(&config_path).serialize(&values)?;
+    fs::write(&config_path, contents)
         .with_context(|| format!("failed to write to config file {}", config_path.display()))
 }
 
+/// Rewrite the config file in another format, keeping the original as
+/// e.g. `config.yml.bak`. Comments aren't carried over.
+pub fn convert(to: ConfigFormat) -> anyhow::Result<(PathBuf, PathBuf)> {
+    let config_path = get_config_path();
+    let converted_path = get_config_dir().join(to.file_name());
+
+    if ConfigFormat::of(&config_path) == to {
+        bail!(
+            "Config file {} is already {}",
+            config_path.display(),
+            to.name()
+        );
+    }
+    if converted_path.exists() {
+        bail!(
+            "{} already exists, remove it to convert {}",
+            converted_path.display(),
+            config_path.display()
+        );
+    }
+
+    let raw = read_raw()?;
+    fs::write(&converted_path, to.serialize(&raw)?).with_context(|| {
+        format!(
+            "failed to write to config file {}",
+            converted_path.display()
+        )
+    })?;
+
+    let extension = config_path
+        .extension()
+        .unwrap_or_default()
+        .to_string_lossy();
+    let backup_path = config_path.with_extension(format!("{extension}.bak"));
+    fs::rename(&config_path, &backup_path)
+        .with_context(|| format!("failed to move config file to {}", backup_path.display()))?;
+
+    Ok((converted_path, backup_path))
+}
+
+/// Move the values a profile overrides back into that profile, restoring
+/// the top-level ones from `config.yml`, so changes made while a profile is
+/// active stay within it
+fn into_profile(values: serde_yaml::Value, profile: &str) -> anyhow::Result<serde_yaml::Value> {
+    let raw = read_raw()?;
+
+    let serde_yaml::Value::Mapping(mut values) = values else {
+        bail!("config should serialize to a mapping");
+    };
+    let overridden: Vec<(&str, &str)> = match profile_mapping(&mut values, profile) {
+        Some(overrides) => PROFILE_KEYS
+            .into_iter()
+            .filter(|(profile_key, _)| overrides.contains_key(*profile_key))
+            .collect(),
+        None => bail!("Profile `{profile}` not found in config"),
+    };
+
+    for (profile_key, config_key) in overridden {
+        let current = match raw.get(config_key) {
+            Some(original) => values.insert(config_key.into(), original.clone()),
+            None => values.remove(config_key),
+        };
+
+        let overrides = profile_mapping(&mut values, profile).expect("profile should exist");
+        match current {
+            Some(current) => overrides.insert(profile_key.into(), current),
+            None => overrides.remove(profile_key),
+        };
+    }
+
+    Ok(serde_yaml::Value::Mapping(values))
+}
+
+fn profile_mapping<'a>(
+    values: &'a mut serde_yaml::Mapping,
+    profile: &str,
+) -> Option<&'a mut serde_yaml::Mapping> {
+    match values.get_mut("profiles")?.get_mut(profile)? {
+        serde_yaml::Value::Mapping(overrides) => Some(overrides),
+        _ => None,
+    }
+}
+
+/// Drop keys that are inherited unchanged from included files, so writing
+/// the merged config back doesn't copy shared settings into `config.yml`
+fn without_included_values(values: serde_yaml::Value) -> anyhow::Result<serde_yaml::Value> {
+    let raw = read_raw()?;
+    let included = read_includes(&raw)?;
+
+    let serde_yaml::Value::Mapping(mut values) = values else {
+        bail!("config should serialize to a mapping");
+    };
+
+    values.retain(|key, value| raw.get(key).is_some() || included.get(key) != Some(value));
+
+    Ok(serde_yaml::Value::Mapping(values))
+}
+
 pub fn resolve_editor() -> anyhow::Result<String> {
     if let Ok(config) = read() {
         if let Some(editor) = &config.editor {
@@ -90,21 +1019,15 @@ impl TryFrom<app_settings::Settings> for Config {
     type Error = anyhow::Error;
 
     fn try_from(settings: app_settings::Settings) -> Result<Self, Self::Error> {
-        let vaults: Vec<Vault> = Vec::from_iter(settings.vaults.values().map(|vault| {
-            Vault {
-                // @TODO: fn to get name from path
-                name: vault
-                    .path
-                    .components()
-                    .last()
-                    .unwrap()
-                    .as_os_str()
-                    .to_str()
-                    .unwrap()
-                    .to_string(),
-                path: PathBuf::from(&vault.path),
-            }
-        }));
+        let vaults: Vec<Vault> = settings
+            .vaults_by_recency()
+            .into_iter()
+            .map(|vault| Vault {
+                name: vault_name(&vault.path),
+                path: vault.path.clone(),
+                ..Default::default()
+            })
+            .collect();
 
         match vaults.len() {
             0 => {
@@ -115,10 +1038,33 @@ impl TryFrom<app_settings::Settings> for Config {
             }
             _n => {
                 let config = Self {
+                    version: CONFIG_VERSION,
                     current_vault: vaults[0].clone().name,
+                    default_vault: None,
                     vaults,
                     editor: None,
-                    theme: ThemeName::default(),
+                    theme: default_theme_name(),
+                    themes: BTreeMap::new(),
+                    attachment_folder: None,
+                    excalidraw_converter: None,
+                    pandoc: None,
+                    template_folder: None,
+                    ignore: Vec::new(),
+                    scan: ScanLimits::default(),
+                    dates: DateFormats::default(),
+                    fuzzy: FuzzyScoring::default(),
+                    matching: MatchSettings::default(),
+                    zettel: ZettelSettings::default(),
+                    cite: CiteSettings::default(),
+                    periodic: PeriodicSettings::default(),
+                    relations: RelationSettings::default(),
+                    ambiguous_links: AmbiguitySettings::default(),
+                    secrets: BTreeMap::new(),
+                    hooks: Hooks::default(),
+                    include: Vec::new(),
+                    profiles: BTreeMap::new(),
+                    active_profile: None,
+                    overrides: Vec::new(),
                 };
 
                 Ok(config)
@@ -135,6 +1081,7 @@ pub fn create_from_settings() -> anyhow::Result<Config> {
 
     fs::OpenOptions::new()
         .create(true)
+        .truncate(false)
         .write(true)
         .open(&config_path)
         .with_context(|| format!("failed to create config file at {}", config_path.display()))?;
@@ -149,7 +1096,6 @@ pub fn create_from_settings() -> anyhow::Result<Config> {
 
 #[cfg(test)]
 mod tests {
-    #[cfg(target_os = "macos")]
     use super::*;
     #[cfg(target_os = "macos")]
     use regex::Regex;
@@ -169,4 +1115,55 @@ mod tests {
         let dir = format!("{}", get_config_path().display());
         assert!(re.is_match(&dir));
     }
+
+    fn rename_editor(values: &mut serde_yaml::Mapping) {
+        if let Some(editor) = values.remove("editor") {
+            values.insert("editor_command".into(), editor);
+        }
+    }
+
+    fn add_theme(values: &mut serde_yaml::Mapping) {
+        values.insert("theme".into(), "terminal".into());
+    }
+
+    #[test]
+    fn migrate_applies_missing_migrations_in_order() {
+        let mut raw: serde_yaml::Value = serde_yaml::from_str("editor: vim").unwrap();
+
+        let from = migrate(&mut raw, &[rename_editor, add_theme]).unwrap();
+
+        assert_eq!(from, Some(1));
+        assert_eq!(
+            raw,
+            serde_yaml::from_str::<serde_yaml::Value>(
+                "{editor_command: vim, theme: terminal, version: 3}"
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn migrate_skips_migrations_already_applied() {
+        let mut raw: serde_yaml::Value = serde_yaml::from_str("{editor: vim, version: 2}").unwrap();
+
+        migrate(&mut raw, &[rename_editor, add_theme]).unwrap();
+
+        assert_eq!(raw["editor"].as_str(), Some("vim"));
+        assert_eq!(raw["theme"].as_str(), Some("terminal"));
+    }
+
+    #[test]
+    fn migrate_leaves_current_configs_alone() {
+        let mut raw: serde_yaml::Value = serde_yaml::from_str("{editor: vim}").unwrap();
+
+        assert_eq!(migrate(&mut raw, &[]).unwrap(), None);
+        assert!(raw.get("version").is_none());
+    }
+
+    #[test]
+    fn migrate_rejects_newer_versions() {
+        let mut raw: serde_yaml::Value = serde_yaml::from_str("{version: 2}").unwrap();
+
+        assert!(migrate(&mut raw, &[]).is_err());
+    }
 }
@@ -1,5 +1,53 @@
+pub mod attach;
+pub mod audit;
+pub mod backlinks;
+pub mod bench;
 pub mod browse;
+pub mod cat;
+pub mod check_links;
+pub mod cite;
+pub mod complete_paths;
+pub mod complete_tags;
+pub mod completions;
 pub mod config;
+pub mod convert_links;
+pub mod daemon;
+pub mod daily;
+pub mod dedupe;
+pub mod delete;
+pub mod diff;
+pub mod expire;
+pub mod export;
+pub mod git;
+pub mod graph;
+pub mod grep;
+pub mod import;
+pub mod index;
 pub mod init;
+pub mod links;
+pub mod list;
+pub mod mcp;
+pub mod meta;
+pub mod moc;
+pub mod move_folder;
+pub mod move_notes;
 pub mod notes;
+pub mod open;
+pub mod orphans;
+pub mod outline;
+pub mod path;
+pub mod query;
+pub mod random;
+pub mod recent;
+pub mod related;
+pub mod rename;
+pub mod rename_heading;
+pub mod search;
+pub mod serve;
+pub mod tag;
+pub mod tags;
+pub mod template;
+pub mod tree;
+pub mod uri;
 pub mod vaults;
+pub mod wordcount;
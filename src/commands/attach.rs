@@ -0,0 +1,42 @@
+use crate::{
+    attachments, cli_config, resolve, scan,
+    util::{get_current_vault, CommandResult},
+};
+use clap::Args;
+use std::path::Path;
+
+#[derive(Args, Debug, Clone)]
+pub struct AttachCommand {
+    #[arg(help = "The name or path of the note to attach files to")]
+    note: String,
+
+    #[arg(
+        required = true,
+        help = "Files to copy into the vault's attachment folder, paths may be dragged in from a file manager"
+    )]
+    files: Vec<String>,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+pub fn entry(cmd: &AttachCommand) -> CommandResult {
+    let vault = get_current_vault(cmd.vault.clone())?;
+    let note_path = resolve::pick_note(&cmd.note, &vault.path)?;
+    let note_name = scan::relative_note_path(&note_path, &vault.path);
+    let note_folder = Path::new(&note_name).parent().unwrap_or(Path::new(""));
+    let attachment_folder = attachments::attachment_folder(
+        cli_config::read()?.attachment_folder.as_deref(),
+        &vault.path,
+        note_folder,
+    );
+    let mut output = Vec::new();
+
+    for file in &cmd.files {
+        let source = attachments::normalize_dropped_path(file);
+        let link = attachments::attach(&vault.path, &attachment_folder, &note_path, &source)?;
+        output.push(format!("Attached {link} to {note_name}"));
+    }
+
+    Ok(Some(output.join("\n")))
+}
@@ -0,0 +1,388 @@
+use crate::{
+    ambiguity::{self, AmbiguitySettings},
+    link_graph::{Edge, LinkGraph},
+    link_resolver::{candidate_names, has_subpath, LinkResolver},
+    matching::{self, MatchSettings},
+    scan,
+    util::{get_current_vault, CommandResult},
+};
+use clap::{Args, Subcommand};
+use libobsidian::links::{parse_links, Link, LinkKind};
+use serde::Serialize;
+use std::{collections::BTreeMap, fmt, fs, path::Path};
+use tabled::{builder::Builder, settings::Style};
+
+#[derive(Args, Debug, Clone)]
+#[command(args_conflicts_with_subcommands = true)]
+#[command(arg_required_else_help = true)]
+pub struct AuditCommand {
+    #[command(subcommand)]
+    command: Option<Subcommands>,
+}
+
+#[derive(Debug, Subcommand, Clone)]
+enum Subcommands {
+    /// Report broken, ambiguous, non-note, miscased and self links
+    Links(LinksArgs),
+    /// List the names several notes or files share, and which one the name
+    /// means
+    Ambiguous(AmbiguousArgs),
+}
+
+#[derive(Args, Debug, Clone)]
+struct LinksArgs {
+    #[arg(long, short = 'f', default_value = "table")]
+    format: AuditFormat,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+#[derive(Args, Debug, Clone)]
+struct AmbiguousArgs {
+    #[arg(long, short = 'f', default_value = "table")]
+    format: AuditFormat,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+enum AuditFormat {
+    Table,
+    /// An array of problems, each with its note, line, kind and link
+    Json,
+    /// A note with a section for each kind of problem
+    Markdown,
+}
+
+/// What's wrong with a link, in the order the report lists them
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "kebab-case")]
+enum Problem {
+    /// Points at nothing, or at a block that isn't there
+    Broken,
+    /// Names several notes, so which one it opens depends on their paths
+    Ambiguous,
+    /// Links, rather than embeds, a file that isn't a note
+    NotMarkdown,
+    /// Written in a different case from the file it finds, which breaks on
+    /// case-sensitive file systems
+    CaseMismatch,
+    /// Points at the note it's in
+    SelfLink,
+}
+
+impl Problem {
+    fn heading(self) -> &'static str {
+        match self {
+            Problem::Broken => "Broken links",
+            Problem::Ambiguous => "Ambiguous links",
+            Problem::NotMarkdown => "Links to files that aren't notes",
+            Problem::CaseMismatch => "Links in a different case",
+            Problem::SelfLink => "Links to their own note",
+        }
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Problem::Broken => "broken",
+            Problem::Ambiguous => "ambiguous",
+            Problem::NotMarkdown => "not markdown",
+            Problem::CaseMismatch => "case mismatch",
+            Problem::SelfLink => "self link",
+        })
+    }
+}
+
+#[derive(Serialize, Debug)]
+struct Finding {
+    /// Vault-relative
+    note: String,
+    line: usize,
+    problem: Problem,
+    /// As written in the note
+    link: String,
+    /// Every note an ambiguous link could mean, the one it opens first
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    candidates: Vec<String>,
+}
+
+pub fn entry(cmd: &AuditCommand) -> CommandResult {
+    match &cmd.command {
+        Some(Subcommands::Links(args)) => links(args),
+        Some(Subcommands::Ambiguous(args)) => ambiguous(args),
+        None => todo!(),
+    }
+}
+
+fn links(args: &LinksArgs) -> CommandResult {
+    let vault = get_current_vault(args.vault.clone())?;
+    let graph = LinkGraph::current(&vault.path)?;
+    let resolver = graph.resolver(&vault.path);
+
+    let mut findings = Vec::new();
+    let mut notes = scan::note_paths(&vault.path)?;
+    notes.sort();
+    for note_path in notes {
+        let Ok(contents) = fs::read_to_string(&note_path) else {
+            continue;
+        };
+        let relative = scan::relative_note_path(&note_path, &vault.path);
+        for link in parse_links(&contents) {
+            if link.is_external() {
+                continue;
+            }
+            for (problem, candidates) in audit(&resolver, &link, &note_path, &vault.path) {
+                findings.push(Finding {
+                    note: relative.clone(),
+                    line: link.line,
+                    problem,
+                    link: contents[link.range.clone()].to_string(),
+                    candidates,
+                });
+            }
+        }
+    }
+    findings.sort_by_key(|finding| finding.problem);
+
+    match args.format {
+        AuditFormat::Json => Ok(Some(serde_json::to_string(&findings)?)),
+        _ if findings.is_empty() => Ok(None),
+        AuditFormat::Markdown => Ok(Some(markdown(&findings))),
+        AuditFormat::Table => {
+            let mut builder = Builder::new();
+            for finding in &findings {
+                builder.push_record([
+                    finding.note.clone(),
+                    finding.line.to_string(),
+                    finding.problem.to_string(),
+                    finding.link.clone(),
+                ]);
+            }
+            builder.insert_record(0, vec!["Note", "Line", "Problem", "Link"]);
+
+            let mut table = builder.build();
+            table.with(Style::sharp());
+            Ok(Some(format!("{table}")))
+        }
+    }
+}
+
+/// A name several files share
+#[derive(Serialize, Debug)]
+struct SharedName {
+    /// The file name, without `.md` for notes
+    name: String,
+    /// Vault-relative, the one links by the name alone resolve to first
+    files: Vec<String>,
+    /// How many links use the name alone
+    links: usize,
+}
+
+fn ambiguous(args: &AmbiguousArgs) -> CommandResult {
+    let vault = get_current_vault(args.vault.clone())?;
+    let graph = LinkGraph::current(&vault.path)?;
+    let settings = AmbiguitySettings::read();
+    let matching = MatchSettings::read();
+    let files = || graph.files().iter().map(String::as_str);
+
+    // Links are looked up by name once the vault's files are grouped by
+    // name, rather than each searching every file
+    let by_name = ambiguity::files_by_name(files(), &matching);
+    let mut links: BTreeMap<String, usize> = BTreeMap::new();
+    for source in graph.notes() {
+        for edge in graph.links_from(source) {
+            if let Some(name) = linked_name(edge, &by_name, &matching) {
+                *links.entry(name).or_default() += 1;
+            }
+        }
+    }
+    let names: Vec<SharedName> = ambiguity::shared_names(files(), &settings, &matching)
+        .into_iter()
+        .map(|(folded, files)| {
+            let name = files[0].rsplit('/').next().unwrap_or(files[0]);
+            SharedName {
+                name: name.strip_suffix(".md").unwrap_or(name).to_string(),
+                links: links.get(&folded).copied().unwrap_or(0),
+                files: files.into_iter().map(str::to_string).collect(),
+            }
+        })
+        .collect();
+
+    match args.format {
+        AuditFormat::Json => Ok(Some(serde_json::to_string(&names)?)),
+        _ if names.is_empty() => Ok(None),
+        AuditFormat::Markdown => {
+            let items: Vec<String> = names
+                .iter()
+                .map(|shared| {
+                    let files: Vec<String> = shared
+                        .files
+                        .iter()
+                        .map(|file| format!("`{file}`"))
+                        .collect();
+                    format!(
+                        "- `{}`, {} link(s): {}",
+                        shared.name,
+                        shared.links,
+                        files.join(", ")
+                    )
+                })
+                .collect();
+            Ok(Some(format!("# Ambiguous names\n\n{}", items.join("\n"))))
+        }
+        AuditFormat::Table => {
+            let mut builder = Builder::new();
+            for shared in &names {
+                builder.push_record([
+                    shared.name.clone(),
+                    shared.files[0].clone(),
+                    shared.files[1..].join("\n"),
+                    shared.links.to_string(),
+                ]);
+            }
+            builder.insert_record(0, vec!["Name", "Resolves to", "Also", "Links"]);
+
+            let mut table = builder.build();
+            table.with(Style::sharp());
+            Ok(Some(format!("{table}")))
+        }
+    }
+}
+
+/// The folded file name a name-only wikilink is looked up by, the way
+/// [`LinkResolver::candidates`] looks for it, or `None` when `edge` isn't one
+/// or a file at the vault's root has the name, since the link means that file
+fn linked_name(
+    edge: &Edge,
+    by_name: &BTreeMap<String, Vec<&str>>,
+    matching: &MatchSettings,
+) -> Option<String> {
+    if edge.kind != LinkKind::Wikilink || edge.target.is_empty() || edge.target.contains('/') {
+        return None;
+    }
+    let names: Vec<String> = candidate_names(&edge.target)
+        .iter()
+        .map(|name| matching.fold(name))
+        .collect();
+    let at_root = names.iter().any(|name| {
+        by_name
+            .get(name)
+            .is_some_and(|files| files.iter().any(|file| !file.contains('/')))
+    });
+    if at_root {
+        return None;
+    }
+
+    names.into_iter().find(|name| by_name.contains_key(name))
+}
+
+/// The problems with `link`, found in `source`, and for an ambiguous link
+/// the notes it could mean
+fn audit(
+    resolver: &LinkResolver,
+    link: &Link,
+    source: &Path,
+    vault_path: &Path,
+) -> Vec<(Problem, Vec<String>)> {
+    let Some(resolved) = resolver
+        .resolve(link, source)
+        .filter(|resolved| has_subpath(link, resolved, resolver.matching()))
+    else {
+        return vec![(Problem::Broken, Vec::new())];
+    };
+    if link.target.is_empty() {
+        return Vec::new();
+    }
+
+    let mut problems = Vec::new();
+    let candidates = resolver.candidates(link);
+    if candidates.len() > 1 {
+        problems.push((
+            Problem::Ambiguous,
+            candidates.into_iter().map(str::to_string).collect(),
+        ));
+    }
+    if !link.embed && !scan::is_markdown(&resolved) {
+        problems.push((Problem::NotMarkdown, Vec::new()));
+    }
+    let relative = scan::relative_note_path(&resolved, vault_path);
+    if is_miscased(&link.target, &relative) {
+        problems.push((Problem::CaseMismatch, Vec::new()));
+    }
+    if resolved == source {
+        problems.push((Problem::SelfLink, Vec::new()));
+    }
+    problems
+}
+
+/// Whether the folders and name written in a link match the end of the path
+/// it resolved to only once case is ignored
+fn is_miscased(target: &str, resolved: &str) -> bool {
+    let resolved = if scan::is_markdown(Path::new(target)) {
+        resolved
+    } else {
+        resolved.strip_suffix(".md").unwrap_or(resolved)
+    };
+    let written = target
+        .split('/')
+        .filter(|part| !matches!(*part, "" | "." | ".."));
+
+    written.rev().zip(resolved.split('/').rev()).any(|(a, b)| {
+        !matching::same(a, b)
+            && matching::compose(a).to_lowercase() == matching::compose(b).to_lowercase()
+    })
+}
+
+fn markdown(findings: &[Finding]) -> String {
+    let mut sections: Vec<String> = vec!["# Link audit".to_string()];
+    for problem in [
+        Problem::Broken,
+        Problem::Ambiguous,
+        Problem::NotMarkdown,
+        Problem::CaseMismatch,
+        Problem::SelfLink,
+    ] {
+        let items: Vec<String> = findings
+            .iter()
+            .filter(|finding| finding.problem == problem)
+            .map(|finding| {
+                let mut item = format!(
+                    "- `{}` line {}: `{}`",
+                    finding.note, finding.line, finding.link
+                );
+                if !finding.candidates.is_empty() {
+                    let candidates: Vec<String> = finding
+                        .candidates
+                        .iter()
+                        .map(|candidate| format!("`{candidate}`"))
+                        .collect();
+                    item.push_str(&format!(" could be {}", candidates.join(", ")));
+                }
+                item
+            })
+            .collect();
+        if !items.is_empty() {
+            sections.push(format!("## {}\n\n{}", problem.heading(), items.join("\n")));
+        }
+    }
+    sections.join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case("Note", "folder/Note.md", false ; "same case")]
+    #[test_case("note", "folder/Note.md", true ; "name")]
+    #[test_case("../Folder/Note.md", "folder/Note.md", true ; "folder")]
+    #[test_case("The Plan", "projects/plan.md", false ; "alias")]
+    #[test_case("image.PNG", "image.png", true ; "attachment")]
+    fn finds_miscased_links(target: &str, resolved: &str, expected: bool) {
+        assert_eq!(is_miscased(target, resolved), expected);
+    }
+}
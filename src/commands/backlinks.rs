@@ -0,0 +1,141 @@
+use crate::{
+    link_graph::{Edge, LinkGraph},
+    mentions, resolve, scan,
+    style::Styler,
+    util::{get_current_vault, CommandResult},
+};
+use clap::Args;
+use libobsidian::blocks::parse_blocks;
+use std::{collections::BTreeSet, fs, path::Path};
+use tabled::{builder::Builder, settings::Style};
+
+#[derive(Args, Debug, Clone)]
+pub struct BacklinksCommand {
+    #[arg(help = "The name or path of the note to list links to")]
+    note: String,
+
+    /// List where other notes mention the note's name or aliases without
+    /// linking to it
+    #[arg(long, short = 'u')]
+    unlinked: bool,
+
+    /// Turn every unlinked mention into a wikilink to the note
+    #[arg(long, requires = "unlinked")]
+    link: bool,
+
+    /// Only list links into the note's `^blocks`, with the text of each block
+    #[arg(long, short = 'b', conflicts_with = "unlinked")]
+    blocks: bool,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+pub fn entry(cmd: &BacklinksCommand) -> CommandResult {
+    let vault = get_current_vault(cmd.vault.clone())?;
+    let note_path = resolve::pick_note(&cmd.note, &vault.path)?;
+    let relative = scan::relative_note_path(&note_path, &vault.path);
+
+    if cmd.unlinked {
+        return unlinked(cmd, &vault.path, &note_path);
+    }
+
+    let graph = LinkGraph::current(&vault.path)?;
+    let links = graph.links_to(&relative);
+    if cmd.blocks {
+        return blocks(&note_path, links);
+    }
+    if links.is_empty() {
+        return Ok(None);
+    }
+
+    let styler = Styler::current();
+    let mut builder = Builder::new();
+    for (source, edge) in links {
+        builder.push_record([
+            styler.path(source),
+            edge.line.to_string(),
+            edge.text.clone(),
+        ]);
+    }
+    builder.insert_record(0, vec!["Note", "Line", "Link"]);
+
+    let mut table = builder.build();
+    table.with(Style::sharp());
+
+    Ok(Some(format!("{table}")))
+}
+
+fn blocks(note_path: &Path, links: Vec<(&str, &Edge)>) -> CommandResult {
+    let links: Vec<(&str, &Edge, &str)> = links
+        .into_iter()
+        .filter_map(|(source, edge)| {
+            let id = edge.subpath.as_deref()?.strip_prefix('^')?;
+            Some((source, edge, id))
+        })
+        .collect();
+    if links.is_empty() {
+        return Ok(None);
+    }
+
+    let blocks = parse_blocks(&fs::read_to_string(note_path).unwrap_or_default());
+    let styler = Styler::current();
+    let mut builder = Builder::new();
+    for (source, edge, id) in links {
+        let text = blocks
+            .iter()
+            .find(|block| block.id.eq_ignore_ascii_case(id))
+            .map_or("(missing)", |block| {
+                block.text.lines().next().unwrap_or_default()
+            });
+        builder.push_record([
+            styler.path(source),
+            edge.line.to_string(),
+            edge.text.clone(),
+            text.to_string(),
+        ]);
+    }
+    builder.insert_record(0, vec!["Note", "Line", "Link", "Block"]);
+
+    let mut table = builder.build();
+    table.with(Style::sharp());
+
+    Ok(Some(format!("{table}")))
+}
+
+fn unlinked(cmd: &BacklinksCommand, vault_path: &Path, note_path: &Path) -> CommandResult {
+    let found = mentions::find(vault_path, note_path)?;
+    if found.is_empty() {
+        return Ok(None);
+    }
+
+    if cmd.link {
+        mentions::link(vault_path, note_path, &found)?;
+        let notes: BTreeSet<&str> = found
+            .iter()
+            .map(|mention| mention.source.as_str())
+            .collect();
+        return Ok(Some(format!(
+            "Linked {} mentions in {} notes",
+            found.len(),
+            notes.len()
+        )));
+    }
+
+    let styler = Styler::current();
+    let mut builder = Builder::new();
+    for mention in &found {
+        let end = mention.column + mention.text.len();
+        builder.push_record([
+            styler.path(&mention.source),
+            mention.line.to_string(),
+            styler.highlight(&mention.context, &[(mention.column, end)]),
+        ]);
+    }
+    builder.insert_record(0, vec!["Note", "Line", "Mention"]);
+
+    let mut table = builder.build();
+    table.with(Style::sharp());
+
+    Ok(Some(format!("{table}")))
+}
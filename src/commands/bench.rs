@@ -0,0 +1,148 @@
+use crate::{
+    cache::MetadataCache,
+    index::Index,
+    link_graph::LinkGraph,
+    scan,
+    search_query::SearchQuery,
+    util::{get_current_vault, CommandResult},
+};
+use clap::Args;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+use tabled::{builder::Builder, settings::Style};
+
+#[derive(Args, Debug, Clone)]
+pub struct BenchCommand {
+    /// The query to time searching for
+    #[arg(long, short = 'q', default_value = "the")]
+    query: String,
+
+    /// How many times to run each stage, reporting the median
+    #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..))]
+    runs: u32,
+
+    #[arg(long, short = 'f', default_value = "table")]
+    format: BenchFormat,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+enum BenchFormat {
+    Table,
+    Json,
+}
+
+/// How long one stage took
+#[derive(Serialize, Debug)]
+struct Timing {
+    stage: String,
+    /// Median over the runs
+    millis: f64,
+    /// What the stage got through, like `120 notes in 8 folders`
+    result: String,
+}
+
+pub fn entry(cmd: &BenchCommand) -> CommandResult {
+    let vault = get_current_vault(cmd.vault.clone())?;
+    let query = SearchQuery::parse(&cmd.query)?;
+
+    // Nothing is read from or saved to the state directory, so every stage
+    // starts cold apart from the filesystem's own caching
+    let mut timings = Vec::new();
+
+    let ((folders, notes), took) = time(cmd.runs, || scan::folders_and_notes(&vault.path))?;
+    timings.push(Timing::new(
+        "Scan",
+        took,
+        format!("{} notes in {} folders", notes.len(), folders.len()),
+    ));
+
+    let (metadata, took) = time(cmd.runs, || {
+        let mut metadata = MetadataCache::default();
+        metadata.refresh(&vault.path).map(|_| metadata)
+    })?;
+    timings.push(Timing::new(
+        "Metadata",
+        took,
+        format!("{} notes", metadata.notes().len()),
+    ));
+
+    let (index, took) = time(cmd.runs, || Index::build(&vault.path))?;
+    timings.push(Timing::new(
+        "Index build",
+        took,
+        format!("{} terms", index.term_count()),
+    ));
+
+    let (found, took) = time(cmd.runs, || query.search_in(&index, &vault.path))?;
+    timings.push(Timing::new(
+        &format!("Search `{}`", cmd.query),
+        took,
+        format!("{} notes", found.len()),
+    ));
+
+    let (graph, took) = time(cmd.runs, || {
+        let mut graph = LinkGraph::default();
+        graph.refresh(&vault.path).map(|_| graph)
+    })?;
+    timings.push(Timing::new(
+        "Link graph",
+        took,
+        format!("{} links", graph.link_count()),
+    ));
+
+    match cmd.format {
+        BenchFormat::Json => Ok(Some(serde_json::to_string(&timings)?)),
+        BenchFormat::Table => {
+            let mut builder = Builder::new();
+            for timing in &timings {
+                builder.push_record([
+                    timing.stage.clone(),
+                    format!("{:.1} ms", timing.millis),
+                    timing.result.clone(),
+                ]);
+            }
+            builder.insert_record(0, vec!["Stage", "Time", "Result"]);
+
+            let mut table = builder.build();
+            table.with(Style::sharp());
+
+            Ok(Some(format!(
+                "{table}\nMedian of {} {} on `{}`",
+                cmd.runs,
+                if cmd.runs == 1 { "run" } else { "runs" },
+                vault.name
+            )))
+        }
+    }
+}
+
+impl Timing {
+    fn new(stage: &str, took: Duration, result: String) -> Self {
+        Self {
+            stage: stage.to_string(),
+            millis: took.as_secs_f64() * 1000.0,
+            result,
+        }
+    }
+}
+
+/// Run `stage` `runs` times, returning its last result and the median time
+fn time<T>(
+    runs: u32,
+    mut stage: impl FnMut() -> anyhow::Result<T>,
+) -> anyhow::Result<(T, Duration)> {
+    let mut took = Vec::new();
+    let mut result = None;
+    for _ in 0..runs {
+        let start = Instant::now();
+        result = Some(stage()?);
+        took.push(start.elapsed());
+    }
+    took.sort();
+
+    let result = result.expect("runs is at least 1");
+    Ok((result, took[took.len() / 2]))
+}
@@ -1,8 +1,7 @@
-use std::path::PathBuf;
-
 use anyhow::{bail, Context};
 use clap::Args;
 
+use crate::note_text;
 use crate::tui;
 use crate::util::{get_current_vault, should_enable_interactivity};
 
@@ -19,9 +18,8 @@ pub fn entry(cmd: &BrowseCommand) -> anyhow::Result<Option<String>> {
     }
 
     let vault = get_current_vault(cmd.vault.clone())?;
-    let vault_path = PathBuf::from(vault.path);
-
-    tui::run(vault_path).context("failed to launch interactive browser")?;
+    note_text::avoid_mapping();
+    tui::run(vault.path).context("failed to launch interactive browser")?;
 
     Ok(None)
 }
@@ -0,0 +1,81 @@
+use crate::{
+    embeds,
+    link_resolver::LinkResolver,
+    render::render_markdown,
+    resolve, style,
+    util::{get_current_vault, CommandResult},
+};
+use anyhow::{anyhow, Context};
+use clap::Args;
+use libobsidian::{blocks::find_block, split_frontmatter};
+use std::fs;
+
+#[derive(Args, Debug, Clone)]
+pub struct CatCommand {
+    #[arg(help = "The name or path of the note, matched exactly or fuzzily within the vault")]
+    note: String,
+
+    /// Leave out the frontmatter block
+    #[arg(long, conflicts_with = "frontmatter_only")]
+    no_frontmatter: bool,
+
+    /// Only print the frontmatter, without its `---` delimiters
+    #[arg(long, conflicts_with = "render")]
+    frontmatter_only: bool,
+
+    /// Only print the block marked with this `^id`
+    #[arg(long, value_name = "ID", conflicts_with = "frontmatter_only")]
+    block: Option<String>,
+
+    /// Show embedded notes, headings and blocks in place of their embeds
+    #[arg(long, short = 'e', conflicts_with = "frontmatter_only")]
+    expand_embeds: bool,
+
+    /// Lay the markdown out for the terminal, styled when output is colored.
+    /// Implies --no-frontmatter
+    #[arg(long)]
+    render: bool,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+pub fn entry(cmd: &CatCommand) -> CommandResult {
+    let vault = get_current_vault(cmd.vault.clone())?;
+    let note_path = resolve::pick_note(&cmd.note, &vault.path)?;
+    let contents = fs::read_to_string(&note_path)
+        .with_context(|| format!("Could not read note {}", note_path.display()))?;
+
+    let (frontmatter, body) = split_frontmatter(&contents);
+    let before_body = &contents[..contents.len() - body.len()];
+    let body = match &cmd.block {
+        Some(id) => {
+            find_block(body, id)
+                .ok_or_else(|| anyhow!("No block ^{} in {}", id.trim_start_matches('^'), cmd.note))?
+                .text
+        }
+        None => body.to_string(),
+    };
+    let body = if cmd.expand_embeds {
+        embeds::expand(&body, &note_path, &LinkResolver::new(&vault.path)?)
+    } else {
+        body
+    };
+
+    let output = if cmd.frontmatter_only {
+        frontmatter.unwrap_or_default().to_string()
+    } else if cmd.render {
+        render_markdown(&body, style::enabled())
+    } else if cmd.no_frontmatter || cmd.block.is_some() {
+        body.trim_start_matches(['\r', '\n']).to_string()
+    } else {
+        format!("{before_body}{body}")
+    };
+
+    let output = output.trim_end_matches(['\r', '\n']);
+    if output.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(output.to_string()))
+}
@@ -0,0 +1,169 @@
+use crate::{
+    link_graph::LinkGraph,
+    link_resolver::{has_subpath, LinkResolver},
+    resolve, scan,
+    util::{get_current_vault, should_enable_interactivity, CommandResult},
+};
+use anyhow::{bail, Context};
+use clap::Args;
+use dialoguer::{theme::ColorfulTheme, Select};
+use libobsidian::links::{parse_links, replace_ranges, Link};
+use std::{
+    fs,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+use tabled::{builder::Builder, settings::Style};
+
+/// Most candidates offered when picking a replacement for a broken link
+const MAX_CANDIDATES: usize = 10;
+
+#[derive(Args, Debug, Clone)]
+pub struct CheckLinksCommand {
+    /// Pick a replacement for each broken link from notes with similar names
+    #[arg(long)]
+    fix_interactive: bool,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+struct BrokenLinks {
+    note_path: PathBuf,
+    relative: String,
+    contents: String,
+    links: Vec<Link>,
+}
+
+pub fn entry(cmd: &CheckLinksCommand) -> CommandResult {
+    let vault = get_current_vault(cmd.vault.clone())?;
+
+    if cmd.fix_interactive && !should_enable_interactivity() {
+        bail!("--fix-interactive needs an interactive terminal");
+    }
+
+    let graph = LinkGraph::current(&vault.path)?;
+    let resolver = graph.resolver(&vault.path);
+    let mut broken = Vec::new();
+
+    // Only notes the graph has broken links or links into headings or blocks
+    // in need reading, since those aren't part of the graph
+    for relative in graph.notes() {
+        let needs_checking = graph
+            .links_from(relative)
+            .iter()
+            .any(|edge| edge.resolved.is_none() || edge.subpath.is_some());
+        if !needs_checking {
+            continue;
+        }
+        let note_path = vault.path.join(relative);
+        let Ok(contents) = fs::read_to_string(&note_path) else {
+            continue;
+        };
+
+        let links: Vec<Link> = parse_links(&contents)
+            .into_iter()
+            .filter(|link| {
+                !link.is_external()
+                    && resolver
+                        .resolve(link, &note_path)
+                        .is_none_or(|resolved| !has_subpath(link, &resolved, resolver.matching()))
+            })
+            .collect();
+
+        if !links.is_empty() {
+            broken.push(BrokenLinks {
+                relative: relative.to_string(),
+                note_path,
+                contents,
+                links,
+            });
+        }
+    }
+
+    if broken.is_empty() {
+        return Ok(None);
+    }
+
+    if cmd.fix_interactive {
+        return fix_interactive(&broken, &resolver, &vault.path);
+    }
+
+    let mut builder = Builder::new();
+    for note in &broken {
+        for link in &note.links {
+            builder.push_record([
+                note.relative.clone(),
+                link.line.to_string(),
+                note.contents[link.range.clone()].to_string(),
+            ]);
+        }
+    }
+    builder.insert_record(0, vec!["Note", "Line", "Broken link"]);
+
+    let mut table = builder.build();
+    table.with(Style::sharp());
+
+    Ok(Some(format!("{table}")))
+}
+
+fn fix_interactive(
+    broken: &[BrokenLinks],
+    resolver: &LinkResolver,
+    vault_path: &Path,
+) -> CommandResult {
+    let total: usize = broken.iter().map(|note| note.links.len()).sum();
+    let mut fixed = 0;
+
+    for note in broken {
+        let mut replacements: Vec<(Range<usize>, String)> = Vec::new();
+
+        for link in &note.links {
+            let candidates: Vec<PathBuf> = resolve::find_notes(&link.target, vault_path)?
+                .into_iter()
+                .take(MAX_CANDIDATES)
+                .collect();
+            let original = &note.contents[link.range.clone()];
+
+            if candidates.is_empty() {
+                eprintln!(
+                    "No candidates for {original} in {}:{}",
+                    note.relative, link.line
+                );
+                continue;
+            }
+
+            let mut items: Vec<String> = candidates
+                .iter()
+                .map(|path| scan::relative_note_path(path, vault_path))
+                .collect();
+            items.push("Skip".to_string());
+
+            let selection = Select::with_theme(&ColorfulTheme::default())
+                .with_prompt(format!(
+                    "Replace {original} in {}:{} with",
+                    note.relative, link.line
+                ))
+                .items(&items)
+                .default(0)
+                .interact()
+                .context("couldn't prompt user for a replacement link")?;
+
+            if let Some(candidate) = candidates.get(selection) {
+                let target = resolver.link_target(candidate, &note.note_path, link.kind);
+                replacements.push((link.range.clone(), link.with_target(&target)));
+            }
+        }
+
+        if !replacements.is_empty() {
+            fixed += replacements.len();
+            fs::write(
+                &note.note_path,
+                replace_ranges(&note.contents, replacements),
+            )
+            .with_context(|| format!("Could not write to note {}", note.note_path.display()))?;
+        }
+    }
+
+    Ok(Some(format!("Fixed {fixed} of {total} broken links")))
+}
@@ -0,0 +1,192 @@
+use crate::{
+    cite::{self, Entry},
+    hooks::{self, Event},
+    matching::MatchSettings,
+    resolve, scan, template,
+    util::{get_current_vault, CommandResult},
+};
+use anyhow::{anyhow, Context};
+use clap::{ArgGroup, Args, Subcommand};
+use libobsidian::frontmatter::set_property;
+use std::{fs, path::Path};
+use tabled::{builder::Builder, settings::Style};
+
+#[derive(Args, Debug, Clone)]
+#[command(args_conflicts_with_subcommands = true)]
+#[command(arg_required_else_help = true)]
+pub struct CiteCommand {
+    #[command(subcommand)]
+    command: Option<Subcommands>,
+}
+
+#[derive(Debug, Subcommand, Clone)]
+enum Subcommands {
+    /// Find entries in the bibliography by citekey, author or title
+    Search(SearchArgs),
+    /// Cite an entry at the end of a note, or create its literature note
+    Insert(InsertArgs),
+}
+
+#[derive(Args, Debug, Clone)]
+struct SearchArgs {
+    #[arg(help = "Words that must all be in the citekey, authors or title")]
+    query: Vec<String>,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+#[derive(Args, Debug, Clone)]
+#[command(group(ArgGroup::new("target").required(true).multiple(true).args(["note", "literature_note"])))]
+struct InsertArgs {
+    #[arg(help = "The citekey of the entry")]
+    key: String,
+
+    #[arg(help = "The name or path of the note to append the citation to")]
+    note: Option<String>,
+
+    /// Create the entry's literature note, `@citekey`, if it doesn't exist,
+    /// and link the citation to it
+    #[arg(long, short = 'l')]
+    literature_note: bool,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+pub fn entry(cmd: &CiteCommand) -> CommandResult {
+    match &cmd.command {
+        Some(Subcommands::Search(args)) => search(args),
+        Some(Subcommands::Insert(args)) => insert(args),
+        None => todo!(),
+    }
+}
+
+fn search(args: &SearchArgs) -> CommandResult {
+    let vault = get_current_vault(args.vault.clone())?;
+    let query = args.query.join(" ");
+    let matching = MatchSettings::read();
+    let entries: Vec<Entry> = cite::settings()?
+        .read_bibliography(&vault.path)?
+        .into_iter()
+        .filter(|entry| entry.matches(&query, &matching))
+        .collect();
+    if entries.is_empty() {
+        return Ok(None);
+    }
+
+    let mut builder = Builder::new();
+    for entry in &entries {
+        builder.push_record([
+            entry.key.clone(),
+            entry.cited_authors(),
+            entry.field("year").to_string(),
+            entry.field("title").to_string(),
+        ]);
+    }
+    builder.insert_record(0, vec!["Key", "Authors", "Year", "Title"]);
+
+    let mut table = builder.build();
+    table.with(Style::sharp());
+
+    Ok(Some(format!("{table}")))
+}
+
+fn insert(args: &InsertArgs) -> CommandResult {
+    let vault = get_current_vault(args.vault.clone())?;
+    let settings = cite::settings()?;
+    let key = args.key.trim_start_matches('@');
+    let entry = settings
+        .read_bibliography(&vault.path)?
+        .into_iter()
+        .find(|entry| entry.key == key)
+        .ok_or_else(|| anyhow!("No entry `{key}` in the bibliography"))?;
+
+    let mut output = Vec::new();
+    let literature_path = settings.literature_note_path(&vault.path, &entry.key);
+    if args.literature_note && !literature_path.exists() {
+        let contents = literature_note(
+            &entry,
+            &literature_path,
+            &vault.path,
+            settings.literature_template.as_deref(),
+        )?;
+        if let Some(parent) = literature_path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Could not create directory {}", parent.display()))?;
+        }
+        fs::write(&literature_path, contents)
+            .with_context(|| format!("Could not create note {}", literature_path.display()))?;
+        hooks::run(Event::AfterCreate, &vault, &literature_path, None)?;
+        output.push(format!(
+            "Created literature note {}",
+            scan::relative_note_path(&literature_path, &vault.path)
+        ));
+    }
+
+    if let Some(note) = &args.note {
+        let note_path = resolve::pick_note(note, &vault.path)?;
+        let mut citation = entry.citation();
+        if literature_path.exists() {
+            citation.push_str(&format!(" [[@{}]]", entry.key));
+        }
+        let contents = fs::read_to_string(&note_path)
+            .with_context(|| format!("Could not read note {}", note_path.display()))?;
+        let contents = match contents.trim_end() {
+            "" => format!("{citation}\n"),
+            existing => format!("{existing}\n\n{citation}\n"),
+        };
+        fs::write(&note_path, contents)
+            .with_context(|| format!("Could not write to note {}", note_path.display()))?;
+        output.push(format!(
+            "Cited {} in {}",
+            entry.key,
+            scan::relative_note_path(&note_path, &vault.path)
+        ));
+    }
+
+    if output.is_empty() {
+        output.push(format!(
+            "Literature note {} already exists",
+            scan::relative_note_path(&literature_path, &vault.path)
+        ));
+    }
+    Ok(Some(output.join("\n")))
+}
+
+/// The contents of a new literature note: the template rendered with the
+/// entry's `{{citekey}}`, `{{authors}}`, `{{year}}`, `{{citation}}` and each
+/// of its fields as `{{bib.title}}` and so on, or a heading and the citation
+/// without a template. Either way the note gets a `citekey` property.
+fn literature_note(
+    entry: &Entry,
+    note_path: &Path,
+    vault_path: &Path,
+    template_name: Option<&str>,
+) -> anyhow::Result<String> {
+    let contents = match template_name {
+        Some(name) => {
+            let template_path = template::find_template(vault_path, name)?;
+            let template_contents = fs::read_to_string(&template_path)
+                .with_context(|| format!("Could not read template `{name}`"))?;
+
+            let mut vars = vec![
+                ("citekey".to_string(), entry.key.clone()),
+                ("authors".to_string(), entry.cited_authors()),
+                ("year".to_string(), entry.field("year").to_string()),
+                ("citation".to_string(), entry.citation()),
+            ];
+            vars.extend(
+                entry
+                    .fields
+                    .iter()
+                    .map(|(name, value)| (format!("bib.{name}"), value.clone())),
+            );
+            let context = template::TemplateContext::for_note(note_path, vault_path, &vars);
+            template::render(&template_contents, &context)
+        }
+        None => format!("# {}\n\n{}\n", entry.field("title"), entry.citation()),
+    };
+
+    Ok(set_property(&contents, "citekey", &entry.key))
+}
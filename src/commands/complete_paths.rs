@@ -0,0 +1,94 @@
+use crate::{
+    matching::MatchSettings,
+    resolve, scan,
+    util::{get_current_vault, CommandResult},
+};
+use clap::Args;
+
+#[derive(Args, Debug, Clone)]
+pub struct CompletePathsCommand {
+    #[arg(
+        default_value = "",
+        help = "What has been typed so far, matched fuzzily against vault-relative paths"
+    )]
+    prefix: String,
+
+    /// Print at most this many paths
+    #[arg(long, short = 'n')]
+    limit: Option<usize>,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+/// How closely a path matches what's been typed, best first
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum MatchKind {
+    PathPrefix,
+    NamePrefix,
+    Substring,
+    Subsequence,
+}
+
+pub fn entry(cmd: &CompletePathsCommand) -> CommandResult {
+    let vault = get_current_vault(cmd.vault.clone())?;
+    let matching = MatchSettings::read();
+    let needle = matching.fold(&cmd.prefix);
+
+    let mut matches: Vec<(MatchKind, String)> = scan::note_paths(&vault.path)?
+        .iter()
+        .map(|path| scan::relative_note_path(path, &vault.path))
+        .filter_map(|relative| {
+            match_kind(&needle, &relative, &matching).map(|kind| (kind, relative))
+        })
+        .collect();
+
+    matches.sort_by(|a, b| (&a.0, a.1.len(), &a.1).cmp(&(&b.0, b.1.len(), &b.1)));
+
+    let paths: Vec<String> = matches
+        .into_iter()
+        .map(|(_, relative)| relative)
+        .take(cmd.limit.unwrap_or(usize::MAX))
+        .collect();
+
+    if paths.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(paths.join("\n")))
+}
+
+fn match_kind(needle: &str, relative: &str, matching: &MatchSettings) -> Option<MatchKind> {
+    let haystack = matching.fold(relative);
+    let name = haystack.rsplit('/').next().unwrap_or(&haystack);
+
+    if haystack.starts_with(needle) {
+        Some(MatchKind::PathPrefix)
+    } else if name.starts_with(needle) {
+        Some(MatchKind::NamePrefix)
+    } else if haystack.contains(needle) {
+        Some(MatchKind::Substring)
+    } else if resolve::is_subsequence(needle, &haystack) {
+        Some(MatchKind::Subsequence)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case("fold", "folder/child-note.md", Some(MatchKind::PathPrefix) ; "path prefix")]
+    #[test_case("child", "folder/child-note.md", Some(MatchKind::NamePrefix) ; "name prefix")]
+    #[test_case("note", "folder/child-note.md", Some(MatchKind::Substring) ; "substring")]
+    #[test_case("fcn", "folder/child-note.md", Some(MatchKind::Subsequence) ; "subsequence")]
+    #[test_case("xyz", "folder/child-note.md", None ; "no match")]
+    fn match_kind_ranks_matches(needle: &str, relative: &str, expected: Option<MatchKind>) {
+        assert_eq!(
+            match_kind(needle, relative, &MatchSettings::default()),
+            expected
+        );
+    }
+}
@@ -0,0 +1,38 @@
+use crate::{
+    cache::MetadataCache,
+    matching::MatchSettings,
+    util::{get_current_vault, CommandResult},
+};
+use clap::Args;
+
+#[derive(Args, Debug, Clone)]
+pub struct CompleteTagsCommand {
+    #[arg(
+        default_value = "",
+        help = "What has been typed so far, with or without the `#`, matched against the start of tags"
+    )]
+    prefix: String,
+
+    /// Print at most this many tags
+    #[arg(long, short = 'n')]
+    limit: Option<usize>,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+pub fn entry(cmd: &CompleteTagsCommand) -> CommandResult {
+    let vault = get_current_vault(cmd.vault.clone())?;
+
+    let tags: Vec<String> = MetadataCache::current(&vault.path)?
+        .complete_tag(&cmd.prefix, &MatchSettings::read())
+        .into_iter()
+        .take(cmd.limit.unwrap_or(usize::MAX))
+        .collect();
+
+    if tags.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(tags.join("\n")))
+}
@@ -0,0 +1,252 @@
+use crate::util::CommandResult;
+use clap::Args;
+use clap_complete::Shell;
+
+#[derive(Args, Debug, Clone)]
+pub struct CompletionsCommand {
+    /// The shell to print completions for. Vault names, note names and tags
+    /// are completed dynamically in bash, zsh, fish and powershell.
+    shell: Shell,
+}
+
+pub fn entry(cmd: &CompletionsCommand, mut command: clap::Command) -> CommandResult {
+    let note_commands = commands_taking(&command, "note");
+    let vault_commands = commands_taking(&command, "vault");
+
+    let mut script = Vec::new();
+    clap_complete::generate(cmd.shell, &mut command, "obx", &mut script);
+    let script = String::from_utf8(script)?;
+
+    let script = match cmd.shell {
+        Shell::Bash => format!(
+            "{script}\n{}",
+            bash_completions(&note_commands, &vault_commands)
+        ),
+        Shell::Zsh => zsh_completions(&script),
+        Shell::Fish => format!(
+            "{script}\n{}",
+            fish_completions(&note_commands, &vault_commands)
+        ),
+        Shell::PowerShell => powershell_completions(&script, &note_commands, &vault_commands),
+        _ => script,
+    };
+
+    Ok(Some(script.trim_end().to_string()))
+}
+
+/// The space separated paths of every subcommand, aliases included, whose
+/// first positional argument is `arg`
+fn commands_taking(command: &clap::Command, arg: &str) -> Vec<String> {
+    let mut paths = Vec::new();
+
+    for sub in command.get_subcommands() {
+        let takes_arg = sub
+            .get_positionals()
+            .next()
+            .is_some_and(|positional| positional.get_id() == arg);
+        let nested = commands_taking(sub, arg);
+
+        for name in std::iter::once(sub.get_name()).chain(sub.get_all_aliases()) {
+            if takes_arg {
+                paths.push(name.to_string());
+            }
+            paths.extend(nested.iter().map(|path| format!("{name} {path}")));
+        }
+    }
+
+    paths
+}
+
+/// Wraps the generated `_obx` so `--vault` values, notes and tags are looked
+/// up when completing
+fn bash_completions(note_commands: &[String], vault_commands: &[String]) -> String {
+    let patterns = |commands: &[String]| {
+        commands
+            .iter()
+            .map(|c| format!("\"{c}\""))
+            .collect::<Vec<_>>()
+    };
+
+    format!(
+        r#"_obx_dynamic() {{
+    local cur="${{COMP_WORDS[COMP_CWORD]}}" prev="${{COMP_WORDS[COMP_CWORD-1]}}"
+    local IFS=$'\n'
+
+    if [[ "$prev" == "--vault" || "$prev" == "-v" ]]; then
+        COMPREPLY=($(compgen -W "$(obx vaults list --format names 2>/dev/null)" -- "$cur"))
+        return 0
+    fi
+
+    if [[ "$prev" == "--tag" ]]; then
+        COMPREPLY=($(obx complete-tags "$cur" 2>/dev/null))
+        return 0
+    fi
+
+    local command="" word
+    for word in "${{COMP_WORDS[@]:1:COMP_CWORD-1}}"; do
+        [[ "$word" == -* ]] && break
+        command="${{command:+$command }}$word"
+    done
+
+    if [[ "$cur" != -* ]]; then
+        case "$command" in
+            {note_patterns})
+                COMPREPLY=($(obx complete-paths "$cur" 2>/dev/null))
+                return 0
+                ;;
+            {vault_patterns})
+                COMPREPLY=($(compgen -W "$(obx vaults list --format names 2>/dev/null)" -- "$cur"))
+                return 0
+                ;;
+            "meta set tags")
+                COMPREPLY=($(obx complete-tags "$cur" 2>/dev/null))
+                return 0
+                ;;
+        esac
+    fi
+
+    IFS=$' \t\n'
+    _obx "$@"
+}}
+
+complete -F _obx_dynamic -o bashdefault -o default obx
+"#,
+        note_patterns = patterns(note_commands).join("|"),
+        vault_patterns = patterns(vault_commands).join("|"),
+    )
+}
+
+/// Points the generated `_arguments` specs for vaults, notes and tags at
+/// helper functions that ask obx for candidates
+fn zsh_completions(script: &str) -> String {
+    let helpers = r#"_obx_vaults() {
+    local -a vaults
+    vaults=("${(@f)$(obx vaults list --format names 2>/dev/null)}")
+    compadd -a vaults
+}
+
+_obx_notes() {
+    local -a notes
+    notes=("${(@f)$(obx complete-paths "$PREFIX" 2>/dev/null)}")
+    compadd -U -V notes -a notes
+}
+
+_obx_tags() {
+    local -a tags
+    tags=("${(@f)$(obx complete-tags "$PREFIX" 2>/dev/null)}")
+    compadd -U -V tags -a tags
+}
+
+# Values for a key, which are tags after `tags`
+_obx_values() {
+    if [[ "$words[CURRENT-1]" == tags ]]; then
+        _obx_tags
+    else
+        _default
+    fi
+}
+
+"#;
+
+    let script: String = script
+        .lines()
+        .map(|line| {
+            let spec = line.trim_start_matches('\'').trim_start_matches(':');
+            if line.contains(":VAULT:_default'") || spec.starts_with("vault -- ") {
+                line.replace(":_default'", ":_obx_vaults'")
+            } else if line.starts_with('\'') && spec.starts_with("note -- ") {
+                line.replace(":_default'", ":_obx_notes'")
+            } else if line.contains(":TAG:_default'") {
+                line.replace(":_default'", ":_obx_tags'")
+            } else if line.starts_with('\'') && spec.starts_with("value -- ") {
+                line.replace(":_default'", ":_obx_values'")
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    match script.rfind("if [ \"$funcstack[1]\" = \"_obx\" ]") {
+        Some(idx) => format!("{}{helpers}{}", &script[..idx], &script[idx..]),
+        None => format!("{script}\n{helpers}"),
+    }
+}
+
+fn fish_completions(note_commands: &[String], vault_commands: &[String]) -> String {
+    let vaults = "(obx vaults list --format names 2>/dev/null)";
+    let notes = "(obx complete-paths (commandline -ct) 2>/dev/null)";
+    let tags = "(obx complete-tags (commandline -ct) 2>/dev/null)";
+    let mut lines = vec![
+        format!("complete -c obx -s v -l vault -x -a '{vaults}'"),
+        format!("complete -c obx -l tag -x -a '{tags}'"),
+        format!(
+            "complete -c obx -n \"__fish_obx_using_subcommand meta; and __fish_seen_subcommand_from set; and test (commandline -opc)[-1] = tags\" -f -a '{tags}'"
+        ),
+    ];
+
+    let candidates = note_commands
+        .iter()
+        .map(|command| (command, notes))
+        .chain(vault_commands.iter().map(|command| (command, vaults)));
+    for (command, candidates) in candidates {
+        let condition = match command.split_once(' ') {
+            Some((parent, sub)) => format!(
+                "__fish_obx_using_subcommand {parent}; and __fish_seen_subcommand_from {sub}"
+            ),
+            None => format!("__fish_obx_using_subcommand {command}"),
+        };
+        lines.push(format!(
+            "complete -c obx -n \"{condition}\" -f -a '{candidates}'"
+        ));
+    }
+
+    lines.join("\n")
+}
+
+/// Answers for vaults, notes and tags before the generated completer's static
+/// ones
+fn powershell_completions(
+    script: &str,
+    note_commands: &[String],
+    vault_commands: &[String],
+) -> String {
+    let commands = |commands: &[String]| {
+        commands
+            .iter()
+            .map(|c| format!("'obx;{}'", c.replace(' ', ";")))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let dynamic = format!(
+        r#"
+    $previous = if ($wordToComplete) {{ $commandElements[-2] }} else {{ $commandElements[-1] }}
+    $positional = -not $wordToComplete.StartsWith('-')
+    $candidates = if ("$previous" -in @('--vault', '-v')) {{
+        obx vaults list --format names 2>$null
+    }} elseif ("$previous" -eq '--tag' -or ($positional -and $command -eq 'obx;meta;set' -and "$previous" -eq 'tags')) {{
+        obx complete-tags $wordToComplete 2>$null
+    }} elseif ($positional -and $command -in @({note_commands})) {{
+        obx complete-paths $wordToComplete 2>$null
+    }} elseif ($positional -and $command -in @({vault_commands})) {{
+        obx vaults list --format names 2>$null | Where-Object {{ $_ -like "$wordToComplete*" }}
+    }}
+    if ($candidates) {{
+        return $candidates | ForEach-Object {{
+            [CompletionResult]::new($_, $_, [CompletionResultType]::ParameterValue, $_)
+        }}
+    }}
+"#,
+        note_commands = commands(note_commands),
+        vault_commands = commands(vault_commands),
+    );
+
+    match script.find("}) -join ';'\n") {
+        Some(idx) => {
+            let end = idx + "}) -join ';'\n".len();
+            format!("{}{dynamic}{}", &script[..end], &script[end..])
+        }
+        None => script.to_string(),
+    }
+}
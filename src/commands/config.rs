@@ -1,6 +1,20 @@
-use crate::{cli_config, theme::ThemeName, util::CommandResult};
-use anyhow::Context;
+use crate::{
+    cli_config,
+    commands::open::open_in_editor,
+    scan::FileSize,
+    secrets, theme,
+    util::{find_executable, CommandResult},
+};
+use anyhow::{anyhow, bail, Context};
+use atty::Stream;
 use clap::{Args, Subcommand};
+use dialoguer::Password;
+use ignore::gitignore::GitignoreBuilder;
+use std::{
+    env,
+    io::{self, Read},
+    path::Path,
+};
 
 #[derive(Args, Debug, Clone)]
 #[command(args_conflicts_with_subcommands = true)]
@@ -18,8 +32,27 @@ enum Subcommands {
     /// Print the absolute path to your config file
     Path,
 
-    /// Update editor or theme preferences
+    /// Print a config value by its dotted key, e.g. `vaults.work.path`
+    Get(GetArgs),
+
+    /// Update a config value by its dotted key, or the editor and theme
     Set(SetArgs),
+
+    /// Open your config file in your editor, creating it if needed, and
+    /// check it once the editor exits
+    Edit,
+
+    /// Check the config for problems, such as missing vault folders or an
+    /// editor that can't be found
+    Validate,
+
+    /// Rewrite the config file as YAML or TOML, keeping the original as a
+    /// `.bak` file
+    Convert(ConvertArgs),
+
+    /// Store tokens for integrations in the OS keychain or encrypted, so
+    /// they're never written to the config in plain text
+    Secret(SecretArgs),
 }
 
 #[derive(clap::ValueEnum, Clone, Debug)]
@@ -28,23 +61,48 @@ enum PrintFormats {
     Json,
 }
 
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+enum ConvertFormats {
+    Yaml,
+    Toml,
+}
+
+#[derive(Args, Debug, Clone)]
+struct ConvertArgs {
+    #[arg(long)]
+    to: ConvertFormats,
+}
+
 #[derive(Args, Debug, Clone)]
 struct PrintArgs {
     #[arg(long, short = 'f', default_value = "yaml")]
     format: PrintFormats,
+
+    /// Print the effective config after merging any `include:` files
+    #[arg(long)]
+    resolved: bool,
 }
 
 pub fn entry(cmd: &ConfigCommand) -> anyhow::Result<Option<String>> {
     match &cmd.command {
-        Some(Subcommands::Print(PrintArgs { format })) => print(format),
+        Some(Subcommands::Print(PrintArgs { format, resolved })) => print(format, *resolved),
         Some(Subcommands::Path) => path(),
+        Some(Subcommands::Get(args)) => get(args),
         Some(Subcommands::Set(args)) => set(args),
+        Some(Subcommands::Edit) => edit(),
+        Some(Subcommands::Validate) => validate(),
+        Some(Subcommands::Convert(ConvertArgs { to })) => convert(*to),
+        Some(Subcommands::Secret(args)) => secret(&args.command),
         None => todo!(),
     }
 }
 
-fn print(format: &PrintFormats) -> CommandResult {
-    let config = cli_config::read()?;
+fn print(format: &PrintFormats, resolved: bool) -> CommandResult {
+    let config = if resolved {
+        serde_yaml::to_value(cli_config::read()?)?
+    } else {
+        cli_config::read_raw()?
+    };
 
     let res = match format {
         PrintFormats::Yaml => serde_yaml::to_string(&config)?,
@@ -63,34 +121,428 @@ fn path() -> CommandResult {
     Ok(Some(config_path))
 }
 
+fn edit() -> CommandResult {
+    let config_path = cli_config::create_default()?;
+
+    open_in_editor(&config_path, None, None)?;
+    cli_config::validate()?;
+
+    Ok(None)
+}
+
+fn convert(to: ConvertFormats) -> CommandResult {
+    let format = match to {
+        ConvertFormats::Yaml => cli_config::ConfigFormat::Yaml,
+        ConvertFormats::Toml => cli_config::ConfigFormat::Toml,
+    };
+
+    let (converted_path, backup_path) = cli_config::convert(format)?;
+
+    Ok(Some(format!(
+        "Converted config to {}, the original is kept at {}",
+        converted_path.display(),
+        backup_path.display()
+    )))
+}
+
+fn validate() -> CommandResult {
+    let config_path = cli_config::get_config_path();
+
+    let problems = match cli_config::validate() {
+        Ok(config) => config_problems(&config),
+        Err(err) => vec![err.to_string()],
+    };
+
+    if !problems.is_empty() {
+        let list: Vec<String> = problems.iter().map(|p| format!("  - {p}")).collect();
+        bail!(
+            "Found {} problem(s) in {}:\n{}",
+            problems.len(),
+            config_path.display(),
+            list.join("\n")
+        );
+    }
+
+    Ok(Some(format!("{} is valid", config_path.display())))
+}
+
+/// Everything wrong with a config that parsed, each as an actionable message
+fn config_problems(config: &cli_config::Config) -> Vec<String> {
+    let mut problems = Vec::new();
+    let vault_names: Vec<&str> = config.vaults.iter().map(|v| v.name.as_str()).collect();
+
+    for (idx, vault) in config.vaults.iter().enumerate() {
+        if vault_names[..idx].contains(&vault.name.as_str()) {
+            problems.push(format!(
+                "Vault `{}` is listed more than once, remove or rename one",
+                vault.name
+            ));
+        }
+        if !vault.path.exists() {
+            problems.push(format!(
+                "Vault `{}` points at {}, which doesn't exist",
+                vault.name,
+                vault.path.display()
+            ));
+        } else if !vault.path.is_dir() {
+            problems.push(format!(
+                "Vault `{}` points at {}, which isn't a directory",
+                vault.name,
+                vault.path.display()
+            ));
+        }
+    }
+
+    // Settings the active profile overrides hold the profile's values, which
+    // are checked along with the other profiles
+    let active_profile = config
+        .active_profile
+        .as_ref()
+        .and_then(|name| config.profiles.get(name));
+    let overridden = |set: fn(&cli_config::Profile) -> bool| active_profile.is_some_and(set);
+
+    let mut vault_refs = Vec::new();
+    let uses_default = config.current_vault.is_empty() && config.default_vault.is_some();
+    if !overridden(|profile| profile.vault.is_some()) && !uses_default {
+        vault_refs.push(("`current_vault`".to_string(), &config.current_vault));
+    }
+    if let Some(vault) = &config.default_vault {
+        vault_refs.push(("`default_vault`".to_string(), vault));
+    }
+    for (name, profile) in &config.profiles {
+        if let Some(vault) = &profile.vault {
+            vault_refs.push((format!("Profile `{name}`"), vault));
+        }
+    }
+    for (setting, vault) in vault_refs {
+        if !vault_names.contains(&vault.as_str()) {
+            problems.push(format!(
+                "{setting} refers to vault `{vault}`, which isn't registered, see `obx vaults list`"
+            ));
+        }
+    }
+
+    let mut editors = Vec::new();
+    if let Some(editor) = &config.editor {
+        if !overridden(|profile| profile.editor.is_some()) {
+            editors.push(("`editor`".to_string(), editor));
+        }
+    }
+    for (name, profile) in &config.profiles {
+        if let Some(editor) = &profile.editor {
+            editors.push((format!("Profile `{name}` editor"), editor));
+        }
+    }
+    for vault in &config.vaults {
+        if let Some(editor) = &vault.editor {
+            editors.push((format!("Vault `{}` editor", vault.name), editor));
+        }
+    }
+    for (setting, editor) in editors {
+        if let Some(problem) = editor_problem(editor) {
+            problems.push(format!("{setting} {problem}"));
+        }
+    }
+
+    let theme_names = theme::theme_names(&config.themes);
+    let mut theme_refs = Vec::new();
+    if !overridden(|profile| profile.theme.is_some()) {
+        theme_refs.push(("`theme`".to_string(), &config.theme));
+    }
+    for (name, profile) in &config.profiles {
+        if let Some(theme) = &profile.theme {
+            theme_refs.push((format!("Profile `{name}` theme"), theme));
+        }
+    }
+    for vault in &config.vaults {
+        if let Some(theme) = &vault.theme {
+            theme_refs.push((format!("Vault `{}` theme", vault.name), theme));
+        }
+    }
+    for (setting, theme) in theme_refs {
+        if !theme_names.contains(theme) {
+            problems.push(format!(
+                "{setting} is set to unknown theme `{theme}`, see `themes` or the built-in themes"
+            ));
+        }
+    }
+    for name in config.themes.keys() {
+        if let Err(err) = theme::resolve(name, &config.themes) {
+            problems.push(err.to_string());
+        }
+    }
+
+    for (name, value) in &config.secrets {
+        if !secrets::is_reference(value) {
+            problems.push(format!(
+                "Secret `{name}` is stored in plain text, move it with `obx config secret set {name}`"
+            ));
+        }
+    }
+
+    if let Err(err) = config.dates.locale() {
+        problems.push(err.to_string());
+    }
+
+    for pattern in &config.ignore {
+        if let Err(err) = GitignoreBuilder::new("").add_line(None, pattern) {
+            problems.push(format!("`ignore` pattern `{pattern}` is invalid: {err}"));
+        }
+    }
+    if let Some(Err(err)) = config.scan.max_file_size.as_ref().map(FileSize::bytes) {
+        problems.push(format!("`scan.max_file_size` is invalid: {err}"));
+    }
+
+    if let Some(pandoc) = &config.pandoc {
+        if !pandoc.is_file() {
+            problems.push(format!(
+                "`pandoc` points at {}, which doesn't exist",
+                pandoc.display()
+            ));
+        }
+    }
+
+    problems
+}
+
+fn editor_problem(editor: &str) -> Option<String> {
+    let program = match shell_words::split(editor) {
+        Ok(words) => words.into_iter().next(),
+        Err(_) => return Some(format!("`{editor}` has unbalanced quotes")),
+    };
+
+    match program {
+        None => Some("is empty, remove it to use $EDITOR".to_string()),
+        Some(program) => missing_program(&program),
+    }
+}
+
+fn missing_program(program: &str) -> Option<String> {
+    if find_executable(program).is_some() {
+        None
+    } else if Path::new(program).is_file() {
+        Some(format!(
+            "`{program}` isn't executable, check its permissions"
+        ))
+    } else {
+        Some(format!(
+            "`{program}` wasn't found, check it's installed and on your $PATH"
+        ))
+    }
+}
+
+/// Editors tried by `--editor-detect` after $VISUAL and $EDITOR, in order
+const COMMON_EDITORS: [&str; 8] = [
+    "nvim", "vim", "hx", "micro", "nano", "emacs", "vi", "notepad",
+];
+
+/// The first usable editor, and where it came from
+fn detect_editor() -> anyhow::Result<(String, String)> {
+    for var in ["VISUAL", "EDITOR"] {
+        let Some(editor) = env::var(var)
+            .ok()
+            .filter(|editor| !editor.trim().is_empty())
+        else {
+            continue;
+        };
+        if editor_problem(&editor).is_none() {
+            return Ok((editor, format!("${var}")));
+        }
+    }
+
+    COMMON_EDITORS
+        .iter()
+        .find(|editor| find_executable(editor).is_some())
+        .map(|editor| (editor.to_string(), "$PATH".to_string()))
+        .context("Couldn't find an editor, set $VISUAL or $EDITOR, or pass --editor")
+}
+
+#[derive(Args, Debug, Clone)]
+struct GetArgs {
+    /// A dotted key, where list entries are picked by name or index
+    key: String,
+}
+
+fn get(args: &GetArgs) -> CommandResult {
+    let value = cli_config::read()?.get_key(&args.key)?;
+
+    let res = match value {
+        serde_yaml::Value::String(value) => value,
+        value => serde_yaml::to_string(&value)?.trim_end().to_string(),
+    };
+
+    Ok(Some(res))
+}
+
 #[derive(Args, Debug, Clone)]
 struct SetArgs {
+    /// A dotted key such as `vaults.work.path` or `themes.mine.accent`
+    #[arg(conflicts_with_all = ["editor", "theme", "clear_editor", "editor_detect"])]
+    key: Option<String>,
+    /// The new value, read as YAML
+    #[arg(requires = "key")]
+    value: Option<String>,
+    /// Remove the key instead of setting it
+    #[arg(long, requires = "key", conflicts_with = "value")]
+    unset: bool,
+    /// The editor command, which is checked for on your $PATH
     #[arg(long)]
     editor: Option<String>,
-    #[arg(long, value_enum)]
-    theme: Option<ThemeName>,
+    /// A built-in theme or one defined under `themes` in the config
+    #[arg(long)]
+    theme: Option<String>,
     #[arg(long, conflicts_with = "editor")]
     clear_editor: bool,
+    /// Use $VISUAL, $EDITOR or the first common editor that's installed
+    #[arg(long, conflicts_with_all = ["editor", "clear_editor"])]
+    editor_detect: bool,
 }
 
 fn set(args: &SetArgs) -> CommandResult {
-    if args.editor.is_none() && args.theme.is_none() && !args.clear_editor {
+    if let Some(key) = &args.key {
+        return set_key(key, args.value.as_deref(), args.unset);
+    }
+
+    if args.editor.is_none() && args.theme.is_none() && !args.clear_editor && !args.editor_detect {
         return Ok(Some("Nothing to update".to_string()));
     }
 
     let mut config = cli_config::read()?;
 
+    let mut messages = Vec::new();
     if args.clear_editor {
         config.editor = None;
     } else if let Some(editor) = &args.editor {
+        let words = shell_words::split(editor)
+            .map_err(|_| anyhow!("Editor `{editor}` has unbalanced quotes"))?;
+        let Some(program) = words.first() else {
+            bail!("The editor is empty, use --clear-editor to fall back to $EDITOR");
+        };
+        if let Some(problem) = missing_program(program) {
+            eprintln!("Warning: {problem}");
+        }
         config.editor = Some(editor.clone());
+    } else if args.editor_detect {
+        let (editor, source) = detect_editor()?;
+        messages.push(format!("Using editor `{editor}` from {source}"));
+        config.editor = Some(editor);
+    }
+
+    if let Some(theme) = &args.theme {
+        theme::resolve(theme, &config.themes)?;
+        config.theme = theme.clone();
+    }
+
+    cli_config::write(&config)?;
+    messages.push("Configuration updated".to_string());
+
+    Ok(Some(messages.join("\n")))
+}
+
+fn set_key(key: &str, value: Option<&str>, unset: bool) -> CommandResult {
+    if value.is_none() && !unset {
+        bail!("Give a value for `{key}`, or --unset to remove it");
     }
 
-    if let Some(theme) = args.theme {
-        config.theme = theme;
+    let config = cli_config::read()?.with_key(key, value)?;
+    if key.starts_with("dates") {
+        config.dates.locale()?;
+    }
+    if key == "theme" {
+        config.resolve_theme()?;
+    } else if let Some(rest) = key.strip_prefix("themes.") {
+        let name = rest.split('.').next().unwrap_or(rest);
+        if config.themes.contains_key(name) {
+            theme::resolve(name, &config.themes)?;
+        }
     }
 
     cli_config::write(&config)?;
 
     Ok(Some("Configuration updated".to_string()))
 }
+
+#[derive(Args, Debug, Clone)]
+struct SecretArgs {
+    #[command(subcommand)]
+    command: SecretCommands,
+}
+
+#[derive(Debug, Subcommand, Clone)]
+enum SecretCommands {
+    /// Store a secret, read from stdin or asked for
+    Set {
+        name: String,
+
+        /// Encrypt it into the config with a passphrase, from
+        /// $OBX_SECRET_PASSPHRASE or asked for, instead of using the keychain
+        #[arg(long)]
+        encrypt: bool,
+    },
+
+    /// Print a secret's value
+    Get { name: String },
+
+    /// Forget a secret, removing it from the keychain too
+    #[command(alias = "rm")]
+    Remove { name: String },
+}
+
+fn secret(command: &SecretCommands) -> CommandResult {
+    let mut config = cli_config::read()?;
+
+    match command {
+        SecretCommands::Set { name, encrypt } => {
+            let value = read_secret(name)?;
+            let storage = if *encrypt {
+                secrets::Storage::Encrypted
+            } else {
+                secrets::Storage::Keyring
+            };
+
+            let reference = secrets::store(name, &value, storage)?;
+            if let Some(previous) = config.secrets.insert(name.clone(), reference.clone()) {
+                if previous != reference {
+                    secrets::forget(&previous)?;
+                }
+            }
+            cli_config::write(&config)?;
+
+            Ok(Some(match storage {
+                secrets::Storage::Keyring => format!("Saved `{name}` to the keychain"),
+                secrets::Storage::Encrypted => format!("Saved `{name}` encrypted in the config"),
+            }))
+        }
+        SecretCommands::Get { name } => Ok(Some(config.secret(name)?)),
+        SecretCommands::Remove { name } => {
+            let Some(reference) = config.secrets.remove(name) else {
+                bail!("No secret named `{name}`, see `obx config print`");
+            };
+            secrets::forget(&reference)?;
+            cli_config::write(&config)?;
+
+            Ok(Some(format!("Removed secret `{name}`")))
+        }
+    }
+}
+
+/// The secret's value from stdin when it's piped, otherwise asked for
+/// without echoing it
+fn read_secret(name: &str) -> anyhow::Result<String> {
+    let value = if atty::isnt(Stream::Stdin) {
+        let mut value = String::new();
+        io::stdin().read_to_string(&mut value)?;
+        value.trim_end_matches(['\r', '\n']).to_string()
+    } else {
+        Password::new()
+            .with_prompt(format!("Value for `{name}`"))
+            .interact()
+            .context("Couldn't prompt for the secret, pipe it in instead")?
+    };
+
+    if value.is_empty() {
+        bail!("The value for `{name}` is empty");
+    }
+
+    Ok(value)
+}
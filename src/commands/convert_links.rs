@@ -0,0 +1,134 @@
+use crate::{
+    link_resolver::LinkResolver,
+    resolve, scan,
+    util::{get_current_vault, CommandResult},
+};
+use anyhow::Context;
+use clap::{ArgGroup, Args};
+use libobsidian::links::{parse_links, replace_ranges, Link, LinkKind};
+use std::{fs, path::Path};
+
+#[derive(Args, Debug, Clone)]
+#[command(group(ArgGroup::new("notes").required(true).args(["path", "all"])))]
+pub struct ConvertLinksCommand {
+    /// The syntax to rewrite links in
+    #[arg(long)]
+    to: LinkSyntax,
+
+    #[arg(help = "The name or path of a note, or a vault-relative folder of notes")]
+    path: Option<String>,
+
+    /// Convert links in every note in the vault
+    #[arg(long)]
+    all: bool,
+
+    /// Show how many links each note would have converted without changing it
+    #[arg(long)]
+    dry_run: bool,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+enum LinkSyntax {
+    /// `[label](relative/path.md)`
+    Markdown,
+    /// `[[name|label]]`
+    Wikilink,
+}
+
+pub fn entry(cmd: &ConvertLinksCommand) -> CommandResult {
+    let vault = get_current_vault(cmd.vault.clone())?;
+    let mut notes = match &cmd.path {
+        Some(path) if vault.path.join(path).is_dir() => scan::note_paths(&vault.path.join(path))?,
+        Some(path) => vec![resolve::pick_note(path, &vault.path)?],
+        None => scan::note_paths(&vault.path)?,
+    };
+    let kind = match cmd.to {
+        LinkSyntax::Markdown => LinkKind::Markdown,
+        LinkSyntax::Wikilink => LinkKind::Wikilink,
+    };
+
+    let resolver = LinkResolver::new(&vault.path)?;
+    let verb = if cmd.dry_run {
+        "Would convert"
+    } else {
+        "Converted"
+    };
+    let mut output = Vec::new();
+    let mut unresolved = 0;
+    notes.sort();
+    for note_path in notes {
+        let contents = fs::read_to_string(&note_path)
+            .with_context(|| format!("Could not read note {}", note_path.display()))?;
+
+        let mut replacements = Vec::new();
+        for link in parse_links(&contents) {
+            if link.kind == kind || link.is_external() {
+                continue;
+            }
+            match convert(&resolver, &link, &note_path, kind) {
+                Some(converted) => replacements.push((link.range, converted)),
+                None => unresolved += 1,
+            }
+        }
+        if replacements.is_empty() {
+            continue;
+        }
+
+        let noun = if replacements.len() == 1 {
+            "link"
+        } else {
+            "links"
+        };
+        output.push(format!(
+            "{verb} {} {noun} in {}",
+            replacements.len(),
+            scan::relative_note_path(&note_path, &vault.path)
+        ));
+        if !cmd.dry_run {
+            fs::write(&note_path, replace_ranges(&contents, replacements))
+                .with_context(|| format!("Could not write to note {}", note_path.display()))?;
+        }
+    }
+
+    if output.is_empty() {
+        output.push("No links to convert".to_string());
+    }
+    if unresolved > 0 {
+        output.push(format!(
+            "Left {unresolved} links to missing files as they were"
+        ));
+    }
+    Ok(Some(output.join("\n")))
+}
+
+/// `link` written as a `kind` link to the same file, or `None` when the file
+/// it points to is missing. Markdown links get a path relative to `source`,
+/// wikilinks the shortest name that's unique in the vault.
+fn convert(resolver: &LinkResolver, link: &Link, source: &Path, kind: LinkKind) -> Option<String> {
+    let target = if link.target.is_empty() {
+        String::new()
+    } else {
+        let target_path = resolver.resolve(link, source)?;
+        resolver.link_target(&target_path, source, kind)
+    };
+
+    let mut converted = Link {
+        kind,
+        target: target.clone(),
+        label: None,
+        ..link.clone()
+    };
+    converted.label = match kind {
+        LinkKind::Markdown if link.embed => link.label.clone(),
+        LinkKind::Markdown => Some(link.display_text()),
+        // A label that only repeats what the wikilink shows anyway is dropped
+        LinkKind::Wikilink => link
+            .label
+            .clone()
+            .filter(|label| !label.is_empty() && *label != converted.display_text()),
+    };
+    Some(converted.with_target(&target))
+}
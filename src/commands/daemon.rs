@@ -0,0 +1,20 @@
+use crate::{
+    note_text,
+    tui::daemon,
+    util::{get_current_vault, CommandResult},
+};
+use clap::Args;
+
+#[derive(Args, Debug, Clone)]
+pub struct DaemonCommand {
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+pub fn entry(cmd: &DaemonCommand) -> CommandResult {
+    let vault = get_current_vault(cmd.vault.clone())?;
+    note_text::avoid_mapping();
+    daemon::serve(&vault)?;
+
+    Ok(None)
+}
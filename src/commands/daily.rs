@@ -0,0 +1,94 @@
+use crate::{
+    hooks::{self, Event},
+    periodic::{self, Period},
+    scan, template,
+    util::{get_current_vault, CommandResult},
+};
+use anyhow::{anyhow, Context};
+use chrono::{Local, NaiveDate};
+use clap::Args;
+use std::fs;
+
+#[derive(Args, Debug, Clone)]
+pub struct DailyCommand {
+    /// The weekly note rather than the daily one
+    #[arg(long, short = 'w')]
+    weekly: bool,
+
+    /// The note for this day, or the week holding it, rather than today
+    #[arg(long, short = 'd', value_name = "YYYY-MM-DD", value_parser = parse_date)]
+    date: Option<NaiveDate>,
+
+    /// Start the note with links to the notes before and after it, or
+    /// update the links already there
+    #[arg(long, short = 'l')]
+    link_neighbors: bool,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+pub fn entry(cmd: &DailyCommand) -> CommandResult {
+    let vault = get_current_vault(cmd.vault.clone())?;
+    let (settings, formats) = periodic::settings()?;
+    let period = if cmd.weekly {
+        Period::Week
+    } else {
+        Period::Day
+    };
+    let date = cmd.date.unwrap_or_else(|| Local::now().date_naive());
+    let start = period.start(date, &formats);
+    let note_path = periodic::note_path(&vault.path, &settings, period, start, &formats);
+    let relative = scan::relative_note_path(&note_path, &vault.path);
+    let link_neighbors = cmd.link_neighbors || settings.link_neighbors;
+
+    if note_path.exists() {
+        if cmd.link_neighbors {
+            let contents = fs::read_to_string(&note_path)
+                .with_context(|| format!("Could not read note {}", note_path.display()))?;
+            let linked =
+                periodic::set_navigation(&contents, &periodic::navigation(period, start, &formats));
+            if linked != contents {
+                fs::write(&note_path, linked)
+                    .with_context(|| format!("Could not write to note {}", note_path.display()))?;
+            }
+        }
+        hooks::run(Event::AfterDaily, &vault, &note_path, None)?;
+        return Ok(Some(relative));
+    }
+
+    let mut contents = match period.template(&settings) {
+        Some(name) => {
+            let template_path = template::find_template(&vault.path, name)?;
+            let template_contents = fs::read_to_string(&template_path)
+                .with_context(|| format!("Could not read template `{name}`"))?;
+            let mut context = template::TemplateContext::for_note(&note_path, &vault.path, &[]);
+            // `{{date}}` is the note's own day
+            if context.now.date_naive() != start {
+                context.now = periodic::at_noon(start);
+            }
+            template::render(&template_contents, &context)
+        }
+        None => String::new(),
+    };
+    if link_neighbors {
+        contents =
+            periodic::set_navigation(&contents, &periodic::navigation(period, start, &formats));
+    }
+
+    if let Some(parent) = note_path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Could not create directory {}", parent.display()))?;
+    }
+    fs::write(&note_path, contents)
+        .with_context(|| format!("Could not create note {}", note_path.display()))?;
+    hooks::run(Event::AfterCreate, &vault, &note_path, None)?;
+    hooks::run(Event::AfterDaily, &vault, &note_path, None)?;
+
+    Ok(Some(format!("Created note {relative}")))
+}
+
+fn parse_date(value: &str) -> anyhow::Result<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| anyhow!("invalid date `{value}`, expected one like 2024-05-10"))
+}
@@ -0,0 +1,484 @@
+use crate::{
+    cli_config::Vault,
+    hooks::{self, Event},
+    index::tokenize,
+    link_resolver::LinkResolver,
+    matching::MatchSettings,
+    scan, trash,
+    util::{get_current_vault, should_enable_interactivity, CommandResult},
+};
+use anyhow::{bail, Context};
+use clap::Args;
+use dialoguer::{theme::ColorfulTheme, Select};
+use libobsidian::{links::replace_ranges, ObsidianNote};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
+    fs,
+    hash::{Hash, Hasher},
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+/// Words in each overlapping run compared by `--fuzzy`
+const SHINGLE_WORDS: usize = 3;
+/// Runs of words found in more notes than this, like a template's headings,
+/// don't make notes candidates for comparing on their own
+const COMMON_SHINGLE_NOTES: usize = 50;
+
+#[derive(Args, Debug, Clone)]
+pub struct DedupeCommand {
+    /// Also find notes that are nearly the same, comparing overlapping runs
+    /// of words
+    #[arg(long)]
+    fuzzy: bool,
+
+    /// How alike notes have to be for `--fuzzy`, from 0 to 1
+    #[arg(
+        long,
+        default_value_t = 0.8,
+        value_name = "SIMILARITY",
+        requires = "fuzzy"
+    )]
+    threshold: f64,
+
+    /// Choose for each group whether to keep the notes, keep one and remove
+    /// the rest, or merge them into one
+    #[arg(long, short = 'i')]
+    interactive: bool,
+
+    /// Move removed notes to the vault's `.trash` folder instead of deleting
+    /// them
+    #[arg(long)]
+    trash: bool,
+
+    #[arg(long, short = 'f', default_value = "text")]
+    format: DedupeFormat,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+enum DedupeFormat {
+    Text,
+    Json,
+}
+
+/// Notes that are the same, or nearly
+#[derive(Serialize, Debug, PartialEq)]
+struct Group {
+    identical: bool,
+    /// How alike the least alike notes joined into the group are, from 0 to 1
+    similarity: f64,
+    notes: Vec<GroupNote>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+struct GroupNote {
+    path: String,
+    size: u64,
+}
+
+/// A note as compared by `dedupe`
+struct Candidate {
+    relative: String,
+    contents: String,
+    hash: Vec<u8>,
+}
+
+pub fn entry(cmd: &DedupeCommand) -> CommandResult {
+    let vault = get_current_vault(cmd.vault.clone())?;
+
+    if !(0.0..=1.0).contains(&cmd.threshold) {
+        bail!("--threshold must be between 0 and 1, not {}", cmd.threshold);
+    }
+    if cmd.interactive && !should_enable_interactivity() {
+        bail!("--interactive needs an interactive terminal");
+    }
+
+    let mut notes = Vec::new();
+    for note_path in scan::note_paths(&vault.path)? {
+        let Ok(contents) = fs::read_to_string(&note_path) else {
+            continue;
+        };
+        // Empty notes are all alike, and not worth reporting
+        if contents.trim().is_empty() {
+            continue;
+        }
+        notes.push(Candidate {
+            relative: scan::relative_note_path(&note_path, &vault.path),
+            hash: Sha256::digest(&contents).to_vec(),
+            contents,
+        });
+    }
+
+    let groups = find_groups(
+        &notes,
+        cmd.fuzzy.then_some(cmd.threshold),
+        &MatchSettings::read(),
+    );
+    if groups.is_empty() {
+        return Ok(None);
+    }
+
+    if cmd.interactive {
+        return resolve_interactive(&groups, &vault, cmd.trash);
+    }
+
+    match cmd.format {
+        DedupeFormat::Json => Ok(Some(serde_json::to_string(&groups)?)),
+        DedupeFormat::Text => Ok(Some(
+            groups
+                .iter()
+                .map(describe_group)
+                .collect::<Vec<String>>()
+                .join("\n\n"),
+        )),
+    }
+}
+
+/// Groups of notes with the same contents, along with notes at least
+/// `threshold` alike when it's given, identical groups first and then the
+/// most alike, comparing words as `matching` folds them
+fn find_groups(
+    notes: &[Candidate],
+    threshold: Option<f64>,
+    matching: &MatchSettings,
+) -> Vec<Group> {
+    let mut groups = DisjointSet::new(notes.len());
+    // The least alike pair joining each note to its group
+    let mut similarity = vec![1.0_f64; notes.len()];
+
+    let mut by_hash: HashMap<&[u8], usize> = HashMap::new();
+    for (idx, note) in notes.iter().enumerate() {
+        match by_hash.get(note.hash.as_slice()) {
+            Some(&first) => groups.join(first, idx),
+            None => {
+                by_hash.insert(&note.hash, idx);
+            }
+        }
+    }
+
+    if let Some(threshold) = threshold {
+        // Only the first of each set of identical notes needs comparing
+        let distinct: Vec<usize> = by_hash.values().copied().collect();
+        let shingles: Vec<HashSet<u64>> = notes
+            .iter()
+            .map(|note| shingles(&note.contents, matching))
+            .collect();
+
+        for (a, b) in candidate_pairs(&distinct, &shingles) {
+            let alike = jaccard(&shingles[a], &shingles[b]);
+            if alike >= threshold {
+                groups.join(a, b);
+                similarity[a] = similarity[a].min(alike);
+                similarity[b] = similarity[b].min(alike);
+            }
+        }
+    }
+
+    let mut members: HashMap<usize, Vec<usize>> = HashMap::new();
+    for idx in 0..notes.len() {
+        members.entry(groups.root(idx)).or_default().push(idx);
+    }
+
+    let mut found: Vec<Group> = members
+        .into_values()
+        .filter(|members| members.len() > 1)
+        .map(|mut members| {
+            members
+                .sort_by(|&a, &b| Path::new(&notes[a].relative).cmp(Path::new(&notes[b].relative)));
+            let first = &notes[members[0]].hash;
+            Group {
+                identical: members.iter().all(|&idx| notes[idx].hash == *first),
+                similarity: members
+                    .iter()
+                    .map(|&idx| similarity[idx])
+                    .fold(1.0, f64::min),
+                notes: members
+                    .iter()
+                    .map(|&idx| GroupNote {
+                        path: notes[idx].relative.clone(),
+                        size: notes[idx].contents.len() as u64,
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+
+    found.sort_by(|a, b| {
+        b.identical
+            .cmp(&a.identical)
+            .then_with(|| b.similarity.total_cmp(&a.similarity))
+            .then_with(|| a.notes[0].path.cmp(&b.notes[0].path))
+    });
+    found
+}
+
+/// Hashes of each run of [`SHINGLE_WORDS`] words in `contents`, or of all of
+/// them for shorter notes
+fn shingles(contents: &str, matching: &MatchSettings) -> HashSet<u64> {
+    let words = tokenize(contents, matching);
+    words
+        .windows(SHINGLE_WORDS.min(words.len()).max(1))
+        .map(|window| {
+            let mut hasher = DefaultHasher::new();
+            window.hash(&mut hasher);
+            hasher.finish()
+        })
+        .collect()
+}
+
+/// Pairs of notes from `distinct` sharing a run of words that isn't in most
+/// of the vault, so every note isn't compared with every other
+fn candidate_pairs(distinct: &[usize], shingles: &[HashSet<u64>]) -> Vec<(usize, usize)> {
+    let mut by_shingle: HashMap<u64, Vec<usize>> = HashMap::new();
+    for &idx in distinct {
+        for &shingle in &shingles[idx] {
+            by_shingle.entry(shingle).or_default().push(idx);
+        }
+    }
+
+    let mut pairs: HashSet<(usize, usize)> = HashSet::new();
+    for notes in by_shingle.values() {
+        if notes.len() > COMMON_SHINGLE_NOTES {
+            continue;
+        }
+        for (i, &a) in notes.iter().enumerate() {
+            for &b in &notes[i + 1..] {
+                pairs.insert((a.min(b), a.max(b)));
+            }
+        }
+    }
+
+    let mut pairs: Vec<(usize, usize)> = pairs.into_iter().collect();
+    pairs.sort_unstable();
+    pairs
+}
+
+/// The share of runs of words two notes have in common
+fn jaccard(a: &HashSet<u64>, b: &HashSet<u64>) -> f64 {
+    let shared = a.intersection(b).count();
+    let total = a.len() + b.len() - shared;
+    if total == 0 {
+        return 1.0;
+    }
+    shared as f64 / total as f64
+}
+
+fn describe_group(group: &Group) -> String {
+    let mut lines = Vec::new();
+    if group.identical {
+        lines.push(format!(
+            "Identical notes, {} bytes each:",
+            group.notes[0].size
+        ));
+        lines.extend(group.notes.iter().map(|note| format!("  {}", note.path)));
+    } else {
+        lines.push(format!(
+            "Similar notes, {:.0}% alike:",
+            group.similarity * 100.0
+        ));
+        lines.extend(
+            group
+                .notes
+                .iter()
+                .map(|note| format!("  {} ({} bytes)", note.path, note.size)),
+        );
+    }
+    lines.join("\n")
+}
+
+/// What to do with a group of notes
+enum Resolution<'g> {
+    KeepAll,
+    KeepOne(&'g GroupNote),
+    MergeInto(&'g GroupNote),
+}
+
+fn resolve_interactive(groups: &[Group], vault: &Vault, use_trash: bool) -> CommandResult {
+    let mut removed = 0;
+    let mut relinked = 0;
+
+    for group in groups {
+        eprintln!("{}", describe_group(group));
+
+        let mut choices = vec![Resolution::KeepAll];
+        choices.extend(group.notes.iter().map(Resolution::KeepOne));
+        if !group.identical {
+            choices.extend(group.notes.iter().map(Resolution::MergeInto));
+        }
+        let items: Vec<String> = choices
+            .iter()
+            .map(|choice| match choice {
+                Resolution::KeepAll => "Keep them all".to_string(),
+                Resolution::KeepOne(note) => format!("Keep {} and remove the others", note.path),
+                Resolution::MergeInto(note) => {
+                    format!("Merge the others into {} and remove them", note.path)
+                }
+            })
+            .collect();
+
+        let selection = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("What should happen to these notes?")
+            .items(&items)
+            .default(0)
+            .interact()
+            .context("couldn't prompt user for how to resolve duplicates")?;
+
+        let (kept, merge) = match choices[selection] {
+            Resolution::KeepAll => continue,
+            Resolution::KeepOne(note) => (note, false),
+            Resolution::MergeInto(note) => (note, true),
+        };
+        let kept_path = vault.path.join(&kept.path);
+        let others: Vec<PathBuf> = group
+            .notes
+            .iter()
+            .filter(|note| note.path != kept.path)
+            .map(|note| vault.path.join(&note.path))
+            .collect();
+
+        if merge {
+            merge_notes(&kept_path, &others)?;
+        }
+        relinked += relink(&vault.path, &others, &kept_path)?;
+
+        for note_path in &others {
+            hooks::run(Event::BeforeDelete, vault, note_path, None)?;
+            if use_trash {
+                trash::move_to_trash(&vault.path, note_path)?;
+            } else {
+                fs::remove_file(note_path)
+                    .with_context(|| format!("Could not delete {}", note_path.display()))?;
+            }
+            removed += 1;
+        }
+    }
+
+    let verb = if use_trash {
+        "Moved to the trash"
+    } else {
+        "Removed"
+    };
+    Ok(Some(format!(
+        "{verb} {removed} {}, updated {relinked} {}",
+        if removed == 1 { "note" } else { "notes" },
+        if relinked == 1 { "link" } else { "links" }
+    )))
+}
+
+/// Append the bodies of `others` to the note at `kept`, leaving out their
+/// frontmatter
+fn merge_notes(kept: &Path, others: &[PathBuf]) -> anyhow::Result<()> {
+    let mut contents = fs::read_to_string(kept)
+        .with_context(|| format!("Could not read note {}", kept.display()))?;
+
+    for other in others {
+        let other_contents = fs::read_to_string(other)
+            .with_context(|| format!("Could not read note {}", other.display()))?;
+        let body = ObsidianNote::parse(other, other_contents.clone())
+            .map(|note| note.file_body)
+            .unwrap_or(other_contents);
+
+        contents = format!("{}\n\n{}\n", contents.trim_end(), body.trim());
+    }
+
+    fs::write(kept, contents).with_context(|| format!("Could not write to note {}", kept.display()))
+}
+
+/// Point links to any of `removed` at `kept` instead, returning how many
+/// were rewritten
+fn relink(vault_path: &Path, removed: &[PathBuf], kept: &Path) -> anyhow::Result<usize> {
+    let resolver = LinkResolver::new(vault_path)?;
+    let mut relinked = 0;
+
+    for note in resolver.notes_linking_to(removed)? {
+        if removed.contains(&note.path) {
+            continue;
+        }
+        let replacements: Vec<(Range<usize>, String)> = note
+            .links
+            .iter()
+            .map(|(link, _)| {
+                (
+                    link.range.clone(),
+                    resolver.retarget(link, kept, &note.path),
+                )
+            })
+            .collect();
+        relinked += replacements.len();
+
+        fs::write(&note.path, replace_ranges(&note.contents, replacements))
+            .with_context(|| format!("Could not write to note {}", note.path.display()))?;
+    }
+
+    Ok(relinked)
+}
+
+/// Union-find over note indices
+struct DisjointSet {
+    parents: Vec<usize>,
+}
+
+impl DisjointSet {
+    fn new(len: usize) -> Self {
+        Self {
+            parents: (0..len).collect(),
+        }
+    }
+
+    fn root(&mut self, mut idx: usize) -> usize {
+        while self.parents[idx] != idx {
+            self.parents[idx] = self.parents[self.parents[idx]];
+            idx = self.parents[idx];
+        }
+        idx
+    }
+
+    fn join(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.root(a), self.root(b));
+        if a != b {
+            self.parents[b.max(a)] = a.min(b);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(relative: &str, contents: &str) -> Candidate {
+        Candidate {
+            relative: relative.to_string(),
+            contents: contents.to_string(),
+            hash: Sha256::digest(contents).to_vec(),
+        }
+    }
+
+    #[test]
+    fn groups_identical_and_similar_notes() {
+        let notes = [
+            candidate("a.md", "the quick brown fox jumps over the lazy dog"),
+            candidate("b.md", "Something else entirely, nothing alike"),
+            candidate("c.md", "the quick brown fox jumps over the lazy dog"),
+            candidate("d.md", "the quick brown fox jumps over the lazy cat"),
+        ];
+
+        let exact = find_groups(&notes, None, &MatchSettings::default());
+        assert_eq!(exact.len(), 1);
+        assert!(exact[0].identical);
+        assert_eq!(exact[0].notes[0].path, "a.md");
+        assert_eq!(exact[0].notes[1].path, "c.md");
+
+        let fuzzy = find_groups(&notes, Some(0.7), &MatchSettings::default());
+        assert_eq!(fuzzy.len(), 1);
+        assert!(!fuzzy[0].identical);
+        assert_eq!(fuzzy[0].notes.len(), 3);
+        assert!((fuzzy[0].similarity - 6.0 / 8.0).abs() < 1e-9);
+
+        assert!(find_groups(&notes[1..], Some(0.9), &MatchSettings::default()).is_empty());
+    }
+}
@@ -0,0 +1,90 @@
+use crate::{
+    hooks::{self, Event},
+    link_graph::LinkGraph,
+    resolve, scan, trash,
+    util::{get_current_vault, should_enable_interactivity, CommandResult},
+};
+use anyhow::{bail, Context};
+use clap::Args;
+use dialoguer::Confirm;
+use std::fs;
+
+#[derive(Args, Debug, Clone)]
+pub struct DeleteCommand {
+    #[arg(help = "The name or path of the note to delete")]
+    note: String,
+
+    /// Delete without asking for confirmation
+    #[arg(long)]
+    force: bool,
+
+    /// Move the note to the vault's `.trash` folder instead of deleting it
+    #[arg(long)]
+    trash: bool,
+
+    /// List the notes linking to the note, not just how many there are
+    #[arg(long, short = 'l')]
+    list_backlinks: bool,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+pub fn entry(cmd: &DeleteCommand) -> CommandResult {
+    let vault = get_current_vault(cmd.vault.clone())?;
+    let note_path = resolve::pick_note(&cmd.note, &vault.path)?;
+    let relative = scan::relative_note_path(&note_path, &vault.path);
+
+    let graph = LinkGraph::current(&vault.path)?;
+    let linking = graph.linking_notes(&relative);
+
+    let mut report = vec![match linking.len() {
+        0 => format!("No notes link to {relative}"),
+        1 => format!("1 note links to {relative}"),
+        count => format!("{count} notes link to {relative}"),
+    }];
+    if cmd.list_backlinks {
+        report.extend(linking.iter().map(|note| format!("  {note}")));
+    }
+
+    if !cmd.force {
+        if !should_enable_interactivity() {
+            bail!(
+                "{}\nRefusing to delete {relative} without confirmation, pass --force",
+                report.join("\n")
+            );
+        }
+
+        eprintln!("{}", report.join("\n"));
+        let action = if cmd.trash {
+            "Move"
+        } else {
+            "Permanently delete"
+        };
+        let confirmation = Confirm::new()
+            .with_prompt(format!(
+                "{action} {relative}{}?",
+                if cmd.trash { " to the trash" } else { "" }
+            ))
+            .interact()
+            .context("couldn't prompt user for confirmation to delete note")?;
+
+        if !confirmation {
+            return Ok(Some("Aborted".to_string()));
+        }
+        report.clear();
+    }
+
+    hooks::run(Event::BeforeDelete, &vault, &note_path, None)?;
+
+    if cmd.trash {
+        trash::move_to_trash(&vault.path, &note_path)?;
+        report.push(format!("Moved {relative} to the trash"));
+    } else {
+        fs::remove_file(&note_path)
+            .with_context(|| format!("Could not delete {}", note_path.display()))?;
+        report.push(format!("Deleted {relative}"));
+    }
+
+    Ok(Some(report.join("\n")))
+}
@@ -0,0 +1,139 @@
+use crate::{
+    resolve, scan, style,
+    util::{get_current_vault, CommandResult},
+};
+use anyhow::{bail, Context};
+use clap::Args;
+use crossterm::style::Stylize;
+use similar::TextDiff;
+use std::{fs, path::Path, process};
+
+#[derive(Args, Debug, Clone)]
+pub struct DiffCommand {
+    #[arg(help = "The name or path of the note, matched exactly or fuzzily within the vault")]
+    note: String,
+
+    #[arg(
+        help = "The note to compare against",
+        required_unless_present = "git",
+        conflicts_with = "git"
+    )]
+    other: Option<String>,
+
+    /// Compare against the note as of a git revision, HEAD if not given
+    #[arg(long, value_name = "REV", num_args = 0..=1, default_missing_value = "HEAD")]
+    git: Option<String>,
+
+    /// Lines of context around each change
+    #[arg(long, short = 'U', default_value_t = 3)]
+    context: usize,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+pub fn entry(cmd: &DiffCommand) -> CommandResult {
+    let vault = get_current_vault(cmd.vault.clone())?;
+    let note_path = resolve::pick_note(&cmd.note, &vault.path)?;
+    let note_name = scan::relative_note_path(&note_path, &vault.path);
+
+    let (old, old_name, new, new_name) = match (&cmd.other, &cmd.git) {
+        (_, Some(rev)) => (
+            committed_contents(&note_path, &note_name, rev)?,
+            format!("{note_name} ({rev})"),
+            read_note(&note_path)?,
+            note_name,
+        ),
+        (Some(other), None) => {
+            let other_path = resolve::pick_note(other, &vault.path)?;
+            (
+                read_note(&note_path)?,
+                note_name,
+                read_note(&other_path)?,
+                scan::relative_note_path(&other_path, &vault.path),
+            )
+        }
+        (None, None) => unreachable!("clap requires either a second note or --git"),
+    };
+
+    let diff = TextDiff::from_lines(&old, &new);
+    let unified = diff
+        .unified_diff()
+        .context_radius(cmd.context)
+        .header(&old_name, &new_name)
+        .missing_newline_hint(false)
+        .to_string();
+
+    if unified.is_empty() {
+        return Ok(None);
+    }
+
+    let output = unified.trim_end_matches('\n');
+    if !style::enabled() {
+        return Ok(Some(output.to_string()));
+    }
+
+    Ok(Some(
+        output
+            .lines()
+            .enumerate()
+            .map(|(idx, line)| color_line(idx, line))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    ))
+}
+
+fn read_note(path: &Path) -> anyhow::Result<String> {
+    fs::read_to_string(path).with_context(|| format!("Could not read note {}", path.display()))
+}
+
+/// The note's contents at `rev`, read with `git show` from the note's folder
+/// so vaults nested inside a larger repository work too
+fn committed_contents(note_path: &Path, note_name: &str, rev: &str) -> anyhow::Result<String> {
+    let folder = note_path.parent().expect("note_path should have a parent");
+    let file_name = note_path
+        .file_name()
+        .expect("note_path should have a file name")
+        .to_string_lossy();
+
+    let inside_repo = process::Command::new("git")
+        .arg("-C")
+        .arg(folder)
+        .args(["rev-parse", "--is-inside-work-tree"])
+        .output()
+        .context("Failed to run git, is it installed?")?;
+    if !inside_repo.status.success() {
+        bail!("{note_name} is not inside a git repository");
+    }
+
+    let shown = process::Command::new("git")
+        .arg("-C")
+        .arg(folder)
+        .arg("show")
+        .arg(format!("{rev}:./{file_name}"))
+        .output()
+        .context("Failed to run git")?;
+    if !shown.status.success() {
+        bail!(
+            "Could not read {note_name} at `{rev}`: {}",
+            String::from_utf8_lossy(&shown.stderr).trim()
+        );
+    }
+
+    Ok(String::from_utf8_lossy(&shown.stdout).into_owned())
+}
+
+/// Color a line of the diff, where the first two lines are the file headers
+fn color_line(idx: usize, line: &str) -> String {
+    if idx < 2 {
+        line.bold().to_string()
+    } else if line.starts_with("@@") {
+        line.cyan().to_string()
+    } else if line.starts_with('-') {
+        line.red().to_string()
+    } else if line.starts_with('+') {
+        line.green().to_string()
+    } else {
+        line.to_string()
+    }
+}
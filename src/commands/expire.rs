@@ -0,0 +1,140 @@
+use crate::{
+    scan, trash,
+    util::{get_current_vault, should_enable_interactivity, CommandResult},
+};
+use anyhow::{bail, Context};
+use chrono::{Local, NaiveDate};
+use clap::Args;
+use dialoguer::Confirm;
+use libobsidian::ObsidianNote;
+use std::path::PathBuf;
+use tabled::{builder::Builder, settings::Style};
+
+#[derive(Args, Debug, Clone)]
+pub struct ExpireCommand {
+    /// Move expired notes to the vault's `.trash` folder instead of listing them
+    #[arg(long)]
+    trash: bool,
+
+    /// Don't ask for confirmation before trashing notes
+    #[arg(long, short = 'y', requires = "trash")]
+    yes: bool,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+struct ExpiredNote {
+    path: PathBuf,
+    relative: String,
+    expires: NaiveDate,
+}
+
+pub fn entry(cmd: &ExpireCommand) -> CommandResult {
+    let vault = get_current_vault(cmd.vault.clone())?;
+    let today = Local::now().date_naive();
+
+    let mut expired = Vec::new();
+    for note_path in scan::note_paths(&vault.path)? {
+        let Ok(note) = ObsidianNote::read_from_path(&note_path) else {
+            continue;
+        };
+        let Some(expires) = expiry_date(&note) else {
+            continue;
+        };
+
+        if expires < today {
+            expired.push(ExpiredNote {
+                relative: scan::relative_note_path(&note_path, &vault.path),
+                path: note_path,
+                expires,
+            });
+        }
+    }
+
+    if expired.is_empty() {
+        return Ok(None);
+    }
+
+    if !cmd.trash {
+        return Ok(Some(format_table(&expired)));
+    }
+
+    if !cmd.yes {
+        if !should_enable_interactivity() {
+            bail!(
+                "Refusing to trash {} expired notes without confirmation, pass --yes",
+                expired.len()
+            );
+        }
+
+        eprintln!("{}", format_table(&expired));
+        let confirmation = Confirm::new()
+            .with_prompt(format!(
+                "Move {} expired notes to the trash?",
+                expired.len()
+            ))
+            .interact()
+            .context("couldn't prompt user for confirmation to trash notes")?;
+
+        if !confirmation {
+            return Ok(Some("Aborted".to_string()));
+        }
+    }
+
+    let mut output = Vec::new();
+    for note in &expired {
+        trash::move_to_trash(&vault.path, &note.path)?;
+        output.push(format!("Trashed {}", note.relative));
+    }
+
+    Ok(Some(output.join("\n")))
+}
+
+/// The note's `expires` frontmatter date. Datetimes are accepted too, only
+/// their date part is used.
+fn expiry_date(note: &ObsidianNote) -> Option<NaiveDate> {
+    let expires = note.properties.as_ref()?.get("expires")?.as_str()?;
+    let date = expires.trim().get(..10)?;
+
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+}
+
+fn format_table(notes: &[ExpiredNote]) -> String {
+    let mut builder = Builder::new();
+
+    for note in notes {
+        builder.push_record([note.relative.clone(), note.expires.to_string()]);
+    }
+
+    builder.insert_record(0, vec!["Note", "Expired"]);
+
+    let mut table = builder.build();
+    table.with(Style::sharp());
+
+    format!("{table}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use test_case::test_case;
+
+    #[test_case("expires: 2024-03-01", Some("2024-03-01") ; "date")]
+    #[test_case("expires: 2024-03-01T09:30:00", Some("2024-03-01") ; "datetime")]
+    #[test_case("expires: soon", None ; "not a date")]
+    #[test_case("title: no expiry", None ; "missing")]
+    fn expiry_date_reads_frontmatter(frontmatter: &str, expected: Option<&str>) {
+        let note = ObsidianNote::parse(
+            Path::new("note.md"),
+            format!("---\n{frontmatter}\n---\nBody"),
+        )
+        .unwrap();
+
+        assert_eq!(
+            expiry_date(&note),
+            expected.map(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap())
+        );
+    }
+}
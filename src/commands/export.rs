@@ -0,0 +1,469 @@
+use crate::{
+    cli_config,
+    formats::yaml_to_json_value,
+    html::{self, HtmlExporter},
+    link_resolver::LinkResolver,
+    pandoc::{self, PdfOptions},
+    resolve, scan,
+    util::{get_current_vault, CommandResult},
+};
+use anyhow::{anyhow, bail, Context};
+use clap::{Args, Subcommand};
+use libobsidian::{
+    excalidraw::{self, DrawingPayload},
+    split_frontmatter, ObsidianNote,
+};
+use serde::Serialize;
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    process,
+};
+
+#[derive(Args, Debug, Clone)]
+#[command(args_conflicts_with_subcommands = true)]
+#[command(arg_required_else_help = true)]
+pub struct ExportCommand {
+    #[command(subcommand)]
+    command: Option<Subcommands>,
+}
+
+#[derive(Debug, Subcommand, Clone)]
+enum Subcommands {
+    /// Render an Excalidraw drawing to an image with the configured converter
+    Excalidraw(ExcalidrawArgs),
+
+    /// Render a note, or every note in a folder, to standalone HTML pages
+    Html(HtmlArgs),
+
+    /// Convert a note to PDF with pandoc
+    Pdf(PdfArgs),
+
+    /// Dump every note's path, properties, tags, links, headings and word count as JSON
+    Json(JsonArgs),
+}
+
+#[derive(Args, Debug, Clone)]
+struct ExcalidrawArgs {
+    #[arg(help = "The name or path of the drawing to export")]
+    note: String,
+
+    /// Export as SVG (the default)
+    #[arg(long, conflicts_with = "png")]
+    svg: bool,
+
+    /// Export as PNG
+    #[arg(long)]
+    png: bool,
+
+    /// Where to write the image, defaults to the drawing's name in the current directory
+    #[arg(long, short = 'o')]
+    out: Option<PathBuf>,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+#[derive(Args, Debug, Clone)]
+struct HtmlArgs {
+    #[arg(help = "The name or path of a note, or a vault-relative folder to export every note in")]
+    target: String,
+
+    /// The folder to write pages and attachments into
+    #[arg(long, short = 'o')]
+    out: PathBuf,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+#[derive(Args, Debug, Clone)]
+struct PdfArgs {
+    #[arg(help = "The name or path of the note to export")]
+    note: String,
+
+    /// Where to write the PDF, defaults to the note's name in the current directory
+    #[arg(long, short = 'o')]
+    out: Option<PathBuf>,
+
+    /// A pandoc template to render with
+    #[arg(long)]
+    template: Option<PathBuf>,
+
+    /// Page margin, e.g. `2cm` or `1in`
+    #[arg(long)]
+    margin: Option<String>,
+
+    /// The frontmatter property used as the document title, falling back to the note's name
+    #[arg(long, default_value = "title", value_name = "PROPERTY")]
+    title_from: String,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+#[derive(Args, Debug, Clone)]
+struct JsonArgs {
+    /// Write to a file instead of printing
+    #[arg(long, short = 'o')]
+    out: Option<PathBuf>,
+
+    /// Indent the output
+    #[arg(long)]
+    pretty: bool,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+struct VaultExport {
+    vault: String,
+    notes: Vec<NoteExport>,
+}
+
+#[derive(Serialize, Debug)]
+struct NoteExport {
+    path: String,
+    title: String,
+    frontmatter: serde_json::Value,
+    tags: Vec<String>,
+    links: Vec<LinkExport>,
+    headings: Vec<HeadingExport>,
+    word_count: usize,
+}
+
+#[derive(Serialize, Debug)]
+struct LinkExport {
+    target: String,
+    subpath: Option<String>,
+    /// The vault-relative path the link resolves to, `None` for broken and
+    /// external links
+    resolved: Option<String>,
+    embed: bool,
+    line: usize,
+}
+
+#[derive(Serialize, Debug)]
+struct HeadingExport {
+    level: usize,
+    text: String,
+    line: usize,
+}
+
+pub fn entry(cmd: &ExportCommand) -> CommandResult {
+    match &cmd.command {
+        Some(Subcommands::Excalidraw(args)) => export_excalidraw(args),
+        Some(Subcommands::Html(args)) => export_html(args),
+        Some(Subcommands::Pdf(args)) => export_pdf(args),
+        Some(Subcommands::Json(args)) => export_json(args),
+        None => todo!(),
+    }
+}
+
+fn export_excalidraw(args: &ExcalidrawArgs) -> CommandResult {
+    let vault = get_current_vault(args.vault.clone())?;
+    let note_path = resolve::pick_note(&args.note, &vault.path)?;
+    let format = if args.png { "png" } else { "svg" };
+
+    let converter = cli_config::read()?.excalidraw_converter.ok_or_else(|| {
+        anyhow!("No Excalidraw converter configured, set `excalidraw_converter` in your config")
+    })?;
+
+    let contents = fs::read_to_string(&note_path)
+        .with_context(|| format!("Failed to read {}", note_path.display()))?;
+
+    let scene = match excalidraw::split_drawing(&contents).1 {
+        Some(drawing) => match drawing.payload {
+            DrawingPayload::Json(scene) => scene,
+            DrawingPayload::Compressed => bail!(
+                "Drawing is compressed, turn off compression in the Excalidraw plugin settings to export it"
+            ),
+        },
+        None => bail!("`{}` is not an Excalidraw drawing", args.note),
+    };
+
+    let output = args
+        .out
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(format!("{}.{format}", drawing_name(&note_path))));
+
+    // A fresh file only this user can read, removed when it's dropped
+    let mut input = tempfile::Builder::new()
+        .prefix(&format!("obx-{}-", drawing_name(&note_path)))
+        .suffix(".excalidraw")
+        .tempfile()
+        .context("Could not create a temporary file for the drawing")?;
+    input
+        .write_all(scene.as_bytes())
+        .and_then(|_| input.flush())
+        .context("Could not write the drawing to a temporary file")?;
+
+    run_converter(&converter, input.path(), &output, format)?;
+
+    Ok(Some(format!("Exported {}", output.display())))
+}
+
+fn export_html(args: &HtmlArgs) -> CommandResult {
+    let vault = get_current_vault(args.vault.clone())?;
+    let notes = notes_to_export(&args.target, &vault.path)?;
+    let theme = cli_config::read()?.resolve_theme()?;
+    let exporter = HtmlExporter::new(&vault.path, notes.clone(), &theme)?;
+
+    let mut attachments: Vec<PathBuf> = Vec::new();
+    for note_path in &notes {
+        let rendered = exporter.render(note_path)?;
+        let page = args.out.join(html::output_path(&vault.path, note_path));
+        write_creating_dirs(&page, rendered.html.as_bytes())?;
+
+        for attachment in rendered.attachments {
+            if !attachments.contains(&attachment) {
+                attachments.push(attachment);
+            }
+        }
+    }
+
+    for attachment in &attachments {
+        let destination = args
+            .out
+            .join(scan::relative_note_path(attachment, &vault.path));
+        let contents = fs::read(attachment)
+            .with_context(|| format!("Failed to read {}", attachment.display()))?;
+        write_creating_dirs(&destination, &contents)?;
+    }
+
+    Ok(Some(format!(
+        "Exported {} note(s) and {} attachment(s) to {}",
+        notes.len(),
+        attachments.len(),
+        args.out.display()
+    )))
+}
+
+fn export_pdf(args: &PdfArgs) -> CommandResult {
+    let vault = get_current_vault(args.vault.clone())?;
+    let note_path = resolve::pick_note(&args.note, &vault.path)?;
+    let pandoc = pandoc::find_pandoc()?;
+
+    let note = ObsidianNote::read_from_path(&note_path)
+        .with_context(|| format!("Could not parse note {}", note_path.display()))?;
+    let name = note_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let title = note
+        .properties
+        .as_ref()
+        .and_then(|properties| properties.get(&args.title_from))
+        .and_then(|title| title.as_str())
+        .unwrap_or(&name);
+
+    let contents = note.file_raw_contents.as_deref().unwrap_or_default();
+    let body = split_frontmatter(contents).1;
+    let resolver = LinkResolver::new(&vault.path)?;
+    let markdown = pandoc::standard_markdown(body, &note_path, &vault.path, &resolver);
+
+    let output = args
+        .out
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(format!("{name}.pdf")));
+    let note_dir = note_path.parent().unwrap_or(&vault.path);
+
+    pandoc::markdown_to_pdf(
+        &pandoc,
+        &markdown,
+        &output,
+        &[note_dir, &vault.path],
+        &PdfOptions {
+            title,
+            template: args.template.as_deref(),
+            margin: args.margin.as_deref(),
+        },
+    )?;
+
+    Ok(Some(format!("Exported {}", output.display())))
+}
+
+fn export_json(args: &JsonArgs) -> CommandResult {
+    let vault = get_current_vault(args.vault.clone())?;
+    let resolver = LinkResolver::new(&vault.path)?;
+    let mut notes = Vec::new();
+
+    for note_path in scan::note_paths(&vault.path)? {
+        let Ok(note) = ObsidianNote::read_from_path(&note_path) else {
+            continue;
+        };
+
+        let links = note
+            .links()
+            .into_iter()
+            .map(|link| LinkExport {
+                resolved: resolver
+                    .resolve(&link, &note_path)
+                    .map(|resolved| scan::relative_note_path(&resolved, &vault.path)),
+                target: link.target,
+                subpath: link.subpath,
+                embed: link.embed,
+                line: link.line,
+            })
+            .collect();
+
+        let headings = note
+            .headings()
+            .into_iter()
+            .map(|heading| HeadingExport {
+                level: heading.level,
+                text: heading.text,
+                line: heading.line,
+            })
+            .collect();
+
+        notes.push(NoteExport {
+            path: scan::relative_note_path(&note_path, &vault.path),
+            title: note_path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            frontmatter: note
+                .properties
+                .as_ref()
+                .map(yaml_to_json_value)
+                .unwrap_or_else(|| serde_json::Value::Object(serde_json::Map::new())),
+            tags: note.tags(),
+            links,
+            headings,
+            word_count: note.file_body.split_whitespace().count(),
+        });
+    }
+
+    let export = VaultExport {
+        vault: vault.name,
+        notes,
+    };
+    let json = if args.pretty {
+        serde_json::to_string_pretty(&export)?
+    } else {
+        serde_json::to_string(&export)?
+    };
+
+    match &args.out {
+        Some(out) => {
+            write_creating_dirs(out, json.as_bytes())?;
+            Ok(Some(format!(
+                "Exported {} note(s) to {}",
+                export.notes.len(),
+                out.display()
+            )))
+        }
+        None => Ok(Some(json)),
+    }
+}
+
+/// The notes named by an export target: every note within a vault folder,
+/// or the single note it resolves to
+fn notes_to_export(target: &str, vault_path: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let folder = vault_path.join(target);
+    if !target.is_empty() && folder.is_dir() {
+        let notes = scan::note_paths(&folder)?;
+        if notes.is_empty() {
+            bail!("No notes found in `{target}`");
+        }
+        return Ok(notes);
+    }
+
+    Ok(vec![resolve::pick_note(target, vault_path)?])
+}
+
+fn write_creating_dirs(path: &Path, contents: &[u8]) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Could not create directory {}", parent.display()))?;
+    }
+    fs::write(path, contents).with_context(|| format!("Could not write {}", path.display()))
+}
+
+/// The drawing's file name without the `.excalidraw.md` suffix
+fn drawing_name(path: &Path) -> String {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    stem.strip_suffix(".excalidraw")
+        .map(str::to_string)
+        .unwrap_or(stem)
+}
+
+/// Run a converter command template, filling in the `{input}`, `{output}`
+/// and `{format}` placeholders of each argument
+fn run_converter(template: &str, input: &Path, output: &Path, format: &str) -> anyhow::Result<()> {
+    let args = converter_args(template, input, output, format)?;
+    let Some((program, args)) = args.split_first() else {
+        bail!("`excalidraw_converter` is empty");
+    };
+
+    let status = process::Command::new(program)
+        .args(args)
+        .status()
+        .with_context(|| format!("Failed to run Excalidraw converter `{program}`"))?;
+
+    if !status.success() {
+        bail!("Excalidraw converter `{program}` failed with {status}");
+    }
+
+    Ok(())
+}
+
+fn converter_args(
+    template: &str,
+    input: &Path,
+    output: &Path,
+    format: &str,
+) -> anyhow::Result<Vec<String>> {
+    let args = shell_words::split(template)
+        .with_context(|| format!("Invalid `excalidraw_converter` command `{template}`"))?;
+
+    Ok(args
+        .into_iter()
+        .map(|arg| {
+            arg.replace("{input}", &input.to_string_lossy())
+                .replace("{output}", &output.to_string_lossy())
+                .replace("{format}", format)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converter_args_fills_placeholders() {
+        let args = converter_args(
+            "convert --to {format} '{input}' -o {output}",
+            Path::new("/tmp/My Drawing.excalidraw"),
+            Path::new("out.svg"),
+            "svg",
+        )
+        .unwrap();
+
+        assert_eq!(
+            args,
+            vec![
+                "convert",
+                "--to",
+                "svg",
+                "/tmp/My Drawing.excalidraw",
+                "-o",
+                "out.svg"
+            ]
+        );
+    }
+
+    #[test]
+    fn drawing_name_strips_excalidraw_suffix() {
+        assert_eq!(drawing_name(Path::new("a/Sketch.excalidraw.md")), "Sketch");
+        assert_eq!(drawing_name(Path::new("a/Sketch.md")), "Sketch");
+    }
+}
@@ -0,0 +1,240 @@
+use crate::{
+    autocommit,
+    git::VaultRepo,
+    util::{get_current_vault, parse_interval, CommandResult},
+    watch::watch_vault,
+};
+use clap::{Args, Subcommand};
+use std::{
+    path::{Component, Path},
+    thread,
+    time::Duration,
+};
+
+/// How long changes settle before they're committed when watching, so a
+/// burst of saves makes one commit
+const AUTOCOMMIT_DEBOUNCE: Duration = Duration::from_secs(2);
+
+#[derive(Args, Debug, Clone)]
+#[command(args_conflicts_with_subcommands = true)]
+#[command(arg_required_else_help = true)]
+pub struct GitCommand {
+    #[command(subcommand)]
+    command: Option<Subcommands>,
+}
+
+#[derive(Debug, Subcommand, Clone)]
+enum Subcommands {
+    /// List the vault's uncommitted changes and where its branch stands
+    Status(StatusArgs),
+    /// Commit every change in the vault
+    Commit(CommitArgs),
+    /// Pull with a rebase, then push
+    Sync(SyncArgs),
+    /// Commit changes as notes are saved, or every `--interval`, until interrupted
+    Autocommit(AutocommitArgs),
+    /// Stop `autocommit` from committing until resumed
+    Pause(VaultArgs),
+    /// Let a paused `autocommit` commit again
+    Resume(VaultArgs),
+    /// Show what `autocommit` has done
+    Activity(ActivityArgs),
+}
+
+#[derive(Args, Debug, Clone)]
+struct StatusArgs {
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+#[derive(Args, Debug, Clone)]
+struct CommitArgs {
+    /// The commit message, a summary of the changed files when left out
+    #[arg(long, short = 'm')]
+    message: Option<String>,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+#[derive(Args, Debug, Clone)]
+struct SyncArgs {
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+#[derive(Args, Debug, Clone)]
+struct AutocommitArgs {
+    /// Commit whatever changed this often, like `30s`, `5m` or `1h`, instead of as notes are saved
+    #[arg(long, short = 'i', value_parser = parse_interval)]
+    interval: Option<Duration>,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+#[derive(Args, Debug, Clone)]
+struct VaultArgs {
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+#[derive(Args, Debug, Clone)]
+struct ActivityArgs {
+    /// Show at most this many of the latest entries
+    #[arg(long, short = 'n', default_value_t = 20)]
+    limit: usize,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+pub fn entry(cmd: &GitCommand) -> CommandResult {
+    match &cmd.command {
+        Some(Subcommands::Status(args)) => status(args),
+        Some(Subcommands::Commit(args)) => commit(args),
+        Some(Subcommands::Sync(args)) => sync(args),
+        Some(Subcommands::Autocommit(args)) => autocommit(args),
+        Some(Subcommands::Pause(args)) => pause(args),
+        Some(Subcommands::Resume(args)) => resume(args),
+        Some(Subcommands::Activity(args)) => activity(args),
+        None => todo!(),
+    }
+}
+
+fn status(args: &StatusArgs) -> CommandResult {
+    let vault = get_current_vault(args.vault.clone())?;
+    let repo = VaultRepo::open(&vault.path)?;
+
+    let mut lines = vec![repo.branch_state()?.to_string()];
+    let changes = repo.changes()?;
+    if changes.is_empty() {
+        lines.push("Nothing to commit".to_string());
+    }
+    lines.extend(changes.iter().map(ToString::to_string));
+    Ok(Some(lines.join("\n")))
+}
+
+fn commit(args: &CommitArgs) -> CommandResult {
+    let vault = get_current_vault(args.vault.clone())?;
+    let repo = VaultRepo::open(&vault.path)?;
+
+    Ok(Some(match repo.commit(args.message.as_deref())? {
+        Some((id, summary)) => format!("Committed {id} {summary}"),
+        None => "Nothing to commit".to_string(),
+    }))
+}
+
+fn sync(args: &SyncArgs) -> CommandResult {
+    let vault = get_current_vault(args.vault.clone())?;
+    let repo = VaultRepo::open(&vault.path)?;
+
+    Ok(Some(repo.sync()?.to_string()))
+}
+
+fn autocommit(args: &AutocommitArgs) -> CommandResult {
+    let vault = get_current_vault(args.vault.clone())?;
+    let repo = VaultRepo::open(&vault.path)?;
+
+    if let Some(interval) = args.interval {
+        autocommit::record(&vault.path, "Started")?;
+        eprintln!(
+            "Committing `{}` every {}, press Ctrl-C to stop",
+            vault.name,
+            humanize(interval)
+        );
+        loop {
+            thread::sleep(interval);
+            commit_changes(&repo, &vault.path);
+        }
+    }
+
+    let watcher = watch_vault(&vault, AUTOCOMMIT_DEBOUNCE)?;
+
+    autocommit::record(&vault.path, "Started")?;
+    eprintln!(
+        "Committing `{}` as notes change, press Ctrl-C to stop",
+        vault.name
+    );
+
+    for changed in watcher {
+        // Committing writes to `.git`, which would otherwise wake the watcher again
+        let outside_git = changed?.iter().any(|path| {
+            !path
+                .components()
+                .any(|component| component == Component::Normal(".git".as_ref()))
+        });
+        if outside_git {
+            commit_changes(&repo, &vault.path);
+        }
+    }
+
+    Ok(None)
+}
+
+/// Commit whatever changed unless paused, printing and logging what
+/// happened. Failures are reported rather than returned so one bad commit
+/// doesn't stop the daemon.
+fn commit_changes(repo: &VaultRepo, vault_path: &Path) {
+    if autocommit::is_paused(vault_path) {
+        return;
+    }
+    let (entry, failed) = match repo.commit(None) {
+        Ok(Some((id, summary))) => (format!("Committed {id} {summary}"), false),
+        Ok(None) => return,
+        Err(err) => (format!("Could not commit: {err:#}"), true),
+    };
+    if let Err(err) = autocommit::record(vault_path, &entry) {
+        eprintln!("{err:#}");
+    }
+    if failed {
+        eprintln!("{entry}");
+    } else {
+        println!("{entry}");
+    }
+}
+
+/// An interval the way it'd be written on the command line
+fn humanize(interval: Duration) -> String {
+    match interval.as_secs() {
+        seconds if seconds % 3600 == 0 => format!("{}h", seconds / 3600),
+        seconds if seconds % 60 == 0 => format!("{}m", seconds / 60),
+        seconds => format!("{seconds}s"),
+    }
+}
+
+fn pause(args: &VaultArgs) -> CommandResult {
+    let vault = get_current_vault(args.vault.clone())?;
+    if !autocommit::pause(&vault.path)? {
+        return Ok(Some(format!(
+            "Autocommit is already paused for `{}`",
+            vault.name
+        )));
+    }
+    autocommit::record(&vault.path, "Paused")?;
+
+    Ok(Some(format!("Paused autocommit for `{}`", vault.name)))
+}
+
+fn resume(args: &VaultArgs) -> CommandResult {
+    let vault = get_current_vault(args.vault.clone())?;
+    if !autocommit::resume(&vault.path)? {
+        return Ok(Some(format!(
+            "Autocommit isn't paused for `{}`",
+            vault.name
+        )));
+    }
+    autocommit::record(&vault.path, "Resumed")?;
+
+    Ok(Some(format!("Resumed autocommit for `{}`", vault.name)))
+}
+
+fn activity(args: &ActivityArgs) -> CommandResult {
+    let vault = get_current_vault(args.vault.clone())?;
+    let entries = autocommit::activity(&vault.path, args.limit)?;
+    if entries.is_empty() {
+        return Ok(Some(format!("No autocommit activity for `{}`", vault.name)));
+    }
+
+    Ok(Some(entries.join("\n")))
+}
@@ -0,0 +1,159 @@
+use crate::{
+    cache::MetadataCache,
+    graph_export::Subgraph,
+    graph_stats::GraphStats,
+    link_graph::LinkGraph,
+    matching::MatchSettings,
+    resolve, scan,
+    util::{get_current_vault, CommandResult},
+};
+use anyhow::bail;
+use clap::{Args, Subcommand};
+use std::collections::BTreeSet;
+
+#[derive(Args, Debug, Clone)]
+#[command(args_conflicts_with_subcommands = true)]
+#[command(arg_required_else_help = true)]
+pub struct GraphCommand {
+    #[command(subcommand)]
+    command: Option<Subcommands>,
+}
+
+#[derive(Debug, Subcommand, Clone)]
+enum Subcommands {
+    /// Print the notes and the links between them for Graphviz, Gephi or other tools
+    Export(ExportArgs),
+    /// Report link counts, the most linked notes and how connected the vault is
+    Stats(StatsArgs),
+}
+
+#[derive(Args, Debug, Clone)]
+struct StatsArgs {
+    #[arg(long, short = 'f', default_value = "report")]
+    format: StatsFormat,
+
+    /// How many of the most linked notes to list
+    #[arg(long, default_value_t = 10)]
+    top: usize,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+enum StatsFormat {
+    Report,
+    Json,
+}
+
+#[derive(Args, Debug, Clone)]
+struct ExportArgs {
+    #[arg(long, short = 'f', default_value = "dot")]
+    format: GraphFormat,
+
+    /// Only include notes within this vault-relative folder
+    #[arg(long)]
+    folder: Option<String>,
+
+    /// Only include notes carrying this tag or a tag nested beneath it
+    #[arg(long)]
+    tag: Option<String>,
+
+    /// Only include notes linked to or from this note, directly or through
+    /// others up to `--depth` links away
+    #[arg(long, value_name = "NOTE")]
+    root: Option<String>,
+
+    /// How many links away from `--root` to go
+    #[arg(long, short = 'd', default_value_t = 1, requires = "root")]
+    depth: usize,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+enum GraphFormat {
+    /// For Graphviz
+    Dot,
+    /// For Gephi, yEd and Cytoscape
+    Graphml,
+    /// An object with `nodes` and `edges` arrays
+    Json,
+}
+
+pub fn entry(cmd: &GraphCommand) -> CommandResult {
+    match &cmd.command {
+        Some(Subcommands::Export(args)) => export(args),
+        Some(Subcommands::Stats(args)) => stats(args),
+        None => todo!(),
+    }
+}
+
+fn export(args: &ExportArgs) -> CommandResult {
+    let vault = get_current_vault(args.vault.clone())?;
+    let graph = LinkGraph::current(&vault.path)?;
+    let cache = MetadataCache::current(&vault.path)?;
+
+    let folder_prefix = match &args.folder {
+        Some(folder) => {
+            let folder_path = vault.path.join(folder);
+            if !folder_path.is_dir() {
+                bail!("Folder `{}` not found in vault `{}`", folder, vault.name);
+            }
+            match scan::relative_note_path(&folder_path, &vault.path).trim_end_matches('/') {
+                "" | "." => String::new(),
+                relative => format!("{relative}/"),
+            }
+        }
+        None => String::new(),
+    };
+    let matching = MatchSettings::read();
+    let tag = args
+        .tag
+        .as_deref()
+        .map(|tag| matching.fold(tag.trim_start_matches('#')));
+    let neighborhood = match &args.root {
+        Some(root) => {
+            let root_path = resolve::pick_note(root, &vault.path)?;
+            let relative = scan::relative_note_path(&root_path, &vault.path);
+            Some(graph.neighborhood(&relative, args.depth))
+        }
+        None => None,
+    };
+
+    let notes: BTreeSet<&str> = graph
+        .notes()
+        .into_iter()
+        .filter(|relative| relative.starts_with(&folder_prefix))
+        .filter(|relative| match &tag {
+            Some(tag) => cache
+                .get(relative)
+                .is_some_and(|note| note.tags.iter().any(|t| matching.has_tag(t, tag))),
+            None => true,
+        })
+        .filter(|relative| {
+            neighborhood
+                .as_ref()
+                .is_none_or(|neighborhood| neighborhood.contains_key(relative))
+        })
+        .collect();
+
+    let subgraph = Subgraph::new(&graph, &cache, &notes);
+    Ok(Some(match args.format {
+        GraphFormat::Dot => subgraph.to_dot(),
+        GraphFormat::Graphml => subgraph.to_graphml(),
+        GraphFormat::Json => serde_json::to_string(&subgraph)?,
+    }))
+}
+
+fn stats(args: &StatsArgs) -> CommandResult {
+    let vault = get_current_vault(args.vault.clone())?;
+    let graph = LinkGraph::current(&vault.path)?;
+    let stats = GraphStats::new(&graph, args.top);
+
+    Ok(Some(match args.format {
+        StatsFormat::Report => stats.report(),
+        StatsFormat::Json => serde_json::to_string(&stats)?,
+    }))
+}
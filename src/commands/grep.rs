@@ -0,0 +1,340 @@
+use crate::{
+    matching::{CaseArgs, MatchSettings},
+    note_text::NoteText,
+    scan,
+    style::Styler,
+    util::{get_current_vault, CommandResult},
+};
+use anyhow::Context;
+use clap::Args;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use libobsidian::excalidraw;
+use regex::{Captures, RegexBuilder};
+use serde::Serialize;
+use std::path::Path;
+
+#[derive(Args, Debug, Clone)]
+pub struct GrepCommand {
+    #[arg(help = "The regular expression to search for")]
+    pattern: String,
+
+    /// Only search notes whose vault-relative path matches the glob. Globs without
+    /// a `/` are matched against the file name. May be repeated
+    #[arg(long, short = 'g')]
+    glob: Vec<String>,
+
+    /// Print NUM lines of trailing context after each match
+    #[arg(long, short = 'A', value_name = "NUM")]
+    after_context: Option<usize>,
+
+    /// Print NUM lines of leading context before each match
+    #[arg(long, short = 'B', value_name = "NUM")]
+    before_context: Option<usize>,
+
+    /// Print NUM lines of context around each match
+    #[arg(long, short = 'C', value_name = "NUM")]
+    context: Option<usize>,
+
+    /// Only print the paths of notes containing a match
+    #[arg(long, short = 'l')]
+    files_with_matches: bool,
+
+    /// Print each match on its own line rather than the whole line it's on
+    #[arg(long, short = 'o')]
+    only_matching: bool,
+
+    /// Print TEXT in place of each match, with `$1` or `${name}` filled in
+    /// from the pattern's capture groups
+    #[arg(long, short = 'r', value_name = "TEXT")]
+    replace: Option<String>,
+
+    /// Print a record for each matching line. Context lines are left out
+    #[arg(
+        long,
+        short = 'f',
+        default_value = "text",
+        conflicts_with = "files_with_matches"
+    )]
+    format: GrepFormat,
+
+    #[command(flatten)]
+    case: CaseArgs,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+enum GrepFormat {
+    Text,
+    /// An array of matching lines
+    Json,
+    /// One JSON matching line per line, for tools reading results as they go
+    Jsonl,
+}
+
+#[derive(Serialize, Debug)]
+struct GrepRecord {
+    path: String,
+    /// Counting from 1
+    line: usize,
+    snippet: String,
+    matches: Vec<GrepMatch>,
+}
+
+/// Where a match is in its line, in bytes, and the text matched or what
+/// `--replace` made of it
+#[derive(Serialize, Debug)]
+struct GrepMatch {
+    start: usize,
+    end: usize,
+    text: String,
+}
+
+pub fn entry(cmd: &GrepCommand) -> CommandResult {
+    let vault = get_current_vault(cmd.vault.clone())?;
+    let pattern = RegexBuilder::new(&cmd.pattern)
+        .case_insensitive(
+            !cmd.case
+                .case(&MatchSettings::read())
+                .is_sensitive(&literal_text(&cmd.pattern)),
+        )
+        .build()
+        .with_context(|| format!("Invalid regular expression `{}`", cmd.pattern))?;
+    let globs = build_globs(&cmd.glob)?;
+
+    let before = cmd.before_context.or(cmd.context).unwrap_or(0);
+    let after = cmd.after_context.or(cmd.context).unwrap_or(0);
+
+    let styler = Styler::current();
+    let mut output: Vec<String> = Vec::new();
+    let mut records: Vec<GrepRecord> = Vec::new();
+
+    for note_path in scan::note_paths(&vault.path)? {
+        let relative = scan::relative_note_path(&note_path, &vault.path);
+        if !matches_globs(&globs, &relative) {
+            continue;
+        }
+
+        let Ok(contents) = NoteText::read(&note_path) else {
+            continue;
+        };
+        let lines: Vec<&str> = excalidraw::readable_content(&note_path, contents.as_str())
+            .lines()
+            .collect();
+        let matching: Vec<usize> = lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| pattern.is_match(line))
+            .map(|(idx, _)| idx)
+            .collect();
+
+        if matching.is_empty() {
+            continue;
+        }
+
+        if cmd.files_with_matches {
+            output.push(styler.path(&relative));
+            continue;
+        }
+
+        if !matches!(cmd.format, GrepFormat::Text) {
+            for &idx in &matching {
+                let line = lines[idx];
+                let matches = pattern
+                    .captures_iter(line)
+                    .map(|caps| {
+                        let whole = caps.get(0).expect("captures always hold the whole match");
+                        GrepMatch {
+                            start: whole.start(),
+                            end: whole.end(),
+                            text: found_text(&caps, cmd.replace.as_deref()),
+                        }
+                    })
+                    .collect();
+                records.push(GrepRecord {
+                    path: relative.clone(),
+                    line: idx + 1,
+                    snippet: line.to_string(),
+                    matches,
+                });
+            }
+            continue;
+        }
+
+        if cmd.only_matching {
+            for &idx in &matching {
+                for caps in pattern.captures_iter(lines[idx]) {
+                    let found = found_text(&caps, cmd.replace.as_deref());
+                    output.push(format!(
+                        "{}:{}:{}",
+                        styler.path(&relative),
+                        idx + 1,
+                        styler.highlight(&found, &[(0, found.len())])
+                    ));
+                }
+            }
+            continue;
+        }
+
+        for (start, end) in context_ranges(&matching, before, after, lines.len()) {
+            if (before > 0 || after > 0) && !output.is_empty() {
+                output.push("--".to_string());
+            }
+
+            for (idx, line) in lines.iter().enumerate().take(end + 1).skip(start) {
+                let is_match = matching.binary_search(&idx).is_ok();
+                let separator = if is_match { ':' } else { '-' };
+                let line = match &cmd.replace {
+                    Some(replace) if is_match => pattern.replace_all(line, replace.as_str()).into(),
+                    None if is_match => {
+                        let matches: Vec<(usize, usize)> = pattern
+                            .find_iter(line)
+                            .map(|found| (found.start(), found.end()))
+                            .collect();
+                        styler.highlight(line, &matches)
+                    }
+                    _ => line.to_string(),
+                };
+                output.push(format!(
+                    "{}{separator}{}{separator}{line}",
+                    styler.path(&relative),
+                    idx + 1
+                ));
+            }
+        }
+    }
+
+    match cmd.format {
+        GrepFormat::Text => {}
+        GrepFormat::Json => {
+            if !records.is_empty() {
+                output.push(serde_json::to_string(&records)?);
+            }
+        }
+        GrepFormat::Jsonl => {
+            for record in &records {
+                output.push(serde_json::to_string(record)?);
+            }
+        }
+    }
+
+    if output.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(output.join("\n")))
+}
+
+/// The text a match stands for, filled into `replace` when there is one
+fn found_text(caps: &Captures, replace: Option<&str>) -> String {
+    match replace {
+        Some(replace) => {
+            let mut expanded = String::new();
+            caps.expand(replace, &mut expanded);
+            expanded
+        }
+        None => caps[0].to_string(),
+    }
+}
+
+fn build_globs(patterns: &[String]) -> anyhow::Result<Option<GlobSet>> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let pattern = if pattern.contains('/') {
+            pattern.clone()
+        } else {
+            format!("**/{pattern}")
+        };
+        builder.add(Glob::new(&pattern).with_context(|| format!("Invalid glob `{pattern}`"))?);
+    }
+
+    Ok(Some(builder.build()?))
+}
+
+fn matches_globs(globs: &Option<GlobSet>, relative_path: &str) -> bool {
+    match globs {
+        Some(globs) => globs.is_match(Path::new(relative_path)),
+        None => true,
+    }
+}
+
+/// The characters of a regular expression that match themselves, leaving out
+/// escapes like `\W` and `\p{Lu}` and group names, so smart case only goes by
+/// capitals meant to be found
+fn literal_text(pattern: &str) -> String {
+    let mut literal = String::new();
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some('p' | 'P') = chars.next() {
+                    if chars.peek() == Some(&'{') {
+                        chars.by_ref().find(|&c| c == '}');
+                    } else {
+                        chars.next();
+                    }
+                }
+            }
+            '(' if chars.peek() == Some(&'?') => {
+                let rest: String = chars.clone().take(3).collect();
+                if rest.starts_with("?P<")
+                    || (rest.starts_with("?<") && !rest.ends_with(['=', '!']))
+                {
+                    chars.by_ref().find(|&c| c == '>');
+                }
+            }
+            _ => literal.push(c),
+        }
+    }
+    literal
+}
+
+/// Expand each matching line into an inclusive range of lines to print,
+/// merging ranges that overlap or touch
+fn context_ranges(
+    matching: &[usize],
+    before: usize,
+    after: usize,
+    line_count: usize,
+) -> Vec<(usize, usize)> {
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+
+    for &idx in matching {
+        let start = idx.saturating_sub(before);
+        let end = (idx + after).min(line_count.saturating_sub(1));
+
+        match ranges.last_mut() {
+            Some(last) if start <= last.1 + 1 => last.1 = last.1.max(end),
+            _ => ranges.push((start, end)),
+        }
+    }
+
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn context_ranges_merges_overlapping() {
+        assert_eq!(context_ranges(&[2, 4], 1, 1, 10), vec![(1, 5)]);
+    }
+
+    #[test]
+    fn context_ranges_clamps_to_file() {
+        assert_eq!(context_ranges(&[0, 9], 2, 2, 10), vec![(0, 2), (7, 9)]);
+    }
+
+    #[test]
+    fn literal_text_skips_escapes_and_group_names() {
+        assert_eq!(literal_text(r"To\Wdo"), "Todo");
+        assert_eq!(literal_text(r"\p{Lu}\S+\.md"), "+md");
+        assert_eq!(literal_text(r"(?P<Name>x)(?<Other>y)"), "x)y)");
+    }
+}
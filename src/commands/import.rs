@@ -0,0 +1,76 @@
+use crate::{
+    attachments, cli_config,
+    import::{enex::EnexImporter, notion::NotionImporter, write_import, Importer},
+    util::{get_current_vault, CommandResult},
+};
+use anyhow::bail;
+use clap::{Args, Subcommand};
+use std::path::PathBuf;
+
+#[derive(Args, Debug, Clone)]
+#[command(args_conflicts_with_subcommands = true)]
+#[command(arg_required_else_help = true)]
+pub struct ImportCommand {
+    #[command(subcommand)]
+    command: Option<Subcommands>,
+}
+
+#[derive(Debug, Subcommand, Clone)]
+enum Subcommands {
+    /// Import a Notion "Markdown & CSV" export, zipped or unzipped
+    Notion(ImportArgs),
+
+    /// Import an Evernote .enex export
+    Enex(ImportArgs),
+}
+
+#[derive(Args, Debug, Clone)]
+struct ImportArgs {
+    #[arg(help = "The export file (or folder) to import")]
+    source: PathBuf,
+
+    /// Vault-relative folder to put the imported notes in, defaults to the vault root
+    #[arg(long, short = 'd')]
+    dest: Option<PathBuf>,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+pub fn entry(cmd: &ImportCommand) -> CommandResult {
+    match &cmd.command {
+        Some(Subcommands::Notion(args)) => import(&NotionImporter, args),
+        Some(Subcommands::Enex(args)) => import(&EnexImporter, args),
+        None => todo!(),
+    }
+}
+
+fn import(importer: &dyn Importer, args: &ImportArgs) -> CommandResult {
+    let vault = get_current_vault(args.vault.clone())?;
+
+    if !args.source.exists() {
+        bail!("`{}` not found", args.source.display());
+    }
+
+    // Imported notes have no single folder, so attachments that Obsidian
+    // would keep next to a note go next to the import instead
+    let dest = args.dest.clone().unwrap_or_default();
+    let attachment_folder = attachments::attachment_folder(
+        cli_config::read()?.attachment_folder.as_deref(),
+        &vault.path,
+        &dest,
+    );
+
+    let imported = importer.read(&args.source)?;
+    let summary = write_import(imported, &vault.path, &dest, &attachment_folder)?;
+
+    let location = match dest.as_os_str().is_empty() {
+        true => format!("vault `{}`", vault.name),
+        false => dest.display().to_string(),
+    };
+
+    Ok(Some(format!(
+        "Imported {} note(s) and {} attachment(s) into {location}",
+        summary.notes, summary.attachments
+    )))
+}
@@ -0,0 +1,143 @@
+use crate::{
+    index::{self, Index, Update},
+    link_graph::LinkGraph,
+    note_text, scan,
+    util::{get_current_vault, CommandResult},
+    watch::watch_vault,
+};
+use clap::{Args, Subcommand};
+use std::{path::PathBuf, time::Duration};
+
+/// How long changes settle before the index is updated, so a burst of saves
+/// is handled once
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(500);
+
+#[derive(Args, Debug, Clone)]
+#[command(args_conflicts_with_subcommands = true)]
+#[command(arg_required_else_help = true)]
+pub struct IndexCommand {
+    #[command(subcommand)]
+    command: Option<Subcommands>,
+}
+
+#[derive(Debug, Subcommand, Clone)]
+enum Subcommands {
+    /// Index every note in the vault, replacing any existing index
+    Build(VaultArgs),
+
+    /// Show where the index is kept, its size and how many notes changed since it was built
+    Status(VaultArgs),
+
+    /// Delete the vault's index
+    Clear(VaultArgs),
+
+    /// Keep the index and link graph up to date as notes change, until interrupted
+    Watch(VaultArgs),
+}
+
+#[derive(Args, Debug, Clone)]
+struct VaultArgs {
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+pub fn entry(cmd: &IndexCommand) -> CommandResult {
+    match &cmd.command {
+        Some(Subcommands::Build(args)) => build(args),
+        Some(Subcommands::Status(args)) => status(args),
+        Some(Subcommands::Clear(args)) => clear(args),
+        Some(Subcommands::Watch(args)) => watch(args),
+        None => todo!(),
+    }
+}
+
+fn build(args: &VaultArgs) -> CommandResult {
+    let vault = get_current_vault(args.vault.clone())?;
+    let index = Index::build(&vault.path)?;
+    index.save(&vault.path)?;
+
+    Ok(Some(format!(
+        "Indexed {} notes in `{}`",
+        index.note_count(),
+        vault.name
+    )))
+}
+
+fn status(args: &VaultArgs) -> CommandResult {
+    let vault = get_current_vault(args.vault.clone())?;
+    let Some(index) = Index::load(&vault.path)? else {
+        return Ok(Some(format!(
+            "No index for `{}`, build one with `obx index build`",
+            vault.name
+        )));
+    };
+    let changes = index.changes(&vault.path)?;
+
+    Ok(Some(format!(
+        "Index: {}\nNotes: {}\nTerms: {}\nLinks: {}\nChanged since built: {}",
+        index::index_path(&vault.path).display(),
+        index.note_count(),
+        index.term_count(),
+        index.link_count(),
+        changes.len()
+    )))
+}
+
+fn clear(args: &VaultArgs) -> CommandResult {
+    let vault = get_current_vault(args.vault.clone())?;
+
+    if Index::clear(&vault.path)? {
+        Ok(Some(format!("Removed the index for `{}`", vault.name)))
+    } else {
+        Ok(Some(format!("No index for `{}`", vault.name)))
+    }
+}
+
+fn watch(args: &VaultArgs) -> CommandResult {
+    let vault = get_current_vault(args.vault.clone())?;
+    note_text::avoid_mapping();
+    let mut index = match Index::load(&vault.path)? {
+        Some(mut index) => {
+            index.refresh(&vault.path)?;
+            index
+        }
+        None => Index::build(&vault.path)?,
+    };
+    index.save(&vault.path)?;
+    let mut graph = LinkGraph::current(&vault.path)?;
+
+    let ignored = scan::Ignored::for_path(&vault.path);
+    let watcher = watch_vault(&vault, WATCH_DEBOUNCE)?;
+
+    eprintln!(
+        "Watching `{}` with {} notes indexed, press Ctrl-C to stop",
+        vault.name,
+        index.note_count()
+    );
+
+    for changed in watcher {
+        let paths: Vec<PathBuf> = changed?
+            .into_iter()
+            .map(|relative| vault.path.join(relative))
+            .collect();
+
+        let updates = index.update_paths(&vault.path, &ignored, &paths)?;
+        // Any file coming or going can change what links resolve to
+        if graph.refresh(&vault.path)? {
+            graph.save(&vault.path)?;
+        }
+        if updates.is_empty() {
+            continue;
+        }
+        index.save(&vault.path)?;
+
+        for update in updates {
+            match update {
+                Update::Indexed(path) => println!("Indexed {path}"),
+                Update::Removed(path) => println!("Removed {path}"),
+            }
+        }
+    }
+
+    Ok(None)
+}
@@ -0,0 +1,65 @@
+use crate::{
+    link_resolver::LinkResolver,
+    resolve, scan,
+    style::Styler,
+    util::{get_current_vault, CommandResult},
+};
+use anyhow::Context;
+use clap::Args;
+use libobsidian::links::parse_links;
+use std::fs;
+use tabled::{builder::Builder, settings::Style};
+
+#[derive(Args, Debug, Clone)]
+pub struct LinksCommand {
+    #[arg(help = "The name or path of the note to list links from")]
+    note: String,
+
+    /// Print absolute paths for resolved targets instead of vault-relative ones
+    #[arg(long)]
+    resolve: bool,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+pub fn entry(cmd: &LinksCommand) -> CommandResult {
+    let vault = get_current_vault(cmd.vault.clone())?;
+    let note_path = resolve::pick_note(&cmd.note, &vault.path)?;
+    let contents = fs::read_to_string(&note_path)
+        .with_context(|| format!("Failed to read {}", note_path.display()))?;
+
+    let links = parse_links(&contents);
+    if links.is_empty() {
+        return Ok(None);
+    }
+
+    let resolver = LinkResolver::new(&vault.path)?;
+    let styler = Styler::current();
+    let mut builder = Builder::new();
+
+    for link in &links {
+        let target = if link.is_external() {
+            "external".to_string()
+        } else {
+            match resolver.resolve(link, &note_path) {
+                Some(path) if cmd.resolve => path.display().to_string(),
+                Some(path) => styler.path(&scan::relative_note_path(&path, &vault.path)),
+                None => "missing".to_string(),
+            }
+        };
+
+        builder.push_record([
+            link.line.to_string(),
+            contents[link.range.clone()].to_string(),
+            target,
+        ]);
+    }
+
+    builder.insert_record(0, vec!["Line", "Link", "Target"]);
+
+    let mut table = builder.build();
+    table.with(Style::sharp());
+
+    Ok(Some(format!("{table}")))
+}
@@ -0,0 +1,279 @@
+use crate::{
+    cache::MetadataCache,
+    cli_config,
+    dates::DateFormats,
+    matching::MatchSettings,
+    paging::Paging,
+    scan,
+    search_query::SearchQuery,
+    style::Styler,
+    util::{get_current_vault, parse_since, CommandResult},
+};
+use anyhow::bail;
+use chrono::{DateTime, Local};
+use clap::Args;
+use libobsidian::canvas;
+use serde::Serialize;
+use std::{fs, path::Path};
+use tabled::{builder::Builder, settings::Style};
+
+#[derive(Args, Debug, Clone)]
+pub struct ListCommand {
+    /// Only list notes within this vault-relative folder
+    #[arg(long)]
+    folder: Option<String>,
+
+    /// Only list notes carrying this tag or one nested beneath it, in
+    /// frontmatter or inline
+    #[arg(long)]
+    tag: Option<String>,
+
+    /// Only list notes modified since a duration ago (`3d`, `12h`) or a date (`2024-01-31`)
+    #[arg(long, value_name = "TIME", visible_alias = "modified-since")]
+    modified_after: Option<String>,
+
+    /// Only list notes last modified before a duration ago or a date
+    #[arg(long, value_name = "TIME")]
+    modified_before: Option<String>,
+
+    /// Only list notes created since a duration ago or a date, going by their
+    /// `created` or `date` property before the file's creation time
+    #[arg(long, value_name = "TIME")]
+    created_after: Option<String>,
+
+    /// Only list notes created before a duration ago or a date
+    #[arg(long, value_name = "TIME")]
+    created_before: Option<String>,
+
+    /// Only list notes whose name contains this text, ignoring case
+    #[arg(long, value_name = "TEXT")]
+    name_contains: Option<String>,
+
+    /// Only list notes matching a search query, like `tag:#project -draft`,
+    /// see `obx search`
+    #[arg(long, short = 'q')]
+    query: Option<String>,
+
+    /// List `.canvas` files too. They have no tags, so aren't listed when
+    /// filtering by tag or query.
+    #[arg(long)]
+    include_canvas: bool,
+
+    #[command(flatten)]
+    paging: Paging,
+
+    #[arg(long, short = 'f', default_value = "table")]
+    format: ListFormat,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+enum ListFormat {
+    Table,
+    Paths,
+    Json,
+}
+
+#[derive(Serialize, Debug)]
+struct ListedNote {
+    path: String,
+    name: String,
+    #[serde(serialize_with = "serialize_modified")]
+    modified: Option<DateTime<Local>>,
+    tags: Vec<String>,
+}
+
+pub fn entry(cmd: &ListCommand) -> CommandResult {
+    let vault = get_current_vault(cmd.vault.clone())?;
+
+    // Vault-relative, ending in `/`
+    let folder_prefix = match &cmd.folder {
+        Some(folder) => {
+            let folder_path = vault.path.join(folder);
+            if !folder_path.is_dir() {
+                bail!("Folder `{}` not found in vault `{}`", folder, vault.name);
+            }
+            let relative = scan::relative_note_path(&folder_path, &vault.path);
+            match relative.trim_end_matches('/') {
+                "" | "." => String::new(),
+                relative => format!("{relative}/"),
+            }
+        }
+        None => String::new(),
+    };
+
+    let modified = TimeRange::parse(&cmd.modified_after, &cmd.modified_before)?;
+    let created = TimeRange::parse(&cmd.created_after, &cmd.created_before)?;
+    let tag = cmd.tag.as_deref().map(|tag| tag.trim_start_matches('#'));
+    let matching = MatchSettings::read();
+    let name_contains = cmd.name_contains.as_deref().map(|name| matching.fold(name));
+    let query = cmd.query.as_deref().map(SearchQuery::parse).transpose()?;
+
+    let mut notes = Vec::new();
+
+    let cache = MetadataCache::current(&vault.path)?;
+    for (relative, metadata) in cache.notes() {
+        if !relative.starts_with(&folder_prefix) {
+            continue;
+        }
+        let note_path = vault.path.join(relative);
+        if !modified.contains(metadata.modified()) || !created.contains(metadata.created()) {
+            continue;
+        }
+        let modified = metadata.modified();
+
+        let name = note_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        if let Some(needle) = &name_contains {
+            if !matching.fold(&name).contains(needle) {
+                continue;
+            }
+        }
+
+        let tags = metadata.tags.clone();
+        if let Some(tag) = tag {
+            if !tags.iter().any(|t| matching.has_tag(t, tag)) {
+                continue;
+            }
+        }
+
+        if let Some(query) = &query {
+            if !query.matches_path(&note_path, &vault.path)? {
+                continue;
+            }
+        }
+
+        notes.push(ListedNote {
+            path: relative.to_string(),
+            name,
+            modified,
+            tags,
+        });
+    }
+
+    if cmd.include_canvas && tag.is_none() && query.is_none() {
+        for canvas_path in scan::file_paths(&vault.path)? {
+            let relative = scan::relative_note_path(&canvas_path, &vault.path);
+            if !canvas::is_canvas_path(&canvas_path) || !relative.starts_with(&folder_prefix) {
+                continue;
+            }
+            let metadata = fs::metadata(&canvas_path).ok();
+            let modified_time = metadata
+                .as_ref()
+                .and_then(|metadata| metadata.modified().ok())
+                .map(DateTime::<Local>::from);
+            let created_time = metadata
+                .as_ref()
+                .and_then(|metadata| metadata.created().ok())
+                .map(DateTime::<Local>::from);
+            if !modified.contains(modified_time) || !created.contains(created_time) {
+                continue;
+            }
+            let name = canvas_path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            if let Some(needle) = &name_contains {
+                if !matching.fold(&name).contains(needle) {
+                    continue;
+                }
+            }
+
+            notes.push(ListedNote {
+                path: relative,
+                name,
+                modified: modified_time,
+                tags: Vec::new(),
+            });
+        }
+        notes.sort_by(|a, b| Path::new(&a.path).cmp(Path::new(&b.path)));
+    }
+
+    let notes = cmd.paging.apply(notes);
+    let formatted = match cmd.format {
+        ListFormat::Json => serde_json::to_string(&notes)?,
+        ListFormat::Paths => notes
+            .iter()
+            .map(|note| Styler::current().path(&note.path))
+            .collect::<Vec<String>>()
+            .join("\n"),
+        ListFormat::Table => format_table(&notes, &cli_config::read()?.dates),
+    };
+
+    if formatted.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(formatted))
+}
+
+/// Bounds from a pair of `--…-after`/`--…-before` flags
+struct TimeRange {
+    after: Option<DateTime<Local>>,
+    before: Option<DateTime<Local>>,
+}
+
+impl TimeRange {
+    fn parse(after: &Option<String>, before: &Option<String>) -> anyhow::Result<Self> {
+        Ok(Self {
+            after: after.as_deref().map(parse_since).transpose()?,
+            before: before.as_deref().map(parse_since).transpose()?,
+        })
+    }
+
+    /// Whether `time` is within the bounds, with an unknown time only
+    /// passing when there are none
+    fn contains(&self, time: Option<DateTime<Local>>) -> bool {
+        match time {
+            Some(time) => {
+                self.after.is_none_or(|after| time >= after)
+                    && self.before.is_none_or(|before| time < before)
+            }
+            None => self.after.is_none() && self.before.is_none(),
+        }
+    }
+}
+
+fn format_table(notes: &[ListedNote], dates: &DateFormats) -> String {
+    let styler = Styler::current();
+    let mut builder = Builder::new();
+
+    for note in notes {
+        builder.push_record([
+            styler.path(&note.path),
+            note.modified
+                .map(|dt| styler.date(&dates.format(&dt, &dates.timestamp)))
+                .unwrap_or_default(),
+            note.tags
+                .iter()
+                .map(|tag| styler.tag(&format!("#{tag}")))
+                .collect::<Vec<String>>()
+                .join(" "),
+        ]);
+    }
+
+    builder.insert_record(0, vec!["Note", "Modified", "Tags"]);
+
+    let mut table = builder.build();
+    table.with(Style::sharp());
+
+    format!("{table}")
+}
+
+fn serialize_modified<S>(
+    modified: &Option<DateTime<Local>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    match modified {
+        Some(dt) => serializer.serialize_str(&dt.to_rfc3339()),
+        None => serializer.serialize_none(),
+    }
+}
@@ -0,0 +1,34 @@
+use crate::{
+    mcp::McpServer,
+    note_text,
+    util::{get_current_vault, CommandResult},
+};
+use clap::Args;
+use std::io::{self, BufRead, Write};
+
+#[derive(Args, Debug, Clone)]
+pub struct McpCommand {
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+pub fn entry(cmd: &McpCommand) -> CommandResult {
+    let vault = get_current_vault(cmd.vault.clone())?;
+    note_text::avoid_mapping();
+    eprintln!("Serving `{}` over MCP on stdio", vault.name);
+    let server = McpServer::new(vault);
+
+    let mut stdout = io::stdout().lock();
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(reply) = server.handle(&line) {
+            writeln!(stdout, "{reply}")?;
+            stdout.flush()?;
+        }
+    }
+
+    Ok(None)
+}
@@ -0,0 +1,185 @@
+use crate::{
+    formats::yaml_to_json_value,
+    resolve, scan,
+    util::{get_current_vault, CommandResult},
+};
+use anyhow::{bail, Context};
+use clap::{Args, Subcommand};
+use libobsidian::{
+    frontmatter::{remove_property, set_property},
+    ObsidianNote,
+};
+use std::{fs, path::PathBuf};
+
+#[derive(Args, Debug, Clone)]
+#[command(args_conflicts_with_subcommands = true)]
+#[command(arg_required_else_help = true)]
+pub struct MetaCommand {
+    #[command(subcommand)]
+    command: Option<Subcommands>,
+}
+
+#[derive(Debug, Subcommand, Clone)]
+enum Subcommands {
+    /// Print the value of a frontmatter property, or of `key:: value` inline
+    /// fields when there's no such property
+    Get(GetArgs),
+
+    /// Set a frontmatter property, adding a frontmatter block if needed
+    Set(SetArgs),
+
+    /// Remove a frontmatter property
+    Unset(UnsetArgs),
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+enum MetaFormat {
+    Yaml,
+    Json,
+}
+
+#[derive(Args, Debug, Clone)]
+struct NoteArgs {
+    #[arg(help = "The name or path of the note, matched exactly or fuzzily within the vault")]
+    note: String,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+#[derive(Args, Debug, Clone)]
+struct GetArgs {
+    key: String,
+
+    #[command(flatten)]
+    note: NoteArgs,
+
+    #[arg(long, short = 'f', default_value = "yaml")]
+    format: MetaFormat,
+}
+
+#[derive(Args, Debug, Clone)]
+struct SetArgs {
+    key: String,
+
+    #[arg(help = "The new value, parsed as YAML so `3`, `true` and `[a, b]` keep their types")]
+    value: String,
+
+    #[command(flatten)]
+    note: NoteArgs,
+}
+
+#[derive(Args, Debug, Clone)]
+struct UnsetArgs {
+    key: String,
+
+    #[command(flatten)]
+    note: NoteArgs,
+}
+
+pub fn entry(cmd: &MetaCommand) -> CommandResult {
+    match &cmd.command {
+        Some(Subcommands::Get(args)) => get(args),
+        Some(Subcommands::Set(args)) => set(args),
+        Some(Subcommands::Unset(args)) => unset(args),
+        None => todo!(),
+    }
+}
+
+/// Resolve the note, returning its path and the name to show in messages
+fn find_note(args: &NoteArgs) -> anyhow::Result<(PathBuf, String)> {
+    let vault = get_current_vault(args.vault.clone())?;
+    let note_path = resolve::pick_note(&args.note, &vault.path)?;
+    let relative = scan::relative_note_path(&note_path, &vault.path);
+
+    Ok((note_path, relative))
+}
+
+fn get(args: &GetArgs) -> CommandResult {
+    let (note_path, relative) = find_note(&args.note)?;
+    let note = ObsidianNote::read_from_path(&note_path)
+        .with_context(|| format!("Could not parse note {relative}"))?;
+
+    let Some(value) = note.field(&args.key) else {
+        bail!("Property `{}` is not set on {relative}", args.key);
+    };
+
+    let formatted = match (&args.format, value) {
+        (MetaFormat::Json, value) => serde_json::to_string(&yaml_to_json_value(&value))?,
+        (MetaFormat::Yaml, serde_yaml::Value::String(value)) => value,
+        (MetaFormat::Yaml, value) => serde_yaml::to_string(&value)?.trim_end().to_string(),
+    };
+
+    Ok(Some(formatted))
+}
+
+fn set(args: &SetArgs) -> CommandResult {
+    let (note_path, relative) = find_note(&args.note)?;
+    let contents = fs::read_to_string(&note_path)
+        .with_context(|| format!("Could not read note {relative}"))?;
+
+    let updated = set_property(&contents, &args.key, &yaml_value(&args.value)?);
+    fs::write(&note_path, updated).with_context(|| format!("Could not write note {relative}"))?;
+
+    Ok(Some(format!("Set `{}` on {relative}", args.key)))
+}
+
+fn unset(args: &UnsetArgs) -> CommandResult {
+    let (note_path, relative) = find_note(&args.note)?;
+    let contents = fs::read_to_string(&note_path)
+        .with_context(|| format!("Could not read note {relative}"))?;
+
+    let Some(updated) = remove_property(&contents, &args.key) else {
+        bail!("Property `{}` is not set on {relative}", args.key);
+    };
+    fs::write(&note_path, updated).with_context(|| format!("Could not write note {relative}"))?;
+
+    Ok(Some(format!("Removed `{}` from {relative}", args.key)))
+}
+
+/// The value as it should be written after `key: `. A single-line scalar
+/// or `[flow, list]` is kept as typed, anything else is quoted, so `- a`
+/// doesn't become a broken block list and `#x` isn't dropped as a comment.
+fn yaml_value(input: &str) -> anyhow::Result<String> {
+    use serde_yaml::Value;
+
+    let typed = input.trim();
+    let keep_as_typed = !input.contains('\n')
+        && match serde_yaml::from_str::<Value>(typed) {
+            Ok(Value::Sequence(_)) => typed.starts_with('['),
+            // Otherwise a comment, which would lose the value
+            Ok(Value::Null) => matches!(typed, "null" | "Null" | "NULL" | "~"),
+            // Unless quoted, anything the parse drops, like a trailing
+            // comment, would be lost too
+            Ok(Value::String(parsed)) => parsed == typed || typed.starts_with(['\'', '"']),
+            Ok(Value::Bool(_) | Value::Number(_)) => true,
+            _ => false,
+        };
+
+    if keep_as_typed {
+        return Ok(typed.to_string());
+    }
+
+    let quoted = serde_yaml::to_string(&Value::String(input.to_string()))?;
+    Ok(quoted.trim_end().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case("done", "done" ; "plain string")]
+    #[test_case("[a, b]", "[a, b]" ; "flow list")]
+    #[test_case("3", "3" ; "number")]
+    #[test_case("key: value", "'key: value'" ; "would be a mapping")]
+    #[test_case("", "''" ; "empty")]
+    #[test_case("- a", "'- a'" ; "would be a block list")]
+    #[test_case("#x", "'#x'" ; "would be a comment")]
+    #[test_case("done #later", "'done #later'" ; "trailing comment")]
+    #[test_case("!custom y", "'!custom y'" ; "tagged")]
+    #[test_case("~", "~" ; "null")]
+    fn yaml_value_quotes_when_needed(input: &str, expected: &str) {
+        assert_eq!(yaml_value(input).unwrap(), expected);
+    }
+}
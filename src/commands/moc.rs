@@ -0,0 +1,252 @@
+use crate::{
+    cache::MetadataCache,
+    link_resolver::LinkResolver,
+    scan,
+    util::{get_current_vault, is_relative_inside, CommandResult},
+};
+use anyhow::{bail, Context};
+use clap::Args;
+use libobsidian::links::LinkKind;
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Written around the list, so regenerating it leaves the rest of the note be
+const START_MARKER: &str = "<!-- obx moc start -->";
+const END_MARKER: &str = "<!-- obx moc end -->";
+
+#[derive(Args, Debug, Clone)]
+pub struct MocCommand {
+    #[arg(help = "The vault-relative folder to list the notes of")]
+    folder: String,
+
+    /// The index note's name, in the folder. Defaults to the folder's name.
+    #[arg(long, short = 'n')]
+    name: Option<String>,
+
+    /// Put the notes under a heading for each subfolder or tag
+    #[arg(long, short = 'g')]
+    group_by: Option<MocGrouping>,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+enum MocGrouping {
+    /// The folder each note is in
+    Folder,
+    /// Each of a note's tags, so a note may be listed more than once
+    Tag,
+}
+
+pub fn entry(cmd: &MocCommand) -> CommandResult {
+    let vault = get_current_vault(cmd.vault.clone())?;
+    // `.` lists the whole vault
+    if cmd.folder != "." && !is_relative_inside(cmd.folder.trim_end_matches('/')) {
+        bail!(
+            "Invalid folder `{}`, expected one within the vault",
+            cmd.folder
+        );
+    }
+    if let Some(name) = cmd.name.as_deref().filter(|name| !is_relative_inside(name)) {
+        bail!("Invalid name `{name}`, expected one within the folder");
+    }
+    let folder_path = vault.path.join(&cmd.folder);
+    if !folder_path.is_dir() {
+        bail!(
+            "Folder `{}` not found in vault `{}`",
+            cmd.folder,
+            vault.name
+        );
+    }
+    let folder_name = match scan::relative_note_path(&folder_path, &vault.path)
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+    {
+        None | Some("" | ".") => vault.name.clone(),
+        Some(name) => name.to_string(),
+    };
+    let name = cmd.name.as_deref().unwrap_or(&folder_name);
+    let index_path = folder_path.join(format!("{}.md", name.trim_end_matches(".md")));
+
+    let mut notes: Vec<PathBuf> = scan::note_paths(&folder_path)?
+        .into_iter()
+        .filter(|path| *path != index_path)
+        .collect();
+    notes.sort();
+
+    let resolver = LinkResolver::new(&vault.path)?;
+    let cache = MetadataCache::current(&vault.path)?;
+    let entries: Vec<Entry> = notes
+        .iter()
+        .map(|note_path| {
+            let relative = scan::relative_note_path(note_path, &vault.path);
+            let subfolder = scan::relative_note_path(note_path, &folder_path)
+                .rsplit_once('/')
+                .map(|(folder, _)| folder.to_string());
+            Entry {
+                link: link(&resolver, note_path, &index_path),
+                tags: cache
+                    .get(&relative)
+                    .map(|note| note.tags.clone())
+                    .unwrap_or_default(),
+                subfolder,
+            }
+        })
+        .collect();
+    let list = render(&entries, cmd.group_by);
+
+    let relative = scan::relative_note_path(&index_path, &vault.path);
+    let (contents, verb) = match fs::read_to_string(&index_path) {
+        Ok(existing) => match update(&existing, &list) {
+            updated if updated == existing => return Ok(Some(format!("{relative} is up to date"))),
+            updated => (updated, "Updated"),
+        },
+        Err(_) => (format!("# {name}\n\n{}\n", block(&list)), "Created"),
+    };
+    fs::write(&index_path, contents)
+        .with_context(|| format!("Could not write to note {}", index_path.display()))?;
+
+    let noun = if notes.len() == 1 { "note" } else { "notes" };
+    Ok(Some(format!(
+        "{verb} {relative} listing {} {noun}",
+        notes.len()
+    )))
+}
+
+struct Entry {
+    /// The wikilink to the note
+    link: String,
+    tags: Vec<String>,
+    /// Where the note is below the listed folder, `None` when it's directly
+    /// in it
+    subfolder: Option<String>,
+}
+
+/// A wikilink to `note_path` from the index, labelled with the note's name
+/// when the link has to be a path
+fn link(resolver: &LinkResolver, note_path: &Path, index_path: &Path) -> String {
+    let target = resolver.link_target(note_path, index_path, LinkKind::Wikilink);
+    match target.rsplit_once('/') {
+        Some((_, name)) => format!("[[{target}|{name}]]"),
+        None => format!("[[{target}]]"),
+    }
+}
+
+/// The list of links, under a heading for each group when grouping
+fn render(entries: &[Entry], grouping: Option<MocGrouping>) -> String {
+    let item = |entry: &&Entry| format!("- {}", entry.link);
+    let Some(grouping) = grouping else {
+        return entries
+            .iter()
+            .map(|entry| item(&entry))
+            .collect::<Vec<_>>()
+            .join("\n");
+    };
+
+    // Notes outside every group come first, without a heading
+    let mut ungrouped = Vec::new();
+    let mut groups: BTreeMap<&str, Vec<&Entry>> = BTreeMap::new();
+    for entry in entries {
+        match grouping {
+            MocGrouping::Folder => match &entry.subfolder {
+                Some(folder) => groups.entry(folder).or_default().push(entry),
+                None => ungrouped.push(entry),
+            },
+            MocGrouping::Tag if entry.tags.is_empty() => ungrouped.push(entry),
+            MocGrouping::Tag => {
+                for tag in &entry.tags {
+                    groups.entry(tag).or_default().push(entry);
+                }
+            }
+        }
+    }
+
+    let mut sections = Vec::new();
+    if !ungrouped.is_empty() {
+        sections.push(ungrouped.iter().map(item).collect::<Vec<_>>().join("\n"));
+    }
+    for (group, entries) in groups {
+        let heading = match grouping {
+            MocGrouping::Folder => format!("## {group}"),
+            MocGrouping::Tag => format!("## #{group}"),
+        };
+        let items: Vec<String> = entries.iter().map(item).collect();
+        sections.push(format!("{heading}\n\n{}", items.join("\n")));
+    }
+    sections.join("\n\n")
+}
+
+/// `list` between the markers
+fn block(list: &str) -> String {
+    format!("{START_MARKER}\n{list}\n{END_MARKER}")
+}
+
+/// `existing` with the list between its markers replaced, or added at the end
+/// when it has none
+fn update(existing: &str, list: &str) -> String {
+    let markers = existing.find(START_MARKER).and_then(|start| {
+        existing[start..]
+            .find(END_MARKER)
+            .map(|end| (start, start + end + END_MARKER.len()))
+    });
+    match markers {
+        Some((start, end)) => format!("{}{}{}", &existing[..start], block(list), &existing[end..]),
+        None => format!("{}\n\n{}\n", existing.trim_end(), block(list)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(link: &str, subfolder: Option<&str>, tags: &[&str]) -> Entry {
+        Entry {
+            link: link.to_string(),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            subfolder: subfolder.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn groups_by_folder_and_tag() {
+        let entries = [
+            entry("[[a]]", None, &["rust"]),
+            entry("[[b]]", Some("sub"), &["rust", "cli"]),
+            entry("[[c]]", Some("sub"), &[]),
+        ];
+
+        assert_eq!(
+            render(&entries, Some(MocGrouping::Folder)),
+            "- [[a]]\n\n## sub\n\n- [[b]]\n- [[c]]"
+        );
+        assert_eq!(
+            render(&entries, Some(MocGrouping::Tag)),
+            "- [[c]]\n\n## #cli\n\n- [[b]]\n\n## #rust\n\n- [[a]]\n- [[b]]"
+        );
+    }
+
+    #[test]
+    fn keeps_what_is_outside_the_markers() {
+        let existing = format!(
+            "# Index\n\nIntro\n\n{}\n\n## Notes by hand\n",
+            block("- [[old]]")
+        );
+
+        assert_eq!(
+            update(&existing, "- [[new]]"),
+            format!(
+                "# Index\n\nIntro\n\n{}\n\n## Notes by hand\n",
+                block("- [[new]]")
+            )
+        );
+        assert_eq!(
+            update("# Index\n", "- [[new]]"),
+            format!("# Index\n\n{}\n", block("- [[new]]"))
+        );
+    }
+}
@@ -0,0 +1,90 @@
+use crate::{
+    hooks::{self, Event},
+    relocate::Relocation,
+    scan,
+    util::{get_current_vault, is_relative_inside, CommandResult},
+};
+use anyhow::{bail, Context};
+use clap::Args;
+use std::{fs, path::PathBuf};
+
+#[derive(Args, Debug, Clone)]
+pub struct MoveFolderCommand {
+    #[arg(help = "The vault-relative folder to move")]
+    folder: String,
+
+    #[arg(help = "Where it goes, vault-relative, which mustn't exist yet")]
+    destination: String,
+
+    /// Show which notes would change without touching any files
+    #[arg(long)]
+    dry_run: bool,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+pub fn entry(cmd: &MoveFolderCommand) -> CommandResult {
+    let vault = get_current_vault(cmd.vault.clone())?;
+    for path in [&cmd.folder, &cmd.destination] {
+        if !is_relative_inside(path.trim_end_matches('/')) {
+            bail!("Invalid folder `{path}`, expected one within the vault");
+        }
+    }
+    let folder = vault.path.join(cmd.folder.trim_end_matches('/'));
+    let destination = vault.path.join(cmd.destination.trim_end_matches('/'));
+    if !folder.is_dir() || folder == vault.path {
+        bail!(
+            "Folder `{}` not found in vault `{}`",
+            cmd.folder,
+            vault.name
+        );
+    }
+    if destination.exists() {
+        bail!("`{}` already exists", cmd.destination);
+    }
+    if destination.starts_with(&folder) {
+        bail!("Can't move `{}` into itself", cmd.folder);
+    }
+
+    // Every file is planned as moved so links into the folder, to
+    // attachments as well as notes, follow it
+    let moves: Vec<(PathBuf, PathBuf)> = scan::file_paths(&vault.path)?
+        .into_iter()
+        .filter_map(|file| {
+            let within = file.strip_prefix(&folder).ok()?.to_path_buf();
+            Some((file, destination.join(within)))
+        })
+        .collect();
+    let relocation = Relocation::plan(&vault.path, moves)?;
+
+    let verb = if cmd.dry_run { "Would move" } else { "Moved" };
+    let mut output = vec![format!(
+        "{verb} {} to {}",
+        scan::relative_note_path(&folder, &vault.path),
+        scan::relative_note_path(&destination, &vault.path)
+    )];
+    output.extend(relocation.summary(&vault.path, cmd.dry_run));
+
+    if !cmd.dry_run {
+        relocation.apply_edits()?;
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Could not create directory {}", parent.display()))?;
+        }
+        fs::rename(&folder, &destination).with_context(|| {
+            format!(
+                "Could not move {} to {}",
+                folder.display(),
+                destination.display()
+            )
+        })?;
+        for (from, to) in &relocation.moves {
+            if scan::is_markdown(to) {
+                hooks::run(Event::AfterMove, &vault, to, Some(from))?;
+            }
+        }
+    }
+
+    Ok(Some(output.join("\n")))
+}
@@ -0,0 +1,110 @@
+use crate::{
+    hooks::{self, Event},
+    relocate::Relocation,
+    resolve, scan,
+    util::{get_current_vault, CommandResult},
+};
+use anyhow::{bail, Context};
+use clap::Args;
+use globset::GlobBuilder;
+use std::path::{Path, PathBuf};
+
+#[derive(Args, Debug, Clone)]
+pub struct MoveCommand {
+    #[arg(
+        required = true,
+        help = "Names or paths of the notes to move. Globs such as `inbox/*.md` match vault-relative paths"
+    )]
+    notes: Vec<String>,
+
+    #[arg(help = "The vault-relative folder to move the notes into, created if missing")]
+    folder: String,
+
+    /// Show which notes would change without touching any files
+    #[arg(long)]
+    dry_run: bool,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+pub fn entry(cmd: &MoveCommand) -> CommandResult {
+    let vault = get_current_vault(cmd.vault.clone())?;
+    let folder = vault.path.join(cmd.folder.trim_matches('/'));
+    if folder.is_file() {
+        bail!("`{}` is a file, not a folder", cmd.folder);
+    }
+
+    let mut notes: Vec<PathBuf> = Vec::new();
+    for query in &cmd.notes {
+        for note in expand_query(query, &vault.path)? {
+            if !notes.contains(&note) {
+                notes.push(note);
+            }
+        }
+    }
+
+    let moves: Vec<(PathBuf, PathBuf)> = notes
+        .into_iter()
+        .filter(|note| note.parent() != Some(folder.as_path()))
+        .map(|note| {
+            let file_name = note.file_name().context("note should have a file name")?;
+            let destination = folder.join(file_name);
+            Ok((note, destination))
+        })
+        .collect::<anyhow::Result<_>>()?;
+
+    if moves.is_empty() {
+        return Ok(Some(format!("Nothing to move, already in {}", cmd.folder)));
+    }
+
+    let relocation = Relocation::plan(&vault.path, moves)?;
+
+    let verb = if cmd.dry_run { "Would move" } else { "Moved" };
+    let mut output: Vec<String> = relocation
+        .moves
+        .iter()
+        .map(|(from, to)| {
+            format!(
+                "{verb} {} to {}",
+                scan::relative_note_path(from, &vault.path),
+                scan::relative_note_path(to, &vault.path)
+            )
+        })
+        .collect();
+    output.extend(relocation.summary(&vault.path, cmd.dry_run));
+
+    if !cmd.dry_run {
+        relocation.apply()?;
+        for (from, to) in &relocation.moves {
+            hooks::run(Event::AfterMove, &vault, to, Some(from))?;
+        }
+    }
+
+    Ok(Some(output.join("\n")))
+}
+
+/// The notes a command line argument refers to: every note matching it when
+/// it's a glob, otherwise the single note it resolves to
+fn expand_query(query: &str, vault_path: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    if !query.contains(['*', '?', '[', '{']) {
+        return Ok(vec![resolve::pick_note(query, vault_path)?]);
+    }
+
+    let glob = GlobBuilder::new(query)
+        .literal_separator(true)
+        .build()
+        .with_context(|| format!("Invalid glob `{query}`"))?
+        .compile_matcher();
+
+    let matches: Vec<PathBuf> = scan::note_paths(vault_path)?
+        .into_iter()
+        .filter(|path| glob.is_match(scan::relative_note_path(path, vault_path)))
+        .collect();
+
+    if matches.is_empty() {
+        bail!("No notes matching `{query}` found in vault");
+    }
+
+    Ok(matches)
+}
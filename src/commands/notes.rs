@@ -1,17 +1,23 @@
 use crate::{
     cli_config,
+    commands::uri,
+    editor,
     formats::{yaml_to_json_value, yaml_to_string_map},
+    hooks::{self, Event},
+    resolve, scan, template,
     util::{get_current_vault, resolve_note_path, should_enable_interactivity, CommandResult},
+    vault_settings,
+    zettel::ZettelSettings,
 };
 use anyhow::{anyhow, bail, Context};
 use atty::{isnt, Stream};
+use chrono::Local;
 use clap::{Args, Subcommand};
 use dialoguer::Confirm;
-use libobsidian::{ObsidianNote, Properties};
+use libobsidian::{frontmatter::set_property, tags::frontmatter_tags, ObsidianNote, Properties};
 use std::{
     fs, io,
     path::{Path, PathBuf},
-    process,
 };
 use tabled::{builder::Builder, settings::Style};
 use walkdir::WalkDir;
@@ -32,11 +38,11 @@ enum Subcommands {
     /// Output the raw markdown contents of a note
     View(ViewArgs),
 
-    /// Open a note in the Obsidian app
-    Open(OpenArgs),
+    /// Open a note in the Obsidian app, the same as `obx uri --open`
+    Open(uri::UriCommand),
 
-    /// Print the Obsidian URI of a note
-    Uri(UriArgs),
+    /// Print the Obsidian URI of a note, the same as `obx uri`
+    Uri(uri::UriCommand),
 
     /// Create a new note
     Create(CreateArgs),
@@ -88,18 +94,23 @@ struct ViewArgs {
 struct CreateArgs {
     #[command(flatten)]
     common: NoteArgs,
-}
 
-#[derive(Args, Debug, Clone)]
-struct OpenArgs {
-    #[command(flatten)]
-    common: NoteArgs,
-}
+    /// Start the note from a template in the vault's template folder
+    #[arg(long, short = 't')]
+    template: Option<String>,
 
-#[derive(Args, Debug, Clone)]
-struct UriArgs {
-    #[command(flatten)]
-    common: NoteArgs,
+    /// Set a custom template variable, can be repeated
+    #[arg(long = "var", value_name = "KEY=VALUE", value_parser = template::parse_var, requires = "template")]
+    vars: Vec<(String, String)>,
+
+    /// Add a tag to the note's `tags` property, can be repeated
+    #[arg(long = "tag", value_name = "TAG")]
+    tags: Vec<String>,
+
+    /// Start the note's name with a Zettelkasten ID, the time in the
+    /// config's `zettel.id_format`
+    #[arg(long, short = 'z')]
+    zettel: bool,
 }
 
 #[derive(Args, Debug, Clone)]
@@ -174,18 +185,25 @@ pub fn entry(cmd: &NotesCommand) -> anyhow::Result<Option<String>> {
             let args = EnrichedNoteArgs::from_args(common)?;
             view(args)
         }
-        Some(Subcommands::Uri(UriArgs { common })) => {
-            let args = EnrichedNoteArgs::from_args(common)?;
-            uri(args)
-        }
-        Some(Subcommands::Open(OpenArgs { common })) => {
-            let args = EnrichedNoteArgs::from_args(common)?;
-            open(args)
-        }
-        Some(Subcommands::Create(CreateArgs { common })) => {
+        Some(Subcommands::Uri(args)) => uri::entry(args),
+        Some(Subcommands::Open(args)) => uri::open(args),
+        Some(Subcommands::Create(CreateArgs {
+            common,
+            template,
+            vars,
+            tags,
+            zettel,
+        })) => {
             let stdin = maybe_stdin()?;
-            let args = EnrichedNoteArgs::from_args(common)?;
-            create(args, stdin)
+            let args = if *zettel {
+                EnrichedNoteArgs::for_zettel(common)?
+            } else {
+                EnrichedNoteArgs::for_new_note(common)?
+            };
+            match template {
+                Some(template) => create_from_template(args, template, vars, tags, stdin),
+                None => create(args, tags, stdin),
+            }
         }
         Some(Subcommands::Edit(EditArgs {
             common,
@@ -249,6 +267,47 @@ impl EnrichedNoteArgs {
     }
 }
 
+impl EnrichedNoteArgs {
+    /// Like [`EnrichedNoteArgs::from_args`], but a bare note name goes in the
+    /// folder the Obsidian app puts new notes in
+    fn for_new_note(args: &NoteArgs) -> anyhow::Result<Self> {
+        let vault = get_current_vault(args.vault.clone())?;
+
+        let folder = match vault_settings::read(&vault.path) {
+            Some(settings) if !args.note.contains(['/', '\\']) => settings.new_note_folder(),
+            _ => PathBuf::new(),
+        };
+        let note = folder.join(&args.note);
+
+        Self::from_args(&NoteArgs {
+            note: note.to_string_lossy().into_owned(),
+            vault: Some(vault.name),
+        })
+    }
+}
+
+impl EnrichedNoteArgs {
+    /// Like [`EnrichedNoteArgs::for_new_note`], with a Zettelkasten ID for
+    /// now in front of the note's name
+    fn for_zettel(args: &NoteArgs) -> anyhow::Result<Self> {
+        let settings = ZettelSettings::current();
+        let now = Local::now();
+        let note = Self::for_new_note(&NoteArgs {
+            note: settings.prefix(&args.note, &now),
+            vault: args.vault.clone(),
+        })?;
+
+        let id = settings.id(&now);
+        if let Some(existing) = resolve::find_by_id(&id, &note.vault.path)?.first() {
+            bail!(
+                "A note with the ID {id} already exists: {}",
+                scan::relative_note_path(existing, &note.vault.path)
+            );
+        }
+        Ok(note)
+    }
+}
+
 struct EnrichedListArgs {
     vault: cli_config::Vault,
     base_path: PathBuf,
@@ -302,18 +361,15 @@ fn list(args: EnrichedListArgs, format: &ListFormatOption) -> CommandResult {
 
 fn collect_notes(base_path: &Path, vault_path: &Path) -> anyhow::Result<Vec<String>> {
     let mut notes = Vec::new();
+    let ignored = scan::Ignored::for_path(vault_path);
 
-    for entry in WalkDir::new(base_path) {
+    let walker = WalkDir::new(base_path)
+        .into_iter()
+        .filter_entry(|entry| !scan::is_ignored_entry(&ignored, entry));
+    for entry in walker {
         let entry = entry?;
 
-        if entry.file_type().is_file()
-            && entry
-                .path()
-                .extension()
-                .and_then(|ext| ext.to_str())
-                .map(|ext| ext.eq_ignore_ascii_case("md"))
-                .unwrap_or(false)
-        {
+        if entry.file_type().is_file() && ignored.is_readable_note(entry.path()) {
             let relative_path = entry.path().strip_prefix(vault_path).with_context(|| {
                 format!(
                     "Could not determine note path relative to vault: {}",
@@ -348,27 +404,6 @@ fn format_note_table(notes: &[String]) -> String {
     format!("{table}")
 }
 
-fn obsidian_note_uri(note_path: &PathBuf, vault: String) -> String {
-    format!(
-        "obsidian://open?vault={vault}&file={file}",
-        file = note_path.display()
-    )
-}
-
-fn open(note: EnrichedNoteArgs) -> CommandResult {
-    let uri = obsidian_note_uri(&note.note_path, note.vault.name);
-
-    open::that(&uri).with_context(|| format!("Could not open obsidian url `{uri}`"))?;
-
-    Ok(None)
-}
-
-fn uri(note: EnrichedNoteArgs) -> CommandResult {
-    let uri = obsidian_note_uri(&note.note_path, note.vault.name);
-
-    Ok(Some(uri))
-}
-
 fn write_note(obsidian_note: &ObsidianNote) -> anyhow::Result<()> {
     let note_dir = obsidian_note
         .file_path
@@ -409,26 +444,104 @@ fn create_note(note: &EnrichedNoteArgs, note_contents: &str) -> anyhow::Result<(
     write_note(&obsidian_note)
 }
 
-fn create(note: EnrichedNoteArgs, stdin: Option<String>) -> CommandResult {
-    let (content, properties) = parse_create_stdin(stdin)?;
+fn create(note: EnrichedNoteArgs, tags: &[String], stdin: Option<String>) -> CommandResult {
+    let (content, mut properties) = parse_create_stdin(stdin)?;
+    if !tags.is_empty() {
+        let tags = with_tags(properties.as_ref(), tags);
+        let mut mapping = match properties {
+            Some(serde_yaml::Value::Mapping(mapping)) => mapping,
+            _ => serde_yaml::Mapping::new(),
+        };
+        mapping.insert("tags".into(), tags.into());
+        properties = Some(serde_yaml::Value::Mapping(mapping));
+    }
 
     let obsidian_note = ObsidianNote {
         file_path: note.note_path.clone(),
         file_raw_contents: Some(content.clone().unwrap_or_default()),
         file_body: content.unwrap_or_default(),
-        properties: properties,
+        properties,
     };
 
     write_note(&obsidian_note)?;
 
+    edit_created(&note)
+}
+
+fn create_from_template(
+    note: EnrichedNoteArgs,
+    template_name: &str,
+    vars: &[(String, String)],
+    tags: &[String],
+    stdin: Option<String>,
+) -> CommandResult {
+    let template_path = template::find_template(&note.vault.path, template_name)?;
+    let template_contents = fs::read_to_string(&template_path)
+        .with_context(|| format!("Could not read template `{template_name}`"))?;
+
+    let context = template::TemplateContext::for_note(&note.note_path, &note.vault.path, vars);
+    let mut contents = template::render(&template_contents, &context);
+
+    // Piped input still works alongside a template: JSON sets properties in
+    // the template's frontmatter, anything else is appended to the body
+    let (stdin_body, stdin_properties) = parse_create_stdin(stdin)?;
+    if let Some(serde_yaml::Value::Mapping(properties)) = stdin_properties {
+        for (key, value) in properties {
+            let Some(key) = key.as_str() else {
+                continue;
+            };
+            let value = serde_json::to_string(&yaml_to_json_value(&value))?;
+            contents = set_property(&contents, key, &value);
+        }
+    }
+    if !tags.is_empty() {
+        let properties = ObsidianNote::parse(&note.note_path, contents.clone())
+            .ok()
+            .and_then(|note| note.properties);
+        let tags = with_tags(properties.as_ref(), tags);
+        contents = set_property(&contents, "tags", &serde_json::to_string(&tags)?);
+    }
+    if let Some(body) = stdin_body.filter(|body| !body.trim().is_empty()) {
+        if !contents.is_empty() && !contents.ends_with('\n') {
+            contents.push('\n');
+        }
+        contents.push_str(&body);
+    }
+
+    let note_dir = note
+        .note_path
+        .parent()
+        .expect("note_path should have a parent");
+    fs::create_dir_all(note_dir)
+        .with_context(|| format!("Could not create directory {}", note_dir.display()))?;
+    fs::write(&note.note_path, contents)
+        .with_context(|| format!("Could not create note {}", note.note_path.display()))?;
+
+    edit_created(&note)
+}
+
+/// The tags already in `properties` followed by any of `added` that aren't,
+/// without their `#`
+fn with_tags(properties: Option<&Properties>, added: &[String]) -> Vec<String> {
+    let mut tags = properties.map(frontmatter_tags).unwrap_or_default();
+    for tag in added {
+        let tag = tag.trim().trim_start_matches('#');
+        if !tag.is_empty() && !tags.iter().any(|existing| existing == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
+
+fn edit_created(note: &EnrichedNoteArgs) -> CommandResult {
     let editor = cli_config::resolve_editor()?;
 
-    let editor_status = process::Command::new(&editor)
-        .arg(&note.note_path)
+    let editor_status = editor::command(&editor, &note.note_path, None, Some(&note.vault.path))?
         .status()
         .with_context(|| format!("failed to execute editor `{editor}`"))?;
 
     if editor_status.success() {
+        hooks::run(Event::AfterCreate, &note.vault, &note.note_path, None)?;
         // @TODO: this isn't strictly true, discarding changes with :q!
         // in vim will still show this message
         Ok(Some(format!("Created note {}", &note.note_path.display())))
@@ -462,6 +575,7 @@ fn parse_create_stdin(
 fn edit(note: EnrichedNoteArgs, create_flag: &bool) -> CommandResult {
     let note_exists = note.note_path.exists();
     let term_is_attended = should_enable_interactivity();
+    let mut created = false;
 
     if !note_exists {
         let mut confirmation = false;
@@ -481,6 +595,7 @@ fn edit(note: EnrichedNoteArgs, create_flag: &bool) -> CommandResult {
         if confirmation || *create_flag {
             let note_contents = "";
             create_note(&note, note_contents)?;
+            created = true;
         } else {
             return Ok(Some("Aborted".to_string()));
         }
@@ -488,12 +603,14 @@ fn edit(note: EnrichedNoteArgs, create_flag: &bool) -> CommandResult {
 
     let editor = cli_config::resolve_editor()?;
 
-    let editor_status = process::Command::new(&editor)
-        .arg(&note.note_path)
+    let editor_status = editor::command(&editor, &note.note_path, None, Some(&note.vault.path))?
         .status()
         .with_context(|| format!("failed to execute editor `{editor}`"))?;
 
     if editor_status.success() {
+        if created {
+            hooks::run(Event::AfterCreate, &note.vault, &note.note_path, None)?;
+        }
         // @TODO: this isn't strictly true, discarding changes with :q!
         // in vim will still show this message
         Ok(Some(format!("Saved changes to {}", &note.note_file)))
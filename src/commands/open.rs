@@ -0,0 +1,48 @@
+use crate::{
+    cli_config, editor, resolve,
+    util::{get_current_vault, CommandResult},
+};
+use anyhow::Context;
+use clap::Args;
+use std::path::Path;
+
+#[derive(Args, Debug, Clone)]
+pub struct OpenCommand {
+    #[arg(help = "The name or path of the note, matched exactly or fuzzily within the vault")]
+    note: String,
+
+    /// The line to open the note at, for editors set up with `{line}`
+    #[arg(long, short = 'l')]
+    line: Option<usize>,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+pub fn entry(cmd: &OpenCommand) -> CommandResult {
+    let vault = get_current_vault(cmd.vault.clone())?;
+    let note_path = resolve::pick_note(&cmd.note, &vault.path)?;
+
+    open_in_editor(&note_path, cmd.line, Some(&vault.path))?;
+
+    Ok(None)
+}
+
+/// Open a note in the configured editor, waiting for it to exit
+pub fn open_in_editor(
+    note_path: &Path,
+    line: Option<usize>,
+    vault_path: Option<&Path>,
+) -> anyhow::Result<()> {
+    let editor = cli_config::resolve_editor()?;
+
+    let editor_status = editor::command(&editor, note_path, line, vault_path)?
+        .status()
+        .with_context(|| format!("failed to execute editor `{editor}`"))?;
+
+    if editor_status.success() {
+        Ok(())
+    } else {
+        Err(anyhow::Error::msg("Editor exited with non-0 exit code"))
+    }
+}
@@ -0,0 +1,31 @@
+use crate::{
+    link_graph::LinkGraph,
+    style::Styler,
+    util::{get_current_vault, CommandResult},
+};
+use clap::Args;
+
+#[derive(Args, Debug, Clone)]
+pub struct OrphansCommand {
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+pub fn entry(cmd: &OrphansCommand) -> CommandResult {
+    let vault = get_current_vault(cmd.vault.clone())?;
+    let graph = LinkGraph::current(&vault.path)?;
+
+    let orphans = graph.orphans();
+    if orphans.is_empty() {
+        return Ok(None);
+    }
+
+    let styler = Styler::current();
+    Ok(Some(
+        orphans
+            .iter()
+            .map(|orphan| styler.path(orphan))
+            .collect::<Vec<String>>()
+            .join("\n"),
+    ))
+}
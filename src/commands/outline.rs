@@ -0,0 +1,171 @@
+use crate::{
+    resolve,
+    util::{get_current_vault, CommandResult},
+};
+use clap::Args;
+use libobsidian::{headings::Heading, ObsidianNote};
+use serde::Serialize;
+
+#[derive(Args, Debug, Clone)]
+pub struct OutlineCommand {
+    #[arg(help = "The name or path of the note")]
+    note: String,
+
+    /// How many levels of nested headings to show
+    #[arg(long, short = 'd')]
+    depth: Option<usize>,
+
+    /// Show how many words each section has, its subsections included
+    #[arg(long, short = 'w')]
+    words: bool,
+
+    /// Print the outline as nested JSON objects
+    #[arg(long)]
+    json: bool,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+/// A heading and the headings nested beneath it
+#[derive(Serialize, Debug, PartialEq)]
+struct Section {
+    level: usize,
+    text: String,
+    /// Counting from 1, within the note's body
+    line: usize,
+    words: usize,
+    children: Vec<Section>,
+}
+
+pub fn entry(cmd: &OutlineCommand) -> CommandResult {
+    let vault = get_current_vault(cmd.vault.clone())?;
+    let note_path = resolve::pick_note(&cmd.note, &vault.path)?;
+    let note = ObsidianNote::read_from_path(&note_path)?;
+
+    let sections = outline(
+        &note.file_body,
+        &note.headings(),
+        cmd.depth.unwrap_or(usize::MAX),
+    );
+    if cmd.json {
+        return Ok(Some(serde_json::to_string(&sections)?));
+    }
+    if sections.is_empty() {
+        return Ok(None);
+    }
+
+    let mut lines = Vec::new();
+    print(&sections, 0, cmd.words, &mut lines);
+    Ok(Some(lines.join("\n")))
+}
+
+/// The tree of `headings` in `body`, down to `depth` levels of nesting. A
+/// heading is nested under the nearest heading above it with a lower level,
+/// so skipped levels don't leave gaps.
+fn outline(body: &str, headings: &[Heading], depth: usize) -> Vec<Section> {
+    let lines: Vec<&str> = body.lines().collect();
+    // Words in the headings themselves aren't counted
+    let words = |from: usize, to: usize| -> usize {
+        (from.min(lines.len())..to.min(lines.len()))
+            .filter(|idx| !headings.iter().any(|heading| heading.line == idx + 1))
+            .map(|idx| lines[idx].split_whitespace().count())
+            .sum()
+    };
+
+    // Each heading's section runs to the next heading at its level or above
+    let ends: Vec<usize> = headings
+        .iter()
+        .enumerate()
+        .map(|(idx, heading)| {
+            headings[idx + 1..]
+                .iter()
+                .find(|next| next.level <= heading.level)
+                .map_or(lines.len(), |next| next.line - 1)
+        })
+        .collect();
+
+    let mut remaining = headings.iter().zip(ends).peekable();
+    build(&mut remaining, 0, 1, depth, &words)
+}
+
+fn build<'a>(
+    headings: &mut std::iter::Peekable<impl Iterator<Item = (&'a Heading, usize)>>,
+    parent_level: usize,
+    depth: usize,
+    max_depth: usize,
+    words: &dyn Fn(usize, usize) -> usize,
+) -> Vec<Section> {
+    let mut sections = Vec::new();
+    while let Some((heading, end)) = headings.next_if(|(heading, _)| heading.level > parent_level) {
+        let children = build(headings, heading.level, depth + 1, max_depth, words);
+        if depth <= max_depth {
+            sections.push(Section {
+                level: heading.level,
+                text: heading.text.clone(),
+                line: heading.line,
+                words: words(heading.line, end),
+                children,
+            });
+        }
+    }
+    sections
+}
+
+fn print(sections: &[Section], indent: usize, words: bool, lines: &mut Vec<String>) {
+    for section in sections {
+        let mut line = format!(
+            "{}{} {}",
+            "  ".repeat(indent),
+            "#".repeat(section.level),
+            section.text
+        );
+        if words {
+            let noun = if section.words == 1 { "word" } else { "words" };
+            line.push_str(&format!(" ({} {noun})", section.words));
+        }
+        lines.push(line);
+        print(&section.children, indent + 1, words, lines);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use libobsidian::headings::parse_headings;
+
+    fn summary(sections: &[Section]) -> Vec<String> {
+        let mut lines = Vec::new();
+        print(sections, 0, true, &mut lines);
+        lines
+    }
+
+    #[test]
+    fn nests_headings_and_counts_words() {
+        let body = indoc! {"
+            ## Intro
+            one two
+            #### Skipped a level
+            three
+            ## Next
+            four five six
+            ### Deeper
+        "};
+        let headings = parse_headings(body);
+
+        assert_eq!(
+            summary(&outline(body, &headings, usize::MAX)),
+            [
+                "## Intro (3 words)",
+                "  #### Skipped a level (1 word)",
+                "## Next (3 words)",
+                "  ### Deeper (0 words)",
+            ]
+        );
+        assert_eq!(
+            summary(&outline(body, &headings, 1)),
+            ["## Intro (3 words)", "## Next (3 words)"]
+        );
+    }
+}
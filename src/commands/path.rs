@@ -0,0 +1,57 @@
+use crate::{
+    link_graph::LinkGraph,
+    resolve, scan,
+    util::{get_current_vault, CommandResult},
+};
+use anyhow::bail;
+use clap::Args;
+
+#[derive(Args, Debug, Clone)]
+pub struct PathCommand {
+    #[arg(help = "The name or path of the note to start from")]
+    from: String,
+
+    #[arg(help = "The name or path of the note to reach")]
+    to: String,
+
+    /// Give up on chains longer than this many links
+    #[arg(long, short = 'd')]
+    max_depth: Option<usize>,
+
+    /// Follow links backwards too, from the note linked to the note linking
+    #[arg(long, short = 'u')]
+    undirected: bool,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+pub fn entry(cmd: &PathCommand) -> CommandResult {
+    let vault = get_current_vault(cmd.vault.clone())?;
+    let from = scan::relative_note_path(&resolve::pick_note(&cmd.from, &vault.path)?, &vault.path);
+    let to = scan::relative_note_path(&resolve::pick_note(&cmd.to, &vault.path)?, &vault.path);
+    let graph = LinkGraph::current(&vault.path)?;
+
+    let Some(path) = graph.shortest_path(&from, &to, cmd.max_depth, cmd.undirected) else {
+        let within = cmd
+            .max_depth
+            .map(|depth| match depth {
+                1 => " within 1 link".to_string(),
+                depth => format!(" within {depth} links"),
+            })
+            .unwrap_or_default();
+        bail!("No chain of links from {from} to {to}{within}");
+    };
+
+    // Steps taken against a link, with --undirected, point back
+    let mut output = path[0].to_string();
+    for step in path.windows(2) {
+        let arrow = if graph.linked_notes(step[0]).contains(&step[1]) {
+            "→"
+        } else {
+            "←"
+        };
+        output.push_str(&format!(" {arrow} {}", step[1]));
+    }
+    Ok(Some(output))
+}
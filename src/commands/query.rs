@@ -0,0 +1,112 @@
+use crate::{
+    formats::yaml_to_json_value,
+    query::{field_value, Expr},
+    scan,
+    util::{get_current_vault, CommandResult},
+};
+use clap::Args;
+use libobsidian::ObsidianNote;
+use tabled::{builder::Builder, settings::Style};
+
+#[derive(Args, Debug, Clone)]
+pub struct QueryCommand {
+    /// A filter over frontmatter properties and `key:: value` inline fields, e.g.
+    /// `status == "active" && due < 2025-01-01`.
+    /// Supports `==`, `!=`, `<`, `<=`, `>`, `>=`, `contains`, `&&`, `||`, `!` and parentheses,
+    /// plus `file.name` and `file.tags` fields
+    query: String,
+
+    /// Comma-separated properties to show for each matching note
+    #[arg(long, short = 's', value_delimiter = ',', value_name = "FIELDS")]
+    select: Vec<String>,
+
+    #[arg(long, short = 'f', default_value = "table")]
+    format: QueryFormat,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+enum QueryFormat {
+    Table,
+    Json,
+}
+
+pub fn entry(cmd: &QueryCommand) -> CommandResult {
+    let vault = get_current_vault(cmd.vault.clone())?;
+    let expr = Expr::parse(&cmd.query)?;
+
+    let mut matches: Vec<(String, ObsidianNote)> = Vec::new();
+    for note_path in scan::note_paths(&vault.path)? {
+        let Ok(note) = ObsidianNote::read_from_path(&note_path) else {
+            continue;
+        };
+
+        if expr.matches(&note) {
+            matches.push((scan::relative_note_path(&note_path, &vault.path), note));
+        }
+    }
+
+    if matches.is_empty() {
+        return Ok(None);
+    }
+
+    let formatted = match cmd.format {
+        QueryFormat::Json => {
+            let rows: Vec<serde_json::Value> = matches
+                .iter()
+                .map(|(path, note)| {
+                    let mut row = serde_json::Map::new();
+                    row.insert("path".to_string(), path.clone().into());
+                    for field in &cmd.select {
+                        let value = field_value(note, field)
+                            .map(|value| yaml_to_json_value(&value))
+                            .unwrap_or_default();
+                        row.insert(field.clone(), value);
+                    }
+                    serde_json::Value::Object(row)
+                })
+                .collect();
+            serde_json::to_string(&rows)?
+        }
+        QueryFormat::Table => {
+            let mut builder = Builder::new();
+            for (path, note) in &matches {
+                let mut record = vec![path.clone()];
+                record.extend(cmd.select.iter().map(|field| {
+                    field_value(note, field)
+                        .map(display_value)
+                        .unwrap_or_default()
+                }));
+                builder.push_record(record);
+            }
+
+            let mut header = vec!["Note".to_string()];
+            header.extend(cmd.select.iter().cloned());
+            builder.insert_record(0, header);
+
+            let mut table = builder.build();
+            table.with(Style::sharp());
+            format!("{table}")
+        }
+    };
+
+    Ok(Some(formatted))
+}
+
+/// A property value as a single table cell, with lists joined by commas
+fn display_value(value: serde_yaml::Value) -> String {
+    match value {
+        serde_yaml::Value::String(s) => s,
+        serde_yaml::Value::Null => String::new(),
+        serde_yaml::Value::Sequence(items) => items
+            .into_iter()
+            .map(display_value)
+            .collect::<Vec<String>>()
+            .join(", "),
+        value => serde_yaml::to_string(&value)
+            .map(|s| s.trim_end().to_string())
+            .unwrap_or_default(),
+    }
+}
@@ -0,0 +1,68 @@
+use crate::{
+    commands::open::open_in_editor,
+    matching::MatchSettings,
+    scan,
+    util::{get_current_vault, CommandResult},
+};
+use anyhow::bail;
+use clap::Args;
+use libobsidian::ObsidianNote;
+use rand::seq::SliceRandom;
+use std::path::PathBuf;
+
+#[derive(Args, Debug, Clone)]
+pub struct RandomCommand {
+    /// Only pick from notes within this vault-relative folder
+    #[arg(long)]
+    folder: Option<String>,
+
+    /// Only pick from notes carrying this tag or one nested beneath it, in
+    /// frontmatter or inline
+    #[arg(long)]
+    tag: Option<String>,
+
+    /// Open the note in your editor instead of printing its path
+    #[arg(long, short = 'o')]
+    open: bool,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+pub fn entry(cmd: &RandomCommand) -> CommandResult {
+    let vault = get_current_vault(cmd.vault.clone())?;
+
+    let base_path = match &cmd.folder {
+        Some(folder) => {
+            let folder_path = vault.path.join(folder);
+            if !folder_path.is_dir() {
+                bail!("Folder `{}` not found in vault `{}`", folder, vault.name);
+            }
+            folder_path
+        }
+        None => vault.path.clone(),
+    };
+
+    let tag = cmd.tag.as_deref().map(|tag| tag.trim_start_matches('#'));
+    let matching = MatchSettings::read();
+    let candidates: Vec<PathBuf> = scan::note_paths(&base_path)?
+        .into_iter()
+        .filter(|path| match tag {
+            Some(tag) => ObsidianNote::read_from_path(path)
+                .map(|note| note.tags().iter().any(|t| matching.has_tag(t, tag)))
+                .unwrap_or(false),
+            None => true,
+        })
+        .collect();
+
+    let Some(note_path) = candidates.choose(&mut rand::thread_rng()) else {
+        bail!("No notes to pick from");
+    };
+
+    if cmd.open {
+        open_in_editor(note_path, None, Some(&vault.path))?;
+        return Ok(None);
+    }
+
+    Ok(Some(scan::relative_note_path(note_path, &vault.path)))
+}
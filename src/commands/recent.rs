@@ -0,0 +1,81 @@
+use crate::{
+    scan,
+    style::Styler,
+    util::{get_current_vault, parse_since, relative_time, CommandResult},
+};
+use chrono::{DateTime, Local};
+use clap::Args;
+use std::fs;
+use tabled::{builder::Builder, settings::Style};
+
+#[derive(Args, Debug, Clone)]
+pub struct RecentCommand {
+    #[arg(default_value_t = 10, help = "How many notes to list")]
+    count: usize,
+
+    /// Only list notes modified since a duration ago (`3d`, `12h`) or a date (`2024-01-31`)
+    #[arg(long, value_name = "TIME")]
+    since: Option<String>,
+
+    #[arg(long, short = 'f', default_value = "table")]
+    format: RecentFormat,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+enum RecentFormat {
+    Table,
+    Paths,
+}
+
+pub fn entry(cmd: &RecentCommand) -> CommandResult {
+    let vault = get_current_vault(cmd.vault.clone())?;
+    let since = cmd.since.as_deref().map(parse_since).transpose()?;
+
+    let mut notes: Vec<(DateTime<Local>, String)> = scan::note_paths(&vault.path)?
+        .into_iter()
+        .filter_map(|path| {
+            let modified = fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+            Some((
+                DateTime::<Local>::from(modified),
+                scan::relative_note_path(&path, &vault.path),
+            ))
+        })
+        .filter(|(modified, _)| since.map(|since| *modified >= since).unwrap_or(true))
+        .collect();
+
+    notes.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+    notes.truncate(cmd.count);
+
+    if notes.is_empty() {
+        return Ok(None);
+    }
+
+    let styler = Styler::current();
+    let formatted = match cmd.format {
+        RecentFormat::Paths => notes
+            .iter()
+            .map(|(_, path)| styler.path(path))
+            .collect::<Vec<String>>()
+            .join("\n"),
+        RecentFormat::Table => {
+            let now = Local::now();
+            let mut builder = Builder::new();
+            for (modified, path) in &notes {
+                builder.push_record([
+                    styler.path(path),
+                    styler.date(&relative_time(*modified, now)),
+                ]);
+            }
+            builder.insert_record(0, vec!["Note", "Modified"]);
+
+            let mut table = builder.build();
+            table.with(Style::sharp());
+            format!("{table}")
+        }
+    };
+
+    Ok(Some(formatted))
+}
@@ -0,0 +1,79 @@
+use crate::{
+    cache::MetadataCache,
+    index::Index,
+    link_graph::LinkGraph,
+    related::related_notes,
+    resolve, scan,
+    style::Styler,
+    util::{get_current_vault, CommandResult},
+};
+use clap::Args;
+use tabled::{builder::Builder, settings::Style};
+
+#[derive(Args, Debug, Clone)]
+pub struct RelatedCommand {
+    #[arg(help = "The name or path of the note to find related notes for")]
+    note: String,
+
+    /// Print at most this many notes
+    #[arg(long, short = 'n', default_value_t = 10)]
+    limit: usize,
+
+    #[arg(long, short = 'f', default_value = "table")]
+    format: RelatedFormat,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+enum RelatedFormat {
+    Table,
+    Paths,
+    Json,
+}
+
+pub fn entry(cmd: &RelatedCommand) -> CommandResult {
+    let vault = get_current_vault(cmd.vault.clone())?;
+    let note_path = resolve::pick_note(&cmd.note, &vault.path)?;
+    let relative = scan::relative_note_path(&note_path, &vault.path);
+
+    let mut related = related_notes(
+        &Index::current(&vault.path)?,
+        &MetadataCache::current(&vault.path)?,
+        &LinkGraph::current(&vault.path)?,
+        &relative,
+    );
+    related.truncate(cmd.limit);
+    if related.is_empty() {
+        return Ok(None);
+    }
+
+    let styler = Styler::current();
+    match cmd.format {
+        RelatedFormat::Json => Ok(Some(serde_json::to_string(&related)?)),
+        RelatedFormat::Paths => Ok(Some(
+            related
+                .iter()
+                .map(|note| styler.path(&note.path))
+                .collect::<Vec<String>>()
+                .join("\n"),
+        )),
+        RelatedFormat::Table => {
+            let mut builder = Builder::new();
+            for note in &related {
+                builder.push_record([
+                    styler.path(&note.path),
+                    format!("{:.2}", note.score),
+                    note.reasons(),
+                ]);
+            }
+            builder.insert_record(0, vec!["Note", "Score", "Why"]);
+
+            let mut table = builder.build();
+            table.with(Style::sharp());
+
+            Ok(Some(format!("{table}")))
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use crate::{
+    hooks::{self, Event},
+    relocate::Relocation,
+    resolve, scan,
+    util::{get_current_vault, CommandResult},
+};
+use anyhow::{bail, Context};
+use clap::Args;
+
+#[derive(Args, Debug, Clone)]
+pub struct RenameCommand {
+    #[arg(help = "The name or path of the note to rename")]
+    note: String,
+
+    #[arg(help = "The new name for the note, it stays in the same folder")]
+    new_name: String,
+
+    /// Show which notes would change without touching any files
+    #[arg(long)]
+    dry_run: bool,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+pub fn entry(cmd: &RenameCommand) -> CommandResult {
+    let vault = get_current_vault(cmd.vault.clone())?;
+    let note_path = resolve::pick_note(&cmd.note, &vault.path)?;
+
+    let new_name = cmd.new_name.strip_suffix(".md").unwrap_or(&cmd.new_name);
+    if new_name.is_empty() || new_name.contains(['/', '\\']) {
+        bail!(
+            "Invalid note name `{}`, names can't contain `/`",
+            cmd.new_name
+        );
+    }
+
+    let new_path = note_path
+        .parent()
+        .context("note should be inside a folder")?
+        .join(format!("{new_name}.md"));
+
+    let relocation = Relocation::plan(&vault.path, vec![(note_path.clone(), new_path.clone())])?;
+
+    let verb = if cmd.dry_run {
+        "Would rename"
+    } else {
+        "Renamed"
+    };
+    let mut output = vec![format!(
+        "{verb} {} to {}",
+        scan::relative_note_path(&note_path, &vault.path),
+        scan::relative_note_path(&new_path, &vault.path)
+    )];
+    output.extend(relocation.summary(&vault.path, cmd.dry_run));
+
+    if !cmd.dry_run {
+        relocation.apply()?;
+        hooks::run(Event::AfterMove, &vault, &new_path, Some(&note_path))?;
+    }
+
+    Ok(Some(output.join("\n")))
+}
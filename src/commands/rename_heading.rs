@@ -0,0 +1,140 @@
+use crate::{
+    link_resolver::{find_heading, same_heading, LinkResolver},
+    matching::MatchSettings,
+    resolve, scan,
+    util::{get_current_vault, CommandResult},
+};
+use anyhow::{anyhow, bail, Context};
+use clap::Args;
+use libobsidian::links::{parse_links, replace_ranges, Link};
+use std::fs;
+
+#[derive(Args, Debug, Clone)]
+pub struct RenameHeadingCommand {
+    #[arg(help = "The name or path of the note with the heading")]
+    note: String,
+
+    #[arg(help = "The heading to rename")]
+    heading: String,
+
+    #[arg(help = "Its new text")]
+    new_heading: String,
+
+    /// Point the links into the heading, like `[[note#Heading]]`, at its new
+    /// text
+    #[arg(long, short = 'u')]
+    update_links: bool,
+
+    /// Show what would change without touching any files
+    #[arg(long)]
+    dry_run: bool,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+pub fn entry(cmd: &RenameHeadingCommand) -> CommandResult {
+    let vault = get_current_vault(cmd.vault.clone())?;
+    let note_path = resolve::pick_note(&cmd.note, &vault.path)?;
+    let relative = scan::relative_note_path(&note_path, &vault.path);
+    let new_heading = cmd.new_heading.trim();
+    if new_heading.is_empty() || new_heading.contains('\n') {
+        bail!("Invalid heading `{}`", cmd.new_heading);
+    }
+
+    let contents = fs::read_to_string(&note_path)
+        .with_context(|| format!("Could not read note {}", note_path.display()))?;
+    let matching = MatchSettings::read();
+    let heading = find_heading(&contents, &cmd.heading, &matching)
+        .ok_or_else(|| anyhow!("No heading `{}` in {relative}", cmd.heading))?;
+
+    let renamed: String = contents
+        .split_inclusive('\n')
+        .enumerate()
+        .map(|(idx, line)| {
+            if idx + 1 != heading.line {
+                return line.to_string();
+            }
+            let indent = &line[..line.len() - line.trim_start().len()];
+            let ending = &line[line.trim_end_matches(['\r', '\n']).len()..];
+            format!(
+                "{indent}{} {new_heading}{ending}",
+                "#".repeat(heading.level)
+            )
+        })
+        .collect();
+
+    let (done, planned) = if cmd.dry_run {
+        ("Would rename", "Would update")
+    } else {
+        ("Renamed", "Updated")
+    };
+    let mut output = vec![format!(
+        "{done} heading `{}` to `{new_heading}` in {relative}",
+        heading.text
+    )];
+
+    let mut writes = vec![(note_path.clone(), renamed)];
+    if cmd.update_links {
+        let resolver = LinkResolver::new(&vault.path)?;
+        for source in scan::note_paths(&vault.path)? {
+            let contents = match writes.iter().position(|(path, _)| *path == source) {
+                Some(idx) => writes.remove(idx).1,
+                None => fs::read_to_string(&source)
+                    .with_context(|| format!("Could not read note {}", source.display()))?,
+            };
+            let replacements: Vec<_> = parse_links(&contents)
+                .into_iter()
+                .filter(|link| {
+                    link.heading()
+                        .is_some_and(|linked| same_heading(linked, &heading.text, &matching))
+                        && resolver.resolve(link, &source).as_deref() == Some(note_path.as_path())
+                })
+                .map(|link| (link.range.clone(), retitle(&link, new_heading)))
+                .collect();
+
+            if !replacements.is_empty() {
+                let noun = if replacements.len() == 1 {
+                    "link"
+                } else {
+                    "links"
+                };
+                output.push(format!(
+                    "{planned} {} {noun} in {}",
+                    replacements.len(),
+                    scan::relative_note_path(&source, &vault.path)
+                ));
+                writes.push((source, replace_ranges(&contents, replacements)));
+            } else if source == note_path {
+                writes.push((source, contents));
+            }
+        }
+    }
+
+    if !cmd.dry_run {
+        for (path, contents) in writes {
+            fs::write(&path, contents)
+                .with_context(|| format!("Could not write to note {}", path.display()))?;
+        }
+    }
+
+    Ok(Some(output.join("\n")))
+}
+
+/// The link pointed at `heading` in place of the one it points into, keeping
+/// any headings above it in the subpath
+fn retitle(link: &Link, heading: &str) -> String {
+    let subpath = match link
+        .subpath
+        .as_deref()
+        .and_then(|subpath| subpath.rsplit_once('#'))
+    {
+        Some((parents, _)) => format!("{parents}#{heading}"),
+        None => heading.to_string(),
+    };
+    Link {
+        subpath: Some(subpath),
+        ..link.clone()
+    }
+    .with_target(&link.target)
+}
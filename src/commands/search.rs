@@ -0,0 +1,213 @@
+use crate::{
+    cli_config,
+    matching::{CaseArgs, MatchSettings},
+    note_text::NoteText,
+    paging::Paging,
+    scan,
+    search_query::{MatchOffsets, SearchQuery, SearchResult, Snippet},
+    style::Styler,
+    util::{get_current_vault, CommandResult},
+};
+use chrono::{DateTime, Local};
+use clap::Args;
+use serde::Serialize;
+use std::fs;
+
+#[derive(Args, Debug, Clone)]
+pub struct SearchCommand {
+    /// The words to search for, every one must appear in a note. Filters like
+    /// `tag:#project`, `path:Work/`, `before:2024-06-01`, `after:3d`,
+    /// `"exact phrase"` and `-excluded` narrow it down, and `AND`, `OR`, `NOT`
+    /// and parentheses combine terms
+    #[arg(required = true, num_args = 1..)]
+    query: Vec<String>,
+
+    /// Match the query fuzzily against note names and paths rather than
+    /// searching their contents, ranked by the config's `fuzzy` scoring
+    #[arg(long)]
+    fuzzy: bool,
+
+    /// Only notes created since a duration ago (`3d`, `12h`) or a date
+    /// (`2024-01-31`), going by their `created` or `date` property before the
+    /// file's creation time
+    #[arg(long, value_name = "TIME")]
+    created_after: Option<String>,
+
+    /// Only notes created before a duration ago or a date
+    #[arg(long, value_name = "TIME")]
+    created_before: Option<String>,
+
+    /// Only notes modified since a duration ago or a date
+    #[arg(long, value_name = "TIME")]
+    modified_after: Option<String>,
+
+    /// Only notes last modified before a duration ago or a date
+    #[arg(long, value_name = "TIME")]
+    modified_before: Option<String>,
+
+    /// How to order the notes, best matches first by default
+    #[arg(long, short = 's', default_value = "score")]
+    sort: SearchSort,
+
+    #[command(flatten)]
+    case: CaseArgs,
+
+    #[command(flatten)]
+    paging: Paging,
+
+    #[arg(long, short = 'f', default_value = "paths")]
+    format: SearchFormat,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+enum SearchSort {
+    /// Best matches first
+    Score,
+    /// Most recently modified first
+    Modified,
+    /// Alphabetically by vault-relative path
+    Path,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+enum SearchFormat {
+    Paths,
+    /// An array of notes, with the first line the query matched
+    Json,
+    /// One JSON note per line, for tools reading results as they go
+    Jsonl,
+}
+
+#[derive(Serialize, Debug)]
+struct JsonResult<'a> {
+    path: &'a str,
+    score: f64,
+    modified: Option<String>,
+    /// The line the snippet is from, counting from 1
+    line: Option<usize>,
+    snippet: Option<String>,
+    /// Where the query's words and phrases are in the snippet
+    matches: Vec<MatchOffsets>,
+}
+
+pub fn entry(cmd: &SearchCommand) -> CommandResult {
+    let vault = get_current_vault(cmd.vault.clone())?;
+    let query = cmd.query.join(" ");
+    let dates = SearchQuery::parse(&date_terms(cmd).join(" "))?;
+    let matching = MatchSettings::read();
+    let case = cmd.case.case(&matching);
+
+    let mut results: Vec<SearchResult> = if cmd.fuzzy {
+        let scoring = cli_config::read()?.fuzzy;
+        scoring
+            .rank_with_case(
+                &query,
+                &matching,
+                case,
+                &scan::note_paths(&vault.path)?,
+                &vault.path,
+            )
+            .into_iter()
+            .filter_map(|found| {
+                dates
+                    .matches_path(&found.path, &vault.path)
+                    .map(|matches| {
+                        matches.then(|| SearchResult {
+                            modified: fs::metadata(&found.path)
+                                .and_then(|meta| meta.modified())
+                                .ok()
+                                .map(DateTime::<Local>::from),
+                            path: found.relative,
+                            score: found.score,
+                        })
+                    })
+                    .transpose()
+            })
+            .collect::<anyhow::Result<_>>()?
+    } else {
+        SearchQuery::parse(&query)?
+            .with_case(case)
+            .and(dates)
+            .search(&vault.path)?
+    };
+
+    match cmd.sort {
+        SearchSort::Score => {}
+        SearchSort::Modified => results.sort_by(|a, b| {
+            b.modified
+                .cmp(&a.modified)
+                .then_with(|| a.path.cmp(&b.path))
+        }),
+        SearchSort::Path => results.sort_by(|a, b| a.path.cmp(&b.path)),
+    }
+    let results = cmd.paging.apply(results);
+
+    if results.is_empty() {
+        return Ok(None);
+    }
+
+    if let SearchFormat::Paths = cmd.format {
+        let styler = Styler::current();
+        return Ok(Some(
+            results
+                .iter()
+                .map(|result| styler.path(&result.path))
+                .collect::<Vec<String>>()
+                .join("\n"),
+        ));
+    }
+
+    // Fuzzy queries match names, so there's nothing in the notes to show
+    let snippet_query = if cmd.fuzzy {
+        None
+    } else {
+        Some(SearchQuery::parse(&query)?.with_case(case))
+    };
+    let records: Vec<JsonResult> = results
+        .iter()
+        .map(|result| {
+            let snippet: Option<Snippet> = snippet_query.as_ref().and_then(|query| {
+                let contents = NoteText::read(&vault.path.join(&result.path)).ok()?;
+                query.snippet(&contents)
+            });
+            JsonResult {
+                path: &result.path,
+                score: result.score,
+                modified: result.modified.map(|modified| modified.to_rfc3339()),
+                line: snippet.as_ref().map(|snippet| snippet.line),
+                matches: snippet
+                    .as_ref()
+                    .map(|snippet| snippet.matches.clone())
+                    .unwrap_or_default(),
+                snippet: snippet.map(|snippet| snippet.text),
+            }
+        })
+        .collect();
+
+    let formatted = match cmd.format {
+        SearchFormat::Jsonl => records
+            .iter()
+            .map(serde_json::to_string)
+            .collect::<Result<Vec<String>, _>>()?
+            .join("\n"),
+        _ => serde_json::to_string(&records)?,
+    };
+
+    Ok(Some(formatted))
+}
+
+/// The date flags as query terms, like `created:>2024-01-01`
+fn date_terms(cmd: &SearchCommand) -> Vec<String> {
+    [
+        ("created:>", &cmd.created_after),
+        ("created:<", &cmd.created_before),
+        ("modified:>", &cmd.modified_after),
+        ("modified:<", &cmd.modified_before),
+    ]
+    .into_iter()
+    .filter_map(|(prefix, time)| Some(format!("{prefix}{}", time.as_deref()?)))
+    .collect()
+}
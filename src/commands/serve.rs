@@ -0,0 +1,131 @@
+use crate::{
+    api, note_text,
+    util::{get_current_vault, CommandResult},
+};
+use anyhow::{anyhow, bail};
+use clap::Args;
+use std::env;
+use tiny_http::{Header, Response, Server};
+
+/// Where the token can come from instead of `--token`, which other users on
+/// the machine could see in the process list
+const TOKEN_VAR: &str = "OBX_API_TOKEN";
+
+#[derive(Args, Debug, Clone)]
+pub struct ServeCommand {
+    /// Serve the JSON API for listing, reading, writing, searching and querying notes
+    #[arg(long, required = true)]
+    api: bool,
+
+    /// The address to listen on, only this machine by default
+    #[arg(long, default_value = "127.0.0.1")]
+    host: String,
+
+    /// The port to listen on, any free one when 0
+    #[arg(long, short = 'p', default_value_t = 4040)]
+    port: u16,
+
+    /// Only answer requests with an `Authorization: Bearer <token>` header, needed when
+    /// listening beyond this machine. Also read from OBX_API_TOKEN
+    #[arg(long)]
+    token: Option<String>,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+pub fn entry(cmd: &ServeCommand) -> CommandResult {
+    let vault = get_current_vault(cmd.vault.clone())?;
+    note_text::avoid_mapping();
+    let server = Server::http((cmd.host.as_str(), cmd.port))
+        .map_err(|err| anyhow!("Could not listen on {}:{}: {err}", cmd.host, cmd.port))?;
+    let address = server
+        .server_addr()
+        .to_ip()
+        .ok_or_else(|| anyhow!("Could not listen on {}:{}", cmd.host, cmd.port))?;
+    let token = cmd
+        .token
+        .clone()
+        .or_else(|| env::var(TOKEN_VAR).ok())
+        .filter(|token| !token.is_empty());
+    if token.is_none() && !address.ip().is_loopback() {
+        bail!("Serving beyond this machine needs a token, set one with `--token` or {TOKEN_VAR}");
+    }
+    let authorization = token.map(|token| format!("Bearer {token}"));
+    // A web page could reach the API through a domain it points at this
+    // machine, so requests have to name this machine as their host, and ones
+    // from browsers, which carry an `Origin`, are refused
+    let local_hosts = address.ip().is_loopback().then(|| {
+        let port = address.port();
+        [
+            format!("127.0.0.1:{port}"),
+            format!("localhost:{port}"),
+            format!("[::1]:{port}"),
+        ]
+    });
+
+    eprintln!(
+        "Serving the API for `{}` on http://{address}, press Ctrl-C to stop",
+        vault.name
+    );
+
+    let content_type = Header::from_bytes("Content-Type", "application/json")
+        .expect("the content type header should be valid");
+    for mut request in server.incoming_requests() {
+        let header = |name: &'static str| {
+            request
+                .headers()
+                .iter()
+                .find(|header| header.field.equiv(name))
+                .map(|header| header.value.as_str())
+        };
+        let authorized = authorization.as_ref().is_none_or(|expected| {
+            header("Authorization")
+                .is_some_and(|given| constant_time_eq(given.as_bytes(), expected.as_bytes()))
+        });
+        let local = local_hosts.as_ref().is_none_or(|hosts| {
+            header("Host").is_some_and(|host| hosts.iter().any(|local| local == host))
+        }) && header("Origin").is_none();
+
+        let mut body = String::new();
+        let response = if !local {
+            api::Response {
+                status: 403,
+                body: serde_json::json!({ "error": "Only requests from this machine are answered" }),
+            }
+        } else if !authorized {
+            api::Response {
+                status: 401,
+                body: serde_json::json!({ "error": "Missing or wrong token" }),
+            }
+        } else {
+            match request.as_reader().read_to_string(&mut body) {
+                Ok(_) => api::handle(&vault, request.method().as_str(), request.url(), &body),
+                Err(_) => api::Response {
+                    status: 400,
+                    body: serde_json::json!({ "error": "The body isn't UTF-8" }),
+                },
+            }
+        };
+
+        let reply = Response::from_string(response.body.to_string())
+            .with_status_code(response.status)
+            .with_header(content_type.clone());
+        if let Err(err) = request.respond(reply) {
+            eprintln!("Could not respond: {err}");
+        }
+    }
+
+    Ok(None)
+}
+
+/// Compare `given` to `expected` taking the same time wherever they differ,
+/// so the token can't be guessed a byte at a time
+fn constant_time_eq(given: &[u8], expected: &[u8]) -> bool {
+    given.len() == expected.len()
+        && given
+            .iter()
+            .zip(expected)
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
@@ -0,0 +1,139 @@
+use crate::{
+    matching::MatchSettings,
+    scan,
+    util::{get_current_vault, CommandResult},
+};
+use anyhow::{bail, Context};
+use clap::{Args, Subcommand};
+use libobsidian::tags::{is_tag_char, rename_tags};
+use std::fs;
+
+#[derive(Args, Debug, Clone)]
+#[command(args_conflicts_with_subcommands = true)]
+#[command(arg_required_else_help = true)]
+pub struct TagCommand {
+    #[command(subcommand)]
+    command: Option<Subcommands>,
+}
+
+#[derive(Debug, Subcommand, Clone)]
+enum Subcommands {
+    /// Rename a tag, and the tags nested beneath it, in every note
+    Rename(RenameArgs),
+    /// Replace several tags, and the tags nested beneath them, with one
+    Merge(MergeArgs),
+}
+
+#[derive(Args, Debug, Clone)]
+struct RenameArgs {
+    #[arg(help = "The tag to rename")]
+    old: String,
+
+    #[arg(help = "Its new name")]
+    new: String,
+
+    /// Show how many tags each note would have renamed without changing it
+    #[arg(long)]
+    dry_run: bool,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+#[derive(Args, Debug, Clone)]
+struct MergeArgs {
+    #[arg(help = "The tags to merge", required = true)]
+    tags: Vec<String>,
+
+    /// The tag they become
+    #[arg(long, value_name = "TAG")]
+    into: String,
+
+    /// Show how many tags each note would have merged without changing it
+    #[arg(long)]
+    dry_run: bool,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+pub fn entry(cmd: &TagCommand) -> CommandResult {
+    match &cmd.command {
+        Some(Subcommands::Rename(args)) => retag(
+            std::slice::from_ref(&args.old),
+            &args.new,
+            args.dry_run,
+            args.vault.clone(),
+            ("Renamed", "Would rename"),
+        ),
+        Some(Subcommands::Merge(args)) => retag(
+            &args.tags,
+            &args.into,
+            args.dry_run,
+            args.vault.clone(),
+            ("Merged", "Would merge"),
+        ),
+        None => todo!(),
+    }
+}
+
+/// Rename each of `from`, and the tags nested beneath them, to `to` in every
+/// note in the vault, listing the notes changed
+fn retag(
+    from: &[String],
+    to: &str,
+    dry_run: bool,
+    vault: Option<String>,
+    (done, planned): (&str, &str),
+) -> CommandResult {
+    let vault = get_current_vault(vault)?;
+    let from: Vec<&str> = from.iter().map(|tag| tag.trim_start_matches('#')).collect();
+    let to = to.trim_start_matches('#');
+    for tag in from.iter().chain([&to]) {
+        if !is_tag(tag) {
+            bail!("`{tag}` isn't a valid tag");
+        }
+    }
+
+    let matching = MatchSettings::read();
+    let rename = |tag: &str| {
+        let matched = from
+            .iter()
+            .find_map(|old| matching.tag_match_len(tag, old))?;
+        Some(format!("{to}{}", &tag[matched..]))
+    };
+
+    let verb = if dry_run { planned } else { done };
+    let mut output = Vec::new();
+    for note_path in scan::note_paths(&vault.path)? {
+        let contents = fs::read_to_string(&note_path)
+            .with_context(|| format!("Could not read note {}", note_path.display()))?;
+        let (updated, count) = rename_tags(&contents, rename);
+        if count == 0 {
+            continue;
+        }
+
+        let noun = if count == 1 { "tag" } else { "tags" };
+        output.push(format!(
+            "{verb} {count} {noun} in {}",
+            scan::relative_note_path(&note_path, &vault.path)
+        ));
+        if !dry_run {
+            fs::write(&note_path, updated)
+                .with_context(|| format!("Could not write to note {}", note_path.display()))?;
+        }
+    }
+
+    if output.is_empty() {
+        let tags: Vec<String> = from.iter().map(|tag| format!("#{tag}")).collect();
+        output.push(format!("No notes carry {}", tags.join(" or ")));
+    }
+    Ok(Some(output.join("\n")))
+}
+
+/// Whether `tag` can be written as an inline `#tag`
+fn is_tag(tag: &str) -> bool {
+    !tag.is_empty()
+        && tag.chars().all(is_tag_char)
+        && !tag.chars().all(|c| c.is_ascii_digit() || c == '/')
+}
@@ -0,0 +1,158 @@
+use crate::{
+    cache::MetadataCache,
+    matching::MatchSettings,
+    paging::Paging,
+    style::Styler,
+    util::{get_current_vault, CommandResult},
+};
+use clap::Args;
+use tabled::{builder::Builder, settings::Style};
+
+#[derive(Args, Debug, Clone)]
+pub struct TagsCommand {
+    /// How to order the tags
+    #[arg(long, short = 's', default_value = "count")]
+    sort: TagSort,
+
+    /// List the notes carrying this tag, or one nested beneath it, instead of
+    /// counting tags
+    #[arg(long, value_name = "TAG")]
+    notes: Option<String>,
+
+    /// Show nested tags like `#project/alpha` beneath their parents, each
+    /// counting the notes carrying it or a tag beneath it
+    #[arg(long, short = 't', conflicts_with = "notes")]
+    tree: bool,
+
+    #[command(flatten)]
+    paging: Paging,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+enum TagSort {
+    /// Most used first, ties broken by name
+    Count,
+    /// Alphabetically
+    Name,
+}
+
+pub fn entry(cmd: &TagsCommand) -> CommandResult {
+    let vault = get_current_vault(cmd.vault.clone())?;
+    let wanted = cmd.notes.as_deref().map(|tag| tag.trim_start_matches('#'));
+
+    let cache = MetadataCache::current(&vault.path)?;
+    let styler = Styler::current();
+
+    if let Some(wanted) = wanted {
+        let matching = MatchSettings::read();
+        let tagged_notes: Vec<&str> = cache
+            .notes()
+            .into_iter()
+            .filter(|(_, note)| note.tags.iter().any(|tag| matching.has_tag(tag, wanted)))
+            .map(|(relative, _)| relative)
+            .collect();
+        let tagged_notes = cmd.paging.apply(tagged_notes);
+        if tagged_notes.is_empty() {
+            return Ok(None);
+        }
+        return Ok(Some(
+            tagged_notes
+                .into_iter()
+                .map(|relative| styler.path(relative))
+                .collect::<Vec<String>>()
+                .join("\n"),
+        ));
+    }
+
+    let mut counts = if cmd.tree {
+        cache.nested_tag_counts()
+    } else {
+        cache.tag_counts()
+    };
+    if let TagSort::Name = cmd.sort {
+        counts.sort_by(|a, b| a.0.cmp(&b.0));
+    }
+
+    let rows: Vec<(String, usize)> = if cmd.tree {
+        tree(&counts, None, 0)
+            .into_iter()
+            .map(|(depth, tag, count)| {
+                let name = match (depth, tag.rsplit_once('/')) {
+                    (0, _) | (_, None) => styler.tag(&format!("#{tag}")),
+                    (_, Some((_, name))) => styler.tag(name),
+                };
+                (format!("{}{name}", "  ".repeat(depth)), count)
+            })
+            .collect()
+    } else {
+        counts
+            .into_iter()
+            .map(|(tag, count)| (styler.tag(&format!("#{tag}")), count))
+            .collect()
+    };
+
+    let rows = cmd.paging.apply(rows);
+    if rows.is_empty() {
+        return Ok(None);
+    }
+
+    let mut builder = Builder::new();
+    for (tag, count) in rows {
+        builder.push_record([tag, count.to_string()]);
+    }
+    builder.insert_record(0, vec!["Tag", "Notes"]);
+
+    let mut table = builder.build();
+    table.with(Style::sharp());
+
+    Ok(Some(format!("{table}")))
+}
+
+/// The tags nested directly beneath `parent`, or the top-level tags, each
+/// followed by the tags beneath it, in the order of `counts`. Returns each
+/// tag with how deeply it's nested and its count.
+fn tree<'a>(
+    counts: &'a [(String, usize)],
+    parent: Option<&str>,
+    depth: usize,
+) -> Vec<(usize, &'a str, usize)> {
+    let mut rows = Vec::new();
+    for (tag, count) in counts {
+        if tag.rsplit_once('/').map(|(parent, _)| parent) != parent {
+            continue;
+        }
+        rows.push((depth, tag.as_str(), *count));
+        rows.extend(tree(counts, Some(tag), depth + 1));
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nests_tags_beneath_their_parents() {
+        let counts = [
+            ("project".to_string(), 3),
+            ("work".to_string(), 2),
+            ("project/beta".to_string(), 2),
+            ("project/alpha".to_string(), 1),
+            ("project/alpha/docs".to_string(), 1),
+        ];
+
+        assert_eq!(
+            tree(&counts, None, 0),
+            [
+                (0, "project", 3),
+                (1, "project/beta", 2),
+                (1, "project/alpha", 1),
+                (2, "project/alpha/docs", 1),
+                (0, "work", 2),
+            ]
+        );
+    }
+}
@@ -0,0 +1,43 @@
+use crate::{
+    template,
+    util::{get_current_vault, CommandResult},
+};
+use clap::{Args, Subcommand};
+
+#[derive(Args, Debug, Clone)]
+#[command(args_conflicts_with_subcommands = true)]
+#[command(arg_required_else_help = true)]
+pub struct TemplateCommand {
+    #[command(subcommand)]
+    command: Option<Subcommands>,
+}
+
+#[derive(Debug, Subcommand, Clone)]
+enum Subcommands {
+    /// List the templates in the vault's template folder
+    List(ListArgs),
+}
+
+#[derive(Args, Debug, Clone)]
+struct ListArgs {
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+pub fn entry(cmd: &TemplateCommand) -> CommandResult {
+    match &cmd.command {
+        Some(Subcommands::List(args)) => list(args),
+        None => todo!(),
+    }
+}
+
+fn list(args: &ListArgs) -> CommandResult {
+    let vault = get_current_vault(args.vault.clone())?;
+    let templates = template::list_templates(&vault.path)?;
+
+    if templates.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(templates.join("\n")))
+}
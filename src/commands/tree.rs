@@ -0,0 +1,84 @@
+use crate::{
+    cache::MetadataCache,
+    link_graph::LinkGraph,
+    relations::{self, Relations},
+    resolve, scan,
+    util::{get_current_vault, CommandResult},
+};
+use clap::Args;
+
+#[derive(Args, Debug, Clone)]
+pub struct TreeCommand {
+    #[arg(help = "The name or path of the note")]
+    note: String,
+
+    /// Show children only this many levels below the note
+    #[arg(long, short = 'd')]
+    depth: Option<usize>,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+pub fn entry(cmd: &TreeCommand) -> CommandResult {
+    let vault = get_current_vault(cmd.vault.clone())?;
+    let note = scan::relative_note_path(&resolve::pick_note(&cmd.note, &vault.path)?, &vault.path);
+    let cache = MetadataCache::current(&vault.path)?;
+    let resolver = LinkGraph::current(&vault.path)?.resolver(&vault.path);
+    let relations = Relations::new(&cache, &resolver, &vault.path, &relations::settings()?);
+
+    // The note's first parent, that one's first parent and so on above it,
+    // then everything below it
+    let ancestors = relations.ancestors(&note);
+    let mut lines: Vec<String> = ancestors
+        .iter()
+        .enumerate()
+        .map(|(level, ancestor)| format!("{}{ancestor}", "  ".repeat(level)))
+        .collect();
+    let mut branch = ancestors.clone();
+    descend(
+        &relations,
+        &note,
+        ancestors.len(),
+        cmd.depth,
+        &mut branch,
+        &mut lines,
+    );
+
+    let related = relations.related(&note);
+    if !related.is_empty() {
+        lines.push(String::new());
+        lines.push(format!("Related: {}", related.join(", ")));
+    }
+    Ok(Some(lines.join("\n")))
+}
+
+/// Add `note` and its children below it to `lines`, `depth` levels further
+/// at most, leaving out notes already on the `branch` leading to it
+fn descend<'a>(
+    relations: &'a Relations,
+    note: &'a str,
+    level: usize,
+    depth: Option<usize>,
+    branch: &mut Vec<&'a str>,
+    lines: &mut Vec<String>,
+) {
+    lines.push(format!("{}{note}", "  ".repeat(level)));
+    if depth == Some(0) {
+        return;
+    }
+    branch.push(note);
+    for child in relations.children(note) {
+        if !branch.contains(&child) {
+            descend(
+                relations,
+                child,
+                level + 1,
+                depth.map(|depth| depth - 1),
+                branch,
+                lines,
+            );
+        }
+    }
+    branch.pop();
+}
@@ -0,0 +1,91 @@
+use crate::{
+    link_resolver::find_heading,
+    matching::MatchSettings,
+    obsidian_uri::{self, Target, WriteMode},
+    resolve,
+    util::{get_current_vault, CommandResult},
+};
+use anyhow::{anyhow, Context};
+use clap::Args;
+use libobsidian::{blocks::find_block, split_frontmatter};
+use std::fs;
+
+#[derive(Args, Debug, Clone)]
+pub struct UriCommand {
+    #[arg(help = "The name or path of the note, matched exactly or fuzzily within the vault")]
+    note: String,
+
+    /// Go to this heading, with the Advanced URI plugin
+    #[arg(long, conflicts_with = "block")]
+    heading: Option<String>,
+
+    /// Go to the block marked with this `^id`, with the Advanced URI plugin
+    #[arg(long, value_name = "ID")]
+    block: Option<String>,
+
+    /// How to write `--data` to the note, or where to put the cursor without it, with the
+    /// Advanced URI plugin
+    #[arg(long, short = 'm')]
+    mode: Option<WriteMode>,
+
+    /// Text to write to the note, at the heading or block when given one
+    #[arg(long, short = 'd', requires = "mode")]
+    data: Option<String>,
+
+    /// Open the URI, showing the note in the Obsidian app, instead of printing it
+    #[arg(long, short = 'o')]
+    open: bool,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+pub fn entry(cmd: &UriCommand) -> CommandResult {
+    let vault = get_current_vault(cmd.vault.clone())?;
+    let note_path = resolve::pick_note(&cmd.note, &vault.path)?;
+
+    let mut target = Target {
+        mode: cmd.mode,
+        data: cmd.data.clone(),
+        ..Target::default()
+    };
+    if cmd.heading.is_some() || cmd.block.is_some() {
+        let contents = fs::read_to_string(&note_path)
+            .with_context(|| format!("Could not read note {}", note_path.display()))?;
+        let (_, body) = split_frontmatter(&contents);
+
+        // The plugin matches the heading or block as written in the note
+        if let Some(heading) = &cmd.heading {
+            let found = find_heading(body, heading, &MatchSettings::read())
+                .ok_or_else(|| anyhow!("No heading `{heading}` in {}", cmd.note))?;
+            target.heading = Some(found.text);
+        }
+        if let Some(id) = &cmd.block {
+            let id = id.trim_start_matches('^');
+            find_block(body, id).ok_or_else(|| anyhow!("No block ^{id} in {}", cmd.note))?;
+            target.block = Some(id.to_string());
+        }
+    }
+
+    let uri = if target.is_empty() {
+        obsidian_uri::open_uri(&vault.path, &note_path)
+    } else {
+        obsidian_uri::advanced_uri(&vault.path, &note_path, &target)
+    };
+
+    if cmd.open {
+        open::that(&uri).with_context(|| format!("Could not open obsidian url `{uri}`"))?;
+        return Ok(None);
+    }
+
+    Ok(Some(uri))
+}
+
+/// Open the note in the Obsidian app whether or not `--open` was given, for
+/// `obx notes open`
+pub fn open(cmd: &UriCommand) -> CommandResult {
+    entry(&UriCommand {
+        open: true,
+        ..cmd.clone()
+    })
+}
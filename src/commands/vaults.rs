@@ -1,11 +1,14 @@
 use crate::{
-    cli_config,
+    app_settings, cli_config,
     util::{get_current_vault, CommandResult},
 };
-use anyhow::{anyhow, Context};
+use anyhow::{anyhow, bail, Context};
 use clap::{Args, Subcommand};
 use dialoguer::{theme::ColorfulTheme, Select};
-use std::{fs, io, path::PathBuf};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
 use tabled::{builder::Builder, settings::Style};
 
 #[derive(Args, Debug, Clone)]
@@ -22,6 +25,17 @@ enum Subcommands {
     /// unless --name is explicitly provided
     Create(CreateArgs),
 
+    /// Register an existing folder as a vault, without switching to it
+    Add(AddArgs),
+
+    /// Forget a vault. Its files are left untouched
+    #[command(visible_alias = "rm")]
+    Remove(RemoveArgs),
+
+    /// Register the vaults the Obsidian app knows about, skipping ones
+    /// already registered
+    Discover,
+
     /// List all vaults
     List(ListArgs),
 
@@ -48,6 +62,21 @@ struct CreateArgs {
     name: Option<String>,
 }
 
+#[derive(Args, Debug, Clone)]
+struct AddArgs {
+    #[arg(help = "The name to refer to the vault by")]
+    name: String,
+
+    #[arg(help = "Path to the vault's folder")]
+    vault_path: PathBuf,
+}
+
+#[derive(Args, Debug, Clone)]
+struct RemoveArgs {
+    #[arg(help = "The name of the vault to remove")]
+    name: String,
+}
+
 #[derive(Args, Debug, Clone)]
 struct SwitchArgs {
     #[arg(help = "The name of the vault to switch to")]
@@ -58,6 +87,8 @@ struct SwitchArgs {
 enum ListFormats {
     Pretty,
     Json,
+    /// Just the names, one per line
+    Names,
 }
 
 #[derive(Args, Debug, Clone)]
@@ -72,6 +103,9 @@ pub fn entry(cmd: &VaultsCommand) -> anyhow::Result<Option<String>> {
             vault_path: vault,
             name,
         })) => create(vault, name.clone()),
+        Some(Subcommands::Add(AddArgs { name, vault_path })) => add(name, vault_path),
+        Some(Subcommands::Remove(RemoveArgs { name })) => remove(name),
+        Some(Subcommands::Discover) => discover(),
         Some(Subcommands::List(ListArgs { format })) => list(format),
         Some(Subcommands::Switch(SwitchArgs { vault })) => switch(vault),
         Some(Subcommands::Current) => current(),
@@ -80,22 +114,122 @@ pub fn entry(cmd: &VaultsCommand) -> anyhow::Result<Option<String>> {
     }
 }
 
-fn create(vault_path: &PathBuf, vault_name_override: Option<String>) -> CommandResult {
-    let vault_name = vault_name_override.unwrap_or_else(|| {
-        vault_path
-            .components()
-            .last()
-            .unwrap()
-            .as_os_str()
-            .to_str()
-            .unwrap()
-            .to_string()
+fn create(vault_path: &Path, vault_name_override: Option<String>) -> CommandResult {
+    let vault_name = vault_name_override.unwrap_or_else(|| cli_config::vault_name(vault_path));
+
+    let resolved_path = resolve_vault_dir(vault_path, "create")?;
+
+    let mut config = cli_config::read()?;
+    ensure_name_free(&config, &vault_name)?;
+
+    config.current_vault = vault_name.clone();
+    config.vaults.push(cli_config::Vault {
+        name: vault_name.clone(),
+        path: resolved_path,
+        ..Default::default()
+    });
+
+    let _ = cli_config::write(&config);
+
+    Ok(Some(format!("Created vault {vault_name}")))
+}
+
+fn add(vault_name: &str, vault_path: &Path) -> CommandResult {
+    let resolved_path = resolve_vault_dir(vault_path, "add")?;
+
+    let mut config = cli_config::read()?;
+    ensure_name_free(&config, vault_name)?;
+
+    config.vaults.push(cli_config::Vault {
+        name: vault_name.to_string(),
+        path: resolved_path,
+        ..Default::default()
     });
 
+    cli_config::write(&config)?;
+
+    Ok(Some(format!("Added vault {vault_name}")))
+}
+
+fn remove(vault_name: &str) -> CommandResult {
+    let mut config = cli_config::read()?;
+
+    let Some(idx) = config.vaults.iter().position(|v| v.name == vault_name) else {
+        bail!("Could not remove vault `{vault_name}`, vault doesn't exist");
+    };
+    if config.vaults.len() == 1 {
+        bail!("Could not remove vault `{vault_name}`, it's the only vault");
+    }
+
+    config.vaults.remove(idx);
+
+    let mut message = format!("Removed vault {vault_name}");
+    if config.current_vault == vault_name {
+        config.current_vault = config.vaults[0].name.clone();
+        message.push_str(&format!(", switched to vault {}", config.current_vault));
+    }
+
+    cli_config::write(&config)?;
+
+    Ok(Some(message))
+}
+
+fn discover() -> CommandResult {
+    let settings = app_settings::read()?;
+    let mut config = cli_config::read()?;
+    let mut added = Vec::new();
+
+    for app_vault in settings.vaults_by_recency() {
+        if !app_vault.path.is_dir() {
+            continue;
+        }
+
+        let already_registered = config
+            .vaults
+            .iter()
+            .any(|v| same_dir(&v.path, &app_vault.path));
+        if already_registered {
+            continue;
+        }
+
+        let base_name = cli_config::vault_name(&app_vault.path);
+        let name = std::iter::once(base_name.clone())
+            .chain((2..).map(|n| format!("{base_name}-{n}")))
+            .find(|candidate| !config.vaults.iter().any(|v| &v.name == candidate))
+            .expect("should find a free vault name");
+
+        added.push(format!("Added vault {name} ({})", app_vault.path.display()));
+        config.vaults.push(cli_config::Vault {
+            name,
+            path: app_vault.path.clone(),
+            ..Default::default()
+        });
+    }
+
+    if added.is_empty() {
+        return Ok(Some("No new vaults found".to_string()));
+    }
+
+    cli_config::write(&config)?;
+
+    Ok(Some(added.join("\n")))
+}
+
+/// Whether two paths point at the same folder, comparing them as given when
+/// either can't be canonicalized
+fn same_dir(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Canonicalize the folder a vault is being registered at, checking it exists
+fn resolve_vault_dir(vault_path: &Path, action: &str) -> anyhow::Result<PathBuf> {
     let resolved_path = fs::canonicalize(vault_path).map_err(|err| {
         if err.kind() == io::ErrorKind::NotFound {
             anyhow!(
-                "Could not create vault at path `{}`, directory not found",
+                "Could not {action} vault at path `{}`, directory not found",
                 vault_path.display()
             )
         } else {
@@ -104,23 +238,21 @@ fn create(vault_path: &PathBuf, vault_name_override: Option<String>) -> CommandR
     })?;
 
     if !resolved_path.is_dir() {
-        return Err(anyhow!(
-            "Could not create vault at path `{}`, path must be a directory",
+        bail!(
+            "Could not {action} vault at path `{}`, path must be a directory",
             vault_path.display()
-        ));
+        );
     }
 
-    let mut config = cli_config::read()?;
-
-    config.current_vault = vault_name.clone();
-    config.vaults.push(cli_config::Vault {
-        name: vault_name.clone(),
-        path: resolved_path,
-    });
+    Ok(resolved_path)
+}
 
-    let _ = cli_config::write(&config);
+fn ensure_name_free(config: &cli_config::Config, vault_name: &str) -> anyhow::Result<()> {
+    if config.vaults.iter().any(|v| v.name == vault_name) {
+        bail!("A vault named `{vault_name}` already exists");
+    }
 
-    Ok(Some(format!("Created vault {vault_name}")))
+    Ok(())
 }
 
 fn list(list_format: &ListFormats) -> CommandResult {
@@ -129,6 +261,12 @@ fn list(list_format: &ListFormats) -> CommandResult {
     let formatted = match list_format {
         &ListFormats::Json => serde_json::to_string(&config.vaults)?,
         ListFormats::Pretty => format_vault_table(&config),
+        ListFormats::Names => config
+            .vaults
+            .iter()
+            .map(|vault| vault.name.as_str())
+            .collect::<Vec<_>>()
+            .join("\n"),
     };
 
     Ok(Some(formatted))
@@ -0,0 +1,95 @@
+use crate::{
+    embeds,
+    link_resolver::LinkResolver,
+    resolve,
+    util::{get_current_vault, CommandResult},
+};
+use anyhow::Context;
+use clap::Args;
+use libobsidian::split_frontmatter;
+use std::fs;
+
+#[derive(Args, Debug, Clone)]
+pub struct WordcountCommand {
+    #[arg(help = "The name or path of the note")]
+    note: String,
+
+    /// Count the words of embedded notes, headings and blocks where they're
+    /// embedded, up to three levels deep
+    #[arg(long, short = 'e')]
+    with_embeds: bool,
+
+    /// Count the words in the frontmatter too
+    #[arg(long)]
+    include_frontmatter: bool,
+
+    /// Count the words in code blocks too
+    #[arg(long)]
+    include_code: bool,
+
+    #[arg(long, short = 'v')]
+    vault: Option<String>,
+}
+
+pub fn entry(cmd: &WordcountCommand) -> CommandResult {
+    let vault = get_current_vault(cmd.vault.clone())?;
+    let note_path = resolve::pick_note(&cmd.note, &vault.path)?;
+    let contents = fs::read_to_string(&note_path)
+        .with_context(|| format!("Could not read note {}", note_path.display()))?;
+
+    let (frontmatter, body) = split_frontmatter(&contents);
+    let frontmatter_words = match frontmatter {
+        Some(frontmatter) if cmd.include_frontmatter => count_words(frontmatter, true),
+        _ => 0,
+    };
+    let body = if cmd.with_embeds {
+        let resolver = LinkResolver::new(&vault.path)?;
+        embeds::expand(body, &note_path, &resolver)
+    } else {
+        body.to_string()
+    };
+    let count = frontmatter_words + count_words(&body, cmd.include_code);
+
+    let noun = if count == 1 { "word" } else { "words" };
+    Ok(Some(format!("{count} {noun}")))
+}
+
+/// The words in `text` split on whitespace, leaving out fenced code blocks
+/// unless `include_code`
+fn count_words(text: &str, include_code: bool) -> usize {
+    let mut in_code_block = false;
+    let mut count = 0;
+    for line in text.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+            if !include_code {
+                continue;
+            }
+        }
+        if in_code_block && !include_code {
+            continue;
+        }
+        count += line.split_whitespace().count();
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn leaves_out_code_blocks() {
+        let text = indoc! {"
+            Two words
+            ```rust
+            let code = 1;
+            ```
+            and three more
+        "};
+
+        assert_eq!(count_words(text, false), 5);
+        assert_eq!(count_words(text, true), 11);
+    }
+}
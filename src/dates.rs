@@ -0,0 +1,221 @@
+//! How dates and times are shown and written, using the Moment.js style
+//! formats Obsidian uses
+
+use chrono::{DateTime, Datelike, Local, Locale, Weekday};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct DateFormats {
+    /// Modification times in `obx list` and the TUI
+    pub timestamp: String,
+    /// The default for `{{date}}` in templates
+    pub date: String,
+    /// The default for `{{time}}` in templates
+    pub time: String,
+    /// The names of daily notes
+    pub daily_note: String,
+    /// The names of weekly notes, after the week's first day
+    pub weekly_note: String,
+    /// Where weeks start, for `w`/`ww` week numbers and the calendar
+    #[serde(
+        serialize_with = "serialize_weekday",
+        deserialize_with = "deserialize_weekday"
+    )]
+    pub first_day_of_week: Weekday,
+    /// Month and day names follow this locale, e.g. `fr_FR`, rather than
+    /// English
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+}
+
+impl Default for DateFormats {
+    fn default() -> Self {
+        Self {
+            timestamp: "YYYY-MM-DD HH:mm".to_string(),
+            date: "YYYY-MM-DD".to_string(),
+            time: "HH:mm".to_string(),
+            daily_note: "YYYY-MM-DD".to_string(),
+            weekly_note: "YYYY-[W]ww".to_string(),
+            first_day_of_week: Weekday::Mon,
+            locale: None,
+        }
+    }
+}
+
+impl DateFormats {
+    pub fn is_default(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// The configured locale, or an error naming the bad value
+    pub fn locale(&self) -> anyhow::Result<Locale> {
+        match &self.locale {
+            None => Ok(Locale::POSIX),
+            Some(name) => name.replace('-', "_").parse().map_err(|_| {
+                anyhow::anyhow!(
+                    "Unknown locale `{name}` in `dates.locale`, expected one like `fr_FR`"
+                )
+            }),
+        }
+    }
+
+    /// Format `time` with a Moment.js style `format`. An invalid locale
+    /// falls back to English, `obx config validate` reports it.
+    pub fn format(&self, time: &DateTime<Local>, format: &str) -> String {
+        let locale = self.locale().unwrap_or(Locale::POSIX);
+        let chrono_format = moment_to_chrono(format, self.week_number(time));
+        time.format_localized(&chrono_format, locale).to_string()
+    }
+
+    /// The week of the year, where weeks start on `first_day_of_week` and
+    /// the first one holds January 1st
+    fn week_number(&self, time: &DateTime<Local>) -> u32 {
+        let jan_first = time
+            .date_naive()
+            .with_ordinal(1)
+            .expect("every year has a first day");
+        let offset = jan_first.weekday().days_since(self.first_day_of_week);
+
+        (time.ordinal0() + offset) / 7 + 1
+    }
+}
+
+/// Translate Moment.js tokens to chrono's. Text in `[brackets]` is kept
+/// as-is.
+fn moment_to_chrono(format: &str, week: u32) -> String {
+    const TOKENS: [(&str, &str); 17] = [
+        ("YYYY", "%Y"),
+        ("YY", "%y"),
+        ("MMMM", "%B"),
+        ("MMM", "%b"),
+        ("MM", "%m"),
+        ("M", "%-m"),
+        ("DD", "%d"),
+        ("D", "%-d"),
+        ("dddd", "%A"),
+        ("ddd", "%a"),
+        ("HH", "%H"),
+        ("H", "%-H"),
+        ("hh", "%I"),
+        ("h", "%-I"),
+        ("mm", "%M"),
+        ("ss", "%S"),
+        ("A", "%p"),
+    ];
+
+    let mut chrono_format = String::new();
+    let mut rest = format;
+
+    'outer: while let Some(c) = rest.chars().next() {
+        if c == '[' {
+            if let Some(end) = rest.find(']') {
+                chrono_format.push_str(&rest[1..end].replace('%', "%%"));
+                rest = &rest[end + 1..];
+                continue;
+            }
+        }
+
+        if let Some(after) = rest.strip_prefix("ww") {
+            chrono_format.push_str(&format!("{week:02}"));
+            rest = after;
+            continue;
+        }
+        if let Some(after) = rest.strip_prefix('w') {
+            chrono_format.push_str(&week.to_string());
+            rest = after;
+            continue;
+        }
+
+        for (token, replacement) in TOKENS {
+            if let Some(after) = rest.strip_prefix(token) {
+                chrono_format.push_str(replacement);
+                rest = after;
+                continue 'outer;
+            }
+        }
+
+        if c == '%' {
+            chrono_format.push_str("%%");
+        } else {
+            chrono_format.push(c);
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+
+    chrono_format
+}
+
+fn serialize_weekday<S: Serializer>(day: &Weekday, serializer: S) -> Result<S::Ok, S::Error> {
+    let name = match day {
+        Weekday::Mon => "monday",
+        Weekday::Tue => "tuesday",
+        Weekday::Wed => "wednesday",
+        Weekday::Thu => "thursday",
+        Weekday::Fri => "friday",
+        Weekday::Sat => "saturday",
+        Weekday::Sun => "sunday",
+    };
+    serializer.serialize_str(name)
+}
+
+fn deserialize_weekday<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Weekday, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    name.parse().map_err(|_| {
+        serde::de::Error::custom(format!("unknown day `{name}`, expected one like `monday`"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use test_case::test_case;
+
+    fn thursday() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 3, 7, 14, 5, 9).unwrap()
+    }
+
+    #[test_case("YYYY-MM-DD HH:mm", "2024-03-07 14:05" ; "default timestamp")]
+    #[test_case("dddd D MMMM YYYY", "Thursday 7 March 2024" ; "names")]
+    #[test_case("hh:mm:ss A", "02:05:09 PM" ; "twelve hour")]
+    #[test_case("[Week] ww, gggg", "Week 10, gggg" ; "week and escaped text")]
+    fn formats_moment_tokens(format: &str, expected: &str) {
+        assert_eq!(DateFormats::default().format(&thursday(), format), expected);
+    }
+
+    #[test]
+    fn uses_locale_names() {
+        let formats = DateFormats {
+            locale: Some("fr_FR".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(formats.format(&thursday(), "dddd D MMMM"), "jeudi 7 mars");
+    }
+
+    #[test]
+    fn rejects_unknown_locales() {
+        let formats = DateFormats {
+            locale: Some("xx_YY".to_string()),
+            ..Default::default()
+        };
+
+        assert!(formats.locale().is_err());
+    }
+
+    #[test]
+    fn counts_weeks_from_first_day() {
+        // January 1st 2024 is a Monday, so a Sunday start puts it in a week
+        // that began in December
+        let sunday = Local.with_ymd_and_hms(2024, 1, 7, 9, 0, 0).unwrap();
+        let monday_start = DateFormats::default();
+        let sunday_start = DateFormats {
+            first_day_of_week: Weekday::Sun,
+            ..Default::default()
+        };
+
+        assert_eq!(monday_start.format(&sunday, "w"), "1");
+        assert_eq!(sunday_start.format(&sunday, "w"), "2");
+    }
+}
@@ -0,0 +1,94 @@
+use anyhow::{bail, Context};
+use std::{path::Path, process};
+
+/// Build the command for an editor setting such as `code --wait` or
+/// `nvim +{line} {file}`. It's split into arguments the way a shell would,
+/// then `{file}`, `{line}` and `{vault}` are filled in. The file is passed
+/// last when the setting doesn't place it with `{file}`.
+pub fn command(
+    editor: &str,
+    file: &Path,
+    line: Option<usize>,
+    vault: Option<&Path>,
+) -> anyhow::Result<process::Command> {
+    let words = shell_words::split(editor)
+        .with_context(|| format!("Could not parse editor `{editor}`, check its quotes"))?;
+    let Some((program, args)) = words.split_first() else {
+        bail!("The editor is empty, set `editor` or $EDITOR");
+    };
+
+    let file_arg = file.to_string_lossy();
+    let line_arg = line.unwrap_or(1).to_string();
+    let vault_arg = vault
+        .or_else(|| file.parent())
+        .map(|vault| vault.to_string_lossy())
+        .unwrap_or_default();
+
+    let mut command = process::Command::new(program);
+    for arg in args {
+        command.arg(
+            arg.replace("{file}", &file_arg)
+                .replace("{line}", &line_arg)
+                .replace("{vault}", &vault_arg),
+        );
+    }
+    if !words.iter().any(|word| word.contains("{file}")) {
+        command.arg(file);
+    }
+
+    Ok(command)
+}
+
+/// The program an editor setting runs, for messages
+pub fn program(editor: &str) -> String {
+    shell_words::split(editor)
+        .ok()
+        .and_then(|words| words.into_iter().next())
+        .unwrap_or_else(|| editor.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(editor: &str, line: Option<usize>) -> Vec<String> {
+        let command = command(
+            editor,
+            Path::new("/vault/notes/a b.md"),
+            line,
+            Some(Path::new("/vault")),
+        )
+        .unwrap();
+
+        std::iter::once(command.get_program())
+            .chain(command.get_args())
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn appends_file_without_placeholder() {
+        assert_eq!(
+            args("code --wait", None),
+            ["code", "--wait", "/vault/notes/a b.md"]
+        );
+    }
+
+    #[test]
+    fn fills_in_placeholders() {
+        assert_eq!(
+            args("nvim +{line} {file}", Some(12)),
+            ["nvim", "+12", "/vault/notes/a b.md"]
+        );
+        assert_eq!(
+            args("'my editor' --root={vault} {file}:{line}", None),
+            ["my editor", "--root=/vault", "/vault/notes/a b.md:1"]
+        );
+    }
+
+    #[test]
+    fn rejects_empty_and_unbalanced_editors() {
+        assert!(command(" ", Path::new("a.md"), None, None).is_err());
+        assert!(command("code 'x", Path::new("a.md"), None, None).is_err());
+    }
+}
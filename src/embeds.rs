@@ -0,0 +1,214 @@
+//! Embedded notes, `![[note]]`, `![[note#heading]]` and `![[note#^block]]`,
+//! replaced by the text they show, so a note reads the way it does in
+//! Obsidian. Used by `cat --expand-embeds` and the HTML and PDF exports.
+
+use crate::{
+    link_resolver::{find_heading, LinkResolver},
+    matching::MatchSettings,
+    scan,
+};
+use libobsidian::{
+    blocks::find_block,
+    headings::parse_headings,
+    links::{parse_links, replace_ranges, LinkKind},
+    split_frontmatter,
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// How many levels of embeds within embeds are expanded
+pub const MAX_DEPTH: usize = 3;
+
+/// `body`, from the note at `note_path`, with its embeds of notes expanded.
+/// Embeds of other files, of missing headings or blocks, of anything more
+/// than [`MAX_DEPTH`] levels down, or of something already being expanded
+/// are left as they are.
+pub fn expand(body: &str, note_path: &Path, resolver: &LinkResolver) -> String {
+    let mut expanding = vec![(note_path.to_path_buf(), None)];
+    expand_within(body, note_path, resolver, &mut expanding)
+}
+
+/// `expanding` holds each note and subpath being expanded, outermost first
+fn expand_within(
+    body: &str,
+    note_path: &Path,
+    resolver: &LinkResolver,
+    expanding: &mut Vec<(PathBuf, Option<String>)>,
+) -> String {
+    let mut replacements = Vec::new();
+
+    for link in parse_links(body) {
+        if !link.embed || link.is_external() || expanding.len() > MAX_DEPTH {
+            continue;
+        }
+        let Some(target) = resolver.resolve(&link, note_path) else {
+            continue;
+        };
+        let key = (target.clone(), link.subpath.clone());
+        if !scan::is_markdown(&target) || expanding.contains(&key) {
+            continue;
+        }
+        let Some(text) = fs::read_to_string(&target).ok().and_then(|contents| {
+            embedded_text(&contents, link.subpath.as_deref(), resolver.matching())
+        }) else {
+            continue;
+        };
+
+        expanding.push(key);
+        let expanded = expand_within(&text, &target, resolver, expanding);
+        expanding.pop();
+        let expanded = relocate_links(&expanded, &target, note_path, resolver);
+
+        // Embeds within a paragraph get blank lines around them, so what they
+        // show stays a block of its own
+        let line_start = body[..link.range.start]
+            .rfind('\n')
+            .map_or(0, |idx| idx + 1);
+        let line_end = body[link.range.end..]
+            .find('\n')
+            .map_or(body.len(), |idx| link.range.end + idx);
+        let alone = body[line_start..link.range.start].trim().is_empty()
+            && body[link.range.end..line_end].trim().is_empty();
+        let replacement = if alone {
+            expanded.trim().to_string()
+        } else {
+            format!("\n\n{}\n\n", expanded.trim())
+        };
+        replacements.push((link.range.clone(), replacement));
+    }
+
+    replace_ranges(body, replacements)
+}
+
+/// The part of a note's `contents` an embed with `subpath` shows: the body,
+/// a heading's section or a block
+fn embedded_text(
+    contents: &str,
+    subpath: Option<&str>,
+    matching: &MatchSettings,
+) -> Option<String> {
+    let body = split_frontmatter(contents).1;
+    match subpath {
+        None => Some(body.to_string()),
+        Some(subpath) => match subpath.strip_prefix('^') {
+            Some(id) => find_block(body, id).map(|block| block.text),
+            None => section(body, subpath, matching),
+        },
+    }
+}
+
+/// The heading and everything below it up to the next heading at its level or
+/// above. Nested subpaths like `Heading#Subheading` match the last heading.
+fn section(body: &str, heading: &str, matching: &MatchSettings) -> Option<String> {
+    let heading = heading.rsplit('#').next().unwrap_or(heading).trim();
+    let found = find_heading(body, heading, matching)?;
+    let headings = parse_headings(body);
+    let idx = headings
+        .iter()
+        .position(|candidate| candidate.line == found.line)?;
+    let start = headings[idx].line - 1;
+    let end = headings[idx + 1..]
+        .iter()
+        .find(|next| next.level <= headings[idx].level)
+        .map_or(usize::MAX, |next| next.line - 1);
+
+    Some(
+        body.lines()
+            .skip(start)
+            .take(end - start)
+            .collect::<Vec<_>>()
+            .join("\n"),
+    )
+}
+
+/// Markdown links in `text`, which are relative to the embedded note at
+/// `from`, rewritten to be relative to `to`, where the text is going
+fn relocate_links(text: &str, from: &Path, to: &Path, resolver: &LinkResolver) -> String {
+    if from.parent() == to.parent() {
+        return text.to_string();
+    }
+    let replacements = parse_links(text)
+        .into_iter()
+        .filter(|link| link.kind == LinkKind::Markdown && !link.is_external())
+        .filter(|link| !link.target.is_empty())
+        .filter_map(|link| {
+            let target = resolver.resolve(&link, from)?;
+            Some((link.range.clone(), resolver.retarget(&link, &target, to)))
+        })
+        .collect();
+    replace_ranges(text, replacements)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::{prelude::*, TempDir};
+    use indoc::indoc;
+
+    #[test]
+    fn expands_notes_headings_and_blocks() {
+        let dir = TempDir::new().unwrap();
+        dir.child("source.md")
+            .write_str("![[whole]]\n\nSee ![[parts#Second]] and ![[parts#^quote]]")
+            .unwrap();
+        dir.child("whole.md")
+            .write_str("---\ntitle: Whole\n---\nThe whole note\n")
+            .unwrap();
+        dir.child("parts.md")
+            .write_str(indoc! {"
+                # First
+                ## Second
+                In the second
+                ### Nested
+                Still the second
+                ## Third
+                > A quote ^quote
+            "})
+            .unwrap();
+        let resolver = LinkResolver::from_files(
+            dir.path(),
+            vec!["source.md".into(), "whole.md".into(), "parts.md".into()],
+        );
+        let source = dir.child("source.md").to_path_buf();
+
+        assert_eq!(
+            expand(&fs::read_to_string(&source).unwrap(), &source, &resolver),
+            "The whole note\n\nSee \n\n## Second\nIn the second\n### Nested\nStill the second\n\n and \n\n> A quote\n\n"
+        );
+    }
+
+    #[test]
+    fn stops_at_cycles() {
+        let dir = TempDir::new().unwrap();
+        dir.child("a.md").write_str("A\n![[b]]").unwrap();
+        dir.child("b.md").write_str("B\n![[a]]").unwrap();
+        let resolver = LinkResolver::from_files(dir.path(), vec!["a.md".into(), "b.md".into()]);
+        let a = dir.child("a.md").to_path_buf();
+
+        assert_eq!(expand("A\n![[b]]", &a, &resolver), "A\nB\n![[a]]");
+    }
+
+    #[test]
+    fn keeps_links_in_embedded_notes_working() {
+        let dir = TempDir::new().unwrap();
+        dir.child("folder/embedded.md")
+            .write_str("[Image](image.png)")
+            .unwrap();
+        dir.child("folder/image.png").write_str("").unwrap();
+        let resolver = LinkResolver::from_files(
+            dir.path(),
+            vec![
+                "source.md".into(),
+                "folder/embedded.md".into(),
+                "folder/image.png".into(),
+            ],
+        );
+
+        assert_eq!(
+            expand("![[embedded]]", &dir.path().join("source.md"), &resolver),
+            "[Image](folder/image.png)"
+        );
+    }
+}
@@ -30,7 +30,8 @@ pub fn yaml_to_json_value(yaml: &YamlValue) -> JsonValue {
             }
             JsonValue::Object(json_map)
         }
-        YamlValue::Tagged(_) => todo!(),
+        // Custom tags like `!custom y` mean nothing to Obsidian, so keep the value
+        YamlValue::Tagged(tagged) => yaml_to_json_value(&tagged.value),
     }
 }
 
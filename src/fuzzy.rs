@@ -0,0 +1,315 @@
+//! Fuzzy matching of note paths, shared by `obx search --fuzzy`, the TUI's
+//! quick switcher and note names given to commands like `obx open`
+
+use crate::{
+    matching::{Case, MatchSettings},
+    scan,
+};
+use nucleo_matcher::{
+    pattern::{CaseMatching, Normalization, Pattern},
+    Config, Matcher, Utf32Str,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// How fuzzy matches are ranked. Every note is scored on how well its
+/// vault-relative path matches, then the boosts are added on top.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct FuzzyScoring {
+    /// How many times a match in the note's name counts on top of the path
+    /// match, so `meet` ranks `meeting.md` over `meetups/notes.md`
+    pub title_boost: f64,
+    /// The extra share of its score a note modified today gets, shrinking
+    /// to nothing over `recency_days`
+    pub recency_boost: f64,
+    pub recency_days: u32,
+}
+
+impl Default for FuzzyScoring {
+    fn default() -> Self {
+        Self {
+            title_boost: 1.0,
+            recency_boost: 0.25,
+            recency_days: 14,
+        }
+    }
+}
+
+/// A note matching a fuzzy query
+#[derive(Debug, Clone, PartialEq)]
+pub struct FuzzyMatch {
+    pub path: PathBuf,
+    pub relative: String,
+    pub score: f64,
+    /// The note's alias that matched, when it matched better than its name
+    pub alias: Option<String>,
+}
+
+impl FuzzyScoring {
+    pub fn is_default(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// The notes among `note_paths` matching `query`, best first. The query
+    /// uses fzf's syntax: space-separated words that must all match, with
+    /// `^` and `$` anchoring and `'` asking for an exact substring.
+    pub fn rank(
+        &self,
+        query: &str,
+        matching: &MatchSettings,
+        note_paths: &[PathBuf],
+        vault_path: &Path,
+    ) -> Vec<FuzzyMatch> {
+        self.rank_with_case(query, matching, matching.case, note_paths, vault_path)
+    }
+
+    /// Like [`FuzzyScoring::rank`], matching case as `case` says rather
+    /// than `matching`
+    pub fn rank_with_case(
+        &self,
+        query: &str,
+        matching: &MatchSettings,
+        case: Case,
+        note_paths: &[PathBuf],
+        vault_path: &Path,
+    ) -> Vec<FuzzyMatch> {
+        self.rank_notes(
+            query,
+            matching,
+            case,
+            note_paths,
+            vault_path,
+            &HashMap::new(),
+        )
+    }
+
+    /// Like [`FuzzyScoring::rank`], with notes also matching by their
+    /// `aliases`, keyed by vault-relative path. The best of a note's name and
+    /// aliases gets the title boost.
+    pub fn rank_with_aliases(
+        &self,
+        query: &str,
+        matching: &MatchSettings,
+        note_paths: &[PathBuf],
+        vault_path: &Path,
+        aliases: &HashMap<&str, &[String]>,
+    ) -> Vec<FuzzyMatch> {
+        self.rank_notes(
+            query,
+            matching,
+            matching.case,
+            note_paths,
+            vault_path,
+            aliases,
+        )
+    }
+
+    fn rank_notes(
+        &self,
+        query: &str,
+        settings: &MatchSettings,
+        case: Case,
+        note_paths: &[PathBuf],
+        vault_path: &Path,
+        aliases: &HashMap<&str, &[String]>,
+    ) -> Vec<FuzzyMatch> {
+        let case_matching = match case {
+            Case::Smart => CaseMatching::Smart,
+            Case::Sensitive => CaseMatching::Respect,
+            Case::Insensitive => CaseMatching::Ignore,
+        };
+        let pattern = Pattern::parse(
+            &settings.normalize(query),
+            case_matching,
+            Normalization::Smart,
+        );
+        let mut matcher = Matcher::new(Config::DEFAULT.match_paths());
+        let mut buf = Vec::new();
+        let now = SystemTime::now();
+
+        let mut matches: Vec<FuzzyMatch> = note_paths
+            .iter()
+            .filter_map(|path| {
+                let relative = scan::relative_note_path(path, vault_path);
+                let normalized = settings.normalize(&relative);
+                let path_score = pattern.score(Utf32Str::new(&normalized, &mut buf), &mut matcher);
+
+                let title = normalized
+                    .rsplit('/')
+                    .next()
+                    .and_then(|name| name.strip_suffix(".md"))
+                    .unwrap_or(&normalized);
+                let title_score = pattern
+                    .score(Utf32Str::new(title, &mut buf), &mut matcher)
+                    .unwrap_or(0);
+                let best_alias = aliases
+                    .get(relative.as_str())
+                    .into_iter()
+                    .flat_map(|aliases| aliases.iter())
+                    .filter_map(|alias| {
+                        let normalized = settings.normalize(alias);
+                        let score =
+                            pattern.score(Utf32Str::new(&normalized, &mut buf), &mut matcher)?;
+                        Some((score, alias))
+                    })
+                    .max_by_key(|(score, _)| *score)
+                    .filter(|(score, _)| *score > title_score);
+                if path_score.is_none() && best_alias.is_none() {
+                    return None;
+                }
+
+                let title_score = best_alias.map_or(title_score, |(score, _)| score);
+                let score = (f64::from(path_score.unwrap_or(0))
+                    + self.title_boost * f64::from(title_score))
+                    * (1.0 + self.recency_boost * self.freshness(path, now));
+
+                Some(FuzzyMatch {
+                    path: path.clone(),
+                    relative,
+                    score,
+                    alias: best_alias.map(|(_, alias)| alias.clone()),
+                })
+            })
+            .collect();
+
+        // Equal scores fall back to shorter paths, then alphabetical order
+        matches.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.relative.len().cmp(&b.relative.len()))
+                .then_with(|| a.relative.cmp(&b.relative))
+        });
+
+        matches
+    }
+
+    /// 1 for a note modified today, down to 0 once it's `recency_days` old.
+    /// Whole days are counted so notes saved moments apart rank the same.
+    fn freshness(&self, path: &Path, now: SystemTime) -> f64 {
+        if self.recency_days == 0 {
+            return 0.0;
+        }
+        let Some(age) = fs::metadata(path)
+            .and_then(|meta| meta.modified())
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok())
+        else {
+            return 0.0;
+        };
+
+        let days = age.as_secs() / SECONDS_PER_DAY;
+        let days_left = u64::from(self.recency_days).saturating_sub(days);
+        days_left as f64 / f64::from(self.recency_days)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::{prelude::*, TempDir};
+
+    fn ranked(scoring: &FuzzyScoring, query: &str, notes: &[&str]) -> Vec<String> {
+        let dir = TempDir::new().unwrap();
+        let paths: Vec<PathBuf> = notes
+            .iter()
+            .map(|note| {
+                let child = dir.child(note);
+                child.write_str("").unwrap();
+                child.to_path_buf()
+            })
+            .collect();
+
+        scoring
+            .rank(query, &MatchSettings::default(), &paths, dir.path())
+            .into_iter()
+            .map(|found| found.relative)
+            .collect()
+    }
+
+    #[test]
+    fn matches_aliases() {
+        let dir = TempDir::new().unwrap();
+        let paths: Vec<PathBuf> = ["plan.md", "zebra.md"]
+            .iter()
+            .map(|note| {
+                let child = dir.child(note);
+                child.write_str("").unwrap();
+                child.to_path_buf()
+            })
+            .collect();
+        let zebra_aliases = ["Roadmap".to_string()];
+        let aliases = HashMap::from([("zebra.md", &zebra_aliases[..])]);
+
+        let found = FuzzyScoring::default().rank_with_aliases(
+            "roadmap",
+            &MatchSettings::default(),
+            &paths,
+            dir.path(),
+            &aliases,
+        );
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].relative, "zebra.md");
+        assert_eq!(found[0].alias.as_deref(), Some("Roadmap"));
+    }
+
+    #[test]
+    fn boosts_title_matches() {
+        let notes = ["meetups/notes.md", "meeting.md", "other.md"];
+
+        assert_eq!(
+            ranked(&FuzzyScoring::default(), "meet", &notes),
+            ["meeting.md", "meetups/notes.md"]
+        );
+    }
+
+    #[test]
+    fn matches_every_word() {
+        let notes = ["projects/alpha.md", "projects/beta.md", "alpha.md"];
+
+        assert_eq!(
+            ranked(&FuzzyScoring::default(), "proj alp", &notes),
+            ["projects/alpha.md"]
+        );
+    }
+
+    #[test]
+    fn breaks_ties_by_length() {
+        let notes = ["complex-note.md", "simple-note.md"];
+
+        assert_eq!(
+            ranked(&FuzzyScoring::default(), "note", &notes),
+            ["simple-note.md", "complex-note.md"]
+        );
+    }
+
+    #[test]
+    fn boosts_recent_notes() {
+        let dir = TempDir::new().unwrap();
+        let old = dir.child("old-plan.md");
+        let new = dir.child("new-plan.md");
+        old.write_str("").unwrap();
+        new.write_str("").unwrap();
+        let month_ago = SystemTime::now() - std::time::Duration::from_secs(30 * SECONDS_PER_DAY);
+        fs::File::options()
+            .write(true)
+            .open(old.path())
+            .unwrap()
+            .set_modified(month_ago)
+            .unwrap();
+
+        let paths = [old.to_path_buf(), new.to_path_buf()];
+        let ranked =
+            FuzzyScoring::default().rank("plan", &MatchSettings::default(), &paths, dir.path());
+
+        assert_eq!(ranked[0].relative, "new-plan.md");
+        assert!(ranked[0].score > ranked[1].score);
+    }
+}
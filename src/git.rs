@@ -0,0 +1,607 @@
+//! The git repository a vault is kept in, for `obx git`. The vault can be
+//! the repository's root or a folder within it; either way only changes
+//! inside the vault are listed and committed, whichever folder obx is run
+//! from.
+
+use anyhow::{anyhow, bail, Context};
+use git2::{
+    build::CheckoutBuilder, BranchType, Cred, CredentialType, ErrorCode, FetchOptions,
+    IndexAddOption, PushOptions, RemoteCallbacks, Repository, Signature, Status, StatusOptions,
+};
+use std::{
+    cell::Cell,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+/// Credentials are asked for again after each rejection, so give up after
+/// this many rather than loop
+const CREDENTIAL_ATTEMPTS: usize = 3;
+
+pub struct VaultRepo {
+    repo: Repository,
+    /// The vault's folder relative to the repository's root, `""` when
+    /// they're the same
+    prefix: String,
+}
+
+/// A file in the vault that differs from the last commit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    pub kind: ChangeKind,
+    /// Vault-relative
+    pub path: String,
+    /// Where a renamed file was, vault-relative
+    pub from: Option<String>,
+}
+
+/// What happened to a file, in the order changes are summarised
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ChangeKind {
+    Added,
+    Modified,
+    Renamed,
+    Deleted,
+    /// Left with conflicts by a merge or rebase
+    Conflicted,
+}
+
+impl ChangeKind {
+    /// A letter like `git status --short` shows
+    pub fn code(self) -> char {
+        match self {
+            ChangeKind::Added => 'A',
+            ChangeKind::Modified => 'M',
+            ChangeKind::Renamed => 'R',
+            ChangeKind::Deleted => 'D',
+            ChangeKind::Conflicted => 'U',
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            ChangeKind::Added => "added",
+            ChangeKind::Modified => "modified",
+            ChangeKind::Renamed => "renamed",
+            ChangeKind::Deleted => "deleted",
+            ChangeKind::Conflicted => "conflicted",
+        }
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.from {
+            Some(from) => write!(f, "{} {from} → {}", self.kind.code(), self.path),
+            None => write!(f, "{} {}", self.kind.code(), self.path),
+        }
+    }
+}
+
+/// Where the current branch stands against the branch it tracks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BranchState {
+    /// `None` before the first commit
+    pub branch: Option<String>,
+    /// Like `origin/main`, with the commits only on each side as of the last
+    /// fetch
+    pub upstream: Option<(String, usize, usize)>,
+}
+
+impl fmt::Display for BranchState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(branch) = &self.branch else {
+            return f.write_str("No commits yet");
+        };
+        write!(f, "On branch {branch}")?;
+        match self.upstream {
+            Some((ref upstream, 0, 0)) => write!(f, ", up to date with {upstream}"),
+            Some((ref upstream, ahead, behind)) => {
+                let counts: Vec<String> = [(ahead, "ahead"), (behind, "behind")]
+                    .into_iter()
+                    .filter(|(count, _)| *count > 0)
+                    .map(|(count, side)| format!("{count} {side}"))
+                    .collect();
+                write!(f, ", {} of {upstream}", counts.join(" and "))
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+/// What `sync` did
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Synced {
+    /// Like `origin/main`
+    pub upstream: String,
+    pub pulled: usize,
+    pub pushed: usize,
+}
+
+impl fmt::Display for Synced {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.pulled == 0 && self.pushed == 0 {
+            return write!(f, "Already in sync with {}", self.upstream);
+        }
+        let mut lines = Vec::new();
+        if self.pulled > 0 {
+            lines.push(format!(
+                "Pulled {} from {}",
+                commits(self.pulled),
+                self.upstream
+            ));
+        }
+        if self.pushed > 0 {
+            lines.push(format!(
+                "Pushed {} to {}",
+                commits(self.pushed),
+                self.upstream
+            ));
+        }
+        f.write_str(&lines.join("\n"))
+    }
+}
+
+fn commits(count: usize) -> String {
+    match count {
+        1 => "1 commit".to_string(),
+        count => format!("{count} commits"),
+    }
+}
+
+impl VaultRepo {
+    /// The repository holding the vault at `vault_path`
+    pub fn open(vault_path: &Path) -> anyhow::Result<Self> {
+        let repo = Repository::discover(vault_path).map_err(|_| {
+            anyhow!(
+                "Vault at {} isn't in a git repository, create one with `git init`",
+                vault_path.display()
+            )
+        })?;
+        let workdir = repo
+            .workdir()
+            .ok_or_else(|| anyhow!("The repository holding the vault is bare"))?;
+        let prefix = canonical(vault_path)?
+            .strip_prefix(canonical(workdir)?)
+            .map(|prefix| prefix.to_string_lossy().replace('\\', "/"))
+            .unwrap_or_default();
+
+        Ok(Self { repo, prefix })
+    }
+
+    /// Every file in the vault that differs from the last commit, staged or
+    /// not, by path
+    pub fn changes(&self) -> anyhow::Result<Vec<Change>> {
+        let mut options = StatusOptions::new();
+        options
+            .include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .renames_head_to_index(true)
+            .renames_index_to_workdir(true);
+        for pathspec in self.pathspec() {
+            options.pathspec(pathspec);
+        }
+
+        let mut changes: Vec<Change> = self
+            .repo
+            .statuses(Some(&mut options))?
+            .iter()
+            .filter_map(|entry| {
+                let status = entry.status();
+                let kind = change_kind(status)?;
+                let delta = entry.index_to_workdir().or_else(|| entry.head_to_index());
+                let path = delta
+                    .as_ref()
+                    .and_then(|delta| delta.new_file().path())
+                    .map(|path| path.to_string_lossy().into_owned())
+                    .or_else(|| entry.path().map(str::to_string))?;
+                let from = (kind == ChangeKind::Renamed)
+                    .then(|| delta?.old_file().path().map(Path::to_path_buf))
+                    .flatten()
+                    .map(|path| self.vault_relative(&path.to_string_lossy()));
+                Some(Change {
+                    kind,
+                    path: self.vault_relative(&path),
+                    from,
+                })
+            })
+            .collect();
+        changes.sort_by(|a, b| a.path.cmp(&b.path));
+        changes.dedup();
+        Ok(changes)
+    }
+
+    /// The current branch and how far it is from its upstream, without
+    /// fetching
+    pub fn branch_state(&self) -> anyhow::Result<BranchState> {
+        let head = match self.repo.head() {
+            Ok(head) => head,
+            Err(err) if is_unborn(&err) => {
+                return Ok(BranchState {
+                    branch: None,
+                    upstream: None,
+                })
+            }
+            Err(err) => return Err(err.into()),
+        };
+        let branch = head.shorthand().unwrap_or("HEAD").to_string();
+        let upstream = self
+            .repo
+            .find_branch(&branch, BranchType::Local)
+            .and_then(|local| local.upstream())
+            .ok()
+            .and_then(|upstream| {
+                let name = upstream.name().ok()??.to_string();
+                let (ahead, behind) = self
+                    .repo
+                    .graph_ahead_behind(head.target()?, upstream.get().target()?)
+                    .ok()?;
+                Some((name, ahead, behind))
+            });
+
+        Ok(BranchState {
+            branch: Some(branch),
+            upstream,
+        })
+    }
+
+    /// Stage every change in the vault and commit it with `message`, or a
+    /// summary of the changes without one. Returns the short commit ID and
+    /// the message's first line, or `None` when there was nothing to commit.
+    pub fn commit(&self, message: Option<&str>) -> anyhow::Result<Option<(String, String)>> {
+        let changes = self.changes()?;
+        if changes.is_empty() {
+            return Ok(None);
+        }
+        let message = match message {
+            Some(message) => message.to_string(),
+            None => commit_message(&changes),
+        };
+
+        let parent = match self.repo.head() {
+            Ok(head) => Some(head.peel_to_commit()?),
+            Err(err) if is_unborn(&err) => None,
+            Err(err) => return Err(err.into()),
+        };
+        let mut index = self.repo.index()?;
+        // The commit is of the whole index, so anything staged beside the
+        // vault would go along with the vault's changes
+        if !self.prefix.is_empty() {
+            let head_tree = parent.as_ref().map(git2::Commit::tree).transpose()?;
+            let staged = self
+                .repo
+                .diff_tree_to_index(head_tree.as_ref(), Some(&index), None)?;
+            let outside = staged.deltas().find_map(|delta| {
+                let path = delta.new_file().path().or(delta.old_file().path())?;
+                (!path.starts_with(&self.prefix)).then(|| path.display().to_string())
+            });
+            if let Some(outside) = outside {
+                bail!("{outside} is staged outside the vault, commit or unstage it with git first");
+            }
+        }
+
+        let pathspec = self.pathspec();
+        index.add_all(&pathspec, IndexAddOption::DEFAULT, None)?;
+        index.update_all(&pathspec, None)?;
+        index.write()?;
+        let tree = self.repo.find_tree(index.write_tree()?)?;
+
+        let signature = self.signature()?;
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        let id = self.repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            &message,
+            &tree,
+            &parents,
+        )?;
+
+        let summary = message.lines().next().unwrap_or_default().to_string();
+        Ok(Some((id.to_string()[..7].to_string(), summary)))
+    }
+
+    /// Fetch the current branch's upstream, rebase any local commits onto
+    /// it, and push, like `git pull --rebase && git push`. A branch without
+    /// an upstream syncs with the same branch on `origin`, which it then
+    /// tracks.
+    pub fn sync(&self) -> anyhow::Result<Synced> {
+        if !self.changes()?.is_empty() {
+            bail!("The vault has uncommitted changes, commit them with `obx git commit` first");
+        }
+        let head = self
+            .repo
+            .head()
+            .map_err(|_| anyhow!("Nothing to sync yet, commit with `obx git commit` first"))?;
+        let branch = head
+            .shorthand()
+            .ok_or_else(|| anyhow!("Not on a branch"))?
+            .to_string();
+        let remote_name = self
+            .repo
+            .branch_upstream_remote(&format!("refs/heads/{branch}"))
+            .ok()
+            .and_then(|name| name.as_str().map(str::to_string))
+            .unwrap_or_else(|| "origin".to_string());
+        let mut remote = self.repo.find_remote(&remote_name).map_err(|_| {
+            anyhow!("No remote `{remote_name}` to sync with, add one with `git remote add`")
+        })?;
+        let upstream = format!("{remote_name}/{branch}");
+        let config = self.repo.config()?;
+
+        let mut fetch_options = FetchOptions::new();
+        fetch_options.remote_callbacks(callbacks(&config));
+        remote
+            .fetch(
+                &[format!("refs/heads/{branch}:refs/remotes/{upstream}")],
+                Some(&mut fetch_options),
+                None,
+            )
+            .map_err(|err| anyhow!("Could not fetch from {remote_name}: {}", err.message()))?;
+
+        let upstream_id = self
+            .repo
+            .find_reference(&format!("refs/remotes/{upstream}"))
+            .ok()
+            .and_then(|reference| reference.target());
+        let pulled = match upstream_id {
+            Some(upstream_id) => self.rebase_onto(&branch, &upstream, upstream_id)?,
+            None => 0,
+        };
+
+        let local_id = self.repo.refname_to_id(&format!("refs/heads/{branch}"))?;
+        let pushed = match upstream_id {
+            Some(upstream_id) => self.repo.graph_ahead_behind(local_id, upstream_id)?.0,
+            None => {
+                let mut walk = self.repo.revwalk()?;
+                walk.push(local_id)?;
+                walk.count()
+            }
+        };
+        if pushed > 0 {
+            let rejected: Cell<Option<String>> = Cell::new(None);
+            let mut push_callbacks = callbacks(&config);
+            push_callbacks.push_update_reference(|_, status| {
+                if let Some(status) = status {
+                    rejected.set(Some(status.to_string()));
+                }
+                Ok(())
+            });
+            let mut push_options = PushOptions::new();
+            push_options.remote_callbacks(push_callbacks);
+            remote
+                .push(
+                    &[format!("refs/heads/{branch}:refs/heads/{branch}")],
+                    Some(&mut push_options),
+                )
+                .map_err(|err| anyhow!("Could not push to {remote_name}: {}", err.message()))?;
+            if let Some(reason) = rejected.take() {
+                bail!("{remote_name} rejected the push: {reason}");
+            }
+        }
+        if upstream_id.is_none() {
+            self.repo
+                .find_branch(&branch, BranchType::Local)?
+                .set_upstream(Some(&upstream))?;
+        }
+
+        Ok(Synced {
+            upstream,
+            pulled,
+            pushed,
+        })
+    }
+
+    /// Bring `branch` up to `upstream_id`, fast-forwarding or replaying its
+    /// own commits on top, and return how many commits came in. A rebase
+    /// that conflicts is abandoned, leaving the branch as it was.
+    fn rebase_onto(
+        &self,
+        branch: &str,
+        upstream: &str,
+        upstream_id: git2::Oid,
+    ) -> anyhow::Result<usize> {
+        let local_id = self.repo.refname_to_id(&format!("refs/heads/{branch}"))?;
+        let (ahead, behind) = self.repo.graph_ahead_behind(local_id, upstream_id)?;
+        if behind == 0 {
+            return Ok(0);
+        }
+
+        if ahead == 0 {
+            let target = self.repo.find_object(upstream_id, None)?;
+            self.repo
+                .checkout_tree(&target, Some(CheckoutBuilder::new().safe()))?;
+            self.repo
+                .find_reference(&format!("refs/heads/{branch}"))?
+                .set_target(
+                    upstream_id,
+                    &format!("obx git sync: fast-forward to {upstream}"),
+                )?;
+            return Ok(behind);
+        }
+
+        let signature = self.signature()?;
+        // From the branch rather than its commit, so finishing moves the branch
+        let local = self.repo.reference_to_annotated_commit(
+            &self.repo.find_reference(&format!("refs/heads/{branch}"))?,
+        )?;
+        let onto = self.repo.find_annotated_commit(upstream_id)?;
+        let mut rebase = self.repo.rebase(Some(&local), Some(&onto), None, None)?;
+        while let Some(operation) = rebase.next() {
+            operation?;
+            if self.repo.index()?.has_conflicts() {
+                rebase.abort()?;
+                bail!(
+                    "Your commits conflict with {upstream}, nothing was changed. Resolve it with `git pull --rebase`"
+                );
+            }
+            match rebase.commit(None, &signature, None) {
+                Ok(_) => {}
+                // The commit's changes are already upstream
+                Err(err) if err.code() == ErrorCode::Applied => {}
+                Err(err) => {
+                    rebase.abort()?;
+                    return Err(err.into());
+                }
+            }
+        }
+        rebase.finish(Some(&signature))?;
+        Ok(behind)
+    }
+
+    fn signature(&self) -> anyhow::Result<Signature<'static>> {
+        self.repo.signature().map_err(|_| {
+            anyhow!("Set who commits with `git config user.name` and `git config user.email`")
+        })
+    }
+
+    /// Pathspecs matching only what's in the vault, none when it's the whole
+    /// repository. libgit2 only matches a folder's contents by its name when
+    /// the name has no glob characters, so the escaped name is followed by
+    /// `/**` instead.
+    fn pathspec(&self) -> Vec<String> {
+        if self.prefix.is_empty() {
+            return Vec::new();
+        }
+        vec![format!("{}/**", escape_pathspec(&self.prefix))]
+    }
+
+    /// A repository-relative path relative to the vault instead
+    fn vault_relative(&self, path: &str) -> String {
+        if self.prefix.is_empty() {
+            return path.to_string();
+        }
+        path.strip_prefix(&self.prefix)
+            .map(|rest| rest.trim_start_matches('/'))
+            .unwrap_or(path)
+            .to_string()
+    }
+}
+
+fn canonical(path: &Path) -> anyhow::Result<PathBuf> {
+    path.canonicalize()
+        .with_context(|| format!("Could not find {}", path.display()))
+}
+
+/// `path` as a pathspec matching only itself, with the characters git reads
+/// as a glob escaped
+fn escape_pathspec(path: &str) -> String {
+    let mut escaped = String::with_capacity(path.len());
+    for c in path.chars() {
+        if matches!(c, '*' | '?' | '[' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn is_unborn(err: &git2::Error) -> bool {
+    matches!(err.code(), ErrorCode::UnbornBranch | ErrorCode::NotFound)
+}
+
+fn change_kind(status: Status) -> Option<ChangeKind> {
+    if status.is_conflicted() {
+        Some(ChangeKind::Conflicted)
+    } else if status.intersects(Status::INDEX_RENAMED | Status::WT_RENAMED) {
+        Some(ChangeKind::Renamed)
+    } else if status.intersects(Status::INDEX_NEW | Status::WT_NEW) {
+        Some(ChangeKind::Added)
+    } else if status.intersects(Status::INDEX_DELETED | Status::WT_DELETED) {
+        Some(ChangeKind::Deleted)
+    } else if status.intersects(
+        Status::INDEX_MODIFIED
+            | Status::WT_MODIFIED
+            | Status::INDEX_TYPECHANGE
+            | Status::WT_TYPECHANGE,
+    ) {
+        Some(ChangeKind::Modified)
+    } else {
+        None
+    }
+}
+
+/// A commit message for `changes`: what happened to the one file, or how
+/// many files changed which way followed by the list of them
+pub fn commit_message(changes: &[Change]) -> String {
+    if let [change] = changes {
+        return match (change.kind, &change.from) {
+            (ChangeKind::Added, _) => format!("Add {}", change.path),
+            (ChangeKind::Deleted, _) => format!("Delete {}", change.path),
+            (ChangeKind::Renamed, Some(from)) => format!("Rename {from} to {}", change.path),
+            _ => format!("Update {}", change.path),
+        };
+    }
+
+    let mut kinds: Vec<ChangeKind> = changes.iter().map(|change| change.kind).collect();
+    kinds.sort();
+    kinds.dedup();
+    let counts: Vec<String> = kinds
+        .iter()
+        .map(|&kind| {
+            let count = changes.iter().filter(|change| change.kind == kind).count();
+            format!("{count} {}", kind.name())
+        })
+        .collect();
+    let listed: Vec<String> = changes.iter().map(Change::to_string).collect();
+    format!(
+        "Update {} files: {}\n\n{}\n",
+        changes.len(),
+        counts.join(", "),
+        listed.join("\n")
+    )
+}
+
+/// Credentials from the SSH agent or git's credential helpers, the way the
+/// git command finds them
+fn callbacks(config: &git2::Config) -> RemoteCallbacks<'_> {
+    let attempts = Cell::new(0);
+    let mut callbacks = RemoteCallbacks::new();
+    callbacks.credentials(move |url, username, allowed| {
+        attempts.set(attempts.get() + 1);
+        if attempts.get() > CREDENTIAL_ATTEMPTS {
+            return Err(git2::Error::from_str("no credentials were accepted"));
+        }
+        if allowed.contains(CredentialType::SSH_KEY) {
+            return Cred::ssh_key_from_agent(username.unwrap_or("git"));
+        }
+        if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) {
+            return Cred::credential_helper(config, url, username);
+        }
+        Cred::default()
+    });
+    callbacks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarises_changes() {
+        let change = |kind, path: &str| Change {
+            kind,
+            path: path.to_string(),
+            from: None,
+        };
+
+        assert_eq!(
+            commit_message(&[change(ChangeKind::Added, "Inbox/idea.md")]),
+            "Add Inbox/idea.md"
+        );
+        assert_eq!(
+            commit_message(&[
+                change(ChangeKind::Modified, "plan.md"),
+                change(ChangeKind::Added, "idea.md"),
+                change(ChangeKind::Modified, "todo.md"),
+            ]),
+            "Update 3 files: 1 added, 2 modified\n\nM plan.md\nA idea.md\nM todo.md\n"
+        );
+    }
+
+    #[test]
+    fn escapes_globs_in_pathspecs() {
+        assert_eq!(escape_pathspec("Notes/2024"), "Notes/2024");
+        assert_eq!(escape_pathspec("[Work] *?"), "\\[Work] \\*\\?");
+    }
+}
@@ -0,0 +1,194 @@
+//! The link graph written out for other tools: DOT for Graphviz, GraphML
+//! for Gephi and yEd, and JSON for anything else. Nodes are notes and each
+//! edge stands for every link from one note to another, counted in its
+//! weight. Links to attachments, missing notes and a note's own headings
+//! are left out.
+
+use crate::{cache::MetadataCache, link_graph::LinkGraph};
+use quick_xml::escape::escape;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Subgraph<'a> {
+    pub nodes: Vec<Node<'a>>,
+    pub edges: Vec<GraphEdge<'a>>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Node<'a> {
+    /// Vault-relative path
+    pub id: &'a str,
+    /// The note's name
+    pub label: &'a str,
+    pub tags: Vec<&'a str>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct GraphEdge<'a> {
+    pub source: &'a str,
+    pub target: &'a str,
+    /// How many links there are from `source` to `target`
+    pub weight: usize,
+}
+
+impl<'a> Subgraph<'a> {
+    /// The notes in `notes` and the links between them
+    pub fn new(graph: &'a LinkGraph, cache: &'a MetadataCache, notes: &BTreeSet<&'a str>) -> Self {
+        let nodes = notes
+            .iter()
+            .map(|&id| Node {
+                id,
+                label: note_name(id),
+                tags: cache
+                    .get(id)
+                    .map(|note| note.tags.iter().map(String::as_str).collect())
+                    .unwrap_or_default(),
+            })
+            .collect();
+
+        let mut weights: BTreeMap<(&str, &str), usize> = BTreeMap::new();
+        for &source in notes {
+            for edge in graph.links_from(source) {
+                let Some(target) = edge.resolved.as_deref() else {
+                    continue;
+                };
+                if target != source && notes.contains(target) {
+                    *weights.entry((source, target)).or_default() += 1;
+                }
+            }
+        }
+        let edges = weights
+            .into_iter()
+            .map(|((source, target), weight)| GraphEdge {
+                source,
+                target,
+                weight,
+            })
+            .collect();
+
+        Self { nodes, edges }
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph vault {\n");
+        for node in &self.nodes {
+            dot.push_str(&format!(
+                "  {} [label={}];\n",
+                dot_string(node.id),
+                dot_string(node.label)
+            ));
+        }
+        for edge in &self.edges {
+            let weight = match edge.weight {
+                1 => String::new(),
+                weight => format!(" [weight={weight}]"),
+            };
+            dot.push_str(&format!(
+                "  {} -> {}{weight};\n",
+                dot_string(edge.source),
+                dot_string(edge.target)
+            ));
+        }
+        dot.push('}');
+        dot
+    }
+
+    pub fn to_graphml(&self) -> String {
+        let mut xml = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+            "  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n",
+            "  <key id=\"tags\" for=\"node\" attr.name=\"tags\" attr.type=\"string\"/>\n",
+            "  <key id=\"weight\" for=\"edge\" attr.name=\"weight\" attr.type=\"int\"/>\n",
+            "  <graph id=\"vault\" edgedefault=\"directed\">\n",
+        ));
+        for node in &self.nodes {
+            xml.push_str(&format!(
+                "    <node id=\"{}\"><data key=\"label\">{}</data><data key=\"tags\">{}</data></node>\n",
+                escape(node.id),
+                escape(node.label),
+                escape(&node.tags.join(" "))
+            ));
+        }
+        for edge in &self.edges {
+            xml.push_str(&format!(
+                "    <edge source=\"{}\" target=\"{}\"><data key=\"weight\">{}</data></edge>\n",
+                escape(edge.source),
+                escape(edge.target),
+                edge.weight
+            ));
+        }
+        xml.push_str("  </graph>\n</graphml>");
+        xml
+    }
+}
+
+/// A note's file name without its folders or `.md`
+fn note_name(relative: &str) -> &str {
+    let name = relative.rsplit('/').next().unwrap_or(relative);
+    name.strip_suffix(".md").unwrap_or(name)
+}
+
+/// `text` as a quoted DOT identifier
+fn dot_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::{prelude::*, TempDir};
+
+    fn subgraph_of(dir: &TempDir, f: impl FnOnce(Subgraph)) {
+        let mut graph = LinkGraph::default();
+        graph.refresh(dir.path()).unwrap();
+        let mut cache = MetadataCache::default();
+        cache.refresh(dir.path()).unwrap();
+        let notes: BTreeSet<&str> = graph.notes().into_iter().collect();
+
+        f(Subgraph::new(&graph, &cache, &notes));
+    }
+
+    #[test]
+    fn counts_links_between_notes() {
+        let dir = TempDir::new().unwrap();
+        dir.child("a.md")
+            .write_str("[[b]] and [[b#Later]], [[a#Top]] ![[image.png]] [[gone]]")
+            .unwrap();
+        dir.child("folder/b.md")
+            .write_str("#topic back to [[a]]")
+            .unwrap();
+        dir.child("image.png").write_str("").unwrap();
+
+        subgraph_of(&dir, |subgraph| {
+            assert_eq!(
+                subgraph.to_dot(),
+                concat!(
+                    "digraph vault {\n",
+                    "  \"a.md\" [label=\"a\"];\n",
+                    "  \"folder/b.md\" [label=\"b\"];\n",
+                    "  \"a.md\" -> \"folder/b.md\" [weight=2];\n",
+                    "  \"folder/b.md\" -> \"a.md\";\n",
+                    "}"
+                )
+            );
+            assert_eq!(subgraph.nodes[1].tags, ["topic"]);
+        });
+    }
+
+    #[test]
+    fn escapes_names() {
+        let dir = TempDir::new().unwrap();
+        dir.child("Q&A \"draft\".md").write_str("").unwrap();
+
+        subgraph_of(&dir, |subgraph| {
+            assert!(subgraph
+                .to_dot()
+                .contains(r#""Q&A \"draft\".md" [label="Q&A \"draft\""];"#));
+            assert!(subgraph
+                .to_graphml()
+                .contains(r#"<node id="Q&amp;A &quot;draft&quot;.md">"#));
+        });
+    }
+}
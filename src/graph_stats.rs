@@ -0,0 +1,353 @@
+//! Figures about the shape of the link graph, for `obx graph stats`: how
+//! many links notes have each way, which notes most others link to, and how
+//! connected the vault is. Like the [exports](crate::graph_export), only
+//! links from one note to another count, once per pair of notes.
+
+use crate::link_graph::LinkGraph;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+/// The degree ranges the distributions count notes in
+const BUCKETS: [(usize, Option<usize>); 5] = [
+    (0, Some(0)),
+    (1, Some(1)),
+    (2, Some(4)),
+    (5, Some(9)),
+    (10, None),
+];
+
+/// At most this many notes are walked from for the average path length, a
+/// walk from every note taking too long in a big vault
+const PATH_SAMPLES: usize = 200;
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct GraphStats {
+    pub notes: usize,
+    /// Pairs of notes where the first links to the second
+    pub links: usize,
+    pub orphans: usize,
+    /// Out of every note, from 0 to 100
+    pub orphan_percentage: f64,
+    /// Groups of notes linked to each other, either way, directly or through
+    /// others
+    pub components: usize,
+    pub largest_component: usize,
+    /// The mean number of links between notes that are connected, following
+    /// links either way, estimated from paths out of a spread of notes in a
+    /// big vault. `None` when no two notes are.
+    pub average_path_length: Option<f64>,
+    /// How many notes link to each note
+    pub in_degree: Degrees,
+    /// How many notes each note links to
+    pub out_degree: Degrees,
+    /// The notes most other notes link to, most first
+    pub hubs: Vec<Hub>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Degrees {
+    pub mean: f64,
+    pub median: f64,
+    pub max: usize,
+    pub distribution: Vec<Bucket>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Bucket {
+    /// Like `2-4`, or `10+` for the last
+    pub degree: String,
+    pub notes: usize,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Hub {
+    pub note: String,
+    /// How many notes link to it
+    pub links: usize,
+}
+
+impl GraphStats {
+    /// The figures for every note in `graph`, listing up to `top` hubs
+    pub fn new(graph: &LinkGraph, top: usize) -> Self {
+        let notes = graph.notes();
+        let mut outgoing: BTreeMap<&str, BTreeSet<&str>> =
+            notes.iter().map(|&note| (note, BTreeSet::new())).collect();
+        let mut incoming = outgoing.clone();
+        for &source in &notes {
+            for edge in graph.links_from(source) {
+                let Some(target) = edge.resolved.as_deref() else {
+                    continue;
+                };
+                if target == source || !incoming.contains_key(target) {
+                    continue;
+                }
+                if let Some(targets) = outgoing.get_mut(source) {
+                    targets.insert(target);
+                }
+                if let Some(sources) = incoming.get_mut(target) {
+                    sources.insert(source);
+                }
+            }
+        }
+
+        let mut undirected = outgoing.clone();
+        for (&target, sources) in &incoming {
+            if let Some(neighbors) = undirected.get_mut(target) {
+                neighbors.extend(sources);
+            }
+        }
+
+        let mut hubs: Vec<Hub> = incoming
+            .iter()
+            .filter(|(_, sources)| !sources.is_empty())
+            .map(|(note, sources)| Hub {
+                note: note.to_string(),
+                links: sources.len(),
+            })
+            .collect();
+        hubs.sort_by(|a, b| b.links.cmp(&a.links).then_with(|| a.note.cmp(&b.note)));
+        hubs.truncate(top);
+
+        let orphans = graph.orphans().len();
+        let components = component_sizes(&undirected);
+
+        Self {
+            notes: notes.len(),
+            links: outgoing.values().map(BTreeSet::len).sum(),
+            orphans,
+            orphan_percentage: round(percentage(orphans, notes.len())),
+            components: components.len(),
+            largest_component: components.iter().copied().max().unwrap_or(0),
+            average_path_length: average_path_length(&undirected).map(round),
+            in_degree: Degrees::new(incoming.values().map(BTreeSet::len).collect()),
+            out_degree: Degrees::new(outgoing.values().map(BTreeSet::len).collect()),
+            hubs,
+        }
+    }
+
+    /// The figures as lines of text
+    pub fn report(&self) -> String {
+        let mut lines = vec![
+            format!("Notes: {}", self.notes),
+            format!("Links between notes: {}", self.links),
+            format!("Orphans: {} ({}%)", self.orphans, self.orphan_percentage),
+            format!(
+                "Connected components: {}, the largest with {} {}",
+                self.components,
+                self.largest_component,
+                if self.largest_component == 1 {
+                    "note"
+                } else {
+                    "notes"
+                }
+            ),
+            format!(
+                "Average path length: {}",
+                self.average_path_length
+                    .map_or_else(|| "none".to_string(), |length| length.to_string())
+            ),
+        ];
+        for (title, degrees) in [
+            ("Links in", &self.in_degree),
+            ("Links out", &self.out_degree),
+        ] {
+            lines.push(String::new());
+            lines.push(format!(
+                "{title}: mean {}, median {}, max {}",
+                degrees.mean, degrees.median, degrees.max
+            ));
+            for bucket in &degrees.distribution {
+                lines.push(format!("  {:<5} {}", bucket.degree, bucket.notes));
+            }
+        }
+        if !self.hubs.is_empty() {
+            lines.push(String::new());
+            lines.push("Most linked notes:".to_string());
+            let width = self.hubs[0].links.to_string().len();
+            for hub in &self.hubs {
+                lines.push(format!("  {:>width$} {}", hub.links, hub.note));
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+impl Degrees {
+    fn new(mut degrees: Vec<usize>) -> Self {
+        degrees.sort_unstable();
+        let median = match degrees.len() {
+            0 => 0.0,
+            len if len % 2 == 1 => degrees[len / 2] as f64,
+            len => (degrees[len / 2 - 1] + degrees[len / 2]) as f64 / 2.0,
+        };
+        let total: usize = degrees.iter().sum();
+        let distribution = BUCKETS
+            .iter()
+            .map(|&(low, high)| Bucket {
+                degree: match high {
+                    Some(high) if high == low => low.to_string(),
+                    Some(high) => format!("{low}-{high}"),
+                    None => format!("{low}+"),
+                },
+                notes: degrees
+                    .iter()
+                    .filter(|&&degree| degree >= low && high.is_none_or(|high| degree <= high))
+                    .count(),
+            })
+            .collect();
+
+        Self {
+            mean: round(if degrees.is_empty() {
+                0.0
+            } else {
+                total as f64 / degrees.len() as f64
+            }),
+            median,
+            max: degrees.last().copied().unwrap_or(0),
+            distribution,
+        }
+    }
+}
+
+/// The number of notes in each group of connected notes
+fn component_sizes(neighbors: &BTreeMap<&str, BTreeSet<&str>>) -> Vec<usize> {
+    let mut seen = BTreeSet::new();
+    let mut sizes = Vec::new();
+    for &start in neighbors.keys() {
+        if seen.insert(start) {
+            sizes.push(distances(neighbors, start, &mut seen).len());
+        }
+    }
+    sizes
+}
+
+/// The mean of the shortest paths between pairs of connected notes, from
+/// every note or, in a big vault, [`PATH_SAMPLES`] spread evenly through it
+fn average_path_length(neighbors: &BTreeMap<&str, BTreeSet<&str>>) -> Option<f64> {
+    let step = neighbors.len().div_ceil(PATH_SAMPLES).max(1);
+    let (mut total, mut pairs) = (0, 0);
+    for &start in neighbors.keys().step_by(step) {
+        let mut seen = BTreeSet::from([start]);
+        for distance in distances(neighbors, start, &mut seen) {
+            if distance > 0 {
+                total += distance;
+                pairs += 1;
+            }
+        }
+    }
+    (pairs > 0).then(|| total as f64 / pairs as f64)
+}
+
+/// How many links away from `start` each note reachable from it is,
+/// breadth first, `start` included at 0. Notes already in `seen` are
+/// skipped, and every note reached is added to it.
+fn distances<'a>(
+    neighbors: &BTreeMap<&'a str, BTreeSet<&'a str>>,
+    start: &'a str,
+    seen: &mut BTreeSet<&'a str>,
+) -> Vec<usize> {
+    let mut found = Vec::new();
+    let mut queue = VecDeque::from([(start, 0)]);
+    while let Some((note, distance)) = queue.pop_front() {
+        found.push(distance);
+        for &next in neighbors.get(note).into_iter().flatten() {
+            if seen.insert(next) {
+                queue.push_back((next, distance + 1));
+            }
+        }
+    }
+    found
+}
+
+fn percentage(part: usize, whole: usize) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 * 100.0 / whole as f64
+    }
+}
+
+/// Two decimal places are plenty for a report
+fn round(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::{prelude::*, TempDir};
+
+    #[test]
+    fn measures_the_graph() {
+        let dir = TempDir::new().unwrap();
+        dir.child("hub.md").write_str("[[a]] [[b]]").unwrap();
+        dir.child("a.md")
+            .write_str("[[hub]] [[hub]] [[b]]")
+            .unwrap();
+        dir.child("b.md").write_str("[[missing]]").unwrap();
+        dir.child("pair/one.md").write_str("[[two]]").unwrap();
+        dir.child("pair/two.md").write_str("").unwrap();
+        dir.child("alone.md").write_str("[[alone]]").unwrap();
+        let mut graph = LinkGraph::default();
+        graph.refresh(dir.path()).unwrap();
+
+        let stats = GraphStats::new(&graph, 2);
+
+        assert_eq!(stats.notes, 6);
+        assert_eq!(stats.links, 5);
+        assert_eq!(stats.orphans, 1);
+        assert_eq!(stats.orphan_percentage, 16.67);
+        assert_eq!(stats.components, 3);
+        assert_eq!(stats.largest_component, 3);
+        // hub, a and b are each one link apart, one and two are too
+        assert_eq!(stats.average_path_length, Some(1.0));
+        assert_eq!(stats.in_degree.max, 2);
+        assert_eq!(stats.out_degree.median, 0.5);
+        assert_eq!(
+            stats.in_degree.distribution[..3],
+            [
+                Bucket {
+                    degree: "0".to_string(),
+                    notes: 2
+                },
+                Bucket {
+                    degree: "1".to_string(),
+                    notes: 3
+                },
+                Bucket {
+                    degree: "2-4".to_string(),
+                    notes: 1
+                },
+            ]
+        );
+        assert_eq!(
+            stats.hubs,
+            [
+                Hub {
+                    note: "b.md".to_string(),
+                    links: 2
+                },
+                Hub {
+                    note: "a.md".to_string(),
+                    links: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn estimates_path_lengths_in_big_vaults() {
+        // A chain of notes, each linked to the next
+        let names: Vec<String> = (0..2000).map(|idx| format!("{idx:04}")).collect();
+        let mut neighbors: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+        for pair in names.windows(2) {
+            neighbors.entry(&pair[0]).or_default().insert(&pair[1]);
+            neighbors.entry(&pair[1]).or_default().insert(&pair[0]);
+        }
+
+        // Every pair averages a third of the chain apart
+        let exact = 2001.0 / 3.0;
+        let estimate = average_path_length(&neighbors).unwrap();
+        assert!((estimate - exact).abs() / exact < 0.01, "{estimate}");
+    }
+}
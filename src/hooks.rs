@@ -0,0 +1,104 @@
+use crate::cli_config::{self, Vault};
+use anyhow::{bail, Context};
+use serde::{Deserialize, Serialize};
+use std::{
+    io,
+    path::Path,
+    process::{self, Stdio},
+};
+
+/// Commands run around note operations, split into arguments the way a shell
+/// would. Each runs from the vault's folder with the note and vault in
+/// `OBX_NOTE_PATH`, `OBX_VAULT` and `OBX_VAULT_PATH`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Hooks {
+    /// Run once a note is created, after the editor closes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub after_create: Option<String>,
+    /// Run for each note renamed or moved, with its old path in
+    /// `OBX_PREVIOUS_NOTE_PATH`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub after_move: Option<String>,
+    /// Run before a note is deleted, a non-zero exit keeps the note
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub before_delete: Option<String>,
+    /// Run each time `obx daily` opens or creates a periodic note
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub after_daily: Option<String>,
+}
+
+impl Hooks {
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Event {
+    AfterCreate,
+    AfterMove,
+    BeforeDelete,
+    AfterDaily,
+}
+
+impl Event {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Event::AfterCreate => "after_create",
+            Event::AfterMove => "after_move",
+            Event::BeforeDelete => "before_delete",
+            Event::AfterDaily => "after_daily",
+        }
+    }
+
+    fn command(&self, hooks: &Hooks) -> Option<String> {
+        match self {
+            Event::AfterCreate => hooks.after_create.clone(),
+            Event::AfterMove => hooks.after_move.clone(),
+            Event::BeforeDelete => hooks.before_delete.clone(),
+            Event::AfterDaily => hooks.after_daily.clone(),
+        }
+    }
+}
+
+/// Run the configured hook for `event`, if there is one. The hook's output
+/// goes to stderr so it doesn't mix with obx's own.
+pub fn run(
+    event: Event,
+    vault: &Vault,
+    note: &Path,
+    previous: Option<&Path>,
+) -> anyhow::Result<()> {
+    let hooks = cli_config::read()?.hooks;
+    let Some(command) = event.command(&hooks) else {
+        return Ok(());
+    };
+    let name = event.name();
+
+    let words = shell_words::split(&command)
+        .with_context(|| format!("Could not parse the `{name}` hook `{command}`"))?;
+    let Some((program, args)) = words.split_first() else {
+        return Ok(());
+    };
+
+    let mut hook = process::Command::new(program);
+    hook.args(args)
+        .current_dir(&vault.path)
+        .env("OBX_HOOK", name)
+        .env("OBX_NOTE_PATH", note)
+        .env("OBX_VAULT", &vault.name)
+        .env("OBX_VAULT_PATH", &vault.path)
+        .stdout(Stdio::from(io::stderr()));
+    if let Some(previous) = previous {
+        hook.env("OBX_PREVIOUS_NOTE_PATH", previous);
+    }
+
+    let status = hook
+        .status()
+        .with_context(|| format!("Failed to run the `{name}` hook `{program}`"))?;
+    if !status.success() {
+        bail!("The `{name}` hook `{command}` failed with {status}");
+    }
+
+    Ok(())
+}
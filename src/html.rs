@@ -0,0 +1,269 @@
+use crate::{
+    embeds,
+    link_resolver::{relative_between, LinkResolver},
+    scan,
+    theme::Theme,
+};
+use anyhow::Context;
+use libobsidian::{
+    links::{parse_links, replace_ranges, Link, LinkKind},
+    split_frontmatter,
+};
+use pulldown_cmark::{html, CowStr, Event, Options, Parser, Tag, TagEnd};
+use ratatui::style::Color;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Renders notes to standalone HTML pages. Links between the exported notes
+/// become relative hrefs, assuming each page keeps its note's vault-relative
+/// path with an `.html` extension.
+pub struct HtmlExporter {
+    vault_path: PathBuf,
+    resolver: LinkResolver,
+    /// The notes being exported, the only ones links can point to
+    notes: Vec<PathBuf>,
+    stylesheet: String,
+}
+
+pub struct RenderedNote {
+    pub html: String,
+    /// Files the note links to or embeds, which need copying alongside it
+    pub attachments: Vec<PathBuf>,
+}
+
+impl HtmlExporter {
+    pub fn new(vault_path: &Path, notes: Vec<PathBuf>, theme: &Theme) -> anyhow::Result<Self> {
+        Ok(Self {
+            vault_path: vault_path.to_path_buf(),
+            resolver: LinkResolver::new(vault_path)?,
+            notes,
+            stylesheet: stylesheet(theme),
+        })
+    }
+
+    pub fn render(&self, note_path: &Path) -> anyhow::Result<RenderedNote> {
+        let contents = fs::read_to_string(note_path)
+            .with_context(|| format!("Could not read note {}", note_path.display()))?;
+        let body = embeds::expand(split_frontmatter(&contents).1, note_path, &self.resolver);
+        let (markdown, attachments) = self.rewrite_links(&body, note_path);
+
+        let title = note_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        let html = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n{}</style>\n</head>\n<body>\n<main>\n{}</main>\n</body>\n</html>\n",
+            escape_html(&title),
+            self.stylesheet,
+            markdown_to_html(&markdown)
+        );
+
+        Ok(RenderedNote { html, attachments })
+    }
+
+    /// Turn wikilinks into markdown links to the exported pages, or plain text
+    /// when their target isn't being exported, and collect linked attachments
+    fn rewrite_links(&self, body: &str, note_path: &Path) -> (String, Vec<PathBuf>) {
+        let source = output_path(&self.vault_path, note_path);
+        let mut attachments: Vec<PathBuf> = Vec::new();
+        let mut replacements = Vec::new();
+
+        for link in parse_links(body) {
+            if link.is_external() {
+                continue;
+            }
+
+            let label = link_label(&link);
+            let replacement = match self.resolver.resolve(&link, note_path) {
+                Some(target) if scan::is_markdown(&target) && self.notes.contains(&target) => {
+                    let href = relative_between(&source, &output_path(&self.vault_path, &target));
+                    markdown_link(&link, false, &href, heading_anchor(&link), &label)
+                }
+                Some(target) if !scan::is_markdown(&target) => {
+                    let relative = scan::relative_note_path(&target, &self.vault_path);
+                    let href = relative_between(&source, &relative);
+                    if !attachments.contains(&target) {
+                        attachments.push(target);
+                    }
+                    markdown_link(&link, link.embed, &href, None, &label)
+                }
+                _ => label,
+            };
+
+            replacements.push((link.range.clone(), replacement));
+        }
+
+        (replace_ranges(body, replacements), attachments)
+    }
+}
+
+/// Where the page for a note goes, relative to the export folder
+pub fn output_path(vault_path: &Path, note_path: &Path) -> String {
+    let relative = scan::relative_note_path(note_path, vault_path);
+    let stem = relative.strip_suffix(".md").unwrap_or(&relative);
+
+    format!("{stem}.html")
+}
+
+/// A link's display text, escaped for use as a markdown link label
+pub fn link_label(link: &Link) -> String {
+    link.display_text().replace('[', "\\[").replace(']', "\\]")
+}
+
+pub fn markdown_link(
+    link: &Link,
+    embed: bool,
+    href: &str,
+    anchor: Option<String>,
+    label: &str,
+) -> String {
+    Link {
+        kind: LinkKind::Markdown,
+        embed,
+        target: String::new(),
+        subpath: anchor,
+        label: Some(label.to_string()),
+        range: link.range.clone(),
+        line: link.line,
+    }
+    .with_target(href)
+}
+
+/// The id of the heading a link points into. Block references (`#^id`) have
+/// no counterpart in the exported page.
+fn heading_anchor(link: &Link) -> Option<String> {
+    link.subpath
+        .as_deref()
+        .filter(|subpath| !subpath.starts_with('^'))
+        .map(slugify)
+}
+
+/// A heading's text as an HTML id, e.g. `Next steps!` becomes `next-steps`
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+
+    for c in text.trim().chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if (c.is_whitespace() || c == '-') && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+
+    slug.trim_end_matches('-').to_string()
+}
+
+/// Render markdown to an HTML fragment, giving every heading an id so links
+/// to `#headings` work
+pub fn markdown_to_html(markdown: &str) -> String {
+    let options = Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_TABLES
+        | Options::ENABLE_FOOTNOTES;
+    let events: Vec<Event> = Parser::new_ext(markdown, options).collect();
+
+    let with_ids = events.iter().enumerate().map(|(idx, event)| match event {
+        Event::Start(Tag::Heading {
+            level,
+            id: None,
+            classes,
+            attrs,
+        }) => {
+            let text: String = events[idx + 1..]
+                .iter()
+                .take_while(|event| !matches!(event, Event::End(TagEnd::Heading(_))))
+                .filter_map(|event| match event {
+                    Event::Text(text) | Event::Code(text) => Some(text.as_ref()),
+                    _ => None,
+                })
+                .collect();
+
+            Event::Start(Tag::Heading {
+                level: *level,
+                id: Some(CowStr::from(slugify(&text))),
+                classes: classes.clone(),
+                attrs: attrs.clone(),
+            })
+        }
+        event => event.clone(),
+    });
+
+    let mut output = String::new();
+    html::push_html(&mut output, with_ids);
+    output
+}
+
+fn stylesheet(theme: &Theme) -> String {
+    format!(
+        "body {{ background: {background}; color: {text}; font-family: -apple-system, BlinkMacSystemFont, \"Segoe UI\", sans-serif; line-height: 1.6; }}
+main {{ max-width: 46rem; margin: 2rem auto; padding: 0 1rem; }}
+h1, h2, h3, h4, h5, h6 {{ color: {heading}; line-height: 1.25; }}
+a {{ color: {link}; }}
+code, pre {{ font-family: ui-monospace, Menlo, monospace; font-size: 0.9em; }}
+pre {{ padding: 0.75rem; overflow-x: auto; border: 1px solid {tag}; border-radius: 4px; }}
+blockquote {{ margin-left: 0; padding-left: 1rem; border-left: 3px solid {accent}; }}
+table {{ border-collapse: collapse; }}
+th, td {{ padding: 0.25rem 0.75rem; border: 1px solid {tag}; }}
+img {{ max-width: 100%; }}
+",
+        background = css_color(theme.background),
+        text = css_color(theme.note),
+        heading = css_color(theme.heading),
+        link = css_color(theme.link),
+        accent = css_color(theme.accent),
+        tag = css_color(theme.tag),
+    )
+}
+
+fn css_color(color: Color) -> String {
+    match color {
+        Color::Rgb(r, g, b) => format!("#{r:02x}{g:02x}{b:02x}"),
+        // The terminal theme's palette colours, as their CSS namesakes
+        Color::Red | Color::LightRed => "red".to_string(),
+        Color::Green | Color::LightGreen => "green".to_string(),
+        Color::Yellow | Color::LightYellow => "goldenrod".to_string(),
+        Color::Blue | Color::LightBlue => "royalblue".to_string(),
+        Color::Magenta | Color::LightMagenta => "darkmagenta".to_string(),
+        Color::Cyan | Color::LightCyan => "darkcyan".to_string(),
+        _ => "inherit".to_string(),
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case("Simple note", "simple-note" ; "spaces")]
+    #[test_case("Next steps!", "next-steps" ; "punctuation")]
+    #[test_case("A - B", "a-b" ; "dashes collapse")]
+    fn slugify_makes_ids(text: &str, expected: &str) {
+        assert_eq!(slugify(text), expected);
+    }
+
+    #[test]
+    fn headings_get_ids() {
+        assert_eq!(
+            markdown_to_html("## Some `code` heading"),
+            "<h2 id=\"some-code-heading\">Some <code>code</code> heading</h2>\n"
+        );
+    }
+
+    #[test]
+    fn output_path_swaps_extension() {
+        assert_eq!(
+            output_path(Path::new("/vault"), Path::new("/vault/a/b.md")),
+            "a/b.html"
+        );
+    }
+}
@@ -0,0 +1,480 @@
+use super::{dedupe_name, sanitize_file_name, Import, ImportedAttachment, ImportedNote, Importer};
+use crate::attachments::attachment_link;
+use anyhow::{bail, Context};
+use base64::Engine;
+use chrono::NaiveDateTime;
+use md5::{Digest, Md5};
+use quick_xml::{
+    escape::resolve_html5_entity,
+    events::{BytesStart, Event},
+    Reader,
+};
+use std::{collections::HashMap, fs, path::Path};
+
+/// Imports an Evernote `.enex` export, turning each note's ENML into
+/// markdown with its created date and tags as frontmatter
+pub struct EnexImporter;
+
+impl Importer for EnexImporter {
+    fn read(&self, source: &Path) -> anyhow::Result<Import> {
+        let xml = fs::read_to_string(source)
+            .with_context(|| format!("Could not read {}", source.display()))?;
+        let notes = parse_enex(&xml).with_context(|| {
+            format!("Could not parse {} as an Evernote export", source.display())
+        })?;
+
+        if notes.is_empty() {
+            bail!("No notes found in {}", source.display());
+        }
+
+        let mut import = Import::default();
+        let mut taken_names = Vec::new();
+
+        for note in notes {
+            let mut media: HashMap<String, String> = HashMap::new();
+            for resource in note.resources {
+                let hash = format!("{:x}", Md5::digest(&resource.data));
+                let name = dedupe_name(&resource.file_name(&hash), &mut taken_names);
+                media.insert(hash, name.clone());
+                import.attachments.push(ImportedAttachment {
+                    file_name: name,
+                    data: resource.data,
+                });
+            }
+
+            let body = enml_to_markdown(&note.content, &media)?;
+            import.notes.push(ImportedNote {
+                path: sanitize_file_name(&note.title),
+                contents: with_frontmatter(&body, note.created.as_deref(), &note.tags)?,
+            });
+        }
+
+        Ok(import)
+    }
+}
+
+#[derive(Debug, Default)]
+struct EnexNote {
+    title: String,
+    /// The note's ENML, an XHTML document rooted at `<en-note>`
+    content: String,
+    created: Option<String>,
+    tags: Vec<String>,
+    resources: Vec<Resource>,
+}
+
+#[derive(Debug, Default)]
+struct Resource {
+    data: Vec<u8>,
+    mime: String,
+    file_name: Option<String>,
+}
+
+impl Resource {
+    /// The resource's original file name, or one made from its hash when
+    /// Evernote didn't record it
+    fn file_name(&self, hash: &str) -> String {
+        if let Some(name) = self
+            .file_name
+            .as_deref()
+            .filter(|name| !name.trim().is_empty())
+        {
+            return sanitize_file_name(name);
+        }
+
+        let extension = match self.mime.rsplit('/').next().unwrap_or_default() {
+            "jpeg" => "jpg",
+            "svg+xml" => "svg",
+            "" => "bin",
+            other => other,
+        };
+        format!("{}.{extension}", &hash[..hash.len().min(12)])
+    }
+}
+
+fn parse_enex(xml: &str) -> anyhow::Result<Vec<EnexNote>> {
+    let mut reader = Reader::from_str(xml);
+    let mut notes = Vec::new();
+    let mut note: Option<EnexNote> = None;
+    let mut resource: Option<Resource> = None;
+    let mut path: Vec<String> = Vec::new();
+    let mut text = String::new();
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(start) => {
+                let name = local_name(&start);
+                match name.as_str() {
+                    "note" => note = Some(EnexNote::default()),
+                    "resource" => resource = Some(Resource::default()),
+                    _ => {}
+                }
+                path.push(name);
+                text.clear();
+            }
+            Event::Text(content) => text.push_str(&content.unescape_with(resolve_entity)?),
+            Event::CData(content) => text.push_str(&String::from_utf8_lossy(&content)),
+            Event::End(_) => {
+                let name = path.pop().unwrap_or_default();
+                let parent = path.last().map(String::as_str).unwrap_or_default();
+
+                match (parent, name.as_str()) {
+                    (_, "note") => notes.extend(note.take()),
+                    (_, "resource") => {
+                        if let (Some(note), Some(resource)) = (note.as_mut(), resource.take()) {
+                            note.resources.push(resource);
+                        }
+                    }
+                    ("note", field) => {
+                        if let Some(note) = note.as_mut() {
+                            match field {
+                                "title" => note.title = text.trim().to_string(),
+                                "content" => note.content = text.clone(),
+                                "created" => note.created = Some(text.trim().to_string()),
+                                "tag" => note.tags.push(text.trim().to_string()),
+                                _ => {}
+                            }
+                        }
+                    }
+                    ("resource", "data") => {
+                        if let Some(resource) = resource.as_mut() {
+                            let encoded: String =
+                                text.chars().filter(|c| !c.is_whitespace()).collect();
+                            resource.data = base64::engine::general_purpose::STANDARD
+                                .decode(encoded)
+                                .context("Invalid base64 in a note attachment")?;
+                        }
+                    }
+                    ("resource", "mime") => {
+                        if let Some(resource) = resource.as_mut() {
+                            resource.mime = text.trim().to_string();
+                        }
+                    }
+                    ("resource-attributes", "file-name") => {
+                        if let Some(resource) = resource.as_mut() {
+                            resource.file_name = Some(text.trim().to_string());
+                        }
+                    }
+                    _ => {}
+                }
+                text.clear();
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(notes)
+}
+
+fn with_frontmatter(body: &str, created: Option<&str>, tags: &[String]) -> anyhow::Result<String> {
+    let mut properties = serde_yaml::Mapping::new();
+
+    if let Some(created) = created {
+        let created = NaiveDateTime::parse_from_str(created, "%Y%m%dT%H%M%SZ")
+            .map(|created| created.format("%Y-%m-%dT%H:%M:%S").to_string())
+            .unwrap_or_else(|_| created.to_string());
+        properties.insert("created".into(), created.into());
+    }
+    if !tags.is_empty() {
+        properties.insert(
+            "tags".into(),
+            serde_yaml::Value::Sequence(tags.iter().map(|tag| tag.as_str().into()).collect()),
+        );
+    }
+
+    if properties.is_empty() {
+        return Ok(format!("{body}\n"));
+    }
+
+    let yaml = serde_yaml::to_string(&properties)?;
+    Ok(format!("---\n{yaml}---\n{body}\n"))
+}
+
+fn local_name(start: &BytesStart) -> String {
+    String::from_utf8_lossy(start.local_name().as_ref()).into_owned()
+}
+
+fn resolve_entity(entity: &str) -> Option<&'static str> {
+    match entity {
+        "nbsp" => Some(" "),
+        entity => resolve_html5_entity(entity),
+    }
+}
+
+/// Convert ENML, Evernote's XHTML dialect, to markdown. `media` maps the MD5
+/// hashes `<en-media>` tags refer to onto attachment file names.
+fn enml_to_markdown(enml: &str, media: &HashMap<String, String>) -> anyhow::Result<String> {
+    let mut reader = Reader::from_str(enml);
+    let mut writer = MarkdownWriter::default();
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(start) => writer.start(&start, media, false)?,
+            Event::Empty(start) => writer.start(&start, media, true)?,
+            Event::End(end) => {
+                writer.end(&String::from_utf8_lossy(end.local_name().as_ref()));
+            }
+            Event::Text(content) => writer.text(&content.unescape_with(resolve_entity)?),
+            Event::CData(content) => writer.text(&String::from_utf8_lossy(&content)),
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(writer.finish())
+}
+
+#[derive(Default)]
+struct MarkdownWriter {
+    output: String,
+    /// The next number for each open list, `None` for bulleted lists
+    lists: Vec<Option<usize>>,
+    links: Vec<String>,
+    in_pre: bool,
+}
+
+impl MarkdownWriter {
+    fn start(
+        &mut self,
+        start: &BytesStart,
+        media: &HashMap<String, String>,
+        empty: bool,
+    ) -> anyhow::Result<()> {
+        let name = local_name(start);
+        let attribute = |key: &str| -> anyhow::Result<Option<String>> {
+            Ok(start
+                .try_get_attribute(key)?
+                .map(|attr| attr.unescape_value())
+                .transpose()?
+                .map(|value| value.into_owned()))
+        };
+
+        match name.as_str() {
+            "div" => self.line_break(),
+            "p" | "blockquote" => self.block_break(),
+            "br" => self.output.push('\n'),
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.block_break();
+                let level = name[1..].parse().unwrap_or(1);
+                self.output.push_str(&format!("{} ", "#".repeat(level)));
+            }
+            "b" | "strong" => self.output.push_str("**"),
+            "i" | "em" => self.output.push('*'),
+            "s" | "strike" | "del" => self.output.push_str("~~"),
+            "code" if !self.in_pre => self.output.push('`'),
+            "a" => {
+                self.links.push(attribute("href")?.unwrap_or_default());
+                self.output.push('[');
+            }
+            "ul" | "ol" => {
+                if self.lists.is_empty() {
+                    self.block_break();
+                }
+                self.lists.push((name == "ol").then_some(1));
+            }
+            "li" => {
+                self.line_break();
+                let indent = "  ".repeat(self.lists.len().saturating_sub(1));
+                let marker = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}. ", *number - 1)
+                    }
+                    _ => "- ".to_string(),
+                };
+                self.output.push_str(&indent);
+                self.output.push_str(&marker);
+            }
+            "en-todo" => {
+                let checked = attribute("checked")?.as_deref() == Some("true");
+                let prefix = if self.at_line_start() { "- " } else { "" };
+                let checkbox = if checked { "[x] " } else { "[ ] " };
+                self.output.push_str(prefix);
+                self.output.push_str(checkbox);
+            }
+            "en-media" => {
+                let name = attribute("hash")?.and_then(|hash| media.get(&hash));
+                if let Some(name) = name {
+                    self.output.push_str(&attachment_link(Path::new(name)));
+                }
+            }
+            "hr" => {
+                self.block_break();
+                self.output.push_str("---\n");
+            }
+            "pre" => {
+                self.block_break();
+                self.output.push_str("```\n");
+                self.in_pre = true;
+            }
+            "td" | "th" if !self.at_line_start() => self.output.push_str(" | "),
+            _ => {}
+        }
+
+        if empty && !matches!(name.as_str(), "br" | "en-todo" | "en-media" | "hr") {
+            self.end(&name);
+        }
+
+        Ok(())
+    }
+
+    fn end(&mut self, name: &str) {
+        match name {
+            "div" => self.line_break(),
+            "p" | "blockquote" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => self.block_break(),
+            "b" | "strong" => self.output.push_str("**"),
+            "i" | "em" => self.output.push('*'),
+            "s" | "strike" | "del" => self.output.push_str("~~"),
+            "code" if !self.in_pre => self.output.push('`'),
+            "a" => {
+                let href = self.links.pop().unwrap_or_default();
+                self.output.push_str(&format!("]({href})"));
+            }
+            "ul" | "ol" => {
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.block_break();
+                }
+            }
+            "pre" => {
+                self.line_break();
+                self.output.push_str("```\n");
+                self.in_pre = false;
+            }
+            "tr" => self.line_break(),
+            _ => {}
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if self.in_pre {
+            self.output.push_str(text);
+            return;
+        }
+
+        // Outside of <pre>, whitespace (including newlines in the source)
+        // collapses to single spaces like it would in a browser
+        let mut collapsed = String::new();
+        for (idx, word) in text.split_whitespace().enumerate() {
+            if idx > 0 {
+                collapsed.push(' ');
+            }
+            collapsed.push_str(word);
+        }
+        if text.starts_with(char::is_whitespace) && !self.at_line_start() {
+            collapsed.insert(0, ' ');
+        }
+        if text.ends_with(char::is_whitespace) && !collapsed.is_empty() {
+            collapsed.push(' ');
+        }
+
+        self.output.push_str(&collapsed);
+    }
+
+    fn at_line_start(&self) -> bool {
+        self.output.is_empty() || self.output.ends_with('\n')
+    }
+
+    fn line_break(&mut self) {
+        let trimmed = self.output.trim_end_matches(' ').len();
+        self.output.truncate(trimmed);
+        if !self.at_line_start() {
+            self.output.push('\n');
+        }
+    }
+
+    fn block_break(&mut self) {
+        self.line_break();
+        if !self.output.is_empty() && !self.output.ends_with("\n\n") {
+            self.output.push('\n');
+        }
+    }
+
+    fn finish(self) -> String {
+        let mut output = String::new();
+        let mut blank_lines = 0;
+
+        for line in self.output.trim().lines() {
+            if line.trim().is_empty() {
+                blank_lines += 1;
+                if blank_lines > 1 {
+                    continue;
+                }
+            } else {
+                blank_lines = 0;
+            }
+            output.push_str(line.trim_end());
+            output.push('\n');
+        }
+
+        output.trim_end().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    const ENEX: &str = indoc! {r#"
+        <?xml version="1.0" encoding="UTF-8"?>
+        <!DOCTYPE en-export SYSTEM "http://xml.evernote.com/pub/evernote-export3.dtd">
+        <en-export>
+          <note>
+            <title>Trip &amp; plans</title>
+            <content><![CDATA[<?xml version="1.0" encoding="UTF-8"?>
+        <!DOCTYPE en-note SYSTEM "http://xml.evernote.com/pub/enml2.dtd">
+        <en-note><div><b>Packing</b> list&nbsp;for <a href="https://example.com">the trip</a></div><div><br/></div><ul><li>Passport</li><li>Charger</li></ul><div><en-todo checked="true"/>Book hotel</div><div><en-media hash="5d41402abc4b2a76b9719d911017c592" type="image/png"/></div></en-note>]]></content>
+            <created>20240301T091500Z</created>
+            <tag>travel</tag>
+            <resource>
+              <data encoding="base64">aGVs
+        bG8=</data>
+              <mime>image/png</mime>
+              <resource-attributes><file-name>map.png</file-name></resource-attributes>
+            </resource>
+          </note>
+        </en-export>
+    "#};
+
+    #[test]
+    fn parses_notes_and_resources() {
+        let notes = parse_enex(ENEX).unwrap();
+
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].title, "Trip & plans");
+        assert_eq!(notes[0].tags, vec!["travel"]);
+        assert_eq!(notes[0].resources[0].data, b"hello");
+        assert_eq!(notes[0].resources[0].file_name.as_deref(), Some("map.png"));
+    }
+
+    #[test]
+    fn converts_enml_to_markdown() {
+        let notes = parse_enex(ENEX).unwrap();
+        let media = HashMap::from([(
+            "5d41402abc4b2a76b9719d911017c592".to_string(),
+            "map.png".to_string(),
+        )]);
+
+        assert_eq!(
+            enml_to_markdown(&notes[0].content, &media).unwrap(),
+            indoc! {"
+                **Packing** list for [the trip](https://example.com)
+
+                - Passport
+                - Charger
+
+                - [x] Book hotel
+                ![[map.png]]"}
+        );
+    }
+
+    #[test]
+    fn adds_frontmatter() {
+        assert_eq!(
+            with_frontmatter("Body", Some("20240301T091500Z"), &["travel".to_string()]).unwrap(),
+            "---\ncreated: 2024-03-01T09:15:00\ntags:\n- travel\n---\nBody\n"
+        );
+    }
+}
@@ -0,0 +1,195 @@
+use crate::{attachments::unique_destination, util::is_relative_inside};
+use anyhow::{bail, Context};
+use libobsidian::links::{parse_links, replace_ranges};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+pub mod enex;
+pub mod notion;
+
+/// Converts another app's export into notes and attachments
+pub trait Importer {
+    fn read(&self, source: &Path) -> anyhow::Result<Import>;
+}
+
+#[derive(Debug, Default)]
+pub struct Import {
+    pub notes: Vec<ImportedNote>,
+    pub attachments: Vec<ImportedAttachment>,
+}
+
+#[derive(Debug)]
+pub struct ImportedNote {
+    /// Where the note goes relative to the import destination, without the
+    /// `.md` extension
+    pub path: String,
+    /// Markdown contents, linking to attachments by file name with wikilinks
+    pub contents: String,
+}
+
+#[derive(Debug)]
+pub struct ImportedAttachment {
+    pub file_name: String,
+    pub data: Vec<u8>,
+}
+
+/// What was written to the vault by [`write_import`]
+pub struct ImportSummary {
+    pub notes: usize,
+    pub attachments: usize,
+}
+
+/// Write imported notes under `dest` and attachments into
+/// `attachment_folder`, both vault-relative. Nothing is overwritten: clashing
+/// files get a numbered name and links to renamed attachments are updated.
+pub fn write_import(
+    import: Import,
+    vault_path: &Path,
+    dest: &Path,
+    attachment_folder: &Path,
+) -> anyhow::Result<ImportSummary> {
+    let attachment_dir = vault_path.join(attachment_folder);
+    let mut renamed: HashMap<String, String> = HashMap::new();
+
+    if !import.attachments.is_empty() {
+        fs::create_dir_all(&attachment_dir)
+            .with_context(|| format!("Could not create directory {}", attachment_dir.display()))?;
+    }
+
+    for attachment in &import.attachments {
+        if !is_relative_inside(&attachment.file_name) {
+            bail!(
+                "Refusing to import `{}`, outside the attachment folder",
+                attachment.file_name
+            );
+        }
+        let destination = unique_destination(&attachment_dir, Path::new(&attachment.file_name))?;
+        fs::write(&destination, &attachment.data)
+            .with_context(|| format!("Could not write {}", destination.display()))?;
+
+        let written_name = file_name(&destination);
+        if written_name != attachment.file_name {
+            renamed.insert(attachment.file_name.clone(), written_name);
+        }
+    }
+
+    for note in &import.notes {
+        if !is_relative_inside(&note.path) {
+            bail!(
+                "Refusing to import `{}`, outside the destination",
+                note.path
+            );
+        }
+        let note_path = vault_path.join(dest).join(format!("{}.md", note.path));
+        let folder = note_path
+            .parent()
+            .expect("note_path should have a parent")
+            .to_path_buf();
+        fs::create_dir_all(&folder)
+            .with_context(|| format!("Could not create directory {}", folder.display()))?;
+
+        let note_path = unique_destination(&folder, &note_path)?;
+        fs::write(&note_path, rename_links(&note.contents, &renamed))
+            .with_context(|| format!("Could not write note {}", note_path.display()))?;
+    }
+
+    Ok(ImportSummary {
+        notes: import.notes.len(),
+        attachments: import.attachments.len(),
+    })
+}
+
+fn rename_links(contents: &str, renamed: &HashMap<String, String>) -> String {
+    if renamed.is_empty() {
+        return contents.to_string();
+    }
+
+    let replacements = parse_links(contents)
+        .into_iter()
+        .filter_map(|link| {
+            let new_name = renamed.get(&link.target)?;
+            Some((link.range.clone(), link.with_target(new_name)))
+        })
+        .collect();
+
+    replace_ranges(contents, replacements)
+}
+
+/// Make a page title safe to use as a file name, replacing the characters
+/// Obsidian doesn't allow in note names
+pub fn sanitize_file_name(title: &str) -> String {
+    let cleaned: String = title
+        .trim()
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' | '#' | '^' | '[' | ']' => '-',
+            c if c.is_control() => ' ',
+            c => c,
+        })
+        .collect();
+
+    match cleaned.trim_matches('.').trim() {
+        "" => "Untitled".to_string(),
+        name => name.to_string(),
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Pick names for attachments that are unique within one import, so links
+/// to two different `image.png` files stay distinct
+pub(crate) fn dedupe_name(name: &str, taken: &mut Vec<String>) -> String {
+    let path = PathBuf::from(name);
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+
+    let unique = std::iter::once(name.to_string())
+        .chain((1..).map(|n| format!("{stem} {n}{extension}")))
+        .find(|candidate| !taken.contains(candidate))
+        .expect("should find a free name");
+
+    taken.push(unique.clone());
+    unique
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case("Meeting notes", "Meeting notes" ; "plain")]
+    #[test_case("Q1/Q2: plans?", "Q1-Q2- plans-" ; "reserved characters")]
+    #[test_case("  ", "Untitled" ; "blank")]
+    fn sanitize_file_name_cleans_titles(title: &str, expected: &str) {
+        assert_eq!(sanitize_file_name(title), expected);
+    }
+
+    #[test]
+    fn dedupe_name_numbers_clashes() {
+        let mut taken = Vec::new();
+        assert_eq!(dedupe_name("image.png", &mut taken), "image.png");
+        assert_eq!(dedupe_name("image.png", &mut taken), "image 1.png");
+    }
+
+    #[test]
+    fn rename_links_points_at_written_names() {
+        let renamed = HashMap::from([("a.png".to_string(), "a 1.png".to_string())]);
+        assert_eq!(
+            rename_links("![[a.png]] [[b.png]]", &renamed),
+            "![[a 1.png]] [[b.png]]"
+        );
+    }
+}
@@ -0,0 +1,226 @@
+use super::{dedupe_name, Import, ImportedAttachment, ImportedNote, Importer};
+use crate::{attachments::attachment_link, link_resolver::normalize};
+use anyhow::{bail, Context};
+use libobsidian::links::{parse_links, replace_ranges, LinkKind};
+use regex::Regex;
+use std::{
+    collections::HashMap,
+    fs,
+    io::{Cursor, Read, Seek},
+    path::Path,
+    sync::OnceLock,
+};
+use walkdir::WalkDir;
+
+/// Imports a Notion "Markdown & CSV" export, either the downloaded `.zip` or
+/// an unzipped folder. Database CSVs are skipped.
+pub struct NotionImporter;
+
+impl Importer for NotionImporter {
+    fn read(&self, source: &Path) -> anyhow::Result<Import> {
+        let files = if source.is_dir() {
+            read_folder(source)?
+        } else {
+            let file = fs::File::open(source)
+                .with_context(|| format!("Could not open {}", source.display()))?;
+            let mut files = Vec::new();
+            read_zip(file, &mut files)
+                .with_context(|| format!("Could not read {} as a zip file", source.display()))?;
+            files
+        };
+
+        convert(files)
+    }
+}
+
+/// Every file in the export as its `/`-separated path and contents
+type ExportFiles = Vec<(String, Vec<u8>)>;
+
+fn read_folder(folder: &Path) -> anyhow::Result<ExportFiles> {
+    let mut files = Vec::new();
+
+    for entry in WalkDir::new(folder) {
+        let entry = entry?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+
+        let relative = entry
+            .path()
+            .strip_prefix(folder)?
+            .to_string_lossy()
+            .replace(std::path::MAIN_SEPARATOR_STR, "/");
+        files.push((relative, fs::read(entry.path())?));
+    }
+
+    Ok(files)
+}
+
+/// Read every file in a zip, descending into the nested zips Notion uses for
+/// large exports
+fn read_zip<R: Read + Seek>(reader: R, files: &mut ExportFiles) -> anyhow::Result<()> {
+    let mut archive = zip::ZipArchive::new(reader)?;
+
+    for idx in 0..archive.len() {
+        let mut entry = archive.by_index(idx)?;
+        if entry.is_dir() {
+            continue;
+        }
+
+        // Entries like `../escaped.md` would be written outside the vault
+        let Some(name) = entry.enclosed_name() else {
+            continue;
+        };
+        let name = name
+            .to_string_lossy()
+            .replace(std::path::MAIN_SEPARATOR_STR, "/");
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data)?;
+
+        if name.to_lowercase().ends_with(".zip") {
+            read_zip(Cursor::new(data), files)?;
+        } else {
+            files.push((name, data));
+        }
+    }
+
+    Ok(())
+}
+
+fn convert(files: ExportFiles) -> anyhow::Result<Import> {
+    let (pages, others): (ExportFiles, ExportFiles) = files
+        .into_iter()
+        .partition(|(path, _)| path.to_lowercase().ends_with(".md"));
+
+    if pages.is_empty() {
+        bail!("No markdown pages found, export from Notion as \"Markdown & CSV\"");
+    }
+
+    let page_names: HashMap<&str, String> = pages
+        .iter()
+        .map(|(path, _)| (path.as_str(), note_name(&clean_path(path))))
+        .collect();
+    let others: HashMap<&str, &Vec<u8>> = others
+        .iter()
+        .filter(|(path, _)| !path.to_lowercase().ends_with(".csv"))
+        .map(|(path, data)| (path.as_str(), data))
+        .collect();
+
+    let mut import = Import::default();
+    let mut attachment_names: HashMap<String, String> = HashMap::new();
+    let mut taken_names = Vec::new();
+
+    for (path, data) in &pages {
+        let contents = String::from_utf8_lossy(data);
+        let page_dir = path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("");
+
+        let mut replacements = Vec::new();
+        for link in parse_links(&contents) {
+            if link.kind != LinkKind::Markdown || link.is_external() {
+                continue;
+            }
+            let Some(linked) = normalize(page_dir, &link.target) else {
+                continue;
+            };
+
+            let replacement = if let Some(name) = page_names.get(linked.as_str()) {
+                match link.label.as_deref() {
+                    Some(label) if !label.is_empty() && label != name => {
+                        format!("[[{name}|{label}]]")
+                    }
+                    _ => format!("[[{name}]]"),
+                }
+            } else if let Some(data) = others.get(linked.as_str()) {
+                let file_name = attachment_names.entry(linked.clone()).or_insert_with(|| {
+                    let original = linked.rsplit('/').next().unwrap_or(&linked);
+                    let name = dedupe_name(original, &mut taken_names);
+                    import.attachments.push(ImportedAttachment {
+                        file_name: name.clone(),
+                        data: data.to_vec(),
+                    });
+                    name
+                });
+                attachment_link(Path::new(file_name.as_str()))
+            } else {
+                continue;
+            };
+
+            replacements.push((link.range.clone(), replacement));
+        }
+
+        let clean = clean_path(path);
+        import.notes.push(ImportedNote {
+            path: clean.strip_suffix(".md").unwrap_or(&clean).to_string(),
+            contents: replace_ranges(&contents, replacements),
+        });
+    }
+
+    Ok(import)
+}
+
+/// Drop the 32 character ids Notion appends to every page and folder name
+fn clean_path(path: &str) -> String {
+    static PAGE_ID: OnceLock<Regex> = OnceLock::new();
+    let page_id = PAGE_ID.get_or_init(|| {
+        Regex::new(r" [0-9a-f]{32}(\.md)?$").expect("page id regex should compile")
+    });
+
+    path.split('/')
+        .map(|part| page_id.replace(part, "$1").into_owned())
+        .collect::<Vec<String>>()
+        .join("/")
+}
+
+fn note_name(clean_path: &str) -> String {
+    let file_name = clean_path.rsplit('/').next().unwrap_or(clean_path);
+    file_name
+        .strip_suffix(".md")
+        .unwrap_or(file_name)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case(
+        "Projects 0123456789abcdef0123456789abcdef/Launch 0123456789abcdef0123456789abcdef.md",
+        "Projects/Launch.md" ;
+        "page and folder ids"
+    )]
+    #[test_case("Notes/image.png", "Notes/image.png" ; "no ids")]
+    fn clean_path_strips_ids(path: &str, expected: &str) {
+        assert_eq!(clean_path(path), expected);
+    }
+
+    #[test]
+    fn converts_links_to_pages_and_attachments() {
+        let id = "0123456789abcdef0123456789abcdef";
+        let files = vec![
+            (
+                format!("Home {id}.md"),
+                format!(
+                    "# Home\n\nSee [Launch plan](Home%20{id}/Launch%20{id}.md) and ![](Home%20{id}/chart.png)\n"
+                )
+                .into_bytes(),
+            ),
+            (
+                format!("Home {id}/Launch {id}.md"),
+                b"# Launch\n\nBack to [Home](../Home%20abc.md)".to_vec(),
+            ),
+            (format!("Home {id}/chart.png"), b"png".to_vec()),
+        ];
+
+        let import = convert(files).unwrap();
+
+        assert_eq!(import.notes[0].path, "Home");
+        assert_eq!(
+            import.notes[0].contents,
+            "# Home\n\nSee [[Launch|Launch plan]] and ![[chart.png]]\n"
+        );
+        assert_eq!(import.notes[1].path, "Home/Launch");
+        assert_eq!(import.attachments.len(), 1);
+        assert_eq!(import.attachments[0].file_name, "chart.png");
+    }
+}
@@ -0,0 +1,670 @@
+//! A full-text and link index of a vault's notes, kept in the vault's state
+//! directory so searching a large vault doesn't mean reading every note
+
+use crate::{
+    matching::{self, MatchSettings},
+    note_text::NoteText,
+    scan,
+    state_file::{self, file_stamp},
+};
+use anyhow::Context;
+use libobsidian::links::parse_links;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Bumped whenever the format changes, older indexes are rebuilt
+const INDEX_VERSION: u32 = 3;
+const INDEX_FILE: &str = "index.json";
+/// Words in a note's name count this many times over words in its body
+const TITLE_WEIGHT: u32 = 3;
+
+// BM25 tuning, the usual defaults
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Index {
+    version: u32,
+    /// Whether terms were indexed without diacritics, rebuilding the index
+    /// when the config changes
+    #[serde(default)]
+    ignore_diacritics: bool,
+    /// Every indexed note, by vault-relative path
+    notes: BTreeMap<String, IndexedNote>,
+    /// Each term, with how often it appears in each note containing it
+    terms: BTreeMap<String, BTreeMap<String, u32>>,
+    /// The targets of each note's links to other notes and files, as written
+    links: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct IndexedNote {
+    /// The note's modification time when it was indexed, in milliseconds
+    /// since the epoch
+    modified: u128,
+    /// The note's size in bytes, which catches changes made within the same
+    /// millisecond
+    size: u64,
+    /// How many terms the note has, so matches in long notes weigh less
+    length: u32,
+}
+
+/// A note matching a search, best matches have the highest score
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hit {
+    pub path: String,
+    pub score: f64,
+}
+
+/// What [`Index::update_paths`] did with a changed path
+#[derive(Debug, Clone, PartialEq)]
+pub enum Update {
+    Indexed(String),
+    Removed(String),
+}
+
+/// Notes that differ from what's indexed
+#[derive(Debug, Default)]
+pub struct Changes {
+    /// Notes that are new or modified since they were indexed
+    pub changed: Vec<PathBuf>,
+    /// Vault-relative paths of indexed notes that are gone
+    pub removed: Vec<String>,
+}
+
+impl Changes {
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty() && self.removed.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.changed.len() + self.removed.len()
+    }
+}
+
+/// Where the index for the vault at `vault_path` is kept
+pub fn index_path(vault_path: &Path) -> PathBuf {
+    state_file::path(vault_path, INDEX_FILE)
+}
+
+impl Index {
+    /// The saved index, or `None` when there isn't one or it was written by
+    /// an older obx
+    pub fn load(vault_path: &Path) -> anyhow::Result<Option<Self>> {
+        let path = index_path(vault_path);
+        let Some(index) = state_file::load::<Self>(&path).with_context(|| {
+            format!("Index {} is damaged, run `obx index clear`", path.display())
+        })?
+        else {
+            return Ok(None);
+        };
+
+        Ok((index.version == INDEX_VERSION
+            && index.ignore_diacritics == MatchSettings::read().ignore_diacritics)
+            .then_some(index))
+    }
+
+    /// The saved index brought up to date, saving it again if anything
+    /// changed, or a fresh one kept in memory when none has been built
+    pub fn current(vault_path: &Path) -> anyhow::Result<Self> {
+        match Self::load(vault_path)? {
+            Some(mut index) => {
+                if index.refresh(vault_path)? {
+                    index.save(vault_path)?;
+                }
+                Ok(index)
+            }
+            None => Self::build(vault_path),
+        }
+    }
+
+    /// Index every note in the vault
+    pub fn build(vault_path: &Path) -> anyhow::Result<Self> {
+        let mut index = Self {
+            version: INDEX_VERSION,
+            ignore_diacritics: MatchSettings::read().ignore_diacritics,
+            ..Default::default()
+        };
+        for note_path in scan::note_paths(vault_path)? {
+            index.add_note(vault_path, &note_path)?;
+        }
+
+        Ok(index)
+    }
+
+    /// How terms are folded, the way they were when the index was built
+    fn matching(&self) -> MatchSettings {
+        MatchSettings {
+            ignore_diacritics: self.ignore_diacritics,
+            ..Default::default()
+        }
+    }
+
+    pub fn save(&self, vault_path: &Path) -> anyhow::Result<()> {
+        state_file::save(&index_path(vault_path), self, "index")
+    }
+
+    /// Delete the saved index, returning whether there was one
+    pub fn clear(vault_path: &Path) -> anyhow::Result<bool> {
+        let path = index_path(vault_path);
+        if !path.exists() {
+            return Ok(false);
+        }
+
+        fs::remove_file(&path)
+            .with_context(|| format!("Could not remove index {}", path.display()))?;
+        Ok(true)
+    }
+
+    pub fn note_count(&self) -> usize {
+        self.notes.len()
+    }
+
+    pub fn term_count(&self) -> usize {
+        self.terms.len()
+    }
+
+    pub fn link_count(&self) -> usize {
+        self.links.values().map(Vec::len).sum()
+    }
+
+    /// The targets of the links in a note, by its vault-relative path
+    pub fn links_from(&self, relative: &str) -> &[String] {
+        self.links.get(relative).map_or(&[], Vec::as_slice)
+    }
+
+    /// The notes added, modified or removed since they were indexed
+    pub fn changes(&self, vault_path: &Path) -> anyhow::Result<Changes> {
+        let mut changes = Changes::default();
+        let mut seen = HashSet::new();
+
+        for note_path in scan::note_paths(vault_path)? {
+            let relative = scan::relative_note_path(&note_path, vault_path);
+            if !self.is_up_to_date(&relative, &note_path) {
+                changes.changed.push(note_path);
+            }
+            seen.insert(relative);
+        }
+
+        changes.removed = self
+            .notes
+            .keys()
+            .filter(|path| !seen.contains(*path))
+            .cloned()
+            .collect();
+
+        Ok(changes)
+    }
+
+    fn is_up_to_date(&self, relative: &str, note_path: &Path) -> bool {
+        self.notes
+            .get(relative)
+            .is_some_and(|note| Some((note.modified, note.size)) == file_stamp(note_path))
+    }
+
+    /// Re-index whatever changed on disk, returning whether anything did
+    pub fn refresh(&mut self, vault_path: &Path) -> anyhow::Result<bool> {
+        let changes = self.changes(vault_path)?;
+        for relative in &changes.removed {
+            self.remove_note(relative);
+        }
+        for note_path in &changes.changed {
+            self.add_note(vault_path, note_path)?;
+        }
+
+        Ok(!changes.is_empty())
+    }
+
+    /// How many other notes link to each note, by vault-relative path. Link
+    /// targets are matched to notes by path, or else by name the way
+    /// wikilinks resolve, preferring the shortest path.
+    pub fn in_degrees(&self) -> HashMap<&str, usize> {
+        let mut by_path: HashMap<String, &str> = HashMap::new();
+        let mut by_name: HashMap<String, &str> = HashMap::new();
+        let matching = self.matching();
+        for relative in self.notes.keys() {
+            let without_ext = matching.fold(relative.strip_suffix(".md").unwrap_or(relative));
+            let name = without_ext
+                .rsplit('/')
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            by_name
+                .entry(name)
+                .and_modify(|existing| {
+                    if relative.len() < existing.len() {
+                        *existing = relative;
+                    }
+                })
+                .or_insert(relative);
+            by_path.insert(without_ext, relative);
+        }
+
+        let mut degrees: HashMap<&str, usize> = HashMap::new();
+        for (source, targets) in &self.links {
+            let linked: HashSet<&str> = targets
+                .iter()
+                .filter_map(|target| {
+                    let target = matching.fold(target.strip_suffix(".md").unwrap_or(target));
+                    let target = target.trim_start_matches("./");
+                    by_path.get(target).copied().or_else(|| {
+                        let name = target.rsplit('/').next().unwrap_or_default();
+                        by_name.get(name).copied()
+                    })
+                })
+                .filter(|target| target != source)
+                .collect();
+            for target in linked {
+                *degrees.entry(target).or_default() += 1;
+            }
+        }
+
+        degrees
+    }
+
+    /// Bring the index up to date for paths reported as changed, such as by
+    /// a file watcher. Modified notes are indexed again, and paths that are
+    /// gone are removed along with any notes beneath them, in case a folder
+    /// went.
+    pub fn update_paths(
+        &mut self,
+        vault_path: &Path,
+        ignored: &scan::Ignored,
+        paths: &[PathBuf],
+    ) -> anyhow::Result<Vec<Update>> {
+        let mut updates = Vec::new();
+
+        for path in paths {
+            let relative = scan::relative_note_path(path, vault_path);
+            if scan::is_vault_note(ignored, vault_path, path) {
+                // Reading a note is reported as a change too
+                if self.is_up_to_date(&relative, path) {
+                    continue;
+                }
+                self.add_note(vault_path, path)?;
+                updates.push(Update::Indexed(relative));
+            } else if path.is_file() {
+                // A note that's ignored now, or grew past the scan limits
+                if self.notes.contains_key(&relative) {
+                    self.remove_note(&relative);
+                    updates.push(Update::Removed(relative));
+                }
+            } else if !path.exists() {
+                let folder = format!("{relative}/");
+                let gone: Vec<String> = self
+                    .notes
+                    .keys()
+                    .filter(|note| **note == relative || note.starts_with(&folder))
+                    .cloned()
+                    .collect();
+                for note in gone {
+                    self.remove_note(&note);
+                    updates.push(Update::Removed(note));
+                }
+            }
+        }
+
+        Ok(updates)
+    }
+
+    /// Index a note, replacing what was indexed for it before
+    pub fn add_note(&mut self, vault_path: &Path, note_path: &Path) -> anyhow::Result<()> {
+        let relative = scan::relative_note_path(note_path, vault_path);
+        self.remove_note(&relative);
+
+        let Ok(contents) = NoteText::read(note_path) else {
+            // Unreadable notes are left out, like binary files named `.md`
+            return Ok(());
+        };
+
+        let stamp = file_stamp(note_path).unwrap_or_default();
+        let mut counts: HashMap<String, u32> = HashMap::new();
+        let title = note_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let matching = self.matching();
+        for term in tokenize(&title, &matching) {
+            *counts.entry(term).or_default() += TITLE_WEIGHT;
+        }
+        for line in contents.lines() {
+            for term in tokenize(line, &matching) {
+                *counts.entry(term).or_default() += 1;
+            }
+        }
+
+        self.notes.insert(
+            relative.clone(),
+            IndexedNote {
+                modified: stamp.0,
+                size: stamp.1,
+                length: counts.values().sum(),
+            },
+        );
+        let links: Vec<String> = parse_links(contents.as_str())
+            .into_iter()
+            .filter(|link| !link.is_external() && !link.target.is_empty())
+            .map(|link| link.target)
+            .collect();
+        if !links.is_empty() {
+            self.links.insert(relative.clone(), links);
+        }
+        for (term, count) in counts {
+            self.terms
+                .entry(term)
+                .or_default()
+                .insert(relative.clone(), count);
+        }
+
+        Ok(())
+    }
+
+    /// Drop a note, by its vault-relative path, from the index
+    pub fn remove_note(&mut self, relative: &str) {
+        if self.notes.remove(relative).is_none() {
+            return;
+        }
+        self.links.remove(relative);
+
+        self.terms.retain(|_, notes| {
+            notes.remove(relative);
+            !notes.is_empty()
+        });
+    }
+
+    /// How alike each other note's words are to those of the note at
+    /// `relative`, as the cosine of their TF-IDF weights, most alike first.
+    /// Words in every note carry no weight, and notes with nothing in
+    /// common are left out.
+    pub fn similar(&self, relative: &str) -> Vec<Hit> {
+        if !self.notes.contains_key(relative) {
+            return Vec::new();
+        }
+
+        let note_count = self.notes.len() as f64;
+        let mut own_norm = 0.0;
+        let mut norms: HashMap<&str, f64> = HashMap::new();
+        let mut dots: HashMap<&str, f64> = HashMap::new();
+        for notes in self.terms.values() {
+            let idf = (note_count / notes.len() as f64).ln();
+            if idf <= 0.0 {
+                continue;
+            }
+            let weight = |count: u32| (1.0 + f64::from(count).ln()) * idf;
+
+            let own = notes.get(relative).map(|count| weight(*count));
+            for (path, count) in notes {
+                let other = weight(*count);
+                *norms.entry(path).or_default() += other * other;
+                if let (Some(own), false) = (own, path == relative) {
+                    *dots.entry(path).or_default() += own * other;
+                }
+            }
+            own_norm += own.map_or(0.0, |own| own * own);
+        }
+
+        let mut hits: Vec<Hit> = dots
+            .into_iter()
+            .map(|(path, dot)| Hit {
+                path: path.to_string(),
+                score: dot / (own_norm.sqrt() * norms[path].sqrt()),
+            })
+            .collect();
+        hits.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.path.cmp(&b.path))
+        });
+
+        hits
+    }
+
+    /// The notes containing every word of `query`, best first. Each word also
+    /// matches longer words it's the start of, so `proj` finds `project`.
+    pub fn search(&self, query: &str) -> Vec<Hit> {
+        let words = tokenize(query, &self.matching());
+        if words.is_empty() || self.notes.is_empty() {
+            return Vec::new();
+        }
+
+        let note_count = self.notes.len() as f64;
+        let average_length = self
+            .notes
+            .values()
+            .map(|note| f64::from(note.length))
+            .sum::<f64>()
+            / note_count;
+
+        let mut scores: Option<HashMap<&str, f64>> = None;
+        for word in &words {
+            let mut word_scores: HashMap<&str, f64> = HashMap::new();
+
+            for (_, notes) in self
+                .terms
+                .range(word.clone()..)
+                .take_while(|(term, _)| term.starts_with(word.as_str()))
+            {
+                let matching = notes.len() as f64;
+                let idf = (1.0 + (note_count - matching + 0.5) / (matching + 0.5)).ln();
+
+                for (path, count) in notes {
+                    let length = self.notes.get(path).map_or(0, |note| note.length);
+                    let tf = f64::from(*count);
+                    let norm = K1 * (1.0 - B + B * f64::from(length) / average_length);
+                    *word_scores.entry(path.as_str()).or_default() +=
+                        idf * tf * (K1 + 1.0) / (tf + norm);
+                }
+            }
+
+            scores = Some(match scores {
+                None => word_scores,
+                Some(previous) => previous
+                    .into_iter()
+                    .filter_map(|(path, score)| {
+                        word_scores.get(path).map(|extra| (path, score + extra))
+                    })
+                    .collect(),
+            });
+        }
+
+        let mut hits: Vec<Hit> = scores
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(path, score)| Hit {
+                path: path.to_string(),
+                score,
+            })
+            .collect();
+        hits.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.path.cmp(&b.path))
+        });
+
+        hits
+    }
+}
+
+/// Split text into lowercase words, treating anything but letters and digits
+/// as a separator. Words are folded like [`MatchSettings::fold`], so
+/// however accents are written they match.
+pub fn tokenize(text: &str, matching: &MatchSettings) -> Vec<String> {
+    words(text, matching)
+        .into_iter()
+        .map(|word| word.to_lowercase())
+        .collect()
+}
+
+/// The words in `text` split like [`tokenize`] splits them, keeping their
+/// case for case-sensitive matching
+pub fn words(text: &str, matching: &MatchSettings) -> Vec<String> {
+    // Combining accents aren't alphanumeric, so decomposed text is composed
+    // before it's split
+    matching::compose(text)
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| matching.normalize(word).into_owned())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::{prelude::*, TempDir};
+
+    fn vault(notes: &[(&str, &str)]) -> TempDir {
+        let dir = TempDir::new().unwrap();
+        for (path, contents) in notes {
+            dir.child(path).write_str(contents).unwrap();
+        }
+        dir
+    }
+
+    fn paths(hits: Vec<Hit>) -> Vec<String> {
+        hits.into_iter().map(|hit| hit.path).collect()
+    }
+
+    #[test]
+    fn tokenizes_words() {
+        assert_eq!(
+            tokenize(
+                "Hello, wörld! #tag [[Some-Link]]",
+                &MatchSettings::default()
+            ),
+            ["hello", "wörld", "tag", "some", "link"]
+        );
+        assert_eq!(
+            tokenize("Cafe\u{301} au lait", &MatchSettings::default()),
+            ["café", "au", "lait"]
+        );
+    }
+
+    #[test]
+    fn requires_every_word() {
+        let dir = vault(&[
+            ("a.md", "apples and pears"),
+            ("b.md", "apples only"),
+            ("c.md", "nothing here"),
+        ]);
+        let index = Index::build(dir.path()).unwrap();
+
+        assert_eq!(paths(index.search("apples")), ["b.md", "a.md"]);
+        assert_eq!(paths(index.search("pears apples")), ["a.md"]);
+        assert!(index.search("bananas").is_empty());
+    }
+
+    #[test]
+    fn matches_prefixes_and_ranks_titles_first() {
+        let dir = vault(&[
+            ("notes/meeting.md", "agenda"),
+            ("other.md", "we had a meeting about the agenda"),
+        ]);
+        let index = Index::build(dir.path()).unwrap();
+
+        assert_eq!(
+            paths(index.search("meet")),
+            ["notes/meeting.md", "other.md"]
+        );
+    }
+
+    #[test]
+    fn refreshes_changed_notes() {
+        let dir = vault(&[("a.md", "old words"), ("b.md", "kept")]);
+        let mut index = Index::build(dir.path()).unwrap();
+
+        fs::remove_file(dir.child("b.md").path()).unwrap();
+        dir.child("c.md").write_str("new words").unwrap();
+
+        assert_eq!(index.changes(dir.path()).unwrap().len(), 2);
+        assert!(index.refresh(dir.path()).unwrap());
+        assert_eq!(paths(index.search("words")), ["a.md", "c.md"]);
+        assert!(index.search("kept").is_empty());
+        assert!(!index.refresh(dir.path()).unwrap());
+    }
+
+    #[test]
+    fn finds_similar_notes() {
+        let dir = vault(&[
+            ("budget.md", "quarterly budget review for marketing"),
+            ("budget-draft.md", "draft budget review"),
+            ("marketing.md", "marketing plans and review"),
+            ("recipes.md", "soup and bread and review"),
+        ]);
+        let index = Index::build(dir.path()).unwrap();
+
+        assert_eq!(
+            paths(index.similar("budget.md")),
+            ["budget-draft.md", "marketing.md"]
+        );
+        assert!(index.similar("missing.md").is_empty());
+    }
+
+    #[test]
+    fn indexes_links() {
+        let dir = vault(&[(
+            "a.md",
+            "See [[b]] and [c](c.md) or [site](https://example.com)",
+        )]);
+        let index = Index::build(dir.path()).unwrap();
+
+        assert_eq!(index.links_from("a.md"), ["b", "c.md"]);
+        assert_eq!(index.link_count(), 2);
+    }
+
+    #[test]
+    fn counts_linking_notes() {
+        let dir = vault(&[
+            ("a.md", "[[b]] [[b]] [[a]]"),
+            ("c.md", "[b](folder/b.md) [[missing]]"),
+            ("folder/b.md", "[[A]]"),
+        ]);
+        let index = Index::build(dir.path()).unwrap();
+        let degrees = index.in_degrees();
+
+        assert_eq!(degrees.get("folder/b.md"), Some(&2));
+        assert_eq!(degrees.get("a.md"), Some(&1));
+        assert_eq!(degrees.get("c.md"), None);
+    }
+
+    #[test]
+    fn updates_changed_paths() {
+        let dir = vault(&[
+            ("a.md", "first"),
+            ("folder/b.md", "second"),
+            (".hidden/c.md", "third"),
+        ]);
+        let mut index = Index::build(dir.path()).unwrap();
+        let ignored = scan::Ignored::new(dir.path(), &[]);
+
+        dir.child("a.md").write_str("changed [[b]]").unwrap();
+        dir.child(".hidden/c.md").write_str("still hidden").unwrap();
+        fs::remove_dir_all(dir.child("folder").path()).unwrap();
+
+        let updates = index
+            .update_paths(
+                dir.path(),
+                &ignored,
+                &[
+                    dir.child("a.md").to_path_buf(),
+                    dir.child(".hidden/c.md").to_path_buf(),
+                    dir.child("folder").to_path_buf(),
+                ],
+            )
+            .unwrap();
+
+        assert_eq!(
+            updates,
+            [
+                Update::Indexed("a.md".to_string()),
+                Update::Removed("folder/b.md".to_string())
+            ]
+        );
+        assert_eq!(paths(index.search("changed")), ["a.md"]);
+        assert!(index.search("second").is_empty());
+        assert_eq!(index.links_from("a.md"), ["b"]);
+    }
+}
@@ -0,0 +1,637 @@
+//! The vault's links, resolved to the files they point at and saved in the
+//! vault's state directory, so commands asking what links where don't each
+//! parse every note. Only notes that changed since it was saved are parsed
+//! again, and links are only resolved again when files come or go. Canvases
+//! are kept alongside the notes, so the notes they show have backlinks too.
+
+use crate::{
+    ambiguity::AmbiguitySettings,
+    link_resolver::LinkResolver,
+    matching::MatchSettings,
+    scan,
+    state_file::{self, file_stamp},
+};
+use libobsidian::{
+    canvas::{self, Canvas},
+    links::{parse_links, Link, LinkKind},
+    ObsidianNote,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Bumped whenever the format changes, older graphs are rebuilt
+const GRAPH_VERSION: u32 = 4;
+const GRAPH_FILE: &str = "links.json";
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct LinkGraph {
+    version: u32,
+    /// Every file in the vault, vault-relative, which links resolve against
+    files: Vec<String>,
+    /// How names several files share were resolved, links are resolved
+    /// again when it changes
+    ambiguity: AmbiguitySettings,
+    /// Each note's links, by vault-relative path
+    notes: BTreeMap<String, NoteLinks>,
+    /// Each canvas's file cards and links, by vault-relative path
+    canvases: BTreeMap<String, NoteLinks>,
+    /// The notes and canvases linking to each file, by vault-relative path, leaving out
+    /// links from a note to itself
+    backlinks: BTreeMap<String, BTreeSet<String>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct NoteLinks {
+    /// Modification time in milliseconds since the epoch
+    modified: u128,
+    size: u64,
+    links: Vec<Edge>,
+    /// The note's `aliases`, which wikilinks can use instead of its name
+    aliases: Vec<String>,
+}
+
+/// A link from one note or canvas to a file, or to a heading within itself
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Edge {
+    pub kind: LinkKind,
+    pub embed: bool,
+    /// The linked file as written, empty for links within the same note
+    pub target: String,
+    /// The heading or `^block` the link points into
+    pub subpath: Option<String>,
+    /// The whole link as written, like `![[image.png]]`
+    pub text: String,
+    /// Counting from 1
+    pub line: usize,
+    /// The vault-relative file it resolves to, `None` when that's missing
+    pub resolved: Option<String>,
+}
+
+impl Edge {
+    fn from_link(link: Link, contents: &str) -> Self {
+        Self {
+            text: contents[link.range.clone()].to_string(),
+            kind: link.kind,
+            embed: link.embed,
+            target: link.target,
+            subpath: link.subpath,
+            line: link.line,
+            resolved: None,
+        }
+    }
+
+    /// The link as the resolver takes it
+    pub fn as_link(&self) -> Link {
+        Link {
+            kind: self.kind,
+            embed: self.embed,
+            target: self.target.clone(),
+            subpath: self.subpath.clone(),
+            label: None,
+            range: 0..0,
+            line: self.line,
+        }
+    }
+}
+
+/// Where the link graph for the vault at `vault_path` is kept
+pub fn graph_path(vault_path: &Path) -> PathBuf {
+    state_file::path(vault_path, GRAPH_FILE)
+}
+
+impl LinkGraph {
+    /// The vault's saved graph brought up to date, saving it again if
+    /// anything changed. A graph that can't be read is rebuilt.
+    pub fn current(vault_path: &Path) -> anyhow::Result<Self> {
+        let mut graph = Self::load(vault_path).unwrap_or_default();
+        if graph.refresh(vault_path)? {
+            graph.save(vault_path)?;
+        }
+
+        Ok(graph)
+    }
+
+    fn load(vault_path: &Path) -> Option<Self> {
+        let graph: Self = state_file::load(&graph_path(vault_path)).ok()??;
+
+        (graph.version == GRAPH_VERSION).then_some(graph)
+    }
+
+    pub fn save(&self, vault_path: &Path) -> anyhow::Result<()> {
+        state_file::save(&graph_path(vault_path), self, "link graph")
+    }
+
+    /// Parse notes that are new or changed and drop ones that are gone,
+    /// resolving links again where that could change what they point at.
+    /// Returns whether anything changed.
+    pub fn refresh(&mut self, vault_path: &Path) -> anyhow::Result<bool> {
+        let (files, note_paths) = scan::files_and_notes(vault_path)?;
+        let files: Vec<String> = files
+            .iter()
+            .map(|path| scan::relative_note_path(path, vault_path))
+            .collect();
+        let ambiguity = AmbiguitySettings::read();
+        let resolve_all =
+            self.version != GRAPH_VERSION || files != self.files || ambiguity != self.ambiguity;
+        self.version = GRAPH_VERSION;
+        self.files = files;
+        self.ambiguity = ambiguity;
+
+        let mut changed_notes = Vec::new();
+        let mut aliases_changed = false;
+        let mut notes = BTreeMap::new();
+        for note_path in note_paths {
+            let relative = scan::relative_note_path(&note_path, vault_path);
+            let (modified, size) = file_stamp(&note_path).unwrap_or_default();
+
+            let previous = self.notes.remove(&relative);
+            let note = match previous {
+                Some(note) if note.modified == modified && note.size == size => note,
+                previous => {
+                    changed_notes.push(relative.clone());
+                    let note = NoteLinks::read(&note_path, modified, size);
+                    let previous_aliases = previous.map(|note| note.aliases).unwrap_or_default();
+                    aliases_changed |= note.aliases != previous_aliases;
+                    note
+                }
+            };
+            notes.insert(relative, note);
+        }
+        let removed_notes = !self.notes.is_empty();
+        aliases_changed |= self.notes.values().any(|note| !note.aliases.is_empty());
+        self.notes = notes;
+
+        let mut canvases = BTreeMap::new();
+        for relative in self
+            .files
+            .iter()
+            .filter(|file| canvas::is_canvas_path(Path::new(file)))
+        {
+            let canvas_path = vault_path.join(relative);
+            let (modified, size) = file_stamp(&canvas_path).unwrap_or_default();
+            let canvas = match self.canvases.remove(relative) {
+                Some(canvas) if canvas.modified == modified && canvas.size == size => canvas,
+                _ => {
+                    changed_notes.push(relative.clone());
+                    NoteLinks::read_canvas(&canvas_path, modified, size)
+                }
+            };
+            canvases.insert(relative.clone(), canvas);
+        }
+        let removed_notes = removed_notes || !self.canvases.is_empty();
+        self.canvases = canvases;
+
+        if !resolve_all && changed_notes.is_empty() && !removed_notes {
+            return Ok(false);
+        }
+
+        let resolver = self.resolver(vault_path);
+        let to_resolve = if resolve_all || aliases_changed {
+            self.notes
+                .keys()
+                .chain(self.canvases.keys())
+                .cloned()
+                .collect()
+        } else {
+            changed_notes
+        };
+        for relative in to_resolve {
+            let source = vault_path.join(&relative);
+            let note = match self.notes.get_mut(&relative) {
+                Some(note) => Some(note),
+                None => self.canvases.get_mut(&relative),
+            };
+            if let Some(note) = note {
+                for edge in &mut note.links {
+                    edge.resolved = resolver
+                        .resolve(&edge.as_link(), &source)
+                        .map(|path| scan::relative_note_path(&path, vault_path));
+                }
+            }
+        }
+        self.link_back();
+
+        Ok(true)
+    }
+
+    /// Rebuild the reverse edges from the forward ones
+    fn link_back(&mut self) {
+        self.backlinks.clear();
+        for (source, note) in self.notes.iter().chain(&self.canvases) {
+            for target in note.links.iter().filter_map(|edge| edge.resolved.as_ref()) {
+                if target != source {
+                    self.backlinks
+                        .entry(target.clone())
+                        .or_default()
+                        .insert(source.clone());
+                }
+            }
+        }
+    }
+
+    /// A resolver for the files and aliases the graph knows about, without
+    /// walking the vault again
+    pub fn resolver(&self, vault_path: &Path) -> LinkResolver {
+        LinkResolver::with_settings(
+            vault_path,
+            self.files.clone(),
+            MatchSettings::read(),
+            self.ambiguity.clone(),
+        )
+        .with_aliases(self.notes.iter().flat_map(|(relative, note)| {
+            note.aliases
+                .iter()
+                .map(move |alias| (relative.as_str(), alias.as_str()))
+        }))
+    }
+
+    /// Every file in the vault, vault-relative
+    pub fn files(&self) -> &[String] {
+        &self.files
+    }
+
+    pub fn note_count(&self) -> usize {
+        self.notes.len()
+    }
+
+    pub fn link_count(&self) -> usize {
+        self.notes.values().map(|note| note.links.len()).sum()
+    }
+
+    /// Every note by vault-relative path, sorted like [`scan::note_paths`]
+    pub fn notes(&self) -> Vec<&str> {
+        let mut notes: Vec<&str> = self.notes.keys().map(String::as_str).collect();
+        notes.sort_by(|a, b| Path::new(a).cmp(Path::new(b)));
+        notes
+    }
+
+    /// Every canvas by vault-relative path, sorted like [`Self::notes`]
+    pub fn canvases(&self) -> Vec<&str> {
+        let mut canvases: Vec<&str> = self.canvases.keys().map(String::as_str).collect();
+        canvases.sort_by(|a, b| Path::new(a).cmp(Path::new(b)));
+        canvases
+    }
+
+    /// The links in a note, in the order they're written, or in a canvas, in
+    /// the order of its cards
+    pub fn links_from(&self, relative: &str) -> &[Edge] {
+        self.notes
+            .get(relative)
+            .or_else(|| self.canvases.get(relative))
+            .map_or(&[], |note| note.links.as_slice())
+    }
+
+    /// Each link from another note or a canvas to the file at `relative`,
+    /// along with the file it's in, sorted by that file's path
+    pub fn links_to(&self, relative: &str) -> Vec<(&str, &Edge)> {
+        self.backlinks
+            .get(relative)
+            .into_iter()
+            .flatten()
+            .flat_map(|source| {
+                self.links_from(source)
+                    .iter()
+                    .filter(|edge| edge.resolved.as_deref() == Some(relative))
+                    .map(move |edge| (source.as_str(), edge))
+            })
+            .collect()
+    }
+
+    /// The other notes and canvases linking to the file at `relative`, sorted
+    pub fn linking_notes(&self, relative: &str) -> Vec<&str> {
+        self.backlinks
+            .get(relative)
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// The other notes a note links to or is linked from, sorted
+    pub fn neighbors(&self, relative: &str) -> Vec<&str> {
+        let mut neighbors: BTreeSet<&str> = self
+            .linking_notes(relative)
+            .into_iter()
+            .filter(|source| self.notes.contains_key(*source))
+            .collect();
+        neighbors.extend(self.linked_notes(relative));
+        neighbors.into_iter().collect()
+    }
+
+    /// The notes within `depth` links of `root`, following links either
+    /// way, with how many links away each is. `root` itself is at 0.
+    pub fn neighborhood(&self, root: &str, depth: usize) -> BTreeMap<&str, usize> {
+        let mut distances = BTreeMap::new();
+        let Some((root, _)) = self.notes.get_key_value(root) else {
+            return distances;
+        };
+        distances.insert(root.as_str(), 0);
+
+        let mut frontier = vec![root.as_str()];
+        for distance in 1..=depth {
+            let mut next = Vec::new();
+            for note in frontier {
+                for neighbor in self.neighbors(note) {
+                    if !distances.contains_key(neighbor) {
+                        distances.insert(neighbor, distance);
+                        next.push(neighbor);
+                    }
+                }
+            }
+            frontier = next;
+        }
+
+        distances
+    }
+
+    /// The notes `from` links to, without itself
+    pub fn linked_notes(&self, from: &str) -> Vec<&str> {
+        let linked: BTreeSet<&str> = self
+            .links_from(from)
+            .iter()
+            .filter_map(|edge| edge.resolved.as_deref())
+            .filter(|target| *target != from && self.notes.contains_key(*target))
+            .collect();
+        linked.into_iter().collect()
+    }
+
+    /// One of the shortest chains of links from `from` to `to`, both
+    /// included, going no more than `max_depth` links. Links are followed
+    /// from the note they're in to the note they point at, or either way when
+    /// `undirected`.
+    pub fn shortest_path(
+        &self,
+        from: &str,
+        to: &str,
+        max_depth: Option<usize>,
+        undirected: bool,
+    ) -> Option<Vec<&str>> {
+        let (from, _) = self.notes.get_key_value(from)?;
+        let (to, _) = self.notes.get_key_value(to)?;
+        // The note each reached note was first reached from
+        let mut previous: BTreeMap<&str, Option<&str>> = BTreeMap::from([(from.as_str(), None)]);
+        let mut frontier = vec![from.as_str()];
+
+        let mut depth = 0;
+        while !previous.contains_key(to.as_str()) {
+            if frontier.is_empty() || max_depth.is_some_and(|max| depth >= max) {
+                return None;
+            }
+            depth += 1;
+            let mut next = Vec::new();
+            for note in frontier {
+                let neighbors = if undirected {
+                    self.neighbors(note)
+                } else {
+                    self.linked_notes(note)
+                };
+                for neighbor in neighbors {
+                    if !previous.contains_key(neighbor) {
+                        previous.insert(neighbor, Some(note));
+                        next.push(neighbor);
+                    }
+                }
+            }
+            frontier = next;
+        }
+
+        let mut path = vec![to.as_str()];
+        while let Some(Some(note)) = previous.get(path[path.len() - 1]) {
+            path.push(note);
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Links whose target doesn't exist, by the note they're in
+    pub fn broken(&self) -> Vec<(&str, Vec<&Edge>)> {
+        self.notes()
+            .into_iter()
+            .map(|source| {
+                let broken: Vec<&Edge> = self
+                    .links_from(source)
+                    .iter()
+                    .filter(|edge| edge.resolved.is_none())
+                    .collect();
+                (source, broken)
+            })
+            .filter(|(_, broken)| !broken.is_empty())
+            .collect()
+    }
+
+    /// Notes that no other note or canvas links to and that don't link to any other
+    /// file, like the unconnected dots in Obsidian's graph view
+    pub fn orphans(&self) -> Vec<&str> {
+        self.notes()
+            .into_iter()
+            .filter(|relative| {
+                !self.backlinks.contains_key(*relative)
+                    && self.links_from(relative).iter().all(|edge| {
+                        edge.resolved
+                            .as_deref()
+                            .is_none_or(|target| target == *relative)
+                    })
+            })
+            .collect()
+    }
+}
+
+impl NoteLinks {
+    fn read(note_path: &Path, modified: u128, size: u64) -> Self {
+        let contents = fs::read_to_string(note_path).unwrap_or_default();
+        let links = parse_links(&contents)
+            .into_iter()
+            .filter(|link| !link.is_external())
+            .map(|link| Edge::from_link(link, &contents))
+            .collect();
+        let aliases = ObsidianNote::parse(note_path, contents)
+            .map(|note| note.aliases())
+            .unwrap_or_default();
+
+        Self {
+            modified,
+            size,
+            links,
+            aliases,
+        }
+    }
+}
+
+impl NoteLinks {
+    /// A canvas's file cards and the links in its text cards. A canvas that
+    /// can't be parsed has none.
+    fn read_canvas(canvas_path: &Path, modified: u128, size: u64) -> Self {
+        let contents = fs::read_to_string(canvas_path).unwrap_or_default();
+        let links = Canvas::parse(&contents)
+            .map(|canvas| canvas.links(&contents))
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(link, _)| !link.is_external())
+            .map(|(link, text)| Edge {
+                text,
+                kind: link.kind,
+                embed: link.embed,
+                target: link.target,
+                subpath: link.subpath,
+                line: link.line,
+                resolved: None,
+            })
+            .collect();
+
+        Self {
+            modified,
+            size,
+            links,
+            aliases: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::{prelude::*, TempDir};
+
+    #[test]
+    fn resolves_links_both_ways() {
+        let dir = TempDir::new().unwrap();
+        dir.child("a.md")
+            .write_str("[[b#Plans]] ![[image.png]] [c](folder/c.md) [[#Top]] [[gone]]")
+            .unwrap();
+        dir.child("folder/b.md").write_str("back to [[a]]").unwrap();
+        dir.child("folder/c.md").write_str("").unwrap();
+        dir.child("image.png").write_str("").unwrap();
+        dir.child("lonely.md").write_str("[[lonely#Self]]").unwrap();
+
+        let mut graph = LinkGraph::default();
+        assert!(graph.refresh(dir.path()).unwrap());
+
+        let resolved: Vec<Option<&str>> = graph
+            .links_from("a.md")
+            .iter()
+            .map(|edge| edge.resolved.as_deref())
+            .collect();
+        assert_eq!(
+            resolved,
+            [
+                Some("folder/b.md"),
+                Some("image.png"),
+                Some("folder/c.md"),
+                Some("a.md"),
+                None
+            ]
+        );
+        assert_eq!(
+            graph.links_from("a.md")[0].subpath.as_deref(),
+            Some("Plans")
+        );
+        assert!(graph.links_from("a.md")[1].embed);
+
+        assert_eq!(graph.linking_notes("a.md"), ["folder/b.md"]);
+        assert_eq!(graph.links_to("folder/b.md")[0].1.text, "[[b#Plans]]");
+        assert_eq!(graph.broken()[0].1[0].target, "gone");
+        assert_eq!(graph.orphans(), ["lonely.md"]);
+    }
+
+    #[test]
+    fn only_reparses_changed_notes() {
+        let dir = TempDir::new().unwrap();
+        dir.child("a.md").write_str("[[b]]").unwrap();
+
+        let mut graph = LinkGraph::default();
+        graph.refresh(dir.path()).unwrap();
+        assert_eq!(graph.links_from("a.md")[0].resolved, None);
+        assert!(!graph.refresh(dir.path()).unwrap());
+
+        // A new file can fix links in notes that didn't change
+        dir.child("b.md").write_str("").unwrap();
+        assert!(graph.refresh(dir.path()).unwrap());
+        assert_eq!(graph.linking_notes("b.md"), ["a.md"]);
+
+        fs::remove_file(dir.child("a.md").path()).unwrap();
+        assert!(graph.refresh(dir.path()).unwrap());
+        assert!(graph.linking_notes("b.md").is_empty());
+    }
+
+    #[test]
+    fn walks_neighborhoods_both_ways() {
+        let dir = TempDir::new().unwrap();
+        dir.child("a.md").write_str("[[b]] ![[image.png]]").unwrap();
+        dir.child("b.md").write_str("[[c]]").unwrap();
+        dir.child("c.md").write_str("").unwrap();
+        dir.child("d.md").write_str("[[a]]").unwrap();
+        dir.child("image.png").write_str("").unwrap();
+
+        let mut graph = LinkGraph::default();
+        graph.refresh(dir.path()).unwrap();
+
+        assert_eq!(graph.neighbors("a.md"), ["b.md", "d.md"]);
+        assert_eq!(
+            graph.neighborhood("a.md", 1),
+            BTreeMap::from([("a.md", 0), ("b.md", 1), ("d.md", 1)])
+        );
+        assert_eq!(graph.neighborhood("a.md", 2).get("c.md"), Some(&2));
+        assert!(graph.neighborhood("missing.md", 2).is_empty());
+    }
+
+    #[test]
+    fn finds_shortest_paths() {
+        let dir = TempDir::new().unwrap();
+        dir.child("a.md").write_str("[[b]] [[long]]").unwrap();
+        dir.child("long.md").write_str("[[longer]]").unwrap();
+        dir.child("longer.md").write_str("[[d]]").unwrap();
+        dir.child("b.md").write_str("[[c]]").unwrap();
+        dir.child("c.md").write_str("[[d]]").unwrap();
+        dir.child("d.md").write_str("").unwrap();
+        dir.child("e.md").write_str("[[d]]").unwrap();
+
+        let mut graph = LinkGraph::default();
+        graph.refresh(dir.path()).unwrap();
+
+        assert_eq!(
+            graph.shortest_path("a.md", "d.md", None, false),
+            Some(vec!["a.md", "b.md", "c.md", "d.md"])
+        );
+        assert_eq!(graph.shortest_path("a.md", "d.md", Some(2), false), None);
+        assert_eq!(graph.shortest_path("a.md", "e.md", None, false), None);
+        assert_eq!(
+            graph
+                .shortest_path("a.md", "e.md", None, true)
+                .map(|path| path.len()),
+            Some(5)
+        );
+        assert_eq!(
+            graph.shortest_path("a.md", "a.md", Some(0), false),
+            Some(vec!["a.md"])
+        );
+    }
+
+    #[test]
+    fn links_back_from_canvases() {
+        let dir = TempDir::new().unwrap();
+        dir.child("a.md").write_str("").unwrap();
+        dir.child("b.md").write_str("").unwrap();
+        dir.child("board.canvas")
+            .write_str(
+                r#"{"nodes":[
+                    {"id":"1","type":"file","file":"a.md"},
+                    {"id":"2","type":"text","text":"About [[b]]"}
+                ]}"#,
+            )
+            .unwrap();
+
+        let mut graph = LinkGraph::default();
+        graph.refresh(dir.path()).unwrap();
+
+        assert_eq!(graph.notes(), ["a.md", "b.md"]);
+        assert_eq!(graph.canvases(), ["board.canvas"]);
+        assert_eq!(graph.linking_notes("a.md"), ["board.canvas"]);
+        assert_eq!(graph.links_to("b.md")[0].1.text, "[[b]]");
+        assert!(graph.orphans().is_empty());
+        assert!(graph.neighbors("a.md").is_empty());
+    }
+}
@@ -0,0 +1,534 @@
+use crate::{
+    ambiguity::AmbiguitySettings, cache::MetadataCache, matching::MatchSettings, scan,
+    zettel::ZettelSettings,
+};
+use libobsidian::{
+    blocks::find_block,
+    headings::{parse_headings, Heading},
+    links::{parse_links, Link, LinkKind},
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Resolves link targets to files in a vault the way Obsidian does: markdown
+/// links are relative to the linking note, wikilinks match a vault-relative
+/// path or, failing that, a path ending in the target, then a note's
+/// `aliases`. Several of those are picked between by the config's
+/// [`ambiguous_links`](AmbiguitySettings), the shortest path by default. A wikilink to a bare Zettelkasten ID matches the note
+/// whose name starts with it.
+#[derive(Clone)]
+pub struct LinkResolver {
+    vault_path: PathBuf,
+    /// Every file in the vault, relative to its root with `/` separators
+    files: Vec<String>,
+    /// The same files folded with [`MatchSettings::fold`], for matching links
+    /// regardless of case and how accents are written
+    folded: Vec<String>,
+    /// Each note's aliases, folded, alongside its vault-relative path
+    aliases: Vec<(String, String)>,
+    /// The config's settings when the resolver was made
+    matching: MatchSettings,
+    ambiguity: AmbiguitySettings,
+}
+
+/// A note along with the links in it that point at particular files
+pub struct LinkingNote {
+    pub path: PathBuf,
+    pub contents: String,
+    /// Each matching link with the absolute path it resolves to
+    pub links: Vec<(Link, PathBuf)>,
+}
+
+impl LinkResolver {
+    pub fn new(vault_path: &Path) -> anyhow::Result<Self> {
+        let files = scan::file_paths(vault_path)?
+            .iter()
+            .map(|path| scan::relative_note_path(path, vault_path))
+            .collect();
+        let metadata = MetadataCache::current(vault_path)?;
+
+        Ok(
+            Self::from_files(vault_path, files).with_aliases(
+                metadata.notes().into_iter().flat_map(|(relative, note)| {
+                    note.aliases
+                        .iter()
+                        .map(move |alias| (relative, alias.as_str()))
+                }),
+            ),
+        )
+    }
+
+    /// A resolver for files already found, vault-relative with `/` separators
+    pub fn from_files(vault_path: &Path, files: Vec<String>) -> Self {
+        Self::with_settings(
+            vault_path,
+            files,
+            MatchSettings::read(),
+            AmbiguitySettings::read(),
+        )
+    }
+
+    /// Like [`Self::from_files`], with settings other than the config's
+    pub fn with_settings(
+        vault_path: &Path,
+        files: Vec<String>,
+        matching: MatchSettings,
+        ambiguity: AmbiguitySettings,
+    ) -> Self {
+        let folded = files.iter().map(|file| matching.fold(file)).collect();
+        Self {
+            vault_path: vault_path.to_path_buf(),
+            files,
+            folded,
+            aliases: Vec::new(),
+            matching,
+            ambiguity,
+        }
+    }
+
+    pub fn matching(&self) -> &MatchSettings {
+        &self.matching
+    }
+
+    /// Resolve wikilinks to these `(note, alias)` pairs too, for notes with
+    /// no file matching the link
+    pub fn with_aliases<'a>(
+        mut self,
+        aliases: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Self {
+        self.aliases = aliases
+            .into_iter()
+            .map(|(relative, alias)| (self.matching.fold(alias), relative.to_string()))
+            .collect();
+        self
+    }
+
+    /// The absolute path of the file `link` points to, or `None` when it's
+    /// missing or external. `source` is the note containing the link.
+    pub fn resolve(&self, link: &Link, source: &Path) -> Option<PathBuf> {
+        self.resolve_with(link, source, true)
+    }
+
+    /// Like [`LinkResolver::resolve`], but path-like targets (markdown links
+    /// and wikilinks containing a `/`) must match exactly rather than falling
+    /// back to any file whose path ends the same way
+    pub fn resolve_strict(&self, link: &Link, source: &Path) -> Option<PathBuf> {
+        self.resolve_with(link, source, false)
+    }
+
+    fn resolve_with(&self, link: &Link, source: &Path, lenient: bool) -> Option<PathBuf> {
+        if link.is_external() {
+            return None;
+        }
+        if link.target.is_empty() {
+            return Some(source.to_path_buf());
+        }
+
+        self.resolve_relative(link, source, lenient)
+            .map(|relative| self.vault_path.join(relative))
+    }
+
+    fn resolve_relative(&self, link: &Link, source: &Path, lenient: bool) -> Option<&String> {
+        let candidates = candidate_names(&link.target);
+        let explicitly_relative = link.target.starts_with("./") || link.target.starts_with("../");
+
+        if link.kind == LinkKind::Markdown || explicitly_relative {
+            let source_relative = scan::relative_note_path(source, &self.vault_path);
+            let source_dir = source_relative
+                .rsplit_once('/')
+                .map(|(dir, _)| dir)
+                .unwrap_or("");
+
+            for candidate in &candidates {
+                if let Some(found) = normalize(source_dir, candidate).and_then(|p| self.find(&p)) {
+                    return Some(found);
+                }
+            }
+        }
+
+        if explicitly_relative {
+            return None;
+        }
+
+        for candidate in &candidates {
+            let candidate = candidate.trim_start_matches('/');
+            if let Some(found) = self.find(candidate) {
+                return Some(found);
+            }
+        }
+
+        let name_only = link.kind == LinkKind::Wikilink && !link.target.contains('/');
+        if !lenient && !name_only {
+            return None;
+        }
+
+        candidates
+            .iter()
+            .find_map(|candidate| self.ending_with(candidate).into_iter().next())
+            .or_else(|| {
+                name_only
+                    .then(|| self.find_by_alias(&link.target))
+                    .flatten()
+            })
+            .or_else(|| name_only.then(|| self.find_by_id(&link.target)).flatten())
+    }
+
+    /// The files whose paths end in `/name`, the one the name means first
+    fn ending_with(&self, name: &str) -> Vec<&String> {
+        let suffix = format!("/{}", self.matching.fold(name));
+        let mut found: Vec<&String> = self
+            .files
+            .iter()
+            .zip(&self.folded)
+            .filter(|(_, folded)| folded.ends_with(&suffix))
+            .map(|(file, _)| file)
+            .collect();
+        self.ambiguity.rank(&mut found, &self.matching);
+        found
+    }
+
+    /// The note with `alias` among its aliases, ranked like notes sharing a
+    /// name when several are
+    fn find_by_alias(&self, alias: &str) -> Option<&String> {
+        let folded = self.matching.fold(alias);
+        let mut found: Vec<&String> = self
+            .aliases
+            .iter()
+            .filter(|(candidate, _)| *candidate == folded)
+            .map(|(_, relative)| relative)
+            .collect();
+        self.ambiguity.rank(&mut found, &self.matching);
+        found.into_iter().next()
+    }
+
+    /// The note whose name starts with the Zettelkasten ID `id`
+    fn find_by_id(&self, id: &str) -> Option<&String> {
+        let settings = ZettelSettings::current();
+        if !settings.is_id(id) {
+            return None;
+        }
+        self.files
+            .iter()
+            .filter(|file| {
+                let name = file.rsplit('/').next().unwrap_or(file);
+                name.strip_suffix(".md")
+                    .is_some_and(|stem| settings.id_of(stem) == Some(id))
+            })
+            .min_by_key(|file| (file.len(), file.as_str()))
+    }
+
+    /// Every file a name-only wikilink could mean when no file sits at that
+    /// path from the vault's root, the one it resolves to first. More than
+    /// one makes the link ambiguous.
+    pub fn candidates(&self, link: &Link) -> Vec<&str> {
+        if link.kind != LinkKind::Wikilink || link.target.is_empty() || link.target.contains('/') {
+            return Vec::new();
+        }
+        let candidates = candidate_names(&link.target);
+        if candidates
+            .iter()
+            .any(|candidate| self.find(candidate).is_some())
+        {
+            return Vec::new();
+        }
+
+        candidates
+            .iter()
+            .map(|candidate| self.ending_with(candidate))
+            .find(|found| !found.is_empty())
+            .map(|found| found.into_iter().map(String::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    /// How a `kind` link in `source` should refer to `target`: wikilinks use
+    /// the bare note name when it's unique in the vault and the vault-relative
+    /// path otherwise, markdown links use a path relative to `source`
+    pub fn link_target(&self, target: &Path, source: &Path, kind: LinkKind) -> String {
+        let target_relative = scan::relative_note_path(target, &self.vault_path);
+
+        match kind {
+            LinkKind::Wikilink => {
+                let file_name = target_relative
+                    .rsplit('/')
+                    .next()
+                    .unwrap_or(&target_relative);
+                let folded_name = self.matching.fold(file_name);
+                let name_is_unique = self
+                    .folded
+                    .iter()
+                    .filter(|file| file.rsplit('/').next() == Some(folded_name.as_str()))
+                    .count()
+                    <= 1;
+
+                let link = if name_is_unique {
+                    file_name
+                } else {
+                    &target_relative
+                };
+                link.strip_suffix(".md").unwrap_or(link).to_string()
+            }
+            LinkKind::Markdown => {
+                let source_relative = scan::relative_note_path(source, &self.vault_path);
+                relative_between(&source_relative, &target_relative)
+            }
+        }
+    }
+
+    /// Every note containing links that resolve to one of `targets`. Links
+    /// from a note to a heading within itself are left out.
+    pub fn notes_linking_to(&self, targets: &[PathBuf]) -> anyhow::Result<Vec<LinkingNote>> {
+        let mut linking = Vec::new();
+
+        for note_path in scan::note_paths(&self.vault_path)? {
+            let Ok(contents) = fs::read_to_string(&note_path) else {
+                continue;
+            };
+
+            let links: Vec<(Link, PathBuf)> = parse_links(&contents)
+                .into_iter()
+                .filter(|link| !link.target.is_empty())
+                .filter_map(|link| {
+                    let resolved = self.resolve(&link, &note_path)?;
+                    targets.contains(&resolved).then_some((link, resolved))
+                })
+                .collect();
+
+            if !links.is_empty() {
+                linking.push(LinkingNote {
+                    path: note_path,
+                    contents,
+                    links,
+                });
+            }
+        }
+
+        Ok(linking)
+    }
+
+    /// Rewrite `link`, found in `source`, to point at `target` while keeping
+    /// its style: wikilinks written as paths stay paths, and an explicit `.md`
+    /// extension is kept
+    pub fn retarget(&self, link: &Link, target: &Path, source: &Path) -> String {
+        let mut new_target = match link.kind {
+            LinkKind::Wikilink if link.target.contains('/') => {
+                let relative = scan::relative_note_path(target, &self.vault_path);
+                relative
+                    .strip_suffix(".md")
+                    .map(str::to_string)
+                    .unwrap_or(relative)
+            }
+            kind => self.link_target(target, source, kind),
+        };
+
+        if link.kind == LinkKind::Wikilink
+            && link.target.ends_with(".md")
+            && scan::is_markdown(target)
+        {
+            new_target.push_str(".md");
+        }
+
+        link.with_target(&new_target)
+    }
+
+    /// Update the known files after a file has been moved from `from` to `to`
+    pub fn record_move(&mut self, from: &Path, to: &Path) {
+        let from = scan::relative_note_path(from, &self.vault_path);
+        let to = scan::relative_note_path(to, &self.vault_path);
+
+        for (_, relative) in &mut self.aliases {
+            if *relative == from {
+                relative.clone_from(&to);
+            }
+        }
+        self.files.retain(|file| *file != from);
+        self.files.push(to);
+        self.files.sort();
+        self.folded = self
+            .files
+            .iter()
+            .map(|file| self.matching.fold(file))
+            .collect();
+    }
+
+    fn find(&self, relative: &str) -> Option<&String> {
+        self.files
+            .iter()
+            .find(|file| file.as_str() == relative)
+            .or_else(|| {
+                let folded = self.matching.fold(relative);
+                self.files
+                    .iter()
+                    .zip(&self.folded)
+                    .find(|(_, file)| **file == folded)
+                    .map(|(file, _)| file)
+            })
+    }
+}
+
+/// Whether the heading or `^block` that `link` points into, if any, is in
+/// `resolved`, the file it resolved to. Only notes are checked, since the
+/// subpaths of other files, like `#page=3` of a PDF, aren't headings.
+pub fn has_subpath(link: &Link, resolved: &Path, matching: &MatchSettings) -> bool {
+    if link.subpath.is_none() || !scan::is_markdown(resolved) {
+        return true;
+    }
+    let Ok(contents) = fs::read_to_string(resolved) else {
+        return false;
+    };
+    match (link.block_id(), link.heading()) {
+        (Some(id), _) => find_block(&contents, id).is_some(),
+        (None, Some(heading)) => find_heading(&contents, heading, matching).is_some(),
+        (None, None) => true,
+    }
+}
+
+/// The heading in `content` called `heading`, ignoring case and the
+/// punctuation Obsidian leaves out of links, so `[[note#Why: a reason]]`,
+/// `[[note#Why a reason]]` and `[](note.md#why-a-reason)` all find
+/// `## Why: a reason`
+pub fn find_heading(content: &str, heading: &str, matching: &MatchSettings) -> Option<Heading> {
+    let wanted = heading_key(heading, matching);
+    parse_headings(content)
+        .into_iter()
+        .find(|candidate| heading_key(&candidate.text, matching) == wanted)
+}
+
+/// Whether a link's `heading` points at the heading with `text`, matching
+/// like [`find_heading`]
+pub fn same_heading(heading: &str, text: &str, matching: &MatchSettings) -> bool {
+    heading_key(heading, matching) == heading_key(text, matching)
+}
+
+fn heading_key(text: &str, matching: &MatchSettings) -> String {
+    matching
+        .fold(text)
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The file names a link target could refer to, notes first: `note` may mean
+/// `note.md`, while `image.png` only means itself
+pub fn candidate_names(target: &str) -> Vec<String> {
+    if scan::is_markdown(Path::new(target)) {
+        vec![target.to_string()]
+    } else {
+        vec![format!("{target}.md"), target.to_string()]
+    }
+}
+
+/// Join a relative link onto the linking note's folder, collapsing `.` and
+/// `..`. Returns `None` for links escaping the vault.
+pub fn normalize(base_dir: &str, target: &str) -> Option<String> {
+    let mut parts: Vec<&str> = base_dir
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .collect();
+
+    for part in target.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            part => parts.push(part),
+        }
+    }
+
+    Some(parts.join("/"))
+}
+
+/// A path from the folder of `source` to `target`, both vault-relative
+pub fn relative_between(source: &str, target: &str) -> String {
+    let source_dir: Vec<&str> = source.split('/').collect();
+    let source_dir = &source_dir[..source_dir.len() - 1];
+    let target_parts: Vec<&str> = target.split('/').collect();
+
+    let common = source_dir
+        .iter()
+        .zip(&target_parts)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut parts: Vec<&str> = vec![".."; source_dir.len() - common];
+    parts.extend(&target_parts[common..]);
+
+    parts.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case("[[café]]", Some("cafe\u{301}.md") ; "composed link to decomposed name")]
+    #[test_case("[[CAFÉ]]", Some("cafe\u{301}.md") ; "ignoring case")]
+    #[test_case("[[folder/Résumé]]", Some("folder/re\u{301}sume\u{301}.md") ; "path")]
+    fn resolves_links_however_accents_are_written(link: &str, expected: Option<&str>) {
+        let vault = Path::new("/vault");
+        let files = vec![
+            "cafe\u{301}.md".to_string(),
+            "folder/re\u{301}sume\u{301}.md".to_string(),
+        ];
+        let resolver = LinkResolver::from_files(vault, files);
+
+        let link = &parse_links(link)[0];
+        assert_eq!(
+            resolver.resolve(link, &vault.join("source.md")),
+            expected.map(|file| vault.join(file))
+        );
+    }
+
+    #[test_case("[[202405101230]]", Some("folder/202405101230 Atomic notes.md") ; "id")]
+    #[test_case("[[202405101231]]", None ; "other id")]
+    #[test_case("[[folder/202405101230]]", None ; "id in a path")]
+    fn resolves_zettel_ids(link: &str, expected: Option<&str>) {
+        let vault = Path::new("/vault");
+        let files = vec!["folder/202405101230 Atomic notes.md".to_string()];
+        let resolver = LinkResolver::from_files(vault, files);
+
+        let link = &parse_links(link)[0];
+        assert_eq!(
+            resolver.resolve(link, &vault.join("source.md")),
+            expected.map(|file| vault.join(file))
+        );
+    }
+
+    #[test_case("[[The Plan]]", Some("projects/plan.md") ; "alias")]
+    #[test_case("[[the plan#Goals]]", Some("projects/plan.md") ; "alias ignoring case")]
+    #[test_case("[[Plan]]", Some("Plan.md") ; "names before aliases")]
+    #[test_case("[[projects/The Plan]]", None ; "alias in a path")]
+    fn resolves_aliases(link: &str, expected: Option<&str>) {
+        let vault = Path::new("/vault");
+        let files = vec!["Plan.md".to_string(), "projects/plan.md".to_string()];
+        let resolver = LinkResolver::from_files(vault, files).with_aliases([
+            ("projects/plan.md", "The Plan"),
+            ("projects/plan.md", "plan"),
+        ]);
+
+        let link = &parse_links(link)[0];
+        assert_eq!(
+            resolver.resolve(link, &vault.join("source.md")),
+            expected.map(|file| vault.join(file))
+        );
+    }
+
+    #[test_case("folder", "note.md", Some("folder/note.md") ; "sibling")]
+    #[test_case("folder/sub", "../note.md", Some("folder/note.md") ; "parent")]
+    #[test_case("", "./a/b.md", Some("a/b.md") ; "dot segment")]
+    #[test_case("", "../outside.md", None ; "escapes vault")]
+    fn normalize_joins_relative_links(base: &str, target: &str, expected: Option<&str>) {
+        assert_eq!(normalize(base, target).as_deref(), expected);
+    }
+
+    #[test_case("a.md", "b.md", "b.md" ; "same folder")]
+    #[test_case("folder/a.md", "b.md", "../b.md" ; "parent folder")]
+    #[test_case("a.md", "folder/sub/b.md", "folder/sub/b.md" ; "subfolder")]
+    #[test_case("x/a.md", "y/b.md", "../y/b.md" ; "sibling folder")]
+    fn relative_between_walks_up_and_down(source: &str, target: &str, expected: &str) {
+        assert_eq!(relative_between(source, target), expected);
+    }
+}
@@ -1,20 +1,72 @@
 use std::process::exit;
 
-use clap::{Parser, Subcommand};
+use clap::{ColorChoice, CommandFactory, Parser, Subcommand};
 
+pub mod ambiguity;
+pub mod api;
 pub mod app_settings;
+pub mod attachments;
+pub mod autocommit;
+pub mod cache;
+pub mod cite;
 pub mod cli_config;
 pub mod commands;
+pub mod dates;
+pub mod editor;
+pub mod embeds;
 pub mod formats;
+pub mod fuzzy;
+pub mod git;
+pub mod graph_export;
+pub mod graph_stats;
+pub mod hooks;
+pub mod html;
+pub mod import;
+pub mod index;
+pub mod link_graph;
+pub mod link_resolver;
+pub mod matching;
+pub mod mcp;
+pub mod mentions;
+pub mod note_text;
+pub mod obsidian_uri;
+pub mod paging;
+pub mod pandoc;
+pub mod periodic;
+pub mod query;
+pub mod related;
+pub mod relations;
+pub mod relocate;
+pub mod render;
+pub mod resolve;
+pub mod scan;
+pub mod search_query;
+pub mod secrets;
+pub mod state_file;
+pub mod style;
+pub mod template;
 pub mod theme;
+pub mod trash;
 pub mod tui;
 pub mod util;
+pub mod vault_settings;
+pub mod watch;
+pub mod zettel;
 
 #[derive(Parser)]
 #[command(arg_required_else_help = true)]
 struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
+
+    /// Use a named profile from the config, overriding OBX_PROFILE
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
+    /// When to color output, in the config theme's colors. `auto` colors it
+    /// on a terminal unless NO_COLOR is set
+    #[arg(long, global = true, value_name = "WHEN", default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
 }
 
 #[derive(Subcommand, Debug)]
@@ -26,6 +78,7 @@ enum Commands {
     Notes(commands::notes::NotesCommand),
 
     /// Commands for interacting with vaults
+    #[command(visible_alias = "vault")]
     Vaults(commands::vaults::VaultsCommand),
 
     /// Commands for managing config
@@ -33,17 +86,214 @@ enum Commands {
 
     /// Launch the interactive vault browser
     Browse(commands::browse::BrowseCommand),
+
+    /// Open a note by name, fuzzy match, or path in your configured editor
+    Open(commands::open::OpenCommand),
+
+    /// Search notes with a regular expression, printing matching lines with optional context
+    Grep(commands::grep::GrepCommand),
+
+    /// Copy files into the vault's attachment folder and embed them in a note
+    Attach(commands::attach::AttachCommand),
+
+    /// List notes in a vault, filtered by folder, tag, name or modification time
+    List(commands::list::ListCommand),
+
+    /// Export notes to other formats
+    Export(commands::export::ExportCommand),
+
+    /// List the tags used across the vault with how many notes carry each
+    Tags(commands::tags::TagsCommand),
+
+    /// List or trash notes whose `expires` frontmatter date has passed
+    Expire(commands::expire::ExpireCommand),
+
+    /// Print vault-relative note paths matching a prefix, for shell and editor completion
+    CompletePaths(commands::complete_paths::CompletePathsCommand),
+    /// Print the vault's tags starting with a prefix, most used first, for shell completion
+    CompleteTags(commands::complete_tags::CompleteTagsCommand),
+
+    /// List the links and embeds in a note and whether their targets exist
+    Links(commands::links::LinksCommand),
+
+    /// Report links and embeds whose targets don't exist, optionally fixing them interactively
+    CheckLinks(commands::check_links::CheckLinksCommand),
+
+    /// Rename a note and update every link pointing at it
+    Rename(commands::rename::RenameCommand),
+
+    /// Move notes into a folder and update links that would break
+    Move(commands::move_notes::MoveCommand),
+
+    /// Delete a note, reporting how many notes link to it first
+    Delete(commands::delete::DeleteCommand),
+
+    /// List the most recently modified notes
+    Recent(commands::recent::RecentCommand),
+
+    /// Print or open a random note, optionally from a folder or tag
+    Random(commands::random::RandomCommand),
+
+    /// Print a note's contents, optionally without frontmatter or rendered for the terminal
+    Cat(commands::cat::CatCommand),
+
+    /// Read and edit individual frontmatter properties of a note
+    Meta(commands::meta::MetaCommand),
+
+    /// Find notes whose frontmatter matches a filter expression
+    Query(commands::query::QueryCommand),
+
+    /// Work with the note templates used by `notes create --template`
+    Template(commands::template::TemplateCommand),
+
+    /// Import notes from other apps' exports
+    Import(commands::import::ImportCommand),
+
+    /// Show a unified diff between two notes, or a note and its git history
+    Diff(commands::diff::DiffCommand),
+
+    /// Print shell completions, including vault and note names
+    Completions(commands::completions::CompletionsCommand),
+
+    /// Build, inspect or clear the vault's full-text search index
+    Index(commands::index::IndexCommand),
+
+    /// Search notes for words, best matches first, using the index when one is built
+    Search(commands::search::SearchCommand),
+
+    /// Find notes with the same or nearly the same contents
+    Dedupe(commands::dedupe::DedupeCommand),
+
+    /// List the links to a note from other notes
+    Backlinks(commands::backlinks::BacklinksCommand),
+
+    /// List notes with no links to or from other notes
+    Orphans(commands::orphans::OrphansCommand),
+
+    /// Time scanning, indexing, searching and building the link graph for the vault
+    #[command(hide = true)]
+    Bench(commands::bench::BenchCommand),
+
+    /// List the notes most like a note, by their words, tags and links
+    Related(commands::related::RelatedCommand),
+
+    /// Export the vault's link graph for visualising in other tools
+    Graph(commands::graph::GraphCommand),
+
+    /// Rewrite links between wikilink and markdown syntax
+    ConvertLinks(commands::convert_links::ConvertLinksCommand),
+
+    /// Report problems across the vault
+    Audit(commands::audit::AuditCommand),
+
+    /// Create or update a note linking to every note in a folder
+    Moc(commands::moc::MocCommand),
+
+    /// Print the headings of a note as a tree
+    Outline(commands::outline::OutlineCommand),
+
+    /// Rename or merge tags across the vault
+    Tag(commands::tag::TagCommand),
+
+    /// Search a BibTeX bibliography and cite its entries in notes
+    Cite(commands::cite::CiteCommand),
+
+    /// Count the words in a note, optionally with the notes it embeds
+    Wordcount(commands::wordcount::WordcountCommand),
+
+    /// Rename a heading in a note, and optionally the links pointing into it
+    RenameHeading(commands::rename_heading::RenameHeadingCommand),
+
+    /// Create today's daily note, or this week's weekly note, and print its path
+    Daily(commands::daily::DailyCommand),
+
+    /// Find the shortest chain of links from one note to another
+    Path(commands::path::PathCommand),
+
+    /// Move a folder and everything in it, updating every link pointing into or out of it
+    MoveFolder(commands::move_folder::MoveFolderCommand),
+
+    /// Show the hierarchy of parents and children that frontmatter links like `up` define
+    Tree(commands::tree::TreeCommand),
+
+    /// Commit the vault's changes and sync them with its git remote
+    Git(commands::git::GitCommand),
+
+    /// Serve the vault over HTTP for other tools to use
+    Serve(commands::serve::ServeCommand),
+
+    /// Let AI assistants search, read and create notes over the Model Context Protocol
+    Mcp(commands::mcp::McpCommand),
+
+    /// Print the obsidian:// URI that opens a note in the Obsidian app, or open it
+    Uri(commands::uri::UriCommand),
+
+    /// Share previews and search results between the TUI windows open on a vault
+    Daemon(commands::daemon::DaemonCommand),
 }
 
 fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
 
+    if let Some(profile) = &cli.profile {
+        std::env::set_var("OBX_PROFILE", profile);
+    }
+    style::set_choice(cli.color);
+
     let res = match &cli.command {
         Some(Commands::Init(args)) => commands::init::entry(args),
         Some(Commands::Notes(args)) => commands::notes::entry(args),
         Some(Commands::Vaults(args)) => commands::vaults::entry(args),
         Some(Commands::Config(args)) => commands::config::entry(args),
         Some(Commands::Browse(args)) => commands::browse::entry(args),
+        Some(Commands::Open(args)) => commands::open::entry(args),
+        Some(Commands::Grep(args)) => commands::grep::entry(args),
+        Some(Commands::Attach(args)) => commands::attach::entry(args),
+        Some(Commands::List(args)) => commands::list::entry(args),
+        Some(Commands::Export(args)) => commands::export::entry(args),
+        Some(Commands::Tags(args)) => commands::tags::entry(args),
+        Some(Commands::Expire(args)) => commands::expire::entry(args),
+        Some(Commands::CompletePaths(args)) => commands::complete_paths::entry(args),
+        Some(Commands::CompleteTags(args)) => commands::complete_tags::entry(args),
+        Some(Commands::Links(args)) => commands::links::entry(args),
+        Some(Commands::CheckLinks(args)) => commands::check_links::entry(args),
+        Some(Commands::Rename(args)) => commands::rename::entry(args),
+        Some(Commands::Move(args)) => commands::move_notes::entry(args),
+        Some(Commands::Delete(args)) => commands::delete::entry(args),
+        Some(Commands::Recent(args)) => commands::recent::entry(args),
+        Some(Commands::Random(args)) => commands::random::entry(args),
+        Some(Commands::Cat(args)) => commands::cat::entry(args),
+        Some(Commands::Meta(args)) => commands::meta::entry(args),
+        Some(Commands::Query(args)) => commands::query::entry(args),
+        Some(Commands::Template(args)) => commands::template::entry(args),
+        Some(Commands::Import(args)) => commands::import::entry(args),
+        Some(Commands::Diff(args)) => commands::diff::entry(args),
+        Some(Commands::Completions(args)) => commands::completions::entry(args, Cli::command()),
+        Some(Commands::Index(args)) => commands::index::entry(args),
+        Some(Commands::Search(args)) => commands::search::entry(args),
+        Some(Commands::Dedupe(args)) => commands::dedupe::entry(args),
+        Some(Commands::Backlinks(args)) => commands::backlinks::entry(args),
+        Some(Commands::Orphans(args)) => commands::orphans::entry(args),
+        Some(Commands::Bench(args)) => commands::bench::entry(args),
+        Some(Commands::Related(args)) => commands::related::entry(args),
+        Some(Commands::Graph(args)) => commands::graph::entry(args),
+        Some(Commands::ConvertLinks(args)) => commands::convert_links::entry(args),
+        Some(Commands::Audit(args)) => commands::audit::entry(args),
+        Some(Commands::Moc(args)) => commands::moc::entry(args),
+        Some(Commands::Outline(args)) => commands::outline::entry(args),
+        Some(Commands::Tag(args)) => commands::tag::entry(args),
+        Some(Commands::Cite(args)) => commands::cite::entry(args),
+        Some(Commands::Wordcount(args)) => commands::wordcount::entry(args),
+        Some(Commands::RenameHeading(args)) => commands::rename_heading::entry(args),
+        Some(Commands::Daily(args)) => commands::daily::entry(args),
+        Some(Commands::Path(args)) => commands::path::entry(args),
+        Some(Commands::MoveFolder(args)) => commands::move_folder::entry(args),
+        Some(Commands::Tree(args)) => commands::tree::entry(args),
+        Some(Commands::Git(args)) => commands::git::entry(args),
+        Some(Commands::Serve(args)) => commands::serve::entry(args),
+        Some(Commands::Mcp(args)) => commands::mcp::entry(args),
+        Some(Commands::Uri(args)) => commands::uri::entry(args),
+        Some(Commands::Daemon(args)) => commands::daemon::entry(args),
         None => {
             todo!("Needs a sub-command");
         }
@@ -0,0 +1,217 @@
+//! How text typed into queries, note names and links is matched against the
+//! vault. Text is compared in composed Unicode (NFC), since macOS names files
+//! in decomposed form (NFD), writing `é` as `e` followed by a combining
+//! accent, while what's typed is almost always composed. The config's
+//! `matching` settings can ignore diacritics too, so `cafe` finds `café`.
+//!
+//! Searches ignore case unless what's typed has a capital letter in it, like
+//! the smart case of editors, so `Rust` finds only `Rust` while `rust` finds
+//! both. `--case-sensitive`, `--ignore-case` or the config's `case` override
+//! it.
+
+use crate::cli_config;
+use clap::Args;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use unicode_normalization::{
+    char::is_combining_mark, is_nfc_quick, IsNormalized, UnicodeNormalization,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct MatchSettings {
+    /// Match letters regardless of accents and other diacritics
+    pub ignore_diacritics: bool,
+
+    /// When searches match case
+    pub case: Case,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Case {
+    /// Match case when what's typed has a capital letter
+    #[default]
+    Smart,
+    Sensitive,
+    Insensitive,
+}
+
+impl Case {
+    /// Whether to match case when looking for `typed`
+    pub fn is_sensitive(self, typed: &str) -> bool {
+        match self {
+            Case::Smart => typed.chars().any(char::is_uppercase),
+            Case::Sensitive => true,
+            Case::Insensitive => false,
+        }
+    }
+}
+
+/// `--case-sensitive` and `--ignore-case` for commands matching text
+#[derive(Args, Debug, Clone, Default)]
+pub struct CaseArgs {
+    /// Match case exactly, even when everything typed is lowercase
+    #[arg(long, conflicts_with = "ignore_case")]
+    case_sensitive: bool,
+
+    /// Ignore case, even when what's typed has capital letters
+    #[arg(long, short = 'i')]
+    ignore_case: bool,
+}
+
+impl CaseArgs {
+    /// The case mode asked for, or `settings`' when neither flag is given
+    pub fn case(&self, settings: &MatchSettings) -> Case {
+        if self.case_sensitive {
+            Case::Sensitive
+        } else if self.ignore_case {
+            Case::Insensitive
+        } else {
+            settings.case
+        }
+    }
+}
+
+impl MatchSettings {
+    pub fn is_default(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// The config's settings. Read when a command or request starts and
+    /// passed along, rather than kept for the whole process, so the servers
+    /// and the TUI see the config as it is now.
+    pub fn read() -> Self {
+        cli_config::read()
+            .map(|config| config.matching)
+            .unwrap_or_default()
+    }
+
+    /// `text` composed, and without diacritics when they're ignored, keeping
+    /// its case
+    pub fn normalize<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if self.ignore_diacritics {
+            if text.is_ascii() {
+                return Cow::Borrowed(text);
+            }
+            // Hangul decomposes into letters rather than marks, so the
+            // result is composed again
+            return Cow::Owned(
+                text.nfd()
+                    .filter(|c| !is_combining_mark(*c))
+                    .nfc()
+                    .collect(),
+            );
+        }
+
+        compose(text)
+    }
+
+    /// `text` normalized and lowercased, for comparing regardless of case
+    pub fn fold(&self, text: &str) -> String {
+        self.normalize(text).to_lowercase()
+    }
+
+    /// Whether `a` and `b` are the same regardless of case, and of
+    /// diacritics when they're ignored
+    pub fn same_ignoring_case(&self, a: &str, b: &str) -> bool {
+        a.eq_ignore_ascii_case(b) || self.fold(a) == self.fold(b)
+    }
+
+    /// Whether `tag` is `wanted` or nested beneath it, the way
+    /// `#project/alpha` is beneath `#project` in Obsidian, ignoring case
+    pub fn has_tag(&self, tag: &str, wanted: &str) -> bool {
+        self.tag_match_len(tag, wanted).is_some()
+    }
+
+    /// Like [`Self::has_tag`], but with how many bytes of `tag` matched
+    /// `wanted`, for replacing them. That can differ from `wanted`'s length
+    /// when the case or composition differs.
+    pub fn tag_match_len(&self, tag: &str, wanted: &str) -> Option<usize> {
+        let trimmed = tag.trim_start_matches('#');
+        let wanted = self.fold(wanted.trim_start_matches('#'));
+        trimmed
+            .match_indices('/')
+            .map(|(idx, _)| idx)
+            .chain([trimmed.len()])
+            .find(|&end| self.fold(&trimmed[..end]) == wanted)
+            .map(|end| tag.len() - trimmed.len() + end)
+    }
+}
+
+/// `text` in composed form (NFC), borrowed when it already is
+pub fn compose(text: &str) -> Cow<'_, str> {
+    if is_nfc_quick(text.chars()) == IsNormalized::Yes {
+        Cow::Borrowed(text)
+    } else {
+        Cow::Owned(text.nfc().collect())
+    }
+}
+
+/// Whether `a` and `b` are the same text once composed
+pub fn same(a: &str, b: &str) -> bool {
+    a == b || compose(a) == compose(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    const DECOMPOSED: &str = "cafe\u{301}";
+
+    #[test_case("project", "project" ; "same tag")]
+    #[test_case("Project/Alpha", "#project" ; "nested, other case")]
+    #[test_case("project/alpha/one", "project/alpha" ; "deeply nested")]
+    fn has_nested_tags(tag: &str, wanted: &str) {
+        assert!(MatchSettings::default().has_tag(tag, wanted));
+    }
+
+    #[test]
+    fn leaves_out_tags_only_sharing_a_prefix() {
+        let settings = MatchSettings::default();
+        assert!(!settings.has_tag("projects", "project"));
+        assert!(!settings.has_tag("project", "project/alpha"));
+    }
+
+    #[test_case("project/alpha", "project", Some(7) ; "nested")]
+    #[test_case("#Project/Alpha", "PROJECT/alpha", Some(14) ; "other case")]
+    #[test_case("cafe\u{301}/menu", "café", Some(6) ; "decomposed")]
+    #[test_case("projects", "project", None ; "only a prefix")]
+    fn measures_tag_matches(tag: &str, wanted: &str, expected: Option<usize>) {
+        assert_eq!(
+            MatchSettings::default().tag_match_len(tag, wanted),
+            expected
+        );
+    }
+
+    #[test]
+    fn composes_decomposed_text() {
+        assert_eq!(compose(DECOMPOSED), "café");
+        assert!(matches!(compose("café"), Cow::Borrowed(_)));
+        assert!(same(DECOMPOSED, "café"));
+    }
+
+    #[test_case(false, "Café", "café" ; "keeps diacritics")]
+    #[test_case(true, "Café", "cafe" ; "strips diacritics")]
+    #[test_case(true, DECOMPOSED, "cafe" ; "strips decomposed diacritics")]
+    #[test_case(true, "Crème Brûlée", "creme brulee" ; "several diacritics")]
+    #[test_case(true, "한국어", "한국어" ; "keeps hangul")]
+    fn folds_text(ignore_diacritics: bool, text: &str, expected: &str) {
+        let settings = MatchSettings {
+            ignore_diacritics,
+            ..Default::default()
+        };
+        assert_eq!(settings.fold(text), expected);
+    }
+
+    #[test_case(Case::Smart, "rust", false)]
+    #[test_case(Case::Smart, "Rust", true)]
+    #[test_case(Case::Smart, "été", false ; "smart lowercase accents")]
+    #[test_case(Case::Smart, "Été", true ; "smart uppercase accents")]
+    #[test_case(Case::Sensitive, "rust", true)]
+    #[test_case(Case::Insensitive, "Rust", false)]
+    fn decides_case_sensitivity(case: Case, typed: &str, expected: bool) {
+        assert_eq!(case.is_sensitive(typed), expected);
+    }
+}
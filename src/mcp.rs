@@ -0,0 +1,244 @@
+//! A Model Context Protocol server for `obx mcp`, letting assistants search,
+//! read and create notes in one vault. Messages are JSON-RPC, one per line;
+//! each tool call is logged to stderr, which MCP clients keep as the
+//! server's log.
+
+use crate::{
+    cache::MetadataCache,
+    cli_config::Vault,
+    hooks::{self, Event},
+    note_text::NoteText,
+    resolve, scan,
+    search_query::SearchQuery,
+    util::vault_note_path,
+};
+use anyhow::bail;
+use chrono::Local;
+use serde_json::{json, Value};
+use std::fs;
+
+/// The newest first, the one answered with when a client asks for another
+const PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+
+/// How many results `search_notes` gives without a `limit`
+const DEFAULT_SEARCH_LIMIT: u64 = 20;
+
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+pub struct McpServer {
+    vault: Vault,
+}
+
+impl McpServer {
+    pub fn new(vault: Vault) -> Self {
+        Self { vault }
+    }
+
+    /// Answer one message, or `None` for notifications, which get no reply
+    pub fn handle(&self, message: &str) -> Option<Value> {
+        let Ok(message) = serde_json::from_str::<Value>(message) else {
+            return Some(error(Value::Null, PARSE_ERROR, "Invalid JSON"));
+        };
+        let id = message.get("id").cloned()?;
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        let result = match message["method"].as_str().unwrap_or_default() {
+            "initialize" => Ok(self.initialize(&params)),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": tools() })),
+            "tools/call" => self.call_tool(&params),
+            method => Err((METHOD_NOT_FOUND, format!("Unknown method `{method}`"))),
+        };
+
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => error(id, code, &message),
+        })
+    }
+
+    fn initialize(&self, params: &Value) -> Value {
+        let asked = params["protocolVersion"].as_str().unwrap_or_default();
+        let version = PROTOCOL_VERSIONS
+            .iter()
+            .find(|version| **version == asked)
+            .unwrap_or(&PROTOCOL_VERSIONS[0]);
+
+        json!({
+            "protocolVersion": version,
+            "capabilities": { "tools": {} },
+            "serverInfo": { "name": "obx", "version": env!("CARGO_PKG_VERSION") },
+            "instructions": format!("Notes in the Obsidian vault `{}`", self.vault.name),
+        })
+    }
+
+    fn call_tool(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let name = params["name"].as_str().unwrap_or_default();
+        let arguments = &params["arguments"];
+        let outcome = match name {
+            "search_notes" => self.search_notes(arguments),
+            "read_note" => self.read_note(arguments),
+            "create_note" => self.create_note(arguments),
+            "list_tags" => self.list_tags(),
+            _ => return Err((INVALID_PARAMS, format!("Unknown tool `{name}`"))),
+        };
+
+        eprintln!(
+            "{} {name} {arguments} {}",
+            Local::now().format("%Y-%m-%d %H:%M:%S"),
+            if outcome.is_ok() { "ok" } else { "failed" }
+        );
+        let (text, is_error) = match outcome {
+            Ok(text) => (text, false),
+            Err(err) => (format!("{err:#}"), true),
+        };
+        Ok(json!({
+            "content": [{ "type": "text", "text": text }],
+            "isError": is_error,
+        }))
+    }
+
+    fn search_notes(&self, arguments: &Value) -> anyhow::Result<String> {
+        let text = string_argument(arguments, "query")?;
+        let limit = arguments["limit"].as_u64().unwrap_or(DEFAULT_SEARCH_LIMIT) as usize;
+        let query = SearchQuery::parse(text)?;
+
+        let results = query.search(&self.vault.path)?;
+        if results.is_empty() {
+            return Ok(format!("No notes match `{text}`"));
+        }
+        let lines: Vec<String> = results
+            .iter()
+            .take(limit)
+            .map(|result| {
+                let snippet = NoteText::read(&self.vault.path.join(&result.path))
+                    .ok()
+                    .and_then(|contents| query.snippet(&contents));
+                match snippet {
+                    Some(snippet) => format!("{}:{}: {}", result.path, snippet.line, snippet.text),
+                    None => result.path.clone(),
+                }
+            })
+            .collect();
+        Ok(lines.join("\n"))
+    }
+
+    fn read_note(&self, arguments: &Value) -> anyhow::Result<String> {
+        let note = string_argument(arguments, "note")?;
+        vault_note_path(note, &self.vault.path)?;
+
+        let mut matches = resolve::find_notes(note, &self.vault.path)?;
+        let note_path = match matches.len() {
+            0 => bail!("No note matching `{note}` found in vault"),
+            1 => matches.remove(0),
+            _ => bail!(
+                "Multiple notes match `{note}`, read one by its path:\n{}",
+                matches
+                    .iter()
+                    .map(|path| scan::relative_note_path(path, &self.vault.path))
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            ),
+        };
+        Ok(fs::read_to_string(note_path)?)
+    }
+
+    /// Only ever makes new notes, so nothing already in the vault is lost
+    fn create_note(&self, arguments: &Value) -> anyhow::Result<String> {
+        let path = string_argument(arguments, "path")?;
+        let content = arguments["content"].as_str().unwrap_or_default();
+        let note_path = vault_note_path(path, &self.vault.path)?;
+        if note_path.exists() {
+            bail!(
+                "{} already exists",
+                scan::relative_note_path(&note_path, &self.vault.path)
+            );
+        }
+
+        if let Some(parent) = note_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&note_path, content)?;
+        hooks::run(Event::AfterCreate, &self.vault, &note_path, None)?;
+
+        Ok(format!(
+            "Created {}",
+            scan::relative_note_path(&note_path, &self.vault.path)
+        ))
+    }
+
+    fn list_tags(&self) -> anyhow::Result<String> {
+        let counts = MetadataCache::current(&self.vault.path)?.tag_counts();
+        if counts.is_empty() {
+            return Ok("No tags in the vault".to_string());
+        }
+        Ok(counts
+            .iter()
+            .map(|(tag, count)| format!("#{tag} ({count})"))
+            .collect::<Vec<String>>()
+            .join("\n"))
+    }
+}
+
+fn string_argument<'a>(arguments: &'a Value, name: &str) -> anyhow::Result<&'a str> {
+    match arguments[name].as_str() {
+        Some(value) => Ok(value),
+        None => bail!("Missing the `{name}` argument"),
+    }
+}
+
+fn error(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+fn tools() -> Value {
+    json!([
+        {
+            "name": "search_notes",
+            "description": "Search the vault's notes, returning each match's path with the first matching line. \
+                Every word must appear in a note; `\"exact phrase\"`, `-excluded`, `tag:#project`, \
+                `path:Folder/`, `OR` and parentheses refine the query.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "description": "What to search for" },
+                    "limit": { "type": "integer", "description": "Return at most this many notes, 20 by default" },
+                },
+                "required": ["query"],
+            },
+        },
+        {
+            "name": "read_note",
+            "description": "Read a note's full markdown, including its frontmatter.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "note": { "type": "string", "description": "The note's vault-relative path or its name" },
+                },
+                "required": ["note"],
+            },
+        },
+        {
+            "name": "create_note",
+            "description": "Create a new note. Fails rather than overwrite a note that already exists.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "Vault-relative path, `.md` is added when left out" },
+                    "content": { "type": "string", "description": "The note's markdown" },
+                },
+                "required": ["path", "content"],
+            },
+        },
+        {
+            "name": "list_tags",
+            "description": "List every tag in the vault with how many notes carry it, most used first.",
+            "inputSchema": { "type": "object", "properties": {} },
+        },
+    ])
+}
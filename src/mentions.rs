@@ -0,0 +1,270 @@
+//! Unlinked mentions: places where a note is named in another note's text,
+//! by its name or one of its aliases, without being linked to, like the
+//! unlinked mentions under Obsidian's backlinks. Names are matched
+//! regardless of case, only as whole words, and never inside links, code or
+//! frontmatter.
+
+use crate::{link_resolver::LinkResolver, matching, scan};
+use anyhow::{bail, Context};
+use libobsidian::{
+    links::{parse_links, replace_ranges, LinkKind},
+    split_frontmatter, ObsidianNote,
+};
+use regex::{Regex, RegexBuilder};
+use std::{collections::BTreeMap, fs, ops::Range, path::Path};
+
+/// A note named in another note without a link
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mention {
+    /// The vault-relative path of the note it's in
+    pub source: String,
+    /// Counting from 1
+    pub line: usize,
+    /// Where it is in the note's contents, in bytes
+    pub range: Range<usize>,
+    /// The name as written
+    pub text: String,
+    /// The line it's on, trimmed
+    pub context: String,
+    /// Where the mention starts in `context`, in bytes
+    pub column: usize,
+}
+
+/// The names a note is mentioned by: its file name without `.md` and its
+/// aliases
+pub fn names(note_path: &Path) -> Vec<String> {
+    let mut names: Vec<String> = note_path
+        .file_stem()
+        .map(|stem| matching::compose(&stem.to_string_lossy()).into_owned())
+        .into_iter()
+        .collect();
+    if let Ok(note) = ObsidianNote::read_from_path(note_path) {
+        for alias in note.aliases() {
+            if !names.contains(&alias) {
+                names.push(alias);
+            }
+        }
+    }
+    names
+}
+
+/// Every unlinked mention of the note at `note_path` in the vault's other
+/// notes, in path order
+pub fn find(vault_path: &Path, note_path: &Path) -> anyhow::Result<Vec<Mention>> {
+    let Some(pattern) = pattern(&names(note_path)) else {
+        return Ok(Vec::new());
+    };
+
+    let mut mentions = Vec::new();
+    let mut sources = scan::note_paths(vault_path)?;
+    sources.sort();
+    for source in sources {
+        if source == note_path {
+            continue;
+        }
+        let Ok(contents) = fs::read_to_string(&source) else {
+            continue;
+        };
+        let relative = scan::relative_note_path(&source, vault_path);
+        for (line, range) in find_in(&contents, &pattern) {
+            let line_start = contents[..range.start].rfind('\n').map_or(0, |idx| idx + 1);
+            let line_end = contents[range.end..]
+                .find('\n')
+                .map_or(contents.len(), |idx| range.end + idx);
+            let whole_line = &contents[line_start..line_end];
+            let context = whole_line.trim();
+            let indent = whole_line.len() - whole_line.trim_start().len();
+            mentions.push(Mention {
+                source: relative.clone(),
+                line,
+                text: contents[range.clone()].to_string(),
+                context: context.to_string(),
+                column: range.start - line_start - indent,
+                range,
+            });
+        }
+    }
+    Ok(mentions)
+}
+
+/// Turn `mentions` into wikilinks to the note at `note_path`, keeping what
+/// was written as each link's text when it isn't the link itself. Nothing is
+/// written to a note that changed since its mentions were found.
+pub fn link(vault_path: &Path, note_path: &Path, mentions: &[Mention]) -> anyhow::Result<()> {
+    let mut by_source: BTreeMap<&str, Vec<&Mention>> = BTreeMap::new();
+    for mention in mentions {
+        by_source.entry(&mention.source).or_default().push(mention);
+    }
+
+    let resolver = LinkResolver::new(vault_path)?;
+    for (relative, mentions) in by_source {
+        let source = vault_path.join(relative);
+        let contents = fs::read_to_string(&source)
+            .with_context(|| format!("Could not read note {relative}"))?;
+        if mentions
+            .iter()
+            .any(|mention| contents.get(mention.range.clone()) != Some(mention.text.as_str()))
+        {
+            bail!("{relative} changed since its mentions were found");
+        }
+
+        let target = resolver.link_target(note_path, &source, LinkKind::Wikilink);
+        let replacements = mentions
+            .iter()
+            .map(|mention| {
+                let link = if mention.text == target {
+                    format!("[[{target}]]")
+                } else {
+                    format!("[[{target}|{}]]", mention.text)
+                };
+                (mention.range.clone(), link)
+            })
+            .collect();
+        fs::write(&source, replace_ranges(&contents, replacements))
+            .with_context(|| format!("Could not write note {relative}"))?;
+    }
+    Ok(())
+}
+
+/// A case-insensitive pattern matching any of `names`, longest first so a
+/// longer name wins over one it starts with
+fn pattern(names: &[String]) -> Option<Regex> {
+    let mut names: Vec<&String> = names
+        .iter()
+        .filter(|name| !name.trim().is_empty())
+        .collect();
+    if names.is_empty() {
+        return None;
+    }
+    names.sort_by_key(|name| std::cmp::Reverse(name.len()));
+    let alternatives: Vec<String> = names.iter().map(|name| regex::escape(name)).collect();
+    RegexBuilder::new(&alternatives.join("|"))
+        .case_insensitive(true)
+        .build()
+        .ok()
+}
+
+/// The line and byte range of each whole-word match of `pattern` in
+/// `contents`, outside frontmatter, code and links
+fn find_in(contents: &str, pattern: &Regex) -> Vec<(usize, Range<usize>)> {
+    let (_, body) = split_frontmatter(contents);
+    let body_start = contents.len() - body.len();
+    let links: Vec<Range<usize>> = parse_links(contents)
+        .into_iter()
+        .map(|link| link.range)
+        .collect();
+
+    let mut found = Vec::new();
+    let mut in_code_block = false;
+    let mut offset = 0;
+    for (idx, line) in contents.split_inclusive('\n').enumerate() {
+        let line_start = offset;
+        offset += line.len();
+        if line_start < body_start {
+            continue;
+        }
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+
+        let mut skipped = inline_code(line);
+        skipped.extend(
+            links
+                .iter()
+                .filter(|link| link.start < offset && line_start < link.end)
+                .map(|link| {
+                    link.start.saturating_sub(line_start)..(link.end - line_start).min(line.len())
+                }),
+        );
+        for matched in pattern.find_iter(line) {
+            let range = matched.range();
+            let overlaps = skipped
+                .iter()
+                .any(|span| span.start < range.end && range.start < span.end);
+            if !overlaps && is_whole_word(line, &range) {
+                found.push((idx + 1, line_start + range.start..line_start + range.end));
+            }
+        }
+    }
+    found
+}
+
+/// Whether the text at `range` isn't part of a longer word
+fn is_whole_word(line: &str, range: &Range<usize>) -> bool {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    !line[..range.start].chars().next_back().is_some_and(is_word)
+        && !line[range.end..].chars().next().is_some_and(is_word)
+}
+
+/// The byte ranges of the line's inline code spans
+fn inline_code(line: &str) -> Vec<Range<usize>> {
+    let mut spans = Vec::new();
+    let mut open = None;
+    for (idx, c) in line.char_indices() {
+        if c == '`' {
+            match open.take() {
+                Some(start) => spans.push(start..idx + 1),
+                None => open = Some(idx),
+            }
+        }
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::{prelude::*, TempDir};
+
+    fn matches<'a>(contents: &'a str, names: &[&str]) -> Vec<&'a str> {
+        let names: Vec<String> = names.iter().map(|name| name.to_string()).collect();
+        find_in(contents, &pattern(&names).unwrap())
+            .into_iter()
+            .map(|(_, range)| &contents[range])
+            .collect()
+    }
+
+    #[test]
+    fn finds_whole_words_outside_links_and_code() {
+        let contents =
+            "---\ntitle: Rust\n---\nrust and [[Rust]] and `rust`\nRusty, Rust.\n```\nrust\n```\n";
+
+        assert_eq!(matches(contents, &["Rust"]), ["rust", "Rust"]);
+    }
+
+    #[test]
+    fn prefers_the_longest_name() {
+        assert_eq!(
+            matches("The Rust Book and Rust", &["Rust", "Rust Book"]),
+            ["Rust Book", "Rust"]
+        );
+    }
+
+    #[test]
+    fn links_mentions() {
+        let dir = TempDir::new().unwrap();
+        dir.child("Rust.md")
+            .write_str("---\naliases: [Ferris]\n---\n")
+            .unwrap();
+        dir.child("notes/daily.md")
+            .write_str("Learning rust with Ferris")
+            .unwrap();
+        let target = dir.child("Rust.md").to_path_buf();
+
+        let mentions = find(dir.path(), &target).unwrap();
+        assert_eq!(
+            mentions.iter().map(|m| m.text.as_str()).collect::<Vec<_>>(),
+            ["rust", "Ferris"]
+        );
+        assert_eq!(mentions[0].context, "Learning rust with Ferris");
+
+        link(dir.path(), &target, &mentions).unwrap();
+        dir.child("notes/daily.md")
+            .assert("Learning [[Rust|rust]] with [[Rust|Ferris]]");
+        assert!(link(dir.path(), &target, &mentions[..1]).is_err());
+    }
+}
@@ -0,0 +1,129 @@
+//! A note's contents for searching and indexing. Notes of a megabyte or more,
+//! like exported logs or transcripts, are memory-mapped rather than copied
+//! into memory by one-shot commands, and callers walk them a line at a time.
+//! Processes that keep running, like `obx serve` or the TUI, read every note
+//! through a buffer instead, see [`avoid_mapping`].
+
+use memmap2::Mmap;
+use std::{
+    fs::File,
+    io::{self, BufReader, Read},
+    path::Path,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// Notes at least this big are mapped instead of read
+const MAP_THRESHOLD: u64 = 1024 * 1024;
+
+/// Set by processes that keep running, which never map notes
+static AVOID_MAPPING: AtomicBool = AtomicBool::new(false);
+
+/// Read notes through a buffer from now on rather than mapping them. A
+/// mapped note that's truncated while it's read kills the process with
+/// `SIGBUS`, which is a risk worth taking for a one-shot `obx grep` but not
+/// for a server or the TUI that could be running while notes are edited.
+pub fn avoid_mapping() {
+    AVOID_MAPPING.store(true, Ordering::Relaxed);
+}
+
+pub struct NoteText(Contents);
+
+enum Contents {
+    Read(String),
+    /// Checked to be UTF-8 when mapped
+    Mapped(Mmap),
+}
+
+impl NoteText {
+    /// The note at `path`, failing like [`std::fs::read_to_string`] when it
+    /// isn't valid UTF-8
+    pub fn read(path: &Path) -> io::Result<Self> {
+        Self::read_with(path, !AVOID_MAPPING.load(Ordering::Relaxed))
+    }
+
+    fn read_with(path: &Path, may_map: bool) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let len = file.metadata()?.len();
+        if len < MAP_THRESHOLD || !may_map {
+            let mut text = String::with_capacity(usize::try_from(len).unwrap_or_default());
+            BufReader::new(file).read_to_string(&mut text)?;
+            return Ok(Self::from(text));
+        }
+
+        // Safety: this is only sound while nothing else writes to the note.
+        // One that's truncated while mapped raises `SIGBUS` when the missing
+        // pages are read, and one that's rewritten can stop being the UTF-8
+        // checked here. Only one-shot commands map notes, accepting that
+        // like other search tools do, see `avoid_mapping`.
+        let map = unsafe { Mmap::map(&file)? };
+        std::str::from_utf8(&map).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+        Ok(Self(Contents::Mapped(map)))
+    }
+
+    pub fn as_str(&self) -> &str {
+        match &self.0 {
+            Contents::Read(text) => text,
+            // Safety: checked to be UTF-8 when mapped, see `read`
+            Contents::Mapped(map) => unsafe { std::str::from_utf8_unchecked(map) },
+        }
+    }
+
+    /// The lines of the note, without their line endings
+    pub fn lines(&self) -> std::str::Lines<'_> {
+        self.as_str().lines()
+    }
+
+    pub fn is_mapped(&self) -> bool {
+        matches!(self.0, Contents::Mapped(_))
+    }
+}
+
+impl From<String> for NoteText {
+    fn from(text: String) -> Self {
+        Self(Contents::Read(text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::{prelude::*, TempDir};
+
+    #[test]
+    fn maps_large_notes() {
+        let dir = TempDir::new().unwrap();
+        let small = dir.child("small.md");
+        small.write_str("one\r\ntwo").unwrap();
+        let large = dir.child("large.md");
+        large
+            .write_str(&"log line\n".repeat(MAP_THRESHOLD as usize / 9 + 1))
+            .unwrap();
+
+        let small = NoteText::read(small.path()).unwrap();
+        assert!(!small.is_mapped());
+        assert_eq!(small.lines().collect::<Vec<_>>(), ["one", "two"]);
+
+        let mapped = NoteText::read(large.path()).unwrap();
+        assert!(mapped.is_mapped());
+        assert!(mapped.lines().all(|line| line == "log line"));
+
+        let read = NoteText::read_with(large.path(), false).unwrap();
+        assert!(!read.is_mapped());
+        assert_eq!(read.as_str(), mapped.as_str());
+    }
+
+    #[test]
+    fn rejects_invalid_text() {
+        let dir = TempDir::new().unwrap();
+        let note = dir.child("binary.md");
+        let mut bytes = vec![b'a'; MAP_THRESHOLD as usize];
+        bytes.push(0xff);
+        note.write_binary(&bytes).unwrap();
+
+        assert_eq!(
+            NoteText::read(note.path()).err().unwrap().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+}
@@ -0,0 +1,129 @@
+//! `obsidian://` URIs, which the Obsidian app opens notes from. The app
+//! knows vaults by their folder's name rather than obx's name for them.
+//! Pointing at a heading or block, or writing to the note, needs the
+//! Advanced URI community plugin's `obsidian://advanced-uri` instead.
+
+use crate::{cli_config, scan};
+use clap::ValueEnum;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use std::path::Path;
+
+/// Everything but what JavaScript's `encodeURIComponent` leaves alone, which
+/// is how the app expects values to be encoded
+const COMPONENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'!')
+    .remove(b'~')
+    .remove(b'*')
+    .remove(b'\'')
+    .remove(b'(')
+    .remove(b')');
+
+/// The URI opening `note_path` in the vault at `vault_path`
+pub fn open_uri(vault_path: &Path, note_path: &Path) -> String {
+    format!(
+        "obsidian://open?vault={}&file={}",
+        encode(&cli_config::vault_name(vault_path)),
+        encode(&scan::relative_note_path(note_path, vault_path))
+    )
+}
+
+/// How an Advanced URI writes its `data` to the note
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum WriteMode {
+    Append,
+    Prepend,
+    Overwrite,
+    /// Into a new note, numbered when the name is taken
+    New,
+}
+
+impl WriteMode {
+    fn as_str(self) -> &'static str {
+        match self {
+            WriteMode::Append => "append",
+            WriteMode::Prepend => "prepend",
+            WriteMode::Overwrite => "overwrite",
+            WriteMode::New => "new",
+        }
+    }
+}
+
+/// Where in a note an Advanced URI goes and what it writes there
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Target {
+    /// The heading's text, without `#`s
+    pub heading: Option<String>,
+    /// The block's ID, without `^`
+    pub block: Option<String>,
+    pub mode: Option<WriteMode>,
+    pub data: Option<String>,
+}
+
+impl Target {
+    /// Whether the plain `open` URI is enough
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+/// The Advanced URI opening `note_path` at `target`
+pub fn advanced_uri(vault_path: &Path, note_path: &Path, target: &Target) -> String {
+    let mut uri = format!(
+        "obsidian://advanced-uri?vault={}&filepath={}",
+        encode(&cli_config::vault_name(vault_path)),
+        encode(&scan::relative_note_path(note_path, vault_path))
+    );
+    let params = [
+        ("heading", target.heading.as_deref()),
+        ("block", target.block.as_deref()),
+        ("mode", target.mode.map(WriteMode::as_str)),
+        ("data", target.data.as_deref()),
+    ];
+    for (name, value) in params {
+        if let Some(value) = value {
+            uri.push_str(&format!("&{name}={}", encode(value)));
+        }
+    }
+    uri
+}
+
+fn encode(value: &str) -> String {
+    utf8_percent_encode(value, COMPONENT).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_vault_and_file() {
+        assert_eq!(
+            open_uri(
+                Path::new("/home/me/My Vault"),
+                Path::new("/home/me/My Vault/Projects/Plan & notes.md")
+            ),
+            "obsidian://open?vault=My%20Vault&file=Projects%2FPlan%20%26%20notes.md"
+        );
+    }
+
+    #[test]
+    fn adds_advanced_targets() {
+        let target = Target {
+            heading: Some("Next steps".to_string()),
+            mode: Some(WriteMode::Append),
+            data: Some("- [ ] Call Sam".to_string()),
+            ..Target::default()
+        };
+        assert_eq!(
+            advanced_uri(
+                Path::new("/vault"),
+                Path::new("/vault/plan.md"),
+                &target
+            ),
+            "obsidian://advanced-uri?vault=vault&filepath=plan.md&heading=Next%20steps&mode=append&data=-%20%5B%20%5D%20Call%20Sam"
+        );
+    }
+}
@@ -0,0 +1,67 @@
+//! `--limit`, `--offset` and `--page` for commands printing lists, so scripts
+//! can walk through a large vault a page at a time. The lists are sorted with
+//! ties broken by path, so pages neither overlap nor skip anything.
+
+use clap::Args;
+
+#[derive(Args, Debug, Clone, Default)]
+pub struct Paging {
+    /// Print at most this many results
+    #[arg(long, short = 'n')]
+    limit: Option<usize>,
+
+    /// Skip this many results before printing
+    #[arg(long, conflicts_with = "page")]
+    offset: Option<usize>,
+
+    /// Print this page of `--limit` results, counting from 1
+    #[arg(long, requires = "limit", value_parser = clap::value_parser!(u64).range(1..))]
+    page: Option<u64>,
+}
+
+impl Paging {
+    /// The items on the requested page
+    pub fn apply<T>(&self, items: Vec<T>) -> Vec<T> {
+        items
+            .into_iter()
+            .skip(self.skip())
+            .take(self.limit.unwrap_or(usize::MAX))
+            .collect()
+    }
+
+    fn skip(&self) -> usize {
+        match self.page {
+            Some(page) => usize::try_from(page - 1)
+                .unwrap_or(usize::MAX)
+                .saturating_mul(self.limit.unwrap_or(0)),
+            None => self.offset.unwrap_or(0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case(None, None, None, &[1, 2, 3, 4, 5] ; "everything")]
+    #[test_case(Some(2), None, None, &[1, 2] ; "limit")]
+    #[test_case(Some(2), Some(3), None, &[4, 5] ; "offset")]
+    #[test_case(None, Some(4), None, &[5] ; "offset alone")]
+    #[test_case(Some(2), None, Some(2), &[3, 4] ; "page")]
+    #[test_case(Some(2), None, Some(4), &[] ; "past the end")]
+    fn pages_through_items(
+        limit: Option<usize>,
+        offset: Option<usize>,
+        page: Option<u64>,
+        expected: &[u32],
+    ) {
+        let paging = Paging {
+            limit,
+            offset,
+            page,
+        };
+
+        assert_eq!(paging.apply(vec![1, 2, 3, 4, 5]), expected);
+    }
+}
@@ -0,0 +1,114 @@
+use crate::{
+    cli_config, embeds,
+    html::{link_label, markdown_link},
+    link_resolver::LinkResolver,
+    scan,
+    util::find_executable,
+};
+use anyhow::{anyhow, bail, Context};
+use libobsidian::links::{parse_links, replace_ranges};
+use std::{
+    env,
+    ffi::OsString,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+/// The pandoc executable, from `pandoc` in the config or found on `$PATH`
+pub fn find_pandoc() -> anyhow::Result<PathBuf> {
+    if let Some(pandoc) = cli_config::read()?.pandoc {
+        return Ok(pandoc);
+    }
+
+    find_executable("pandoc")
+        .ok_or_else(|| anyhow!("pandoc not found, install it or set `pandoc` in your config"))
+}
+
+/// Convert a note's body to markdown pandoc understands: note embeds are
+/// [expanded](embeds::expand), links to other notes become their display
+/// text, and attachment links point at vault-relative paths (pass the vault
+/// as a resource path)
+pub fn standard_markdown(
+    body: &str,
+    note_path: &Path,
+    vault_path: &Path,
+    resolver: &LinkResolver,
+) -> String {
+    let body = embeds::expand(body, note_path, resolver);
+    let mut replacements = Vec::new();
+
+    for link in parse_links(&body) {
+        if link.is_external() {
+            continue;
+        }
+
+        let label = link_label(&link);
+        let replacement = match resolver.resolve(&link, note_path) {
+            Some(target) if !scan::is_markdown(&target) => {
+                let relative = scan::relative_note_path(&target, vault_path);
+                markdown_link(&link, link.embed, &relative, None, &label)
+            }
+            _ => label,
+        };
+
+        replacements.push((link.range.clone(), replacement));
+    }
+
+    replace_ranges(&body, replacements)
+}
+
+/// Options passed through to pandoc
+pub struct PdfOptions<'a> {
+    pub title: &'a str,
+    pub template: Option<&'a Path>,
+    pub margin: Option<&'a str>,
+}
+
+/// Run pandoc on `markdown`, writing a PDF to `output`. Images and other
+/// resources are looked up relative to each of `resource_paths`.
+pub fn markdown_to_pdf(
+    pandoc: &Path,
+    markdown: &str,
+    output: &Path,
+    resource_paths: &[&Path],
+    options: &PdfOptions,
+) -> anyhow::Result<()> {
+    let resource_path =
+        env::join_paths(resource_paths).context("Could not build the pandoc resource path")?;
+    let mut resource_arg = OsString::from("--resource-path=");
+    resource_arg.push(resource_path);
+
+    let mut command = Command::new(pandoc);
+    command
+        .args(["--from", "markdown", "--output"])
+        .arg(output)
+        .arg(resource_arg)
+        .args(["--metadata", &format!("title={}", options.title)]);
+
+    if let Some(template) = options.template {
+        command.arg("--template").arg(template);
+    }
+    if let Some(margin) = options.margin {
+        command.args(["--variable", &format!("geometry:margin={margin}")]);
+    }
+
+    let mut child = command
+        .stdin(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to run pandoc at {}", pandoc.display()))?;
+
+    child
+        .stdin
+        .take()
+        .expect("pandoc stdin should be piped")
+        .write_all(markdown.as_bytes())
+        .context("Failed to send the note to pandoc")?;
+
+    let status = child.wait().context("Failed to wait for pandoc")?;
+    if !status.success() {
+        bail!("pandoc failed with {status}");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,197 @@
+//! Daily and weekly notes, named with the `dates` formats like Obsidian's
+//! Daily notes and Periodic Notes plugins. Each can start with a line of
+//! links to the notes before and after it, `← [[2024-05-09]] | [[2024-05-11]] →`.
+
+use crate::{cli_config, dates::DateFormats};
+use chrono::{DateTime, Datelike, Days, Local, NaiveDate, TimeZone};
+use libobsidian::split_frontmatter;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct PeriodicSettings {
+    /// Vault-relative folder daily and weekly notes go in, the vault's root
+    /// when unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub folder: Option<PathBuf>,
+    /// The template new daily notes are created from, by name within the
+    /// template folder
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub daily_template: Option<String>,
+    /// The template new weekly notes are created from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weekly_template: Option<String>,
+    /// Give new notes links to the notes before and after them, as if
+    /// `--link-neighbors` were always passed
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub link_neighbors: bool,
+}
+
+impl PeriodicSettings {
+    pub fn is_default(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+/// The config's settings, with its date formats
+pub fn settings() -> anyhow::Result<(PeriodicSettings, DateFormats)> {
+    let config = cli_config::read()?;
+    Ok((config.periodic, config.dates))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Period {
+    Day,
+    Week,
+}
+
+impl Period {
+    /// The first day of the period holding `date`
+    pub fn start(self, date: NaiveDate, formats: &DateFormats) -> NaiveDate {
+        match self {
+            Period::Day => date,
+            Period::Week => {
+                let offset = date.weekday().days_since(formats.first_day_of_week);
+                date - Days::new(offset.into())
+            }
+        }
+    }
+
+    /// The first days of the periods before and after the one starting on
+    /// `start`
+    pub fn neighbors(self, start: NaiveDate) -> (NaiveDate, NaiveDate) {
+        let days = Days::new(match self {
+            Period::Day => 1,
+            Period::Week => 7,
+        });
+        (start - days, start + days)
+    }
+
+    /// The name of the note for the period starting on `start`
+    pub fn name(self, start: NaiveDate, formats: &DateFormats) -> String {
+        let format = match self {
+            Period::Day => &formats.daily_note,
+            Period::Week => &formats.weekly_note,
+        };
+        formats.format(&at_noon(start), format)
+    }
+
+    pub fn template(self, settings: &PeriodicSettings) -> Option<&str> {
+        match self {
+            Period::Day => settings.daily_template.as_deref(),
+            Period::Week => settings.weekly_template.as_deref(),
+        }
+    }
+}
+
+/// Where the note for the period starting on `start` goes
+pub fn note_path(
+    vault_path: &Path,
+    settings: &PeriodicSettings,
+    period: Period,
+    start: NaiveDate,
+    formats: &DateFormats,
+) -> PathBuf {
+    let folder = settings.folder.clone().unwrap_or_default();
+    vault_path
+        .join(folder)
+        .join(format!("{}.md", period.name(start, formats)))
+}
+
+/// The navigation line for the note of the period starting on `start`
+pub fn navigation(period: Period, start: NaiveDate, formats: &DateFormats) -> String {
+    let (previous, next) = period.neighbors(start);
+    format!(
+        "← [[{}]] | [[{}]] →",
+        period.name(previous, formats),
+        period.name(next, formats)
+    )
+}
+
+/// `contents` with `navigation` as the first line of the body, in place of
+/// a navigation line already there
+pub fn set_navigation(contents: &str, navigation: &str) -> String {
+    let body = split_frontmatter(contents).1;
+    let frontmatter = &contents[..contents.len() - body.len()];
+    let body = match body.split_once('\n') {
+        Some((first, rest)) if is_navigation(first) => rest,
+        None if is_navigation(body) => "",
+        _ => body,
+    };
+
+    match body.trim_start_matches(['\r', '\n']) {
+        "" => format!("{frontmatter}{navigation}\n"),
+        body => format!("{frontmatter}{navigation}\n\n{body}"),
+    }
+}
+
+fn is_navigation(line: &str) -> bool {
+    let line = line.trim();
+    line.starts_with("← [[") && line.ends_with("]] →")
+}
+
+/// Midday on `date`, so a clock change that day can't skip it
+pub fn at_noon(date: NaiveDate) -> DateTime<Local> {
+    let noon = date.and_hms_opt(12, 0, 0).expect("noon is a valid time");
+    Local
+        .from_local_datetime(&noon)
+        .earliest()
+        .unwrap_or_else(|| Local.from_utc_datetime(&noon))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Weekday;
+    use indoc::indoc;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn names_neighboring_periods() {
+        let formats = DateFormats::default();
+        let friday = date(2024, 5, 10);
+
+        assert_eq!(
+            navigation(Period::Day, friday, &formats),
+            "← [[2024-05-09]] | [[2024-05-11]] →"
+        );
+        let monday = Period::Week.start(friday, &formats);
+        assert_eq!(monday, date(2024, 5, 6));
+        assert_eq!(
+            navigation(Period::Week, monday, &formats),
+            "← [[2024-W18]] | [[2024-W20]] →"
+        );
+
+        let sunday_start = DateFormats {
+            first_day_of_week: Weekday::Sun,
+            ..Default::default()
+        };
+        assert_eq!(Period::Week.start(friday, &sunday_start), date(2024, 5, 5));
+    }
+
+    #[test]
+    fn inserts_and_updates_navigation() {
+        let navigation = "← [[2024-05-09]] | [[2024-05-11]] →";
+
+        assert_eq!(set_navigation("", navigation), format!("{navigation}\n"));
+        assert_eq!(
+            set_navigation("---\nmood: good\n---\n# Friday\n", navigation),
+            format!("---\nmood: good\n---\n{navigation}\n\n# Friday\n")
+        );
+        assert_eq!(
+            set_navigation(
+                indoc! {"
+                    ← [[2024-05-08]] | [[2024-05-10]] →
+
+                    # Friday
+                "},
+                navigation
+            ),
+            format!("{navigation}\n\n# Friday\n")
+        );
+    }
+}
@@ -0,0 +1,407 @@
+use anyhow::{bail, Context};
+use chrono::NaiveDate;
+use libobsidian::ObsidianNote;
+use std::cmp::Ordering;
+
+/// A parsed frontmatter filter such as `status == "active" && due < 2025-01-01`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare(Operand, CompareOp, Operand),
+    /// A bare operand, true when it's set to something other than `false`,
+    /// `null`, `0`, an empty string or an empty list
+    Truthy(Operand),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+    /// List membership, or substring for strings
+    Contains,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
+    Field(String),
+    Literal(Value),
+}
+
+/// A frontmatter value as seen by queries. Strings shaped like `YYYY-MM-DD`
+/// are treated as dates so they compare chronologically.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Date(NaiveDate),
+    List(Vec<Value>),
+}
+
+impl Value {
+    pub fn from_yaml(yaml: &serde_yaml::Value) -> Self {
+        match yaml {
+            serde_yaml::Value::Null => Value::Null,
+            serde_yaml::Value::Bool(b) => Value::Bool(*b),
+            serde_yaml::Value::Number(n) => n.as_f64().map(Value::Number).unwrap_or(Value::Null),
+            serde_yaml::Value::String(s) => Value::from_string(s),
+            serde_yaml::Value::Sequence(seq) => {
+                Value::List(seq.iter().map(Value::from_yaml).collect())
+            }
+            serde_yaml::Value::Mapping(_) => Value::Null,
+            serde_yaml::Value::Tagged(tagged) => Value::from_yaml(&tagged.value),
+        }
+    }
+
+    fn from_string(s: &str) -> Self {
+        match NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+            Ok(date) => Value::Date(date),
+            Err(_) => Value::String(s.to_string()),
+        }
+    }
+
+    fn is_truthy(&self) -> bool {
+        match self {
+            Value::Null => false,
+            Value::Bool(b) => *b,
+            Value::Number(n) => *n != 0.0,
+            Value::String(s) => !s.is_empty(),
+            Value::Date(_) => true,
+            Value::List(items) => !items.is_empty(),
+        }
+    }
+
+    /// Ordering between values of the same type, `None` when they can't be compared
+    fn compare(&self, other: &Value) -> Option<Ordering> {
+        match (self, other) {
+            (Value::Number(a), Value::Number(b)) => a.partial_cmp(b),
+            (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+            (Value::Date(a), Value::Date(b)) => Some(a.cmp(b)),
+            (Value::Bool(a), Value::Bool(b)) => Some(a.cmp(b)),
+            _ => None,
+        }
+    }
+
+    fn contains(&self, needle: &Value) -> bool {
+        match (self, needle) {
+            (Value::List(items), needle) => items.contains(needle),
+            (Value::String(haystack), Value::String(needle)) => haystack.contains(needle.as_str()),
+            _ => false,
+        }
+    }
+}
+
+impl Expr {
+    pub fn parse(input: &str) -> anyhow::Result<Self> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+        };
+        let expr = parser.or()?;
+
+        if let Some(token) = parser.peek() {
+            bail!("Unexpected {} in query", token.describe());
+        }
+
+        Ok(expr)
+    }
+
+    pub fn matches(&self, note: &ObsidianNote) -> bool {
+        match self {
+            Expr::And(a, b) => a.matches(note) && b.matches(note),
+            Expr::Or(a, b) => a.matches(note) || b.matches(note),
+            Expr::Not(expr) => !expr.matches(note),
+            Expr::Truthy(operand) => operand.value(note).is_truthy(),
+            Expr::Compare(left, op, right) => {
+                let (left, right) = (left.value(note), right.value(note));
+                match op {
+                    CompareOp::Eq => left == right,
+                    CompareOp::NotEq => left != right,
+                    CompareOp::Contains => left.contains(&right),
+                    CompareOp::Lt => left.compare(&right) == Some(Ordering::Less),
+                    CompareOp::LtEq => {
+                        matches!(left.compare(&right), Some(Ordering::Less | Ordering::Equal))
+                    }
+                    CompareOp::Gt => left.compare(&right) == Some(Ordering::Greater),
+                    CompareOp::GtEq => matches!(
+                        left.compare(&right),
+                        Some(Ordering::Greater | Ordering::Equal)
+                    ),
+                }
+            }
+        }
+    }
+}
+
+impl Operand {
+    fn value(&self, note: &ObsidianNote) -> Value {
+        match self {
+            Operand::Literal(value) => value.clone(),
+            Operand::Field(field) => field_value(note, field)
+                .map(|value| Value::from_yaml(&value))
+                .unwrap_or(Value::Null),
+        }
+    }
+}
+
+/// The value of a frontmatter property or Dataview inline field, or of one of
+/// the `file.*` fields describing the note itself
+pub fn field_value(note: &ObsidianNote, field: &str) -> Option<serde_yaml::Value> {
+    let file_field = |value: String| Some(serde_yaml::Value::String(value));
+
+    match field {
+        "file.name" => file_field(note.file_path.file_stem()?.to_string_lossy().into_owned()),
+        "file.tags" => Some(serde_yaml::Value::Sequence(
+            note.tags()
+                .into_iter()
+                .map(serde_yaml::Value::String)
+                .collect(),
+        )),
+        _ => note.field(field),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Literal(Value),
+    Op(CompareOp),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Ident(name) => format!("`{name}`"),
+            Token::Literal(value) => format!("value {value:?}"),
+            Token::Op(op) => format!("operator {op:?}"),
+            Token::And => "`&&`".to_string(),
+            Token::Or => "`||`".to_string(),
+            Token::Not => "`!`".to_string(),
+            Token::Open => "`(`".to_string(),
+            Token::Close => "`)`".to_string(),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> anyhow::Result<Vec<Token>> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut idx = 0;
+
+    while idx < chars.len() {
+        let c = chars[idx];
+        let next = chars.get(idx + 1).copied();
+
+        let (token, len) = match (c, next) {
+            (c, _) if c.is_whitespace() => {
+                idx += 1;
+                continue;
+            }
+            ('(', _) => (Token::Open, 1),
+            (')', _) => (Token::Close, 1),
+            ('&', Some('&')) => (Token::And, 2),
+            ('|', Some('|')) => (Token::Or, 2),
+            ('=', Some('=')) => (Token::Op(CompareOp::Eq), 2),
+            ('!', Some('=')) => (Token::Op(CompareOp::NotEq), 2),
+            ('<', Some('=')) => (Token::Op(CompareOp::LtEq), 2),
+            ('>', Some('=')) => (Token::Op(CompareOp::GtEq), 2),
+            ('!', _) => (Token::Not, 1),
+            ('<', _) => (Token::Op(CompareOp::Lt), 1),
+            ('>', _) => (Token::Op(CompareOp::Gt), 1),
+            ('"' | '\'', _) => {
+                let end = chars[idx + 1..]
+                    .iter()
+                    .position(|&other| other == c)
+                    .with_context(|| format!("Unterminated string in query at {}", idx + 1))?;
+                let text: String = chars[idx + 1..idx + 1 + end].iter().collect();
+                (Token::Literal(Value::String(text)), end + 2)
+            }
+            (c, _)
+                if c.is_ascii_digit() || (c == '-' && next.is_some_and(|n| n.is_ascii_digit())) =>
+            {
+                let len = 1 + chars[idx + 1..]
+                    .iter()
+                    .take_while(|c| c.is_ascii_digit() || matches!(c, '.' | '-'))
+                    .count();
+                let text: String = chars[idx..idx + len].iter().collect();
+                let value = match (NaiveDate::parse_from_str(&text, "%Y-%m-%d"), text.parse()) {
+                    (Ok(date), _) => Value::Date(date),
+                    (_, Ok(number)) => Value::Number(number),
+                    _ => bail!("Invalid number or date `{text}` in query"),
+                };
+                (Token::Literal(value), len)
+            }
+            (c, _) if is_ident_char(c) => {
+                let len = chars[idx..]
+                    .iter()
+                    .take_while(|c| is_ident_char(**c))
+                    .count();
+                let word: String = chars[idx..idx + len].iter().collect();
+                let token = match word.as_str() {
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    "not" => Token::Not,
+                    "contains" => Token::Op(CompareOp::Contains),
+                    "true" => Token::Literal(Value::Bool(true)),
+                    "false" => Token::Literal(Value::Bool(false)),
+                    "null" => Token::Literal(Value::Null),
+                    _ => Token::Ident(word),
+                };
+                (token, len)
+            }
+            (c, _) => bail!("Unexpected `{c}` in query at {}", idx + 1),
+        };
+
+        tokens.push(token);
+        idx += len;
+    }
+
+    Ok(tokens)
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.')
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn or(&mut self) -> anyhow::Result<Expr> {
+        let mut expr = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> anyhow::Result<Expr> {
+        let mut expr = self.unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.next();
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> anyhow::Result<Expr> {
+        match self.peek() {
+            Some(Token::Not) => {
+                self.next();
+                Ok(Expr::Not(Box::new(self.unary()?)))
+            }
+            Some(Token::Open) => {
+                self.next();
+                let expr = self.or()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(expr),
+                    _ => bail!("Expected `)` in query"),
+                }
+            }
+            _ => self.comparison(),
+        }
+    }
+
+    fn comparison(&mut self) -> anyhow::Result<Expr> {
+        let left = self.operand()?;
+
+        if let Some(Token::Op(op)) = self.peek() {
+            let op = *op;
+            self.next();
+            return Ok(Expr::Compare(left, op, self.operand()?));
+        }
+
+        Ok(Expr::Truthy(left))
+    }
+
+    fn operand(&mut self) -> anyhow::Result<Operand> {
+        match self.next() {
+            Some(Token::Ident(name)) => Ok(Operand::Field(name)),
+            Some(Token::Literal(value)) => Ok(Operand::Literal(value)),
+            Some(token) => bail!(
+                "Expected a property or value in query, found {}",
+                token.describe()
+            ),
+            None => bail!("Unexpected end of query"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use std::path::Path;
+    use test_case::test_case;
+
+    fn note() -> ObsidianNote {
+        let contents = indoc! {"
+            ---
+            status: active
+            due: 2024-06-01
+            priority: 2
+            done: false
+            tags:
+              - work
+              - urgent
+            ---
+            Body
+        "};
+        ObsidianNote::parse(Path::new("folder/task.md"), contents.to_string()).unwrap()
+    }
+
+    #[test_case(r#"status == "active""# ; "string equality")]
+    #[test_case("status != 'archived'" ; "single quotes")]
+    #[test_case("due < 2025-01-01" ; "date comparison")]
+    #[test_case("priority >= 2 and priority < 3" ; "number range")]
+    #[test_case(r#"tags contains "work""# ; "list membership")]
+    #[test_case("!done && status" ; "truthiness")]
+    #[test_case("missing == null" ; "missing is null")]
+    #[test_case(r#"file.name == "task""# ; "file fields")]
+    #[test_case(r#"(status == "x" || priority == 2) && !(due > 2024-06-01)"# ; "grouping")]
+    fn matches(query: &str) {
+        assert!(Expr::parse(query).unwrap().matches(&note()));
+    }
+
+    #[test_case("due > 2025-01-01" ; "later date")]
+    #[test_case("missing" ; "missing field")]
+    #[test_case("priority > \"1\"" ; "mismatched types")]
+    #[test_case(r#"tags contains "home""# ; "not in list")]
+    fn doesnt_match(query: &str) {
+        assert!(!Expr::parse(query).unwrap().matches(&note()));
+    }
+
+    #[test_case("status ==" ; "missing operand")]
+    #[test_case("(status" ; "unclosed group")]
+    #[test_case("status \"active\"" ; "trailing token")]
+    #[test_case("status == \"active" ; "unterminated string")]
+    fn rejects_invalid_queries(query: &str) {
+        assert!(Expr::parse(query).is_err());
+    }
+}
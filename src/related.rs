@@ -0,0 +1,130 @@
+//! Notes related to a note, by how alike their words are along with the tags
+//! and links they share, for `obx related` and the TUI
+
+use crate::{cache::MetadataCache, index::Index, link_graph::LinkGraph};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// How much sharing every tag adds to a note's score, next to the 1 of
+/// identical words
+const TAG_WEIGHT: f64 = 0.5;
+/// Added when either note links to the other
+const LINK_WEIGHT: f64 = 0.3;
+/// How much linking to all the same files adds
+const SHARED_LINK_WEIGHT: f64 = 0.3;
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct RelatedNote {
+    /// Vault-relative
+    pub path: String,
+    pub score: f64,
+    /// How alike the notes' words are, from 0 to 1
+    pub text: f64,
+    pub shared_tags: Vec<String>,
+    /// Whether either note links to the other
+    pub linked: bool,
+    /// How many files both notes link to
+    pub shared_links: usize,
+}
+
+impl RelatedNote {
+    /// Why the note is related, like `40% similar words, #project, linked`
+    pub fn reasons(&self) -> String {
+        let mut reasons = Vec::new();
+        if self.text > 0.0 {
+            reasons.push(format!("{:.0}% similar words", self.text * 100.0));
+        }
+        reasons.extend(self.shared_tags.iter().map(|tag| format!("#{tag}")));
+        if self.linked {
+            reasons.push("linked".to_string());
+        }
+        match self.shared_links {
+            0 => {}
+            1 => reasons.push("1 shared link".to_string()),
+            count => reasons.push(format!("{count} shared links")),
+        }
+        reasons.join(", ")
+    }
+}
+
+/// The notes related to the one at vault-relative `relative`, most related
+/// first with ties broken by path
+pub fn related_notes(
+    index: &Index,
+    metadata: &MetadataCache,
+    graph: &LinkGraph,
+    relative: &str,
+) -> Vec<RelatedNote> {
+    let text: HashMap<String, f64> = index
+        .similar(relative)
+        .into_iter()
+        .map(|hit| (hit.path, hit.score))
+        .collect();
+    let tags: HashSet<&String> = metadata
+        .get(relative)
+        .map(|note| note.tags.iter().collect())
+        .unwrap_or_default();
+    let links = link_targets(graph, relative);
+
+    let mut related: Vec<RelatedNote> = metadata
+        .notes()
+        .into_iter()
+        .filter(|(path, _)| *path != relative)
+        .filter_map(|(path, note)| {
+            let text = text.get(path).copied().unwrap_or(0.0);
+
+            let mut shared_tags: Vec<String> = note
+                .tags
+                .iter()
+                .filter(|tag| tags.contains(tag))
+                .cloned()
+                .collect();
+            shared_tags.sort();
+            shared_tags.dedup();
+            let all_tags = tags.len() + note.tags.len() - shared_tags.len();
+
+            let other_links = link_targets(graph, path);
+            let linked = links.contains(path) || other_links.contains(relative);
+            let shared_links = links.intersection(&other_links).count();
+            let all_links = links.union(&other_links).count();
+
+            let score = text
+                + TAG_WEIGHT * ratio(shared_tags.len(), all_tags)
+                + if linked { LINK_WEIGHT } else { 0.0 }
+                + SHARED_LINK_WEIGHT * ratio(shared_links, all_links);
+
+            (score > 0.0).then(|| RelatedNote {
+                path: path.to_string(),
+                score,
+                text,
+                shared_tags,
+                linked,
+                shared_links,
+            })
+        })
+        .collect();
+
+    related.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    related
+}
+
+/// The other files a note links to
+fn link_targets<'g>(graph: &'g LinkGraph, relative: &str) -> HashSet<&'g str> {
+    graph
+        .links_from(relative)
+        .iter()
+        .filter_map(|edge| edge.resolved.as_deref())
+        .filter(|target| *target != relative)
+        .collect()
+}
+
+fn ratio(part: usize, whole: usize) -> f64 {
+    if whole == 0 {
+        return 0.0;
+    }
+    part as f64 / whole as f64
+}
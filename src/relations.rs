@@ -0,0 +1,227 @@
+//! Hierarchies written in frontmatter, like `up: "[[Projects]]"`, as used
+//! to structure PARA and Johnny Decimal vaults. Which properties point at a
+//! note's parents and which at related notes is configurable, and a note's
+//! children are the notes naming it as their parent.
+
+use crate::{cache::MetadataCache, cli_config, link_resolver::LinkResolver};
+use libobsidian::{links::parse_links, Properties};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct RelationSettings {
+    /// Properties linking a note to its parents
+    pub parent_keys: Vec<String>,
+    /// Properties linking a note to related notes
+    pub related_keys: Vec<String>,
+}
+
+impl Default for RelationSettings {
+    fn default() -> Self {
+        Self {
+            parent_keys: vec!["up".to_string(), "parent".to_string()],
+            related_keys: vec!["related".to_string()],
+        }
+    }
+}
+
+impl RelationSettings {
+    pub fn is_default(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+/// The config's settings
+pub fn settings() -> anyhow::Result<RelationSettings> {
+    Ok(cli_config::read()?.relations)
+}
+
+/// How one note stands to another
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Relation {
+    Parent,
+    Child,
+    Related,
+}
+
+impl Relation {
+    pub fn arrow(self) -> &'static str {
+        match self {
+            Relation::Parent => "↑",
+            Relation::Child => "↓",
+            Relation::Related => "↔",
+        }
+    }
+}
+
+/// Every note's parents, children and related notes, vault-relative
+#[derive(Debug, Default)]
+pub struct Relations {
+    parents: BTreeMap<String, BTreeSet<String>>,
+    children: BTreeMap<String, BTreeSet<String>>,
+    related: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl Relations {
+    /// The relations the `settings` properties of the notes in `cache`
+    /// define. Links to missing notes and notes linking themselves are left
+    /// out, and a related note is related both ways.
+    pub fn new(
+        cache: &MetadataCache,
+        resolver: &LinkResolver,
+        vault_path: &Path,
+        settings: &RelationSettings,
+    ) -> Self {
+        let mut relations = Self::default();
+        for (relative, note) in cache.notes() {
+            let source = vault_path.join(relative);
+            for (key, values) in &note.property_links {
+                let is_parent = has_key(&settings.parent_keys, key);
+                let is_related = has_key(&settings.related_keys, key);
+                if !is_parent && !is_related {
+                    continue;
+                }
+                for link in values.iter().flat_map(|value| parse_links(value)) {
+                    let Some(target) = resolver.resolve(&link, &source) else {
+                        continue;
+                    };
+                    let target = crate::scan::relative_note_path(&target, vault_path);
+                    if target == relative || cache.get(&target).is_none() {
+                        continue;
+                    }
+                    if is_parent {
+                        insert(&mut relations.parents, relative, &target);
+                        insert(&mut relations.children, &target, relative);
+                    } else {
+                        insert(&mut relations.related, relative, &target);
+                        insert(&mut relations.related, &target, relative);
+                    }
+                }
+            }
+        }
+        relations
+    }
+
+    pub fn parents(&self, relative: &str) -> Vec<&str> {
+        listed(&self.parents, relative)
+    }
+
+    pub fn children(&self, relative: &str) -> Vec<&str> {
+        listed(&self.children, relative)
+    }
+
+    pub fn related(&self, relative: &str) -> Vec<&str> {
+        listed(&self.related, relative)
+    }
+
+    /// The parents of `relative`, then its children, then its related notes
+    pub fn around(&self, relative: &str) -> Vec<(Relation, &str)> {
+        let parents = self.parents(relative).into_iter();
+        let children = self.children(relative).into_iter();
+        let related = self.related(relative).into_iter();
+        parents
+            .map(|note| (Relation::Parent, note))
+            .chain(children.map(|note| (Relation::Child, note)))
+            .chain(related.map(|note| (Relation::Related, note)))
+            .collect()
+    }
+
+    /// The chain of first parents above `relative`, the topmost first,
+    /// stopping before any note already in it
+    pub fn ancestors(&self, relative: &str) -> Vec<&str> {
+        let mut ancestors: Vec<&str> = Vec::new();
+        let mut current = relative;
+        while let Some(parent) = self.parents(current).first().copied() {
+            if parent == relative || ancestors.contains(&parent) {
+                break;
+            }
+            ancestors.push(parent);
+            current = parent;
+        }
+        ancestors.reverse();
+        ancestors
+    }
+}
+
+fn insert(map: &mut BTreeMap<String, BTreeSet<String>>, from: &str, to: &str) {
+    map.entry(from.to_string())
+        .or_default()
+        .insert(to.to_string());
+}
+
+fn listed<'a>(map: &'a BTreeMap<String, BTreeSet<String>>, relative: &str) -> Vec<&'a str> {
+    map.get(relative)
+        .map(|notes| notes.iter().map(String::as_str).collect())
+        .unwrap_or_default()
+}
+
+fn has_key(keys: &[String], key: &str) -> bool {
+    keys.iter().any(|wanted| wanted.eq_ignore_ascii_case(key))
+}
+
+/// The frontmatter properties holding wikilinks, by lowercase name, with
+/// the links as written. An unquoted `up: [[Projects]]`, which YAML reads as
+/// a list within a list, counts as the link it looks like.
+pub fn property_links(properties: &Properties) -> BTreeMap<String, Vec<String>> {
+    let Some(mapping) = properties.as_mapping() else {
+        return BTreeMap::new();
+    };
+    mapping
+        .iter()
+        .filter_map(|(key, value)| {
+            let mut links = Vec::new();
+            collect_links(value, &mut links);
+            let key = key.as_str()?.to_lowercase();
+            (!links.is_empty()).then_some((key, links))
+        })
+        .collect()
+}
+
+fn collect_links(value: &Properties, links: &mut Vec<String>) {
+    match value {
+        Properties::String(text) if text.contains("[[") => links.push(text.clone()),
+        Properties::Sequence(items) => match items.as_slice() {
+            [Properties::Sequence(inner)] => match inner.as_slice() {
+                [Properties::String(target)] => links.push(format!("[[{target}]]")),
+                _ => inner.iter().for_each(|item| collect_links(item, links)),
+            },
+            _ => items.iter().for_each(|item| collect_links(item, links)),
+        },
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn finds_links_in_properties() {
+        let properties: Properties = serde_yaml::from_str(indoc! {r#"
+            Up: "[[Projects]]"
+            parent: [[Areas]]
+            related:
+              - "[[Alpha]]"
+              - "[[Beta|B]]"
+            status: draft
+        "#})
+        .unwrap();
+
+        assert_eq!(
+            property_links(&properties),
+            BTreeMap::from([
+                ("up".to_string(), vec!["[[Projects]]".to_string()]),
+                ("parent".to_string(), vec!["[[Areas]]".to_string()]),
+                (
+                    "related".to_string(),
+                    vec!["[[Alpha]]".to_string(), "[[Beta|B]]".to_string()]
+                ),
+            ])
+        );
+    }
+}
@@ -0,0 +1,139 @@
+use crate::{link_resolver::LinkResolver, scan};
+use anyhow::{bail, Context};
+use libobsidian::links::{parse_links, replace_ranges};
+use std::{
+    collections::HashMap,
+    fs,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+/// Moving or renaming notes, along with the link rewrites needed so that
+/// every link in the vault still points at the same file afterwards
+pub struct Relocation {
+    pub moves: Vec<(PathBuf, PathBuf)>,
+    pub edits: Vec<NoteEdit>,
+}
+
+/// New contents for a note whose links need rewriting
+pub struct NoteEdit {
+    /// Where the note is now, before any moves are applied
+    pub path: PathBuf,
+    pub contents: String,
+    /// How many links were rewritten
+    pub references: usize,
+}
+
+impl Relocation {
+    /// Work out the link rewrites for moving each note from the first path
+    /// to the second. Nothing is changed on disk until [`Relocation::apply`].
+    pub fn plan(vault_path: &Path, moves: Vec<(PathBuf, PathBuf)>) -> anyhow::Result<Self> {
+        for (idx, (from, to)) in moves.iter().enumerate() {
+            if to.exists() || moves[..idx].iter().any(|(_, earlier)| earlier == to) {
+                bail!(
+                    "Can't move {} to {}, a file already exists there",
+                    scan::relative_note_path(from, vault_path),
+                    scan::relative_note_path(to, vault_path)
+                );
+            }
+        }
+
+        let before = LinkResolver::new(vault_path)?;
+        let mut after = before.clone();
+        for (from, to) in &moves {
+            after.record_move(from, to);
+        }
+
+        let destinations: HashMap<&Path, &Path> = moves
+            .iter()
+            .map(|(from, to)| (from.as_path(), to.as_path()))
+            .collect();
+        let new_location = |path: &Path| -> PathBuf {
+            destinations
+                .get(path)
+                .map(|to| to.to_path_buf())
+                .unwrap_or_else(|| path.to_path_buf())
+        };
+
+        let mut edits = Vec::new();
+
+        for note_path in scan::note_paths(vault_path)? {
+            let Ok(contents) = fs::read_to_string(&note_path) else {
+                continue;
+            };
+            let moved_source = new_location(&note_path);
+
+            let replacements: Vec<(Range<usize>, String)> = parse_links(&contents)
+                .into_iter()
+                .filter(|link| !link.target.is_empty() && !link.is_external())
+                .filter_map(|link| {
+                    let target = new_location(&before.resolve(&link, &note_path)?);
+                    if after.resolve_strict(&link, &moved_source).as_ref() == Some(&target) {
+                        return None;
+                    }
+
+                    let rewritten = after.retarget(&link, &target, &moved_source);
+                    Some((link.range, rewritten))
+                })
+                .collect();
+
+            if !replacements.is_empty() {
+                edits.push(NoteEdit {
+                    references: replacements.len(),
+                    contents: replace_ranges(&contents, replacements),
+                    path: note_path,
+                });
+            }
+        }
+
+        Ok(Self { moves, edits })
+    }
+
+    /// Write the rewritten links, then move the notes
+    pub fn apply(&self) -> anyhow::Result<()> {
+        self.apply_edits()?;
+
+        for (from, to) in &self.moves {
+            if let Some(parent) = to.parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("Could not create directory {}", parent.display()))?;
+            }
+            fs::rename(from, to).with_context(|| {
+                format!("Could not move {} to {}", from.display(), to.display())
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// Write the rewritten links, leaving the moves to the caller, as when a
+    /// whole folder is moved at once
+    pub fn apply_edits(&self) -> anyhow::Result<()> {
+        for edit in &self.edits {
+            fs::write(&edit.path, &edit.contents)
+                .with_context(|| format!("Could not write to note {}", edit.path.display()))?;
+        }
+        Ok(())
+    }
+
+    /// One line per note whose links are rewritten, e.g. `Updated 2 references in a.md`
+    pub fn summary(&self, vault_path: &Path, dry_run: bool) -> Vec<String> {
+        let verb = if dry_run { "Would update" } else { "Updated" };
+
+        self.edits
+            .iter()
+            .map(|edit| {
+                let noun = if edit.references == 1 {
+                    "reference"
+                } else {
+                    "references"
+                };
+                format!(
+                    "{verb} {} {noun} in {}",
+                    edit.references,
+                    scan::relative_note_path(&edit.path, vault_path)
+                )
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,264 @@
+use crossterm::style::{Color, ContentStyle, Stylize};
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+
+/// Render markdown as text for the terminal, ANSI-styled when `styled`
+/// and otherwise keeping only the layout
+pub fn render_markdown(markdown: &str, styled: bool) -> String {
+    let options =
+        Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS | Options::ENABLE_TABLES;
+    let mut renderer = Renderer {
+        styled,
+        ..Renderer::default()
+    };
+
+    for event in Parser::new_ext(markdown, options) {
+        renderer.event(event);
+    }
+
+    renderer.output.trim_end().to_string()
+}
+
+#[derive(Default)]
+struct Renderer {
+    output: String,
+    styles: Vec<ContentStyle>,
+    /// The next number for each open list, `None` for bulleted lists
+    lists: Vec<Option<u64>>,
+    quote_depth: usize,
+    in_code_block: bool,
+    link_urls: Vec<String>,
+    at_line_start: bool,
+    styled: bool,
+}
+
+impl Renderer {
+    fn event(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) if self.in_code_block => {
+                let style = ContentStyle::new().with(Color::DarkGrey);
+                for line in text.lines() {
+                    self.write("    ");
+                    self.write_styled(line, style);
+                    self.newline();
+                }
+            }
+            Event::Text(text) => self.write_styled(&text, self.current_style()),
+            Event::Code(code) => self.write_styled(&code, ContentStyle::new().with(Color::Yellow)),
+            Event::SoftBreak | Event::HardBreak => self.newline(),
+            Event::Rule => {
+                self.block_gap();
+                self.write_styled(&"─".repeat(40), ContentStyle::new().with(Color::DarkGrey));
+                self.newline();
+            }
+            Event::TaskListMarker(done) => self.write(if done { "[x] " } else { "[ ] " }),
+            Event::Html(html) | Event::InlineHtml(html) => self.write(&html),
+            _ => {}
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Heading { level, .. } => {
+                self.block_gap();
+                let style = ContentStyle::new().bold().with(heading_color(level));
+                self.write_styled(&format!("{} ", "#".repeat(level as usize)), style);
+                self.styles.push(style);
+            }
+            Tag::Paragraph if self.lists.is_empty() => self.block_gap(),
+            Tag::BlockQuote(_) => {
+                self.block_gap();
+                self.quote_depth += 1;
+            }
+            Tag::CodeBlock(kind) => {
+                self.block_gap();
+                if let CodeBlockKind::Fenced(lang) = kind {
+                    if !lang.is_empty() {
+                        self.write_styled(&format!("    {lang}"), ContentStyle::new().dim());
+                        self.newline();
+                    }
+                }
+                self.in_code_block = true;
+            }
+            Tag::List(start) => {
+                if self.lists.is_empty() {
+                    self.block_gap();
+                } else if !self.at_line_start {
+                    self.newline();
+                }
+                self.lists.push(start);
+            }
+            Tag::Item => {
+                if !self.at_line_start {
+                    self.newline();
+                }
+                let indent = "  ".repeat(self.lists.len().saturating_sub(1));
+                let marker = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}. ", *number - 1)
+                    }
+                    _ => "• ".to_string(),
+                };
+                self.write(&indent);
+                self.write_styled(&marker, ContentStyle::new().with(Color::Cyan));
+            }
+            Tag::Emphasis => self.push_style(|style| style.italic()),
+            Tag::Strong => self.push_style(|style| style.bold()),
+            Tag::Strikethrough => self.push_style(|style| style.crossed_out()),
+            Tag::Link { dest_url, .. } => {
+                self.link_urls.push(dest_url.to_string());
+                self.push_style(|style| style.underlined().with(Color::Blue));
+            }
+            Tag::TableRow | Tag::TableHead if !self.at_line_start => self.newline(),
+            Tag::TableCell if !self.at_line_start => {
+                self.write_styled(" │ ", ContentStyle::new().with(Color::DarkGrey));
+            }
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Heading(_) => {
+                self.styles.pop();
+                self.newline();
+            }
+            TagEnd::Paragraph => self.newline(),
+            TagEnd::BlockQuote(_) => self.quote_depth = self.quote_depth.saturating_sub(1),
+            TagEnd::CodeBlock => self.in_code_block = false,
+            TagEnd::List(_) => {
+                self.lists.pop();
+                if !self.at_line_start {
+                    self.newline();
+                }
+            }
+            TagEnd::Emphasis | TagEnd::Strong | TagEnd::Strikethrough => {
+                self.styles.pop();
+            }
+            TagEnd::Link => {
+                self.styles.pop();
+                if let Some(url) = self.link_urls.pop() {
+                    if !self.output.ends_with(&url) {
+                        self.write_styled(&format!(" ({url})"), ContentStyle::new().dim());
+                    }
+                }
+            }
+            TagEnd::TableHead | TagEnd::Table => self.newline(),
+            _ => {}
+        }
+    }
+
+    fn current_style(&self) -> ContentStyle {
+        self.styles.last().copied().unwrap_or_default()
+    }
+
+    fn push_style(&mut self, apply: impl FnOnce(ContentStyle) -> ContentStyle) {
+        self.styles.push(apply(self.current_style()));
+    }
+
+    /// Separate blocks with a blank line, except at the very start
+    fn block_gap(&mut self) {
+        if self.output.is_empty() {
+            return;
+        }
+        if !self.at_line_start {
+            self.newline();
+        }
+        if !self.output.ends_with("\n\n") {
+            self.output.push('\n');
+        }
+    }
+
+    fn write(&mut self, text: &str) {
+        self.write_styled(text, ContentStyle::new());
+    }
+
+    fn write_styled(&mut self, text: &str, style: ContentStyle) {
+        if text.is_empty() {
+            return;
+        }
+        if self.at_line_start || self.output.is_empty() {
+            for _ in 0..self.quote_depth {
+                if self.styled {
+                    self.output
+                        .push_str(&"│ ".with(Color::DarkGrey).to_string());
+                } else {
+                    self.output.push_str("│ ");
+                }
+            }
+            self.at_line_start = false;
+        }
+
+        if !self.styled || style == ContentStyle::new() {
+            self.output.push_str(text);
+        } else {
+            self.output.push_str(&style.apply(text).to_string());
+        }
+    }
+
+    fn newline(&mut self) {
+        self.output.push('\n');
+        self.at_line_start = true;
+    }
+}
+
+fn heading_color(level: HeadingLevel) -> Color {
+    match level {
+        HeadingLevel::H1 => Color::Magenta,
+        HeadingLevel::H2 => Color::Blue,
+        HeadingLevel::H3 => Color::Cyan,
+        _ => Color::Green,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Strip ANSI escape sequences so tests can compare the plain layout
+    fn plain(rendered: &str) -> String {
+        let escape = regex::Regex::new("\x1b\\[[0-9;]*m").unwrap();
+        escape.replace_all(rendered, "").into_owned()
+    }
+
+    #[test]
+    fn renders_headings_and_lists() {
+        let rendered = render_markdown(
+            "# Title\n\nSome *text*.\n\n- one\n- two\n\n1. first\n2. second",
+            true,
+        );
+
+        assert_eq!(
+            plain(&rendered),
+            "# Title\n\nSome text.\n\n• one\n• two\n\n1. first\n2. second"
+        );
+    }
+
+    #[test]
+    fn renders_links_quotes_and_code() {
+        let rendered = render_markdown(
+            "> quoted\n\n[site](https://example.com)\n\n```rust\nfn x() {}\n```",
+            true,
+        );
+
+        assert_eq!(
+            plain(&rendered),
+            "│ quoted\n\nsite (https://example.com)\n\n    rust\n    fn x() {}"
+        );
+    }
+
+    #[test]
+    fn styles_output() {
+        assert!(render_markdown("**bold**", true).contains('\x1b'));
+    }
+
+    #[test]
+    fn leaves_out_styles_when_unstyled() {
+        assert_eq!(
+            render_markdown("# Title\n\n> **bold** [site](https://example.com)", false),
+            "# Title\n\n│ bold site (https://example.com)"
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use crate::{
+    ambiguity::AmbiguitySettings,
+    cli_config,
+    matching::{self, MatchSettings},
+    scan,
+    util::{resolve_note_path, should_enable_interactivity},
+    zettel::ZettelSettings,
+};
+use anyhow::bail;
+use dialoguer::{theme::ColorfulTheme, Select};
+use std::path::{Path, PathBuf};
+
+/// Find the notes in a vault matching `query`. A path relative to the vault
+/// wins outright, then exact note names (case-sensitive before insensitive),
+/// then Zettelkasten IDs, and finally fuzzy matches against the
+/// vault-relative path, ranked by the config's `fuzzy` scoring.
+pub fn find_notes(query: &str, vault_path: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let as_path = resolve_note_path(query, vault_path)?;
+    if as_path.is_file() {
+        return Ok(vec![as_path]);
+    }
+
+    let notes = scan::note_paths(vault_path)?;
+    let name = query.strip_suffix(".md").unwrap_or(query);
+    let matching = MatchSettings::read();
+
+    let exact: Vec<PathBuf> = notes
+        .iter()
+        .filter(|path| matching::same(note_name(path), name))
+        .cloned()
+        .collect();
+    if !exact.is_empty() {
+        return Ok(exact);
+    }
+
+    let exact_ignore_case: Vec<PathBuf> = notes
+        .iter()
+        .filter(|path| matching.same_ignoring_case(note_name(path), name))
+        .cloned()
+        .collect();
+    if !exact_ignore_case.is_empty() {
+        return Ok(exact_ignore_case);
+    }
+
+    let by_id = with_id(name, &notes);
+    if !by_id.is_empty() {
+        return Ok(by_id);
+    }
+
+    let scoring = cli_config::read()
+        .map(|config| config.fuzzy)
+        .unwrap_or_default();
+
+    Ok(scoring
+        .rank(name, &matching, &notes, vault_path)
+        .into_iter()
+        .map(|found| found.path)
+        .collect())
+}
+
+/// Resolve `query` to a single note, prompting the user to pick between
+/// multiple matches when interactive and listing them otherwise. Notes that
+/// all have the name asked for are picked between by the config's
+/// `ambiguous_links` policy instead, unless that's to ask.
+pub fn pick_note(query: &str, vault_path: &Path) -> anyhow::Result<PathBuf> {
+    let mut matches = find_notes(query, vault_path)?;
+
+    match matches.len() {
+        0 => bail!("No note matching `{query}` found in vault"),
+        1 => Ok(matches.remove(0)),
+        _ => {
+            let mut candidates: Vec<String> = matches
+                .iter()
+                .map(|path| scan::relative_note_path(path, vault_path))
+                .collect();
+            let name = query.strip_suffix(".md").unwrap_or(query);
+            let settings = AmbiguitySettings::read();
+            let matching = MatchSettings::read();
+            if matches
+                .iter()
+                .all(|path| matching.same_ignoring_case(note_name(path), name))
+            {
+                settings.rank(&mut candidates, &matching);
+                matches = candidates
+                    .iter()
+                    .map(|file| vault_path.join(file))
+                    .collect();
+                if !settings.asks() {
+                    return Ok(matches.remove(0));
+                }
+            }
+
+            if !should_enable_interactivity() {
+                bail!("Multiple notes match `{query}`:\n{}", candidates.join("\n"));
+            }
+
+            let selection = Select::with_theme(&ColorfulTheme::default())
+                .with_prompt(format!("Multiple notes match `{query}`"))
+                .items(&candidates)
+                .default(0)
+                .interact()?;
+
+            Ok(matches.remove(selection))
+        }
+    }
+}
+
+/// The notes whose names start with the Zettelkasten ID `id`
+pub fn find_by_id(id: &str, vault_path: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    Ok(with_id(id, &scan::note_paths(vault_path)?))
+}
+
+fn with_id(id: &str, notes: &[PathBuf]) -> Vec<PathBuf> {
+    let settings = ZettelSettings::current();
+    if !settings.is_id(id) {
+        return Vec::new();
+    }
+    notes
+        .iter()
+        .filter(|path| settings.id_of(note_name(path)) == Some(id))
+        .cloned()
+        .collect()
+}
+
+fn note_name(path: &Path) -> &str {
+    path.file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or_default()
+}
+
+pub fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut haystack_chars = haystack.chars();
+    needle
+        .chars()
+        .all(|c| haystack_chars.by_ref().any(|h| h == c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case("smpl", "simple-note.md", true ; "subsequence")]
+    #[test_case("simple", "simple-note.md", true ; "prefix")]
+    #[test_case("note-simple", "simple-note.md", false ; "out of order")]
+    #[test_case("", "simple-note.md", true ; "empty needle")]
+    fn is_subsequence_matches_in_order(needle: &str, haystack: &str, expected: bool) {
+        assert_eq!(is_subsequence(needle, haystack), expected);
+    }
+}
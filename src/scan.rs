@@ -0,0 +1,342 @@
+use crate::cli_config;
+use anyhow::bail;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, File},
+    io::Read,
+    path::{Path, PathBuf},
+};
+use walkdir::{DirEntry, WalkDir};
+
+/// File in a vault's root listing paths to leave out, in gitignore syntax
+pub const IGNORE_FILE: &str = ".obxignore";
+
+/// How much of a note is checked for NUL bytes when skipping binary files,
+/// the same amount git looks at
+const BINARY_CHECK_BYTES: u64 = 8000;
+
+/// The paths excluded from a vault by its [`IGNORE_FILE`], the config's
+/// `ignore` list and its [`ScanLimits`]
+pub struct Ignored {
+    root: PathBuf,
+    rules: Gitignore,
+    limits: ScanLimits,
+}
+
+/// Files left out of scans by what they are rather than their names, from
+/// the config's `scan` settings, so giant exports don't slow down every
+/// command
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct ScanLimits {
+    /// Notes larger than this aren't read
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_file_size: Option<FileSize>,
+    /// Leave out notes that look binary, with a NUL byte near the start
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub skip_binary: bool,
+    /// Extensions of files to leave out entirely, without the `.`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub exclude_extensions: Vec<String>,
+}
+
+/// A size in bytes, written as a number or like `512KB`, `5MB` or `1GB`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum FileSize {
+    Bytes(u64),
+    Text(String),
+}
+
+impl FileSize {
+    pub fn bytes(&self) -> anyhow::Result<u64> {
+        let text = match self {
+            Self::Bytes(bytes) => return Ok(*bytes),
+            Self::Text(text) => text.trim(),
+        };
+
+        let split = text
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(text.len());
+        let (number, unit) = text.split_at(split);
+        let multiplier: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+            "" | "B" => 1,
+            "K" | "KB" => 1 << 10,
+            "M" | "MB" => 1 << 20,
+            "G" | "GB" => 1 << 30,
+            _ => bail!("`{text}` isn't a file size, use bytes or a size like `5MB`"),
+        };
+        match number.parse::<f64>() {
+            Ok(number) if number >= 0.0 => Ok((number * multiplier as f64) as u64),
+            _ => bail!("`{text}` isn't a file size, use bytes or a size like `5MB`"),
+        }
+    }
+}
+
+impl ScanLimits {
+    pub fn is_default(&self) -> bool {
+        self == &Self::default()
+    }
+
+    fn excludes_extension(&self, path: &Path) -> bool {
+        let Some(extension) = path.extension().and_then(|ext| ext.to_str()) else {
+            return false;
+        };
+        self.exclude_extensions.iter().any(|excluded| {
+            excluded
+                .trim_start_matches('.')
+                .eq_ignore_ascii_case(extension)
+        })
+    }
+
+    /// Whether a file is within the size limit and, when binary files are
+    /// skipped, looks like text
+    fn allows_contents(&self, path: &Path) -> bool {
+        // Invalid sizes are reported by `obx config validate`
+        if let Some(max) = self
+            .max_file_size
+            .as_ref()
+            .and_then(|size| size.bytes().ok())
+        {
+            if fs::metadata(path).is_ok_and(|metadata| metadata.len() > max) {
+                return false;
+            }
+        }
+
+        !self.skip_binary || !looks_binary(path)
+    }
+}
+
+/// Whether a file has a NUL byte near its start, which text files don't
+fn looks_binary(path: &Path) -> bool {
+    let mut start = Vec::new();
+    File::open(path)
+        .and_then(|file| file.take(BINARY_CHECK_BYTES).read_to_end(&mut start))
+        .is_ok_and(|_| start.contains(&0))
+}
+
+impl Ignored {
+    /// The rules for the registered vault containing `path`, or for `path`
+    /// itself when it isn't inside one
+    pub fn for_path(path: &Path) -> Self {
+        let config = cli_config::read().ok();
+        let root = config
+            .as_ref()
+            .and_then(|config| {
+                config
+                    .vaults
+                    .iter()
+                    .filter(|vault| path.starts_with(&vault.path))
+                    .max_by_key(|vault| vault.path.components().count())
+            })
+            .map(|vault| vault.path.clone())
+            .unwrap_or_else(|| path.to_path_buf());
+
+        let (patterns, limits) = config
+            .map(|config| (config.ignore, config.scan))
+            .unwrap_or_default();
+        Self::new(&root, &patterns).with_limits(limits)
+    }
+
+    pub fn new(root: &Path, patterns: &[String]) -> Self {
+        let mut builder = GitignoreBuilder::new(root);
+        for pattern in patterns {
+            // Invalid patterns are reported by `obx config validate`
+            let _ = builder.add_line(None, pattern);
+        }
+        let ignore_file = root.join(IGNORE_FILE);
+        if ignore_file.is_file() {
+            let _ = builder.add(ignore_file);
+        }
+
+        Self {
+            root: root.to_path_buf(),
+            rules: builder.build().unwrap_or_else(|_| Gitignore::empty()),
+            limits: ScanLimits::default(),
+        }
+    }
+
+    pub fn with_limits(mut self, limits: ScanLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        path.starts_with(&self.root)
+            && path != self.root
+            && self
+                .rules
+                .matched_path_or_any_parents(path, is_dir)
+                .is_ignore()
+    }
+
+    /// Whether `path` is a note whose contents can be read, rather than a
+    /// file too large or binary-looking for the vault's [`ScanLimits`]
+    pub fn is_readable_note(&self, path: &Path) -> bool {
+        is_markdown(path)
+            && !self.limits.excludes_extension(path)
+            && self.limits.allows_contents(path)
+    }
+}
+
+/// Recursively collect every markdown note beneath `base_path`, skipping
+/// hidden folders such as `.obsidian` and `.trash`, anything the vault
+/// ignores and notes outside its [`ScanLimits`]. Paths are returned sorted.
+pub fn note_paths(base_path: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    Ok(folders_and_notes(base_path)?.1)
+}
+
+/// Like [`note_paths`], but including attachments and any other files. Only
+/// excluded extensions apply from the [`ScanLimits`], as these files aren't
+/// read.
+pub fn file_paths(base_path: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    Ok(folders_and_files(base_path, &Ignored::for_path(base_path))?.1)
+}
+
+/// The folders beneath `base_path`, starting with itself, and the notes in
+/// them, from a single walk. Both are sorted.
+pub fn folders_and_notes(base_path: &Path) -> anyhow::Result<(Vec<PathBuf>, Vec<PathBuf>)> {
+    let ignored = Ignored::for_path(base_path);
+    let (folders, mut notes) = folders_and_files(base_path, &ignored)?;
+    notes.retain(|path| ignored.is_readable_note(path));
+
+    Ok((folders, notes))
+}
+
+/// Every file beneath `base_path`, as [`file_paths`], and the notes among
+/// them, as [`note_paths`], from a single walk
+pub fn files_and_notes(base_path: &Path) -> anyhow::Result<(Vec<PathBuf>, Vec<PathBuf>)> {
+    let ignored = Ignored::for_path(base_path);
+    let (_, files) = folders_and_files(base_path, &ignored)?;
+    let notes = files
+        .iter()
+        .filter(|path| ignored.is_readable_note(path))
+        .cloned()
+        .collect();
+
+    Ok((files, notes))
+}
+
+/// The folders beneath `base_path`, every file in them and the notes among
+/// those files, from a single walk. All are sorted.
+pub fn folders_files_and_notes(
+    base_path: &Path,
+) -> anyhow::Result<(Vec<PathBuf>, Vec<PathBuf>, Vec<PathBuf>)> {
+    let ignored = Ignored::for_path(base_path);
+    let (folders, files) = folders_and_files(base_path, &ignored)?;
+    let notes = files
+        .iter()
+        .filter(|path| ignored.is_readable_note(path))
+        .cloned()
+        .collect();
+
+    Ok((folders, files, notes))
+}
+
+fn folders_and_files(
+    base_path: &Path,
+    ignored: &Ignored,
+) -> anyhow::Result<(Vec<PathBuf>, Vec<PathBuf>)> {
+    let mut folders = Vec::new();
+    let mut files = Vec::new();
+
+    let walker = WalkDir::new(base_path)
+        .into_iter()
+        .filter_entry(|entry| is_visible(entry) && !is_ignored_entry(ignored, entry));
+    for entry in walker {
+        let entry = entry?;
+
+        if entry.file_type().is_file() {
+            files.push(entry.into_path());
+        } else if entry.file_type().is_dir() {
+            folders.push(entry.into_path());
+        }
+    }
+
+    folders.sort();
+    files.sort();
+
+    Ok((folders, files))
+}
+
+/// The path of a note relative to the vault root, always using `/` as the
+/// separator so output is stable across platforms
+pub fn relative_note_path(note_path: &Path, vault_path: &Path) -> String {
+    note_path
+        .strip_prefix(vault_path)
+        .unwrap_or(note_path)
+        .to_string_lossy()
+        .replace(std::path::MAIN_SEPARATOR_STR, "/")
+}
+
+pub fn is_markdown(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.eq_ignore_ascii_case("md"))
+        .unwrap_or(false)
+}
+
+/// Whether `path` is a note [`note_paths`] would find in the vault at
+/// `vault_path`, for checking single files without walking the vault
+pub fn is_vault_note(ignored: &Ignored, vault_path: &Path, path: &Path) -> bool {
+    let Ok(relative) = path.strip_prefix(vault_path) else {
+        return false;
+    };
+    let hidden = relative
+        .components()
+        .any(|part| part.as_os_str().to_string_lossy().starts_with('.'));
+
+    path.is_file() && !hidden && !ignored.is_ignored(path, false) && ignored.is_readable_note(path)
+}
+
+/// Whether a walked entry is ignored by name, or is a file with an excluded
+/// extension
+pub fn is_ignored_entry(ignored: &Ignored, entry: &DirEntry) -> bool {
+    let is_dir = entry.file_type().is_dir();
+    ignored.is_ignored(entry.path(), is_dir)
+        || (!is_dir && ignored.limits.excludes_extension(entry.path()))
+}
+
+fn is_visible(entry: &DirEntry) -> bool {
+    entry.depth() == 0 || !entry.file_name().to_string_lossy().starts_with('.')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::{prelude::*, TempDir};
+    use test_case::test_case;
+
+    #[test_case(FileSize::Bytes(100), Some(100) ; "bytes")]
+    #[test_case(FileSize::Text("512".into()), Some(512) ; "bytes as text")]
+    #[test_case(FileSize::Text("2KB".into()), Some(2048) ; "kilobytes")]
+    #[test_case(FileSize::Text("1.5 mb".into()), Some(1_572_864) ; "fractional megabytes")]
+    #[test_case(FileSize::Text("1G".into()), Some(1 << 30) ; "gigabytes")]
+    #[test_case(FileSize::Text("lots".into()), None ; "not a size")]
+    #[test_case(FileSize::Text("5TB".into()), None ; "unknown unit")]
+    fn parses_file_sizes(size: FileSize, expected: Option<u64>) {
+        assert_eq!(size.bytes().ok(), expected);
+    }
+
+    #[test]
+    fn skips_notes_outside_limits() {
+        let dir = TempDir::new().unwrap();
+        dir.child("small.md").write_str("text").unwrap();
+        dir.child("large.md").write_str(&"x".repeat(2000)).unwrap();
+        dir.child("binary.md").write_binary(b"PK\0\x03").unwrap();
+        dir.child("export.pdf").write_str("").unwrap();
+
+        let ignored = Ignored::new(dir.path(), &[]).with_limits(ScanLimits {
+            max_file_size: Some(FileSize::Text("1KB".into())),
+            skip_binary: true,
+            exclude_extensions: vec![".PDF".into()],
+        });
+
+        assert!(ignored.is_readable_note(&dir.child("small.md")));
+        assert!(!ignored.is_readable_note(&dir.child("large.md")));
+        assert!(!ignored.is_readable_note(&dir.child("binary.md")));
+        assert!(ignored.limits.excludes_extension(&dir.child("export.pdf")));
+        assert!(Ignored::new(dir.path(), &[]).is_readable_note(&dir.child("binary.md")));
+    }
+}
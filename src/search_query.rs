@@ -0,0 +1,967 @@
+//! The query syntax shared by `obx search`, `obx list --query` and the TUI's
+//! search, so a query finds the same notes wherever it's typed:
+//!
+//! ```text
+//! tag:#project path:Work/ after:2024-01-01 before:2024-06-01 "exact phrase" -draft
+//! ```
+//!
+//! `before:` and `after:` go by when notes were modified. `created:>2024-01-01`
+//! and `created:<30d` go by when they were created, from their `created` or
+//! `date` property or else the file, and `modified:>`/`modified:<` are the
+//! same as `after:`/`before:`.
+//!
+//! Bare words must each start a word in the note's name or contents, quoted
+//! phrases must appear as written, and any term can be negated with a
+//! leading `-`. Words and phrases ignore case unless one of them has a
+//! capital letter, or the config's `matching.case` says otherwise; tags and
+//! paths always ignore it. Terms next to each other must all match, and
+//! `AND`, `OR`, `NOT` and parentheses combine them further:
+//!
+//! ```text
+//! (tag:#a OR tag:#b) AND path:Projects -"status: done"
+//! ```
+
+use crate::{
+    index::{self, tokenize, Index},
+    matching::{Case, MatchSettings},
+    note_text::NoteText,
+    scan,
+    util::{self, parse_since},
+};
+use anyhow::{bail, Context};
+use chrono::{DateTime, Local};
+use libobsidian::ObsidianNote;
+use serde::Serialize;
+use std::{collections::HashMap, fs, path::Path};
+use unicode_normalization::char::is_combining_mark;
+
+/// How much a note modified today counts over an old one, as an extra share
+/// of its score
+const RECENCY_BOOST: f64 = 0.5;
+/// Days for the recency boost to halve
+const RECENCY_HALF_LIFE_DAYS: f64 = 30.0;
+/// Added for each doubling of the notes linking to a note
+const LINK_BOOST: f64 = 0.5;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchQuery {
+    /// Every term in the query, which `expr` refers to by index
+    terms: Vec<Term>,
+    /// `None` for an empty query, which matches everything
+    expr: Option<Expr>,
+    /// The config's settings when the query was parsed
+    matching: MatchSettings,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Term {
+    /// Whether the term is inside an odd number of `NOT`s, so matching notes
+    /// don't have it
+    negated: bool,
+    kind: TermKind,
+    /// A word or phrase as typed, normalized but keeping its case
+    text: String,
+    /// Whether a word or phrase is matched against `text` rather than its
+    /// lowercased kind
+    case_sensitive: bool,
+}
+
+impl Term {
+    fn is_text(&self) -> bool {
+        matches!(self.kind, TermKind::Word(_) | TermKind::Phrase(_))
+    }
+
+    /// What a word or phrase looks for, in the case it's matched in
+    fn needle(&self) -> &str {
+        match &self.kind {
+            _ if self.case_sensitive => &self.text,
+            TermKind::Word(text) | TermKind::Phrase(text) => text,
+            _ => "",
+        }
+    }
+
+    /// `text` the way it's compared with the term
+    fn haystack(&self, text: &str, matching: &MatchSettings) -> String {
+        if self.case_sensitive {
+            matching.normalize(text).into_owned()
+        } else {
+            matching.fold(text)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Term(usize),
+    Not(Box<Expr>),
+    And(Vec<Expr>),
+    Or(Vec<Expr>),
+}
+
+impl Expr {
+    /// Point the terms at their place in a query `offset` terms longer
+    fn shift(&mut self, offset: usize) {
+        match self {
+            Expr::Term(idx) => *idx += offset,
+            Expr::Not(expr) => expr.shift(offset),
+            Expr::And(exprs) | Expr::Or(exprs) => {
+                exprs.iter_mut().for_each(|expr| expr.shift(offset))
+            }
+        }
+    }
+
+    fn eval(&self, found: &[bool]) -> bool {
+        match self {
+            Expr::Term(idx) => found[*idx],
+            Expr::Not(expr) => !expr.eval(found),
+            Expr::And(exprs) => exprs.iter().all(|expr| expr.eval(found)),
+            Expr::Or(exprs) => exprs.iter().any(|expr| expr.eval(found)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TermKind {
+    /// Lowercased, matching words it's the start of
+    Word(String),
+    /// Lowercased, matching anywhere in the contents
+    Phrase(String),
+    /// Without its `#`, also matching nested tags beneath it
+    Tag(String),
+    /// Lowercased, matching anywhere in the vault-relative path
+    Path(String),
+    /// Created or modified before this time
+    Before(Stamp, DateTime<Local>),
+    /// Created or modified at or after this time
+    After(Stamp, DateTime<Local>),
+}
+
+/// Which of a note's times a date filter looks at
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Stamp {
+    Created,
+    Modified,
+}
+
+/// A note found by [`SearchQuery::search`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchResult {
+    /// Vault-relative
+    pub path: String,
+    pub score: f64,
+    pub modified: Option<DateTime<Local>>,
+}
+
+/// The first line of a note where a query's words or phrases appear
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Snippet {
+    /// Counting from 1
+    pub line: usize,
+    pub text: String,
+    pub matches: Vec<MatchOffsets>,
+}
+
+/// Where a match is in a line, in bytes
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct MatchOffsets {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// What a query looks at in a note, read once per note
+pub struct NoteFacts {
+    pub relative: String,
+    pub contents: NoteText,
+    pub tags: Vec<String>,
+    pub created: Option<DateTime<Local>>,
+    pub modified: Option<DateTime<Local>>,
+}
+
+impl NoteFacts {
+    /// Read the note, parsing its tags and creation time only when
+    /// `with_properties` is set since that takes a copy of the whole note
+    pub fn read(
+        note_path: &Path,
+        vault_path: &Path,
+        with_properties: bool,
+    ) -> anyhow::Result<Self> {
+        let contents = NoteText::read(note_path)
+            .with_context(|| format!("Failed to read {}", note_path.display()))?;
+        let (tags, created) = if with_properties {
+            let note = ObsidianNote::parse(note_path, contents.as_str().to_string()).ok();
+            (
+                note.as_ref().map(ObsidianNote::tags).unwrap_or_default(),
+                util::note_created(
+                    note.as_ref().and_then(|note| note.properties.as_ref()),
+                    note_path,
+                ),
+            )
+        } else {
+            (Vec::new(), None)
+        };
+        let modified = fs::metadata(note_path)
+            .and_then(|meta| meta.modified())
+            .ok()
+            .map(DateTime::<Local>::from);
+
+        Ok(Self {
+            relative: scan::relative_note_path(note_path, vault_path),
+            contents,
+            tags,
+            created,
+            modified,
+        })
+    }
+}
+
+impl NoteFacts {
+    fn time(&self, stamp: Stamp) -> Option<DateTime<Local>> {
+        match stamp {
+            Stamp::Created => self.created,
+            Stamp::Modified => self.modified,
+        }
+    }
+}
+
+impl SearchQuery {
+    pub fn parse(query: &str) -> anyhow::Result<Self> {
+        let matching = MatchSettings::read();
+        let mut parser = Parser {
+            query,
+            tokens: split_terms(query)?,
+            pos: 0,
+            terms: Vec::new(),
+            matching: &matching,
+        };
+        let expr = if parser.tokens.is_empty() {
+            None
+        } else {
+            Some(parser.parse_or(false)?)
+        };
+        if let Some(token) = parser.tokens.get(parser.pos) {
+            bail!("Unexpected {} in query `{query}`", token.describe());
+        }
+
+        let case = matching.case;
+        Ok(Self {
+            terms: parser.terms,
+            expr,
+            matching,
+        }
+        .with_case(case))
+    }
+
+    /// Match words and phrases with `case` rather than the config's, with
+    /// smart case going by all of them together
+    pub fn with_case(mut self, case: Case) -> Self {
+        let typed: String = self
+            .terms
+            .iter()
+            .filter(|term| term.is_text())
+            .map(|term| term.text.as_str())
+            .collect();
+        let case_sensitive = case.is_sensitive(&typed);
+        for term in self.terms.iter_mut().filter(|term| term.is_text()) {
+            term.case_sensitive = case_sensitive;
+        }
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.expr.is_none()
+    }
+
+    /// Both must match
+    pub fn and(mut self, other: SearchQuery) -> SearchQuery {
+        let offset = self.terms.len();
+        self.terms.extend(other.terms);
+        let Some(mut other_expr) = other.expr else {
+            return self;
+        };
+        other_expr.shift(offset);
+
+        self.expr = Some(match self.expr {
+            None => other_expr,
+            Some(Expr::And(mut exprs)) => {
+                exprs.push(other_expr);
+                Expr::And(exprs)
+            }
+            Some(expr) => Expr::And(vec![expr, other_expr]),
+        });
+        self
+    }
+
+    /// Whether matching needs the note's tags or creation time
+    fn needs_properties(&self) -> bool {
+        self.terms.iter().any(|term| {
+            matches!(
+                term.kind,
+                TermKind::Tag(_)
+                    | TermKind::Before(Stamp::Created, _)
+                    | TermKind::After(Stamp::Created, _)
+            )
+        })
+    }
+
+    /// The words a note has to contain, for narrowing the search with an
+    /// index before checking the rest of the query. Words under `OR` or
+    /// `NOT` aren't needed by every match, so they're left out.
+    pub fn required_words(&self) -> Vec<&str> {
+        let mut words = Vec::new();
+        let mut pending: Vec<&Expr> = self.expr.iter().collect();
+        while let Some(expr) = pending.pop() {
+            match expr {
+                Expr::Term(idx) => {
+                    if let TermKind::Word(word) = &self.terms[*idx].kind {
+                        words.push(word.as_str());
+                    }
+                }
+                Expr::And(exprs) => pending.extend(exprs.iter().rev()),
+                Expr::Not(_) | Expr::Or(_) => {}
+            }
+        }
+        words
+    }
+
+    /// Every word a matching note could contain, for scoring queries whose
+    /// words are all optional
+    fn optional_words(&self) -> Vec<&str> {
+        self.terms
+            .iter()
+            .filter(|term| !term.negated)
+            .filter_map(|term| match &term.kind {
+                TermKind::Word(word) => Some(word.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The notes matching the query, best first. Words are looked up in the
+    /// vault's index and scored on how often they appear, counting matches in
+    /// a note's name most. Recently modified notes and notes many others link
+    /// to rank higher.
+    pub fn search(&self, vault_path: &Path) -> anyhow::Result<Vec<SearchResult>> {
+        self.search_in(&Index::current(vault_path)?, vault_path)
+    }
+
+    /// Like [`SearchQuery::search`], with an index already at hand
+    pub fn search_in(&self, index: &Index, vault_path: &Path) -> anyhow::Result<Vec<SearchResult>> {
+        let words = self.required_words();
+        let candidates: Vec<(String, f64)> = if words.is_empty() {
+            // Without words every note has to be checked, scored on how many
+            // of the optional words it has
+            let mut optional_scores: HashMap<String, f64> = HashMap::new();
+            for word in self.optional_words() {
+                for hit in index.search(word) {
+                    *optional_scores.entry(hit.path).or_default() += hit.score;
+                }
+            }
+            scan::note_paths(vault_path)?
+                .iter()
+                .map(|path| {
+                    let relative = scan::relative_note_path(path, vault_path);
+                    let score = 1.0 + optional_scores.get(&relative).copied().unwrap_or(0.0);
+                    (relative, score)
+                })
+                .collect()
+        } else {
+            index
+                .search(&words.join(" "))
+                .into_iter()
+                .map(|hit| (hit.path, hit.score))
+                .collect()
+        };
+        let in_degrees = index.in_degrees();
+        let today = Local::now();
+
+        let mut found = Vec::new();
+        for (relative, text_score) in candidates {
+            let note = NoteFacts::read(
+                &vault_path.join(&relative),
+                vault_path,
+                self.needs_properties(),
+            )?;
+            if !self.matches(&note) {
+                continue;
+            }
+
+            let linked_from = in_degrees.get(relative.as_str()).copied().unwrap_or(0);
+            found.push(SearchResult {
+                score: score(text_score, note.modified, linked_from, today),
+                path: relative,
+                modified: note.modified,
+            });
+        }
+        found.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.path.cmp(&b.path))
+        });
+
+        Ok(found)
+    }
+
+    /// Whether the note at `note_path` matches
+    pub fn matches_path(&self, note_path: &Path, vault_path: &Path) -> anyhow::Result<bool> {
+        if self.is_empty() {
+            return Ok(true);
+        }
+        Ok(self.matches(&NoteFacts::read(
+            note_path,
+            vault_path,
+            self.needs_properties(),
+        )?))
+    }
+
+    pub fn matches(&self, note: &NoteFacts) -> bool {
+        let Some(expr) = &self.expr else {
+            return true;
+        };
+        let found_in_text = self.find_in_text(note);
+        let relative = self.matching.fold(&note.relative);
+
+        let found: Vec<bool> = self
+            .terms
+            .iter()
+            .zip(found_in_text)
+            .map(|(term, found_in_text)| match &term.kind {
+                TermKind::Word(_) | TermKind::Phrase(_) => found_in_text,
+                TermKind::Tag(tag) => note.tags.iter().any(|t| self.matching.has_tag(t, tag)),
+                TermKind::Path(path) => relative.contains(path.as_str()),
+                TermKind::Before(stamp, time) => note.time(*stamp).is_some_and(|t| t < *time),
+                TermKind::After(stamp, time) => note.time(*stamp).is_some_and(|t| t >= *time),
+            })
+            .collect();
+
+        expr.eval(&found)
+    }
+
+    /// The first line of `contents` holding any of the words or phrases the
+    /// query looks for, with where they are in it
+    pub fn snippet(&self, contents: &NoteText) -> Option<Snippet> {
+        let (words, phrases): (Vec<&Term>, Vec<&Term>) = self
+            .terms
+            .iter()
+            .filter(|term| !term.negated && term.is_text())
+            .partition(|term| matches!(term.kind, TermKind::Word(_)));
+
+        contents.lines().enumerate().find_map(|(idx, line)| {
+            let mut matches = Vec::new();
+            for (start, word) in word_spans(line) {
+                let found = words.iter().any(|term| {
+                    term.haystack(word, &self.matching)
+                        .starts_with(term.needle())
+                });
+                if found {
+                    matches.push(MatchOffsets {
+                        start,
+                        end: start + word.len(),
+                    });
+                }
+            }
+
+            // Offsets in the folded line only carry over when folding kept
+            // every character's length
+            for term in &phrases {
+                let folded = term.haystack(line, &self.matching);
+                if folded.len() == line.len() {
+                    matches.extend(folded.match_indices(term.needle()).map(|(start, found)| {
+                        MatchOffsets {
+                            start,
+                            end: start + found.len(),
+                        }
+                    }));
+                }
+            }
+
+            if matches.is_empty() {
+                return None;
+            }
+            matches.sort_by_key(|offsets| offsets.start);
+            Some(Snippet {
+                line: idx + 1,
+                text: line.to_string(),
+                matches,
+            })
+        })
+    }
+
+    /// Which of the terms are words or phrases found in the note, going
+    /// through it a line at a time so big notes aren't copied whole. Phrases
+    /// can't hold a line break, so checking each line finds them all.
+    fn find_in_text(&self, note: &NoteFacts) -> Vec<bool> {
+        let mut found = vec![false; self.terms.len()];
+        let mut missing = self.terms.iter().filter(|term| term.is_text()).count();
+        let case_sensitive = self.terms.iter().any(|term| term.case_sensitive);
+        let mut check = |line: &str, with_phrases: bool| {
+            let (words, cased_words) = if case_sensitive {
+                (Vec::new(), index::words(line, &self.matching))
+            } else {
+                (tokenize(line, &self.matching), Vec::new())
+            };
+            for (term, found) in self.terms.iter().zip(found.iter_mut()) {
+                if *found {
+                    continue;
+                }
+                let words = if term.case_sensitive {
+                    &cased_words
+                } else {
+                    &words
+                };
+                *found = match &term.kind {
+                    TermKind::Word(_) => words.iter().any(|w| w.starts_with(term.needle())),
+                    TermKind::Phrase(_) => {
+                        with_phrases && term.haystack(line, &self.matching).contains(term.needle())
+                    }
+                    _ => false,
+                };
+                if *found {
+                    missing -= 1;
+                }
+            }
+            missing == 0
+        };
+
+        let title = note
+            .relative
+            .rsplit('/')
+            .next()
+            .and_then(|name| name.strip_suffix(".md"))
+            .unwrap_or(&note.relative);
+        if !check(title, false) {
+            for line in note.contents.lines() {
+                if check(line, true) {
+                    break;
+                }
+            }
+        }
+
+        found
+    }
+}
+
+/// Combine a note's text match with how recently it was modified and how
+/// many notes link to it. Ages count whole days so notes saved moments apart
+/// rank the same.
+fn score(
+    text_score: f64,
+    modified: Option<DateTime<Local>>,
+    linked_from: usize,
+    now: DateTime<Local>,
+) -> f64 {
+    let freshness = modified.map_or(0.0, |modified| {
+        let days = (now - modified).num_days().max(0) as f64;
+        0.5_f64.powf(days / RECENCY_HALF_LIFE_DAYS)
+    });
+    let links = LINK_BOOST * (1.0 + linked_from as f64).log2();
+
+    text_score * (1.0 + RECENCY_BOOST * freshness) + links
+}
+
+/// The words in `line` as [`tokenize`] splits them, with their byte offsets
+fn word_spans(line: &str) -> impl Iterator<Item = (usize, &str)> {
+    // Decomposed accents stay with their letters
+    line.split(|c: char| !c.is_alphanumeric() && !is_combining_mark(c))
+        .filter(|word| !word.is_empty())
+        .map(move |word| (word.as_ptr() as usize - line.as_ptr() as usize, word))
+}
+
+fn parse_time(term: &str, time: &str) -> anyhow::Result<DateTime<Local>> {
+    parse_since(time).with_context(|| format!("Invalid time in `{term}`"))
+}
+
+/// The `>2024-01-01` or `<30d` of a `created:` or `modified:` term, with a
+/// `>=` or `<=` read the same
+fn time_range(term: &str, stamp: Stamp, range: &str) -> anyhow::Result<TermKind> {
+    if let Some(time) = range.strip_prefix('>') {
+        let time = time.strip_prefix('=').unwrap_or(time);
+        return Ok(TermKind::After(stamp, parse_time(term, time)?));
+    }
+    if let Some(time) = range.strip_prefix('<') {
+        let time = time.strip_prefix('=').unwrap_or(time);
+        return Ok(TermKind::Before(stamp, parse_time(term, time)?));
+    }
+    bail!(
+        "Expected `>` or `<` after the `:` in `{term}`, like `{term_name}:>2024-01-01`",
+        term_name = term.split(':').next().unwrap_or_default()
+    )
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Term { text: String, quoted: bool },
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Term { text, .. } => format!("`{text}`"),
+            Token::And => "`AND`".to_string(),
+            Token::Or => "`OR`".to_string(),
+            Token::Not => "`NOT`".to_string(),
+            Token::Open => "`(`".to_string(),
+            Token::Close => "`)`".to_string(),
+        }
+    }
+}
+
+/// Builds a query's expression from its tokens, with `NOT` binding tightest,
+/// then `AND`, which is implied between terms next to each other, then `OR`
+struct Parser<'q> {
+    query: &'q str,
+    tokens: Vec<Token>,
+    pos: usize,
+    terms: Vec<Term>,
+    matching: &'q MatchSettings,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn parse_or(&mut self, negated: bool) -> anyhow::Result<Expr> {
+        let mut exprs = vec![self.parse_and(negated, None)?];
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            exprs.push(self.parse_and(negated, Some(&Token::Or))?);
+        }
+
+        Ok(match exprs.len() {
+            1 => exprs.remove(0),
+            _ => Expr::Or(exprs),
+        })
+    }
+
+    /// Terms up to the next `OR` or `)`, which must all match. `after` is
+    /// the operator before them, for the error when there aren't any.
+    fn parse_and(&mut self, negated: bool, after: Option<&Token>) -> anyhow::Result<Expr> {
+        let mut exprs = Vec::new();
+        loop {
+            match self.peek() {
+                None | Some(Token::Or) | Some(Token::Close) => break,
+                Some(Token::And) => {
+                    if exprs.is_empty() {
+                        bail!("Nothing before `AND` in query `{}`", self.query);
+                    }
+                    self.pos += 1;
+                    if matches!(
+                        self.peek(),
+                        None | Some(Token::Or) | Some(Token::Close) | Some(Token::And)
+                    ) {
+                        bail!("Nothing after `AND` in query `{}`", self.query);
+                    }
+                }
+                Some(_) => exprs.push(self.parse_unary(negated)?),
+            }
+        }
+
+        match (exprs.len(), after, self.peek()) {
+            (0, Some(after), _) => {
+                bail!(
+                    "Nothing after {} in query `{}`",
+                    after.describe(),
+                    self.query
+                )
+            }
+            (0, None, Some(Token::Or)) => bail!("Nothing before `OR` in query `{}`", self.query),
+            (0, None, Some(Token::Close)) => bail!(
+                "Unexpected `)` in query `{}`, it has nothing to close",
+                self.query
+            ),
+            (1, _, _) => Ok(exprs.remove(0)),
+            _ => Ok(Expr::And(exprs)),
+        }
+    }
+
+    fn parse_unary(&mut self, negated: bool) -> anyhow::Result<Expr> {
+        let token = self.tokens[self.pos].clone();
+        self.pos += 1;
+
+        match token {
+            Token::Not => match self.peek() {
+                None | Some(Token::Or) | Some(Token::And) | Some(Token::Close) => {
+                    bail!("Nothing after `NOT` in query `{}`", self.query)
+                }
+                Some(_) => Ok(Expr::Not(Box::new(self.parse_unary(!negated)?))),
+            },
+            Token::Open => {
+                if self.peek() == Some(&Token::Close) {
+                    bail!("Empty `()` in query `{}`", self.query);
+                }
+                let expr = self.parse_or(negated)?;
+                if self.peek() != Some(&Token::Close) {
+                    bail!("Missing `)` in query `{}`", self.query);
+                }
+                self.pos += 1;
+                Ok(expr)
+            }
+            Token::Term { text, quoted } => self.term(&text, quoted, negated),
+            Token::And | Token::Or | Token::Close => unreachable!("handled by parse_and"),
+        }
+    }
+
+    fn term(&mut self, text: &str, quoted: bool, negated: bool) -> anyhow::Result<Expr> {
+        if quoted {
+            return Ok(self.push_text(negated, TermKind::Phrase(self.matching.fold(text)), text));
+        }
+
+        let kind = match text.split_once(':') {
+            Some(("tag", tag)) => TermKind::Tag(self.matching.fold(tag.trim_start_matches('#'))),
+            Some(("path", path)) => TermKind::Path(self.matching.fold(path)),
+            Some(("before", time)) => TermKind::Before(Stamp::Modified, parse_time(text, time)?),
+            Some(("after", time)) => TermKind::After(Stamp::Modified, parse_time(text, time)?),
+            Some(("created", range)) => time_range(text, Stamp::Created, range)?,
+            Some(("modified", range)) => time_range(text, Stamp::Modified, range)?,
+            _ => {
+                // Words are split like indexed text, so `foo-bar` needs
+                // both `foo` and `bar`
+                let mut words: Vec<Expr> = index::words(text, self.matching)
+                    .into_iter()
+                    .map(|word| self.push_text(negated, TermKind::Word(word.to_lowercase()), &word))
+                    .collect();
+                return Ok(match words.len() {
+                    1 => words.remove(0),
+                    _ => Expr::And(words),
+                });
+            }
+        };
+
+        Ok(self.push(negated, kind))
+    }
+
+    fn push(&mut self, negated: bool, kind: TermKind) -> Expr {
+        self.push_text(negated, kind, "")
+    }
+
+    /// Push a word or phrase, keeping the case it was typed in
+    fn push_text(&mut self, negated: bool, kind: TermKind, text: &str) -> Expr {
+        self.terms.push(Term {
+            negated,
+            kind,
+            text: self.matching.normalize(text).into_owned(),
+            case_sensitive: false,
+        });
+        Expr::Term(self.terms.len() - 1)
+    }
+}
+
+/// Split a query on whitespace into terms and operators, keeping `"quoted
+/// phrases"` together. A leading `-` is short for `NOT`, and parentheses
+/// don't need spaces around them.
+fn split_terms(query: &str) -> anyhow::Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = query.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        match c {
+            '(' => {
+                chars.next();
+                tokens.push(Token::Open);
+                continue;
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::Close);
+                continue;
+            }
+            '-' => {
+                chars.next();
+                // A lone `-` is just punctuation
+                if chars.peek().is_some_and(|c| !c.is_whitespace()) {
+                    tokens.push(Token::Not);
+                }
+                continue;
+            }
+            _ => {}
+        }
+
+        let mut text = String::new();
+        if c == '"' {
+            chars.next();
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some(c) => text.push(c),
+                    None => bail!("Unclosed quote in query `{query}`"),
+                }
+            }
+            tokens.push(Token::Term { text, quoted: true });
+            continue;
+        }
+
+        while let Some(c) = chars.next_if(|c| !c.is_whitespace() && *c != ')') {
+            text.push(c);
+        }
+        tokens.push(match text.as_str() {
+            "AND" => Token::And,
+            "OR" => Token::Or,
+            "NOT" => Token::Not,
+            _ => Token::Term {
+                text,
+                quoted: false,
+            },
+        });
+    }
+
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use test_case::test_case;
+
+    fn note() -> NoteFacts {
+        NoteFacts {
+            relative: "Work/Project Plan.md".to_string(),
+            contents: "The kickoff meeting is on Monday.\nStatus: draft"
+                .to_string()
+                .into(),
+            tags: vec!["project/alpha".to_string(), "Meeting".to_string()],
+            created: Some(Local.with_ymd_and_hms(2023, 11, 2, 9, 0, 0).unwrap()),
+            modified: Some(Local.with_ymd_and_hms(2024, 3, 7, 12, 0, 0).unwrap()),
+        }
+    }
+
+    #[test_case("kick meet", true ; "word prefixes")]
+    #[test_case("plan", true ; "word in name")]
+    #[test_case("kick retro", false ; "every word needed")]
+    #[test_case("\"meeting is on\"", true ; "phrase")]
+    #[test_case("\"meeting on\"", false ; "phrase in order")]
+    #[test_case("-draft", false ; "negated word")]
+    #[test_case("-\"on friday\"", true ; "negated phrase")]
+    #[test_case("tag:#project", true ; "parent tag")]
+    #[test_case("tag:proj", false ; "partial tag")]
+    #[test_case("tag:meeting", true ; "tag ignores case")]
+    #[test_case("path:work/", true ; "path")]
+    #[test_case("-path:Work/", false ; "negated path")]
+    #[test_case("after:2024-03-01 before:2024-04-01", true ; "date range")]
+    #[test_case("before:2024-03-07", false ; "before is exclusive")]
+    #[test_case("created:>2023-11-01 created:<2023-12-01", true ; "created range")]
+    #[test_case("created:>=2024-01-01", false ; "created too early")]
+    #[test_case("modified:>2024-03-01 modified:<2024-04-01", true ; "modified range")]
+    #[test_case("tag:#project path:Work/ \"kickoff meeting\" -retro", true ; "combined")]
+    #[test_case("retro OR kickoff", true ; "or")]
+    #[test_case("retro OR standup", false ; "or neither")]
+    #[test_case("kickoff AND retro", false ; "explicit and")]
+    #[test_case("NOT draft", false ; "not")]
+    #[test_case("(tag:#other OR tag:#project) AND path:Work -status:done", true ; "grouped")]
+    #[test_case("-(draft OR retro)", false ; "negated group")]
+    #[test_case("retro OR kickoff draft", true ; "and binds tighter than or")]
+    #[test_case("(retro OR kickoff) -draft", false ; "group then negation")]
+    #[test_case("-status:draft", false ; "negated words all together")]
+    fn matches_notes(query: &str, expected: bool) {
+        assert_eq!(
+            SearchQuery::parse(query).unwrap().matches(&note()),
+            expected
+        );
+    }
+
+    #[test_case("Monday", Case::Smart, true ; "smart capitals")]
+    #[test_case("MONDAY", Case::Smart, false ; "smart wrong capitals")]
+    #[test_case("monday", Case::Smart, true ; "smart lowercase")]
+    #[test_case("\"The kickoff\"", Case::Smart, true ; "smart phrase")]
+    #[test_case("\"the Kickoff\"", Case::Smart, false ; "smart wrong phrase")]
+    #[test_case("monday", Case::Sensitive, false ; "sensitive")]
+    #[test_case("MONDAY", Case::Insensitive, true ; "insensitive")]
+    #[test_case("monday tag:MEETING path:WORK", Case::Sensitive, false ; "sensitive words")]
+    #[test_case("Monday tag:MEETING path:WORK", Case::Sensitive, true ; "tags and paths ignore case")]
+    fn matches_case(query: &str, case: Case, expected: bool) {
+        let query = SearchQuery::parse(query).unwrap().with_case(case);
+        assert_eq!(query.matches(&note()), expected);
+    }
+
+    #[test]
+    fn ranks_recent_and_linked_notes_higher() {
+        let now = Local.with_ymd_and_hms(2024, 3, 7, 12, 0, 0).unwrap();
+        let month_ago = Local.with_ymd_and_hms(2024, 2, 6, 12, 0, 0).unwrap();
+
+        assert!(score(1.0, Some(now), 0, now) > score(1.0, Some(month_ago), 0, now));
+        assert!(score(1.0, Some(now), 3, now) > score(1.0, Some(now), 0, now));
+        assert_eq!(score(2.0, None, 0, now), 2.0);
+    }
+
+    #[test]
+    fn finds_snippets() {
+        let query = SearchQuery::parse("meet \"on monday\" -draft").unwrap();
+        let snippet = query.snippet(&note().contents).unwrap();
+
+        assert_eq!(snippet.line, 1);
+        assert_eq!(
+            snippet.matches,
+            [
+                MatchOffsets { start: 12, end: 19 },
+                MatchOffsets { start: 23, end: 32 }
+            ]
+        );
+        assert_eq!(
+            SearchQuery::parse("draft")
+                .unwrap()
+                .snippet(&note().contents)
+                .unwrap()
+                .line,
+            2
+        );
+        assert_eq!(
+            SearchQuery::parse("tag:x")
+                .unwrap()
+                .snippet(&note().contents),
+            None
+        );
+    }
+
+    #[test]
+    fn collects_required_words() {
+        let query = SearchQuery::parse("tag:x foo-bar -baz \"a phrase\"").unwrap();
+
+        assert_eq!(query.required_words(), ["foo", "bar"]);
+    }
+
+    #[test]
+    fn rejects_bad_queries() {
+        assert!(SearchQuery::parse("\"unclosed").is_err());
+        assert!(SearchQuery::parse("before:soon").is_err());
+        assert!(SearchQuery::parse("created:2024-01-01").is_err());
+    }
+
+    #[test]
+    fn adds_filters_to_queries() {
+        let query = SearchQuery::parse("retro OR kickoff")
+            .unwrap()
+            .and(SearchQuery::parse("created:<2024-01-01").unwrap());
+        assert!(query.matches(&note()));
+
+        let query = query.and(SearchQuery::parse("-draft").unwrap());
+        assert!(!query.matches(&note()));
+    }
+
+    #[test_case("(a OR b", "Missing `)` in query `(a OR b`" ; "unclosed group")]
+    #[test_case("a)", "Unexpected `)` in query `a)`" ; "unopened group")]
+    #[test_case("a OR", "Nothing after `OR` in query `a OR`" ; "trailing or")]
+    #[test_case("OR a", "Nothing before `OR` in query `OR a`" ; "leading or")]
+    #[test_case("a AND OR b", "Nothing after `AND` in query `a AND OR b`" ; "and then or")]
+    #[test_case("a NOT", "Nothing after `NOT` in query `a NOT`" ; "trailing not")]
+    #[test_case("()", "Empty `()` in query `()`" ; "empty group")]
+    fn explains_syntax_errors(query: &str, message: &str) {
+        assert_eq!(SearchQuery::parse(query).unwrap_err().to_string(), message);
+    }
+
+    #[test]
+    fn leaves_optional_words_out_of_required_ones() {
+        let query = SearchQuery::parse("alpha (beta OR gamma) -delta NOT epsilon").unwrap();
+
+        assert_eq!(query.required_words(), ["alpha"]);
+        assert_eq!(query.optional_words(), ["alpha", "beta", "gamma"]);
+    }
+}
@@ -0,0 +1,189 @@
+//! Tokens for integrations, kept out of the config file. The config's
+//! `secrets` hold a reference instead: `keyring://<service>/<name>` for an
+//! entry in the OS keychain, or `encrypted:<blob>` for a value encrypted
+//! with a passphrase.
+
+use anyhow::{anyhow, bail, Context};
+use argon2::Argon2;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use dialoguer::Password;
+use rand::RngCore;
+use std::env;
+
+pub const KEYRING_SCHEME: &str = "keyring://";
+pub const ENCRYPTED_PREFIX: &str = "encrypted:";
+/// The keychain service obx stores its own secrets under
+pub const KEYRING_SERVICE: &str = "obx";
+/// Supplies the passphrase for encrypted secrets without prompting
+pub const PASSPHRASE_VAR: &str = "OBX_SECRET_PASSPHRASE";
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Storage {
+    Keyring,
+    Encrypted,
+}
+
+/// Whether a config value refers to a stored secret, rather than holding
+/// one in plain text
+pub fn is_reference(value: &str) -> bool {
+    value.starts_with(KEYRING_SCHEME) || value.starts_with(ENCRYPTED_PREFIX)
+}
+
+/// Store a secret called `name`, returning the reference to save in the
+/// config
+pub fn store(name: &str, value: &str, storage: Storage) -> anyhow::Result<String> {
+    match storage {
+        Storage::Keyring => {
+            keyring_entry(KEYRING_SERVICE, name)?
+                .set_password(value)
+                .map_err(|err| anyhow!("Could not save `{name}` to the keychain: {err}"))?;
+            Ok(format!("{KEYRING_SCHEME}{KEYRING_SERVICE}/{name}"))
+        }
+        Storage::Encrypted => Ok(format!(
+            "{ENCRYPTED_PREFIX}{}",
+            encrypt(value, &passphrase(true)?)?
+        )),
+    }
+}
+
+/// The secret a config value refers to. Plain values are returned as they
+/// are.
+pub fn resolve(value: &str) -> anyhow::Result<String> {
+    if let Some(path) = value.strip_prefix(KEYRING_SCHEME) {
+        let (service, name) = parse_keyring_path(path)?;
+        return keyring_entry(service, name)?
+            .get_password()
+            .map_err(|err| anyhow!("Could not read `{value}` from the keychain: {err}"));
+    }
+
+    if let Some(blob) = value.strip_prefix(ENCRYPTED_PREFIX) {
+        return decrypt(blob, &passphrase(false)?);
+    }
+
+    Ok(value.to_string())
+}
+
+/// Delete the keychain entry a reference points at. Encrypted and plain
+/// values only live in the config, so there's nothing else to remove.
+pub fn forget(value: &str) -> anyhow::Result<()> {
+    let Some(path) = value.strip_prefix(KEYRING_SCHEME) else {
+        return Ok(());
+    };
+    let (service, name) = parse_keyring_path(path)?;
+
+    match keyring_entry(service, name)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(err) => bail!("Could not remove `{value}` from the keychain: {err}"),
+    }
+}
+
+fn parse_keyring_path(path: &str) -> anyhow::Result<(&str, &str)> {
+    path.split_once('/')
+        .filter(|(service, name)| !service.is_empty() && !name.is_empty())
+        .with_context(|| {
+            format!("Invalid secret `{KEYRING_SCHEME}{path}`, expected `{KEYRING_SCHEME}<service>/<name>`")
+        })
+}
+
+fn keyring_entry(service: &str, name: &str) -> anyhow::Result<keyring::Entry> {
+    keyring::Entry::new(service, name)
+        .map_err(|err| anyhow!("Could not open the keychain entry for `{name}`: {err}"))
+}
+
+/// The passphrase from [`PASSPHRASE_VAR`], or asked for, twice when it's
+/// being set
+fn passphrase(confirm: bool) -> anyhow::Result<String> {
+    if let Ok(passphrase) = env::var(PASSPHRASE_VAR) {
+        return Ok(passphrase);
+    }
+
+    let mut prompt = Password::new().with_prompt("Secrets passphrase");
+    if confirm {
+        prompt = prompt.with_confirmation("Repeat the passphrase", "The passphrases don't match");
+    }
+    prompt.interact().with_context(|| {
+        format!("Couldn't prompt for the secrets passphrase, set ${PASSPHRASE_VAR}")
+    })
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> anyhow::Result<Key> {
+    let mut key = Key::default();
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|err| anyhow!("Could not derive a key from the passphrase: {err}"))?;
+    Ok(key)
+}
+
+/// Encrypt `value` into base64 of the salt, nonce and ciphertext
+fn encrypt(value: &str, passphrase: &str) -> anyhow::Result<String> {
+    let mut salt = [0; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+    let cipher = ChaCha20Poly1305::new(&derive_key(passphrase, &salt)?);
+    let ciphertext = cipher
+        .encrypt(&nonce, value.as_bytes())
+        .map_err(|_| anyhow!("Could not encrypt the secret"))?;
+
+    Ok(STANDARD.encode([&salt[..], &nonce[..], &ciphertext].concat()))
+}
+
+fn decrypt(blob: &str, passphrase: &str) -> anyhow::Result<String> {
+    let bytes = STANDARD
+        .decode(blob.trim())
+        .ok()
+        .filter(|bytes| bytes.len() > SALT_LEN + NONCE_LEN)
+        .context("The encrypted secret is damaged, set it again")?;
+    let (salt, rest) = bytes.split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+
+    let cipher = ChaCha20Poly1305::new(&derive_key(passphrase, salt)?);
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| anyhow!("Could not decrypt the secret, check the passphrase"))?;
+
+    String::from_utf8(plaintext).context("The decrypted secret isn't valid text")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypts_and_decrypts() {
+        let blob = encrypt("ghp_token", "hunter2").unwrap();
+
+        assert_ne!(blob, encrypt("ghp_token", "hunter2").unwrap());
+        assert_eq!(decrypt(&blob, "hunter2").unwrap(), "ghp_token");
+        assert_eq!(
+            decrypt(&blob, "wrong").unwrap_err().to_string(),
+            "Could not decrypt the secret, check the passphrase"
+        );
+    }
+
+    #[test]
+    fn stores_in_keyring() {
+        keyring::set_default_credential_builder(keyring::mock::default_credential_builder());
+
+        // The mock keychain keeps nothing between entries, so check the
+        // reference rather than reading it back
+        let reference = store("github-token", "ghp_token", Storage::Keyring).unwrap();
+        assert_eq!(reference, "keyring://obx/github-token");
+        assert!(forget(&reference).is_ok());
+    }
+
+    #[test]
+    fn resolves_plain_values() {
+        assert_eq!(resolve("plain").unwrap(), "plain");
+        assert!(!is_reference("plain"));
+        assert!(is_reference("keyring://obx/token"));
+        assert!(resolve("keyring://obx").is_err());
+    }
+}
@@ -0,0 +1,101 @@
+//! The JSON files obx keeps for a vault in its state directory: the search
+//! index, the metadata cache and the link graph. Each is written to a
+//! temporary file first and renamed into place, so a command interrupted
+//! mid-write, or two writing at once, never leave a torn file behind.
+
+use crate::cli_config;
+use anyhow::Context;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+use tempfile::NamedTempFile;
+
+/// Where the state file `name` for the vault at `vault_path` is kept
+pub fn path(vault_path: &Path, name: &str) -> PathBuf {
+    cli_config::vault_state_dir(vault_path).join(name)
+}
+
+/// The file at `path`, `None` when there isn't one and an error when it
+/// can't be parsed
+pub fn load<T: DeserializeOwned>(path: &Path) -> anyhow::Result<Option<T>> {
+    let Ok(contents) = fs::read_to_string(path) else {
+        return Ok(None);
+    };
+
+    let value = serde_json::from_str(&contents)
+        .with_context(|| format!("Could not parse {}", path.display()))?;
+    Ok(Some(value))
+}
+
+/// Replace the file at `path` with `value`, `what` naming it in errors
+pub fn save<T: Serialize>(path: &Path, value: &T, what: &str) -> anyhow::Result<()> {
+    let parent = path.parent().unwrap_or(Path::new("."));
+    fs::create_dir_all(parent)
+        .with_context(|| format!("Could not create directory {}", parent.display()))?;
+
+    // Named uniquely, so threads saving at once don't share a temporary file,
+    // and removed if it's never renamed into place
+    let contents = serde_json::to_string(value)?;
+    NamedTempFile::new_in(parent)
+        .and_then(|mut temp| {
+            temp.write_all(contents.as_bytes())?;
+            temp.persist(path).map_err(|err| err.error)
+        })
+        .with_context(|| format!("Could not write {what} {}", path.display()))?;
+    Ok(())
+}
+
+/// A file's modification time in milliseconds since the epoch, and its size
+pub fn file_stamp(path: &Path) -> Option<(u128, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some((modified.as_millis(), metadata.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::TempDir;
+
+    #[test]
+    fn saves_and_loads() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("state/values.json");
+
+        save(&path, &vec![1, 2, 3], "values").unwrap();
+
+        assert_eq!(load::<Vec<u32>>(&path).unwrap(), Some(vec![1, 2, 3]));
+        assert_eq!(fs::read_dir(path.parent().unwrap()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn saves_from_several_threads_at_once() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("values.json");
+
+        std::thread::scope(|scope| {
+            for value in 0..8 {
+                let path = &path;
+                scope.spawn(move || save(path, &vec![value; 1000], "values").unwrap());
+            }
+        });
+
+        let saved = load::<Vec<u32>>(&path).unwrap().unwrap();
+        assert!(saved.iter().all(|value| *value == saved[0]));
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn tells_missing_from_damaged() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("values.json");
+
+        assert_eq!(load::<Vec<u32>>(&path).unwrap(), None);
+        fs::write(&path, "[1, 2").unwrap();
+        assert!(load::<Vec<u32>>(&path).is_err());
+    }
+}
@@ -0,0 +1,197 @@
+//! Colors for what commands print, taken from the config's theme: paths,
+//! tags, dates and the matches a search or grep found. Output is colored on
+//! a terminal unless `NO_COLOR` is set, and `--color always` or `never`
+//! decides it regardless.
+
+use crate::{cli_config, theme::Theme};
+use atty::Stream;
+use clap::ColorChoice;
+use ratatui::prelude::Color;
+use std::{env, sync::OnceLock};
+
+static CHOICE: OnceLock<ColorChoice> = OnceLock::new();
+
+/// Use the `--color` flag's choice, before anything is printed
+pub fn set_choice(choice: ColorChoice) {
+    let _ = CHOICE.set(choice);
+}
+
+/// Whether output is colored, going by `--color`, `NO_COLOR` and whether
+/// stdout is a terminal
+pub fn enabled() -> bool {
+    match CHOICE.get().copied().unwrap_or(ColorChoice::Auto) {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            let no_color = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+            !no_color && atty::is(Stream::Stdout)
+        }
+    }
+}
+
+/// Styles text with the theme's colors, or leaves it alone when output
+/// isn't colored
+#[derive(Debug, Clone)]
+pub struct Styler {
+    theme: Option<Theme>,
+}
+
+impl Styler {
+    pub fn new(theme: Option<Theme>) -> Self {
+        Self { theme }
+    }
+
+    /// The styler for this run, with the config's theme when output is
+    /// colored
+    pub fn current() -> &'static Self {
+        static STYLER: OnceLock<Styler> = OnceLock::new();
+        STYLER.get_or_init(|| {
+            Styler::new(enabled().then(|| {
+                cli_config::read()
+                    .and_then(|config| config.resolve_theme())
+                    .unwrap_or_default()
+            }))
+        })
+    }
+
+    /// A vault-relative path, with its folders and name in the theme's
+    /// folder and note colors
+    pub fn path(&self, path: &str) -> String {
+        let Some(theme) = &self.theme else {
+            return path.to_string();
+        };
+        match path.rfind('/') {
+            Some(idx) => {
+                let (folders, name) = path.split_at(idx + 1);
+                format!(
+                    "{}{}",
+                    paint(folders, theme.folder),
+                    paint(name, theme.note)
+                )
+            }
+            None => paint(path, theme.note),
+        }
+    }
+
+    pub fn tag(&self, tag: &str) -> String {
+        self.with(tag, |theme| theme.tag)
+    }
+
+    pub fn date(&self, date: &str) -> String {
+        self.with(date, |theme| theme.modified)
+    }
+
+    /// `line` with the byte ranges in `matches` bold in the theme's accent.
+    /// The ranges are in order and don't overlap, as regex and search
+    /// matches are.
+    pub fn highlight(&self, line: &str, matches: &[(usize, usize)]) -> String {
+        let Some(theme) = &self.theme else {
+            return line.to_string();
+        };
+
+        let mut highlighted = String::with_capacity(line.len());
+        let mut end_of_last = 0;
+        for &(start, end) in matches {
+            let start = start.max(end_of_last);
+            if start >= end || end > line.len() {
+                continue;
+            }
+            highlighted.push_str(&line[end_of_last..start]);
+            highlighted.push_str(&bold(&paint(&line[start..end], theme.accent)));
+            end_of_last = end;
+        }
+        highlighted.push_str(&line[end_of_last..]);
+        highlighted
+    }
+
+    fn with(&self, text: &str, color: impl Fn(&Theme) -> Color) -> String {
+        match &self.theme {
+            Some(theme) => paint(text, color(theme)),
+            None => text.to_string(),
+        }
+    }
+}
+
+/// `text` in the foreground `color`. Escapes are written here rather than by
+/// crossterm, which leaves colors out whenever `NO_COLOR` is set, even when
+/// `--color always` asked for them.
+fn paint(text: &str, color: Color) -> String {
+    let code = match color {
+        Color::Rgb(r, g, b) => format!("38;2;{r};{g};{b}"),
+        Color::Indexed(idx) => format!("38;5;{idx}"),
+        Color::Reset => return text.to_string(),
+        named => named_code(named).to_string(),
+    };
+    format!("\x1b[{code}m{text}\x1b[39m")
+}
+
+/// The escape code for one of the terminal's own sixteen colors
+fn named_code(color: Color) -> u8 {
+    match color {
+        Color::Black => 30,
+        Color::Red => 31,
+        Color::Green => 32,
+        Color::Yellow => 33,
+        Color::Blue => 34,
+        Color::Magenta => 35,
+        Color::Cyan => 36,
+        Color::Gray => 37,
+        Color::DarkGray => 90,
+        Color::LightRed => 91,
+        Color::LightGreen => 92,
+        Color::LightYellow => 93,
+        Color::LightBlue => 94,
+        Color::LightMagenta => 95,
+        Color::LightCyan => 96,
+        _ => 97,
+    }
+}
+
+fn bold(text: &str) -> String {
+    format!("\x1b[1m{text}\x1b[22m")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn colored() -> Styler {
+        Styler::new(Some(Theme::default()))
+    }
+
+    #[test]
+    fn leaves_text_alone_without_colors() {
+        let styler = Styler::new(None);
+
+        assert_eq!(styler.path("Work/Plan.md"), "Work/Plan.md");
+        assert_eq!(styler.highlight("a match", &[(2, 7)]), "a match");
+    }
+
+    #[test]
+    fn colors_folders_and_names() {
+        let theme = Theme::default();
+
+        assert_eq!(
+            colored().path("Work/Plan.md"),
+            format!(
+                "{}{}",
+                paint("Work/", theme.folder),
+                paint("Plan.md", theme.note)
+            )
+        );
+    }
+
+    #[test]
+    fn highlights_matches() {
+        let accent = |text| bold(&paint(text, Theme::default().accent));
+
+        assert_eq!(
+            colored().highlight("one two three", &[(0, 3), (8, 13)]),
+            format!("{} two {}", accent("one"), accent("three"))
+        );
+        assert_eq!(
+            colored().highlight("abc", &[(0, 2), (1, 3), (3, 9)]),
+            format!("{}{}", accent("ab"), accent("c"))
+        );
+    }
+}
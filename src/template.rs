@@ -0,0 +1,183 @@
+use crate::{cli_config, dates::DateFormats, scan};
+use anyhow::{anyhow, bail};
+use chrono::{DateTime, Local};
+use regex::{Captures, Regex};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+pub const DEFAULT_TEMPLATE_FOLDER: &str = "templates";
+
+/// The values available to `{{variables}}` when rendering a template
+pub struct TemplateContext {
+    /// The new note's name, without its extension
+    pub title: String,
+    /// The vault-relative folder the new note is created in
+    pub folder: String,
+    pub now: DateTime<Local>,
+    /// The default `{{date}}` and `{{time}}` formats, and the locale
+    pub dates: DateFormats,
+    /// Custom variables passed with `--var`, which take precedence over the
+    /// built-in ones
+    pub vars: HashMap<String, String>,
+}
+
+impl TemplateContext {
+    pub fn for_note(note_path: &Path, vault_path: &Path, vars: &[(String, String)]) -> Self {
+        let relative = scan::relative_note_path(note_path, vault_path);
+        let folder = relative
+            .rsplit_once('/')
+            .map(|(folder, _)| folder.to_string())
+            .unwrap_or_default();
+
+        Self {
+            title: note_path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            folder,
+            now: Local::now(),
+            dates: cli_config::read()
+                .map(|config| config.dates)
+                .unwrap_or_default(),
+            vars: vars.iter().cloned().collect(),
+        }
+    }
+}
+
+/// The absolute path of the vault's template folder, from `template_folder`
+/// in the config or [`DEFAULT_TEMPLATE_FOLDER`]
+pub fn template_folder(vault_path: &Path) -> anyhow::Result<PathBuf> {
+    let folder = cli_config::read()?
+        .template_folder
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_TEMPLATE_FOLDER));
+
+    Ok(vault_path.join(folder))
+}
+
+/// The names of every template, relative to the template folder and without
+/// the `.md` extension
+pub fn list_templates(vault_path: &Path) -> anyhow::Result<Vec<String>> {
+    let folder = template_folder(vault_path)?;
+    if !folder.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let names = scan::note_paths(&folder)?
+        .iter()
+        .map(|path| {
+            let relative = scan::relative_note_path(path, &folder);
+            relative
+                .strip_suffix(".md")
+                .map(str::to_string)
+                .unwrap_or(relative)
+        })
+        .collect();
+
+    Ok(names)
+}
+
+/// Find a template by its name within the template folder, with or without
+/// the `.md` extension
+pub fn find_template(vault_path: &Path, name: &str) -> anyhow::Result<PathBuf> {
+    let folder = template_folder(vault_path)?;
+    let name = name.strip_suffix(".md").unwrap_or(name);
+    let path = folder.join(format!("{name}.md"));
+
+    if !path.is_file() {
+        bail!(
+            "Template `{name}` not found in {}",
+            scan::relative_note_path(&folder, vault_path)
+        );
+    }
+
+    Ok(path)
+}
+
+/// Fill in the `{{title}}`, `{{folder}}`, `{{date}}`, `{{time}}` and custom
+/// variables of a template. Dates and times take an optional Moment.js style
+/// format like Obsidian's, e.g. `{{date:YYYY-MM-DD}}`, otherwise the ones
+/// under `dates` in the config. Unknown variables are left as they are.
+pub fn render(template: &str, context: &TemplateContext) -> String {
+    static VARIABLE: OnceLock<Regex> = OnceLock::new();
+    let variable = VARIABLE.get_or_init(|| {
+        Regex::new(r"\{\{\s*([\w.-]+)\s*(?::([^}]*))?\}\}").expect("variable regex should compile")
+    });
+
+    variable
+        .replace_all(template, |captures: &Captures| {
+            let name = &captures[1];
+            let format = captures.get(2).map(|format| format.as_str().trim());
+
+            if let Some(value) = context.vars.get(name) {
+                return value.clone();
+            }
+
+            match name {
+                "title" => context.title.clone(),
+                "folder" => context.folder.clone(),
+                "date" => context
+                    .dates
+                    .format(&context.now, format.unwrap_or(&context.dates.date)),
+                "time" => context
+                    .dates
+                    .format(&context.now, format.unwrap_or(&context.dates.time)),
+                _ => captures[0].to_string(),
+            }
+        })
+        .into_owned()
+}
+
+/// Parse a `--var key=value` argument
+pub fn parse_var(input: &str) -> anyhow::Result<(String, String)> {
+    let (key, value) = input
+        .split_once('=')
+        .ok_or_else(|| anyhow!("expected KEY=VALUE, got `{input}`"))?;
+
+    if key.trim().is_empty() {
+        bail!("variable name can't be empty");
+    }
+
+    Ok((key.trim().to_string(), value.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use test_case::test_case;
+
+    fn context() -> TemplateContext {
+        TemplateContext {
+            title: "Meeting".to_string(),
+            folder: "work/notes".to_string(),
+            now: Local.with_ymd_and_hms(2024, 3, 7, 14, 5, 9).unwrap(),
+            dates: DateFormats::default(),
+            vars: HashMap::from([("project".to_string(), "Apollo".to_string())]),
+        }
+    }
+
+    #[test_case("# {{title}}", "# Meeting" ; "title")]
+    #[test_case("{{ folder }}", "work/notes" ; "folder with spaces")]
+    #[test_case("{{date}} {{time}}", "2024-03-07 14:05" ; "default formats")]
+    #[test_case("{{date:dddd D MMMM YYYY}}", "Thursday 7 March 2024" ; "date format")]
+    #[test_case("{{time:hh:mm:ss A}}", "02:05:09 PM" ; "time format")]
+    #[test_case("{{date:[Week of] YYYY-MM-DD}}", "Week of 2024-03-07" ; "escaped text")]
+    #[test_case("Project: {{project}}", "Project: Apollo" ; "custom variable")]
+    #[test_case("{{unknown}} {{tp.file.title}}", "{{unknown}} {{tp.file.title}}" ; "unknown kept")]
+    fn renders_variables(template: &str, expected: &str) {
+        assert_eq!(render(template, &context()), expected);
+    }
+
+    #[test]
+    fn parses_vars() {
+        assert_eq!(
+            parse_var("key=a=b").unwrap(),
+            ("key".to_string(), "a=b".to_string())
+        );
+        assert!(parse_var("novalue").is_err());
+        assert!(parse_var("=value").is_err());
+    }
+}
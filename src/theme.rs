@@ -1,6 +1,13 @@
+use crate::cli_config;
+use anyhow::{anyhow, bail, Context};
 use clap::ValueEnum;
 use ratatui::prelude::Color;
 use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
 
 #[derive(Debug, Clone)]
 pub struct Theme {
@@ -10,6 +17,10 @@ pub struct Theme {
     pub note: Color,
     pub modified: Color,
     pub tag: Color,
+    /// Headings in rendered notes
+    pub heading: Color,
+    /// Links in rendered notes
+    pub link: Color,
 }
 
 impl Default for Theme {
@@ -18,25 +29,179 @@ impl Default for Theme {
     }
 }
 
-#[derive(Debug, Clone, Copy, ValueEnum, Serialize, Deserialize, PartialEq, Eq)]
+/// A palette defined under `themes:` in the config. Colours are `#rrggbb`
+/// hex strings, and any left out come from `base16` or `base`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct CustomTheme {
+    /// The built-in or custom theme this one starts from, `obsidian-dark`
+    /// when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+    /// A base16 scheme file to start from instead of `base`, relative to the
+    /// config directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base16: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accent: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub background: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub folder: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub heading: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link: Option<String>,
+}
+
+/// The name of the theme used when none is configured
+pub fn default_theme_name() -> String {
+    ThemeName::default().name().to_string()
+}
+
+/// Every theme name that can be used, built-ins first
+pub fn theme_names(custom: &BTreeMap<String, CustomTheme>) -> Vec<String> {
+    ThemeName::value_variants()
+        .iter()
+        .map(|theme| theme.name().to_string())
+        .chain(custom.keys().cloned())
+        .collect()
+}
+
+/// Look a theme up by name, among the `custom` themes from the config and
+/// then the built-ins
+pub fn resolve(name: &str, custom: &BTreeMap<String, CustomTheme>) -> anyhow::Result<Theme> {
+    resolve_within(name, custom, &mut Vec::new())
+}
+
+fn resolve_within<'a>(
+    name: &'a str,
+    custom: &'a BTreeMap<String, CustomTheme>,
+    seen: &mut Vec<&'a str>,
+) -> anyhow::Result<Theme> {
+    let Some(theme) = custom.get(name) else {
+        return match ThemeName::from_str(name, false) {
+            Ok(builtin) => Ok(builtin.resolve()),
+            Err(_) => bail!(
+                "Unknown theme `{name}`, expected one of {}",
+                theme_names(custom)
+                    .iter()
+                    .map(|name| format!("`{name}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        };
+    };
+
+    if seen.contains(&name) {
+        bail!("Theme `{name}` is based on itself");
+    }
+    seen.push(name);
+
+    let mut resolved = match &theme.base16 {
+        Some(scheme) => load_base16(&cli_config::config_relative_path(scheme))
+            .map_err(|err| anyhow!("Theme `{name}` has an invalid base16 scheme: {err}"))?,
+        None => {
+            let base_name = theme.base.as_deref().unwrap_or(ThemeName::default().name());
+            resolve_within(base_name, custom, seen)
+                .map_err(|err| anyhow!("Theme `{name}` has an invalid base: {err}"))?
+        }
+    };
+
+    let roles = [
+        ("accent", &theme.accent, &mut resolved.accent),
+        ("background", &theme.background, &mut resolved.background),
+        ("folder", &theme.folder, &mut resolved.folder),
+        ("note", &theme.note, &mut resolved.note),
+        ("modified", &theme.modified, &mut resolved.modified),
+        ("tag", &theme.tag, &mut resolved.tag),
+        ("heading", &theme.heading, &mut resolved.heading),
+        ("link", &theme.link, &mut resolved.link),
+    ];
+    for (role, hex, color) in roles {
+        if let Some(hex) = hex {
+            *color = parse_hex(hex).with_context(|| {
+                format!("Theme `{name}` has an invalid `{role}` colour `{hex}`, expected `#rrggbb`")
+            })?;
+        }
+    }
+
+    Ok(resolved)
+}
+
+/// Build a theme from a base16 scheme file, either the classic format with
+/// `base00`..`base0F` at the top level or the newer one nesting them under
+/// `palette`
+pub fn load_base16(path: &Path) -> anyhow::Result<Theme> {
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("could not read {}", path.display()))?;
+    let scheme: serde_yaml::Value = serde_yaml::from_str(&contents)
+        .map_err(|err| anyhow!("could not parse {}: {err}", path.display()))?;
+    let palette = scheme.get("palette").unwrap_or(&scheme);
+
+    let color = |key: &str| -> anyhow::Result<Color> {
+        let hex = palette
+            .get(key)
+            .and_then(serde_yaml::Value::as_str)
+            .with_context(|| format!("{} has no `{key}` colour", path.display()))?;
+        parse_hex(hex).map_err(|err| anyhow!("`{key}` in {}: {err}", path.display()))
+    };
+
+    Ok(Theme {
+        accent: color("base0D")?,
+        background: color("base00")?,
+        folder: color("base0A")?,
+        note: color("base05")?,
+        modified: color("base08")?,
+        tag: color("base0C")?,
+        heading: color("base0E")?,
+        link: color("base0D")?,
+    })
+}
+
+fn parse_hex(hex: &str) -> anyhow::Result<Color> {
+    let digits = hex.trim().trim_start_matches('#');
+    if digits.len() != 6 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        bail!("invalid hex colour `{hex}`");
+    }
+
+    let channel = |idx: usize| u8::from_str_radix(&digits[idx..idx + 2], 16);
+    Ok(Color::Rgb(channel(0)?, channel(2)?, channel(4)?))
+}
+
+#[derive(Debug, Clone, Copy, Default, ValueEnum, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case")]
 #[clap(rename_all = "kebab-case")]
 pub enum ThemeName {
+    #[default]
     ObsidianDark,
     ObsidianLight,
     SolarizedDark,
     SolarizedLight,
     GruvboxDark,
     GruvboxLight,
+    /// Colours from the terminal's own palette, so they follow its scheme
+    Terminal,
 }
 
-impl Default for ThemeName {
-    fn default() -> Self {
-        ThemeName::ObsidianDark
+impl ThemeName {
+    pub fn name(self) -> &'static str {
+        match self {
+            ThemeName::ObsidianDark => "obsidian-dark",
+            ThemeName::ObsidianLight => "obsidian-light",
+            ThemeName::SolarizedDark => "solarized-dark",
+            ThemeName::SolarizedLight => "solarized-light",
+            ThemeName::GruvboxDark => "gruvbox-dark",
+            ThemeName::GruvboxLight => "gruvbox-light",
+            ThemeName::Terminal => "terminal",
+        }
     }
-}
 
-impl ThemeName {
     pub fn resolve(self) -> Theme {
         match self {
             ThemeName::ObsidianDark => Theme {
@@ -46,6 +211,8 @@ impl ThemeName {
                 note: Color::Rgb(208, 208, 208),
                 modified: Color::Rgb(255, 132, 132),
                 tag: Color::Rgb(124, 174, 254),
+                heading: Color::Rgb(255, 203, 107),
+                link: Color::Rgb(166, 218, 149),
             },
             ThemeName::ObsidianLight => Theme {
                 accent: Color::Rgb(76, 110, 245),
@@ -54,6 +221,8 @@ impl ThemeName {
                 note: Color::Rgb(33, 33, 33),
                 modified: Color::Rgb(210, 77, 87),
                 tag: Color::Rgb(114, 124, 245),
+                heading: Color::Rgb(66, 92, 162),
+                link: Color::Rgb(76, 110, 245),
             },
             ThemeName::SolarizedDark => Theme {
                 accent: Color::Rgb(147, 161, 161),
@@ -62,6 +231,8 @@ impl ThemeName {
                 note: Color::Rgb(253, 246, 227),
                 modified: Color::Rgb(203, 75, 22),
                 tag: Color::Rgb(38, 139, 210),
+                heading: Color::Rgb(88, 110, 117),
+                link: Color::Rgb(147, 161, 161),
             },
             ThemeName::SolarizedLight => Theme {
                 accent: Color::Rgb(101, 123, 131),
@@ -70,6 +241,8 @@ impl ThemeName {
                 note: Color::Rgb(0, 43, 54),
                 modified: Color::Rgb(211, 54, 130),
                 tag: Color::Rgb(133, 153, 0),
+                heading: Color::Rgb(38, 139, 210),
+                link: Color::Rgb(101, 123, 131),
             },
             ThemeName::GruvboxDark => Theme {
                 accent: Color::Rgb(215, 153, 33),
@@ -78,6 +251,8 @@ impl ThemeName {
                 note: Color::Rgb(235, 219, 178),
                 modified: Color::Rgb(204, 36, 29),
                 tag: Color::Rgb(104, 157, 106),
+                heading: Color::Rgb(189, 174, 147),
+                link: Color::Rgb(215, 153, 33),
             },
             ThemeName::GruvboxLight => Theme {
                 accent: Color::Rgb(204, 36, 29),
@@ -86,6 +261,18 @@ impl ThemeName {
                 note: Color::Rgb(60, 56, 54),
                 modified: Color::Rgb(204, 36, 29),
                 tag: Color::Rgb(69, 133, 136),
+                heading: Color::Rgb(152, 151, 26),
+                link: Color::Rgb(204, 36, 29),
+            },
+            ThemeName::Terminal => Theme {
+                accent: Color::Green,
+                background: Color::Reset,
+                folder: Color::Yellow,
+                note: Color::Reset,
+                modified: Color::Red,
+                tag: Color::Blue,
+                heading: Color::Magenta,
+                link: Color::Cyan,
             },
         }
     }
@@ -0,0 +1,29 @@
+use crate::attachments::unique_destination;
+use anyhow::Context;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Obsidian's own trash folder at the root of a vault, used when notes are
+/// deleted with "Move to Obsidian trash"
+pub const TRASH_FOLDER: &str = ".trash";
+
+/// Move a note into the vault's `.trash` folder, renaming it if a file with
+/// the same name was trashed before. Returns the note's new path.
+pub fn move_to_trash(vault_path: &Path, note_path: &Path) -> anyhow::Result<PathBuf> {
+    let trash = vault_path.join(TRASH_FOLDER);
+    fs::create_dir_all(&trash)
+        .with_context(|| format!("Could not create directory {}", trash.display()))?;
+
+    let destination = unique_destination(&trash, note_path)?;
+    fs::rename(note_path, &destination).with_context(|| {
+        format!(
+            "Could not move {} to {}",
+            note_path.display(),
+            destination.display()
+        )
+    })?;
+
+    Ok(destination)
+}
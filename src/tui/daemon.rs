@@ -0,0 +1,378 @@
+//! A daemon per vault that TUI instances ask for previews and search
+//! results over a Unix socket in the vault's state directory, so several
+//! windows on one vault share one index kept in memory and one copy of each
+//! read note instead of each reading the vault. `obx daemon` runs it, and the
+//! [worker](super::worker) does the work itself whenever no daemon answers.
+//!
+//! Requests and answers are JSON, one per line:
+//!
+//! ```text
+//! {"preview": "<vault-relative path>"}  {"preview": {"text": "...", "loaded": 120, "size": 120}}
+//! {"search": "<query>"}                 {"search": [{"path": "...", "score": 1.5, "modified": 1700000000000}]}
+//!                                       {"error": "..."} when either fails
+//! ```
+
+// Only the stand-ins at the bottom are used where there are no Unix sockets
+#![cfg_attr(not(unix), allow(dead_code, unused_imports))]
+
+use super::preview::Preview;
+use crate::{
+    cli_config::Vault,
+    index::Index,
+    scan,
+    search_query::{SearchQuery, SearchResult},
+    state_file::{self, file_stamp},
+    util::is_relative_inside,
+    watch::{watch_vault, VaultWatcher},
+};
+use chrono::{Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    io,
+    path::{Path, PathBuf},
+    sync::{Mutex, MutexGuard, PoisonError},
+    time::Duration,
+};
+
+const SOCKET_FILE: &str = "daemon.sock";
+
+/// How long changes settle before the daemon's index is updated
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// How long the TUI waits on an answer before giving up on the daemon
+const ANSWER_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Where the daemon for the vault at `vault_path` listens
+pub fn socket_path(vault_path: &Path) -> PathBuf {
+    state_file::path(vault_path, SOCKET_FILE)
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "snake_case")]
+enum Ask {
+    /// A note's preview, by vault-relative path
+    Preview(String),
+    Search(String),
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "snake_case")]
+enum Answer {
+    Preview {
+        text: String,
+        loaded: u64,
+        size: u64,
+    },
+    Search(Vec<Found>),
+    Error(String),
+}
+
+/// A [`SearchResult`] as it's sent over the socket
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct Found {
+    path: String,
+    score: f64,
+    /// Milliseconds since the epoch
+    modified: Option<i64>,
+}
+
+impl From<&SearchResult> for Found {
+    fn from(result: &SearchResult) -> Self {
+        Self {
+            path: result.path.clone(),
+            score: result.score,
+            modified: result.modified.map(|modified| modified.timestamp_millis()),
+        }
+    }
+}
+
+impl From<Found> for SearchResult {
+    fn from(found: Found) -> Self {
+        Self {
+            path: found.path,
+            score: found.score,
+            modified: found
+                .modified
+                .and_then(|millis| Local.timestamp_millis_opt(millis).single()),
+        }
+    }
+}
+
+/// A note as [`Preview::open`] read it, with the stamp of the file it was
+/// read from
+struct CachedPreview {
+    stamp: (u128, u64),
+    text: String,
+    loaded: u64,
+    size: u64,
+}
+
+/// What the daemon keeps between requests
+struct Shared {
+    index: Index,
+    /// Each note's preview, by vault-relative path
+    previews: HashMap<String, CachedPreview>,
+    /// Results by query, until anything in the vault changes
+    searches: HashMap<String, Vec<Found>>,
+}
+
+fn lock(shared: &Mutex<Shared>) -> MutexGuard<'_, Shared> {
+    shared.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Bring the index up to date as notes change, forgetting previews and
+/// searches the changes could affect
+fn keep_up(vault_path: &Path, watcher: VaultWatcher, shared: &Mutex<Shared>) {
+    let ignored = scan::Ignored::for_path(vault_path);
+    for changed in watcher {
+        let changed = match changed {
+            Ok(changed) => changed,
+            Err(err) => {
+                eprintln!("{err:#}");
+                continue;
+            }
+        };
+
+        let mut shared = lock(shared);
+        for relative in &changed {
+            shared
+                .previews
+                .remove(&relative.to_string_lossy().replace('\\', "/"));
+        }
+        shared.searches.clear();
+        let paths: Vec<PathBuf> = changed
+            .into_iter()
+            .map(|relative| vault_path.join(relative))
+            .collect();
+        if let Err(err) = shared.index.update_paths(vault_path, &ignored, &paths) {
+            eprintln!("{err:#}");
+        }
+    }
+}
+
+fn handle(ask: Ask, vault_path: &Path, shared: &Mutex<Shared>) -> Answer {
+    match ask {
+        Ask::Preview(relative) => preview(&relative, vault_path, shared),
+        Ask::Search(query) => search(&query, vault_path, shared),
+    }
+}
+
+fn preview(relative: &str, vault_path: &Path, shared: &Mutex<Shared>) -> Answer {
+    if !is_relative_inside(relative) {
+        return Answer::Error(format!(
+            "Invalid note path `{relative}`, expected one within the vault"
+        ));
+    }
+    let path = vault_path.join(relative);
+    let stamp = file_stamp(&path);
+
+    let cached = lock(shared)
+        .previews
+        .get(relative)
+        .filter(|cached| Some(cached.stamp) == stamp)
+        .map(|cached| Answer::Preview {
+            text: cached.text.clone(),
+            loaded: cached.loaded,
+            size: cached.size,
+        });
+    if let Some(answer) = cached {
+        return answer;
+    }
+
+    match Preview::open(&path) {
+        Ok(preview) => {
+            let (text, loaded, size) = preview.into_parts();
+            if let Some(stamp) = stamp {
+                let cached = CachedPreview {
+                    stamp,
+                    text: text.clone(),
+                    loaded,
+                    size,
+                };
+                lock(shared).previews.insert(relative.to_string(), cached);
+            }
+            Answer::Preview { text, loaded, size }
+        }
+        Err(err) => Answer::Error(err.to_string()),
+    }
+}
+
+fn search(text: &str, vault_path: &Path, shared: &Mutex<Shared>) -> Answer {
+    let query = match SearchQuery::parse(text) {
+        Ok(query) => query,
+        Err(err) => return Answer::Error(format!("{err:#}")),
+    };
+
+    let mut shared = lock(shared);
+    if let Some(found) = shared.searches.get(text) {
+        return Answer::Search(found.clone());
+    }
+    match query.search_in(&shared.index, vault_path) {
+        Ok(results) => {
+            let found: Vec<Found> = results.iter().map(Found::from).collect();
+            shared.searches.insert(text.to_string(), found.clone());
+            Answer::Search(found)
+        }
+        Err(err) => Answer::Error(format!("{err:#}")),
+    }
+}
+
+#[cfg(unix)]
+mod socket {
+    use super::*;
+    use anyhow::{bail, Context};
+    use std::{
+        fs,
+        io::{BufRead, BufReader, Write},
+        os::unix::net::{UnixListener, UnixStream},
+        sync::Arc,
+        thread,
+    };
+
+    /// Answer the TUI instances on `vault` until interrupted
+    pub fn serve(vault: &Vault) -> anyhow::Result<()> {
+        let socket = socket_path(&vault.path);
+        if UnixStream::connect(&socket).is_ok() {
+            bail!("A daemon is already running for `{}`", vault.name);
+        }
+        // Left behind by a daemon that was stopped
+        let _ = fs::remove_file(&socket);
+        if let Some(parent) = socket.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Could not create directory {}", parent.display()))?;
+        }
+
+        let watcher = watch_vault(vault, WATCH_DEBOUNCE)?;
+        let shared = Arc::new(Mutex::new(Shared {
+            index: Index::current(&vault.path)?,
+            previews: HashMap::new(),
+            searches: HashMap::new(),
+        }));
+        let listener = UnixListener::bind(&socket)
+            .with_context(|| format!("Could not listen on {}", socket.display()))?;
+
+        let vault_path = vault.path.clone();
+        let kept_up = Arc::clone(&shared);
+        thread::spawn(move || keep_up(&vault_path, watcher, &kept_up));
+
+        eprintln!(
+            "Sharing previews and searches for `{}` at {}, press Ctrl-C to stop",
+            vault.name,
+            socket.display()
+        );
+        for stream in listener.incoming() {
+            let Ok(stream) = stream else {
+                continue;
+            };
+            let vault_path = vault.path.clone();
+            let shared = Arc::clone(&shared);
+            thread::spawn(move || answer(stream, &vault_path, &shared));
+        }
+
+        Ok(())
+    }
+
+    /// Answer one TUI's requests until it hangs up
+    fn answer(stream: UnixStream, vault_path: &Path, shared: &Mutex<Shared>) {
+        let Ok(mut writer) = stream.try_clone() else {
+            return;
+        };
+        for line in BufReader::new(stream).lines() {
+            let Ok(line) = line else {
+                return;
+            };
+            let answer = match serde_json::from_str(&line) {
+                Ok(ask) => handle(ask, vault_path, shared),
+                Err(err) => Answer::Error(format!("Invalid request: {err}")),
+            };
+            let Ok(answer) = serde_json::to_string(&answer) else {
+                return;
+            };
+            if writeln!(writer, "{answer}").is_err() {
+                return;
+            }
+        }
+    }
+
+    /// The TUI's connection to its vault's daemon
+    pub struct Client {
+        reader: BufReader<UnixStream>,
+        writer: UnixStream,
+        vault_path: PathBuf,
+    }
+
+    impl Client {
+        /// Connect to the daemon for the vault at `vault_path`, `None` when
+        /// it isn't running
+        pub fn connect(vault_path: &Path) -> Option<Self> {
+            let writer = UnixStream::connect(socket_path(vault_path)).ok()?;
+            writer.set_read_timeout(Some(ANSWER_TIMEOUT)).ok()?;
+            Some(Self {
+                reader: BufReader::new(writer.try_clone().ok()?),
+                writer,
+                vault_path: vault_path.to_path_buf(),
+            })
+        }
+
+        /// The note at `path` read by the daemon, `None` when it couldn't be
+        /// asked
+        pub fn preview(&mut self, path: &Path) -> Option<io::Result<Preview>> {
+            path.strip_prefix(&self.vault_path).ok()?;
+            let relative = scan::relative_note_path(path, &self.vault_path);
+            match self.ask(&Ask::Preview(relative))? {
+                Answer::Preview { text, loaded, size } => {
+                    Some(Ok(Preview::from_parts(path, text, loaded, size)))
+                }
+                Answer::Error(err) => Some(Err(io::Error::other(err))),
+                Answer::Search(_) => None,
+            }
+        }
+
+        /// The daemon's results for `query`, `None` when it couldn't be
+        /// asked
+        pub fn search(&mut self, query: &str) -> Option<anyhow::Result<Vec<SearchResult>>> {
+            match self.ask(&Ask::Search(query.to_string()))? {
+                Answer::Search(found) => Some(Ok(found.into_iter().map(Into::into).collect())),
+                Answer::Error(err) => Some(Err(anyhow::anyhow!(err))),
+                Answer::Preview { .. } => None,
+            }
+        }
+
+        fn ask(&mut self, ask: &Ask) -> Option<Answer> {
+            writeln!(self.writer, "{}", serde_json::to_string(ask).ok()?).ok()?;
+            let mut line = String::new();
+            if self.reader.read_line(&mut line).ok()? == 0 {
+                return None;
+            }
+            serde_json::from_str(&line).ok()
+        }
+    }
+}
+
+#[cfg(unix)]
+pub use socket::{serve, Client};
+
+/// Without Unix sockets there's no daemon, and the TUI does its own work
+#[cfg(not(unix))]
+pub fn serve(_vault: &Vault) -> anyhow::Result<()> {
+    anyhow::bail!("The daemon needs Unix sockets, which this system doesn't have")
+}
+
+#[cfg(not(unix))]
+pub struct Client;
+
+#[cfg(not(unix))]
+impl Client {
+    pub fn connect(_vault_path: &Path) -> Option<Self> {
+        None
+    }
+
+    pub fn preview(&mut self, _path: &Path) -> Option<io::Result<Preview>> {
+        None
+    }
+
+    pub fn search(&mut self, _query: &str) -> Option<anyhow::Result<Vec<SearchResult>>> {
+        None
+    }
+}
@@ -0,0 +1,158 @@
+//! The local graph panel: the notes within a link or two of the selected
+//! note, drawn as a tree beneath it like Obsidian's local graph. The tree is
+//! built from the [link graph](crate::link_graph) on the
+//! [worker](super::worker) thread.
+
+use crate::link_graph::LinkGraph;
+use std::collections::HashSet;
+
+/// How many links away the panel reaches when it's opened
+pub const DEFAULT_DEPTH: usize = 1;
+/// The furthest the panel reaches, so it stays readable
+pub const MAX_DEPTH: usize = 2;
+
+/// A note in the tree, below the note it was reached from
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphRow {
+    /// Vault-relative
+    pub relative: String,
+    /// Links away from the root, which is at 0
+    pub depth: usize,
+    /// How it's linked with the note above it, `None` for the root
+    pub direction: Option<Direction>,
+    /// The heading the note above links into, like `Setup` of
+    /// `[[install#Setup]]`, which the preview jumps to
+    pub heading: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    /// The note above links to it
+    Out,
+    /// It links to the note above
+    In,
+    Both,
+}
+
+impl Direction {
+    pub fn arrow(self) -> &'static str {
+        match self {
+            Direction::Out => "→",
+            Direction::In => "←",
+            Direction::Both => "↔",
+        }
+    }
+}
+
+/// The notes within `depth` links of `root`, each listed once beneath the
+/// first note it was reached from, in path order
+pub fn build(graph: &LinkGraph, root: &str, depth: usize) -> Vec<GraphRow> {
+    let mut rows = vec![GraphRow {
+        relative: root.to_string(),
+        depth: 0,
+        direction: None,
+        heading: None,
+    }];
+    let mut seen: HashSet<String> = HashSet::from([root.to_string()]);
+    add_children(graph, root, 1, depth, &mut seen, &mut rows);
+    rows
+}
+
+/// Add the unseen neighbors of `parent` and, depth first, theirs. Every
+/// neighbor at this level is marked seen before going deeper, so a note
+/// sits as close to the root as it can.
+fn add_children(
+    graph: &LinkGraph,
+    parent: &str,
+    depth: usize,
+    max_depth: usize,
+    seen: &mut HashSet<String>,
+    rows: &mut Vec<GraphRow>,
+) {
+    if depth > max_depth {
+        return;
+    }
+
+    let children: Vec<&str> = graph
+        .neighbors(parent)
+        .into_iter()
+        .filter(|neighbor| seen.insert(neighbor.to_string()))
+        .collect();
+    for child in children {
+        rows.push(GraphRow {
+            relative: child.to_string(),
+            depth,
+            direction: Some(direction(graph, parent, child)),
+            heading: linked_heading(graph, parent, child),
+        });
+        add_children(graph, child, depth + 1, max_depth, seen, rows);
+    }
+}
+
+fn direction(graph: &LinkGraph, from: &str, to: &str) -> Direction {
+    let links_to = |source: &str, target: &str| {
+        graph
+            .links_from(source)
+            .iter()
+            .any(|edge| edge.resolved.as_deref() == Some(target))
+    };
+    match (links_to(from, to), links_to(to, from)) {
+        (true, true) => Direction::Both,
+        (false, true) => Direction::In,
+        _ => Direction::Out,
+    }
+}
+
+/// The first heading of `to` that `from` links into
+fn linked_heading(graph: &LinkGraph, from: &str, to: &str) -> Option<String> {
+    graph
+        .links_from(from)
+        .iter()
+        .filter(|edge| edge.resolved.as_deref() == Some(to))
+        .find_map(|edge| {
+            let subpath = edge.subpath.as_deref()?;
+            let heading = subpath.rsplit('#').next()?.trim();
+            (!subpath.starts_with('^') && !heading.is_empty()).then(|| heading.to_string())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::{prelude::*, TempDir};
+
+    #[test]
+    fn builds_a_tree_of_neighbors() {
+        let dir = TempDir::new().unwrap();
+        dir.child("root.md")
+            .write_str("[[out#Far away]] [[both]]")
+            .unwrap();
+        dir.child("out.md").write_str("[[far]] [[both]]").unwrap();
+        dir.child("both.md").write_str("[[root]]").unwrap();
+        dir.child("in.md").write_str("[[root]]").unwrap();
+        dir.child("far.md").write_str("").unwrap();
+        let mut graph = LinkGraph::default();
+        graph.refresh(dir.path()).unwrap();
+
+        let row = |relative: &str, depth, direction| GraphRow {
+            relative: relative.to_string(),
+            depth,
+            direction,
+            heading: None,
+        };
+        assert_eq!(
+            build(&graph, "root.md", 2),
+            [
+                row("root.md", 0, None),
+                row("both.md", 1, Some(Direction::Both)),
+                row("in.md", 1, Some(Direction::In)),
+                GraphRow {
+                    heading: Some("Far away".to_string()),
+                    ..row("out.md", 1, Some(Direction::Out))
+                },
+                row("far.md", 2, Some(Direction::Out)),
+            ]
+        );
+        assert_eq!(build(&graph, "root.md", 1).len(), 4);
+    }
+}
@@ -1,20 +1,49 @@
+pub mod daemon;
+mod local_graph;
+mod preview;
+mod worker;
+
 use std::{
     collections::{HashMap, HashSet},
-    fs,
     io::{stdout, Stdout},
+    panic,
     path::{Path, PathBuf},
-    process::Command,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
     time::Duration,
 };
 
-use crate::{cli_config, theme::Theme};
+use crate::{
+    attachments,
+    cache::MetadataCache,
+    cli_config,
+    dates::DateFormats,
+    editor,
+    fuzzy::{FuzzyMatch, FuzzyScoring},
+    matching::MatchSettings,
+    mentions::{self, Mention},
+    obsidian_uri,
+    related::RelatedNote,
+    relations::{Relation, RelationSettings},
+    scan,
+    search_query::SearchResult,
+    theme::Theme,
+};
 use anyhow::{anyhow, Context, Result};
 use chrono::{DateTime, Local};
 use crossterm::{
-    event::{self, Event, KeyCode, KeyEvent, KeyEventKind},
+    cursor::Show,
+    event::{
+        self, DisableBracketedPaste, EnableBracketedPaste, Event, KeyCode, KeyEvent, KeyEventKind,
+        KeyModifiers,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use local_graph::GraphRow;
+use preview::Preview;
 use ratatui::{
     backend::CrosstermBackend,
     layout::{Constraint, Direction, Layout, Rect},
@@ -23,8 +52,18 @@ use ratatui::{
     widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
     Terminal,
 };
-use serde_yaml::Value;
-use walkdir::WalkDir;
+use worker::{Request, Response, Worker};
+
+/// The most notes the quick switcher lists
+const SWITCHER_LIMIT: usize = 50;
+/// The most related notes listed for a note
+const RELATED_LIMIT: usize = 20;
+/// The most tags suggested while one is typed into a search
+const TAG_SUGGESTIONS: usize = 5;
+/// How far PageUp and PageDown scroll the preview
+const PAGE_LINES: isize = 20;
+/// How long to wait for input before checking on the worker again
+const POLL_MILLIS: u64 = 50;
 
 #[derive(Debug, Clone)]
 struct FolderEntry {
@@ -43,9 +82,8 @@ struct NoteEntry {
 }
 
 impl NoteEntry {
-    fn formatted_modified(&self) -> Option<String> {
-        self.modified
-            .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+    fn formatted_modified(&self, dates: &DateFormats) -> Option<String> {
+        self.modified.map(|dt| dates.format(&dt, &dates.timestamp))
     }
 }
 
@@ -80,10 +118,58 @@ enum AppAction {
     Open { editor: String, note: PathBuf },
 }
 
+/// What the TUI takes from the config
+struct Settings {
+    theme: Theme,
+    dates: DateFormats,
+    fuzzy: FuzzyScoring,
+    relations: RelationSettings,
+    matching: MatchSettings,
+    editor_command: Option<String>,
+    attachment_folder: Option<PathBuf>,
+}
+
+impl Settings {
+    fn from_config() -> Result<Self> {
+        match cli_config::read() {
+            Ok(cfg) => Ok(Self {
+                theme: cfg.resolve_theme()?,
+                dates: cfg.dates.clone(),
+                fuzzy: cfg.fuzzy.clone(),
+                relations: cfg.relations.clone(),
+                matching: cfg.matching.clone(),
+                editor_command: cfg.editor.clone(),
+                attachment_folder: cfg.attachment_folder.clone(),
+            }),
+            Err(_) => Ok(Self {
+                theme: Theme::default(),
+                dates: DateFormats::default(),
+                fuzzy: FuzzyScoring::default(),
+                relations: RelationSettings::default(),
+                matching: MatchSettings::default(),
+                editor_command: None,
+                attachment_folder: None,
+            }),
+        }
+    }
+}
+
 pub struct AppState {
     vault_path: PathBuf,
     theme: Theme,
+    dates: DateFormats,
+    /// How headings in links are matched when scrolling to them
+    matching: MatchSettings,
     editor_command: Option<String>,
+    /// The config's `attachment_folder`, the folder is otherwise picked per
+    /// note
+    attachment_folder: Option<PathBuf>,
+    /// Reads the vault, previews and searches off the UI thread
+    worker: Worker,
+    /// Folders and note metadata, kept up to date as notes are edited
+    metadata: MetadataCache,
+    /// Whether the worker has sent the vault's metadata yet
+    loaded: bool,
     folders: Vec<FolderEntry>,
     folder_index: HashMap<PathBuf, usize>,
     expanded: HashSet<PathBuf>,
@@ -91,55 +177,237 @@ pub struct AppState {
     notes_cache: HashMap<PathBuf, Vec<NoteEntry>>,
     selected_note: Option<usize>,
     focus: Focus,
-    note_preview: String,
+    preview: Preview,
     base_status: String,
     status: String,
+    attach_input: Option<String>,
+    search_input: Option<String>,
+    /// Notes matching the last search, shown instead of the folder's notes
+    search: Option<SearchResults>,
+    /// The submitted search the worker is still running
+    pending_search: Option<String>,
+    /// The heading to scroll the preview to once the worker has read its
+    /// note
+    pending_heading: Option<(PathBuf, String)>,
+    /// The note the worker is finding related notes for
+    pending_related: Option<PathBuf>,
+    /// The note the worker is finding unlinked mentions of
+    pending_unlinked: Option<PathBuf>,
+    /// The note the worker is finding parents and children of
+    pending_relations: Option<PathBuf>,
+    switcher_input: Option<String>,
+    /// The local graph panel, while it's open
+    local_graph: Option<LocalGraph>,
 }
 
-impl AppState {
-    fn new(vault_path: PathBuf, theme: Theme, editor_command: Option<String>) -> Result<Self> {
-        let folders = build_folder_entries(&vault_path)?;
-        let mut folder_index = HashMap::new();
-        for (idx, folder) in folders.iter().enumerate() {
-            folder_index.insert(folder.path.clone(), idx);
-        }
+struct SearchResults {
+    /// The notes panel's title while they're shown
+    title: String,
+    notes: Vec<NoteEntry>,
+    /// The mentions behind each of `notes`, when they're unlinked mentions
+    unlinked: Option<UnlinkedMentions>,
+}
 
-        let expanded = initialize_expanded_folders(&folders, &vault_path);
+struct UnlinkedMentions {
+    /// The note they mention
+    target: PathBuf,
+    mentions: Vec<Mention>,
+}
 
-        let selected_folder = folders
-            .first()
-            .map(|f| f.path.clone())
-            .unwrap_or_else(|| vault_path.clone());
+struct LocalGraph {
+    /// The note at the top of the tree
+    root: PathBuf,
+    depth: usize,
+    /// `None` until the worker has walked the links
+    rows: Option<Vec<GraphRow>>,
+    selected: usize,
+}
 
-        let mut notes_cache = HashMap::new();
-        let mut selected_note = None;
-        ensure_notes_loaded(&mut notes_cache, &selected_folder)?;
-        if let Some(notes) = notes_cache.get(&selected_folder) {
-            if !notes.is_empty() {
-                selected_note = Some(0);
-            }
-        }
+impl AppState {
+    /// The app with nothing loaded yet, the vault arriving from the worker
+    /// in [`Response::Vault`]
+    fn new(vault_path: PathBuf, settings: Settings) -> Self {
+        let Settings {
+            theme,
+            dates,
+            fuzzy,
+            relations,
+            matching,
+            editor_command,
+            attachment_folder,
+        } = settings;
+        let worker = Worker::spawn(vault_path.clone(), fuzzy, relations);
+        worker.send(Request::LoadVault);
 
         let mut app = Self {
+            selected_folder: vault_path.clone(),
             vault_path,
             theme,
+            dates,
+            matching,
             editor_command,
-            folders,
-            folder_index,
-            expanded,
-            selected_folder,
-            notes_cache,
-            selected_note,
+            attachment_folder,
+            worker,
+            metadata: MetadataCache::default(),
+            loaded: false,
+            folders: Vec::new(),
+            folder_index: HashMap::new(),
+            expanded: HashSet::new(),
+            notes_cache: HashMap::new(),
+            selected_note: None,
             focus: Focus::Folders,
-            note_preview: String::new(),
+            preview: Preview::message(""),
             base_status: String::new(),
             status: String::new(),
+            attach_input: None,
+            search_input: None,
+            search: None,
+            pending_search: None,
+            pending_heading: None,
+            pending_related: None,
+            pending_unlinked: None,
+            pending_relations: None,
+            switcher_input: None,
+            local_graph: None,
         };
-        let base_status = app.default_status_message();
-        app.base_status = base_status.clone();
-        app.status = base_status;
-        app.refresh_note_preview();
-        Ok(app)
+        app.base_status = app.default_status_message();
+        app.status = "Loading vault…".to_string();
+        app
+    }
+
+    /// Rebuild the folders and notes from fresh metadata, keeping the
+    /// selected folder and note where they still exist
+    fn apply_metadata(&mut self, metadata: MetadataCache) {
+        let selected = self.selected_note_path();
+        self.metadata = metadata;
+        self.folders = build_folder_entries(&self.vault_path, &self.metadata);
+        self.folder_index = self
+            .folders
+            .iter()
+            .enumerate()
+            .map(|(idx, folder)| (folder.path.clone(), idx))
+            .collect();
+        if !self.loaded {
+            self.loaded = true;
+            self.expanded = initialize_expanded_folders(&self.folders, &self.vault_path);
+            self.reset_status();
+        }
+        if !self.folder_index.contains_key(&self.selected_folder) {
+            self.selected_folder = self
+                .folders
+                .first()
+                .map(|f| f.path.clone())
+                .unwrap_or_else(|| self.vault_path.clone());
+        }
+
+        self.notes_cache.clear();
+        ensure_notes_loaded(
+            &mut self.notes_cache,
+            &self.selected_folder,
+            &self.vault_path,
+            &self.metadata,
+        );
+        let notes = self.notes_for_selected_folder();
+        self.selected_note = selected
+            .and_then(|path| notes.iter().position(|note| note.path == path))
+            .or((!notes.is_empty()).then_some(0));
+        self.refresh_note_preview();
+    }
+
+    fn handle_response(&mut self, response: Response) {
+        match response {
+            Response::Vault(Ok(metadata)) => self.apply_metadata(metadata),
+            Response::Vault(Err(err)) => self.set_status(err.to_string()),
+            Response::Preview { path, preview } => {
+                if self.preview.path() != Some(path.as_path()) {
+                    return;
+                }
+                self.preview = preview.unwrap_or_else(|err| {
+                    Preview::message(format!("Failed to read note {}: {}", path.display(), err))
+                });
+                if let Some((heading_path, heading)) = self.pending_heading.take() {
+                    if heading_path == path {
+                        self.preview.scroll_to_heading(&heading, &self.matching);
+                    }
+                }
+            }
+            Response::PreviewChunk {
+                path,
+                offset,
+                chunk,
+            } => {
+                if self.preview.path() != Some(path.as_path()) {
+                    return;
+                }
+                match chunk {
+                    Ok(chunk) => {
+                        self.preview.append(offset, chunk);
+                        // The view may have moved on while this was read
+                        self.request_preview_chunk();
+                    }
+                    Err(err) => self.set_status(format!("Failed to read more of the note: {err}")),
+                }
+            }
+            Response::Search { query, found } => {
+                if self.pending_search.as_ref() == Some(&query) {
+                    self.pending_search = None;
+                    match found {
+                        Ok(found) => self.show_search_results(query, found),
+                        Err(err) => self.set_status(err.to_string()),
+                    }
+                }
+            }
+            Response::Related { path, found } => {
+                if self.pending_related.as_ref() == Some(&path) {
+                    self.pending_related = None;
+                    match found {
+                        Ok(found) => self.show_related_notes(&path, found),
+                        Err(err) => self.set_status(err.to_string()),
+                    }
+                }
+            }
+            Response::Unlinked { path, found } => {
+                if self.pending_unlinked.as_ref() == Some(&path) {
+                    self.pending_unlinked = None;
+                    match found {
+                        Ok(found) => self.show_unlinked_mentions(path, found),
+                        Err(err) => self.set_status(err.to_string()),
+                    }
+                }
+            }
+            Response::Relations { path, found } => {
+                if self.pending_relations.as_ref() == Some(&path) {
+                    self.pending_relations = None;
+                    match found {
+                        Ok(found) => self.show_relations(&path, found),
+                        Err(err) => self.set_status(err.to_string()),
+                    }
+                }
+            }
+            Response::Switcher { query, found } => {
+                if self.switcher_input.as_ref() == Some(&query) {
+                    self.show_switcher_matches(query, found);
+                }
+            }
+            Response::LocalGraph { path, depth, rows } => {
+                let Some(graph) = self.local_graph.as_mut() else {
+                    return;
+                };
+                if graph.root != path || graph.depth != depth {
+                    return;
+                }
+                match rows {
+                    Ok(rows) => {
+                        graph.rows = Some(rows);
+                        graph.selected = 0;
+                    }
+                    Err(err) => {
+                        self.local_graph = None;
+                        self.set_status(err.to_string());
+                    }
+                }
+            }
+        }
     }
 
     fn current_folder_entry(&self) -> Option<&FolderEntry> {
@@ -205,7 +473,13 @@ impl AppState {
     }
 
     fn select_folder(&mut self, path: PathBuf) -> Result<()> {
-        ensure_notes_loaded(&mut self.notes_cache, &path)?;
+        ensure_notes_loaded(
+            &mut self.notes_cache,
+            &path,
+            &self.vault_path,
+            &self.metadata,
+        );
+        self.search = None;
         self.selected_folder = path.clone();
         let notes = self.notes_cache.get(&path);
         self.selected_note = notes.and_then(|entries| (!entries.is_empty()).then_some(0));
@@ -230,6 +504,9 @@ impl AppState {
     }
 
     fn notes_for_selected_folder(&self) -> &[NoteEntry] {
+        if let Some(search) = &self.search {
+            return &search.notes;
+        }
         self.notes_cache
             .get(&self.selected_folder)
             .map(|v| v.as_slice())
@@ -240,7 +517,7 @@ impl AppState {
         let notes = self.notes_for_selected_folder();
         if notes.is_empty() {
             self.selected_note = None;
-            self.note_preview.clear();
+            self.preview = Preview::message("");
             return;
         }
         let current = self.selected_note.unwrap_or(0) as isize;
@@ -282,38 +559,52 @@ impl AppState {
         Ok(Some(AppAction::Open { editor, note: path }))
     }
 
-    fn refresh_after_external_edit(&mut self, note_path: &Path) -> Result<()> {
-        self.notes_cache.remove(&self.selected_folder);
-        ensure_notes_loaded(&mut self.notes_cache, &self.selected_folder)?;
-
-        if let Some(entries) = self.notes_cache.get(&self.selected_folder) {
-            if let Some(idx) = entries.iter().position(|note| note.path == note_path) {
-                self.selected_note = Some(idx);
-            } else if entries.is_empty() {
-                self.selected_note = None;
-            } else {
-                self.selected_note = Some(0);
-            }
-        } else {
-            self.selected_note = None;
+    fn open_in_obsidian(&mut self) {
+        let Some(path) = self.selected_note_path() else {
+            self.set_status("Select a note to open in Obsidian");
+            return;
+        };
+        let uri = obsidian_uri::open_uri(&self.vault_path, &path);
+        match open::that(&uri) {
+            Ok(()) => self.set_status(format!(
+                "Opened {} in Obsidian",
+                scan::relative_note_path(&path, &self.vault_path)
+            )),
+            Err(err) => self.set_status(format!("Could not open obsidian url `{uri}`: {err}")),
         }
+    }
 
-        self.refresh_note_preview();
-        Ok(())
+    /// Have the worker read the note again, the vault's metadata arriving
+    /// with the note still selected
+    fn refresh_after_external_edit(&mut self, note_path: &Path) {
+        self.worker
+            .send(Request::NoteChanged(note_path.to_path_buf()));
     }
 
     fn refresh_note_preview(&mut self) {
         if let Some(path) = self.selected_note_path() {
-            match fs::read_to_string(&path) {
-                Ok(content) => {
-                    self.note_preview = content;
-                }
-                Err(err) => {
-                    self.note_preview = format!("Failed to read note {}: {}", path.display(), err);
-                }
-            }
+            self.preview = Preview::loading(&path);
+            self.worker.send(Request::Preview(path));
         } else {
-            self.note_preview = String::from("Select a note to preview");
+            self.preview = Preview::message("Select a note to preview");
+        }
+    }
+
+    fn scroll_preview(&mut self, delta: isize) {
+        self.preview.scroll_by(delta);
+        self.request_preview_chunk();
+    }
+
+    fn request_preview_chunk(&mut self) {
+        let Some(offset) = self.preview.next_chunk() else {
+            return;
+        };
+        if let Some(path) = self.preview.path() {
+            self.worker.send(Request::PreviewChunk {
+                path: path.to_path_buf(),
+                offset,
+                size: self.preview.size(),
+            });
         }
     }
 
@@ -333,17 +624,554 @@ impl AppState {
             .map(|s| s.to_string())
             .unwrap_or_else(|| self.vault_path.to_string_lossy().into_owned());
         format!(
-            "Vault: {} • ↑/↓ navigate • ←/→ fold • Enter open • Tab switch panel • q quit",
+            "Vault: {} • ↑/↓ navigate • ←/→ fold • Enter open • O Obsidian • / search • r related • u mentions • h up/children • g graph • Ctrl-P go to • a attach • Tab switch panel • q quit",
             vault_name
         )
     }
 
+    fn start_attach_prompt(&mut self, initial: &str) {
+        if self.selected_note_entry().is_none() {
+            self.set_status("Select a note to attach files to");
+            return;
+        }
+        self.attach_input = Some(initial.to_string());
+    }
+
+    fn handle_attach_key(&mut self, key: KeyEvent) {
+        let Some(input) = self.attach_input.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Char(c) => input.push(c),
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Enter => self.submit_attachment(),
+            KeyCode::Esc => {
+                self.attach_input = None;
+                self.reset_status();
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_paste(&mut self, text: &str) {
+        if let Some(input) = self.search_input.as_mut() {
+            input.push_str(text);
+            return;
+        }
+        if let Some(input) = self.switcher_input.as_mut() {
+            input.push_str(text);
+            self.update_switcher();
+            return;
+        }
+        match self.attach_input.as_mut() {
+            Some(input) => input.push_str(text),
+            None => self.start_attach_prompt(text),
+        }
+    }
+
+    fn handle_search_key(&mut self, key: KeyEvent) {
+        let Some(input) = self.search_input.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Char(c) => input.push(c),
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Tab => self.complete_search_tag(),
+            KeyCode::Enter => self.submit_search(),
+            KeyCode::Esc => {
+                self.search_input = None;
+                self.pending_search = None;
+                self.reset_status();
+            }
+            _ => {}
+        }
+    }
+
+    /// Tags from the vault finishing the `tag:` the search ends in
+    fn search_tag_suggestions(&self) -> Vec<String> {
+        let Some(typed) = self.search_input.as_deref().and_then(tag_being_typed) else {
+            return Vec::new();
+        };
+
+        let mut tags = self.metadata.complete_tag(typed, &self.matching);
+        tags.truncate(TAG_SUGGESTIONS);
+        tags
+    }
+
+    /// Finish the tag being typed into the search with the best suggestion
+    fn complete_search_tag(&mut self) {
+        let Some(tag) = self.search_tag_suggestions().into_iter().next() else {
+            return;
+        };
+        let Some(input) = self.search_input.as_mut() else {
+            return;
+        };
+
+        let typed = tag_being_typed(input).map_or(0, str::len);
+        input.truncate(input.len() - typed);
+        input.push_str(&tag);
+        input.push(' ');
+    }
+
+    /// Have the worker run a search query over the vault, using its index
+    /// when one is built
+    fn submit_search(&mut self) {
+        let Some(query) = self.search_input.take() else {
+            return;
+        };
+        if query.trim().is_empty() {
+            self.clear_search();
+            return;
+        }
+
+        self.set_status(format!("Searching for `{query}`…"));
+        self.pending_search = Some(query.clone());
+        self.worker.send(Request::Search(query));
+    }
+
+    /// List the notes a search found in the notes panel
+    fn show_search_results(&mut self, query: String, found: Vec<SearchResult>) {
+        let notes: Vec<NoteEntry> = found
+            .into_iter()
+            .filter_map(|result| {
+                let mut entry = note_entry(&self.vault_path, &self.metadata, &result.path)?;
+                entry.name = result.path;
+                Some(entry)
+            })
+            .collect();
+
+        self.set_status(format!("{} notes match `{query}` • Esc clear", notes.len()));
+        self.selected_note = (!notes.is_empty()).then_some(0);
+        self.search = Some(SearchResults {
+            title: format!("Notes matching `{query}`"),
+            notes,
+            unlinked: None,
+        });
+        self.focus = Focus::Notes;
+        self.refresh_note_preview();
+    }
+
+    /// Have the worker find the notes most like the selected one
+    fn request_related(&mut self) {
+        let Some(path) = self.selected_note_path() else {
+            self.set_status("Select a note to find related notes");
+            return;
+        };
+
+        self.set_status(format!(
+            "Finding notes related to {}…",
+            scan::relative_note_path(&path, &self.vault_path)
+        ));
+        self.pending_related = Some(path.clone());
+        self.worker.send(Request::Related(path));
+    }
+
+    /// List the notes related to `path` in the notes panel
+    fn show_related_notes(&mut self, path: &Path, found: Vec<RelatedNote>) {
+        let notes: Vec<NoteEntry> = found
+            .into_iter()
+            .take(RELATED_LIMIT)
+            .filter_map(|related| {
+                let mut entry = note_entry(&self.vault_path, &self.metadata, &related.path)?;
+                entry.name = format!("{} ({})", related.path, related.reasons());
+                Some(entry)
+            })
+            .collect();
+
+        let relative = scan::relative_note_path(path, &self.vault_path);
+        self.set_status(format!(
+            "{} notes related to {relative} • Esc clear",
+            notes.len()
+        ));
+        self.selected_note = (!notes.is_empty()).then_some(0);
+        self.search = Some(SearchResults {
+            title: format!("Related to {relative}"),
+            notes,
+            unlinked: None,
+        });
+        self.focus = Focus::Notes;
+        self.refresh_note_preview();
+    }
+
+    /// Have the worker find the selected note's parents, children and
+    /// related notes, from frontmatter like `up: "[[Projects]]"`
+    fn request_relations(&mut self) {
+        let Some(path) = self.selected_note_path() else {
+            self.set_status("Select a note to find its parents and children");
+            return;
+        };
+
+        self.set_status(format!(
+            "Finding parents and children of {}…",
+            scan::relative_note_path(&path, &self.vault_path)
+        ));
+        self.pending_relations = Some(path.clone());
+        self.worker.send(Request::Relations(path));
+    }
+
+    /// List the notes above, below and beside `path` in the notes panel,
+    /// marked ↑, ↓ and ↔
+    fn show_relations(&mut self, path: &Path, found: Vec<(Relation, String)>) {
+        let notes: Vec<NoteEntry> = found
+            .into_iter()
+            .filter_map(|(relation, relative)| {
+                let mut entry = note_entry(&self.vault_path, &self.metadata, &relative)?;
+                entry.name = format!("{} {relative}", relation.arrow());
+                Some(entry)
+            })
+            .collect();
+
+        let relative = scan::relative_note_path(path, &self.vault_path);
+        self.set_status(if notes.is_empty() {
+            format!("{relative} has no parents, children or related notes • Esc clear")
+        } else {
+            format!("Up and children of {relative} • Esc clear")
+        });
+        self.selected_note = (!notes.is_empty()).then_some(0);
+        self.search = Some(SearchResults {
+            title: format!("Up and children of {relative}"),
+            notes,
+            unlinked: None,
+        });
+        self.focus = Focus::Notes;
+        self.refresh_note_preview();
+    }
+
+    /// Have the worker find where other notes mention the selected one
+    /// without linking to it
+    fn request_unlinked(&mut self) {
+        let Some(path) = self.selected_note_path() else {
+            self.set_status("Select a note to find its unlinked mentions");
+            return;
+        };
+
+        self.set_status(format!(
+            "Finding unlinked mentions of {}…",
+            scan::relative_note_path(&path, &self.vault_path)
+        ));
+        self.pending_unlinked = Some(path.clone());
+        self.worker.send(Request::Unlinked(path));
+    }
+
+    /// List the mentions of `target` in the notes panel, one entry each
+    fn show_unlinked_mentions(&mut self, target: PathBuf, found: Vec<Mention>) {
+        let mut notes = Vec::new();
+        let mut mentions = Vec::new();
+        for mention in found {
+            let Some(mut entry) = note_entry(&self.vault_path, &self.metadata, &mention.source)
+            else {
+                continue;
+            };
+            entry.name = format!("{}:{}  {}", mention.source, mention.line, mention.context);
+            notes.push(entry);
+            mentions.push(mention);
+        }
+
+        let relative = scan::relative_note_path(&target, &self.vault_path);
+        self.set_status(format!(
+            "{} unlinked mentions of {relative} • l link • Esc clear",
+            notes.len()
+        ));
+        self.selected_note = self
+            .selected_note
+            .filter(|idx| *idx < notes.len())
+            .or((!notes.is_empty()).then_some(0));
+        self.search = Some(SearchResults {
+            title: format!("Unlinked mentions of {relative}"),
+            notes,
+            unlinked: Some(UnlinkedMentions { target, mentions }),
+        });
+        self.focus = Focus::Notes;
+        self.refresh_note_preview();
+    }
+
+    /// Turn the selected unlinked mention into a wikilink, then find the
+    /// mentions again as the note's text has moved
+    fn link_selected_mention(&mut self) {
+        let Some(unlinked) = self
+            .search
+            .as_ref()
+            .and_then(|search| search.unlinked.as_ref())
+        else {
+            self.set_status("Press u to list a note's unlinked mentions first");
+            return;
+        };
+        let Some(mention) = self
+            .selected_note
+            .and_then(|idx| unlinked.mentions.get(idx))
+        else {
+            return;
+        };
+
+        let target = unlinked.target.clone();
+        let source = self.vault_path.join(&mention.source);
+        match mentions::link(&self.vault_path, &target, std::slice::from_ref(mention)) {
+            Ok(()) => {
+                self.refresh_after_external_edit(&source);
+                self.pending_unlinked = Some(target.clone());
+                self.worker.send(Request::Unlinked(target));
+            }
+            Err(err) => self.set_status(err.to_string()),
+        }
+    }
+
+    fn start_switcher(&mut self) {
+        self.switcher_input = Some(String::new());
+        self.update_switcher();
+    }
+
+    fn handle_switcher_key(&mut self, key: KeyEvent) -> Result<AppAction> {
+        let Some(input) = self.switcher_input.as_mut() else {
+            return Ok(AppAction::Continue);
+        };
+        match key.code {
+            KeyCode::Char(c) => {
+                input.push(c);
+                self.update_switcher();
+            }
+            KeyCode::Backspace => {
+                input.pop();
+                self.update_switcher();
+            }
+            KeyCode::Up => self.move_note_selection(-1),
+            KeyCode::Down => self.move_note_selection(1),
+            KeyCode::Enter => {
+                self.switcher_input = None;
+                self.focus = Focus::Notes;
+                if let Some(action) = self.prepare_open_action()? {
+                    return Ok(action);
+                }
+            }
+            KeyCode::Esc => {
+                self.switcher_input = None;
+                self.clear_search();
+                self.reset_status();
+            }
+            _ => {}
+        }
+        Ok(AppAction::Continue)
+    }
+
+    /// Have the worker match notes against the switcher's input, or list the
+    /// folder's notes while it's empty
+    fn update_switcher(&mut self) {
+        let Some(query) = self.switcher_input.clone() else {
+            return;
+        };
+        if query.is_empty() {
+            self.clear_search();
+            return;
+        }
+
+        self.worker.send(Request::Switcher(query));
+    }
+
+    /// List the notes best matching the switcher's input
+    fn show_switcher_matches(&mut self, query: String, found: Vec<FuzzyMatch>) {
+        let notes: Vec<NoteEntry> = found
+            .into_iter()
+            .take(SWITCHER_LIMIT)
+            .filter_map(|found| {
+                let mut entry = note_entry(&self.vault_path, &self.metadata, &found.relative)?;
+                entry.name = match found.alias {
+                    Some(alias) => format!("{} ({alias})", found.relative),
+                    None => found.relative,
+                };
+                Some(entry)
+            })
+            .collect();
+
+        self.selected_note = (!notes.is_empty()).then_some(0);
+        self.search = Some(SearchResults {
+            title: format!("Notes matching `{query}`"),
+            notes,
+            unlinked: None,
+        });
+        self.refresh_note_preview();
+    }
+
+    /// Open the local graph panel on the selected note
+    fn start_local_graph(&mut self) {
+        let Some(path) = self.selected_note_path() else {
+            self.set_status("Select a note to show its local graph");
+            return;
+        };
+        self.show_local_graph(path, local_graph::DEFAULT_DEPTH);
+    }
+
+    /// Have the worker walk the links around `root`, the panel showing them
+    /// once they arrive
+    fn show_local_graph(&mut self, root: PathBuf, depth: usize) {
+        self.worker.send(Request::LocalGraph {
+            path: root.clone(),
+            depth,
+        });
+        self.local_graph = Some(LocalGraph {
+            root,
+            depth,
+            rows: None,
+            selected: 0,
+        });
+    }
+
+    /// The note selected in the local graph panel
+    fn selected_graph_note(&self) -> Option<PathBuf> {
+        let graph = self.local_graph.as_ref()?;
+        let row = graph.rows.as_ref()?.get(graph.selected)?;
+        Some(self.vault_path.join(&row.relative))
+    }
+
+    /// The heading of `path`, the selected note, that the note above it in
+    /// the local graph links into
+    fn selected_graph_heading(&self, path: &Path) -> Option<(PathBuf, String)> {
+        let graph = self.local_graph.as_ref()?;
+        let row = graph.rows.as_ref()?.get(graph.selected)?;
+        Some((path.to_path_buf(), row.heading.clone()?))
+    }
+
+    fn handle_local_graph_key(&mut self, key: KeyEvent) -> Result<AppAction> {
+        let Some(graph) = self.local_graph.as_mut() else {
+            return Ok(AppAction::Continue);
+        };
+        let (root, depth) = (graph.root.clone(), graph.depth);
+        match key.code {
+            KeyCode::Up | KeyCode::Down => {
+                let Some(rows) = &graph.rows else {
+                    return Ok(AppAction::Continue);
+                };
+                graph.selected = match key.code {
+                    KeyCode::Up => graph.selected.saturating_sub(1),
+                    _ => (graph.selected + 1).min(rows.len().saturating_sub(1)),
+                };
+                if let Some(path) = self.selected_graph_note() {
+                    self.pending_heading = self.selected_graph_heading(&path);
+                    self.preview = Preview::loading(&path);
+                    self.worker.send(Request::Preview(path));
+                }
+            }
+            KeyCode::Enter => {
+                if let Some(path) = self.selected_graph_note() {
+                    self.pending_heading = self.selected_graph_heading(&path);
+                    self.reveal_note(&path);
+                    self.show_local_graph(path, depth);
+                }
+            }
+            KeyCode::Char(c @ '1'..='9') => {
+                let wanted = (c as usize - '0' as usize).min(local_graph::MAX_DEPTH);
+                if wanted != depth {
+                    self.show_local_graph(root, wanted);
+                }
+            }
+            KeyCode::Char('e') | KeyCode::Char('o') => {
+                if let Some(path) = self.selected_graph_note() {
+                    self.reveal_note(&path);
+                    if let Some(action) = self.prepare_open_action()? {
+                        return Ok(action);
+                    }
+                }
+            }
+            KeyCode::Char('q') => return Ok(AppAction::Quit),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return Ok(AppAction::Quit)
+            }
+            KeyCode::Esc | KeyCode::Char('g') => {
+                self.local_graph = None;
+                self.refresh_note_preview();
+            }
+            _ => {}
+        }
+        Ok(AppAction::Continue)
+    }
+
+    /// Select `path` in the notes panel, opening its folder and the folders
+    /// above it
+    fn reveal_note(&mut self, path: &Path) {
+        let Some(folder) = path.parent() else {
+            return;
+        };
+        for ancestor in folder.ancestors().skip(1) {
+            if !ancestor.starts_with(&self.vault_path) {
+                break;
+            }
+            self.expanded.insert(ancestor.to_path_buf());
+        }
+        if let Err(err) = self.select_folder(folder.to_path_buf()) {
+            self.set_status(err.to_string());
+            return;
+        }
+        if let Some(idx) = self
+            .notes_for_selected_folder()
+            .iter()
+            .position(|note| note.path == path)
+        {
+            self.selected_note = Some(idx);
+            self.refresh_note_preview();
+        }
+        self.focus = Focus::Notes;
+    }
+
+    fn clear_search(&mut self) {
+        if self.search.take().is_some() {
+            let has_notes = !self.notes_for_selected_folder().is_empty();
+            self.selected_note = has_notes.then_some(0);
+            self.refresh_note_preview();
+        }
+    }
+
+    fn submit_attachment(&mut self) {
+        let Some(input) = self.attach_input.take() else {
+            return;
+        };
+        let Some(note) = self.selected_note_path() else {
+            return;
+        };
+
+        let source = attachments::normalize_dropped_path(&input);
+        let note_folder = note
+            .parent()
+            .and_then(|folder| folder.strip_prefix(&self.vault_path).ok())
+            .unwrap_or(Path::new(""));
+        let attachment_folder = attachments::attachment_folder(
+            self.attachment_folder.as_deref(),
+            &self.vault_path,
+            note_folder,
+        );
+        match attachments::attach(&self.vault_path, &attachment_folder, &note, &source) {
+            Ok(link) => {
+                self.set_status(format!("Attached {link}"));
+                self.refresh_note_preview();
+            }
+            Err(err) => self.set_status(err.to_string()),
+        }
+    }
+
     fn handle_key(&mut self, key: KeyEvent) -> Result<AppAction> {
         if key.kind != KeyEventKind::Press {
             return Ok(AppAction::Continue);
         }
+        if self.attach_input.is_some() {
+            self.handle_attach_key(key);
+            return Ok(AppAction::Continue);
+        }
+        if self.search_input.is_some() {
+            self.handle_search_key(key);
+            return Ok(AppAction::Continue);
+        }
+        if self.switcher_input.is_some() {
+            return self.handle_switcher_key(key);
+        }
+        if self.local_graph.is_some() {
+            return self.handle_local_graph_key(key);
+        }
         match key.code {
             KeyCode::Char('q') => return Ok(AppAction::Quit),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return Ok(AppAction::Quit)
+            }
             KeyCode::Tab => {
                 self.focus = self.focus.next();
             }
@@ -357,7 +1185,7 @@ impl AppState {
                     }
                 }
                 Focus::Notes => self.move_note_selection(-1),
-                Focus::Viewer => {}
+                Focus::Viewer => self.scroll_preview(-1),
             },
             KeyCode::Down => match self.focus {
                 Focus::Folders => {
@@ -366,8 +1194,10 @@ impl AppState {
                     }
                 }
                 Focus::Notes => self.move_note_selection(1),
-                Focus::Viewer => {}
+                Focus::Viewer => self.scroll_preview(1),
             },
+            KeyCode::PageUp if self.focus == Focus::Viewer => self.scroll_preview(-PAGE_LINES),
+            KeyCode::PageDown if self.focus == Focus::Viewer => self.scroll_preview(PAGE_LINES),
             KeyCode::Left => {
                 if matches!(self.focus, Focus::Folders) {
                     if let Err(err) = self.collapse_selected_folder() {
@@ -396,13 +1226,28 @@ impl AppState {
                     return Ok(action);
                 }
             }
+            KeyCode::Char('O') => self.open_in_obsidian(),
+            KeyCode::Char('a') => self.start_attach_prompt(""),
             KeyCode::Char('n') | KeyCode::Char('d') => {
                 self.set_status("Action not implemented yet");
             }
+            KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.start_switcher();
+            }
             KeyCode::Char('/') => {
-                self.set_status("Search is not implemented yet");
+                self.search_input = Some(String::new());
             }
+            KeyCode::Char('r') => self.request_related(),
+            KeyCode::Char('g') => self.start_local_graph(),
+            KeyCode::Char('u') => self.request_unlinked(),
+            KeyCode::Char('h') => self.request_relations(),
+            KeyCode::Char('l') => self.link_selected_mention(),
             KeyCode::Esc => {
+                self.pending_search = None;
+                self.pending_related = None;
+                self.pending_unlinked = None;
+                self.pending_relations = None;
+                self.clear_search();
                 self.focus = Focus::Folders;
                 self.reset_status();
             }
@@ -412,6 +1257,15 @@ impl AppState {
     }
 }
 
+/// What's been typed of a tag at the end of a search, after its `tag:` and
+/// any `#`
+fn tag_being_typed(input: &str) -> Option<&str> {
+    let word = input.rsplit(char::is_whitespace).next()?;
+    let tag = word.trim_start_matches(['-', '(']).strip_prefix("tag:")?;
+
+    Some(tag.strip_prefix('#').unwrap_or(tag))
+}
+
 fn initialize_expanded_folders(folders: &[FolderEntry], vault_path: &Path) -> HashSet<PathBuf> {
     let mut expanded = HashSet::new();
     expanded.insert(vault_path.to_path_buf());
@@ -421,188 +1275,233 @@ fn initialize_expanded_folders(folders: &[FolderEntry], vault_path: &Path) -> Ha
     expanded
 }
 
-fn build_folder_entries(vault_path: &Path) -> Result<Vec<FolderEntry>> {
-    let mut entries = Vec::new();
-    let walker = WalkDir::new(vault_path).into_iter();
-    for entry in walker.filter_entry(|e| should_visit_dir(e)) {
-        let entry = entry?;
-        if entry.file_type().is_dir() {
-            let depth = entry.depth();
-            let path = entry.path().to_path_buf();
+fn build_folder_entries(vault_path: &Path, metadata: &MetadataCache) -> Vec<FolderEntry> {
+    metadata
+        .folders()
+        .iter()
+        .map(Path::new)
+        .filter(|relative| {
+            !relative
+                .components()
+                .any(|part| part.as_os_str() == "node_modules")
+        })
+        .map(|relative| {
+            let depth = relative.components().count();
+            let path = vault_path.join(relative);
             let name = if depth == 0 {
                 vault_path
                     .file_name()
                     .map(|n| n.to_string_lossy().into_owned())
                     .unwrap_or_else(|| vault_path.to_string_lossy().into_owned())
             } else {
-                entry.file_name().to_string_lossy().into_owned()
+                relative
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_default()
             };
             let parent = path.parent().map(|p| p.to_path_buf());
-            entries.push(FolderEntry {
+            FolderEntry {
                 path,
                 name,
                 depth,
                 parent,
-            });
-        }
-    }
-    Ok(entries)
-}
-
-fn should_visit_dir(entry: &walkdir::DirEntry) -> bool {
-    if entry.depth() == 0 {
-        return true;
-    }
-    if entry.file_type().is_dir() {
-        let name = entry.file_name().to_string_lossy();
-        return !name.starts_with('.') && name != "node_modules";
-    }
-    true
+            }
+        })
+        .collect()
 }
 
-fn ensure_notes_loaded(cache: &mut HashMap<PathBuf, Vec<NoteEntry>>, folder: &Path) -> Result<()> {
+fn ensure_notes_loaded(
+    cache: &mut HashMap<PathBuf, Vec<NoteEntry>>,
+    folder: &Path,
+    vault_path: &Path,
+    metadata: &MetadataCache,
+) {
     if cache.contains_key(folder) {
-        return Ok(());
+        return;
     }
-    let notes = read_notes(folder)?;
+    let notes = read_notes(folder, vault_path, metadata);
     cache.insert(folder.to_path_buf(), notes);
-    Ok(())
 }
 
-fn read_notes(folder: &Path) -> Result<Vec<NoteEntry>> {
-    let mut entries = Vec::new();
-    if folder.is_dir() {
-        for entry in
-            fs::read_dir(folder).with_context(|| format!("failed to read {}", folder.display()))?
-        {
-            let entry = entry?;
-            let path = entry.path();
-            if is_markdown(&path) {
-                entries.push(build_note_entry(path)?);
-            }
-        }
+fn read_notes(folder: &Path, vault_path: &Path, metadata: &MetadataCache) -> Vec<NoteEntry> {
+    let Ok(folder) = folder.strip_prefix(vault_path) else {
+        return Vec::new();
+    };
+    let mut entries: Vec<NoteEntry> = metadata
+        .notes()
+        .into_iter()
+        .filter(|(relative, _)| Path::new(relative).parent() == Some(folder))
+        .filter_map(|(relative, _)| note_entry(vault_path, metadata, relative))
+        .collect();
+    entries.extend(
+        metadata
+            .canvases()
+            .into_iter()
+            .filter(|(relative, _)| Path::new(relative).parent() == Some(folder))
+            .map(|(relative, modified)| canvas_entry(vault_path, relative, modified)),
+    );
+    entries.sort_by_key(|entry| entry.name.to_lowercase());
+    entries
+}
+
+fn canvas_entry(vault_path: &Path, relative: &str, modified: Option<DateTime<Local>>) -> NoteEntry {
+    let path = vault_path.join(relative);
+    NoteEntry {
+        name: path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default()
+            .to_string(),
+        modified,
+        tags: Vec::new(),
+        path,
     }
-    entries.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
-    Ok(entries)
 }
 
-fn build_note_entry(path: PathBuf) -> Result<NoteEntry> {
-    let metadata = fs::metadata(&path)
-        .with_context(|| format!("failed to read metadata for {}", path.display()))?;
-    let modified = metadata.modified().ok().map(DateTime::<Local>::from);
+fn note_entry(vault_path: &Path, metadata: &MetadataCache, relative: &str) -> Option<NoteEntry> {
+    let note = metadata.get(relative)?;
+    let path = vault_path.join(relative);
     let name = path
         .file_name()
         .and_then(|n| n.to_str())
         .unwrap_or_default()
         .to_string();
 
-    let content = fs::read_to_string(&path).unwrap_or_default();
-    let tags = extract_tags(&content);
-
-    Ok(NoteEntry {
+    Some(NoteEntry {
         path,
         name,
-        modified,
-        tags,
+        modified: note.modified(),
+        tags: note.tags.clone(),
     })
 }
 
-fn extract_tags(content: &str) -> Vec<String> {
-    let mut lines = content.lines();
-    match lines.next() {
-        Some(line) if line.trim() == "---" => {}
-        _ => return Vec::new(),
-    }
+pub fn run(vault_path: PathBuf) -> Result<()> {
+    let settings = Settings::from_config()?;
 
-    let mut front_matter = String::new();
-    for line in lines.by_ref() {
-        if line.trim() == "---" {
-            break;
-        }
-        front_matter.push_str(line);
-        front_matter.push('\n');
-    }
+    let signals = Signals::register()?;
+    let guard = TerminalGuard::enter()?;
+    let backend = CrosstermBackend::new(stdout());
+    let mut terminal = Terminal::new(backend)?;
+    terminal.clear()?;
 
-    if front_matter.is_empty() {
-        return Vec::new();
-    }
+    let res = run_app(&mut terminal, vault_path, settings, &signals);
 
-    let Ok(value) = serde_yaml::from_str::<Value>(&front_matter) else {
-        return Vec::new();
-    };
+    drop(guard);
 
-    match value.get("tags") {
-        Some(Value::Sequence(seq)) => seq
-            .iter()
-            .filter_map(|item| item.as_str().map(|s| s.to_string()))
-            .collect(),
-        Some(Value::String(tag)) => vec![tag.clone()],
-        _ => Vec::new(),
+    res
+}
+
+/// Puts the terminal into raw mode on the alternate screen, and restores it
+/// when dropped so early returns and panics never leave the shell broken
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enter() -> Result<Self> {
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            // Restore before printing, otherwise the message is written to
+            // the alternate screen and lost
+            restore_terminal();
+            default_hook(info);
+        }));
+
+        enable_raw_mode()?;
+        execute!(stdout(), EnterAlternateScreen, EnableBracketedPaste)?;
+        Ok(Self)
     }
 }
 
-fn is_markdown(path: &Path) -> bool {
-    path.extension()
-        .and_then(|ext| ext.to_str())
-        .map(|ext| ext.eq_ignore_ascii_case("md"))
-        .unwrap_or(false)
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore_terminal();
+    }
 }
 
-pub fn run(vault_path: PathBuf) -> Result<()> {
-    let (theme, editor_command) = match cli_config::read() {
-        Ok(cfg) => (cfg.theme.resolve(), cfg.editor.clone()),
-        Err(_) => (Theme::default(), None),
-    };
+fn restore_terminal() {
+    let _ = disable_raw_mode();
+    let _ = execute!(stdout(), DisableBracketedPaste, LeaveAlternateScreen, Show);
+}
 
-    enable_raw_mode()?;
-    let mut stdout = stdout();
-    execute!(stdout, EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
-    terminal.clear()?;
+/// Flags set by signal handlers and polled by the event loop, replacing the
+/// default "terminate immediately" behaviour so we always clean up first
+struct Signals {
+    terminate: Arc<AtomicBool>,
+    interrupt: Arc<AtomicBool>,
+}
 
-    let res = run_app(&mut terminal, vault_path, theme, editor_command);
+impl Signals {
+    fn register() -> Result<Self> {
+        use signal_hook::consts::signal::*;
 
-    disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
-    terminal.show_cursor()?;
+        #[cfg(unix)]
+        let terminate_signals = [SIGTERM, SIGHUP, SIGQUIT];
+        #[cfg(not(unix))]
+        let terminate_signals = [SIGTERM];
 
-    res
+        let terminate = Arc::new(AtomicBool::new(false));
+        for signal in terminate_signals {
+            signal_hook::flag::register(signal, Arc::clone(&terminate))
+                .context("failed to install signal handler")?;
+        }
+
+        let interrupt = Arc::new(AtomicBool::new(false));
+        signal_hook::flag::register(SIGINT, Arc::clone(&interrupt))
+            .context("failed to install signal handler")?;
+
+        Ok(Self {
+            terminate,
+            interrupt,
+        })
+    }
+
+    fn should_quit(&self) -> bool {
+        self.terminate.load(Ordering::Relaxed) || self.interrupt.load(Ordering::Relaxed)
+    }
+
+    /// A SIGINT delivered while a child process had the terminal was meant
+    /// for the child, so don't treat it as a request to quit
+    fn clear_interrupt(&self) {
+        self.interrupt.store(false, Ordering::Relaxed);
+    }
 }
 
 fn run_app(
     terminal: &mut Terminal<CrosstermBackend<Stdout>>,
     vault_path: PathBuf,
-    theme: Theme,
-    editor_command: Option<String>,
+    settings: Settings,
+    signals: &Signals,
 ) -> Result<()> {
-    let mut app = AppState::new(vault_path, theme, editor_command)?;
+    let mut app = AppState::new(vault_path, settings);
 
-    loop {
+    while !signals.should_quit() {
+        while let Some(response) = app.worker.try_recv() {
+            app.handle_response(response);
+        }
         terminal.draw(|f| draw(f, &app))?;
 
-        if event::poll(Duration::from_millis(200))? {
+        if event::poll(Duration::from_millis(POLL_MILLIS))? {
             match event::read()? {
                 Event::Key(key) => match app.handle_key(key)? {
                     AppAction::Quit => break,
                     AppAction::Continue => {}
                     AppAction::Open { editor, note } => {
                         suspend_terminal(terminal)?;
-                        let launch_result = launch_editor(&editor, &note);
+                        let launch_result = launch_editor(&editor, &note, &app.vault_path);
+                        signals.clear_interrupt();
                         resume_terminal(terminal)?;
 
                         match launch_result {
                             Ok(()) => {
-                                if let Err(err) = app.refresh_after_external_edit(&note) {
-                                    app.set_status(err.to_string());
-                                } else {
-                                    let display = note
-                                        .file_name()
-                                        .and_then(|n| n.to_str())
-                                        .map(|s| s.to_string())
-                                        .unwrap_or_else(|| note.display().to_string());
-                                    app.set_status(format!("Opened {display} with {editor}"));
-                                }
+                                app.refresh_after_external_edit(&note);
+                                let display = note
+                                    .file_name()
+                                    .and_then(|n| n.to_str())
+                                    .map(|s| s.to_string())
+                                    .unwrap_or_else(|| note.display().to_string());
+                                app.set_status(format!(
+                                    "Opened {display} with {}",
+                                    editor::program(&editor)
+                                ));
                             }
                             Err(err) => {
                                 app.set_status(err.to_string());
@@ -610,6 +1509,7 @@ fn run_app(
                         }
                     }
                 },
+                Event::Paste(text) => app.handle_paste(&text),
                 Event::Resize(_, _) => {}
                 _ => {}
             }
@@ -621,22 +1521,29 @@ fn run_app(
 
 fn suspend_terminal(terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> Result<()> {
     disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    execute!(
+        terminal.backend_mut(),
+        DisableBracketedPaste,
+        LeaveAlternateScreen
+    )?;
     terminal.show_cursor()?;
     Ok(())
 }
 
 fn resume_terminal(terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> Result<()> {
-    execute!(terminal.backend_mut(), EnterAlternateScreen)?;
+    execute!(
+        terminal.backend_mut(),
+        EnterAlternateScreen,
+        EnableBracketedPaste
+    )?;
     enable_raw_mode()?;
     terminal.hide_cursor()?;
     terminal.clear()?;
     Ok(())
 }
 
-fn launch_editor(editor: &str, note: &Path) -> Result<()> {
-    let status = Command::new(editor)
-        .arg(note)
+fn launch_editor(editor: &str, note: &Path, vault_path: &Path) -> Result<()> {
+    let status = editor::command(editor, note, None, Some(vault_path))?
         .status()
         .with_context(|| format!("failed to execute editor `{editor}`"))?;
 
@@ -674,7 +1581,16 @@ fn draw(frame: &mut Frame, app: &AppState) {
 
     render_folders(frame, left[0], app);
     render_notes(frame, left[1], app);
-    render_viewer(frame, columns[1], app);
+    if let Some(graph) = &app.local_graph {
+        let right = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+            .split(columns[1]);
+        render_viewer(frame, right[0], app);
+        render_local_graph(frame, right[1], app, graph);
+    } else {
+        render_viewer(frame, columns[1], app);
+    }
     render_status(frame, status_area, app);
 }
 
@@ -739,7 +1655,7 @@ fn render_notes(frame: &mut Frame, area: Rect, app: &AppState) {
             note.name.clone(),
             Style::default().fg(theme.note).bg(theme.background),
         )];
-        if let Some(modified) = note.formatted_modified() {
+        if let Some(modified) = note.formatted_modified(&app.dates) {
             spans.push(Span::raw("  "));
             spans.push(Span::styled(
                 modified,
@@ -783,7 +1699,10 @@ fn render_notes(frame: &mut Frame, area: Rect, app: &AppState) {
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title("Notes")
+                .title(match &app.search {
+                    Some(search) => search.title.clone(),
+                    None => "Notes".to_string(),
+                })
                 .style(block_style),
         )
         .highlight_style(highlight);
@@ -799,21 +1718,94 @@ fn render_viewer(frame: &mut Frame, area: Rect, app: &AppState) {
         Style::default().bg(theme.background)
     };
 
-    let paragraph = Paragraph::new(app.note_preview.as_str())
+    let paragraph = Paragraph::new(app.preview.text())
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title("Preview")
+                .title(app.preview.title())
                 .style(block_style),
         )
-        .style(Style::default().fg(theme.note).bg(theme.background));
+        .style(Style::default().fg(theme.note).bg(theme.background))
+        .scroll((app.preview.scroll(), 0));
 
     frame.render_widget(paragraph, area);
 }
 
+fn render_local_graph(frame: &mut Frame, area: Rect, app: &AppState, graph: &LocalGraph) {
+    let theme = &app.theme;
+    let items: Vec<ListItem> = match &graph.rows {
+        Some(rows) => rows
+            .iter()
+            .map(|row| {
+                let indent = "  ".repeat(row.depth);
+                let mut spans = vec![Span::raw(indent)];
+                if let Some(direction) = row.direction {
+                    spans.push(Span::styled(
+                        format!("{} ", direction.arrow()),
+                        Style::default().fg(theme.modified).bg(theme.background),
+                    ));
+                }
+                spans.push(Span::styled(
+                    row.relative.clone(),
+                    Style::default().fg(theme.note).bg(theme.background),
+                ));
+                ListItem::new(Line::from(spans))
+            })
+            .collect(),
+        None => vec![ListItem::new(Line::from(Span::styled(
+            "Following links…",
+            Style::default().fg(theme.note).bg(theme.background),
+        )))],
+    };
+
+    let mut state = ListState::default();
+    if graph.rows.is_some() {
+        state.select(Some(graph.selected));
+    }
+
+    let root = scan::relative_note_path(&graph.root, &app.vault_path);
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Local graph of {root} • depth {}", graph.depth))
+                .style(Style::default().fg(theme.accent).bg(theme.background)),
+        )
+        .highlight_style(
+            Style::default()
+                .fg(theme.accent)
+                .bg(theme.background)
+                .add_modifier(Modifier::BOLD),
+        );
+
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
 fn render_status(frame: &mut Frame, area: Rect, app: &AppState) {
     let theme = &app.theme;
-    let paragraph = Paragraph::new(app.status.as_str())
-        .style(Style::default().fg(theme.note).bg(theme.background));
+    let text = if let Some(input) = &app.attach_input {
+        format!("Attach file (Enter confirm, Esc cancel): {input}")
+    } else if let Some(input) = &app.search_input {
+        let tags = app.search_tag_suggestions();
+        if tags.is_empty() {
+            format!("Search (Enter confirm, Esc cancel): {input}")
+        } else {
+            format!(
+                "Search (Tab complete tag, Enter confirm, Esc cancel): {input}   #{}",
+                tags.join(" #")
+            )
+        }
+    } else if let Some(input) = &app.switcher_input {
+        format!("Go to note (↑/↓ choose, Enter open, Esc cancel): {input}")
+    } else if app.local_graph.is_some() {
+        format!(
+            "Local graph (↑/↓ choose, Enter jump, 1-{} depth, e open, Esc close)",
+            local_graph::MAX_DEPTH
+        )
+    } else {
+        app.status.clone()
+    };
+    let paragraph =
+        Paragraph::new(text).style(Style::default().fg(theme.note).bg(theme.background));
     frame.render_widget(paragraph, area);
 }
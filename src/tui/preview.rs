@@ -0,0 +1,339 @@
+//! The viewer's copy of the selected note. Big notes are read a chunk at a
+//! time as the viewer scrolls, so opening a huge note doesn't freeze the UI.
+//! The reading itself happens on the [worker](super::worker) thread.
+
+use crate::{link_resolver::find_heading, matching::MatchSettings};
+use libobsidian::{
+    canvas::{self, Canvas},
+    excalidraw,
+};
+use std::{
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+/// How much of a note is read at once
+const CHUNK_BYTES: u64 = 64 * 1024;
+/// Another chunk is read once the viewer scrolls within this many lines of
+/// the end of what's loaded
+const LOOKAHEAD_LINES: usize = 200;
+
+pub struct Preview {
+    path: Option<PathBuf>,
+    text: String,
+    /// Bytes of the note read so far
+    loaded: u64,
+    size: u64,
+    lines: usize,
+    scroll: usize,
+    /// Still waiting on the worker for the note
+    loading: bool,
+    /// Waiting on the worker for the next chunk
+    chunk_requested: bool,
+}
+
+/// A piece of a note read from disk
+#[derive(Debug)]
+pub struct Chunk {
+    text: String,
+    /// Bytes read
+    len: u64,
+}
+
+impl Preview {
+    /// A message shown in place of a note
+    pub fn message(text: impl Into<String>) -> Self {
+        let text = text.into();
+        Self {
+            path: None,
+            lines: text.lines().count(),
+            loaded: text.len() as u64,
+            size: text.len() as u64,
+            text,
+            scroll: 0,
+            loading: false,
+            chunk_requested: false,
+        }
+    }
+
+    /// Stands in for the note at `path` until the worker has read it
+    pub fn loading(path: &Path) -> Self {
+        Self {
+            path: Some(path.to_path_buf()),
+            loading: true,
+            ..Self::message("")
+        }
+    }
+
+    /// The start of the note at `path`, with the rest read while scrolling
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let size = path.metadata()?.len();
+        let mut preview = Self {
+            path: Some(path.to_path_buf()),
+            text: String::new(),
+            loaded: 0,
+            size,
+            lines: 0,
+            scroll: 0,
+            loading: false,
+            chunk_requested: false,
+        };
+        preview.append(0, read_chunk(path, 0, size)?);
+
+        if !preview.is_partial() {
+            preview.text = readable_text(path, std::mem::take(&mut preview.text));
+            preview.lines = preview.text.lines().count();
+        }
+
+        Ok(preview)
+    }
+
+    /// A note read elsewhere, such as by the [daemon](super::daemon), from
+    /// its text, how many bytes of it that is and its whole size
+    pub fn from_parts(path: &Path, text: String, loaded: u64, size: u64) -> Self {
+        Self {
+            path: Some(path.to_path_buf()),
+            lines: text.lines().count(),
+            text,
+            loaded,
+            size,
+            scroll: 0,
+            loading: false,
+            chunk_requested: false,
+        }
+    }
+
+    /// The text read so far, how many bytes of the note that is and its
+    /// whole size, the opposite of [`Preview::from_parts`]
+    pub fn into_parts(self) -> (String, u64, u64) {
+        (self.text, self.loaded, self.size)
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    pub fn scroll(&self) -> u16 {
+        u16::try_from(self.scroll).unwrap_or(u16::MAX)
+    }
+
+    /// Whether the note is bigger than one chunk
+    pub fn is_large(&self) -> bool {
+        self.path.is_some() && self.size > CHUNK_BYTES
+    }
+
+    /// Whether some of the note hasn't been read yet
+    pub fn is_partial(&self) -> bool {
+        self.loaded < self.size
+    }
+
+    pub fn title(&self) -> String {
+        if self.loading {
+            return "Preview • loading…".to_string();
+        }
+        if !self.is_large() {
+            return "Preview".to_string();
+        }
+        if self.is_partial() {
+            format!(
+                "Preview • large file, {} of {} loaded",
+                format_size(self.loaded),
+                format_size(self.size)
+            )
+        } else {
+            format!("Preview • large file, {}", format_size(self.size))
+        }
+    }
+
+    /// Move the view to `heading`, if it's in what's loaded so far
+    pub fn scroll_to_heading(&mut self, heading: &str, matching: &MatchSettings) -> bool {
+        let Some(found) = find_heading(&self.text, heading, matching) else {
+            return false;
+        };
+        self.scroll = found.line - 1;
+        true
+    }
+
+    /// Move the view by `delta` lines, within what's loaded so far
+    pub fn scroll_by(&mut self, delta: isize) {
+        self.scroll = self
+            .scroll
+            .saturating_add_signed(delta)
+            .min(self.lines.saturating_sub(1));
+    }
+
+    /// Where the next chunk starts, once the view nears the end of what's
+    /// loaded and it hasn't been asked for yet
+    pub fn next_chunk(&mut self) -> Option<u64> {
+        let wanted = !self.loading
+            && !self.chunk_requested
+            && self.is_partial()
+            && self.scroll + LOOKAHEAD_LINES >= self.lines;
+        self.chunk_requested |= wanted;
+        wanted.then_some(self.loaded)
+    }
+
+    /// Add a chunk read from `offset`, ignoring it if the note has moved on
+    pub fn append(&mut self, offset: u64, chunk: Chunk) {
+        if offset != self.loaded {
+            return;
+        }
+        self.chunk_requested = false;
+        if chunk.len == 0 {
+            // The note shrank since it was opened
+            self.size = self.loaded;
+            return;
+        }
+
+        self.loaded += chunk.len;
+        self.text.push_str(&chunk.text);
+        self.lines = self.text.lines().count();
+    }
+}
+
+/// Read up to a chunk of the note at `path` from `offset`, cut at its last
+/// line break so lines and characters aren't split between chunks. `size`
+/// is the whole note's, to tell whether this is the last chunk.
+pub fn read_chunk(path: &Path, offset: u64, size: u64) -> io::Result<Chunk> {
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(offset))?;
+    let mut buf = Vec::new();
+    file.take(CHUNK_BYTES).read_to_end(&mut buf)?;
+
+    if !buf.is_empty() && offset + (buf.len() as u64) < size {
+        match buf.iter().rposition(|&b| b == b'\n') {
+            Some(end) => buf.truncate(end + 1),
+            None => {
+                let valid = match std::str::from_utf8(&buf) {
+                    Ok(_) => buf.len(),
+                    Err(err) => err.valid_up_to().max(1),
+                };
+                buf.truncate(valid);
+            }
+        }
+    }
+
+    Ok(Chunk {
+        len: buf.len() as u64,
+        text: String::from_utf8_lossy(&buf).into_owned(),
+    })
+}
+
+/// Note content for the viewer, with Excalidraw scene data swapped for a
+/// short placeholder and canvases summed up as their cards and arrows
+fn readable_text(path: &Path, content: String) -> String {
+    if canvas::is_canvas_path(path) {
+        return Canvas::parse(&content).map_or(content, |canvas| canvas.summary());
+    }
+    let readable = excalidraw::readable_content(path, &content);
+    if readable.len() == content.len() {
+        return content;
+    }
+
+    match excalidraw::split_drawing(&content).1 {
+        Some(drawing) => format!("{}\n\n{}", readable.trim_end(), drawing.placeholder()),
+        None => content,
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    const KB: f64 = 1024.0;
+    let bytes = bytes as f64;
+    if bytes < KB * KB {
+        format!("{:.0} KB", bytes / KB)
+    } else if bytes < KB * KB * KB {
+        format!("{:.1} MB", bytes / (KB * KB))
+    } else {
+        format!("{:.1} GB", bytes / (KB * KB * KB))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::{prelude::*, TempDir};
+
+    #[test]
+    fn loads_large_notes_while_scrolling() {
+        let dir = TempDir::new().unwrap();
+        let note = dir.child("log.md");
+        let line = "a line of an exported log\n";
+        let contents = line.repeat(20_000);
+        note.write_str(&contents).unwrap();
+
+        let mut preview = Preview::open(note.path()).unwrap();
+        assert!(preview.is_large());
+        assert!(preview.is_partial());
+        assert!(preview.text().len() as u64 <= CHUNK_BYTES);
+        assert!(preview.text().ends_with('\n'));
+        assert!(preview
+            .title()
+            .starts_with("Preview • large file, 64 KB of"));
+
+        preview.scroll_by(isize::MAX / 2);
+        while let Some(offset) = preview.next_chunk() {
+            assert_eq!(preview.next_chunk(), None);
+            preview.append(
+                offset,
+                read_chunk(note.path(), offset, preview.size).unwrap(),
+            );
+            preview.scroll_by(isize::MAX / 2);
+        }
+        assert!(!preview.is_partial());
+        assert_eq!(preview.text(), contents);
+        assert_eq!(usize::from(preview.scroll()), 19_999);
+    }
+
+    #[test]
+    fn reads_small_notes_whole() {
+        let dir = TempDir::new().unwrap();
+        let note = dir.child("small.md");
+        note.write_str("one\ntwo\n").unwrap();
+
+        let mut preview = Preview::open(note.path()).unwrap();
+        assert_eq!(preview.text(), "one\ntwo\n");
+        assert_eq!(preview.title(), "Preview");
+
+        preview.scroll_by(-3);
+        assert_eq!(preview.scroll(), 0);
+        assert_eq!(preview.next_chunk(), None);
+    }
+
+    #[test]
+    fn scrolls_to_headings() {
+        let dir = TempDir::new().unwrap();
+        let note = dir.child("install.md");
+        note.write_str("# Install\n\nIntro\n\n## Set up: step one\n")
+            .unwrap();
+
+        let mut preview = Preview::open(note.path()).unwrap();
+        assert!(preview.scroll_to_heading("set up step one", &MatchSettings::default()));
+        assert_eq!(preview.scroll(), 4);
+        assert!(!preview.scroll_to_heading("Missing", &MatchSettings::default()));
+        assert_eq!(preview.scroll(), 4);
+    }
+
+    #[test]
+    fn sums_up_canvases() {
+        let dir = TempDir::new().unwrap();
+        let canvas = dir.child("board.canvas");
+        canvas
+            .write_str(r#"{"nodes":[{"id":"a","type":"file","file":"plan.md"}]}"#)
+            .unwrap();
+
+        let preview = Preview::open(canvas.path()).unwrap();
+        assert_eq!(
+            preview.text(),
+            "Canvas: 1 card, 0 arrows\n\nCards\n- Note: plan.md"
+        );
+    }
+}
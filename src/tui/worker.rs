@@ -0,0 +1,396 @@
+//! Filesystem work for the TUI, done on a thread of its own so keypresses
+//! never wait on a slow disk or a network-mounted vault. [`AppState`] sends
+//! [`Request`]s and picks up each [`Response`] between frames.
+//!
+//! [`AppState`]: super::AppState
+
+use super::{
+    daemon::Client,
+    local_graph::{self, GraphRow},
+    preview::{self, Chunk, Preview},
+};
+use crate::{
+    cache::{MetadataCache, NoteMetadata},
+    fuzzy::{FuzzyMatch, FuzzyScoring},
+    index::Index,
+    link_graph::LinkGraph,
+    matching::MatchSettings,
+    mentions::{self, Mention},
+    related::{related_notes, RelatedNote},
+    relations::{Relation, RelationSettings, Relations},
+    scan,
+    search_query::{SearchQuery, SearchResult},
+};
+use std::{
+    collections::{HashMap, HashSet},
+    io, iter,
+    mem::{self, Discriminant},
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+};
+
+pub enum Request {
+    /// Read the vault's folders and note metadata
+    LoadVault,
+    /// A note was edited outside the TUI
+    NoteChanged(PathBuf),
+    Preview(PathBuf),
+    PreviewChunk {
+        path: PathBuf,
+        offset: u64,
+        size: u64,
+    },
+    Search(String),
+    Switcher(String),
+    /// Find the notes most like this one
+    Related(PathBuf),
+    /// Find where other notes mention this one without linking to it
+    Unlinked(PathBuf),
+    /// Find this note's parents, children and related notes
+    Relations(PathBuf),
+    /// Find the notes within `depth` links of this one
+    LocalGraph {
+        path: PathBuf,
+        depth: usize,
+    },
+}
+
+pub enum Response {
+    Vault(anyhow::Result<MetadataCache>),
+    Preview {
+        path: PathBuf,
+        preview: io::Result<Preview>,
+    },
+    PreviewChunk {
+        path: PathBuf,
+        offset: u64,
+        chunk: io::Result<Chunk>,
+    },
+    Search {
+        query: String,
+        found: anyhow::Result<Vec<SearchResult>>,
+    },
+    Switcher {
+        query: String,
+        found: Vec<FuzzyMatch>,
+    },
+    Related {
+        path: PathBuf,
+        found: anyhow::Result<Vec<RelatedNote>>,
+    },
+    Unlinked {
+        path: PathBuf,
+        found: anyhow::Result<Vec<Mention>>,
+    },
+    Relations {
+        path: PathBuf,
+        /// Vault-relative, parents first
+        found: anyhow::Result<Vec<(Relation, String)>>,
+    },
+    LocalGraph {
+        path: PathBuf,
+        depth: usize,
+        rows: anyhow::Result<Vec<GraphRow>>,
+    },
+}
+
+pub struct Worker {
+    requests: Sender<Request>,
+    responses: Receiver<Response>,
+}
+
+impl Worker {
+    pub fn spawn(vault_path: PathBuf, fuzzy: FuzzyScoring, relations: RelationSettings) -> Self {
+        let (requests, incoming) = mpsc::channel();
+        let (outgoing, responses) = mpsc::channel();
+        thread::spawn(move || run(&vault_path, &fuzzy, &relations, &incoming, &outgoing));
+
+        Self {
+            requests,
+            responses,
+        }
+    }
+
+    pub fn send(&self, request: Request) {
+        // The worker only stops once the TUI drops its end
+        let _ = self.requests.send(request);
+    }
+
+    /// The next finished piece of work, without waiting
+    pub fn try_recv(&self) -> Option<Response> {
+        self.responses.try_recv().ok()
+    }
+}
+
+fn run(
+    vault_path: &Path,
+    fuzzy: &FuzzyScoring,
+    relations: &RelationSettings,
+    requests: &Receiver<Request>,
+    responses: &Sender<Response>,
+) {
+    let mut metadata: Option<MetadataCache> = None;
+    let mut daemon: Option<Client> = None;
+
+    while let Ok(first) = requests.recv() {
+        let batch: Vec<Request> = iter::once(first).chain(requests.try_iter()).collect();
+        for request in latest_only(batch) {
+            let response = handle(
+                request,
+                vault_path,
+                fuzzy,
+                relations,
+                &mut metadata,
+                &mut daemon,
+            );
+            if responses.send(response).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// Drop previews, searches, switcher queries, related notes, mentions,
+/// relations and local graphs that newer ones in the batch replace, so holding down an
+/// arrow key doesn't queue up reads
+fn latest_only(batch: Vec<Request>) -> Vec<Request> {
+    let mut seen: HashSet<Discriminant<Request>> = HashSet::new();
+    let mut kept: Vec<Request> = batch
+        .into_iter()
+        .rev()
+        .filter(|request| {
+            let replaceable = matches!(
+                request,
+                Request::Preview(_)
+                    | Request::Search(_)
+                    | Request::Switcher(_)
+                    | Request::Related(_)
+                    | Request::Unlinked(_)
+                    | Request::Relations(_)
+                    | Request::LocalGraph { .. }
+            );
+            !replaceable || seen.insert(mem::discriminant(request))
+        })
+        .collect();
+    kept.reverse();
+    kept
+}
+
+fn handle(
+    request: Request,
+    vault_path: &Path,
+    fuzzy: &FuzzyScoring,
+    relations: &RelationSettings,
+    metadata: &mut Option<MetadataCache>,
+    daemon: &mut Option<Client>,
+) -> Response {
+    match request {
+        Request::LoadVault => {
+            let loaded = load_metadata(vault_path);
+            if let Ok(loaded) = &loaded {
+                *metadata = Some(loaded.clone());
+            }
+            Response::Vault(loaded)
+        }
+        Request::NoteChanged(path) => {
+            let Some(cache) = metadata else {
+                return handle(
+                    Request::LoadVault,
+                    vault_path,
+                    fuzzy,
+                    relations,
+                    metadata,
+                    daemon,
+                );
+            };
+            cache.update_note(vault_path, &path);
+            // A cache that can't be saved is only slower to load next time
+            let _ = cache.save(vault_path);
+            Response::Vault(Ok(cache.clone()))
+        }
+        Request::Preview(path) => Response::Preview {
+            preview: ask_daemon(daemon, vault_path, |client| client.preview(&path))
+                .unwrap_or_else(|| Preview::open(&path)),
+            path,
+        },
+        Request::PreviewChunk { path, offset, size } => Response::PreviewChunk {
+            chunk: preview::read_chunk(&path, offset, size),
+            path,
+            offset,
+        },
+        Request::Search(query) => Response::Search {
+            found: ask_daemon(daemon, vault_path, |client| client.search(&query))
+                .unwrap_or_else(|| SearchQuery::parse(&query).and_then(|q| q.search(vault_path))),
+            query,
+        },
+        Request::Related(path) => {
+            let relative = scan::relative_note_path(&path, vault_path);
+            let found = loaded_metadata(metadata, vault_path).and_then(|metadata| {
+                let index = Index::current(vault_path)?;
+                let graph = load_graph(vault_path)?;
+                Ok(related_notes(&index, metadata, &graph, &relative))
+            });
+            Response::Related { path, found }
+        }
+        Request::Unlinked(path) => Response::Unlinked {
+            found: mentions::find(vault_path, &path),
+            path,
+        },
+        Request::Relations(path) => {
+            let relative = scan::relative_note_path(&path, vault_path);
+            let found = loaded_metadata(metadata, vault_path).and_then(|metadata| {
+                let graph = load_graph(vault_path)?;
+                let resolver = graph.resolver(vault_path);
+                Ok(Relations::new(metadata, &resolver, vault_path, relations)
+                    .around(&relative)
+                    .into_iter()
+                    .map(|(relation, note)| (relation, note.to_string()))
+                    .collect())
+            });
+            Response::Relations { path, found }
+        }
+        Request::LocalGraph { path, depth } => {
+            let relative = scan::relative_note_path(&path, vault_path);
+            let rows =
+                load_graph(vault_path).map(|graph| local_graph::build(&graph, &relative, depth));
+            Response::LocalGraph { path, depth, rows }
+        }
+        Request::Switcher(query) => {
+            if metadata.is_none() {
+                *metadata = load_metadata(vault_path).ok();
+            }
+            let notes: Vec<(&str, &NoteMetadata)> =
+                metadata.iter().flat_map(MetadataCache::notes).collect();
+            let note_paths: Vec<PathBuf> = notes
+                .iter()
+                .map(|(relative, _)| vault_path.join(relative))
+                .collect();
+            let aliases: HashMap<&str, &[String]> = notes
+                .iter()
+                .filter(|(_, note)| !note.aliases.is_empty())
+                .map(|(relative, note)| (*relative, note.aliases.as_slice()))
+                .collect();
+            Response::Switcher {
+                found: fuzzy.rank_with_aliases(
+                    &query,
+                    &MatchSettings::read(),
+                    &note_paths,
+                    vault_path,
+                    &aliases,
+                ),
+                query,
+            }
+        }
+    }
+}
+
+/// Ask the vault's daemon, connecting to it first if need be, or `None` to
+/// do the work here when no daemon answers
+fn ask_daemon<T>(
+    daemon: &mut Option<Client>,
+    vault_path: &Path,
+    ask: impl FnOnce(&mut Client) -> Option<T>,
+) -> Option<T> {
+    if daemon.is_none() {
+        *daemon = Client::connect(vault_path);
+    }
+    let answer = ask(daemon.as_mut()?);
+    if answer.is_none() {
+        // Most likely the daemon stopped, so connect again next time
+        *daemon = None;
+    }
+    answer
+}
+
+/// The vault's cached metadata, or read afresh without saving when the
+/// cache can't be written
+fn load_metadata(vault_path: &Path) -> anyhow::Result<MetadataCache> {
+    MetadataCache::current(vault_path).or_else(|_| {
+        let mut metadata = MetadataCache::default();
+        metadata.refresh(vault_path)?;
+        Ok(metadata)
+    })
+}
+
+/// The metadata the worker holds, loading it first if it hasn't yet
+fn loaded_metadata<'a>(
+    metadata: &'a mut Option<MetadataCache>,
+    vault_path: &Path,
+) -> anyhow::Result<&'a MetadataCache> {
+    match metadata {
+        Some(metadata) => Ok(metadata),
+        None => Ok(metadata.insert(load_metadata(vault_path)?)),
+    }
+}
+
+/// The vault's link graph, or built afresh without saving when it can't be
+/// written
+fn load_graph(vault_path: &Path) -> anyhow::Result<LinkGraph> {
+    LinkGraph::current(vault_path).or_else(|_| {
+        let mut graph = LinkGraph::default();
+        graph.refresh(vault_path)?;
+        Ok(graph)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::{prelude::*, TempDir};
+    use std::time::Duration;
+
+    fn next(worker: &Worker) -> Response {
+        worker
+            .responses
+            .recv_timeout(Duration::from_secs(10))
+            .unwrap()
+    }
+
+    #[test]
+    fn works_off_the_calling_thread() {
+        let dir = TempDir::new().unwrap();
+        dir.child("folder/meeting.md")
+            .write_str("# Agenda")
+            .unwrap();
+        let worker = Worker::spawn(
+            dir.to_path_buf(),
+            FuzzyScoring::default(),
+            RelationSettings::default(),
+        );
+
+        worker.send(Request::LoadVault);
+        let Response::Vault(Ok(metadata)) = next(&worker) else {
+            panic!("expected the vault");
+        };
+        assert_eq!(metadata.folders(), ["", "folder"]);
+
+        let note = dir.child("folder/meeting.md").to_path_buf();
+        worker.send(Request::Preview(note.clone()));
+        let Response::Preview { path, preview } = next(&worker) else {
+            panic!("expected a preview");
+        };
+        assert_eq!(path, note);
+        assert_eq!(preview.unwrap().text(), "# Agenda");
+
+        worker.send(Request::Switcher("meet".to_string()));
+        let Response::Switcher { found, .. } = next(&worker) else {
+            panic!("expected switcher matches");
+        };
+        assert_eq!(found[0].relative, "folder/meeting.md");
+    }
+
+    #[test]
+    fn keeps_only_the_latest_preview() {
+        let batch = vec![
+            Request::Preview(PathBuf::from("a.md")),
+            Request::LoadVault,
+            Request::Preview(PathBuf::from("b.md")),
+        ];
+
+        let kept = latest_only(batch);
+        assert_eq!(kept.len(), 2);
+        assert!(matches!(kept[0], Request::LoadVault));
+        assert!(matches!(&kept[1], Request::Preview(path) if path == Path::new("b.md")));
+    }
+}
@@ -1,14 +1,18 @@
 use crate::cli_config;
-use anyhow::Context;
+use anyhow::{bail, Context};
 use atty::{is, Stream};
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, TimeZone};
+use libobsidian::obsidian_note::Properties;
 use std::{
+    env,
     ffi::OsStr,
-    path::{Path, PathBuf},
+    fs,
+    path::{Component, Path, PathBuf},
 };
 
 pub type CommandResult = anyhow::Result<Option<String>>;
 
-pub fn resolve_note_path(path_or_string: &str, vault_path: &PathBuf) -> anyhow::Result<PathBuf> {
+pub fn resolve_note_path(path_or_string: &str, vault_path: &Path) -> anyhow::Result<PathBuf> {
     let file_path = Path::new(path_or_string);
 
     let path_with_ext: PathBuf = match file_path.extension().and_then(OsStr::to_str) {
@@ -22,29 +26,240 @@ pub fn resolve_note_path(path_or_string: &str, vault_path: &PathBuf) -> anyhow::
     Ok(note_path)
 }
 
+/// Whether `path` only names folders and files beneath where it's joined,
+/// with no absolute start or `..` to lead elsewhere
+pub fn is_relative_inside(path: &str) -> bool {
+    !path.is_empty()
+        && Path::new(path)
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+}
+
+/// Where a markdown note is in the vault, refusing
+/// absolute paths and `..` that would lead out of the vault and hidden
+/// folders like `.obsidian` and `.git`, for paths from outside obx's own user
+pub fn vault_note_path(note: &str, vault_path: &Path) -> anyhow::Result<PathBuf> {
+    let inside = Path::new(note)
+        .components()
+        .all(|component| match component {
+            Component::Normal(name) => !name.to_string_lossy().starts_with('.'),
+            _ => false,
+        });
+    if note.is_empty() || !inside {
+        bail!("Invalid note path `{note}`, expected one within the vault");
+    }
+    // Other extensions are kept as part of the name, so `main.js` is `main.js.md`
+    match Path::new(note).extension().and_then(OsStr::to_str) {
+        Some("md") => Ok(vault_path.join(note)),
+        _ => Ok(vault_path.join(format!("{note}.md"))),
+    }
+}
+
 pub fn get_current_vault(vault_name_override: Option<String>) -> anyhow::Result<cli_config::Vault> {
     let config = cli_config::read()?;
-    let vault_name = vault_name_override.unwrap_or(config.current_vault);
 
-    let found_vault: cli_config::Vault = config
+    let Some(vault_name) = vault_name_override else {
+        return config
+            .vaults
+            .iter()
+            .find(|v| v.name == config.current_vault)
+            .cloned()
+            .context("Expected to find the current vault in config");
+    };
+
+    config
         .vaults
         .iter()
         .find(|v| v.name == vault_name)
-        .context("Expected to find the current vault in config")?
-        .clone();
+        .cloned()
+        .with_context(|| format!("Vault `{vault_name}` not found, see `obx vaults list`"))
+}
+
+/// Find a program the way a shell would: paths containing a separator are
+/// used as they are, bare names are looked up on `$PATH`. Only files that can
+/// be executed count.
+pub fn find_executable(program: &str) -> Option<PathBuf> {
+    let as_path = Path::new(program);
+    if as_path.components().count() > 1 {
+        return is_executable(as_path).then(|| as_path.to_path_buf());
+    }
 
-    Ok(found_vault)
+    let file_name = if cfg!(windows) && as_path.extension().is_none() {
+        format!("{program}.exe")
+    } else {
+        program.to_string()
+    };
+
+    env::var_os("PATH")
+        .iter()
+        .flat_map(env::split_paths)
+        .map(|dir| dir.join(&file_name))
+        .find(|candidate| is_executable(candidate))
+}
+
+pub fn is_executable(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        path.metadata()
+            .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+    }
+
+    #[cfg(not(unix))]
+    {
+        path.is_file()
+    }
 }
 
 pub fn should_enable_interactivity() -> bool {
     is(Stream::Stderr) || is(Stream::Stdin)
 }
 
+/// Parse a point in time given either as a relative duration back from now
+/// (`30m`, `12h`, `3d`, `2w`) or an absolute `YYYY-MM-DD` date
+pub fn parse_since(input: &str) -> anyhow::Result<DateTime<Local>> {
+    parse_since_from(input, Local::now())
+}
+
+fn parse_since_from(input: &str, now: DateTime<Local>) -> anyhow::Result<DateTime<Local>> {
+    let input = input.trim();
+
+    if let Ok(date) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+        let midnight = date.and_hms_opt(0, 0, 0).expect("midnight should be valid");
+        return Local
+            .from_local_datetime(&midnight)
+            .earliest()
+            .with_context(|| format!("Invalid date `{input}`"));
+    }
+
+    let split_at = input
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(input.len());
+    let (amount, unit) = input.split_at(split_at);
+
+    let Ok(amount) = amount.parse::<i64>() else {
+        bail!("Invalid time `{input}`, expected a duration like `3d` or a date like `2024-01-31`");
+    };
+
+    let duration = match unit {
+        "m" => Duration::minutes(amount),
+        "h" => Duration::hours(amount),
+        "d" => Duration::days(amount),
+        "w" => Duration::weeks(amount),
+        _ => bail!(
+            "Invalid time `{input}`, expected a duration like `3d` or a date like `2024-01-31`"
+        ),
+    };
+
+    Ok(now - duration)
+}
+
+/// Parse how often something repeats, like `30s`, `5m` or `1h`
+pub fn parse_interval(input: &str) -> anyhow::Result<std::time::Duration> {
+    let input = input.trim();
+    let split_at = input
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(input.len());
+    let (amount, unit) = input.split_at(split_at);
+
+    let seconds = match (amount.parse::<u64>(), unit) {
+        (Ok(amount), "s") => amount,
+        (Ok(amount), "m") => amount * 60,
+        (Ok(amount), "h") => amount * 60 * 60,
+        _ => bail!("Invalid interval `{input}`, expected a duration like `30s`, `5m` or `1h`"),
+    };
+    if seconds == 0 {
+        bail!("The interval must be longer than zero");
+    }
+
+    Ok(std::time::Duration::from_secs(seconds))
+}
+
+/// When a note was created: its frontmatter's `created` or `date` property,
+/// or the file's creation time when neither is a date
+pub fn note_created(properties: Option<&Properties>, note_path: &Path) -> Option<DateTime<Local>> {
+    ["created", "date"]
+        .iter()
+        .filter_map(|key| properties?.get(key)?.as_str())
+        .find_map(parse_property_time)
+        .or_else(|| {
+            fs::metadata(note_path)
+                .and_then(|meta| meta.created())
+                .ok()
+                .map(DateTime::<Local>::from)
+        })
+}
+
+/// A date or date and time as written in frontmatter, local unless it has
+/// an offset
+fn parse_property_time(value: &str) -> Option<DateTime<Local>> {
+    let value = value.trim();
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Some(time.with_timezone(&Local));
+    }
+
+    let naive = [
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%dT%H:%M",
+        "%Y-%m-%d %H:%M",
+    ]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+    .or_else(|| {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .ok()?
+            .and_hms_opt(0, 0, 0)
+    })?;
+    Local.from_local_datetime(&naive).earliest()
+}
+
+/// A short human description of how long ago `then` was, e.g. `5 minutes ago`
+pub fn relative_time(then: DateTime<Local>, now: DateTime<Local>) -> String {
+    let elapsed = now - then;
+
+    let (amount, unit) = if elapsed < Duration::minutes(1) {
+        return "just now".to_string();
+    } else if elapsed < Duration::hours(1) {
+        (elapsed.num_minutes(), "minute")
+    } else if elapsed < Duration::days(1) {
+        (elapsed.num_hours(), "hour")
+    } else if elapsed < Duration::weeks(1) {
+        (elapsed.num_days(), "day")
+    } else if elapsed < Duration::days(60) {
+        (elapsed.num_weeks(), "week")
+    } else {
+        return then.format("%Y-%m-%d").to_string();
+    };
+
+    if amount == 1 {
+        format!("1 {unit} ago")
+    } else {
+        format!("{amount} {unit}s ago")
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use test_case::test_case;
 
+    #[test_case("created: 2024-03-01", Some("2024-03-01 00:00") ; "date")]
+    #[test_case("created: 2024-03-01T09:15:00", Some("2024-03-01 09:15") ; "date and time")]
+    #[test_case("date: 2024-03-01 09:15", Some("2024-03-01 09:15") ; "date property")]
+    #[test_case("created: soon\ndate: 2024-03-02", Some("2024-03-02 00:00") ; "falls back to date")]
+    fn reads_created_from_properties(frontmatter: &str, expected: Option<&str>) {
+        let properties: Properties = serde_yaml::from_str(frontmatter).unwrap();
+        let created = note_created(Some(&properties), Path::new("missing.md"));
+
+        assert_eq!(
+            created
+                .map(|time| time.format("%Y-%m-%d %H:%M").to_string())
+                .as_deref(),
+            expected
+        );
+    }
+
     #[test_case("foo", "foo.md" ; "plain filename")]
     #[test_case("bar/foo", "bar/foo.md" ; "with path")]
     #[test_case("foo.txt", "foo.txt" ; "with another extension")]
@@ -65,6 +280,87 @@ mod tests {
         );
     }
 
+    #[test_case("Projects/plan", true ; "nested")]
+    #[test_case("../escaped", false ; "parent")]
+    #[test_case("/etc/escaped", false ; "absolute")]
+    #[test_case("", false ; "empty")]
+    fn is_relative_inside_rejects_escapes(path: &str, expected: bool) {
+        assert_eq!(is_relative_inside(path), expected);
+    }
+
+    #[test_case("folder/note", "/vault/folder/note.md" ; "without extension")]
+    #[test_case("note.md", "/vault/note.md" ; "markdown")]
+    #[test_case("plugin/main.js", "/vault/plugin/main.js.md" ; "other extension")]
+    fn vault_note_path_is_markdown(note: &str, expected: &str) {
+        assert_eq!(
+            vault_note_path(note, Path::new("/vault")).unwrap(),
+            PathBuf::from(expected)
+        );
+    }
+
+    #[test_case("../secret" ; "parent")]
+    #[test_case("/etc/passwd" ; "absolute")]
+    #[test_case(".obsidian/app" ; "hidden folder")]
+    #[test_case("" ; "empty")]
+    fn vault_note_path_stays_in_the_vault(note: &str) {
+        assert!(vault_note_path(note, Path::new("/vault")).is_err());
+    }
+
+    #[test_case("30m", 30 * 60 ; "minutes")]
+    #[test_case("12h", 12 * 60 * 60 ; "hours")]
+    #[test_case("3d", 3 * 24 * 60 * 60 ; "days")]
+    #[test_case("2w", 14 * 24 * 60 * 60 ; "weeks")]
+    fn parse_since_accepts_durations(input: &str, seconds: i64) {
+        let now = Local::now();
+        let since = parse_since_from(input, now).unwrap();
+        assert_eq!((now - since).num_seconds(), seconds);
+    }
+
+    #[test]
+    fn parse_since_accepts_dates() {
+        let since = parse_since("2024-01-31").unwrap();
+        assert_eq!(
+            since.format("%Y-%m-%d %H:%M").to_string(),
+            "2024-01-31 00:00"
+        );
+    }
+
+    #[test_case("yesterday" ; "words")]
+    #[test_case("3y" ; "unknown unit")]
+    fn parse_since_rejects_invalid(input: &str) {
+        assert!(parse_since(input).is_err());
+    }
+
+    #[test_case("45s", 45 ; "seconds")]
+    #[test_case("5m", 5 * 60 ; "minutes")]
+    #[test_case("2h", 2 * 60 * 60 ; "hours")]
+    fn parse_interval_accepts_durations(input: &str, seconds: u64) {
+        assert_eq!(parse_interval(input).unwrap().as_secs(), seconds);
+    }
+
+    #[test_case("0m" ; "zero")]
+    #[test_case("5" ; "no unit")]
+    #[test_case("1d" ; "unknown unit")]
+    fn parse_interval_rejects_invalid(input: &str) {
+        assert!(parse_interval(input).is_err());
+    }
+
+    #[test_case(Duration::seconds(20), "just now" ; "seconds")]
+    #[test_case(Duration::minutes(1), "1 minute ago" ; "one minute")]
+    #[test_case(Duration::hours(5), "5 hours ago" ; "hours")]
+    #[test_case(Duration::days(3), "3 days ago" ; "days")]
+    #[test_case(Duration::days(20), "2 weeks ago" ; "weeks")]
+    fn relative_time_describes_elapsed(elapsed: Duration, expected: &str) {
+        let now = Local::now();
+        assert_eq!(relative_time(now - elapsed, now), expected);
+    }
+
+    #[test]
+    fn relative_time_falls_back_to_date() {
+        let then = parse_since("2024-01-31").unwrap();
+        assert_eq!(relative_time(then, then + Duration::days(90)), "2024-01-31");
+    }
+
     #[test]
     #[ignore]
     fn note_path_errors_on_invalid() {
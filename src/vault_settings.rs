@@ -0,0 +1,97 @@
+//! Settings the Obsidian app keeps for each vault in `.obsidian/app.json`,
+//! so files obx creates land where the app would put them
+
+use serde::Deserialize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct VaultSettings {
+    /// `root`, `current` (next to the open note) or `folder`
+    pub new_file_location: Option<String>,
+    /// Used when `new_file_location` is `folder`
+    pub new_file_folder_path: Option<String>,
+    /// `/` for the vault root, `./` or `./sub` relative to the note, or a
+    /// vault-relative folder
+    pub attachment_folder_path: Option<String>,
+}
+
+/// The vault's app settings, or `None` when it has no `app.json` or it can't
+/// be parsed
+pub fn read(vault_path: &Path) -> Option<VaultSettings> {
+    let contents = fs::read_to_string(vault_path.join(".obsidian/app.json")).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+impl VaultSettings {
+    /// The vault-relative folder new notes are created in when only a name
+    /// is given. Without an open note, `current` means the vault root.
+    pub fn new_note_folder(&self) -> PathBuf {
+        match (
+            self.new_file_location.as_deref(),
+            &self.new_file_folder_path,
+        ) {
+            (Some("folder"), Some(folder)) => vault_relative(folder),
+            _ => PathBuf::new(),
+        }
+    }
+
+    /// The vault-relative folder attachments go in for a note in
+    /// `note_folder`, itself relative to the vault
+    pub fn attachment_folder(&self, note_folder: &Path) -> PathBuf {
+        let Some(folder) = self.attachment_folder_path.as_deref() else {
+            return PathBuf::new();
+        };
+
+        match folder.strip_prefix('.') {
+            Some("") => note_folder.to_path_buf(),
+            Some(rest) if rest.starts_with('/') => note_folder.join(vault_relative(rest)),
+            _ => vault_relative(folder),
+        }
+    }
+}
+
+fn vault_relative(folder: &str) -> PathBuf {
+    PathBuf::from(folder.trim_matches('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    fn with_attachment_folder(folder: &str) -> VaultSettings {
+        VaultSettings {
+            attachment_folder_path: Some(folder.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test_case("/", "" ; "vault root")]
+    #[test_case("./", "projects" ; "next to the note")]
+    #[test_case("./assets", "projects/assets" ; "below the note")]
+    #[test_case("Files/Attachments", "Files/Attachments" ; "fixed folder")]
+    fn attachment_folder_follows_app(folder: &str, expected: &str) {
+        let settings = with_attachment_folder(folder);
+        assert_eq!(
+            settings.attachment_folder(Path::new("projects")),
+            PathBuf::from(expected)
+        );
+    }
+
+    #[test]
+    fn new_notes_use_folder_only_when_selected() {
+        let mut settings = VaultSettings {
+            new_file_location: Some("folder".to_string()),
+            new_file_folder_path: Some("Inbox/".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(settings.new_note_folder(), PathBuf::from("Inbox"));
+
+        settings.new_file_location = Some("current".to_string());
+        assert_eq!(settings.new_note_folder(), PathBuf::new());
+    }
+}
@@ -0,0 +1,70 @@
+//! Watching a vault for changes, shared by `obx index watch` and
+//! `obx git autocommit`. Changes are debounced so a burst of saves arrives
+//! as one batch.
+
+use crate::cli_config::Vault;
+use anyhow::{anyhow, Context};
+use notify_debouncer_mini::{
+    new_debouncer,
+    notify::{RecommendedWatcher, RecursiveMode},
+    DebounceEventResult, Debouncer,
+};
+use std::{
+    path::PathBuf,
+    sync::mpsc::{self, Receiver},
+    time::Duration,
+};
+
+/// Batches of changed paths, relative to the vault, for as long as it's kept
+pub struct VaultWatcher {
+    name: String,
+    root: PathBuf,
+    receiver: Receiver<DebounceEventResult>,
+    _debouncer: Debouncer<RecommendedWatcher>,
+}
+
+/// Start watching everything in `vault`, waiting `debounce` for changes to
+/// settle before reporting them
+pub fn watch_vault(vault: &Vault, debounce: Duration) -> anyhow::Result<VaultWatcher> {
+    // Watchers report canonical paths, which differ from the configured one
+    // behind symlinks like macOS's `/var`
+    let root = vault
+        .path
+        .canonicalize()
+        .with_context(|| format!("Could not read vault {}", vault.path.display()))?;
+
+    let (sender, receiver) = mpsc::channel();
+    let mut debouncer = new_debouncer(debounce, sender)
+        .map_err(|err| anyhow!("Could not watch vault `{}`: {err}", vault.name))?;
+    debouncer
+        .watcher()
+        .watch(&root, RecursiveMode::Recursive)
+        .map_err(|err| anyhow!("Could not watch vault `{}`: {err}", vault.name))?;
+
+    Ok(VaultWatcher {
+        name: vault.name.clone(),
+        root,
+        receiver,
+        _debouncer: debouncer,
+    })
+}
+
+impl Iterator for VaultWatcher {
+    type Item = anyhow::Result<Vec<PathBuf>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = self.receiver.recv().ok()?;
+        Some(
+            result
+                .map(|events| {
+                    events
+                        .into_iter()
+                        .filter_map(|event| {
+                            Some(event.path.strip_prefix(&self.root).ok()?.to_path_buf())
+                        })
+                        .collect()
+                })
+                .map_err(|err| anyhow!("Watching `{}` failed: {err}", self.name)),
+        )
+    }
+}
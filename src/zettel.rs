@@ -0,0 +1,131 @@
+//! Zettelkasten IDs: timestamps at the start of note names, like those
+//! Obsidian's unique note creator writes, e.g. `202405101230 Atomic notes`.
+//! `obx notes create --zettel` adds one, and a note can be opened or linked
+//! to by its ID alone.
+
+use crate::{cli_config, dates::DateFormats};
+use chrono::{DateTime, Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct ZettelSettings {
+    /// The ID's format, in Moment.js tokens like the `dates` formats
+    pub id_format: String,
+    /// Put between the ID and the rest of the note's name
+    pub separator: String,
+}
+
+impl Default for ZettelSettings {
+    fn default() -> Self {
+        Self {
+            id_format: "YYYYMMDDHHmm".to_string(),
+            separator: " ".to_string(),
+        }
+    }
+}
+
+impl ZettelSettings {
+    pub fn is_default(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// The config's settings, read once per run
+    pub fn current() -> &'static Self {
+        static SETTINGS: OnceLock<ZettelSettings> = OnceLock::new();
+        SETTINGS.get_or_init(|| {
+            cli_config::read()
+                .map(|config| config.zettel)
+                .unwrap_or_default()
+        })
+    }
+
+    /// The ID for a note created at `time`
+    pub fn id(&self, time: &DateTime<Local>) -> String {
+        DateFormats::default().format(time, &self.id_format)
+    }
+
+    /// `name` with the ID for `time` in front, or the ID alone when `name` is
+    /// empty. Any folders in `name` are kept in front of the ID.
+    pub fn prefix(&self, name: &str, time: &DateTime<Local>) -> String {
+        let (folder, file) = match name.rfind('/') {
+            Some(idx) => name.split_at(idx + 1),
+            None => ("", name),
+        };
+        let id = self.id(time);
+        match file.trim() {
+            "" => format!("{folder}{id}"),
+            file => format!("{folder}{id}{}{file}", self.separator),
+        }
+    }
+
+    /// Whether `text` is written the way this format writes IDs: as long,
+    /// with digits in the same places and the same characters between them
+    pub fn is_id(&self, text: &str) -> bool {
+        let sample = self.id(&Local.with_ymd_and_hms(2000, 1, 1, 0, 0, 0).unwrap());
+        sample.chars().count() == text.chars().count()
+            && sample.chars().zip(text.chars()).all(|(expected, c)| {
+                if expected.is_ascii_digit() {
+                    c.is_ascii_digit()
+                } else {
+                    c == expected
+                }
+            })
+    }
+
+    /// The ID at the start of a note's name, without `.md`, if it has one
+    pub fn id_of<'a>(&self, name: &'a str) -> Option<&'a str> {
+        let id = match name.split_once(self.separator.as_str()) {
+            Some((id, _)) if !self.separator.is_empty() => id,
+            _ => name,
+        };
+        self.is_id(id).then_some(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    fn may_10th() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 5, 10, 12, 30, 0).unwrap()
+    }
+
+    #[test_case("Atomic notes", "202405101230 Atomic notes")]
+    #[test_case("Ideas/Atomic notes", "Ideas/202405101230 Atomic notes" ; "in a folder")]
+    #[test_case("", "202405101230" ; "id alone")]
+    fn prefixes_names(name: &str, expected: &str) {
+        assert_eq!(
+            ZettelSettings::default().prefix(name, &may_10th()),
+            expected
+        );
+    }
+
+    #[test_case("202405101230 Atomic notes", Some("202405101230"))]
+    #[test_case("202405101230", Some("202405101230") ; "id alone")]
+    #[test_case("2024051012 Atomic notes", None ; "too short")]
+    #[test_case("Atomic notes", None ; "no id")]
+    fn finds_ids(name: &str, expected: Option<&str>) {
+        assert_eq!(ZettelSettings::default().id_of(name), expected);
+    }
+
+    #[test]
+    fn follows_the_format() {
+        let settings = ZettelSettings {
+            id_format: "YYYY-MM-DD-HHmm".to_string(),
+            separator: " - ".to_string(),
+        };
+
+        assert_eq!(
+            settings.prefix("Atomic notes", &may_10th()),
+            "2024-05-10-1230 - Atomic notes"
+        );
+        assert_eq!(
+            settings.id_of("2024-05-10-1230 - Atomic notes"),
+            Some("2024-05-10-1230")
+        );
+        assert_eq!(settings.id_of("202405101230 Atomic notes"), None);
+    }
+}
@@ -0,0 +1,91 @@
+use assert_fs::prelude::*;
+mod utils;
+use predicates::prelude::*;
+use utils::*;
+
+mod attach {
+    use super::*;
+
+    #[test]
+    fn copies_file_and_embeds_it() {
+        let cmd = Obx::from_command("attach simple-note photo.png");
+        cmd.temp_dir
+            .child("photo.png")
+            .write_str("not really a png")
+            .unwrap();
+
+        let attachment = cmd.temp_dir.child("main-vault/attachments/photo.png");
+        let note = cmd.temp_dir.child("main-vault/simple-note.md");
+
+        let _ = &cmd.assert_stdout("Attached ![[photo.png]] to simple-note.md\n");
+
+        attachment.assert("not really a png");
+        note.assert(predicate::str::ends_with(
+            "This is the contents of simple-note.md\n![[photo.png]]\n",
+        ));
+    }
+
+    #[test]
+    fn follows_obsidian_attachment_setting() {
+        let cmd = Obx::from_command("attach folder/child-note photo.png");
+        cmd.temp_dir.child("photo.png").write_str("png").unwrap();
+        cmd.temp_dir
+            .child("main-vault/.obsidian/app.json")
+            .write_str(r#"{"attachmentFolderPath": "./assets"}"#)
+            .unwrap();
+
+        let attachment = cmd.temp_dir.child("main-vault/folder/assets/photo.png");
+
+        let _ = &cmd.assert_stdout("Attached ![[photo.png]] to folder/child-note.md\n");
+
+        attachment.assert("png");
+    }
+
+    #[test]
+    fn links_non_media_files() {
+        let cmd = Obx::from_command("attach simple-note data.csv");
+        cmd.temp_dir.child("data.csv").write_str("a,b").unwrap();
+
+        cmd.assert_stdout("Attached [[data.csv]] to simple-note.md\n");
+    }
+
+    #[test]
+    fn reuses_identical_attachments() {
+        let cmd = Obx::from_command("attach simple-note copy.png");
+        cmd.temp_dir
+            .child("copy.png")
+            .write_str("same bytes")
+            .unwrap();
+        cmd.temp_dir
+            .child("main-vault/attachments/original.png")
+            .write_str("same bytes")
+            .unwrap();
+
+        let duplicate = cmd.temp_dir.child("main-vault/attachments/copy.png");
+
+        let _ = &cmd.assert_stdout("Attached ![[original.png]] to simple-note.md\n");
+
+        duplicate.assert(predicate::path::missing());
+    }
+
+    #[test]
+    fn renames_conflicting_attachments() {
+        let cmd = Obx::from_command("attach simple-note photo.png");
+        cmd.temp_dir
+            .child("photo.png")
+            .write_str("new photo")
+            .unwrap();
+        cmd.temp_dir
+            .child("main-vault/attachments/photo.png")
+            .write_str("old photo")
+            .unwrap();
+
+        cmd.assert_stdout("Attached ![[photo 1.png]] to simple-note.md\n");
+    }
+
+    #[test]
+    fn fails_on_missing_file() {
+        Obx::from_command("attach simple-note missing.png")
+            .assert_stderr("Could not attach `missing.png`, file not found\n");
+    }
+}
@@ -0,0 +1,173 @@
+use assert_fs::prelude::*;
+use indoc::indoc;
+mod utils;
+use utils::*;
+
+mod audit {
+    use super::*;
+
+    fn with_problems(cmd: Obx) -> Obx {
+        for (path, contents) in [
+            ("main-vault/a/dup.md", ""),
+            ("main-vault/b/dup.md", ""),
+            ("main-vault/report.pdf", ""),
+            (
+                "main-vault/problems.md",
+                "[[dup]] and [[SIMPLE-NOTE]]\n[report](report.pdf) ![[report.pdf]]\n[[problems#Heading]] [[#Heading]]\n# Heading",
+            ),
+        ] {
+            cmd.temp_dir.child(path).write_str(contents).unwrap();
+        }
+        cmd
+    }
+
+    #[test]
+    fn reports_link_problems() {
+        with_problems(Obx::from_command("audit links")).assert_stdout(indoc! {"
+            ┌───────────────┬──────┬───────────────┬───────────────────────────────┐
+            │ Note          │ Line │ Problem       │ Link                          │
+            ├───────────────┼──────┼───────────────┼───────────────────────────────┤
+            │ link-types.md │ 3    │ broken        │ [[wikilink]]                  │
+            │ link-types.md │ 5    │ broken        │ [[wikilink |different label]] │
+            │ problems.md   │ 1    │ ambiguous     │ [[dup]]                       │
+            │ problems.md   │ 2    │ not markdown  │ [report](report.pdf)          │
+            │ problems.md   │ 1    │ case mismatch │ [[SIMPLE-NOTE]]               │
+            │ problems.md   │ 3    │ self link     │ [[problems#Heading]]          │
+            └───────────────┴──────┴───────────────┴───────────────────────────────┘
+        "});
+    }
+
+    #[test]
+    fn reports_as_json() {
+        let cmd = Obx::from_command("audit links --format json");
+        cmd.temp_dir
+            .child("main-vault/link-types.md")
+            .write_str("[[a/dup]] [[dup]]")
+            .unwrap();
+        with_problems(cmd).assert_stdout(concat!(
+            r#"[{"note":"link-types.md","line":1,"problem":"ambiguous","link":"[[dup]]","candidates":["a/dup.md","b/dup.md"]},"#,
+            r#"{"note":"problems.md","line":1,"problem":"ambiguous","link":"[[dup]]","candidates":["a/dup.md","b/dup.md"]},"#,
+            r#"{"note":"problems.md","line":2,"problem":"not-markdown","link":"[report](report.pdf)"},"#,
+            r#"{"note":"problems.md","line":1,"problem":"case-mismatch","link":"[[SIMPLE-NOTE]]"},"#,
+            r#"{"note":"problems.md","line":3,"problem":"self-link","link":"[[problems#Heading]]"}]"#,
+            "\n"
+        ));
+    }
+
+    #[test]
+    fn reports_as_markdown() {
+        with_problems(Obx::from_command("audit links -f markdown")).assert_stdout(indoc! {"
+            # Link audit
+
+            ## Broken links
+
+            - `link-types.md` line 3: `[[wikilink]]`
+            - `link-types.md` line 5: `[[wikilink |different label]]`
+
+            ## Ambiguous links
+
+            - `problems.md` line 1: `[[dup]]` could be `a/dup.md`, `b/dup.md`
+
+            ## Links to files that aren't notes
+
+            - `problems.md` line 2: `[report](report.pdf)`
+
+            ## Links in a different case
+
+            - `problems.md` line 1: `[[SIMPLE-NOTE]]`
+
+            ## Links to their own note
+
+            - `problems.md` line 3: `[[problems#Heading]]`
+        "});
+    }
+}
+
+mod audit_ambiguous {
+    use super::*;
+    use indoc::formatdoc;
+
+    fn with_shared_names(cmd: Obx) -> Obx {
+        for (path, contents) in [
+            ("main-vault/Projects/Index.md", ""),
+            ("main-vault/Archive/2023/Index.md", "Archived"),
+            (
+                "main-vault/links.md",
+                "[[Index]] and [[index]], [[Archive/2023/Index]]",
+            ),
+        ] {
+            cmd.temp_dir.child(path).write_str(contents).unwrap();
+        }
+        cmd
+    }
+
+    fn with_folder_policy(cmd: Obx) -> Obx {
+        let config = formatdoc! {"
+            current_vault: main
+            vaults:
+              - name: main
+                path: {}/main-vault
+            ambiguous_links:
+              policy: folders
+              folders: [Archive]
+        ", cmd.temp_dir.path().display()};
+        with_shared_names(cmd.with_config_file(&config))
+    }
+
+    #[test]
+    fn lists_shared_names() {
+        with_shared_names(Obx::from_command("audit ambiguous")).assert_stdout(indoc! {"
+            ┌───────┬───────────────────┬───────────────────────┬───────┐
+            │ Name  │ Resolves to       │ Also                  │ Links │
+            ├───────┼───────────────────┼───────────────────────┼───────┤
+            │ Index │ Projects/Index.md │ Archive/2023/Index.md │ 2     │
+            └───────┴───────────────────┴───────────────────────┴───────┘
+        "});
+    }
+
+    #[test]
+    fn leaves_out_links_to_notes_at_the_root() {
+        let cmd = with_shared_names(Obx::from_command("audit ambiguous -f json"));
+        cmd.temp_dir
+            .child("main-vault/Index.md")
+            .write_str("")
+            .unwrap();
+        cmd.assert_stdout(
+            "[{\"name\":\"Index\",\"files\":[\"Index.md\",\"Projects/Index.md\",\"Archive/2023/Index.md\"],\"links\":0}]\n",
+        );
+    }
+
+    #[test]
+    fn lists_shared_names_as_json() {
+        with_folder_policy(Obx::from_command("audit ambiguous -f json")).assert_stdout(
+            "[{\"name\":\"Index\",\"files\":[\"Archive/2023/Index.md\",\"Projects/Index.md\"],\"links\":2}]\n",
+        );
+    }
+
+    #[test]
+    fn resolves_links_by_folder_priority() {
+        with_folder_policy(Obx::from_command("backlinks Archive/2023/Index")).assert_stdout(
+            indoc! {"
+                ┌──────────┬──────┬────────────────────────┐
+                │ Note     │ Line │ Link                   │
+                ├──────────┼──────┼────────────────────────┤
+                │ links.md │ 1    │ [[Index]]              │
+                │ links.md │ 1    │ [[index]]              │
+                │ links.md │ 1    │ [[Archive/2023/Index]] │
+                └──────────┴──────┴────────────────────────┘
+            "},
+        );
+    }
+
+    #[test]
+    fn opens_by_folder_priority() {
+        with_folder_policy(Obx::from_command("cat Index")).assert_stdout("Archived\n");
+    }
+
+    #[test]
+    fn lists_notes_sharing_a_name_when_asking() {
+        with_shared_names(Obx::from_command("cat Index")).assert_stderr(
+            "Multiple notes match `Index`:\nProjects/Index.md\nArchive/2023/Index.md\n",
+        );
+    }
+}
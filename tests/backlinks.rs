@@ -0,0 +1,171 @@
+use assert_fs::prelude::*;
+use indoc::indoc;
+mod utils;
+use utils::*;
+
+mod backlinks {
+    use super::*;
+
+    #[test]
+    fn lists_links_from_other_notes() {
+        let cmd = Obx::from_command("backlinks simple-note");
+        cmd.temp_dir
+            .child("main-vault/folder/linking.md")
+            .write_str("See [[simple-note#Heading]]\n\n![[simple-note]] and [[simple-note|again]]")
+            .unwrap();
+        cmd.temp_dir
+            .child("main-vault/other.md")
+            .write_str("[simple](simple-note.md)")
+            .unwrap();
+
+        cmd.assert_stdout(indoc! {"
+            ┌───────────────────┬──────┬─────────────────────────────────┐
+            │ Note              │ Line │ Link                            │
+            ├───────────────────┼──────┼─────────────────────────────────┤
+            │ complex-note.md   │ 14   │ [[simple-note |link to a note]] │
+            │ folder/linking.md │ 1    │ [[simple-note#Heading]]         │
+            │ folder/linking.md │ 3    │ ![[simple-note]]                │
+            │ folder/linking.md │ 3    │ [[simple-note|again]]           │
+            │ other.md          │ 1    │ [simple](simple-note.md)        │
+            └───────────────────┴──────┴─────────────────────────────────┘
+        "});
+    }
+
+    #[test]
+    fn lists_references_from_canvases() {
+        let cmd = Obx::from_command("backlinks table");
+        cmd.temp_dir
+            .child("main-vault/board.canvas")
+            .write_str(indoc! {r#"
+                {
+                	"nodes":[
+                		{"id":"a","type":"file","file":"table.md","x":0,"y":0,"width":400,"height":400},
+                		{"id":"b","type":"text","text":"See [[table#Heading]]","x":500,"y":0,"width":250,"height":60}
+                	],
+                	"edges":[]
+                }
+            "#})
+            .unwrap();
+
+        cmd.assert_stdout(indoc! {"
+            ┌──────────────┬──────┬───────────────────┐
+            │ Note         │ Line │ Link              │
+            ├──────────────┼──────┼───────────────────┤
+            │ board.canvas │ 3    │ table.md          │
+            │ board.canvas │ 4    │ [[table#Heading]] │
+            └──────────────┴──────┴───────────────────┘
+        "});
+    }
+
+    #[test]
+    fn prints_nothing_without_backlinks() {
+        Obx::from_command("backlinks table").assert_stdout("");
+    }
+
+    #[test]
+    fn picks_up_links_added_since_cached() {
+        let cmd = Obx::from_command("backlinks table");
+        assert_cmd::Command::cargo_bin("obx")
+            .unwrap()
+            .current_dir(&cmd.temp_dir)
+            .env("OBX_CONFIG_DIR", cmd.temp_dir.child("config/obx").path())
+            .env("OBX_STATE_DIR", cmd.temp_dir.child("state/obx").path())
+            .args(["backlinks", "table"])
+            .assert()
+            .success()
+            .stdout("");
+
+        cmd.temp_dir
+            .child("main-vault/new.md")
+            .write_str("[[table]]")
+            .unwrap();
+
+        cmd.assert_stdout(indoc! {"
+            ┌────────┬──────┬───────────┐
+            │ Note   │ Line │ Link      │
+            ├────────┼──────┼───────────┤
+            │ new.md │ 1    │ [[table]] │
+            └────────┴──────┴───────────┘
+        "});
+    }
+
+    #[test]
+    fn lists_unlinked_mentions() {
+        let cmd = Obx::from_command("backlinks table --unlinked");
+        cmd.temp_dir
+            .child("main-vault/table.md")
+            .write_str("---\naliases: [grid]\n---\n")
+            .unwrap();
+        cmd.temp_dir
+            .child("main-vault/mentions.md")
+            .write_str("The Table below, a [[table]], a tablet\n  and a grid `table`")
+            .unwrap();
+
+        cmd.assert_stdout(indoc! {"
+            ┌─────────────┬──────┬────────────────────────────────────────┐
+            │ Note        │ Line │ Mention                                │
+            ├─────────────┼──────┼────────────────────────────────────────┤
+            │ mentions.md │ 1    │ The Table below, a [[table]], a tablet │
+            │ mentions.md │ 2    │ and a grid `table`                     │
+            └─────────────┴──────┴────────────────────────────────────────┘
+        "});
+    }
+
+    #[test]
+    fn links_unlinked_mentions() {
+        let cmd = Obx::from_command("backlinks table --unlinked --link");
+        cmd.temp_dir
+            .child("main-vault/mentions.md")
+            .write_str("See the table and Table")
+            .unwrap();
+
+        cmd.assert_stdout("Linked 2 mentions in 1 notes\n")
+            .temp_dir
+            .child("main-vault/mentions.md")
+            .assert("See the [[table]] and [[table|Table]]");
+    }
+
+    #[test]
+    fn lists_links_to_aliases() {
+        let cmd = Obx::from_command("backlinks table");
+        cmd.temp_dir
+            .child("main-vault/table.md")
+            .write_str("---\naliases:\n  - Grid\n---\n")
+            .unwrap();
+        cmd.temp_dir
+            .child("main-vault/new.md")
+            .write_str("[[grid]] and [[Grid|the grid]]")
+            .unwrap();
+
+        cmd.assert_stdout(indoc! {"
+            ┌────────┬──────┬───────────────────┐
+            │ Note   │ Line │ Link              │
+            ├────────┼──────┼───────────────────┤
+            │ new.md │ 1    │ [[grid]]          │
+            │ new.md │ 1    │ [[Grid|the grid]] │
+            └────────┴──────┴───────────────────┘
+        "});
+    }
+
+    #[test]
+    fn lists_links_into_blocks() {
+        let cmd = Obx::from_command("backlinks blocks --blocks");
+        cmd.temp_dir
+            .child("main-vault/blocks.md")
+            .write_str("- an item ^item\n")
+            .unwrap();
+        cmd.temp_dir
+            .child("main-vault/linking.md")
+            .write_str("[[blocks]] ![[blocks#^item]]\n[[blocks#^gone]]")
+            .unwrap();
+
+        cmd.assert_stdout(indoc! {"
+            ┌────────────┬──────┬───────────────────┬───────────┐
+            │ Note       │ Line │ Link              │ Block     │
+            ├────────────┼──────┼───────────────────┼───────────┤
+            │ linking.md │ 1    │ ![[blocks#^item]] │ - an item │
+            │ linking.md │ 2    │ [[blocks#^gone]]  │ (missing) │
+            └────────────┴──────┴───────────────────┴───────────┘
+        "});
+    }
+}
@@ -0,0 +1,49 @@
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+mod utils;
+use utils::*;
+
+mod bench {
+    use super::*;
+
+    #[test]
+    fn times_each_stage() {
+        let mut cmd = Obx::from_command("bench --runs 1 -f json -q note");
+        let output = cmd.cmd.output().unwrap();
+        assert!(output.status.success());
+
+        let timings: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        let stages: Vec<&str> = timings
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|timing| timing["stage"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            stages,
+            [
+                "Scan",
+                "Metadata",
+                "Index build",
+                "Search `note`",
+                "Link graph"
+            ]
+        );
+        assert_eq!(timings[0]["result"], "8 notes in 2 folders");
+    }
+
+    #[test]
+    fn saves_nothing() {
+        let cmd = Obx::from_command("bench --runs 1").assert_success();
+        cmd.temp_dir
+            .child("state")
+            .assert(predicate::path::missing());
+    }
+
+    #[test]
+    fn is_hidden_from_help() {
+        let mut cmd = Obx::from_command("--help");
+        let output = cmd.cmd.output().unwrap();
+        assert!(!String::from_utf8_lossy(&output.stdout).contains("bench"));
+    }
+}
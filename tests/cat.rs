@@ -0,0 +1,116 @@
+use assert_fs::prelude::*;
+use indoc::indoc;
+mod utils;
+use utils::*;
+
+mod cat {
+    use super::*;
+
+    #[test]
+    fn prints_whole_note() {
+        Obx::from_command("cat with-fm").assert_stdout(indoc! {"
+            ---
+            test-number: 100
+            test-str: a string val
+            test-checkbox: true
+            test-list:
+              - One
+              - Two
+            ---
+
+            The main content of the file
+        "});
+    }
+
+    #[test]
+    fn finds_notes_with_decomposed_names() {
+        let cmd = Obx::from_command("cat café");
+        cmd.temp_dir
+            .child("main-vault/cafe\u{301}.md")
+            .write_str("Espresso")
+            .unwrap();
+
+        cmd.assert_stdout("Espresso\n");
+    }
+
+    #[test]
+    fn prints_without_frontmatter() {
+        Obx::from_command("cat with-fm-properties --no-frontmatter")
+            .assert_stdout("The main content of the file\n");
+    }
+
+    #[test]
+    fn prints_only_frontmatter() {
+        Obx::from_command("cat with-fm-properties --frontmatter-only").assert_stdout(indoc! {"
+            test-number: 100
+            test-str: a string val
+            test-checkbox: true
+            test-list:
+              - One
+              - Two
+        "});
+    }
+
+    #[test]
+    fn renders_markdown() {
+        Obx::from_command("cat simple-note --render --color always").assert_stdout_contains(
+            "\u{1b}[1mSimple note\u{1b}[0m\n\nThis is the contents of simple-note.md\n",
+        );
+    }
+
+    #[test]
+    fn renders_plain_text_unless_colored() {
+        let mut cmd = Obx::from_command("cat simple-note --render");
+        let output = cmd.cmd.output().unwrap();
+        let stdout = String::from_utf8(output.stdout).unwrap();
+
+        assert!(output.status.success());
+        assert!(!stdout.contains('\u{1b}'), "{stdout}");
+        assert!(stdout.contains("This is the contents of simple-note.md"));
+    }
+
+    #[test]
+    fn rejects_conflicting_flags() {
+        let mut cmd = Obx::from_command("cat simple-note --no-frontmatter --frontmatter-only");
+        assert!(!cmd.cmd.output().unwrap().status.success());
+    }
+
+    #[test]
+    fn prints_a_block() {
+        let cmd = Obx::from_command("cat blocks --block ^second");
+        cmd.temp_dir
+            .child("main-vault/blocks.md")
+            .write_str("First paragraph ^first\n\n- an item\n- the second item ^second\n")
+            .unwrap();
+
+        cmd.assert_stdout("- the second item\n");
+    }
+
+    #[test]
+    fn fails_for_missing_blocks() {
+        Obx::from_command("cat simple-note --block missing")
+            .assert_stderr("No block ^missing in simple-note\n");
+    }
+
+    #[test]
+    fn expands_embeds() {
+        let cmd = Obx::from_command("cat embedding --expand-embeds");
+        cmd.temp_dir
+            .child("main-vault/embedding.md")
+            .write_str("---\ntitle: Embedding\n---\nQuote:\n\n![[blocks#^quote]]\n")
+            .unwrap();
+        cmd.temp_dir
+            .child("main-vault/blocks.md")
+            .write_str("> To be or not to be\n\n^quote\n")
+            .unwrap();
+
+        cmd.assert_stdout(indoc! {"
+            ---
+            title: Embedding
+            ---
+            Quote:
+
+            > To be or not to be
+        "});
+    }
+}
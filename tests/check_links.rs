@@ -0,0 +1,114 @@
+use assert_fs::prelude::*;
+use indoc::indoc;
+use predicates::prelude::*;
+mod utils;
+use utils::*;
+
+mod check_links {
+    use super::*;
+
+    #[test]
+    fn reports_broken_links() {
+        let cmd = Obx::from_command("check-links");
+        cmd.temp_dir
+            .child("main-vault/folder/broken.md")
+            .write_str("[[simple-note]]\n![[missing.png]] and [gone](../gone.md)")
+            .unwrap();
+
+        let _ = &cmd.assert_stdout(indoc! {"
+            ┌──────────────────┬──────┬───────────────────────────────┐
+            │ Note             │ Line │ Broken link                   │
+            ├──────────────────┼──────┼───────────────────────────────┤
+            │ folder/broken.md │ 2    │ ![[missing.png]]              │
+            │ folder/broken.md │ 2    │ [gone](../gone.md)            │
+            │ link-types.md    │ 3    │ [[wikilink]]                  │
+            │ link-types.md    │ 5    │ [[wikilink |different label]] │
+            └──────────────────┴──────┴───────────────────────────────┘
+        "});
+    }
+
+    #[test]
+    fn requires_a_terminal_to_fix() {
+        Obx::from_command("check-links --fix-interactive")
+            .assert_stderr("--fix-interactive needs an interactive terminal\n");
+    }
+
+    #[test]
+    fn fixes_links_interactively() {
+        let cmd = Obx::from_command("check-links --fix-interactive");
+        cmd.temp_dir
+            .child("main-vault/link-types.md")
+            .write_str("")
+            .unwrap();
+        let note = cmd.temp_dir.child("main-vault/typo.md");
+        note.write_str("See [[simple-nte#Heading|label]]").unwrap();
+
+        let mut p = cmd.spawn_interactive(Some(5_000)).unwrap();
+
+        p.exp_string("Replace [[simple-nte#Heading|label]] in typo.md:1 with")
+            .unwrap();
+        p.send_line("").unwrap();
+        p.exp_string("Fixed 1 of 1 broken links").unwrap();
+
+        note.assert(predicate::str::diff("See [[simple-note#Heading|label]]"));
+    }
+
+    #[test]
+    fn resolves_links_to_aliases() {
+        let cmd = Obx::from_command("check-links");
+        cmd.temp_dir
+            .child("main-vault/glossary.md")
+            .write_str("---\naliases: [Wikilink]\n---\n")
+            .unwrap();
+
+        let _ = &cmd.assert_stdout("");
+    }
+
+    #[test]
+    fn reports_links_to_missing_blocks() {
+        let cmd = Obx::from_command("check-links");
+        cmd.temp_dir
+            .child("main-vault/link-types.md")
+            .write_str("")
+            .unwrap();
+        cmd.temp_dir
+            .child("main-vault/blocks.md")
+            .write_str("A paragraph ^para\n\n[[blocks#^para]] [[blocks#^gone]]")
+            .unwrap();
+
+        cmd.assert_stdout(indoc! {"
+            ┌───────────┬──────┬──────────────────┐
+            │ Note      │ Line │ Broken link      │
+            ├───────────┼──────┼──────────────────┤
+            │ blocks.md │ 3    │ [[blocks#^gone]] │
+            └───────────┴──────┴──────────────────┘
+        "});
+    }
+
+    #[test]
+    fn reports_links_to_missing_headings() {
+        let cmd = Obx::from_command("check-links");
+        cmd.temp_dir
+            .child("main-vault/link-types.md")
+            .write_str("")
+            .unwrap();
+        cmd.temp_dir
+            .child("main-vault/guide.md")
+            .write_str(indoc! {"
+                # Guide
+                ## Set up: the basics
+                [[#Guide]] [[guide#Set up the basics]] [Basics](guide.md#set-up-the-basics)
+                [[guide#Guide#Missing]] [[#Gone]]
+            "})
+            .unwrap();
+
+        cmd.assert_stdout(indoc! {"
+            ┌──────────┬──────┬─────────────────────────┐
+            │ Note     │ Line │ Broken link             │
+            ├──────────┼──────┼─────────────────────────┤
+            │ guide.md │ 4    │ [[guide#Guide#Missing]] │
+            │ guide.md │ 4    │ [[#Gone]]               │
+            └──────────┴──────┴─────────────────────────┘
+        "});
+    }
+}
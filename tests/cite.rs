@@ -0,0 +1,93 @@
+use assert_fs::prelude::*;
+use indoc::{formatdoc, indoc};
+mod utils;
+use utils::*;
+
+const BIBLIOGRAPHY: &str = indoc! {r#"
+    @book{kernighan1988,
+      author    = {Kernighan, Brian W. and Ritchie, Dennis M.},
+      title     = {The {C} Programming Language},
+      publisher = {Prentice Hall},
+      year      = 1988,
+    }
+
+    @article{lamport1978,
+      author  = {Leslie Lamport},
+      title   = {Time, Clocks, and the Ordering of Events in a Distributed System},
+      journal = {Communications of the ACM},
+      year    = {1978}
+    }
+"#};
+
+fn with_bibliography(command: &str) -> Obx {
+    let cmd = Obx::from_command(command);
+    let dir = cmd.temp_dir.display().to_string();
+    cmd.temp_dir
+        .child("main-vault/references.bib")
+        .write_str(BIBLIOGRAPHY)
+        .unwrap();
+
+    cmd.with_config_file(&formatdoc! {"
+        current_vault: main
+        vaults:
+        - name: main
+          path: {dir}/main-vault
+        cite:
+          bibliography: references.bib
+        "})
+}
+
+mod cite {
+    use super::*;
+
+    #[test]
+    fn searches_entries() {
+        with_bibliography("cite search kernighan").assert_stdout(indoc! {"
+            ┌───────────────┬─────────────────────┬──────┬────────────────────────────┐
+            │ Key           │ Authors             │ Year │ Title                      │
+            ├───────────────┼─────────────────────┼──────┼────────────────────────────┤
+            │ kernighan1988 │ Kernighan & Ritchie │ 1988 │ The C Programming Language │
+            └───────────────┴─────────────────────┴──────┴────────────────────────────┘
+        "});
+    }
+
+    #[test]
+    fn appends_citations() {
+        with_bibliography("cite insert lamport1978 simple-note")
+            .assert_stdout("Cited lamport1978 in simple-note.md\n")
+            .temp_dir
+            .child("main-vault/simple-note.md")
+            .assert(predicates::str::ends_with(
+                "\n\nLamport (1978). *Time, Clocks, and the Ordering of Events in a Distributed System*. Communications of the ACM.\n",
+            ));
+    }
+
+    #[test]
+    fn creates_literature_notes_and_links_to_them() {
+        let cmd = with_bibliography("cite insert @kernighan1988 empty-note --literature-note")
+            .assert_stdout(indoc! {"
+                Created literature note literature/@kernighan1988.md
+                Cited kernighan1988 in empty-note.md
+            "});
+
+        cmd.temp_dir
+            .child("main-vault/literature/@kernighan1988.md")
+            .assert(indoc! {"
+                ---
+                citekey: kernighan1988
+                ---
+                # The C Programming Language
+
+                Kernighan & Ritchie (1988). *The C Programming Language*. Prentice Hall.
+            "});
+        cmd.temp_dir.child("main-vault/empty-note.md").assert(
+            "Kernighan & Ritchie (1988). *The C Programming Language*. Prentice Hall. [[@kernighan1988]]\n",
+        );
+    }
+
+    #[test]
+    fn errors_on_unknown_keys() {
+        with_bibliography("cite insert knuth1984 simple-note")
+            .assert_stderr("No entry `knuth1984` in the bibliography\n");
+    }
+}
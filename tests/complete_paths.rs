@@ -0,0 +1,33 @@
+use indoc::indoc;
+mod utils;
+use utils::*;
+
+mod complete_paths {
+    use super::*;
+
+    #[test]
+    fn ranks_prefix_matches_first() {
+        Obx::from_command("complete-paths c").assert_stdout(indoc! {"
+            complex-note.md
+            folder/child-note.md
+        "});
+    }
+
+    #[test]
+    fn matches_fuzzily() {
+        Obx::from_command("complete-paths fchn").assert_stdout("folder/child-note.md\n");
+    }
+
+    #[test]
+    fn limits_results() {
+        Obx::from_command("complete-paths note -n 2").assert_stdout(indoc! {"
+            empty-note.md
+            simple-note.md
+        "});
+    }
+
+    #[test]
+    fn lists_every_note_without_prefix() {
+        Obx::from_command("complete-paths -n 1").assert_stdout("html.md\n");
+    }
+}
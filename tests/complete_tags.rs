@@ -0,0 +1,46 @@
+use assert_fs::prelude::*;
+use indoc::indoc;
+mod utils;
+use utils::*;
+
+fn with_tagged_notes(command: &str) -> Obx {
+    let cmd = Obx::from_command(command);
+    cmd.temp_dir
+        .child("main-vault/project.md")
+        .write_str("---\ntags: [project, work/programming]\n---\nSome #idea")
+        .unwrap();
+    cmd.temp_dir
+        .child("main-vault/other.md")
+        .write_str("More #programming for #project")
+        .unwrap();
+
+    cmd
+}
+
+mod complete_tags {
+    use super::*;
+
+    #[test]
+    fn ranks_most_used_tags_first() {
+        let _ = &with_tagged_notes("complete-tags pro").assert_stdout(indoc! {"
+            project
+            programming
+            work/programming
+        "});
+    }
+
+    #[test]
+    fn ignores_leading_hash() {
+        let _ = &with_tagged_notes("complete-tags #id").assert_stdout("idea\n");
+    }
+
+    #[test]
+    fn limits_results() {
+        let _ = &with_tagged_notes("complete-tags -n 1").assert_stdout("project\n");
+    }
+
+    #[test]
+    fn prints_nothing_without_matches() {
+        let _ = &with_tagged_notes("complete-tags zzz").assert_stdout("");
+    }
+}
@@ -0,0 +1,116 @@
+use std::{env, fs, path::PathBuf, process::Command};
+mod utils;
+use assert_fs::prelude::{FileWriteStr, PathChild};
+use utils::*;
+
+mod completions {
+    use super::*;
+
+    fn script(shell: &str) -> (Obx, String) {
+        let mut cmd = Obx::from_command(&format!("completions {shell}"));
+        let output = cmd.cmd.output().unwrap();
+        assert!(output.status.success());
+        (cmd, String::from_utf8(output.stdout).unwrap())
+    }
+
+    /// Run the bash completion function for `words`, with the cursor on the
+    /// last word, printing each candidate on its own line
+    fn complete_in_bash(words: &[&str]) -> String {
+        complete_in_bash_with(words, &[])
+    }
+
+    /// Like [`complete_in_bash`], with extra notes written to the vault first
+    fn complete_in_bash_with(words: &[&str], notes: &[(&str, &str)]) -> String {
+        let (cmd, script) = script("bash");
+        for (path, contents) in notes {
+            cmd.temp_dir
+                .child(format!("main-vault/{path}"))
+                .write_str(contents)
+                .unwrap();
+        }
+        let script_file = cmd.temp_dir.child("obx.bash");
+        fs::write(script_file.path(), script).unwrap();
+
+        let bin_dir = PathBuf::from(env!("CARGO_BIN_EXE_obx"))
+            .parent()
+            .unwrap()
+            .to_path_buf();
+        let path = format!("{}:{}", bin_dir.display(), env::var("PATH").unwrap());
+
+        let output = Command::new("bash")
+            .arg("-c")
+            .arg(format!(
+                r#"source {script}; COMP_WORDS=({words}); COMP_CWORD={cword}; _obx_dynamic obx "${{COMP_WORDS[COMP_CWORD]}}" "${{COMP_WORDS[COMP_CWORD-1]}}"; printf '%s\n' "${{COMPREPLY[@]}}""#,
+                script = script_file.display(),
+                words = words
+                    .iter()
+                    .map(|word| format!("'{word}'"))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                cword = words.len() - 1,
+            ))
+            .env("PATH", path)
+            .env(
+                "OBX_CONFIG_DIR",
+                cmd.temp_dir.child("config/obx").display().to_string(),
+            )
+            .output()
+            .unwrap();
+
+        String::from_utf8(output.stdout).unwrap()
+    }
+
+    #[test]
+    fn bash_completes_note_names() {
+        assert_eq!(
+            complete_in_bash(&["obx", "open", "child"]),
+            "folder/child-note.md\n"
+        );
+    }
+
+    #[test]
+    fn bash_completes_vault_names() {
+        assert_eq!(
+            complete_in_bash(&["obx", "cat", "--vault", "sec"]),
+            "secondary\n"
+        );
+    }
+
+    #[test]
+    fn bash_completes_tags() {
+        let notes = [("tagged.md", "#project #programming #work")];
+
+        assert_eq!(
+            complete_in_bash_with(&["obx", "notes", "create", "a", "--tag", "pro"], &notes),
+            "programming\nproject\n"
+        );
+        assert_eq!(
+            complete_in_bash_with(&["obx", "meta", "set", "tags", "wo"], &notes),
+            "work\n"
+        );
+    }
+
+    #[test]
+    fn bash_falls_back_to_subcommands() {
+        assert_eq!(complete_in_bash(&["obx", "ope"]), "open\n");
+    }
+
+    #[test]
+    fn zsh_uses_dynamic_helpers() {
+        let (_, script) = script("zsh");
+
+        assert!(script.contains("'--vault=[]:VAULT:_obx_vaults'"));
+        assert!(script.contains("_obx_notes() {"));
+        assert!(script.contains(":TAG:_obx_tags'"));
+        assert!(script.trim_end().ends_with("fi"));
+    }
+
+    #[test]
+    fn fish_completes_notes_for_open() {
+        let (_, script) = script("fish");
+
+        assert!(script.contains(
+            "complete -c obx -n \"__fish_obx_using_subcommand open\" -f -a '(obx complete-paths (commandline -ct) 2>/dev/null)'"
+        ));
+    }
+}
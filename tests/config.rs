@@ -4,7 +4,7 @@ use serde_yaml::Value;
 use std::fs;
 
 mod utils;
-use utils::Obx;
+use utils::{create_script, Obx};
 
 mod config {
     use super::*;
@@ -69,6 +69,66 @@ mod config {
         assert!(value.get("editor").is_none());
     }
 
+    #[test]
+    fn set_editor_warns_when_missing() {
+        let mut cmd = Obx::from_command("config set --editor definitely-not-an-editor");
+
+        cmd.cmd
+            .assert()
+            .success()
+            .stdout("Configuration updated\n")
+            .stderr("Warning: `definitely-not-an-editor` wasn't found, check it's installed and on your $PATH\n");
+    }
+
+    #[test]
+    fn set_editor_warns_when_not_executable() {
+        let mut cmd = Obx::from_command("config set --editor ./editor.sh");
+        fs::write(cmd.temp_dir.child("editor.sh").path(), "#!/bin/sh\n").unwrap();
+
+        cmd.cmd
+            .assert()
+            .success()
+            .stderr("Warning: `./editor.sh` isn't executable, check its permissions\n");
+    }
+
+    #[test]
+    fn set_editor_rejects_unbalanced_quotes() {
+        Obx::from_command("config set --editor 'vim")
+            .assert_stderr("Editor `'vim` has unbalanced quotes\n");
+    }
+
+    #[test]
+    fn editor_detect_prefers_visual() {
+        let mut cmd = Obx::from_command("config set --editor-detect");
+        let visual = create_script("visual.sh", "", &cmd.temp_dir);
+        let config_file = cmd.temp_dir.child("./config/obx/config.yml");
+        cmd.env("VISUAL", format!("{} --wait", visual.display()));
+
+        cmd.cmd.assert().success().stdout(format!(
+            "Using editor `{} --wait` from $VISUAL\nConfiguration updated\n",
+            visual.display()
+        ));
+
+        let contents = fs::read_to_string(config_file.path()).unwrap();
+        let value: Value = serde_yaml::from_str(&contents).unwrap();
+        assert_eq!(
+            value.get("editor").and_then(Value::as_str),
+            Some(format!("{} --wait", visual.display()).as_str()),
+        );
+    }
+
+    #[test]
+    fn editor_detect_falls_back_to_editor() {
+        let mut cmd = Obx::from_command("config set --editor-detect");
+        let editor = cmd.temp_dir.child("mock_editor.sh");
+        cmd.env("VISUAL", "definitely-not-an-editor");
+
+        cmd.cmd.assert().success().stdout(format!(
+            "Using editor `{}` from $EDITOR\nConfiguration updated\n",
+            editor.display()
+        ));
+    }
+
     #[test]
     fn set_without_changes_is_noop() {
         Obx::from_command("config set")
@@ -77,4 +137,972 @@ mod config {
             .success()
             .stdout("Nothing to update\n");
     }
+
+    mod keys {
+        use super::*;
+
+        fn read_config(cmd: &Obx) -> Value {
+            let config_file = cmd.temp_dir.child("./config/obx/config.yml");
+            let contents = fs::read_to_string(config_file.path()).unwrap();
+            serde_yaml::from_str(&contents).unwrap()
+        }
+
+        #[test]
+        fn get_prints_nested_values() {
+            let cmd = Obx::from_command("config get vaults.secondary.path");
+            let expected = format!("{}/another/path\n", cmd.temp_dir.display());
+
+            cmd.assert_stdout(&expected);
+        }
+
+        #[test]
+        fn get_picks_list_entries_by_index() {
+            Obx::from_command("config get vaults.0.name").assert_stdout("main\n");
+        }
+
+        #[test]
+        fn get_errors_on_unset_keys() {
+            Obx::from_command("config get editor").assert_stderr("Config key `editor` isn't set\n");
+        }
+
+        #[test]
+        fn set_updates_nested_values() {
+            let mut cmd = Obx::from_command("config set vaults.secondary.path /elsewhere");
+            cmd.cmd.assert().success().stdout("Configuration updated\n");
+
+            assert_eq!(
+                read_config(&cmd)["vaults"][1]["path"].as_str(),
+                Some("/elsewhere")
+            );
+        }
+
+        #[test]
+        fn set_creates_missing_mappings() {
+            let mut cmd = Obx::from_command("config set themes.dusk.accent #ff79c6");
+            cmd.cmd.assert().success();
+
+            assert_eq!(
+                read_config(&cmd)["themes"]["dusk"]["accent"].as_str(),
+                Some("#ff79c6")
+            );
+        }
+
+        #[test]
+        fn set_validates_types() {
+            Obx::from_command("config set vaults 5").assert_stderr(
+                "Invalid value for `vaults`: invalid type: integer `5`, expected a sequence\n",
+            );
+        }
+
+        #[test]
+        fn set_keeps_strings_that_look_like_numbers() {
+            let mut cmd = Obx::from_command("config set editor 42");
+            cmd.cmd.assert().success();
+
+            assert_eq!(read_config(&cmd)["editor"].as_str(), Some("42"));
+        }
+
+        #[test]
+        fn set_rejects_unknown_keys() {
+            Obx::from_command("config set colour blue")
+                .assert_stderr("Unknown config key `colour`\n");
+        }
+
+        #[test]
+        fn set_rejects_invalid_theme_colours() {
+            Obx::from_command("config set themes.dusk.accent pink").assert_stderr(
+                "Theme `dusk` has an invalid `accent` colour `pink`, expected `#rrggbb`\n",
+            );
+        }
+
+        #[test]
+        fn unset_removes_values() {
+            let mut cmd = Obx::from_command("config set --unset vaults.secondary");
+            cmd.cmd.assert().success();
+
+            let config = read_config(&cmd);
+            let vaults = config["vaults"].as_sequence().unwrap();
+            assert_eq!(vaults.len(), 1);
+            assert_eq!(vaults[0]["name"].as_str(), Some("main"));
+        }
+
+        #[test]
+        fn unset_refuses_required_keys() {
+            Obx::from_command("config set --unset current_vault")
+                .assert_stderr("Can't unset `current_vault`: missing field `current_vault`\n");
+        }
+
+        #[test]
+        fn set_requires_a_value() {
+            Obx::from_command("config set editor")
+                .assert_stderr("Give a value for `editor`, or --unset to remove it\n");
+        }
+    }
+
+    mod versions {
+        use super::*;
+
+        #[test]
+        fn writes_current_version() {
+            let mut cmd = Obx::from_command("config set --editor nvim");
+            let config_file = cmd.temp_dir.child("./config/obx/config.yml");
+            cmd.cmd.assert().success();
+
+            let contents = fs::read_to_string(config_file.path()).unwrap();
+            let value: Value = serde_yaml::from_str(&contents).unwrap();
+            assert_eq!(value["version"].as_u64(), Some(1));
+        }
+
+        #[test]
+        fn leaves_unversioned_config_in_place() {
+            let mut cmd = Obx::from_command("vaults list");
+            cmd.cmd.assert().success();
+
+            let config_dir = cmd.temp_dir.child("./config/obx/");
+            let files: Vec<_> = fs::read_dir(config_dir.path())
+                .unwrap()
+                .map(|entry| entry.unwrap().file_name())
+                .collect();
+            assert_eq!(files, ["config.yml"]);
+        }
+
+        #[test]
+        fn rejects_newer_versions() {
+            let cmd = Obx::from_command("vaults list");
+            let config_file = cmd.temp_dir.child("./config/obx/config.yml");
+            let expected = format!(
+                "Config file {} is version 99, but this obx only understands up to version 1, upgrade obx to use it\n",
+                config_file.display()
+            );
+
+            cmd.with_config_file("version: 99\ncurrent_vault: main\nvaults: []\n")
+                .assert_stderr(expected);
+        }
+    }
+
+    mod toml {
+        use super::*;
+
+        fn with_toml_config(command: &str) -> Obx {
+            let cmd = Obx::from_command(command);
+            let config_dir = cmd.temp_dir.child("./config/obx/");
+            fs::remove_file(config_dir.child("config.yml").path()).unwrap();
+            fs::write(
+                config_dir.child("config.toml").path(),
+                format!(
+                    "current_vault = \"main\"\n\n[[vaults]]\nname = \"main\"\npath = \"{}/main-vault/\"\n",
+                    cmd.temp_dir.display()
+                ),
+            )
+            .unwrap();
+            cmd
+        }
+
+        #[test]
+        fn reads_toml_config() {
+            with_toml_config("config path").assert_stdout_contains("config.toml");
+        }
+
+        #[test]
+        fn writes_toml_config() {
+            let mut cmd = with_toml_config("config set --editor nvim");
+            let config_file = cmd.temp_dir.child("./config/obx/config.toml");
+            cmd.cmd.assert().success();
+
+            let contents = fs::read_to_string(config_file.path()).unwrap();
+            assert!(contents.contains("editor = \"nvim\""));
+            assert!(contents.contains("[[vaults]]"));
+        }
+
+        #[test]
+        fn convert_rewrites_yaml_as_toml() {
+            let mut cmd = Obx::from_command("config convert --to toml");
+            let config_dir = cmd.temp_dir.child("./config/obx/");
+            cmd.cmd.assert().success();
+
+            let contents = fs::read_to_string(config_dir.child("config.toml").path()).unwrap();
+            let value: ::toml::Value = ::toml::from_str(&contents).unwrap();
+            assert_eq!(value["current_vault"].as_str(), Some("main"));
+            assert_eq!(value["vaults"][1]["name"].as_str(), Some("secondary"));
+            assert!(config_dir.child("config.yml.bak").exists());
+            assert!(!config_dir.child("config.yml").exists());
+
+            let mut current = Obx::from_command("vaults current");
+            current.env("OBX_CONFIG_DIR", config_dir.display().to_string());
+            current.assert_stdout_contains("Current vault is `main`");
+        }
+
+        #[test]
+        fn convert_refuses_same_format() {
+            let cmd = Obx::from_command("config convert --to yaml");
+            let expected = format!(
+                "Config file {} is already YAML\n",
+                cmd.temp_dir.child("./config/obx/config.yml").display()
+            );
+
+            cmd.assert_stderr(expected);
+        }
+    }
+
+    mod include {
+        use super::*;
+        use assert_fs::prelude::FileWriteStr;
+        use indoc::{formatdoc, indoc};
+
+        fn with_team_include(command: &str) -> Obx {
+            let cmd = Obx::from_command(command);
+            let dir = cmd.temp_dir.display().to_string();
+
+            cmd.temp_dir
+                .child("./config/obx/team.yml")
+                .write_str(indoc! {"
+                    editor: team-editor
+                    theme: solarized-dark
+                "})
+                .unwrap();
+
+            cmd.with_config_file(&formatdoc! {"
+                include:
+                - team.yml
+                current_vault: main
+                vaults:
+                - name: main
+                  path: {dir}/main-vault/
+                theme: gruvbox-light
+            "})
+        }
+
+        #[test]
+        fn resolved_print_merges_includes() {
+            let mut cmd = with_team_include("config print --resolved");
+            let output = cmd.cmd.output().unwrap();
+            let value: Value = serde_yaml::from_slice(&output.stdout).unwrap();
+
+            assert_eq!(
+                value.get("editor").and_then(Value::as_str),
+                Some("team-editor"),
+                "expected editor to come from the include",
+            );
+            assert_eq!(
+                value.get("theme").and_then(Value::as_str),
+                Some("gruvbox-light"),
+                "expected config.yml to override the include",
+            );
+        }
+
+        #[test]
+        fn print_shows_unmerged_file() {
+            let mut cmd = with_team_include("config print");
+            let output = cmd.cmd.output().unwrap();
+            let value: Value = serde_yaml::from_slice(&output.stdout).unwrap();
+
+            assert!(value.get("editor").is_none());
+        }
+
+        #[test]
+        fn write_keeps_included_values_out_of_config() {
+            let mut cmd = with_team_include("config set --theme obsidian-light");
+            let config_file = cmd.temp_dir.child("./config/obx/config.yml");
+
+            cmd.cmd.assert().success().stdout("Configuration updated\n");
+
+            let contents = fs::read_to_string(config_file.path()).unwrap();
+            let value: Value = serde_yaml::from_str(&contents).unwrap();
+
+            assert!(value.get("editor").is_none());
+            assert_eq!(
+                value.get("theme").and_then(Value::as_str),
+                Some("obsidian-light"),
+            );
+        }
+
+        #[test]
+        fn errors_on_missing_include() {
+            let cmd = Obx::from_command("vaults list").with_config_file(indoc! {"
+                include:
+                - missing.yml
+                current_vault: main
+                vaults: []
+            "});
+
+            let expected = format!(
+                "failed to read included config file {}\n",
+                cmd.temp_dir.child("./config/obx/missing.yml").display()
+            );
+
+            cmd.assert_stderr(expected);
+        }
+    }
+
+    mod profiles {
+        use super::*;
+        use indoc::formatdoc;
+
+        fn with_writing_profile(command: &str) -> Obx {
+            let cmd = Obx::from_command(command);
+            let dir = cmd.temp_dir.display().to_string();
+
+            cmd.with_config_file(&formatdoc! {"
+                current_vault: main
+                vaults:
+                - name: main
+                  path: {dir}/main-vault/
+                - name: secondary
+                  path: {dir}/another/path
+                theme: gruvbox-light
+                profiles:
+                  writing:
+                    vault: secondary
+                    editor: ia-writer
+                    theme: obsidian-light
+            "})
+        }
+
+        #[test]
+        fn flag_selects_profile() {
+            with_writing_profile("vaults current --profile writing")
+                .assert_stdout_contains("Current vault is `secondary`");
+        }
+
+        #[test]
+        fn env_selects_profile() {
+            let mut cmd = with_writing_profile("vaults current");
+            cmd.env("OBX_PROFILE", "writing");
+
+            cmd.assert_stdout_contains("Current vault is `secondary`");
+        }
+
+        #[test]
+        fn resolved_print_applies_profile() {
+            let mut cmd = with_writing_profile("config print --resolved --profile writing");
+            let output = cmd.cmd.output().unwrap();
+            let value: Value = serde_yaml::from_slice(&output.stdout).unwrap();
+
+            assert_eq!(
+                value.get("editor").and_then(Value::as_str),
+                Some("ia-writer")
+            );
+            assert_eq!(
+                value.get("theme").and_then(Value::as_str),
+                Some("obsidian-light")
+            );
+        }
+
+        #[test]
+        fn write_keeps_changes_within_profile() {
+            let mut cmd =
+                with_writing_profile("config set --theme solarized-dark --profile writing");
+            let config_file = cmd.temp_dir.child("./config/obx/config.yml");
+
+            cmd.cmd.assert().success().stdout("Configuration updated\n");
+
+            let contents = fs::read_to_string(config_file.path()).unwrap();
+            let value: Value = serde_yaml::from_str(&contents).unwrap();
+
+            assert_eq!(
+                value.get("theme").and_then(Value::as_str),
+                Some("gruvbox-light"),
+            );
+            assert_eq!(
+                value.get("current_vault").and_then(Value::as_str),
+                Some("main"),
+            );
+            assert!(value.get("editor").is_none());
+            assert_eq!(
+                value["profiles"]["writing"]["theme"].as_str(),
+                Some("solarized-dark"),
+            );
+            assert_eq!(
+                value["profiles"]["writing"]["vault"].as_str(),
+                Some("secondary"),
+            );
+        }
+
+        #[test]
+        fn errors_on_unknown_profile() {
+            with_writing_profile("vaults current --profile coding")
+                .assert_stderr("Profile `coding` not found in config\n");
+        }
+    }
+
+    mod edit {
+        use super::*;
+        use assert_fs::prelude::PathAssert;
+        use predicates::prelude::*;
+
+        #[test]
+        fn opens_config_in_editor() {
+            let mut cmd =
+                Obx::from_command("config edit").with_editor(r#"echo "editor: nvim" >> "$1""#);
+            let config_file = cmd.temp_dir.child("./config/obx/config.yml");
+
+            cmd.cmd.assert().success().stdout("");
+
+            config_file.assert(predicate::str::ends_with("editor: nvim\n"));
+        }
+
+        #[test]
+        fn creates_commented_default() {
+            let mut cmd = Obx::from_command("config edit");
+            let config_file = cmd.temp_dir.child("./config/obx/config.yml");
+            fs::remove_file(config_file.path()).unwrap();
+
+            cmd.cmd.assert().success();
+
+            config_file.assert(predicate::str::starts_with("# obx configuration."));
+        }
+
+        #[test]
+        fn reports_parse_errors_with_line() {
+            let cmd = Obx::from_command("config edit")
+                .with_editor(r#"printf 'current_vault: main\nvaults: [\n' > "$1""#);
+            let config_file = cmd.temp_dir.child("./config/obx/config.yml");
+
+            let expected = format!(
+                "Config file {} is invalid: did not find expected node content at line 3 column 1, while parsing a flow node\n",
+                config_file.display()
+            );
+            cmd.assert_stderr(expected);
+        }
+
+        #[test]
+        fn reports_invalid_values_with_line() {
+            let cmd = Obx::from_command("config edit").with_editor(r#"echo "profiles: 3" >> "$1""#);
+            let config_file = cmd.temp_dir.child("./config/obx/config.yml");
+
+            let expected = format!(
+                "Config file {} is invalid: profiles: invalid type: integer `3`, expected a map at line 7 column 11\n",
+                config_file.display()
+            );
+            cmd.assert_stderr(expected);
+        }
+    }
+
+    mod validate {
+        use super::*;
+        use indoc::{formatdoc, indoc};
+
+        #[test]
+        fn accepts_valid_config() {
+            let mut cmd = Obx::from_command("config validate");
+            let config_file = cmd.temp_dir.child("./config/obx/config.yml");
+
+            cmd.cmd
+                .assert()
+                .success()
+                .stdout(format!("{} is valid\n", config_file.display()));
+        }
+
+        #[test]
+        fn lists_every_problem() {
+            let cmd = Obx::from_command("config validate");
+            let dir = cmd.temp_dir.display().to_string();
+            let cmd = cmd.with_config_file(&formatdoc! {"
+                current_vault: work
+                vaults:
+                - name: main
+                  path: {dir}/main-vault/
+                - name: gone
+                  path: {dir}/deleted-vault
+                editor: definitely-not-an-editor --wait
+                profiles:
+                  writing:
+                    vault: drafts
+            "});
+            let config_file = cmd.temp_dir.child("./config/obx/config.yml");
+
+            cmd.assert_stderr(formatdoc! {"
+                Found 4 problem(s) in {config}:
+                  - Vault `gone` points at {dir}/deleted-vault, which doesn't exist
+                  - `current_vault` refers to vault `work`, which isn't registered, see `obx vaults list`
+                  - Profile `writing` refers to vault `drafts`, which isn't registered, see `obx vaults list`
+                  - `editor` `definitely-not-an-editor` wasn't found, check it's installed and on your $PATH
+                ",
+                config = config_file.display()
+            });
+        }
+
+        #[test]
+        fn reports_invalid_ignore_patterns() {
+            let cmd = Obx::from_command("config validate");
+            let dir = cmd.temp_dir.display().to_string();
+            let cmd = cmd.with_config_file(&formatdoc! {"
+                current_vault: main
+                vaults:
+                - name: main
+                  path: {dir}/main-vault/
+                ignore:
+                - Archive/
+                - 'drafts/[a-'
+            "});
+            let config_file = cmd.temp_dir.child("./config/obx/config.yml");
+
+            cmd.assert_stderr(formatdoc! {"
+                Found 1 problem(s) in {config}:
+                  - `ignore` pattern `drafts/[a-` is invalid: error parsing glob 'drafts/[a-': unclosed character class; missing ']'
+                ",
+                config = config_file.display()
+            });
+        }
+
+        #[test]
+        fn reports_invalid_file_sizes() {
+            let cmd = Obx::from_command("config validate");
+            let dir = cmd.temp_dir.display().to_string();
+            let cmd = cmd.with_config_file(&formatdoc! {"
+                current_vault: main
+                vaults:
+                - name: main
+                  path: {dir}/main-vault/
+                scan:
+                  max_file_size: huge
+            "});
+            let config_file = cmd.temp_dir.child("./config/obx/config.yml");
+
+            cmd.assert_stderr(formatdoc! {"
+                Found 1 problem(s) in {config}:
+                  - `scan.max_file_size` is invalid: `huge` isn't a file size, use bytes or a size like `5MB`
+                ",
+                config = config_file.display()
+            });
+        }
+
+        #[test]
+        fn reports_unknown_locales() {
+            let cmd = Obx::from_command("config validate");
+            let dir = cmd.temp_dir.display().to_string();
+            let cmd = cmd.with_config_file(&formatdoc! {"
+                current_vault: main
+                vaults:
+                - name: main
+                  path: {dir}/main-vault/
+                dates:
+                  locale: klingon
+            "});
+            let config_file = cmd.temp_dir.child("./config/obx/config.yml");
+
+            cmd.assert_stderr(formatdoc! {"
+                Found 1 problem(s) in {config}:
+                  - Unknown locale `klingon` in `dates.locale`, expected one like `fr_FR`
+                ",
+                config = config_file.display()
+            });
+        }
+
+        #[test]
+        fn reports_schema_errors() {
+            let cmd = Obx::from_command("config validate").with_config_file(indoc! {"
+                current_vault: main
+                vaults: 3
+            "});
+            let config_file = cmd.temp_dir.child("./config/obx/config.yml");
+
+            cmd.assert_stderr(formatdoc! {"
+                Found 1 problem(s) in {config}:
+                  - Config file {config} is invalid: vaults: invalid type: integer `3`, expected a sequence at line 2 column 9
+                ",
+                config = config_file.display()
+            });
+        }
+    }
+
+    mod themes {
+        use super::*;
+        use assert_fs::prelude::FileWriteStr;
+        use indoc::formatdoc;
+
+        fn with_custom_theme(command: &str, accent: &str) -> Obx {
+            let cmd = Obx::from_command(command);
+            let dir = cmd.temp_dir.display().to_string();
+
+            cmd.with_config_file(&formatdoc! {"
+                current_vault: main
+                vaults:
+                - name: main
+                  path: {dir}/main-vault/
+                themes:
+                  dusk:
+                    base: gruvbox-dark
+                    accent: '{accent}'
+                    link: '#00aaff'
+            "})
+        }
+
+        #[test]
+        fn set_accepts_custom_theme() {
+            let mut cmd = with_custom_theme("config set --theme dusk", "#ff00aa");
+            let config_file = cmd.temp_dir.child("./config/obx/config.yml");
+
+            cmd.cmd.assert().success().stdout("Configuration updated\n");
+
+            let contents = fs::read_to_string(config_file.path()).unwrap();
+            let value: Value = serde_yaml::from_str(&contents).unwrap();
+
+            assert_eq!(value.get("theme").and_then(Value::as_str), Some("dusk"));
+            assert_eq!(value["themes"]["dusk"]["accent"].as_str(), Some("#ff00aa"));
+        }
+
+        #[test]
+        fn set_rejects_unknown_theme() {
+            with_custom_theme("config set --theme neon", "#ff00aa").assert_stderr(
+                "Unknown theme `neon`, expected one of `obsidian-dark`, `obsidian-light`, `solarized-dark`, `solarized-light`, `gruvbox-dark`, `gruvbox-light`, `terminal`, `dusk`\n",
+            );
+        }
+
+        #[test]
+        fn set_rejects_invalid_colours() {
+            with_custom_theme("config set --theme dusk", "pink").assert_stderr(
+                "Theme `dusk` has an invalid `accent` colour `pink`, expected `#rrggbb`\n",
+            );
+        }
+
+        #[test]
+        fn export_uses_custom_colours() {
+            let cmd = with_custom_theme("config set --theme dusk", "#ff00aa").assert_success();
+            let out = cmd.temp_dir.child("site");
+
+            let mut export_cmd =
+                Obx::from_command(&format!("export html simple-note --out {}", out.display()));
+            export_cmd.env(
+                "OBX_CONFIG_DIR",
+                cmd.temp_dir.child("./config/obx/").display().to_string(),
+            );
+            export_cmd.assert_success();
+
+            let page = fs::read_to_string(out.child("simple-note.html").path()).unwrap();
+            assert!(page.contains("a { color: #00aaff; }"), "{page}");
+            assert!(page.contains("border-left: 3px solid #ff00aa"), "{page}");
+        }
+
+        #[test]
+        fn set_accepts_terminal_theme() {
+            Obx::from_command("config set --theme terminal")
+                .assert_stdout("Configuration updated\n");
+        }
+
+        #[test]
+        fn export_uses_base16_scheme() {
+            let cmd = Obx::from_command("config set --theme ocean");
+            let dir = cmd.temp_dir.display().to_string();
+            cmd.temp_dir
+                .child("./config/obx/schemes/ocean.yaml")
+                .write_str(indoc::indoc! {r#"
+                    scheme: "Ocean"
+                    author: "Someone"
+                    base00: "2b303b"
+                    base01: "343d46"
+                    base02: "4f5b66"
+                    base03: "65737e"
+                    base04: "a7adba"
+                    base05: "c0c5ce"
+                    base06: "dfe1e8"
+                    base07: "eff1f5"
+                    base08: "bf616a"
+                    base09: "d08770"
+                    base0A: "ebcb8b"
+                    base0B: "a3be8c"
+                    base0C: "96b5b4"
+                    base0D: "8fa1b3"
+                    base0E: "b48ead"
+                    base0F: "ab7967"
+                "#})
+                .unwrap();
+            let cmd = cmd
+                .with_config_file(&formatdoc! {"
+                    current_vault: main
+                    vaults:
+                    - name: main
+                      path: {dir}/main-vault/
+                    themes:
+                      ocean:
+                        base16: schemes/ocean.yaml
+                        tag: '#ffffff'
+                "})
+                .assert_success();
+            let out = cmd.temp_dir.child("site");
+
+            let mut export_cmd =
+                Obx::from_command(&format!("export html simple-note --out {}", out.display()));
+            export_cmd.env(
+                "OBX_CONFIG_DIR",
+                cmd.temp_dir.child("./config/obx/").display().to_string(),
+            );
+            export_cmd.assert_success();
+
+            let page = fs::read_to_string(out.child("simple-note.html").path()).unwrap();
+            assert!(
+                page.contains("background: #2b303b; color: #c0c5ce;"),
+                "{page}"
+            );
+            assert!(
+                page.contains("color: #b48ead; line-height: 1.25;"),
+                "{page}"
+            );
+            assert!(page.contains("border: 1px solid #ffffff;"), "{page}");
+        }
+
+        #[test]
+        fn reports_missing_base16_colours() {
+            let cmd = Obx::from_command("config set --theme ocean");
+            let dir = cmd.temp_dir.display().to_string();
+            let scheme = cmd.temp_dir.child("./config/obx/ocean.yaml");
+            scheme.write_str("base00: \"2b303b\"\n").unwrap();
+
+            let cmd = cmd.with_config_file(&formatdoc! {"
+                current_vault: main
+                vaults:
+                - name: main
+                  path: {dir}/main-vault/
+                themes:
+                  ocean:
+                    base16: ocean.yaml
+            "});
+
+            cmd.assert_stderr(format!(
+                "Theme `ocean` has an invalid base16 scheme: {} has no `base0D` colour\n",
+                scheme.display()
+            ));
+        }
+    }
+
+    mod env_overrides {
+        use super::*;
+        use indoc::formatdoc;
+
+        fn with_vault_settings(command: &str) -> Obx {
+            let cmd = Obx::from_command(command);
+            let dir = cmd.temp_dir.display().to_string();
+
+            cmd.with_config_file(&formatdoc! {"
+                current_vault: main
+                vaults:
+                - name: main
+                  path: {dir}/main-vault/
+                  theme: gruvbox-dark
+                - name: secondary
+                  path: {dir}/another/path
+                editor: config-editor
+                theme: obsidian-light
+            "})
+        }
+
+        fn resolved_value(cmd: &mut Obx, key: &str) -> Option<String> {
+            let output = cmd.cmd.output().unwrap();
+            let value: Value = serde_yaml::from_slice(&output.stdout).unwrap();
+            value.get(key).and_then(Value::as_str).map(String::from)
+        }
+
+        #[test]
+        fn current_vault_settings_override_config() {
+            let mut cmd = with_vault_settings("config print --resolved");
+            assert_eq!(
+                resolved_value(&mut cmd, "theme").as_deref(),
+                Some("gruvbox-dark")
+            );
+        }
+
+        #[test]
+        fn env_overrides_vault_settings() {
+            let mut cmd = with_vault_settings("config print --resolved");
+            cmd.env("OBX_THEME", "terminal")
+                .env("OBX_EDITOR", "env-editor");
+
+            let output = cmd.cmd.output().unwrap();
+            let value: Value = serde_yaml::from_slice(&output.stdout).unwrap();
+            assert_eq!(value["theme"].as_str(), Some("terminal"));
+            assert_eq!(value["editor"].as_str(), Some("env-editor"));
+        }
+
+        #[test]
+        fn env_selects_vault() {
+            let mut cmd = with_vault_settings("vaults current");
+            cmd.env("OBX_VAULT", "secondary");
+
+            cmd.assert_stdout_contains("Current vault is `secondary`");
+        }
+
+        #[test]
+        fn obsidian_vault_env_selects_vault() {
+            let mut cmd = with_vault_settings("vaults current");
+            cmd.env("OBSIDIAN_VAULT", "secondary");
+
+            cmd.assert_stdout_contains("Current vault is `secondary`");
+        }
+
+        #[test]
+        fn working_directory_selects_vault() {
+            let mut cmd = with_vault_settings("vaults current");
+            let inside = cmd.temp_dir.child("another/path");
+            cmd.cmd.current_dir(inside.path());
+
+            cmd.assert_stdout_contains("Current vault is `secondary`");
+        }
+
+        #[test]
+        fn env_beats_working_directory() {
+            let mut cmd = with_vault_settings("vaults current");
+            let inside = cmd.temp_dir.child("another/path");
+            cmd.cmd.current_dir(inside.path());
+            cmd.env("OBX_VAULT", "main");
+
+            cmd.assert_stdout_contains("Current vault is `main`");
+        }
+
+        #[test]
+        fn default_vault_fills_empty_current_vault() {
+            let cmd = Obx::from_command("vaults current");
+            let dir = cmd.temp_dir.display().to_string();
+
+            cmd.with_config_file(&formatdoc! {"
+                current_vault: ''
+                default_vault: secondary
+                vaults:
+                - name: main
+                  path: {dir}/main-vault/
+                - name: secondary
+                  path: {dir}/another/path
+            "})
+                .assert_stdout_contains("Current vault is `secondary`");
+        }
+
+        #[test]
+        fn vault_flag_beats_env() {
+            let mut cmd = with_vault_settings("cat simple-note --vault main");
+            cmd.env("OBX_VAULT", "secondary");
+
+            cmd.assert_stdout_contains("# Simple note");
+        }
+
+        #[test]
+        fn env_editor_opens_notes() {
+            let mut cmd = with_vault_settings("open simple-note");
+            let script = create_script(
+                "env_editor.sh",
+                r#"echo "opened by OBX_EDITOR" >> "$1""#,
+                &cmd.temp_dir,
+            );
+            cmd.env("OBX_EDITOR", script.path());
+
+            cmd.assert_content(
+                "main-vault/simple-note.md",
+                "# Simple note\n\nThis is the contents of simple-note.mdopened by OBX_EDITOR\n",
+            );
+        }
+
+        #[test]
+        fn write_leaves_overrides_out_of_config() {
+            let mut cmd = with_vault_settings("config set --editor nvim");
+            cmd.env("OBX_THEME", "terminal")
+                .env("OBX_VAULT", "secondary");
+            let config_file = cmd.temp_dir.child("./config/obx/config.yml");
+
+            cmd.cmd.assert().success().stdout("Configuration updated\n");
+
+            let contents = fs::read_to_string(config_file.path()).unwrap();
+            let value: Value = serde_yaml::from_str(&contents).unwrap();
+
+            assert_eq!(value["editor"].as_str(), Some("nvim"));
+            assert_eq!(value["theme"].as_str(), Some("obsidian-light"));
+            assert_eq!(value["current_vault"].as_str(), Some("main"));
+        }
+    }
+
+    mod secrets {
+        use super::*;
+        use indoc::formatdoc;
+
+        /// Run `obx config secret ...` against the config of `base`
+        fn secret_command(args: &str, base: &Obx) -> assert_cmd::Command {
+            let mut cmd = assert_cmd::Command::cargo_bin("obx").unwrap();
+            cmd.current_dir(&base.temp_dir)
+                .env(
+                    "OBX_CONFIG_DIR",
+                    base.temp_dir.child("./config/obx/").path(),
+                )
+                .env("OBX_SECRET_PASSPHRASE", "correct horse")
+                .args(["config", "secret"])
+                .args(args.split(' '));
+            cmd
+        }
+
+        #[test]
+        fn stores_encrypted_secrets() {
+            let cmd = Obx::from_command("config path");
+
+            let mut set = secret_command("set github-token --encrypt", &cmd);
+            set.write_stdin("ghp_abc123\n")
+                .assert()
+                .success()
+                .stdout("Saved `github-token` encrypted in the config\n");
+
+            let contents =
+                fs::read_to_string(cmd.temp_dir.child("./config/obx/config.yml").path()).unwrap();
+            assert!(!contents.contains("ghp_abc123"));
+            let value: Value = serde_yaml::from_str(&contents).unwrap();
+            assert!(value["secrets"]["github-token"]
+                .as_str()
+                .unwrap()
+                .starts_with("encrypted:"));
+
+            secret_command("get github-token", &cmd)
+                .assert()
+                .success()
+                .stdout("ghp_abc123\n");
+        }
+
+        #[test]
+        fn rejects_wrong_passphrase() {
+            let cmd = Obx::from_command("config path");
+            secret_command("set github-token --encrypt", &cmd)
+                .write_stdin("ghp_abc123")
+                .assert()
+                .success();
+
+            secret_command("get github-token", &cmd)
+                .env("OBX_SECRET_PASSPHRASE", "wrong")
+                .assert()
+                .failure()
+                .stderr("Could not decrypt the secret, check the passphrase\n");
+        }
+
+        #[test]
+        fn removes_secrets() {
+            let cmd = Obx::from_command("config path");
+            secret_command("set github-token --encrypt", &cmd)
+                .write_stdin("ghp_abc123")
+                .assert()
+                .success();
+
+            secret_command("rm github-token", &cmd)
+                .assert()
+                .success()
+                .stdout("Removed secret `github-token`\n");
+            secret_command("get github-token", &cmd)
+                .assert()
+                .failure()
+                .stderr("No secret named `github-token`, add it with `obx config secret set github-token`\n");
+        }
+
+        #[test]
+        fn validate_reports_plain_text_secrets() {
+            let cmd = Obx::from_command("config validate");
+            let dir = cmd.temp_dir.display().to_string();
+            let cmd = cmd.with_config_file(&formatdoc! {"
+                current_vault: main
+                vaults:
+                - name: main
+                  path: {dir}/main-vault/
+                secrets:
+                  github-token: ghp_abc123
+            "});
+            let config_file = cmd.temp_dir.child("./config/obx/config.yml");
+
+            cmd.assert_stderr(formatdoc! {"
+                Found 1 problem(s) in {config}:
+                  - Secret `github-token` is stored in plain text, move it with `obx config secret set github-token`
+                ",
+                config = config_file.display()
+            });
+        }
+    }
 }
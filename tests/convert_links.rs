@@ -0,0 +1,63 @@
+use assert_fs::prelude::*;
+use indoc::indoc;
+mod utils;
+use utils::*;
+
+mod convert_links {
+    use super::*;
+
+    #[test]
+    fn converts_wikilinks_to_markdown() {
+        Obx::from_command("convert-links --to markdown complex-note")
+            .assert_stdout("Converted 1 link in complex-note.md\n")
+            .temp_dir
+            .child("main-vault/complex-note.md")
+            .assert(predicates::str::ends_with(
+                "and a [link to a note](simple-note.md)",
+            ));
+    }
+
+    #[test]
+    fn writes_paths_relative_to_the_note() {
+        let cmd = Obx::from_command("convert-links --to markdown folder");
+        cmd.temp_dir
+            .child("main-vault/folder/child-note.md")
+            .write_str("See [[simple-note#Heading]] and [[#Own heading]]")
+            .unwrap();
+
+        cmd.assert_stdout("Converted 2 links in folder/child-note.md\n")
+            .assert_content(
+                "main-vault/folder/child-note.md",
+                "See [simple-note > Heading](../simple-note.md#Heading) and [Own heading](#Own%20heading)",
+            );
+    }
+
+    #[test]
+    fn converts_markdown_links_to_wikilinks() {
+        let cmd = Obx::from_command("convert-links --to wikilink folder/child-note");
+        cmd.temp_dir
+            .child("main-vault/folder/child-note.md")
+            .write_str("[simple-note](../simple-note.md) and [a note](../simple-note.md)")
+            .unwrap();
+
+        cmd.assert_stdout("Converted 2 links in folder/child-note.md\n")
+            .assert_content(
+                "main-vault/folder/child-note.md",
+                "[[simple-note]] and [[simple-note|a note]]",
+            );
+    }
+
+    #[test]
+    fn dry_run_leaves_notes_alone() {
+        Obx::from_command("convert-links --to markdown --all --dry-run")
+            .assert_stdout(indoc! {"
+                Would convert 1 link in complex-note.md
+                Left 2 links to missing files as they were
+            "})
+            .temp_dir
+            .child("main-vault/complex-note.md")
+            .assert(predicates::str::ends_with(
+                "[[simple-note |link to a note]]",
+            ));
+    }
+}
@@ -0,0 +1,114 @@
+#![cfg(unix)]
+
+use assert_fs::prelude::*;
+use std::{
+    io::{BufRead, BufReader, Write},
+    os::unix::net::UnixStream,
+    process::{Child, Stdio},
+    thread,
+    time::Duration,
+};
+mod utils;
+use utils::*;
+
+/// `obx daemon` for the main vault, with the socket it's listening on
+fn daemon() -> (Obx, Child, String) {
+    let mut cmd = Obx::from_command("daemon");
+    let mut daemon = cmd
+        .cmd
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut started = String::new();
+    BufReader::new(daemon.stderr.take().unwrap())
+        .read_line(&mut started)
+        .unwrap();
+    let socket = started
+        .split(" at ")
+        .nth(1)
+        .and_then(|rest| rest.rsplit_once(", press"))
+        .map(|(socket, _)| socket.to_string())
+        .unwrap_or_else(|| panic!("unexpected start: {started}"));
+    (cmd, daemon, socket)
+}
+
+/// Send requests over one connection, returning each answer
+fn ask(socket: &str, requests: &[serde_json::Value]) -> Vec<serde_json::Value> {
+    let mut stream = UnixStream::connect(socket).unwrap();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    requests
+        .iter()
+        .map(|request| {
+            writeln!(stream, "{request}").unwrap();
+            let mut answer = String::new();
+            reader.read_line(&mut answer).unwrap();
+            serde_json::from_str(&answer).unwrap()
+        })
+        .collect()
+}
+
+mod daemon {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn answers_previews_and_searches() {
+        let (cmd, mut daemon, socket) = daemon();
+
+        let answers = ask(
+            &socket,
+            &[
+                json!({ "preview": "simple-note.md" }),
+                json!({ "search": "contents simple" }),
+                json!({ "preview": "../outside.md" }),
+            ],
+        );
+        let text = "# Simple note\n\nThis is the contents of simple-note.md";
+        assert_eq!(
+            answers[0],
+            json!({ "preview": { "text": text, "loaded": 53, "size": 53 } })
+        );
+        assert_eq!(answers[1]["search"][0]["path"], "simple-note.md");
+        assert_eq!(
+            answers[2],
+            json!({ "error": "Invalid note path `../outside.md`, expected one within the vault" })
+        );
+
+        // Later searches see notes as they change
+        cmd.temp_dir
+            .child("main-vault/fresh.md")
+            .write_str("Something unheard of")
+            .unwrap();
+        let mut found = json!([]);
+        for _ in 0..50 {
+            found = ask(&socket, &[json!({ "search": "unheard" })]).remove(0);
+            if found["search"][0]["path"] == "fresh.md" {
+                break;
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+        assert_eq!(found["search"][0]["path"], "fresh.md");
+
+        daemon.kill().unwrap();
+        daemon.wait().unwrap();
+    }
+
+    #[test]
+    fn runs_once_per_vault() {
+        let (cmd, mut daemon, _socket) = daemon();
+
+        let mut second = Obx::from_command("daemon");
+        second
+            .cmd
+            .env("OBX_STATE_DIR", cmd.temp_dir.child("state/obx/").path());
+        second
+            .cmd
+            .env("OBX_CONFIG_DIR", cmd.temp_dir.child("config/obx/").path());
+        second.assert_stderr("A daemon is already running for `main`\n");
+
+        daemon.kill().unwrap();
+        daemon.wait().unwrap();
+    }
+}
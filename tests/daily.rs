@@ -0,0 +1,107 @@
+use assert_fs::prelude::*;
+use indoc::{formatdoc, indoc};
+mod utils;
+use utils::*;
+
+fn with_periodic_settings(command: &str, settings: &str) -> Obx {
+    let cmd = Obx::from_command(command);
+    let dir = cmd.temp_dir.display().to_string();
+    cmd.with_config_file(&formatdoc! {"
+        current_vault: main
+        vaults:
+        - name: main
+          path: {dir}/main-vault
+        periodic:
+        {settings}"})
+}
+
+mod daily {
+    use super::*;
+
+    #[test]
+    fn creates_the_daily_note() {
+        let cmd = Obx::from_command("daily --date 2024-05-10")
+            .assert_stdout("Created note 2024-05-10.md\n");
+
+        cmd.temp_dir.child("main-vault/2024-05-10.md").assert("");
+    }
+
+    #[test]
+    fn prints_the_path_of_an_existing_note() {
+        let cmd = Obx::from_command("daily --date 2024-05-10");
+        cmd.temp_dir
+            .child("main-vault/2024-05-10.md")
+            .write_str("Already written\n")
+            .unwrap();
+
+        let cmd = cmd.assert_stdout("2024-05-10.md\n");
+        cmd.temp_dir
+            .child("main-vault/2024-05-10.md")
+            .assert("Already written\n");
+    }
+
+    #[test]
+    fn links_neighbors_of_new_notes_from_the_config() {
+        let cmd = with_periodic_settings(
+            "daily --date 2024-05-10",
+            "  folder: journal\n  link_neighbors: true\n",
+        )
+        .assert_stdout("Created note journal/2024-05-10.md\n");
+
+        cmd.temp_dir
+            .child("main-vault/journal/2024-05-10.md")
+            .assert("← [[2024-05-09]] | [[2024-05-11]] →\n");
+    }
+
+    #[test]
+    fn updates_the_links_of_existing_notes() {
+        let cmd = Obx::from_command("daily --weekly --date 2024-05-10 --link-neighbors");
+        cmd.temp_dir
+            .child("main-vault/2024-W19.md")
+            .write_str(indoc! {"
+                ← [[2024-W17]] | [[2024-W19]] →
+
+                # Week 19
+            "})
+            .unwrap();
+
+        let cmd = cmd.assert_stdout("2024-W19.md\n");
+        cmd.temp_dir
+            .child("main-vault/2024-W19.md")
+            .assert(indoc! {"
+            ← [[2024-W18]] | [[2024-W20]] →
+
+            # Week 19
+        "});
+    }
+
+    #[test]
+    fn creates_notes_from_templates() {
+        let cmd = with_periodic_settings("daily --date 2024-05-10 -l", "  daily_template: daily\n");
+        cmd.temp_dir
+            .child("main-vault/templates/daily.md")
+            .write_str("---\ntags: [journal]\n---\n# {{date:dddd D MMMM}}\n")
+            .unwrap();
+
+        let cmd = cmd.assert_stdout("Created note 2024-05-10.md\n");
+        cmd.temp_dir
+            .child("main-vault/2024-05-10.md")
+            .assert(indoc! {"
+            ---
+            tags: [journal]
+            ---
+            ← [[2024-05-09]] | [[2024-05-11]] →
+
+            # Friday 10 May
+        "});
+    }
+
+    #[test]
+    fn rejects_invalid_dates() {
+        Obx::from_command("daily --date 10/05/2024").assert_stderr(indoc! {"
+            error: invalid value '10/05/2024' for '--date <YYYY-MM-DD>': invalid date `10/05/2024`, expected one like 2024-05-10
+
+            For more information, try '--help'.
+        "});
+    }
+}
@@ -0,0 +1,74 @@
+use assert_fs::prelude::*;
+use indoc::indoc;
+mod utils;
+use utils::*;
+
+mod dedupe {
+    use super::*;
+
+    const MEETING: &str = "Notes from the weekly planning meeting about the budget and hiring";
+
+    #[test]
+    fn prints_nothing_without_duplicates() {
+        Obx::from_command("dedupe").assert_stdout("");
+    }
+
+    #[test]
+    fn groups_identical_notes() {
+        let cmd = Obx::from_command("dedupe");
+        for name in ["meeting.md", "folder/meeting copy.md"] {
+            cmd.temp_dir
+                .child("main-vault")
+                .child(name)
+                .write_str(MEETING)
+                .unwrap();
+        }
+
+        cmd.assert_stdout(indoc! {"
+            Identical notes, 66 bytes each:
+              folder/meeting copy.md
+              meeting.md
+        "});
+    }
+
+    #[test]
+    fn groups_similar_notes_with_fuzzy() {
+        let cmd = Obx::from_command("dedupe --fuzzy --threshold 0.7");
+        cmd.temp_dir
+            .child("main-vault/meeting.md")
+            .write_str(MEETING)
+            .unwrap();
+        cmd.temp_dir
+            .child("main-vault/meeting-edited.md")
+            .write_str(&format!("{MEETING} plans"))
+            .unwrap();
+
+        cmd.assert_stdout(indoc! {"
+            Similar notes, 90% alike:
+              meeting-edited.md (72 bytes)
+              meeting.md (66 bytes)
+        "});
+    }
+
+    #[test]
+    fn prints_json() {
+        let cmd = Obx::from_command("dedupe -f json");
+        for name in ["a.md", "b.md"] {
+            cmd.temp_dir
+                .child("main-vault")
+                .child(name)
+                .write_str(MEETING)
+                .unwrap();
+        }
+
+        cmd.assert_stdout(
+            "[{\"identical\":true,\"similarity\":1.0,\"notes\":[{\"path\":\"a.md\",\"size\":66},{\"path\":\"b.md\",\"size\":66}]}]\n",
+        );
+    }
+
+    #[test]
+    fn needs_a_terminal_to_resolve_duplicates() {
+        Obx::from_command("dedupe --interactive")
+            .assert_stderr("--interactive needs an interactive terminal\n");
+    }
+}
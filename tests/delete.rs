@@ -0,0 +1,83 @@
+use assert_fs::prelude::*;
+use indoc::indoc;
+use predicates::prelude::*;
+mod utils;
+use utils::*;
+
+fn with_backlinks(command: &str) -> Obx {
+    let cmd = Obx::from_command(command);
+    cmd.temp_dir
+        .child("main-vault/folder/linking.md")
+        .write_str("[md](../simple-note.md)")
+        .unwrap();
+
+    cmd
+}
+
+mod delete {
+    use super::*;
+
+    #[test]
+    fn deletes_with_force() {
+        let cmd = with_backlinks("delete simple-note --force").assert_stdout(indoc! {"
+            2 notes link to simple-note.md
+            Deleted simple-note.md
+        "});
+
+        cmd.temp_dir
+            .child("main-vault/simple-note.md")
+            .assert(predicate::path::missing());
+        cmd.temp_dir
+            .child("main-vault/.trash/simple-note.md")
+            .assert(predicate::path::missing());
+    }
+
+    #[test]
+    fn lists_backlinks() {
+        let _ = &with_backlinks("delete simple-note --force -l").assert_stdout(indoc! {"
+            2 notes link to simple-note.md
+              complex-note.md
+              folder/linking.md
+            Deleted simple-note.md
+        "});
+    }
+
+    #[test]
+    fn moves_to_trash() {
+        let cmd = Obx::from_command("delete table --force --trash").assert_stdout(indoc! {"
+            No notes link to table.md
+            Moved table.md to the trash
+        "});
+
+        cmd.temp_dir
+            .child("main-vault/.trash/table.md")
+            .assert(predicate::path::exists());
+    }
+
+    #[test]
+    fn requires_confirmation_when_not_interactive() {
+        let cmd = with_backlinks("delete simple-note").assert_stderr(indoc! {"
+            2 notes link to simple-note.md
+            Refusing to delete simple-note.md without confirmation, pass --force
+        "});
+
+        cmd.temp_dir
+            .child("main-vault/simple-note.md")
+            .assert(predicate::path::exists());
+    }
+
+    #[test]
+    fn deletes_after_confirmation() {
+        let cmd = Obx::from_command("delete table");
+        let mut p = cmd.spawn_interactive(Some(5_000)).unwrap();
+
+        p.exp_string("No notes link to table.md").unwrap();
+        p.exp_string("Permanently delete table.md? [y/n]").unwrap();
+        p.send_line("y").unwrap();
+        p.exp_string("Deleted table.md").unwrap();
+
+        cmd.temp_dir
+            .child("main-vault/table.md")
+            .assert(predicate::path::missing());
+    }
+}
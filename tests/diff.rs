@@ -0,0 +1,118 @@
+use assert_fs::prelude::*;
+use indoc::indoc;
+use std::process::Command;
+mod utils;
+use utils::*;
+
+mod diff {
+    use super::*;
+
+    fn git(cmd: &Obx, args: &[&str]) {
+        let status = Command::new("git")
+            .current_dir(cmd.temp_dir.child("main-vault").path())
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {args:?} failed");
+    }
+
+    fn write_note(cmd: &Obx, name: &str, contents: &str) {
+        cmd.temp_dir
+            .child("main-vault")
+            .child(name)
+            .write_str(contents)
+            .unwrap();
+    }
+
+    #[test]
+    fn diffs_two_notes() {
+        let cmd = Obx::from_command("diff before after");
+        write_note(&cmd, "before.md", "one\ntwo\nthree\n");
+        write_note(&cmd, "after.md", "one\n2\nthree\nfour\n");
+
+        cmd.assert_stdout(indoc! {"
+            --- before.md
+            +++ after.md
+            @@ -1,3 +1,4 @@
+             one
+            -two
+            +2
+             three
+            +four
+        "});
+    }
+
+    #[test]
+    fn prints_nothing_for_identical_notes() {
+        let cmd = Obx::from_command("diff before after");
+        write_note(&cmd, "before.md", "same\n");
+        write_note(&cmd, "after.md", "same\n");
+
+        cmd.assert_stdout("");
+    }
+
+    #[test]
+    fn colors_when_asked() {
+        let cmd = Obx::from_command("diff before after --color always");
+        write_note(&cmd, "before.md", "one\n");
+        write_note(&cmd, "after.md", "two\n");
+
+        cmd.assert_stdout_contains("\u{1b}[38;5;9m-one\u{1b}[39m\n\u{1b}[38;5;10m+two\u{1b}[39m");
+    }
+
+    #[test]
+    fn diffs_against_last_commit() {
+        let cmd = Obx::from_command("diff simple-note --git");
+        git(&cmd, &["init", "--quiet"]);
+        git(&cmd, &["add", "."]);
+        git(&cmd, &["commit", "--quiet", "-m", "Initial"]);
+        write_note(
+            &cmd,
+            "simple-note.md",
+            "# Simple note\n\nThis is the new contents of simple-note.md",
+        );
+
+        cmd.assert_stdout(concat!(
+            "--- simple-note.md (HEAD)\n",
+            "+++ simple-note.md\n",
+            "@@ -1,3 +1,3 @@\n",
+            " # Simple note\n",
+            " \n",
+            "-This is the contents of simple-note.md\n",
+            "+This is the new contents of simple-note.md\n",
+        ));
+    }
+
+    #[test]
+    fn diffs_against_older_revision() {
+        let cmd = Obx::from_command("diff folder/note-in-folder --git HEAD~1");
+        write_note(&cmd, "folder/note-in-folder.md", "first\n");
+        git(&cmd, &["init", "--quiet"]);
+        git(&cmd, &["add", "."]);
+        git(&cmd, &["commit", "--quiet", "-m", "First"]);
+        write_note(&cmd, "folder/note-in-folder.md", "second\n");
+        git(&cmd, &["commit", "--quiet", "-am", "Second"]);
+
+        cmd.assert_stdout(indoc! {"
+            --- folder/note-in-folder.md (HEAD~1)
+            +++ folder/note-in-folder.md
+            @@ -1 +1 @@
+            -first
+            +second
+        "});
+    }
+
+    #[test]
+    fn errors_outside_a_repository() {
+        Obx::from_command("diff simple-note --git")
+            .assert_stderr("simple-note.md is not inside a git repository\n");
+    }
+
+    #[test]
+    fn requires_another_note_or_git() {
+        let mut cmd = Obx::from_command("diff simple-note");
+        assert!(!cmd.cmd.output().unwrap().status.success());
+    }
+}
@@ -0,0 +1,71 @@
+use assert_fs::prelude::*;
+use indoc::indoc;
+use predicates::prelude::*;
+mod utils;
+use utils::*;
+
+fn with_expiring_notes(command: &str) -> Obx {
+    let cmd = Obx::from_command(command);
+    cmd.temp_dir
+        .child("main-vault/clipping.md")
+        .write_str("---\nexpires: 2020-01-31\n---\nA temporary clipping")
+        .unwrap();
+    cmd.temp_dir
+        .child("main-vault/folder/scratch.md")
+        .write_str("---\nexpires: 2021-06-01T10:00:00\n---\nMeeting scratch")
+        .unwrap();
+    cmd.temp_dir
+        .child("main-vault/keep.md")
+        .write_str("---\nexpires: 2999-01-01\n---\nStill needed")
+        .unwrap();
+
+    cmd
+}
+
+mod expire {
+    use super::*;
+
+    #[test]
+    fn lists_expired_notes() {
+        let _ = &with_expiring_notes("expire").assert_stdout(indoc! {"
+            ┌───────────────────┬────────────┐
+            │ Note              │ Expired    │
+            ├───────────────────┼────────────┤
+            │ clipping.md       │ 2020-01-31 │
+            │ folder/scratch.md │ 2021-06-01 │
+            └───────────────────┴────────────┘
+        "});
+    }
+
+    #[test]
+    fn trashes_expired_notes() {
+        let cmd = with_expiring_notes("expire --trash --yes").assert_stdout(indoc! {"
+            Trashed clipping.md
+            Trashed folder/scratch.md
+        "});
+
+        cmd.temp_dir
+            .child("main-vault/clipping.md")
+            .assert(predicate::path::missing());
+        cmd.temp_dir
+            .child("main-vault/.trash/clipping.md")
+            .assert(predicate::path::exists());
+        cmd.temp_dir
+            .child("main-vault/.trash/scratch.md")
+            .assert(predicate::path::exists());
+        cmd.temp_dir
+            .child("main-vault/keep.md")
+            .assert(predicate::path::exists());
+    }
+
+    #[test]
+    fn requires_confirmation_when_not_interactive() {
+        let _ = &with_expiring_notes("expire --trash")
+            .assert_stderr("Refusing to trash 2 expired notes without confirmation, pass --yes\n");
+    }
+
+    #[test]
+    fn prints_nothing_without_expired_notes() {
+        Obx::from_command("expire").assert_stdout("");
+    }
+}
@@ -0,0 +1,284 @@
+use assert_fs::prelude::*;
+use indoc::{formatdoc, indoc};
+use predicates::prelude::*;
+mod utils;
+use utils::*;
+
+const DRAWING: &str = indoc! {r#"
+    ---
+    excalidraw-plugin: parsed
+    ---
+    # Text Elements
+    Whiteboard label ^abc123
+
+    %%
+    # Drawing
+    ```json
+    {"type":"excalidraw","elements":[{"id":"a","text":"payload"}]}
+    ```
+    %%
+"#};
+
+fn with_converter(cmd: Obx, converter: &str) -> Obx {
+    let dir = cmd.temp_dir.display().to_string();
+
+    cmd.with_config_file(&formatdoc! {"
+        current_vault: main
+        vaults:
+        - name: main
+          path: {dir}/main-vault/
+        excalidraw_converter: {converter}
+    "})
+}
+
+mod export {
+    use super::*;
+
+    mod excalidraw {
+        use super::*;
+
+        #[test]
+        fn runs_the_converter() {
+            let cmd = with_converter(
+                Obx::from_command("export excalidraw sketch"),
+                "cp {input} {output}",
+            );
+            cmd.temp_dir
+                .child("main-vault/sketch.excalidraw.md")
+                .write_str(DRAWING)
+                .unwrap();
+
+            let cmd = cmd.assert_stdout("Exported sketch.svg\n");
+
+            cmd.temp_dir
+                .child("sketch.svg")
+                .assert(predicate::str::contains(r#""text":"payload""#));
+        }
+
+        #[test]
+        fn writes_png_to_out_path() {
+            let cmd = with_converter(
+                Obx::from_command("export excalidraw sketch --png --out drawing.png"),
+                "cp {input} {output}",
+            );
+            cmd.temp_dir
+                .child("main-vault/sketch.excalidraw.md")
+                .write_str(DRAWING)
+                .unwrap();
+
+            let cmd = cmd.assert_stdout("Exported drawing.png\n");
+
+            cmd.temp_dir
+                .child("drawing.png")
+                .assert(predicate::path::exists());
+        }
+
+        #[test]
+        fn errors_without_converter() {
+            let cmd = Obx::from_command("export excalidraw sketch");
+            cmd.temp_dir
+                .child("main-vault/sketch.excalidraw.md")
+                .write_str(DRAWING)
+                .unwrap();
+
+            let _ = &cmd.assert_stderr(
+                "No Excalidraw converter configured, set `excalidraw_converter` in your config\n",
+            );
+        }
+
+        #[test]
+        fn errors_on_compressed_drawing() {
+            let cmd = with_converter(
+                Obx::from_command("export excalidraw sketch"),
+                "cp {input} {output}",
+            );
+            cmd.temp_dir
+                .child("main-vault/sketch.excalidraw.md")
+                .write_str("# Text Elements\n%%\n# Drawing\n```compressed-json\nN4Ig\n```\n%%\n")
+                .unwrap();
+
+            let _ = &cmd.assert_stderr(
+                "Drawing is compressed, turn off compression in the Excalidraw plugin settings to export it\n",
+            );
+        }
+
+        #[test]
+        fn errors_on_regular_note() {
+            with_converter(
+                Obx::from_command("export excalidraw simple-note"),
+                "cp {input} {output}",
+            )
+            .assert_stderr("`simple-note` is not an Excalidraw drawing\n");
+        }
+    }
+
+    mod html {
+        use super::*;
+
+        #[test]
+        fn renders_a_note() {
+            let cmd = Obx::from_command("export html simple-note --out site")
+                .assert_stdout("Exported 1 note(s) and 0 attachment(s) to site\n");
+
+            cmd.temp_dir
+                .child("site/simple-note.html")
+                .assert(predicate::str::contains("<title>simple-note</title>"))
+                .assert(predicate::str::contains(
+                    "<h1 id=\"simple-note\">Simple note</h1>\n<p>This is the contents of simple-note.md</p>",
+                ));
+        }
+
+        #[test]
+        fn links_between_exported_notes() {
+            let cmd = Obx::from_command("export html folder --out site");
+            cmd.temp_dir
+                .child("main-vault/folder/other.md")
+                .write_str("See [[child-note#Some Heading|the child]], [[simple-note]] and ![[diagram.png]]")
+                .unwrap();
+            cmd.temp_dir
+                .child("main-vault/attachments/diagram.png")
+                .write_str("png")
+                .unwrap();
+
+            let cmd = cmd.assert_stdout("Exported 2 note(s) and 1 attachment(s) to site\n");
+
+            cmd.temp_dir
+                .child("site/folder/other.html")
+                .assert(predicate::str::contains(concat!(
+                    "See <a href=\"child-note.html#some-heading\">the child</a>, simple-note and ",
+                    "<img src=\"../attachments/diagram.png\" alt=\"diagram.png\" />"
+                )));
+            cmd.temp_dir
+                .child("site/folder/child-note.html")
+                .assert(predicate::path::exists());
+            cmd.temp_dir
+                .child("site/attachments/diagram.png")
+                .assert("png");
+        }
+
+        #[test]
+        fn expands_embedded_notes() {
+            let cmd = Obx::from_command("export html embedding --out site");
+            cmd.temp_dir
+                .child("main-vault/embedding.md")
+                .write_str("![[embedded#Part]]")
+                .unwrap();
+            cmd.temp_dir
+                .child("main-vault/embedded.md")
+                .write_str("# Part\nShown\n# Other\nNot shown")
+                .unwrap();
+
+            let cmd = cmd.assert_stdout("Exported 1 note(s) and 0 attachment(s) to site\n");
+
+            cmd.temp_dir
+                .child("site/embedding.html")
+                .assert(predicate::str::contains(
+                    "<main>\n<h1 id=\"part\">Part</h1>\n<p>Shown</p>\n</main>",
+                ));
+        }
+
+        #[test]
+        fn styles_pages_with_the_theme() {
+            let cmd = Obx::from_command("export html simple-note --out site").assert_success();
+
+            cmd.temp_dir
+                .child("site/simple-note.html")
+                .assert(predicate::str::contains("background: #242526;"));
+        }
+    }
+
+    mod pdf {
+        use super::*;
+
+        /// Configure a fake pandoc that records its arguments and input
+        fn with_mock_pandoc(command: &str) -> Obx {
+            let cmd = Obx::from_command(command);
+            let dir = cmd.temp_dir.display().to_string();
+            create_script(
+                "pandoc.sh",
+                r#"printf '%s\n' "$@" > args.txt; cat > input.md"#,
+                &cmd.temp_dir,
+            );
+
+            cmd.with_config_file(&formatdoc! {"
+                current_vault: main
+                vaults:
+                - name: main
+                  path: {dir}/main-vault/
+                pandoc: {dir}/pandoc.sh
+            "})
+        }
+
+        #[test]
+        fn converts_links_and_embeds() {
+            let cmd = with_mock_pandoc("export pdf report --margin 2cm");
+            cmd.temp_dir
+                .child("main-vault/report.md")
+                .write_str(indoc! {"
+                    ---
+                    title: Quarterly report
+                    ---
+                    See [[complex-note|the details]] and ![[chart.png]]
+
+                    ![[simple-note]]
+                "})
+                .unwrap();
+            cmd.temp_dir
+                .child("main-vault/chart.png")
+                .write_str("png")
+                .unwrap();
+
+            let cmd = cmd.assert_stdout("Exported report.pdf\n");
+
+            cmd.temp_dir.child("input.md").assert(indoc! {"
+                See the details and ![chart.png](chart.png)
+
+                # Simple note
+
+                This is the contents of simple-note.md
+            "});
+            cmd.temp_dir
+                .child("args.txt")
+                .assert(predicate::str::contains("title=Quarterly report\n"))
+                .assert(predicate::str::contains("geometry:margin=2cm\n"));
+        }
+
+        #[test]
+        fn titles_from_chosen_property() {
+            let cmd = with_mock_pandoc(
+                "export pdf with-fm-properties --title-from test-str --out out.pdf",
+            )
+            .assert_stdout("Exported out.pdf\n");
+
+            cmd.temp_dir
+                .child("args.txt")
+                .assert(predicate::str::contains("--output\nout.pdf\n"))
+                .assert(predicate::str::contains("title=a string val\n"));
+        }
+    }
+
+    mod json {
+        use super::*;
+
+        #[test]
+        fn describes_each_note() {
+            Obx::from_command("export json").assert_stdout_contains(concat!(
+                r#"{"path":"complex-note.md","title":"complex-note","#,
+                r#""frontmatter":{"some-property":"a string val"},"tags":[],"links":["#,
+                r#"{"target":"https://example.com","subpath":null,"resolved":null,"embed":false,"line":10},"#,
+                r#"{"target":"simple-note","subpath":null,"resolved":"simple-note.md","embed":false,"line":10}],"#,
+                r#""headings":[{"level":1,"text":"Rich note","line":1}],"word_count":32}"#,
+            ));
+        }
+
+        #[test]
+        fn writes_to_file() {
+            let cmd = Obx::from_command("export json --out vault.json")
+                .assert_stdout("Exported 8 note(s) to vault.json\n");
+
+            cmd.temp_dir
+                .child("vault.json")
+                .assert(predicate::str::starts_with(r#"{"vault":"main","notes":["#));
+        }
+    }
+}
@@ -0,0 +1,332 @@
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use git2::{Repository, RepositoryInitOptions, Signature};
+use indoc::indoc;
+use std::{
+    io::{BufRead, BufReader},
+    path::Path,
+    process::{Command, Stdio},
+};
+mod utils;
+use utils::*;
+
+/// A repository at `path` on branch `main`, with someone to commit as
+fn init(path: &Path, bare: bool) -> Repository {
+    let repo = Repository::init_opts(
+        path,
+        RepositoryInitOptions::new().bare(bare).initial_head("main"),
+    )
+    .unwrap();
+    let mut config = repo.config().unwrap();
+    config.set_str("user.name", "Test").unwrap();
+    config.set_str("user.email", "test@example.com").unwrap();
+    repo
+}
+
+/// Commit everything in the repository's working directory
+fn commit_all(repo: &Repository, message: &str) {
+    let mut index = repo.index().unwrap();
+    index
+        .add_all(["*"], git2::IndexAddOption::DEFAULT, None)
+        .unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let signature = Signature::now("Test", "test@example.com").unwrap();
+    let parent = repo.head().ok().map(|head| head.peel_to_commit().unwrap());
+    let parents: Vec<&git2::Commit> = parent.iter().collect();
+    repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        message,
+        &tree,
+        &parents,
+    )
+    .unwrap();
+}
+
+fn head_message(path: &Path) -> String {
+    let repo = Repository::open(path).unwrap();
+    let message = repo
+        .head()
+        .unwrap()
+        .peel_to_commit()
+        .unwrap()
+        .message()
+        .unwrap()
+        .to_string();
+    message
+}
+
+/// The vault committed to a repository of its own, with a note then changed
+/// and another added
+fn with_changes(command: &str) -> Obx {
+    let cmd = Obx::from_command(command);
+    let repo = init(&cmd.temp_dir.child("main-vault"), false);
+    commit_all(&repo, "Initial commit");
+    cmd.temp_dir
+        .child("main-vault/simple-note.md")
+        .write_str("Changed")
+        .unwrap();
+    cmd.temp_dir
+        .child("main-vault/Inbox/idea.md")
+        .write_str("New")
+        .unwrap();
+    cmd
+}
+
+/// `with_changes`, committed, with a bare repository as its `origin`
+fn with_remote(command: &str) -> Obx {
+    let cmd = with_changes(command);
+    init(&cmd.temp_dir.child("remote.git"), true);
+    let repo = Repository::open(cmd.temp_dir.child("main-vault")).unwrap();
+    repo.remote(
+        "origin",
+        &cmd.temp_dir.child("remote.git").to_string_lossy(),
+    )
+    .unwrap();
+    commit_all(&repo, "Local changes");
+    cmd
+}
+
+/// Another obx command against the same vault and state as `cmd`
+fn rerun(cmd: &Obx, command: &str) -> Command {
+    let mut rerun = Command::cargo_bin("obx").unwrap();
+    rerun.current_dir(&cmd.temp_dir).args(command.split(' '));
+    for (key, value) in cmd.cmd.get_envs() {
+        if let Some(value) = value {
+            rerun.env(key, value);
+        }
+    }
+    rerun
+}
+
+mod git {
+    use super::*;
+
+    #[test]
+    fn lists_changes() {
+        with_changes("git status").assert_stdout(indoc! {"
+            On branch main
+            A Inbox/idea.md
+            M simple-note.md
+        "});
+    }
+
+    #[test]
+    fn lists_only_changes_in_the_vault() {
+        let cmd = Obx::from_command("git status");
+        let repo = init(&cmd.temp_dir, false);
+        commit_all(&repo, "Initial commit");
+        cmd.temp_dir.child("outside.md").write_str("").unwrap();
+        cmd.temp_dir
+            .child("main-vault/folder/new.md")
+            .write_str("")
+            .unwrap();
+
+        cmd.assert_stdout("On branch main\nA folder/new.md\n");
+    }
+
+    #[test]
+    fn commits_with_a_message() {
+        let cmd = with_changes("git commit -m Notes").assert_success();
+        assert_eq!(head_message(&cmd.temp_dir.child("main-vault")), "Notes");
+    }
+
+    #[test]
+    fn commits_with_a_summary_of_the_changes() {
+        let cmd = with_changes("git commit").assert_success();
+        assert_eq!(
+            head_message(&cmd.temp_dir.child("main-vault")),
+            "Update 2 files: 1 added, 1 modified\n\nA Inbox/idea.md\nM simple-note.md\n"
+        );
+    }
+
+    #[test]
+    fn refuses_to_commit_what_is_staged_outside_the_vault() {
+        let cmd = Obx::from_command("git commit -m Notes");
+        let repo = init(&cmd.temp_dir, false);
+        commit_all(&repo, "Initial commit");
+        cmd.temp_dir.child("outside.md").write_str("").unwrap();
+        cmd.temp_dir
+            .child("main-vault/new.md")
+            .write_str("")
+            .unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("outside.md")).unwrap();
+        index.write().unwrap();
+
+        let cmd = cmd.assert_stderr(
+            "outside.md is staged outside the vault, commit or unstage it with git first\n",
+        );
+        assert_eq!(head_message(&cmd.temp_dir), "Initial commit");
+    }
+
+    #[test]
+    fn commits_vaults_named_like_globs() {
+        let cmd = Obx::from_command("git commit -m Notes");
+        let config = format!(
+            "current_vault: globbed\nvaults:\n- name: globbed\n  path: {}/notes[1]/\n",
+            cmd.temp_dir.display()
+        );
+        let cmd = cmd.with_config_file(&config);
+        let repo = init(&cmd.temp_dir, false);
+        commit_all(&repo, "Initial commit");
+        cmd.temp_dir
+            .child("notes[1]/inside.md")
+            .write_str("")
+            .unwrap();
+        cmd.temp_dir
+            .child("notes1/outside.md")
+            .write_str("")
+            .unwrap();
+
+        let cmd = cmd.assert_success();
+        let repo = Repository::open(&cmd.temp_dir).unwrap();
+        let tree = repo.head().unwrap().peel_to_tree().unwrap();
+        assert!(tree.get_path(Path::new("notes[1]/inside.md")).is_ok());
+        assert!(tree.get_path(Path::new("notes1/outside.md")).is_err());
+    }
+
+    #[test]
+    fn has_nothing_to_commit_without_changes() {
+        let cmd = Obx::from_command("git commit");
+        let repo = init(&cmd.temp_dir.child("main-vault"), false);
+        commit_all(&repo, "Initial commit");
+
+        cmd.assert_stdout("Nothing to commit\n");
+    }
+
+    #[test]
+    fn fails_outside_a_repository() {
+        let cmd = Obx::from_command("git status");
+        let vault = cmd.temp_dir.child("main-vault");
+        cmd.assert_stderr(format!(
+            "Vault at {}/ isn't in a git repository, create one with `git init`\n",
+            vault.display()
+        ));
+    }
+
+    #[test]
+    fn pushes_to_the_remote() {
+        let cmd = with_remote("git sync").assert_stdout("Pushed 2 commits to origin/main\n");
+        assert_eq!(
+            head_message(&cmd.temp_dir.child("remote.git")),
+            "Local changes"
+        );
+    }
+
+    #[test]
+    fn rebases_onto_the_remote_before_pushing() {
+        let cmd = with_remote("git sync");
+        let vault = Repository::open(cmd.temp_dir.child("main-vault")).unwrap();
+        let mut remote = vault.find_remote("origin").unwrap();
+        remote
+            .push(&["refs/heads/main:refs/heads/main"], None)
+            .unwrap();
+
+        // Someone else pushes a change from elsewhere
+        let other = Repository::clone(
+            &cmd.temp_dir.child("remote.git").to_string_lossy(),
+            cmd.temp_dir.child("other"),
+        )
+        .unwrap();
+        cmd.temp_dir
+            .child("other/elsewhere.md")
+            .write_str("From elsewhere")
+            .unwrap();
+        commit_all(&other, "Elsewhere");
+        other
+            .find_remote("origin")
+            .unwrap()
+            .push(&["refs/heads/main:refs/heads/main"], None)
+            .unwrap();
+
+        cmd.temp_dir
+            .child("main-vault/local.md")
+            .write_str("Local")
+            .unwrap();
+        commit_all(&vault, "Local");
+
+        let cmd =
+            cmd.assert_stdout("Pulled 1 commit from origin/main\nPushed 1 commit to origin/main\n");
+        cmd.temp_dir
+            .child("main-vault/elsewhere.md")
+            .assert("From elsewhere");
+        assert_eq!(head_message(&cmd.temp_dir.child("remote.git")), "Local");
+    }
+
+    #[test]
+    fn refuses_to_sync_uncommitted_changes() {
+        with_changes("git sync").assert_stderr(
+            "The vault has uncommitted changes, commit them with `obx git commit` first\n",
+        );
+    }
+
+    #[test]
+    fn autocommits_saved_notes() {
+        let mut cmd = Obx::from_command("git autocommit");
+        let repo = init(&cmd.temp_dir.child("main-vault"), false);
+        commit_all(&repo, "Initial commit");
+        let mut daemon = cmd
+            .cmd
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        let mut started = String::new();
+        BufReader::new(daemon.stderr.take().unwrap())
+            .read_line(&mut started)
+            .unwrap();
+        assert_eq!(
+            started,
+            "Committing `main` as notes change, press Ctrl-C to stop\n"
+        );
+
+        cmd.temp_dir
+            .child("main-vault/new-note.md")
+            .write_str("Fresh")
+            .unwrap();
+        let mut committed = String::new();
+        BufReader::new(daemon.stdout.take().unwrap())
+            .read_line(&mut committed)
+            .unwrap();
+        daemon.kill().unwrap();
+        daemon.wait().unwrap();
+
+        assert!(committed.starts_with("Committed "));
+        assert!(committed.ends_with(" Add new-note.md\n"));
+        assert_eq!(
+            head_message(&cmd.temp_dir.child("main-vault")),
+            "Add new-note.md"
+        );
+
+        let output = rerun(&cmd, "git activity").output().unwrap();
+        let activity = String::from_utf8(output.stdout).unwrap();
+        let entries: Vec<&str> = activity
+            .lines()
+            .map(|line| line.splitn(3, ' ').nth(2).unwrap())
+            .collect();
+        assert_eq!(entries, ["Started", committed.trim_end()]);
+    }
+
+    #[test]
+    fn pauses_and_resumes_autocommit() {
+        let cmd = Obx::from_command("git pause").assert_stdout("Paused autocommit for `main`\n");
+        rerun(&cmd, "git pause")
+            .assert()
+            .stdout("Autocommit is already paused for `main`\n");
+        rerun(&cmd, "git resume")
+            .assert()
+            .stdout("Resumed autocommit for `main`\n");
+        rerun(&cmd, "git resume")
+            .assert()
+            .stdout("Autocommit isn't paused for `main`\n");
+    }
+
+    #[test]
+    fn has_no_activity_before_autocommitting() {
+        Obx::from_command("git activity").assert_stdout("No autocommit activity for `main`\n");
+    }
+}
@@ -0,0 +1,125 @@
+use assert_fs::prelude::*;
+use indoc::indoc;
+mod utils;
+use utils::*;
+
+fn with_linked_notes(command: &str) -> Obx {
+    let cmd = Obx::from_command(command);
+    cmd.temp_dir
+        .child("main-vault/hub.md")
+        .write_str("#topic links to [[spoke]] and [[Work/task]]")
+        .unwrap();
+    cmd.temp_dir
+        .child("main-vault/spoke.md")
+        .write_str("[[rim]] and back to [[hub]], [[hub]]")
+        .unwrap();
+    cmd.temp_dir
+        .child("main-vault/rim.md")
+        .write_str("#topic/nested")
+        .unwrap();
+    cmd.temp_dir
+        .child("main-vault/Work/task.md")
+        .write_str("")
+        .unwrap();
+
+    cmd
+}
+
+mod graph {
+    use super::*;
+
+    #[test]
+    fn exports_dot_from_a_root() {
+        with_linked_notes("graph export --root hub").assert_stdout(indoc! {r#"
+            digraph vault {
+              "Work/task.md" [label="task"];
+              "hub.md" [label="hub"];
+              "spoke.md" [label="spoke"];
+              "hub.md" -> "Work/task.md";
+              "hub.md" -> "spoke.md";
+              "spoke.md" -> "hub.md" [weight=2];
+            }
+        "#});
+    }
+
+    #[test]
+    fn follows_links_to_a_depth() {
+        with_linked_notes("graph export --root hub --depth 2 --folder . -f json")
+            .assert_stdout_contains(r#"{"id":"rim.md","label":"rim","tags":["topic/nested"]}"#);
+    }
+
+    #[test]
+    fn scopes_to_folders_and_tags() {
+        with_linked_notes("graph export --tag #topic -f json").assert_stdout(concat!(
+            r#"{"nodes":[{"id":"hub.md","label":"hub","tags":["topic"]},"#,
+            r#"{"id":"rim.md","label":"rim","tags":["topic/nested"]}],"edges":[]}"#,
+            "\n"
+        ));
+        with_linked_notes("graph export --folder Work -f json").assert_stdout(
+            "{\"nodes\":[{\"id\":\"Work/task.md\",\"label\":\"task\",\"tags\":[]}],\"edges\":[]}\n",
+        );
+    }
+
+    #[test]
+    fn exports_graphml() {
+        with_linked_notes("graph export --root rim -f graphml").assert_stdout_contains(indoc! {r#"
+              <graph id="vault" edgedefault="directed">
+                <node id="rim.md"><data key="label">rim</data><data key="tags">topic/nested</data></node>
+                <node id="spoke.md"><data key="label">spoke</data><data key="tags"></data></node>
+                <edge source="spoke.md" target="rim.md"><data key="weight">1</data></edge>
+              </graph>
+            </graphml>
+        "#});
+    }
+
+    #[test]
+    fn needs_a_root_for_a_depth() {
+        let mut cmd = Obx::from_command("graph export --depth 2");
+        let output = cmd.cmd.output().unwrap();
+        assert!(!output.status.success());
+    }
+
+    #[test]
+    fn reports_stats() {
+        with_linked_notes("graph stats --top 3").assert_stdout(indoc! {"
+            Notes: 12
+            Links between notes: 5
+            Orphans: 6 (50%)
+            Connected components: 8, the largest with 4 notes
+            Average path length: 1.57
+
+            Links in: mean 0.42, median 0, max 1
+              0     7
+              1     5
+              2-4   0
+              5-9   0
+              10+   0
+
+            Links out: mean 0.42, median 0, max 2
+              0     9
+              1     1
+              2-4   2
+              5-9   0
+              10+   0
+
+            Most linked notes:
+              1 Work/task.md
+              1 hub.md
+              1 rim.md
+        "});
+    }
+
+    #[test]
+    fn prints_stats_as_json() {
+        with_linked_notes("graph stats --format json --top 1").assert_stdout(concat!(
+            r#"{"notes":12,"links":5,"orphans":6,"orphan_percentage":50.0,"components":8,"#,
+            r#""largest_component":4,"average_path_length":1.57,"#,
+            r#""in_degree":{"mean":0.42,"median":0.0,"max":1,"distribution":[{"degree":"0","notes":7},"#,
+            r#"{"degree":"1","notes":5},{"degree":"2-4","notes":0},{"degree":"5-9","notes":0},{"degree":"10+","notes":0}]},"#,
+            r#""out_degree":{"mean":0.42,"median":0.0,"max":2,"distribution":[{"degree":"0","notes":9},"#,
+            r#"{"degree":"1","notes":1},{"degree":"2-4","notes":2},{"degree":"5-9","notes":0},{"degree":"10+","notes":0}]},"#,
+            r#""hubs":[{"note":"Work/task.md","links":1}]}"#,
+            "\n"
+        ));
+    }
+}
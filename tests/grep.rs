@@ -0,0 +1,186 @@
+use assert_fs::prelude::*;
+use indoc::{formatdoc, indoc};
+mod utils;
+use utils::*;
+
+mod grep {
+    use super::*;
+
+    #[test]
+    fn prints_matching_lines_with_paths() {
+        Obx::from_command("grep contents.of.(simple|complex)").assert_stdout(indoc! {"
+            complex-note.md:7:This is the contents of complex-note.md
+            simple-note.md:3:This is the contents of simple-note.md
+        "});
+    }
+
+    #[test]
+    fn prints_context_lines() {
+        Obx::from_command("grep -C 1 ^It.contains").assert_stdout(indoc! {"
+            complex-note.md-8-
+            complex-note.md:9:It contains a list
+            complex-note.md-10-- item 1
+        "});
+    }
+
+    #[test]
+    fn separates_context_groups() {
+        Obx::from_command("grep -A 1 -g *-note.md ^#").assert_stdout(indoc! {"
+            complex-note.md:5:# Rich note
+            complex-note.md-6-
+            --
+            simple-note.md:1:# Simple note
+            simple-note.md-2-
+        "});
+    }
+
+    #[test]
+    fn lists_only_file_names() {
+        Obx::from_command("grep -l note").assert_stdout(indoc! {"
+            complex-note.md
+            folder/child-note.md
+            simple-note.md
+            table.md
+        "});
+    }
+
+    #[test]
+    fn skips_config_ignore_patterns() {
+        let cmd = Obx::from_command("grep -l note");
+        let dir = cmd.temp_dir.display().to_string();
+
+        cmd.with_config_file(&formatdoc! {"
+            current_vault: main
+            vaults:
+            - name: main
+              path: {dir}/main-vault/
+            ignore:
+            - folder/
+            - table.md
+        "})
+            .assert_stdout(indoc! {"
+            complex-note.md
+            simple-note.md
+        "});
+    }
+
+    #[test]
+    fn filters_by_path_glob() {
+        Obx::from_command("grep -l -g folder/** note").assert_stdout("folder/child-note.md\n");
+    }
+
+    #[test]
+    fn fails_on_invalid_pattern() {
+        Obx::from_command("grep (unclosed")
+            .assert_stderr("Invalid regular expression `(unclosed`\n");
+    }
+
+    #[test]
+    fn skips_excalidraw_payloads() {
+        let cmd = Obx::from_command("grep --case-sensitive -g *.excalidraw.md (label|elements)");
+        cmd.temp_dir
+            .child("main-vault/sketch.excalidraw.md")
+            .write_str(indoc! {r#"
+                # Text Elements
+                A label ^abc123
+
+                %%
+                # Drawing
+                ```json
+                {"type":"excalidraw","elements":[]}
+                ```
+                %%
+            "#})
+            .unwrap();
+
+        cmd.assert_stdout("sketch.excalidraw.md:2:A label ^abc123\n");
+    }
+
+    #[test]
+    fn ignores_case_unless_the_pattern_has_capitals() {
+        Obx::from_command("grep ^#.simple").assert_stdout("simple-note.md:1:# Simple note\n");
+        Obx::from_command("grep ^#.SIMPLE").assert_stdout("");
+        Obx::from_command(r"grep ^#\Wsimple").assert_stdout("simple-note.md:1:# Simple note\n");
+    }
+
+    #[test]
+    fn overrides_smart_case_with_flags() {
+        Obx::from_command("grep --case-sensitive ^#.simple").assert_stdout("");
+        Obx::from_command("grep -i ^#.SIMPLE").assert_stdout("simple-note.md:1:# Simple note\n");
+    }
+
+    #[test]
+    fn highlights_matches_when_colored() {
+        Obx::from_command("grep --color always ^#.Simple").assert_stdout(concat!(
+            "\u{1b}[38;2;208;208;208msimple-note.md\u{1b}[39m:1:",
+            "\u{1b}[1m\u{1b}[38;2;166;218;149m# Simple\u{1b}[39m\u{1b}[22m note\n",
+        ));
+    }
+
+    #[test]
+    fn leaves_out_colors_unless_asked() {
+        Obx::from_command("grep ^#.Simple").assert_stdout("simple-note.md:1:# Simple note\n");
+
+        let mut cmd = Obx::from_command("grep --color always ^#.Simple");
+        cmd.env("NO_COLOR", "1");
+        cmd.assert_stdout_contains("\u{1b}[1m");
+
+        let mut cmd = Obx::from_command("grep --color never ^#.Simple");
+        cmd.env("NO_COLOR", "");
+        cmd.assert_stdout("simple-note.md:1:# Simple note\n");
+    }
+
+    #[test]
+    fn prints_only_matches() {
+        Obx::from_command(r"grep -o contents.of.\w+").assert_stdout(indoc! {"
+            complex-note.md:7:contents of complex
+            simple-note.md:3:contents of simple
+        "});
+    }
+
+    #[test]
+    fn extracts_capture_groups() {
+        let cmd = Obx::from_command(r"grep -o -r $1 -g tasks.md due::\s(\d{4}-\d{2}-\d{2})");
+        cmd.temp_dir
+            .child("main-vault/tasks.md")
+            .write_str(
+                "- [ ] Ship due:: 2024-05-01\n- [ ] Plan due:: 2024-06-02 then due:: 2024-07-03\n",
+            )
+            .unwrap();
+
+        cmd.assert_stdout(indoc! {"
+            tasks.md:1:2024-05-01
+            tasks.md:2:2024-06-02
+            tasks.md:2:2024-07-03
+        "});
+    }
+
+    #[test]
+    fn replaces_matches_in_lines() {
+        Obx::from_command(r"grep -r [$1] -g simple-note.md contents.of.(\w+)")
+            .assert_stdout("simple-note.md:3:This is the [simple]-note.md\n");
+    }
+
+    #[test]
+    fn prints_matches_as_json() {
+        Obx::from_command(r"grep -f json -r $1 -g simple-note.md contents.of.(\w+)").assert_stdout(
+            "[{\"path\":\"simple-note.md\",\"line\":3,\"snippet\":\"This is the contents of simple-note.md\",\
+             \"matches\":[{\"start\":12,\"end\":30,\"text\":\"simple\"}]}]\n",
+        );
+    }
+
+    #[test]
+    fn prints_a_record_per_line_as_jsonl() {
+        let mut cmd = Obx::from_command(r"grep -f jsonl contents.of");
+        let output = cmd.cmd.output().unwrap();
+        let records: Vec<serde_json::Value> = String::from_utf8(output.stdout)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["path"], "complex-note.md");
+        assert_eq!(records[1]["line"], 3);
+    }
+}
@@ -0,0 +1,140 @@
+use assert_fs::prelude::*;
+use indoc::formatdoc;
+use predicates::prelude::*;
+mod utils;
+use utils::*;
+
+mod hooks {
+    use super::*;
+
+    /// Set `hook` to a script that appends its environment to `hooks.log`
+    fn with_logging_hook(command: &str, hook: &str, exit_code: u8) -> Obx {
+        let cmd = Obx::from_command(command);
+        let dir = cmd.temp_dir.display().to_string();
+        let script = create_script(
+            "hook.sh",
+            format!(
+                "echo \"$OBX_HOOK $(pwd) $OBX_VAULT $OBX_NOTE_PATH $OBX_PREVIOUS_NOTE_PATH\" >> {dir}/hooks.log\nexit {exit_code}\n"
+            ),
+            &cmd.temp_dir,
+        );
+
+        cmd.with_config_file(&formatdoc! {"
+            current_vault: main
+            vaults:
+            - name: main
+              path: {dir}/main-vault
+            hooks:
+              {hook}: {script}
+            ",
+            script = script.display()
+        })
+    }
+
+    fn assert_log(cmd: &Obx, expected: &str) {
+        let dir = cmd.temp_dir.display().to_string();
+        cmd.temp_dir
+            .child("hooks.log")
+            .assert(predicate::str::diff(expected.replace("{dir}", &dir)));
+    }
+
+    #[test]
+    fn runs_after_create() {
+        let cmd = with_logging_hook("notes create new-note", "after_create", 0)
+            .assert_created("main-vault/new-note.md");
+
+        assert_log(
+            &cmd,
+            "after_create {dir}/main-vault main {dir}/main-vault/new-note.md \n",
+        );
+    }
+
+    #[test]
+    fn runs_after_rename_with_previous_path() {
+        let cmd = with_logging_hook("rename simple-note renamed", "after_move", 0).assert_success();
+
+        assert_log(
+            &cmd,
+            "after_move {dir}/main-vault main {dir}/main-vault/renamed.md {dir}/main-vault/simple-note.md\n",
+        );
+    }
+
+    #[test]
+    fn runs_after_each_move() {
+        let cmd =
+            with_logging_hook("move simple-note table archive", "after_move", 0).assert_success();
+
+        assert_log(
+            &cmd,
+            concat!(
+                "after_move {dir}/main-vault main {dir}/main-vault/archive/simple-note.md {dir}/main-vault/simple-note.md\n",
+                "after_move {dir}/main-vault main {dir}/main-vault/archive/table.md {dir}/main-vault/table.md\n",
+            ),
+        );
+    }
+
+    #[test]
+    fn skips_hooks_on_dry_run() {
+        let cmd = with_logging_hook("rename simple-note renamed --dry-run", "after_move", 0)
+            .assert_success();
+
+        cmd.temp_dir
+            .child("hooks.log")
+            .assert(predicate::path::missing());
+    }
+
+    #[test]
+    fn runs_after_daily_when_creating_the_note() {
+        let cmd = with_logging_hook("daily --date 2024-05-10", "after_daily", 0)
+            .assert_created("main-vault/2024-05-10.md");
+
+        assert_log(
+            &cmd,
+            "after_daily {dir}/main-vault main {dir}/main-vault/2024-05-10.md \n",
+        );
+    }
+
+    #[test]
+    fn runs_after_daily_when_the_note_exists() {
+        let cmd = with_logging_hook("daily --date 2024-05-10", "after_daily", 0);
+        cmd.temp_dir
+            .child("main-vault/2024-05-10.md")
+            .write_str("Already here")
+            .unwrap();
+        let cmd = cmd.assert_stdout("2024-05-10.md\n");
+
+        assert_log(
+            &cmd,
+            "after_daily {dir}/main-vault main {dir}/main-vault/2024-05-10.md \n",
+        );
+    }
+
+    #[test]
+    fn runs_before_delete() {
+        let cmd =
+            with_logging_hook("delete simple-note --force", "before_delete", 0).assert_success();
+
+        assert_log(
+            &cmd,
+            "before_delete {dir}/main-vault main {dir}/main-vault/simple-note.md \n",
+        );
+        cmd.temp_dir
+            .child("main-vault/simple-note.md")
+            .assert(predicate::path::missing());
+    }
+
+    #[test]
+    fn failing_before_delete_keeps_note() {
+        let cmd = with_logging_hook("delete simple-note --force", "before_delete", 3);
+        let script = cmd.temp_dir.child("hook.sh");
+
+        let cmd = cmd.assert_stderr(format!(
+            "The `before_delete` hook `{}` failed with exit status: 3\n",
+            script.display()
+        ));
+
+        cmd.temp_dir
+            .child("main-vault/simple-note.md")
+            .assert(predicate::path::exists());
+    }
+}
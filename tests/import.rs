@@ -0,0 +1,152 @@
+use assert_fs::prelude::*;
+use indoc::indoc;
+use predicates::prelude::*;
+use std::io::Write;
+mod utils;
+use utils::*;
+
+const PAGE_ID: &str = "0123456789abcdef0123456789abcdef";
+
+mod import {
+    use super::*;
+
+    mod notion {
+        use super::*;
+
+        fn write_export_zip(cmd: &Obx) {
+            let file = std::fs::File::create(cmd.temp_dir.child("export.zip").path()).unwrap();
+            let mut zip = zip::ZipWriter::new(file);
+            let options = zip::write::SimpleFileOptions::default();
+
+            zip.start_file(format!("Roadmap {PAGE_ID}.md"), options)
+                .unwrap();
+            write!(
+                zip,
+                "# Roadmap\n\nNext up: [Launch](Roadmap%20{PAGE_ID}/Launch%20{PAGE_ID}.md)\n\n![](Roadmap%20{PAGE_ID}/plan.png)\n"
+            )
+            .unwrap();
+            zip.start_file(format!("Roadmap {PAGE_ID}/Launch {PAGE_ID}.md"), options)
+                .unwrap();
+            writeln!(zip, "# Launch").unwrap();
+            zip.start_file(format!("Roadmap {PAGE_ID}/plan.png"), options)
+                .unwrap();
+            write!(zip, "png").unwrap();
+            zip.finish().unwrap();
+        }
+
+        #[test]
+        fn imports_zip_export() {
+            let cmd = Obx::from_command("import notion export.zip --dest Imported");
+            write_export_zip(&cmd);
+
+            let cmd = cmd.assert_stdout("Imported 2 note(s) and 1 attachment(s) into Imported\n");
+
+            cmd.temp_dir
+                .child("main-vault/Imported/Roadmap.md")
+                .assert(indoc! {"
+                    # Roadmap
+
+                    Next up: [[Launch]]
+
+                    ![[plan.png]]
+                "});
+            cmd.temp_dir
+                .child("main-vault/Imported/Roadmap/Launch.md")
+                .assert("# Launch\n");
+            cmd.temp_dir
+                .child("main-vault/attachments/plan.png")
+                .assert("png");
+        }
+
+        #[test]
+        fn skips_entries_outside_the_export() {
+            let cmd = Obx::from_command("import notion export.zip");
+            let file = std::fs::File::create(cmd.temp_dir.child("export.zip").path()).unwrap();
+            let mut zip = zip::ZipWriter::new(file);
+            let options = zip::write::SimpleFileOptions::default();
+            zip.start_file("../escaped.md", options).unwrap();
+            writeln!(zip, "# Escaped").unwrap();
+            zip.start_file(format!("Roadmap {PAGE_ID}.md"), options)
+                .unwrap();
+            writeln!(zip, "# Roadmap").unwrap();
+            zip.finish().unwrap();
+
+            let cmd =
+                cmd.assert_stdout("Imported 1 note(s) and 0 attachment(s) into vault `main`\n");
+
+            cmd.temp_dir
+                .child("main-vault/Roadmap.md")
+                .assert("# Roadmap\n");
+            cmd.temp_dir
+                .child("escaped.md")
+                .assert(predicate::path::missing());
+        }
+
+        #[test]
+        fn imports_unzipped_folder() {
+            let cmd = Obx::from_command("import notion export");
+            cmd.temp_dir
+                .child(format!("export/Inbox {PAGE_ID}.md"))
+                .write_str("# Inbox")
+                .unwrap();
+
+            let cmd =
+                cmd.assert_stdout("Imported 1 note(s) and 0 attachment(s) into vault `main`\n");
+
+            cmd.temp_dir
+                .child("main-vault/Inbox.md")
+                .assert(predicate::path::exists());
+        }
+    }
+
+    mod enex {
+        use super::*;
+
+        const ENEX: &str = indoc! {r#"
+            <?xml version="1.0" encoding="UTF-8"?>
+            <en-export>
+              <note>
+                <title>Simple note</title>
+                <content><![CDATA[<en-note><div>From Evernote</div><div><en-media hash="5d41402abc4b2a76b9719d911017c592" type="image/png"/></div></en-note>]]></content>
+                <tag>imported</tag>
+                <resource>
+                  <data encoding="base64">aGVsbG8=</data>
+                  <mime>image/png</mime>
+                  <resource-attributes><file-name>photo.png</file-name></resource-attributes>
+                </resource>
+              </note>
+            </en-export>
+        "#};
+
+        #[test]
+        fn keeps_existing_files() {
+            let cmd = Obx::from_command("import enex notes.enex");
+            cmd.temp_dir.child("notes.enex").write_str(ENEX).unwrap();
+            cmd.temp_dir
+                .child("main-vault/attachments/photo.png")
+                .write_str("existing")
+                .unwrap();
+
+            let cmd =
+                cmd.assert_stdout("Imported 1 note(s) and 1 attachment(s) into vault `main`\n");
+
+            cmd.temp_dir
+                .child("main-vault/Simple note.md")
+                .assert("---\ntags:\n- imported\n---\nFrom Evernote\n![[photo 1.png]]\n");
+            cmd.temp_dir
+                .child("main-vault/attachments/photo 1.png")
+                .assert("hello");
+            cmd.temp_dir
+                .child("main-vault/simple-note.md")
+                .assert(predicate::str::contains(
+                    "This is the contents of simple-note.md",
+                ));
+        }
+
+        #[test]
+        fn errors_on_missing_file() {
+            Obx::from_command("import enex missing.enex")
+                .assert_stderr("`missing.enex` not found\n");
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use std::{
+    io::{BufRead, BufReader},
+    process::Stdio,
+};
+mod utils;
+use utils::*;
+
+mod index {
+    use super::*;
+
+    /// Run `obx index build` against the same vault and state as `cmd`
+    fn build_index(cmd: &Obx) {
+        assert_cmd::Command::cargo_bin("obx")
+            .unwrap()
+            .current_dir(&cmd.temp_dir)
+            .env("OBX_CONFIG_DIR", cmd.temp_dir.child("config/obx").path())
+            .env("OBX_STATE_DIR", cmd.temp_dir.child("state/obx").path())
+            .args(["index", "build"])
+            .assert()
+            .success()
+            .stdout("Indexed 8 notes in `main`\n");
+    }
+
+    #[test]
+    fn builds_into_state_dir() {
+        let cmd = Obx::from_command("index build").assert_stdout("Indexed 8 notes in `main`\n");
+
+        cmd.temp_dir
+            .child("state/obx/vaults")
+            .assert(predicate::path::is_dir());
+    }
+
+    #[test]
+    fn reports_missing_index() {
+        Obx::from_command("index status")
+            .assert_stdout("No index for `main`, build one with `obx index build`\n");
+    }
+
+    #[test]
+    fn reports_changes_since_built() {
+        let cmd = Obx::from_command("index status");
+        build_index(&cmd);
+        cmd.temp_dir
+            .child("main-vault/new-note.md")
+            .write_str("fresh words")
+            .unwrap();
+
+        cmd.assert_stdout_contains("Notes: 8\n")
+            .assert_stdout_contains("Changed since built: 1\n");
+    }
+
+    #[test]
+    fn search_picks_up_changed_notes() {
+        let cmd = Obx::from_command("search fresh");
+        build_index(&cmd);
+        cmd.temp_dir
+            .child("main-vault/new-note.md")
+            .write_str("fresh words")
+            .unwrap();
+
+        cmd.assert_stdout("new-note.md\n");
+    }
+
+    #[test]
+    fn clears_index() {
+        let cmd = Obx::from_command("index clear");
+        build_index(&cmd);
+
+        cmd.assert_stdout("Removed the index for `main`\n");
+    }
+
+    #[test]
+    fn clear_without_index() {
+        Obx::from_command("index clear").assert_stdout("No index for `main`\n");
+    }
+
+    #[test]
+    fn watch_indexes_changed_notes() {
+        let Obx { mut cmd, temp_dir } = Obx::from_command("index watch");
+        let mut watch = cmd
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        let mut started = String::new();
+        BufReader::new(watch.stderr.take().unwrap())
+            .read_line(&mut started)
+            .unwrap();
+        assert_eq!(
+            started,
+            "Watching `main` with 8 notes indexed, press Ctrl-C to stop\n"
+        );
+
+        temp_dir
+            .child("main-vault/new-note.md")
+            .write_str("fresh words")
+            .unwrap();
+        let mut update = String::new();
+        BufReader::new(watch.stdout.take().unwrap())
+            .read_line(&mut update)
+            .unwrap();
+        watch.kill().unwrap();
+        watch.wait().unwrap();
+
+        assert_eq!(update, "Indexed new-note.md\n");
+    }
+}
@@ -0,0 +1,61 @@
+use assert_fs::prelude::*;
+use indoc::indoc;
+mod utils;
+use utils::*;
+
+mod links {
+    use super::*;
+
+    #[test]
+    fn lists_links_with_targets() {
+        Obx::from_command("links link-types").assert_stdout(indoc! {"
+            ┌──────┬──────────────────────────────────────┬──────────┐
+            │ Line │ Link                                 │ Target   │
+            ├──────┼──────────────────────────────────────┼──────────┤
+            │ 1    │ [markdown link](https://example.com) │ external │
+            │ 3    │ [[wikilink]]                         │ missing  │
+            │ 5    │ [[wikilink |different label]]        │ missing  │
+            └──────┴──────────────────────────────────────┴──────────┘
+        "});
+    }
+
+    #[test]
+    fn resolves_notes_and_attachments() {
+        let cmd = Obx::from_command("links folder/linking");
+        cmd.temp_dir
+            .child("main-vault/folder/linking.md")
+            .write_str("[[child-note]] ![[diagram.png]] [up](../simple-note.md#Heading)")
+            .unwrap();
+        cmd.temp_dir
+            .child("main-vault/attachments/diagram.png")
+            .write_str("")
+            .unwrap();
+
+        let _ = &cmd.assert_stdout(indoc! {"
+            ┌──────┬─────────────────────────────────┬─────────────────────────┐
+            │ Line │ Link                            │ Target                  │
+            ├──────┼─────────────────────────────────┼─────────────────────────┤
+            │ 1    │ [[child-note]]                  │ folder/child-note.md    │
+            │ 1    │ ![[diagram.png]]                │ attachments/diagram.png │
+            │ 1    │ [up](../simple-note.md#Heading) │ simple-note.md          │
+            └──────┴─────────────────────────────────┴─────────────────────────┘
+        "});
+    }
+
+    #[test]
+    fn prints_absolute_paths_with_resolve() {
+        let cmd = Obx::from_command("links complex-note --resolve");
+        let expected = cmd
+            .temp_dir
+            .child("main-vault/simple-note.md")
+            .display()
+            .to_string();
+
+        let _ = &cmd.assert_stdout_contains(expected);
+    }
+
+    #[test]
+    fn prints_nothing_without_links() {
+        Obx::from_command("links simple-note").assert_stdout("");
+    }
+}
@@ -0,0 +1,198 @@
+use assert_fs::prelude::*;
+use indoc::{formatdoc, indoc};
+use predicates::prelude::*;
+mod utils;
+use utils::*;
+
+mod list {
+    use super::*;
+
+    #[test]
+    fn prints_paths() {
+        Obx::from_command("list -f paths").assert_stdout(indoc! {"
+            complex-note.md
+            empty-note.md
+            folder/child-note.md
+            html.md
+            link-types.md
+            simple-note.md
+            table.md
+            with-fm-properties.md
+        "});
+    }
+
+    #[test]
+    fn pages_through_notes() {
+        Obx::from_command("list -f paths --limit 3 --page 2").assert_stdout(indoc! {"
+            html.md
+            link-types.md
+            simple-note.md
+        "});
+        Obx::from_command("list -f paths --offset 6").assert_stdout(indoc! {"
+            table.md
+            with-fm-properties.md
+        "});
+    }
+
+    #[test]
+    fn needs_a_limit_for_pages() {
+        let mut cmd = Obx::from_command("list --page 2");
+        let output = cmd.cmd.output().unwrap();
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("--limit <LIMIT>"));
+    }
+
+    #[test]
+    fn includes_canvases() {
+        let cmd = Obx::from_command("list -f paths --folder folder --include-canvas");
+        cmd.temp_dir
+            .child("main-vault/folder/board.canvas")
+            .write_str(r#"{"nodes":[],"edges":[]}"#)
+            .unwrap();
+        cmd.temp_dir
+            .child("main-vault/top.canvas")
+            .write_str(r#"{"nodes":[],"edges":[]}"#)
+            .unwrap();
+
+        cmd.assert_stdout("folder/board.canvas\nfolder/child-note.md\n");
+    }
+
+    #[test]
+    fn filters_by_folder() {
+        Obx::from_command("list -f paths --folder folder").assert_stdout("folder/child-note.md\n");
+    }
+
+    #[test]
+    fn colors_folders_and_names() {
+        Obx::from_command("list -f paths --folder folder --color always").assert_stdout(concat!(
+            "\u{1b}[38;2;255;203;107mfolder/\u{1b}[39m",
+            "\u{1b}[38;2;208;208;208mchild-note.md\u{1b}[39m\n",
+        ));
+    }
+
+    #[test]
+    fn filters_by_name() {
+        Obx::from_command("list -f paths --name-contains NOTE").assert_stdout(indoc! {"
+            complex-note.md
+            empty-note.md
+            folder/child-note.md
+            simple-note.md
+        "});
+    }
+
+    #[test]
+    fn filters_by_tag() {
+        let cmd = Obx::from_command("list -f paths --tag #project");
+        cmd.temp_dir
+            .child("main-vault/tagged.md")
+            .write_str("---\ntags: [project]\n---\nBody")
+            .unwrap();
+        cmd.temp_dir
+            .child("main-vault/folder/inline-tagged.md")
+            .write_str("Working on #project today")
+            .unwrap();
+
+        cmd.assert_stdout(indoc! {"
+            folder/inline-tagged.md
+            tagged.md
+        "});
+    }
+
+    #[test]
+    fn filters_by_query() {
+        let mut cmd = Obx::from_command("list -f paths");
+        cmd.temp_dir
+            .child("main-vault/project/draft.md")
+            .write_str("#project/alpha draft")
+            .unwrap();
+        cmd.temp_dir
+            .child("main-vault/project/final.md")
+            .write_str("#project/alpha done")
+            .unwrap();
+        cmd.cmd.args(["--query", "tag:#project -draft"]);
+
+        cmd.assert_stdout("project/final.md\n");
+    }
+
+    #[test]
+    fn picks_up_notes_added_since_cached() {
+        let cmd = Obx::from_command("list -f paths --folder project");
+        assert_cmd::Command::cargo_bin("obx")
+            .unwrap()
+            .current_dir(&cmd.temp_dir)
+            .env("OBX_CONFIG_DIR", cmd.temp_dir.child("config/obx").path())
+            .env("OBX_STATE_DIR", cmd.temp_dir.child("state/obx").path())
+            .args(["list", "-f", "paths"])
+            .assert()
+            .success();
+        cmd.temp_dir
+            .child("state/obx/vaults")
+            .assert(predicate::path::is_dir());
+
+        cmd.temp_dir
+            .child("main-vault/project/new.md")
+            .write_str("#fresh")
+            .unwrap();
+
+        cmd.assert_stdout("project/new.md\n");
+    }
+
+    #[test]
+    fn filters_by_modified_time() {
+        Obx::from_command("list -f paths --modified-since 2999-01-01").assert_stdout("");
+    }
+
+    #[test]
+    fn filters_by_created_time() {
+        let cmd = Obx::from_command("list -f paths --created-before 2021-01-01");
+        for (name, created) in [("old.md", "2020-05-01"), ("new.md", "2023-05-01")] {
+            cmd.temp_dir
+                .child("main-vault/journal")
+                .child(name)
+                .write_str(&format!("---\ncreated: {created}\n---\nEntry"))
+                .unwrap();
+        }
+
+        cmd.assert_stdout("journal/old.md\n");
+    }
+
+    #[test]
+    fn filters_by_modified_range() {
+        Obx::from_command("list -f paths --modified-after 2000-01-01 --modified-before 2000-02-01")
+            .assert_stdout("");
+    }
+
+    #[test]
+    fn prints_json() {
+        let mut cmd = Obx::from_command("list -f json --folder folder");
+        let output = cmd.cmd.output().unwrap();
+        let notes: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+
+        assert_eq!(notes[0]["path"], "folder/child-note.md");
+        assert_eq!(notes[0]["name"], "child-note");
+        assert_eq!(notes[0]["tags"], serde_json::json!([]));
+        assert!(notes[0]["modified"].is_string());
+    }
+
+    #[test]
+    fn uses_configured_timestamp_format() {
+        let cmd = Obx::from_command("list --folder folder");
+        let dir = cmd.temp_dir.display().to_string();
+
+        cmd.with_config_file(&formatdoc! {"
+            current_vault: main
+            vaults:
+            - name: main
+              path: {dir}/main-vault/
+            dates:
+              timestamp: '[changed] YYYY'
+        "})
+            .assert_stdout_contains("│ folder/child-note.md │ changed 20");
+    }
+
+    #[test]
+    fn errors_when_folder_missing() {
+        Obx::from_command("list --folder missing-folder")
+            .assert_stderr("Folder `missing-folder` not found in vault `main`\n");
+    }
+}
@@ -0,0 +1,144 @@
+use assert_fs::prelude::*;
+use serde_json::{json, Value};
+use std::{io::Write, process::Stdio};
+mod utils;
+use utils::*;
+
+/// The replies `obx mcp` writes for `messages`, sent one per line
+fn replies(cmd: &mut Obx, messages: &[Value]) -> Vec<Value> {
+    let mut server = cmd
+        .cmd
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let mut stdin = server.stdin.take().unwrap();
+    for message in messages {
+        writeln!(stdin, "{message}").unwrap();
+    }
+    drop(stdin);
+
+    let output = server.wait_with_output().unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+fn call(id: u64, tool: &str, arguments: Value) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": "tools/call",
+        "params": { "name": tool, "arguments": arguments },
+    })
+}
+
+fn text(reply: &Value) -> (&str, bool) {
+    (
+        reply["result"]["content"][0]["text"].as_str().unwrap(),
+        reply["result"]["isError"].as_bool().unwrap(),
+    )
+}
+
+mod mcp {
+    use super::*;
+
+    #[test]
+    fn initializes_and_lists_tools() {
+        let replies = replies(
+            &mut Obx::from_command("mcp"),
+            &[
+                json!({
+                    "jsonrpc": "2.0",
+                    "id": 1,
+                    "method": "initialize",
+                    "params": { "protocolVersion": "2024-11-05", "capabilities": {} },
+                }),
+                json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }),
+                json!({ "jsonrpc": "2.0", "id": 2, "method": "tools/list" }),
+                json!({ "jsonrpc": "2.0", "id": 3, "method": "resources/list" }),
+            ],
+        );
+
+        assert_eq!(replies.len(), 3);
+        assert_eq!(replies[0]["result"]["protocolVersion"], "2024-11-05");
+        assert_eq!(replies[0]["result"]["serverInfo"]["name"], "obx");
+        let tools: Vec<&str> = replies[1]["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|tool| tool["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            tools,
+            ["search_notes", "read_note", "create_note", "list_tags"]
+        );
+        assert_eq!(replies[2]["error"]["code"], -32601);
+    }
+
+    #[test]
+    fn searches_and_reads_notes() {
+        let replies = replies(
+            &mut Obx::from_command("mcp"),
+            &[
+                call(
+                    1,
+                    "search_notes",
+                    json!({ "query": "\"contents of simple\"" }),
+                ),
+                call(2, "read_note", json!({ "note": "simple-note" })),
+                call(3, "read_note", json!({ "note": "../outside" })),
+            ],
+        );
+
+        assert_eq!(
+            text(&replies[0]),
+            (
+                "simple-note.md:3: This is the contents of simple-note.md",
+                false
+            )
+        );
+        assert_eq!(
+            text(&replies[1]),
+            (
+                "# Simple note\n\nThis is the contents of simple-note.md",
+                false
+            )
+        );
+        assert_eq!(
+            text(&replies[2]),
+            (
+                "Invalid note path `../outside`, expected one within the vault",
+                true
+            )
+        );
+    }
+
+    #[test]
+    fn creates_notes_without_overwriting() {
+        let mut cmd = Obx::from_command("mcp");
+        let replies = replies(
+            &mut cmd,
+            &[
+                call(
+                    1,
+                    "create_note",
+                    json!({ "path": "Inbox/idea", "content": "New" }),
+                ),
+                call(
+                    2,
+                    "create_note",
+                    json!({ "path": "simple-note.md", "content": "" }),
+                ),
+            ],
+        );
+
+        assert_eq!(text(&replies[0]), ("Created Inbox/idea.md", false));
+        assert_eq!(text(&replies[1]), ("simple-note.md already exists", true));
+        cmd.temp_dir.child("main-vault/Inbox/idea.md").assert("New");
+    }
+}
@@ -0,0 +1,97 @@
+use assert_fs::prelude::*;
+use indoc::indoc;
+use predicates::prelude::*;
+mod utils;
+use utils::*;
+
+mod meta {
+    use super::*;
+
+    mod get {
+        use super::*;
+
+        #[test]
+        fn prints_scalar() {
+            Obx::from_command("meta get test-str with-fm-properties")
+                .assert_stdout("a string val\n");
+        }
+
+        #[test]
+        fn prints_list_as_yaml() {
+            Obx::from_command("meta get test-list with-fm-properties")
+                .assert_stdout("- One\n- Two\n");
+        }
+
+        #[test]
+        fn prints_json() {
+            Obx::from_command("meta get test-list with-fm-properties -f json")
+                .assert_stdout("[\"One\",\"Two\"]\n");
+        }
+
+        #[test]
+        fn falls_back_to_inline_fields() {
+            let cmd = Obx::from_command("meta get author book");
+            cmd.temp_dir
+                .child("main-vault/book.md")
+                .write_str("- Author:: Frank Herbert")
+                .unwrap();
+
+            cmd.assert_stdout("Frank Herbert\n");
+        }
+
+        #[test]
+        fn errors_on_missing_key() {
+            Obx::from_command("meta get status simple-note")
+                .assert_stderr("Property `status` is not set on simple-note.md\n");
+        }
+    }
+
+    mod set {
+        use super::*;
+
+        #[test]
+        fn replaces_value_in_place() {
+            let cmd = Obx::from_command("meta set test-str done with-fm-properties")
+                .assert_stdout("Set `test-str` on with-fm-properties.md\n");
+
+            cmd.temp_dir
+                .child("main-vault/with-fm-properties.md")
+                .assert(predicate::str::diff(indoc! {"
+                    ---
+                    test-number: 100
+                    test-str: done
+                    test-checkbox: true
+                    test-list:
+                      - One
+                      - Two
+                    ---
+
+                    The main content of the file"}));
+        }
+
+        #[test]
+        fn creates_frontmatter() {
+            let cmd = Obx::from_command("meta set status active simple-note").assert_success();
+
+            cmd.temp_dir
+                .child("main-vault/simple-note.md")
+                .assert(predicate::str::starts_with(
+                    "---\nstatus: active\n---\n# Simple note",
+                ));
+        }
+    }
+
+    mod unset {
+        use super::*;
+
+        #[test]
+        fn removes_block_value() {
+            let cmd = Obx::from_command("meta unset test-list with-fm-properties")
+                .assert_stdout("Removed `test-list` from with-fm-properties.md\n");
+
+            cmd.temp_dir
+                .child("main-vault/with-fm-properties.md")
+                .assert(predicate::str::contains("test-checkbox: true\n---\n"));
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use assert_fs::prelude::*;
+use indoc::indoc;
+mod utils;
+use utils::*;
+
+mod moc {
+    use super::*;
+
+    #[test]
+    fn creates_an_index_note() {
+        Obx::from_command("moc folder")
+            .assert_stdout("Created folder/folder.md listing 1 note\n")
+            .assert_content(
+                "main-vault/folder/folder.md",
+                indoc! {"
+                    # folder
+
+                    <!-- obx moc start -->
+                    - [[child-note]]
+                    <!-- obx moc end -->
+                "},
+            );
+    }
+
+    #[test]
+    fn keeps_what_was_written_by_hand() {
+        let cmd = Obx::from_command("moc folder --name Index --group-by folder");
+        cmd.temp_dir
+            .child("main-vault/folder/Index.md")
+            .write_str(indoc! {"
+                # Index
+
+                <!-- obx moc start -->
+                - [[gone]]
+                <!-- obx moc end -->
+
+                ## By hand
+            "})
+            .unwrap();
+        cmd.temp_dir
+            .child("main-vault/folder/sub/grandchild.md")
+            .write_str("")
+            .unwrap();
+
+        cmd.assert_stdout("Updated folder/Index.md listing 2 notes\n")
+            .assert_content(
+                "main-vault/folder/Index.md",
+                indoc! {"
+                    # Index
+
+                    <!-- obx moc start -->
+                    - [[child-note]]
+
+                    ## sub
+
+                    - [[grandchild]]
+                    <!-- obx moc end -->
+
+                    ## By hand
+                "},
+            );
+    }
+
+    #[test]
+    fn refuses_paths_outside_the_vault() {
+        Obx::from_command("moc ../elsewhere")
+            .assert_stderr("Invalid folder `../elsewhere`, expected one within the vault\n");
+        let cmd = Obx::from_command("moc folder --name ../../escaped")
+            .assert_stderr("Invalid name `../../escaped`, expected one within the folder\n");
+        cmd.temp_dir
+            .child("escaped.md")
+            .assert(predicates::path::missing());
+    }
+
+    #[test]
+    fn fails_for_missing_folders() {
+        Obx::from_command("moc nowhere")
+            .assert_stderr("Folder `nowhere` not found in vault `main`\n");
+    }
+}
@@ -0,0 +1,78 @@
+use assert_fs::prelude::*;
+use indoc::indoc;
+use predicates::prelude::*;
+mod utils;
+use utils::*;
+
+mod move_notes {
+    use super::*;
+
+    #[test]
+    fn moves_notes_and_fixes_relative_links() {
+        let cmd = Obx::from_command("move folder/child-note archive");
+        cmd.temp_dir
+            .child("main-vault/folder/sibling.md")
+            .write_str("[child](child-note.md) and [[child-note]]")
+            .unwrap();
+        cmd.temp_dir
+            .child("main-vault/folder/child-note.md")
+            .write_str("Up to [simple](../simple-note.md), across to [sibling](sibling.md)")
+            .unwrap();
+
+        let cmd = cmd.assert_stdout(indoc! {"
+            Moved folder/child-note.md to archive/child-note.md
+            Updated 1 reference in folder/child-note.md
+            Updated 1 reference in folder/sibling.md
+        "});
+
+        cmd.temp_dir
+            .child("main-vault/archive/child-note.md")
+            .assert(predicate::str::diff(
+                "Up to [simple](../simple-note.md), across to [sibling](../folder/sibling.md)",
+            ));
+        cmd.temp_dir
+            .child("main-vault/folder/sibling.md")
+            .assert(predicate::str::diff(
+                "[child](../archive/child-note.md) and [[child-note]]",
+            ));
+    }
+
+    #[test]
+    fn moves_notes_matching_glob() {
+        let cmd = Obx::from_command("move *-note.md archive --dry-run").assert_stdout(indoc! {"
+            Would move complex-note.md to archive/complex-note.md
+            Would move empty-note.md to archive/empty-note.md
+            Would move simple-note.md to archive/simple-note.md
+        "});
+
+        cmd.temp_dir
+            .child("main-vault/archive")
+            .assert(predicate::path::missing());
+    }
+
+    #[test]
+    fn rewrites_path_style_wikilinks() {
+        let cmd = Obx::from_command("move simple-note folder");
+        cmd.temp_dir
+            .child("main-vault/path-style.md")
+            .write_str("[[simple-note]] and [[./simple-note|label]]")
+            .unwrap();
+
+        let cmd = cmd.assert_stdout(indoc! {"
+            Moved simple-note.md to folder/simple-note.md
+            Updated 1 reference in path-style.md
+        "});
+
+        cmd.temp_dir
+            .child("main-vault/path-style.md")
+            .assert(predicate::str::diff(
+                "[[simple-note]] and [[folder/simple-note|label]]",
+            ));
+    }
+
+    #[test]
+    fn errors_when_glob_matches_nothing() {
+        Obx::from_command("move nothing-*.md archive")
+            .assert_stderr("No notes matching `nothing-*.md` found in vault\n");
+    }
+}
@@ -0,0 +1,104 @@
+use assert_fs::prelude::*;
+use indoc::indoc;
+use predicates::prelude::*;
+mod utils;
+use utils::*;
+
+fn with_project_folder(command: &str) -> Obx {
+    let cmd = Obx::from_command(command);
+    cmd.temp_dir
+        .child("main-vault/projects/alpha.md")
+        .write_str("[beta](beta.md), [home](../simple-note.md) and ![diagram](img/diagram.png)")
+        .unwrap();
+    cmd.temp_dir
+        .child("main-vault/projects/beta.md")
+        .write_str("[[projects/alpha]]")
+        .unwrap();
+    cmd.temp_dir
+        .child("main-vault/projects/img/diagram.png")
+        .write_str("")
+        .unwrap();
+    cmd.temp_dir
+        .child("main-vault/index.md")
+        .write_str(
+            "[[projects/alpha]] [alpha](projects/alpha.md) [[beta]] ![[projects/img/diagram.png]]",
+        )
+        .unwrap();
+
+    cmd
+}
+
+mod move_folder {
+    use super::*;
+
+    #[test]
+    fn moves_the_folder_and_rewrites_links() {
+        let cmd = with_project_folder("move-folder projects archive/2024/projects").assert_stdout(
+            indoc! {"
+                Moved projects to archive/2024/projects
+                Updated 3 references in index.md
+                Updated 1 reference in projects/alpha.md
+                Updated 1 reference in projects/beta.md
+            "},
+        );
+
+        cmd.temp_dir
+            .child("main-vault/projects")
+            .assert(predicate::path::missing());
+        cmd.temp_dir
+            .child("main-vault/archive/2024/projects/img/diagram.png")
+            .assert(predicate::path::exists());
+        cmd.temp_dir
+            .child("main-vault/archive/2024/projects/alpha.md")
+            .assert(
+                "[beta](beta.md), [home](../../../simple-note.md) and ![diagram](img/diagram.png)",
+            );
+        cmd.temp_dir
+            .child("main-vault/archive/2024/projects/beta.md")
+            .assert("[[archive/2024/projects/alpha]]");
+        cmd.temp_dir.child("main-vault/index.md").assert(
+            "[[archive/2024/projects/alpha]] [alpha](archive/2024/projects/alpha.md) [[beta]] ![[archive/2024/projects/img/diagram.png]]",
+        );
+    }
+
+    #[test]
+    fn dry_run_changes_nothing() {
+        let cmd =
+            with_project_folder("move-folder projects archive --dry-run").assert_stdout(indoc! {"
+                Would move projects to archive
+                Would update 3 references in index.md
+                Would update 1 reference in projects/beta.md
+            "});
+
+        cmd.temp_dir
+            .child("main-vault/archive")
+            .assert(predicate::path::missing());
+        cmd.temp_dir
+            .child("main-vault/projects/beta.md")
+            .assert("[[projects/alpha]]");
+    }
+
+    #[test]
+    fn refuses_an_existing_destination() {
+        with_project_folder("move-folder projects folder")
+            .assert_stderr("`folder` already exists\n");
+    }
+
+    #[test]
+    fn refuses_folders_outside_the_vault() {
+        let cmd = with_project_folder("move-folder projects ../elsewhere")
+            .assert_stderr("Invalid folder `../elsewhere`, expected one within the vault\n");
+        cmd.temp_dir
+            .child("main-vault/projects")
+            .assert(predicate::path::is_dir());
+
+        with_project_folder("move-folder /tmp archive")
+            .assert_stderr("Invalid folder `/tmp`, expected one within the vault\n");
+    }
+
+    #[test]
+    fn refuses_to_move_into_itself() {
+        with_project_folder("move-folder projects projects/old")
+            .assert_stderr("Can't move `projects` into itself\n");
+    }
+}
@@ -1,5 +1,5 @@
 use assert_fs::prelude::*;
-use indoc::indoc;
+use indoc::{formatdoc, indoc};
 mod utils;
 use predicates::prelude::*;
 use serde_json::json;
@@ -78,6 +78,47 @@ mod notes {
             " });
         }
 
+        #[test]
+        fn skips_obxignore_paths() {
+            let cmd = Obx::from_command("notes list -f json");
+            cmd.temp_dir
+                .child("main-vault/.obxignore")
+                .write_str("folder/\n*-note.md\n!simple-note.md\n")
+                .unwrap();
+
+            cmd.assert_stdout(
+                "[\"html.md\",\"link-types.md\",\"simple-note.md\",\"table.md\",\"with-fm-properties.md\"]\n",
+            );
+        }
+
+        #[test]
+        fn skips_notes_outside_scan_limits() {
+            let cmd = Obx::from_command("notes list -f json");
+            let dir = cmd.temp_dir.display().to_string();
+            cmd.temp_dir
+                .child("main-vault/export.md")
+                .write_str(&"x".repeat(2048))
+                .unwrap();
+            cmd.temp_dir
+                .child("main-vault/binary.md")
+                .write_binary(b"PK\0\x03")
+                .unwrap();
+
+            let _ = &cmd
+                .with_config_file(&formatdoc! {"
+                    current_vault: main
+                    vaults:
+                    - name: main
+                      path: {dir}/main-vault/
+                    scan:
+                      max_file_size: 1KB
+                      skip_binary: true
+                "})
+                .assert_stdout(
+                    "[\"complex-note.md\",\"empty-note.md\",\"folder/child-note.md\",\"html.md\",\"link-types.md\",\"simple-note.md\",\"table.md\",\"with-fm-properties.md\"]\n",
+                );
+        }
+
         #[test]
         fn errors_when_folder_missing() {
             Obx::from_command("notes list missing-folder")
@@ -215,6 +256,51 @@ mod notes {
             created_file.assert(predicate::str::diff(file_content));
         }
 
+        #[test]
+        fn adds_tags_to_frontmatter() {
+            let json_content = r#"{"tags": ["test"]}"#;
+            let cmd = Obx::from_command("notes create tagged --tag #project --tag test");
+
+            let mut wrapped_cmd = assert_cmd::Command::from(cmd.cmd);
+            wrapped_cmd.write_stdin(json_content);
+            let _ = &wrapped_cmd.assert().success();
+
+            cmd.temp_dir
+                .child("main-vault/tagged.md")
+                .assert(predicate::str::diff(indoc! {"
+                    ---
+                    tags:
+                    - test
+                    - project
+                    ---
+                "}));
+        }
+
+        #[test]
+        fn adds_tags_to_template() {
+            let cmd = Obx::from_command("notes create task --template task --tag doing");
+            cmd.temp_dir
+                .child("main-vault/templates/task.md")
+                .write_str("---\ntags: [todo]\n---\n# {{title}}\n")
+                .unwrap();
+
+            let _ = &cmd.assert_content(
+                "main-vault/task.md",
+                "---\ntags: [\"todo\",\"doing\"]\n---\n# task\n",
+            );
+        }
+
+        #[test]
+        fn uses_obsidian_new_note_folder() {
+            let cmd = Obx::from_command("notes create new-note");
+            cmd.temp_dir
+                .child("main-vault/.obsidian/app.json")
+                .write_str(r#"{"newFileLocation": "folder", "newFileFolderPath": "Inbox"}"#)
+                .unwrap();
+
+            cmd.assert_created("main-vault/Inbox/new-note.md");
+        }
+
         #[test]
         fn allows_specifying_vault() {
             Obx::from_command("notes create created-in-another-vault --vault=secondary")
@@ -228,6 +314,101 @@ mod notes {
                 .assert_stderr("The note simple-node.md already exists");
         }
 
+        #[test]
+        fn fills_in_template() {
+            let cmd = Obx::from_command(
+                "notes create projects/apollo --template project --var owner=Sam",
+            );
+            cmd.temp_dir
+                .child("main-vault/templates/project.md")
+                .write_str(
+                    "---\nowner: {{owner}}\n---\n# {{title}}\n\nIn {{folder}}, {{unknown}}\n",
+                )
+                .unwrap();
+
+            let _ = &cmd.assert_content(
+                "main-vault/projects/apollo.md",
+                "---\nowner: Sam\n---\n# apollo\n\nIn projects, {{unknown}}\n",
+            );
+        }
+
+        #[test]
+        fn uses_configured_date_format_in_template() {
+            let cmd = Obx::from_command("notes create log --template log");
+            let dir = cmd.temp_dir.display().to_string();
+            cmd.temp_dir
+                .child("main-vault/templates/log.md")
+                .write_str("{{date}}, {{time:[at noon]}}")
+                .unwrap();
+
+            let _ = &cmd
+                .with_config_file(&formatdoc! {"
+                    current_vault: main
+                    vaults:
+                    - name: main
+                      path: {dir}/main-vault/
+                    dates:
+                      date: '[logged]'
+                "})
+                .assert_content("main-vault/log.md", "logged, at noon");
+        }
+
+        fn with_zettel_format(cmd: Obx) -> Obx {
+            let dir = cmd.temp_dir.display().to_string();
+            cmd.with_config_file(&formatdoc! {"
+                current_vault: main
+                vaults:
+                - name: main
+                  path: {dir}/main-vault/
+                zettel:
+                  id_format: '[zk]'
+                  separator: '-'
+            "})
+        }
+
+        #[test]
+        fn prefixes_zettel_ids() {
+            with_zettel_format(Obx::from_command("notes create ideas/atomic --zettel"))
+                .assert_created("main-vault/ideas/zk-atomic.md");
+        }
+
+        #[test]
+        fn refuses_duplicate_zettel_ids() {
+            let cmd = Obx::from_command("notes create atomic --zettel");
+            cmd.temp_dir
+                .child("main-vault/zk-older.md")
+                .touch()
+                .unwrap();
+
+            with_zettel_format(cmd)
+                .assert_stderr("A note with the ID zk already exists: zk-older.md\n");
+        }
+
+        #[test]
+        fn merges_json_stdin_into_template() {
+            let cmd = Obx::from_command("notes create task --template task");
+            cmd.temp_dir
+                .child("main-vault/templates/task.md")
+                .write_str("---\nstatus: todo\n---\n# {{title}}\n")
+                .unwrap();
+
+            let mut wrapped_cmd = assert_cmd::Command::from(cmd.cmd);
+            wrapped_cmd.write_stdin(r#"{"status": "doing", "tags": ["a"]}"#);
+            let _ = &wrapped_cmd.assert().success();
+
+            cmd.temp_dir
+                .child("main-vault/task.md")
+                .assert(predicate::str::diff(
+                    "---\nstatus: \"doing\"\ntags: [\"a\"]\n---\n# task\n",
+                ));
+        }
+
+        #[test]
+        fn fails_for_missing_template() {
+            Obx::from_command("notes create new-note --template nope")
+                .assert_stderr("Template `nope` not found in templates\n");
+        }
+
         #[test]
         fn opens_editor() {
             let cmd = Obx::from_command("notes create new-note.md")
@@ -317,7 +498,7 @@ mod notes {
     }
 
     mod open {
-        // These are wrappers around `notes uri`,
+        // These are aliases for `obx uri --open`
         use super::*;
 
         #[test]
@@ -339,24 +520,21 @@ mod notes {
 
         #[test]
         fn prints_uri_with_current_vault() {
-            let cmd = Obx::from_command("notes uri simple-note.md");
-            let expected_path = cmd.temp_dir.child("main-vault/simple-note.md");
-            let expected_uri = format!(
-                "obsidian://open?vault=main&file={}\n",
-                expected_path.display()
-            );
-            cmd.assert_stdout(expected_uri);
+            Obx::from_command("notes uri simple-note.md")
+                .assert_stdout("obsidian://open?vault=main-vault&file=simple-note.md\n");
         }
 
         #[test]
         fn prints_uri_with_specified_vault() {
-            let cmd = Obx::from_command("notes uri from-another-vault.md --vault=secondary");
-            let expected_path = cmd.temp_dir.child("another/path/from-another-vault.md");
-            let expected_uri = format!(
-                "obsidian://open?vault=secondary&file={}\n",
-                expected_path.display()
-            );
-            cmd.assert_stdout(expected_uri);
+            // The app knows the vault by its folder's name, `path`
+            Obx::from_command("notes uri from-another-vault.md --vault=secondary")
+                .assert_stdout("obsidian://open?vault=path&file=from-another-vault.md\n");
+        }
+
+        #[test]
+        fn encodes_nested_paths() {
+            Obx::from_command("notes uri folder/child-note.md")
+                .assert_stdout("obsidian://open?vault=main-vault&file=folder%2Fchild-note.md\n");
         }
     }
 
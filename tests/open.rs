@@ -0,0 +1,110 @@
+use assert_fs::prelude::*;
+mod utils;
+use predicates::prelude::*;
+use utils::*;
+
+mod open {
+    use super::*;
+
+    #[test]
+    fn opens_note_by_exact_name() {
+        let cmd = Obx::from_command("open child-note")
+            .with_editor(r#"echo "This was appended by \$EDITOR" >> "$1""#);
+
+        let edit_file = &cmd.temp_dir.child("main-vault/folder/child-note.md");
+
+        let _ = &cmd.assert_success();
+        edit_file.assert(predicate::str::contains("This was appended by $EDITOR"));
+    }
+
+    #[test]
+    fn opens_note_by_path() {
+        let cmd = Obx::from_command("open folder/child-note.md")
+            .with_editor(r#"echo "This was appended by \$EDITOR" >> "$1""#);
+
+        let edit_file = &cmd.temp_dir.child("main-vault/folder/child-note.md");
+
+        let _ = &cmd.assert_success();
+        edit_file.assert(predicate::str::contains("This was appended by $EDITOR"));
+    }
+
+    #[test]
+    fn opens_single_fuzzy_match() {
+        let cmd = Obx::from_command("open wfmprop")
+            .with_editor(r#"echo "This was appended by \$EDITOR" >> "$1""#);
+
+        let edit_file = &cmd.temp_dir.child("main-vault/with-fm-properties.md");
+
+        let _ = &cmd.assert_success();
+        edit_file.assert(predicate::str::contains("This was appended by $EDITOR"));
+    }
+
+    #[test]
+    fn opens_note_by_zettel_id() {
+        let cmd = Obx::from_command("open 202405101230")
+            .with_editor(r#"echo "This was appended by \$EDITOR" >> "$1""#);
+        let edit_file = cmd
+            .temp_dir
+            .child("main-vault/folder/202405101230 Atomic notes.md");
+        edit_file.touch().unwrap();
+
+        let _ = &cmd.assert_success();
+        edit_file.assert(predicate::str::contains("This was appended by $EDITOR"));
+    }
+
+    /// An editor script that logs the arguments it was called with
+    fn logging_editor(cmd: &Obx) -> String {
+        let dir = cmd.temp_dir.display();
+        create_script(
+            "logging_editor.sh",
+            format!("echo \"$@\" > {dir}/editor.log"),
+            &cmd.temp_dir,
+        )
+        .display()
+        .to_string()
+    }
+
+    #[test]
+    fn fills_in_editor_placeholders() {
+        let mut cmd = Obx::from_command("open child-note --line 7");
+        let editor = logging_editor(&cmd);
+        cmd.env(
+            "EDITOR",
+            format!("{editor} +{{line}} --root {{vault}} {{file}}"),
+        );
+        let dir = cmd.temp_dir.display().to_string();
+
+        let cmd = cmd.assert_success();
+        cmd.temp_dir.child("editor.log").assert(format!(
+            "+7 --root {dir}/main-vault/ {dir}/main-vault/folder/child-note.md\n"
+        ));
+    }
+
+    #[test]
+    fn passes_editor_arguments_before_the_file() {
+        let cmd = Obx::from_command("open simple-note");
+        let editor = logging_editor(&cmd);
+        let dir = cmd.temp_dir.display().to_string();
+
+        let cmd = cmd
+            .with_config_file(&format!(
+                "current_vault: main\nvaults:\n- name: main\n  path: {dir}/main-vault\neditor: {editor} --wait\n"
+            ))
+            .assert_success();
+        cmd.temp_dir
+            .child("editor.log")
+            .assert(format!("--wait {dir}/main-vault/simple-note.md\n"));
+    }
+
+    #[test]
+    fn lists_ambiguous_matches_when_non_interactive() {
+        Obx::from_command("open le-note").assert_stderr(
+            "Multiple notes match `le-note`:\nsimple-note.md\ncomplex-note.md\nfolder/child-note.md\n",
+        );
+    }
+
+    #[test]
+    fn fails_when_nothing_matches() {
+        Obx::from_command("open zzz").assert_stderr("No note matching `zzz` found in vault\n");
+    }
+}
@@ -0,0 +1,22 @@
+use assert_fs::prelude::*;
+use indoc::indoc;
+mod utils;
+use utils::*;
+
+mod orphans {
+    use super::*;
+
+    #[test]
+    fn lists_notes_without_links() {
+        let cmd = Obx::from_command("orphans");
+        cmd.temp_dir
+            .child("main-vault/hub.md")
+            .write_str("[[simple-note]] [[table]] [[html]] [[empty-note]] [[child-note]]")
+            .unwrap();
+
+        cmd.assert_stdout(indoc! {"
+            link-types.md
+            with-fm-properties.md
+        "});
+    }
+}
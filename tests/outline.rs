@@ -0,0 +1,53 @@
+use assert_fs::prelude::*;
+use indoc::indoc;
+mod utils;
+use utils::*;
+
+mod outline {
+    use super::*;
+
+    fn with_long_note(cmd: Obx) -> Obx {
+        cmd.temp_dir
+            .child("main-vault/long.md")
+            .write_str(indoc! {"
+                ---
+                title: Long
+                ---
+                # Long
+                Intro
+                ## Setup
+                Install it first
+                ### On Linux
+                ## Usage
+            "})
+            .unwrap();
+        cmd
+    }
+
+    #[test]
+    fn prints_the_heading_tree() {
+        with_long_note(Obx::from_command("outline long --words")).assert_stdout(indoc! {"
+            # Long (4 words)
+              ## Setup (3 words)
+                ### On Linux (0 words)
+              ## Usage (0 words)
+        "});
+    }
+
+    #[test]
+    fn stops_at_the_depth() {
+        with_long_note(Obx::from_command("outline long --depth 2")).assert_stdout(indoc! {"
+            # Long
+              ## Setup
+              ## Usage
+        "});
+    }
+
+    #[test]
+    fn prints_json() {
+        with_long_note(Obx::from_command("outline long -d 1 --json")).assert_stdout(concat!(
+            r#"[{"level":1,"text":"Long","line":1,"words":4,"children":[]}]"#,
+            "\n"
+        ));
+    }
+}
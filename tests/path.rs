@@ -0,0 +1,52 @@
+use assert_fs::prelude::*;
+mod utils;
+use utils::*;
+
+fn with_linked_notes(command: &str) -> Obx {
+    let cmd = Obx::from_command(command);
+    cmd.temp_dir
+        .child("main-vault/hub.md")
+        .write_str("Links to [[spoke]] and [[Work/task]]")
+        .unwrap();
+    cmd.temp_dir
+        .child("main-vault/spoke.md")
+        .write_str("[[rim]] and back to [[hub]]")
+        .unwrap();
+    cmd.temp_dir
+        .child("main-vault/rim.md")
+        .write_str("")
+        .unwrap();
+    cmd.temp_dir
+        .child("main-vault/Work/task.md")
+        .write_str("")
+        .unwrap();
+
+    cmd
+}
+
+mod path {
+    use super::*;
+
+    #[test]
+    fn follows_links() {
+        with_linked_notes("path hub rim").assert_stdout("hub.md → spoke.md → rim.md\n");
+    }
+
+    #[test]
+    fn follows_links_backwards_when_undirected() {
+        with_linked_notes("path rim Work/task --undirected")
+            .assert_stdout("rim.md ← spoke.md → hub.md → Work/task.md\n");
+    }
+
+    #[test]
+    fn fails_without_a_chain() {
+        with_linked_notes("path rim hub")
+            .assert_stderr("No chain of links from rim.md to hub.md\n");
+    }
+
+    #[test]
+    fn stops_at_the_max_depth() {
+        with_linked_notes("path hub rim --max-depth 1")
+            .assert_stderr("No chain of links from hub.md to rim.md within 1 link\n");
+    }
+}
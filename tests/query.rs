@@ -0,0 +1,100 @@
+use assert_fs::prelude::*;
+use indoc::indoc;
+mod utils;
+use utils::*;
+
+fn with_task_notes(command: &str) -> Obx {
+    let cmd = Obx::from_command(command);
+    cmd.temp_dir
+        .child("main-vault/tasks/write-docs.md")
+        .write_str("---\nstatus: active\ndue: 2024-11-01\ntags: [work]\n---\nWrite the docs")
+        .unwrap();
+    cmd.temp_dir
+        .child("main-vault/tasks/ship-it.md")
+        .write_str("---\nstatus: active\ndue: 2025-03-01\n---\nShip it")
+        .unwrap();
+    cmd.temp_dir
+        .child("main-vault/tasks/old.md")
+        .write_str("---\nstatus: done\ndue: 2024-01-01\n---\nDone already")
+        .unwrap();
+
+    cmd
+}
+
+mod query {
+    use super::*;
+
+    #[test]
+    fn lists_matching_notes() {
+        let _ =
+            &with_task_notes("query status==\"active\"&&due<2025-01-01").assert_stdout(indoc! {"
+            ┌─────────────────────┐
+            │ Note                │
+            ├─────────────────────┤
+            │ tasks/write-docs.md │
+            └─────────────────────┘
+        "});
+    }
+
+    #[test]
+    fn shows_selected_fields() {
+        let _ =
+            &with_task_notes("query status==\"active\" --select due,tags").assert_stdout(indoc! {"
+            ┌─────────────────────┬────────────┬──────┐
+            │ Note                │ due        │ tags │
+            ├─────────────────────┼────────────┼──────┤
+            │ tasks/ship-it.md    │ 2025-03-01 │      │
+            │ tasks/write-docs.md │ 2024-11-01 │ work │
+            └─────────────────────┴────────────┴──────┘
+        "});
+    }
+
+    #[test]
+    fn reads_inline_fields() {
+        let cmd = with_task_notes("query rating>=4 --select rating,due");
+        cmd.temp_dir
+            .child("main-vault/books/dune.md")
+            .write_str("Rating:: 5\n\nFinished it, [due:: 2024-02-01]")
+            .unwrap();
+        cmd.temp_dir
+            .child("main-vault/books/other.md")
+            .write_str("Rating:: 3")
+            .unwrap();
+
+        cmd.assert_stdout(indoc! {"
+            ┌───────────────┬────────┬────────────┐
+            │ Note          │ rating │ due        │
+            ├───────────────┼────────┼────────────┤
+            │ books/dune.md │ 5      │ 2024-02-01 │
+            └───────────────┴────────┴────────────┘
+        "});
+    }
+
+    #[test]
+    fn outputs_json() {
+        Obx::from_command("query test-number>50 --select test-list,test-checkbox -f json")
+            .assert_stdout(
+                "[{\"path\":\"with-fm-properties.md\",\"test-list\":[\"One\",\"Two\"],\"test-checkbox\":true}]\n",
+            );
+    }
+
+    #[test]
+    fn outputs_json_for_tagged_values() {
+        let cmd = Obx::from_command("query status==\"tagged\" --select x -f json");
+        cmd.temp_dir
+            .child("main-vault/tagged.md")
+            .write_str("---\nstatus: tagged\nx: !custom y\n---\n")
+            .unwrap();
+        cmd.assert_stdout("[{\"path\":\"tagged.md\",\"x\":\"y\"}]\n");
+    }
+
+    #[test]
+    fn prints_nothing_without_matches() {
+        Obx::from_command("query status==\"missing\"").assert_stdout("");
+    }
+
+    #[test]
+    fn rejects_invalid_queries() {
+        Obx::from_command("query status==").assert_stderr("Unexpected end of query\n");
+    }
+}
@@ -0,0 +1,52 @@
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+mod utils;
+use utils::*;
+
+mod random {
+    use super::*;
+
+    #[test]
+    fn picks_a_note() {
+        let mut cmd = Obx::from_command("random");
+        let output = cmd.cmd.output().unwrap();
+        let picked = String::from_utf8(output.stdout).unwrap();
+
+        assert!(output.status.success());
+        cmd.temp_dir
+            .child(format!("main-vault/{}", picked.trim()))
+            .assert(predicate::path::is_file());
+    }
+
+    #[test]
+    fn picks_from_folder() {
+        Obx::from_command("random --folder folder").assert_stdout("folder/child-note.md\n");
+    }
+
+    #[test]
+    fn picks_from_tag() {
+        let cmd = Obx::from_command("random --tag resurface");
+        cmd.temp_dir
+            .child("main-vault/tagged.md")
+            .write_str("A note to #resurface")
+            .unwrap();
+
+        let _ = &cmd.assert_stdout("tagged.md\n");
+    }
+
+    #[test]
+    fn opens_in_editor() {
+        let cmd = Obx::from_command("random --folder folder --open")
+            .with_editor(r#"echo "opened" >> "$1""#)
+            .assert_success();
+
+        cmd.temp_dir
+            .child("main-vault/folder/child-note.md")
+            .assert(predicate::str::ends_with("opened\n"));
+    }
+
+    #[test]
+    fn errors_without_candidates() {
+        Obx::from_command("random --tag missing").assert_stderr("No notes to pick from\n");
+    }
+}
@@ -0,0 +1,56 @@
+use filetime::{set_file_mtime, FileTime};
+use indoc::indoc;
+use std::time::{Duration, SystemTime};
+use walkdir::WalkDir;
+mod utils;
+use utils::*;
+
+/// Age every fixture by a day, then make a few notes look recently touched
+fn with_modified_notes(command: &str) -> Obx {
+    let cmd = Obx::from_command(command);
+    let now = SystemTime::now();
+    let ago = |seconds: u64| FileTime::from_system_time(now - Duration::from_secs(seconds));
+
+    for entry in WalkDir::new(cmd.temp_dir.join("main-vault")) {
+        set_file_mtime(entry.unwrap().path(), ago(10 * 24 * 60 * 60)).unwrap();
+    }
+    set_file_mtime(cmd.temp_dir.join("main-vault/simple-note.md"), ago(5 * 60)).unwrap();
+    set_file_mtime(cmd.temp_dir.join("main-vault/table.md"), ago(3 * 60 * 60)).unwrap();
+    set_file_mtime(
+        cmd.temp_dir.join("main-vault/folder/child-note.md"),
+        ago(2 * 24 * 60 * 60),
+    )
+    .unwrap();
+
+    cmd
+}
+
+mod recent {
+    use super::*;
+
+    #[test]
+    fn lists_most_recent_first() {
+        let _ = &with_modified_notes("recent 3").assert_stdout(indoc! {"
+            ┌──────────────────────┬───────────────┐
+            │ Note                 │ Modified      │
+            ├──────────────────────┼───────────────┤
+            │ simple-note.md       │ 5 minutes ago │
+            │ table.md             │ 3 hours ago   │
+            │ folder/child-note.md │ 2 days ago    │
+            └──────────────────────┴───────────────┘
+        "});
+    }
+
+    #[test]
+    fn filters_by_since() {
+        let _ = &with_modified_notes("recent --since 1d -f paths").assert_stdout(indoc! {"
+            simple-note.md
+            table.md
+        "});
+    }
+
+    #[test]
+    fn prints_nothing_when_nothing_is_recent() {
+        Obx::from_command("recent --since 2999-01-01").assert_stdout("");
+    }
+}
@@ -0,0 +1,62 @@
+use assert_fs::prelude::*;
+mod utils;
+use utils::*;
+
+mod related {
+    use super::*;
+
+    #[test]
+    fn ranks_notes_by_words_tags_and_links() {
+        let cmd = Obx::from_command("related garden -n 2 -f paths");
+        cmd.temp_dir
+            .child("main-vault/garden.md")
+            .write_str("#plants\n\nTomatoes and basil grow in the raised garden beds.")
+            .unwrap();
+        cmd.temp_dir
+            .child("main-vault/beds.md")
+            .write_str("Raised garden beds for tomatoes and basil.")
+            .unwrap();
+        cmd.temp_dir
+            .child("main-vault/seeds.md")
+            .write_str("#plants\n\nOrdering seeds.")
+            .unwrap();
+
+        cmd.assert_stdout("beds.md\nseeds.md\n");
+    }
+
+    #[test]
+    fn counts_links_between_notes() {
+        let mut cmd = Obx::from_command("related simple-note -f paths");
+        let output = cmd.cmd.output().unwrap();
+        let stdout = String::from_utf8(output.stdout).unwrap();
+
+        assert!(output.status.success());
+        assert!(stdout.lines().any(|line| line == "complex-note.md"));
+    }
+
+    #[test]
+    fn explains_why_notes_are_related() {
+        let cmd = Obx::from_command("related a");
+        cmd.temp_dir
+            .child("main-vault/a.md")
+            .write_str("#topic [[b]]")
+            .unwrap();
+        cmd.temp_dir
+            .child("main-vault/b.md")
+            .write_str("#topic")
+            .unwrap();
+
+        cmd.assert_stdout_contains("#topic, linked");
+    }
+
+    #[test]
+    fn prints_nothing_without_related_notes() {
+        let cmd = Obx::from_command("related lonely");
+        cmd.temp_dir
+            .child("main-vault/lonely.md")
+            .write_str("Xylophones quietly zigzag.")
+            .unwrap();
+
+        cmd.assert_stdout("");
+    }
+}
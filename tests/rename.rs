@@ -0,0 +1,90 @@
+use assert_fs::prelude::*;
+use indoc::indoc;
+use predicates::prelude::*;
+mod utils;
+use utils::*;
+
+fn with_linking_notes(command: &str) -> Obx {
+    let cmd = Obx::from_command(command);
+    cmd.temp_dir
+        .child("main-vault/folder/linking.md")
+        .write_str("[[simple-note#Heading|label]], [md](../simple-note.md) and [[child-note]]")
+        .unwrap();
+    cmd.temp_dir
+        .child("main-vault/path-style.md")
+        .write_str("![[folder/child-note.md]]")
+        .unwrap();
+
+    cmd
+}
+
+mod rename {
+    use super::*;
+
+    #[test]
+    fn renames_and_rewrites_links() {
+        let cmd = with_linking_notes("rename simple-note renamed").assert_stdout(indoc! {"
+            Renamed simple-note.md to renamed.md
+            Updated 1 reference in complex-note.md
+            Updated 2 references in folder/linking.md
+        "});
+
+        cmd.temp_dir
+            .child("main-vault/simple-note.md")
+            .assert(predicate::path::missing());
+        cmd.temp_dir
+            .child("main-vault/renamed.md")
+            .assert(predicate::path::exists());
+        cmd.temp_dir
+            .child("main-vault/complex-note.md")
+            .assert(predicate::str::contains("[[renamed|link to a note]]"));
+        cmd.temp_dir
+            .child("main-vault/folder/linking.md")
+            .assert(predicate::str::diff(
+                "[[renamed#Heading|label]], [md](../renamed.md) and [[child-note]]",
+            ));
+    }
+
+    #[test]
+    fn keeps_path_style_links() {
+        let cmd = with_linking_notes("rename folder/child-note kid").assert_stdout(indoc! {"
+            Renamed folder/child-note.md to folder/kid.md
+            Updated 1 reference in folder/linking.md
+            Updated 1 reference in path-style.md
+        "});
+
+        cmd.temp_dir
+            .child("main-vault/path-style.md")
+            .assert(predicate::str::diff("![[folder/kid.md]]"));
+    }
+
+    #[test]
+    fn dry_run_changes_nothing() {
+        let cmd =
+            with_linking_notes("rename simple-note renamed --dry-run").assert_stdout(indoc! {"
+            Would rename simple-note.md to renamed.md
+            Would update 1 reference in complex-note.md
+            Would update 2 references in folder/linking.md
+        "});
+
+        cmd.temp_dir
+            .child("main-vault/simple-note.md")
+            .assert(predicate::path::exists());
+        cmd.temp_dir
+            .child("main-vault/complex-note.md")
+            .assert(predicate::str::contains("[[simple-note |link to a note]]"));
+    }
+
+    #[test]
+    fn refuses_to_overwrite() {
+        Obx::from_command("rename simple-note complex-note").assert_stderr(
+            "Can't move simple-note.md to complex-note.md, a file already exists there\n",
+        );
+    }
+
+    #[test]
+    fn rejects_folders_in_name() {
+        Obx::from_command("rename simple-note folder/renamed")
+            .assert_stderr("Invalid note name `folder/renamed`, names can't contain `/`\n");
+    }
+}
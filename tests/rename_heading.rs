@@ -0,0 +1,86 @@
+use assert_fs::prelude::*;
+use indoc::indoc;
+mod utils;
+use utils::*;
+
+mod rename_heading {
+    use super::*;
+
+    fn write_notes(cmd: &Obx) {
+        cmd.temp_dir
+            .child("main-vault/guide.md")
+            .write_str(indoc! {"
+                # Guide
+                ## Setup ##
+                See [[#Setup]].
+            "})
+            .unwrap();
+        cmd.temp_dir
+            .child("main-vault/readme.md")
+            .write_str(indoc! {"
+                [[guide#Setup|Setting up]] and [steps](guide.md#Guide#Setup)
+                [[guide#Guide]] [[other#Setup]] [[guide#Setup again]]
+            "})
+            .unwrap();
+    }
+
+    #[test]
+    fn renames_the_heading_only() {
+        let cmd = Obx::from_command("rename-heading guide.md setup Installing");
+        write_notes(&cmd);
+
+        let cmd = cmd.assert_stdout("Renamed heading `Setup` to `Installing` in guide.md\n");
+        cmd.temp_dir.child("main-vault/guide.md").assert(indoc! {"
+            # Guide
+            ## Installing
+            See [[#Setup]].
+        "});
+        cmd.temp_dir
+            .child("main-vault/readme.md")
+            .assert(predicates::str::contains("[[guide#Setup|Setting up]]"));
+    }
+
+    #[test]
+    fn updates_links_into_the_heading() {
+        let cmd = Obx::from_command("rename-heading guide.md Setup Installing --update-links");
+        write_notes(&cmd);
+
+        let cmd = cmd.assert_stdout(indoc! {"
+            Renamed heading `Setup` to `Installing` in guide.md
+            Updated 1 link in guide.md
+            Updated 2 links in readme.md
+        "});
+        cmd.temp_dir.child("main-vault/guide.md").assert(indoc! {"
+            # Guide
+            ## Installing
+            See [[#Installing]].
+        "});
+        cmd.temp_dir.child("main-vault/readme.md").assert(indoc! {"
+            [[guide#Installing|Setting up]] and [steps](guide.md#Guide#Installing)
+            [[guide#Guide]] [[other#Setup]] [[guide#Setup again]]
+        "});
+    }
+
+    #[test]
+    fn dry_run_changes_nothing() {
+        let cmd = Obx::from_command("rename-heading guide Setup Installing -u --dry-run");
+        write_notes(&cmd);
+
+        let cmd = cmd.assert_stdout(indoc! {"
+            Would rename heading `Setup` to `Installing` in guide.md
+            Would update 1 link in guide.md
+            Would update 2 links in readme.md
+        "});
+        cmd.temp_dir
+            .child("main-vault/guide.md")
+            .assert(predicates::str::contains("## Setup ##"));
+    }
+
+    #[test]
+    fn fails_for_a_missing_heading() {
+        let cmd = Obx::from_command("rename-heading guide.md Missing Installing");
+        write_notes(&cmd);
+
+        cmd.assert_stderr("No heading `Missing` in guide.md\n");
+    }
+}
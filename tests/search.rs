@@ -0,0 +1,257 @@
+use assert_fs::prelude::*;
+use indoc::formatdoc;
+use predicates::prelude::*;
+mod utils;
+use utils::*;
+
+mod search {
+    use super::*;
+
+    #[test]
+    fn requires_every_word() {
+        Obx::from_command("search rich simple").assert_stdout("complex-note.md\n");
+    }
+
+    #[test]
+    fn ranks_name_matches_first() {
+        Obx::from_command("search simple").assert_stdout("simple-note.md\ncomplex-note.md\n");
+    }
+
+    #[test]
+    fn matches_word_prefixes() {
+        Obx::from_command("search subdir").assert_stdout("folder/child-note.md\n");
+    }
+
+    #[test]
+    fn limits_results() {
+        Obx::from_command("search simple --limit 1").assert_stdout("simple-note.md\n");
+        Obx::from_command("search simple --limit 1 --offset 1").assert_stdout("complex-note.md\n");
+    }
+
+    #[test]
+    fn prints_nothing_without_matches() {
+        Obx::from_command("search nonexistentword").assert_stdout("");
+    }
+
+    #[test]
+    fn does_not_save_an_index() {
+        let cmd = Obx::from_command("search simple").assert_success();
+
+        cmd.temp_dir
+            .child("state")
+            .assert(predicate::path::missing());
+    }
+
+    #[test]
+    fn fuzzy_matches_note_paths() {
+        Obx::from_command("search --fuzzy chld").assert_stdout("folder/child-note.md\n");
+    }
+
+    #[test]
+    fn fuzzy_ranks_name_matches_first() {
+        Obx::from_command("search --fuzzy fo")
+            .assert_stdout("with-fm-properties.md\nfolder/child-note.md\n");
+    }
+
+    #[test]
+    fn fuzzy_uses_configured_scoring() {
+        let cmd = Obx::from_command("search --fuzzy fo");
+        let dir = cmd.temp_dir.display().to_string();
+
+        cmd.with_config_file(&formatdoc! {"
+            current_vault: main
+            vaults:
+            - name: main
+              path: {dir}/main-vault
+            fuzzy:
+              title_boost: 0
+            "})
+            .assert_stdout("folder/child-note.md\nwith-fm-properties.md\n");
+    }
+
+    #[test]
+    fn matches_decomposed_accents() {
+        let cmd = Obx::from_command("search crème");
+        cmd.temp_dir
+            .child("main-vault/cafe\u{301}.md")
+            .write_str("Cre\u{300}me brûle\u{301}e")
+            .unwrap();
+
+        cmd.assert_stdout("cafe\u{301}.md\n");
+    }
+
+    #[test]
+    fn ignores_diacritics_when_configured() {
+        let cmd = Obx::from_command("search creme");
+        let dir = cmd.temp_dir.display().to_string();
+        cmd.temp_dir
+            .child("main-vault/dessert.md")
+            .write_str("Crème brûlée")
+            .unwrap();
+
+        cmd.with_config_file(&formatdoc! {"
+            current_vault: main
+            vaults:
+            - name: main
+              path: {dir}/main-vault
+            matching:
+              ignore_diacritics: true
+            "})
+            .assert_stdout("dessert.md\n");
+    }
+
+    fn search_rust_notes(command: &str) -> Obx {
+        let cmd = Obx::from_command(command);
+        cmd.temp_dir
+            .child("main-vault/speed.md")
+            .write_str("Rust is fast")
+            .unwrap();
+        cmd.temp_dir
+            .child("main-vault/oxide.md")
+            .write_str("rust on iron")
+            .unwrap();
+        cmd
+    }
+
+    #[test]
+    fn matches_case_when_the_query_has_capitals() {
+        search_rust_notes("search Rust").assert_stdout("speed.md\n");
+        search_rust_notes("search rust --sort path").assert_stdout("oxide.md\nspeed.md\n");
+        search_rust_notes("search \"Is fast\"").assert_stdout("");
+    }
+
+    #[test]
+    fn overrides_smart_case_with_flags() {
+        search_rust_notes("search rust --case-sensitive").assert_stdout("oxide.md\n");
+        search_rust_notes("search RUST -i --sort path").assert_stdout("oxide.md\nspeed.md\n");
+    }
+
+    #[test]
+    fn uses_configured_case() {
+        let cmd = search_rust_notes("search Rust --sort path");
+        let dir = cmd.temp_dir.display().to_string();
+
+        cmd.with_config_file(&formatdoc! {"
+            current_vault: main
+            vaults:
+            - name: main
+              path: {dir}/main-vault
+            matching:
+              case: insensitive
+            "})
+            .assert_stdout("oxide.md\nspeed.md\n");
+    }
+
+    #[test]
+    fn filters_with_query_terms() {
+        Obx::from_command("search note path:folder/").assert_stdout("folder/child-note.md\n");
+    }
+
+    #[test]
+    fn combines_terms_with_operators() {
+        Obx::from_command("search -s path (path:folder/ OR table) AND NOT simple")
+            .assert_stdout("folder/child-note.md\ntable.md\n");
+    }
+
+    #[test]
+    fn explains_query_syntax_errors() {
+        Obx::from_command("search (note OR")
+            .assert_stderr("Nothing after `OR` in query `(note OR`\n");
+    }
+
+    #[test]
+    fn excludes_negated_terms() {
+        Obx::from_command("search simple -- -rich").assert_stdout("simple-note.md\n");
+    }
+
+    #[test]
+    fn matches_phrases() {
+        let mut cmd = Obx::from_command("search");
+        cmd.cmd.arg("\"contents of simple\"");
+
+        cmd.assert_stdout("simple-note.md\n");
+    }
+
+    #[test]
+    fn reports_unclosed_quotes() {
+        let mut cmd = Obx::from_command("search");
+        cmd.cmd.arg("\"rich");
+
+        cmd.assert_stderr("Unclosed quote in query `\"rich`\n");
+    }
+
+    #[test]
+    fn ranks_linked_notes_higher() {
+        let cmd = Obx::from_command("search budget");
+        for (name, contents) in [
+            ("a-plan.md", "budget"),
+            ("b-plan.md", "budget"),
+            ("c.md", "see [[b-plan]]"),
+        ] {
+            cmd.temp_dir
+                .child("main-vault")
+                .child(name)
+                .write_str(contents)
+                .unwrap();
+        }
+
+        cmd.assert_stdout("b-plan.md\na-plan.md\n");
+    }
+
+    #[test]
+    fn filters_by_created_time() {
+        let cmd = Obx::from_command("search budget --created-after 2022-01-01");
+        for (name, contents) in [
+            ("old-budget.md", "---\ncreated: 2021-06-01\n---\nbudget"),
+            ("new-budget.md", "---\ndate: 2024-06-01\n---\nbudget"),
+        ] {
+            cmd.temp_dir
+                .child("main-vault")
+                .child(name)
+                .write_str(contents)
+                .unwrap();
+        }
+
+        cmd.assert_stdout("new-budget.md\n");
+    }
+
+    #[test]
+    fn filters_by_created_time_in_the_query() {
+        let cmd = Obx::from_command("search budget created:<2022-01-01");
+        cmd.temp_dir
+            .child("main-vault/old-budget.md")
+            .write_str("---\ncreated: 2021-06-01\n---\nbudget")
+            .unwrap();
+
+        cmd.assert_stdout("old-budget.md\n");
+    }
+
+    #[test]
+    fn sorts_by_path() {
+        Obx::from_command("search simple --sort path")
+            .assert_stdout("complex-note.md\nsimple-note.md\n");
+    }
+
+    #[test]
+    fn prints_scores_as_json() {
+        Obx::from_command("search rich simple -f json")
+            .assert_stdout_contains("[{\"path\":\"complex-note.md\",\"score\":");
+    }
+
+    #[test]
+    fn prints_snippets_as_jsonl() {
+        let mut cmd = Obx::from_command("search -f jsonl contents simple -s path");
+        let output = cmd.cmd.output().unwrap();
+        let records: Vec<serde_json::Value> = String::from_utf8(output.stdout)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1]["path"], "simple-note.md");
+        assert_eq!(records[1]["line"], 1);
+        assert_eq!(records[1]["snippet"], "# Simple note");
+        assert_eq!(records[1]["matches"][0]["start"], 2);
+    }
+}
@@ -0,0 +1,247 @@
+use assert_fs::prelude::*;
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpStream,
+    process::{Child, Stdio},
+};
+mod utils;
+use utils::*;
+
+/// `obx serve --api` on a free port, with the address it's listening on
+fn serve() -> (Obx, Child, String) {
+    serve_with(Obx::from_command("serve --api --port 0"))
+}
+
+fn serve_with(mut cmd: Obx) -> (Obx, Child, String) {
+    let mut server = cmd
+        .cmd
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut started = String::new();
+    BufReader::new(server.stderr.take().unwrap())
+        .read_line(&mut started)
+        .unwrap();
+    let address = started
+        .split("http://")
+        .nth(1)
+        .and_then(|rest| rest.split(',').next())
+        .unwrap()
+        .to_string();
+    (cmd, server, address)
+}
+
+/// The status code and body of a response
+fn request(address: &str, method: &str, path: &str, body: &str) -> (u16, serde_json::Value) {
+    request_with(address, method, path, body, "")
+}
+
+/// `request` with extra header lines, each ending in `\r\n`, which can
+/// replace the `Host` header
+fn request_with(
+    address: &str,
+    method: &str,
+    path: &str,
+    body: &str,
+    headers: &str,
+) -> (u16, serde_json::Value) {
+    let host = if headers.starts_with("Host:") {
+        String::new()
+    } else {
+        format!("Host: {address}\r\n")
+    };
+    let mut stream = TcpStream::connect(address).unwrap();
+    write!(
+        stream,
+        "{method} {path} HTTP/1.1\r\n{host}Connection: close\r\n{headers}Content-Length: {}\r\n\r\n{body}",
+        body.len()
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    let status = response[9..12].parse().unwrap();
+    let (_, body) = response.split_once("\r\n\r\n").unwrap();
+    (status, serde_json::from_str(body).unwrap())
+}
+
+mod serve {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn serves_notes_and_queries() {
+        let (cmd, mut server, address) = serve();
+
+        let (status, notes) = request(&address, "GET", "/notes", "");
+        assert_eq!(status, 200);
+        assert!(notes
+            .as_array()
+            .unwrap()
+            .contains(&json!("folder/child-note.md")));
+
+        assert_eq!(
+            request(&address, "GET", "/notes/simple-note", ""),
+            (
+                200,
+                json!({
+                    "path": "simple-note.md",
+                    "properties": null,
+                    "content": "# Simple note\n\nThis is the contents of simple-note.md",
+                })
+            )
+        );
+
+        assert_eq!(
+            request(&address, "PUT", "/notes/Inbox/new%20idea.md", "Fresh"),
+            (201, json!({ "path": "Inbox/new idea.md" }))
+        );
+        cmd.temp_dir
+            .child("main-vault/Inbox/new idea.md")
+            .assert("Fresh");
+
+        let (status, results) = request(&address, "GET", "/search?q=fresh", "");
+        assert_eq!(status, 200);
+        assert_eq!(results[0]["path"], "Inbox/new idea.md");
+        assert_eq!(results[0]["snippet"], "Fresh");
+
+        assert_eq!(
+            request(
+                &address,
+                "GET",
+                "/query?q=test-number%20%3E%2050&select=test-str",
+                ""
+            ),
+            (
+                200,
+                json!([{ "path": "with-fm-properties.md", "test-str": "a string val" }])
+            )
+        );
+
+        assert_eq!(
+            request(&address, "GET", "/notes/missing", ""),
+            (404, json!({ "error": "No note missing" }))
+        );
+
+        server.kill().unwrap();
+        server.wait().unwrap();
+    }
+
+    #[test]
+    fn serves_tagged_frontmatter() {
+        let cmd = Obx::from_command("serve --api --port 0");
+        cmd.temp_dir
+            .child("main-vault/tagged.md")
+            .write_str("---\nx: !custom y\n---\n")
+            .unwrap();
+        let (_cmd, mut server, address) = serve_with(cmd);
+
+        let (status, note) = request(&address, "GET", "/notes/tagged", "");
+        assert_eq!((status, &note["properties"]), (200, &json!({ "x": "y" })));
+        assert_eq!(
+            request(
+                &address,
+                "GET",
+                "/query?q=x%20%3D%3D%20%22y%22&select=x",
+                ""
+            ),
+            (200, json!([{ "path": "tagged.md", "x": "y" }]))
+        );
+
+        server.kill().unwrap();
+        server.wait().unwrap();
+    }
+
+    #[test]
+    fn only_writes_markdown_outside_hidden_folders() {
+        let (cmd, mut server, address) = serve();
+
+        assert_eq!(
+            request(&address, "PUT", "/notes/.obsidian/app.json", "{}").0,
+            400
+        );
+        assert_eq!(
+            request(&address, "PUT", "/notes/plugin/main.js", "code"),
+            (201, json!({ "path": "plugin/main.js.md" }))
+        );
+        cmd.temp_dir
+            .child("main-vault/.obsidian/app.json")
+            .assert(predicates::path::missing());
+
+        server.kill().unwrap();
+        server.wait().unwrap();
+    }
+
+    #[test]
+    fn requires_the_token_when_given_one() {
+        let (_cmd, mut server, address) =
+            serve_with(Obx::from_command("serve --api --port 0 --token secret"));
+
+        assert_eq!(
+            request(&address, "GET", "/notes", ""),
+            (401, json!({ "error": "Missing or wrong token" }))
+        );
+        let (status, _) = request_with(
+            &address,
+            "GET",
+            "/notes",
+            "",
+            "Authorization: Bearer secret\r\n",
+        );
+        assert_eq!(status, 200);
+
+        server.kill().unwrap();
+        server.wait().unwrap();
+    }
+
+    #[test]
+    fn refuses_requests_from_other_sites() {
+        let (_cmd, mut server, address) = serve();
+        let port = address.rsplit(':').next().unwrap();
+
+        let (status, _) = request_with(
+            &address,
+            "GET",
+            "/notes",
+            "",
+            &format!("Host: localhost:{port}\r\n"),
+        );
+        assert_eq!(status, 200);
+        assert_eq!(
+            request_with(
+                &address,
+                "GET",
+                "/notes",
+                "",
+                &format!("Host: rebound.example:{port}\r\n")
+            ),
+            (
+                403,
+                json!({ "error": "Only requests from this machine are answered" })
+            )
+        );
+        assert_eq!(
+            request_with(
+                &address,
+                "GET",
+                "/notes",
+                "",
+                "Origin: https://rebound.example\r\n"
+            )
+            .0,
+            403
+        );
+
+        server.kill().unwrap();
+        server.wait().unwrap();
+    }
+
+    #[test]
+    fn needs_a_token_beyond_this_machine() {
+        Obx::from_command("serve --api --port 0 --host 0.0.0.0").assert_stderr(
+            "Serving beyond this machine needs a token, set one with `--token` or OBX_API_TOKEN\n",
+        );
+    }
+}
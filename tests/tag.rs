@@ -0,0 +1,82 @@
+use assert_fs::prelude::*;
+use indoc::indoc;
+mod utils;
+use utils::*;
+
+fn with_tagged_notes(command: &str) -> Obx {
+    let cmd = Obx::from_command(command);
+    cmd.temp_dir
+        .child("main-vault/project.md")
+        .write_str("---\ntags: [project, work]\n---\nSome #project/alpha idea")
+        .unwrap();
+    cmd.temp_dir
+        .child("main-vault/folder/meeting.md")
+        .write_str("Notes for #work and #projects")
+        .unwrap();
+
+    cmd
+}
+
+mod tag {
+    use super::*;
+
+    #[test]
+    fn renames_tags_and_nested_tags() {
+        with_tagged_notes("tag rename project plan")
+            .assert_stdout("Renamed 2 tags in project.md\n")
+            .assert_content(
+                "main-vault/project.md",
+                "---\ntags: [plan, work]\n---\nSome #plan/alpha idea".to_string(),
+            )
+            .assert_content(
+                "main-vault/folder/meeting.md",
+                "Notes for #work and #projects".to_string(),
+            );
+    }
+
+    #[test]
+    fn renames_tags_written_differently() {
+        let cmd = Obx::from_command("tag rename café bistro");
+        cmd.temp_dir
+            .child("main-vault/menu.md")
+            .write_str("---\ntags: [cafe\u{301}/food]\n---\n#CAFÉ/drinks")
+            .unwrap();
+
+        cmd.assert_stdout("Renamed 2 tags in menu.md\n")
+            .assert_content(
+                "main-vault/menu.md",
+                "---\ntags: [bistro/food]\n---\n#bistro/drinks".to_string(),
+            );
+    }
+
+    #[test]
+    fn merges_tags() {
+        with_tagged_notes("tag merge #project #work --into #focus")
+            .assert_stdout(indoc! {"
+                Merged 1 tag in folder/meeting.md
+                Merged 3 tags in project.md
+            "})
+            .assert_content(
+                "main-vault/project.md",
+                "---\ntags: [focus]\n---\nSome #focus/alpha idea".to_string(),
+            );
+    }
+
+    #[test]
+    fn dry_run_leaves_notes_alone() {
+        with_tagged_notes("tag rename work job --dry-run")
+            .assert_stdout(indoc! {"
+                Would rename 1 tag in folder/meeting.md
+                Would rename 1 tag in project.md
+            "})
+            .assert_content(
+                "main-vault/folder/meeting.md",
+                "Notes for #work and #projects".to_string(),
+            );
+    }
+
+    #[test]
+    fn errors_on_invalid_tags() {
+        with_tagged_notes("tag rename work 123").assert_stderr("`123` isn't a valid tag\n");
+    }
+}
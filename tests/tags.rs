@@ -0,0 +1,136 @@
+use assert_fs::prelude::*;
+use indoc::{formatdoc, indoc};
+mod utils;
+use utils::*;
+
+fn with_tagged_notes(command: &str) -> Obx {
+    let cmd = Obx::from_command(command);
+    cmd.temp_dir
+        .child("main-vault/project.md")
+        .write_str("---\ntags: [project, work]\n---\nSome #idea")
+        .unwrap();
+    cmd.temp_dir
+        .child("main-vault/folder/meeting.md")
+        .write_str("Notes for #work and #project")
+        .unwrap();
+    cmd.temp_dir
+        .child("main-vault/other.md")
+        .write_str("Another #work note")
+        .unwrap();
+
+    cmd
+}
+
+mod tags {
+    use super::*;
+
+    #[test]
+    fn colors_tags_with_the_theme() {
+        let cmd = with_tagged_notes("tags --color always --sort name");
+        let dir = cmd.temp_dir.display().to_string();
+
+        cmd.with_config_file(&formatdoc! {"
+            current_vault: main
+            vaults:
+            - name: main
+              path: {dir}/main-vault
+            theme: terminal
+            "})
+            .assert_stdout_contains("│ \u{1b}[34m#idea\u{1b}[39m    │ 1     │");
+    }
+
+    #[test]
+    fn counts_tags_by_usage() {
+        let _ = &with_tagged_notes("tags").assert_stdout(indoc! {"
+            ┌──────────┬───────┐
+            │ Tag      │ Notes │
+            ├──────────┼───────┤
+            │ #work    │ 3     │
+            │ #project │ 2     │
+            │ #idea    │ 1     │
+            └──────────┴───────┘
+        "});
+    }
+
+    #[test]
+    fn pages_through_tags() {
+        let _ = &with_tagged_notes("tags --limit 2 --page 2").assert_stdout(indoc! {"
+            ┌───────┬───────┐
+            │ Tag   │ Notes │
+            ├───────┼───────┤
+            │ #idea │ 1     │
+            └───────┴───────┘
+        "});
+    }
+
+    #[test]
+    fn sorts_by_name() {
+        let _ = &with_tagged_notes("tags --sort name").assert_stdout(indoc! {"
+            ┌──────────┬───────┐
+            │ Tag      │ Notes │
+            ├──────────┼───────┤
+            │ #idea    │ 1     │
+            │ #project │ 2     │
+            │ #work    │ 3     │
+            └──────────┴───────┘
+        "});
+    }
+
+    #[test]
+    fn lists_notes_with_tag() {
+        let _ = &with_tagged_notes("tags --notes #project").assert_stdout(indoc! {"
+            folder/meeting.md
+            project.md
+        "});
+    }
+
+    #[test]
+    fn nests_tags_in_a_tree() {
+        let cmd = with_tagged_notes("tags --tree --sort name");
+        cmd.temp_dir
+            .child("main-vault/alpha.md")
+            .write_str("#project/alpha and #project/alpha/docs")
+            .unwrap();
+        cmd.temp_dir
+            .child("main-vault/beta.md")
+            .write_str("#project/beta")
+            .unwrap();
+
+        cmd.assert_stdout(indoc! {"
+            ┌──────────┬───────┐
+            │ Tag      │ Notes │
+            ├──────────┼───────┤
+            │ #idea    │ 1     │
+            │ #project │ 4     │
+            │   alpha  │ 1     │
+            │     docs │ 1     │
+            │   beta   │ 1     │
+            │ #work    │ 3     │
+            └──────────┴───────┘
+        "});
+    }
+
+    #[test]
+    fn lists_notes_with_nested_tags() {
+        let cmd = with_tagged_notes("tags --notes #Project");
+        cmd.temp_dir
+            .child("main-vault/alpha.md")
+            .write_str("#project/alpha")
+            .unwrap();
+        cmd.temp_dir
+            .child("main-vault/projects.md")
+            .write_str("#projects")
+            .unwrap();
+
+        cmd.assert_stdout(indoc! {"
+            alpha.md
+            folder/meeting.md
+            project.md
+        "});
+    }
+
+    #[test]
+    fn prints_nothing_without_tags() {
+        Obx::from_command("tags").assert_stdout("");
+    }
+}
@@ -0,0 +1,52 @@
+use assert_fs::prelude::*;
+use indoc::formatdoc;
+mod utils;
+use utils::*;
+
+mod template {
+    use super::*;
+
+    mod list {
+        use super::*;
+
+        #[test]
+        fn lists_templates() {
+            let cmd = Obx::from_command("template list");
+            cmd.temp_dir
+                .child("main-vault/templates/daily.md")
+                .write_str("# {{date}}")
+                .unwrap();
+            cmd.temp_dir
+                .child("main-vault/templates/work/meeting.md")
+                .write_str("# {{title}}")
+                .unwrap();
+
+            let _ = &cmd.assert_stdout("daily\nwork/meeting\n");
+        }
+
+        #[test]
+        fn uses_configured_folder() {
+            let cmd = Obx::from_command("template list");
+            let dir = cmd.temp_dir.display().to_string();
+            cmd.temp_dir
+                .child("main-vault/meta/tpl/weekly.md")
+                .write_str("# Week")
+                .unwrap();
+
+            let _ = &cmd
+                .with_config_file(&formatdoc! {"
+                    current_vault: main
+                    vaults:
+                    - name: main
+                      path: {dir}/main-vault/
+                    template_folder: meta/tpl
+                "})
+                .assert_stdout("weekly\n");
+        }
+
+        #[test]
+        fn prints_nothing_without_templates() {
+            Obx::from_command("template list").assert_stdout("");
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use assert_fs::prelude::*;
+use indoc::{formatdoc, indoc};
+mod utils;
+use utils::*;
+
+fn with_structured_notes(cmd: Obx) -> Obx {
+    for (path, contents) in [
+        ("Areas.md", ""),
+        ("Projects.md", "---\nup: \"[[Areas]]\"\n---\n"),
+        (
+            "Projects/Website.md",
+            "---\nup: [[Projects]]\nrelated:\n  - \"[[Blog]]\"\n---\n",
+        ),
+        ("Projects/Launch.md", "---\nParent: \"[[Website]]\"\n---\n"),
+        ("Projects/Garden.md", "---\nup: \"[[Projects]]\"\n---\n"),
+        ("Blog.md", "Not [[Website]]'s child, just linked"),
+    ] {
+        cmd.temp_dir
+            .child(format!("main-vault/{path}"))
+            .write_str(contents)
+            .unwrap();
+    }
+
+    cmd
+}
+
+mod tree {
+    use super::*;
+
+    #[test]
+    fn shows_parents_children_and_related_notes() {
+        with_structured_notes(Obx::from_command("tree Website")).assert_stdout(indoc! {"
+            Areas.md
+              Projects.md
+                Projects/Website.md
+                  Projects/Launch.md
+
+            Related: Blog.md
+        "});
+    }
+
+    #[test]
+    fn limits_the_depth() {
+        with_structured_notes(Obx::from_command("tree Areas --depth 1")).assert_stdout(indoc! {"
+            Areas.md
+              Projects.md
+        "});
+    }
+
+    #[test]
+    fn shows_related_notes_both_ways() {
+        with_structured_notes(Obx::from_command("tree Blog")).assert_stdout(indoc! {"
+            Blog.md
+
+            Related: Projects/Website.md
+        "});
+    }
+
+    #[test]
+    fn reads_the_configured_keys() {
+        let cmd = Obx::from_command("tree Launch");
+        let config = formatdoc! {"
+            current_vault: main
+            vaults:
+              - name: main
+                path: {}/main-vault
+            relations:
+              parent_keys: [up]
+        ", cmd.temp_dir.path().display()};
+        with_structured_notes(cmd.with_config_file(&config)).assert_stdout("Projects/Launch.md\n");
+    }
+}
@@ -0,0 +1,49 @@
+use assert_fs::prelude::*;
+mod utils;
+use utils::*;
+
+mod uri {
+    use super::*;
+
+    #[test]
+    fn prints_the_open_uri() {
+        Obx::from_command("uri simple-note")
+            .assert_stdout("obsidian://open?vault=main-vault&file=simple-note.md\n");
+    }
+
+    #[test]
+    fn encodes_nested_paths() {
+        Obx::from_command("uri child-note")
+            .assert_stdout("obsidian://open?vault=main-vault&file=folder%2Fchild-note.md\n");
+    }
+
+    #[test]
+    fn points_at_headings_as_written() {
+        Obx::from_command("uri complex-note --heading rich-note --mode append --data Later")
+            .assert_stdout("obsidian://advanced-uri?vault=main-vault&filepath=complex-note.md&heading=Rich%20note&mode=append&data=Later\n");
+    }
+
+    #[test]
+    fn points_at_blocks() {
+        let cmd = Obx::from_command("uri blocks --block ^abc123");
+        cmd.temp_dir
+            .child("main-vault/blocks.md")
+            .write_str("A paragraph ^abc123\n")
+            .unwrap();
+        cmd.assert_stdout(
+            "obsidian://advanced-uri?vault=main-vault&filepath=blocks.md&block=abc123\n",
+        );
+    }
+
+    #[test]
+    fn fails_on_missing_headings() {
+        Obx::from_command("uri complex-note --heading Elsewhere")
+            .assert_stderr("No heading `Elsewhere` in complex-note\n");
+    }
+
+    #[test]
+    #[ignore = "this opens Obsidian.app on every test run"]
+    fn opens_in_obsidian() {
+        Obx::from_command("uri simple-note --open").assert_success();
+    }
+}
@@ -1,3 +1,5 @@
+#![allow(dead_code)]
+
 use assert_cmd::prelude::*;
 use assert_fs::fixture::ChildPath;
 use assert_fs::prelude::*;
@@ -42,6 +44,10 @@ impl Obx {
         );
 
         cmd.env("OBX_CONFIG_DIR", config_path.display().to_string());
+        cmd.env(
+            "OBX_STATE_DIR",
+            temp_dir.child("./state/obx/").display().to_string(),
+        );
 
         Self { cmd, temp_dir }
             .with_editor("")
@@ -174,15 +180,24 @@ where
 {
     // Create a mock editor script, we can verify it's been called
     // because it will append to the file
-    let mock_editor = temp_dir.child("mock_editor.sh");
-    let editor_content: String = format!("#!/bin/sh\n{}", content.into());
+    create_script("mock_editor.sh", content, temp_dir)
+}
+
+/// Write an executable shell script into the temp dir, for standing in for
+/// external programs
+pub fn create_script<S>(name: &str, content: S, temp_dir: &TempDir) -> ChildPath
+where
+    S: Into<String>,
+{
+    let script = temp_dir.child(name);
+    let script_content: String = format!("#!/bin/sh\n{}", content.into());
 
-    mock_editor.write_str(&editor_content).unwrap();
+    script.write_str(&script_content).unwrap();
 
     #[cfg(unix)]
     {
         use std::os::unix::fs::PermissionsExt;
-        fs::set_permissions(mock_editor.path(), fs::Permissions::from_mode(0o755)).unwrap();
+        fs::set_permissions(script.path(), fs::Permissions::from_mode(0o755)).unwrap();
     }
 
     #[cfg(not(unix))]
@@ -190,5 +205,5 @@ where
         todo!();
     }
 
-    mock_editor
+    script
 }
@@ -66,6 +66,146 @@ mod vaults {
         }
     }
 
+    mod add {
+        use super::*;
+        use assert_fs::prelude::FileTouch;
+
+        #[test]
+        fn registers_without_switching() {
+            let add_cmd = Obx::from_command("vault add work path/to/work");
+            let _ = add_cmd.temp_dir.child("path/to/work/file.md").touch();
+
+            let mut curr_cmd = Obx::from_command("vaults current");
+            let tmp_config_path = add_cmd.temp_dir.child("./config/obx/");
+            curr_cmd.env("OBX_CONFIG_DIR", tmp_config_path.display().to_string());
+
+            let _x = add_cmd.assert_stdout("Added vault work\n");
+
+            curr_cmd.assert_stdout_contains("`main`");
+        }
+
+        #[test]
+        fn fails_on_existing_name() {
+            Obx::from_command("vaults add secondary main-vault")
+                .assert_stderr("A vault named `secondary` already exists\n");
+        }
+
+        #[test]
+        fn fails_on_missing_dir() {
+            Obx::from_command("vaults add work /does/not/exist").assert_stderr(
+                "Could not add vault at path `/does/not/exist`, directory not found\n",
+            );
+        }
+    }
+
+    mod remove {
+        use super::*;
+
+        #[test]
+        fn forgets_vault() {
+            let remove_cmd = Obx::from_command("vaults remove secondary");
+
+            let mut list_cmd = Obx::from_command("vaults list -f json");
+            let tmp_config_path = remove_cmd.temp_dir.child("./config/obx/");
+            list_cmd.env("OBX_CONFIG_DIR", tmp_config_path.display().to_string());
+
+            let _x = remove_cmd.assert_stdout("Removed vault secondary\n");
+
+            let output = list_cmd.cmd.output().unwrap();
+            let vaults: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+            assert_eq!(vaults.as_array().unwrap().len(), 1);
+            assert_eq!(vaults[0]["name"], json!("main"));
+        }
+
+        #[test]
+        fn switches_away_from_removed_current_vault() {
+            Obx::from_command("vaults rm main")
+                .assert_stdout("Removed vault main, switched to vault secondary\n");
+        }
+
+        #[test]
+        fn fails_on_missing_vault() {
+            Obx::from_command("vaults remove does-not-exist")
+                .assert_stderr("Could not remove vault `does-not-exist`, vault doesn't exist\n");
+        }
+    }
+
+    mod discover {
+        use super::*;
+        use assert_fs::prelude::{FileTouch, FileWriteStr};
+
+        fn with_obsidian_settings(mut cmd: Obx) -> Obx {
+            let dir = cmd.temp_dir.to_path_buf();
+            let settings = cmd.temp_dir.child("obsidian/obsidian.json");
+            settings
+                .write_str(&format!(
+                    r#"{{"vaults":{{
+                        "a1":{{"path":"{dir}/main-vault","ts":1700000000003,"open":true}},
+                        "b2":{{"path":"{dir}/vaults/work","ts":1700000000002}},
+                        "c3":{{"path":"{dir}/other/secondary","ts":1700000000001}},
+                        "d4":{{"path":"{dir}/vaults/deleted","ts":1700000000000}}
+                    }}}}"#,
+                    dir = dir.display()
+                ))
+                .unwrap();
+            cmd.temp_dir.child("vaults/work/note.md").touch().unwrap();
+            cmd.temp_dir
+                .child("other/secondary/note.md")
+                .touch()
+                .unwrap();
+
+            cmd.env("OBX_OBSIDIAN_SETTINGS", settings.path());
+            cmd
+        }
+
+        #[test]
+        fn adds_new_vaults() {
+            let cmd = with_obsidian_settings(Obx::from_command("vaults discover"));
+            let dir = cmd.temp_dir.to_path_buf();
+
+            cmd.assert_stdout(format!(
+                "Added vault work ({dir}/vaults/work)\nAdded vault secondary-2 ({dir}/other/secondary)\n",
+                dir = dir.display()
+            ));
+        }
+
+        #[test]
+        fn persists_changes() {
+            let discover_cmd = with_obsidian_settings(Obx::from_command("vaults discover"));
+
+            let mut list_cmd = Obx::from_command("vaults list -f json");
+            let tmp_config_path = discover_cmd.temp_dir.child("./config/obx/");
+            list_cmd.env("OBX_CONFIG_DIR", tmp_config_path.display().to_string());
+
+            let _x = discover_cmd.assert_success();
+
+            let output = list_cmd.cmd.output().unwrap();
+            let vaults: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+            let names: Vec<&str> = vaults
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|v| v["name"].as_str().unwrap())
+                .collect();
+            assert_eq!(names, ["main", "secondary", "work", "secondary-2"]);
+        }
+
+        #[test]
+        fn reports_nothing_new() {
+            let mut cmd = Obx::from_command("vaults discover");
+            let settings = cmd.temp_dir.child("obsidian.json");
+            settings
+                .write_str(&format!(
+                    r#"{{"vaults":{{"a1":{{"path":"{}/main-vault","ts":1}}}}}}"#,
+                    cmd.temp_dir.display()
+                ))
+                .unwrap();
+            cmd.env("OBX_OBSIDIAN_SETTINGS", settings.path());
+
+            cmd.assert_stdout("No new vaults found\n");
+        }
+    }
+
     mod list {
         use super::*;
 
@@ -106,6 +246,11 @@ mod vaults {
 
             cmd.assert_stdout(format!("{stdout_match}\n"));
         }
+
+        #[test]
+        fn prints_vault_names() {
+            Obx::from_command("vaults list -f names").assert_stdout("main\nsecondary\n");
+        }
     }
 
     mod switch {
@@ -160,4 +305,14 @@ mod vaults {
             ));
         }
     }
+
+    mod vault_override {
+        use super::*;
+
+        #[test]
+        fn fails_on_unknown_vault_name() {
+            Obx::from_command("cat simple-note --vault does-not-exist")
+                .assert_stderr("Vault `does-not-exist` not found, see `obx vaults list`\n");
+        }
+    }
 }
@@ -0,0 +1,49 @@
+use assert_fs::prelude::*;
+use indoc::indoc;
+mod utils;
+use utils::*;
+
+fn with_chapter(command: &str) -> Obx {
+    let cmd = Obx::from_command(command);
+    cmd.temp_dir
+        .child("main-vault/chapter.md")
+        .write_str(indoc! {"
+            ---
+            status: draft
+            ---
+            # Chapter one
+
+            ![[scene]]
+
+            ```
+            not counted
+            ```
+        "})
+        .unwrap();
+    cmd.temp_dir
+        .child("main-vault/scene.md")
+        .write_str("---\npov: Ann\n---\nIt was a dark and stormy night.")
+        .unwrap();
+
+    cmd
+}
+
+mod wordcount {
+    use super::*;
+
+    #[test]
+    fn counts_body_words() {
+        with_chapter("wordcount chapter").assert_stdout("4 words\n");
+    }
+
+    #[test]
+    fn counts_embedded_notes() {
+        with_chapter("wordcount chapter --with-embeds").assert_stdout("10 words\n");
+    }
+
+    #[test]
+    fn includes_frontmatter_and_code_when_asked() {
+        with_chapter("wordcount chapter --include-frontmatter --include-code")
+            .assert_stdout("10 words\n");
+    }
+}